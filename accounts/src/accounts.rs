@@ -1,21 +1,27 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::iter;
 
 use account::inherent::{AccountInherentInteraction, Inherent};
+use account::staking_contract::StakingContractKeys;
 use account::{
     Account, AccountError, AccountTransactionInteraction, AccountType, PrunedAccount, Receipt,
-    Receipts,
+    Receipts, StakingContract, StakingContractEntry,
 };
+use beserial::Serialize;
 use database as db;
 use database::{Environment, ReadTransaction, WriteTransaction};
 use hash::Blake2bHash;
 use keys::Address;
+use primitives::account::ValidatorId;
 use transaction::{Transaction, TransactionFlags};
 use tree_primitives::accounts_proof::AccountsProof;
 use tree_primitives::accounts_tree_chunk::AccountsTreeChunk;
+use tree_primitives::address_nibbles::AddressNibbles;
 
 use crate::tree::AccountsTree;
 
 type ReceiptsMap<'a> = HashMap<u16, &'a Vec<u8>>;
+type StakingEntries = BTreeMap<AddressNibbles, Account>;
 
 #[derive(Debug)]
 pub struct Accounts {
@@ -31,17 +37,292 @@ impl Accounts {
 
     pub fn init(&self, txn: &mut WriteTransaction, genesis_accounts: Vec<(Address, Account)>) {
         for (address, account) in genesis_accounts {
-            self.tree.put_batch(txn, &address, account);
+            // The tree only contains the genesis accounts, so it can't be inconsistent.
+            self.put_batch(txn, &address, account)
+                .expect("Failed to store the genesis accounts");
         }
         self.tree.finalize_batch(txn);
     }
 
-    pub fn get(&self, address: &Address, txn_option: Option<&db::Transaction>) -> Account {
+    /// Returns the account at the given address. The staking contract is reassembled from all of
+    /// its entries, which fails if they are inconsistent.
+    pub fn get(
+        &self,
+        address: &Address,
+        txn_option: Option<&db::Transaction>,
+    ) -> Result<Account, AccountError> {
+        match txn_option {
+            Some(txn) => self.get_with_txn(txn, address),
+            None => self.get_with_txn(&ReadTransaction::new(&self.env), address),
+        }
+    }
+
+    fn get_with_txn(
+        &self,
+        txn: &db::Transaction,
+        address: &Address,
+    ) -> Result<Account, AccountError> {
+        if let Some(account) = self.tree.get(txn, address) {
+            return Ok(account);
+        }
+        Ok(self
+            .get_staking_contract(txn, address)?
+            .map(Account::Staking)
+            .unwrap_or(Account::INITIAL))
+    }
+
+    /// Returns the entry of the staking contract at the given address, which doesn't hold any
+    /// validators or stakes. Returns `None` if there is no staking contract at that address.
+    fn get_staking_contract_entry(
+        &self,
+        txn: &db::Transaction,
+        address: &Address,
+    ) -> Option<StakingContract> {
+        let key = AddressNibbles::from_key(address, &StakingContract::contract_key());
+        match self.tree.get_key(txn, &key)? {
+            Account::Staking(contract) => Some(contract),
+            _ => None,
+        }
+    }
+
+    /// Reassembles the staking contract at the given address from all of its entries. Returns
+    /// `None` if there is no staking contract at that address.
+    fn get_staking_contract(
+        &self,
+        txn: &db::Transaction,
+        address: &Address,
+    ) -> Result<Option<StakingContract>, AccountError> {
+        let contract = match self.get_staking_contract_entry(txn, address) {
+            Some(contract) => contract,
+            None => return Ok(None),
+        };
+
+        let entries = self
+            .tree
+            .get_with_prefix(txn, &AddressNibbles::from(address))?
+            .into_iter()
+            .filter_map(|(_, account)| match account {
+                Account::StakingEntry(entry) => Some(entry),
+                _ => None,
+            });
+        Ok(Some(StakingContract::from_entries(contract, entries)?))
+    }
+
+    /// Assembles the staking contract at the given address from the entry of the contract itself
+    /// and only the entries in `keys`. This is much cheaper than getting the whole contract if
+    /// only a few validators or stakes are needed. Returns `None` if there is no staking contract
+    /// at that address.
+    pub fn get_partial_staking_contract(
+        &self,
+        address: &Address,
+        keys: &StakingContractKeys,
+        txn_option: Option<&db::Transaction>,
+    ) -> Result<Option<StakingContract>, AccountError> {
+        let get = |txn: &db::Transaction| match self.get_staking_contract_entry(txn, address) {
+            Some(contract) => self
+                .get_staking_entries(txn, address, contract, keys)
+                .map(|(contract, _)| Some(contract)),
+            None => Ok(None),
+        };
+
         match txn_option {
-            Some(txn) => self.tree.get(txn, address),
-            None => self.tree.get(&ReadTransaction::new(&self.env), address),
+            Some(txn) => get(txn),
+            None => get(&ReadTransaction::new(&self.env)),
+        }
+    }
+
+    /// Returns the entries of the staking contract at the given address whose keys start with
+    /// `prefix`, e.g. all validators without their stakes. The key of an entry is relative to the
+    /// contract address.
+    pub fn get_staking_entries_with_prefix(
+        &self,
+        address: &Address,
+        prefix: &[u8],
+        txn_option: Option<&db::Transaction>,
+    ) -> Result<Vec<StakingContractEntry>, AccountError> {
+        let prefix = AddressNibbles::from_key(address, prefix);
+        let accounts = match txn_option {
+            Some(txn) => self.tree.get_with_prefix(txn, &prefix)?,
+            None => self
+                .tree
+                .get_with_prefix(&ReadTransaction::new(&self.env), &prefix)?,
+        };
+        Ok(accounts
+            .into_iter()
+            .filter_map(|(_, account)| match account {
+                Account::StakingEntry(entry) => Some(entry),
+                _ => None,
+            })
+            .collect())
+    }
+
+    /// Assembles the staking contract at the given address from the entry of the contract itself
+    /// and only the entries in `keys`. Returns the loaded entries as well, so that
+    /// `put_staking_contract` doesn't touch any other entries.
+    fn get_staking_entries(
+        &self,
+        txn: &db::Transaction,
+        address: &Address,
+        contract: StakingContract,
+        keys: &StakingContractKeys,
+    ) -> Result<(StakingContract, StakingEntries), AccountError> {
+        let mut loaded = StakingEntries::new();
+        let mut entry_keys: Vec<Vec<u8>> = keys
+            .validators
+            .keys()
+            .map(StakingContract::validator_key)
+            .chain(
+                keys.active_stakes
+                    .iter()
+                    .map(|(validator_id, staker_address)| {
+                        StakingContract::active_stake_key(validator_id, staker_address)
+                    }),
+            )
+            .chain(
+                keys.inactive_stakes
+                    .iter()
+                    .map(StakingContract::inactive_stake_key),
+            )
+            .collect();
+
+        // Load all stakes of these validators and the inactive stakes of their stakers.
+        for (validator_id, _) in keys.validators.iter().filter(|(_, &all)| all) {
+            let prefix = AddressNibbles::from_key(
+                address,
+                &StakingContract::active_stakes_prefix(validator_id),
+            );
+            for (key, account) in self.tree.get_with_prefix(txn, &prefix)? {
+                if let Account::StakingEntry(StakingContractEntry::ActiveStake {
+                    ref staker_address,
+                    ..
+                }) = account
+                {
+                    entry_keys.push(StakingContract::inactive_stake_key(staker_address));
+                }
+                loaded.insert(key, account);
+            }
+        }
+
+        for key in entry_keys {
+            let key = AddressNibbles::from_key(address, &key);
+            if let Some(account) = self.tree.get_key(txn, &key) {
+                loaded.insert(key, account);
+            }
+        }
+
+        let entries = loaded.values().filter_map(|account| match account {
+            Account::StakingEntry(entry) => Some(entry.clone()),
+            _ => None,
+        });
+        let assembled = StakingContract::from_entries(contract.clone(), entries)?;
+
+        loaded.insert(
+            AddressNibbles::from_key(address, &StakingContract::contract_key()),
+            Account::Staking(contract),
+        );
+        Ok((assembled, loaded))
+    }
+
+    /// Puts an account into the tree. The staking contract is stored as separate entries for the
+    /// contract itself and each of its validators and stakes.
+    fn put_batch(
+        &self,
+        txn: &mut WriteTransaction,
+        address: &Address,
+        account: Account,
+    ) -> Result<(), AccountError> {
+        let contract = match account {
+            Account::Staking(contract) => contract,
+            account => {
+                self.tree.put_batch(txn, address, account);
+                return Ok(());
+            }
+        };
+
+        let stale_entries = self
+            .tree
+            .get_with_prefix(txn, &AddressNibbles::from(address))?
+            .into_iter()
+            .collect();
+        self.put_staking_contract(txn, address, contract, stale_entries);
+        Ok(())
+    }
+
+    /// Stores the entries of the staking contract at the given address. `stale_entries` are the
+    /// entries that the contract was assembled from: only the entries that changed are written and
+    /// those that don't exist anymore are removed. Entries that weren't loaded are left untouched.
+    fn put_staking_contract(
+        &self,
+        txn: &mut WriteTransaction,
+        address: &Address,
+        contract: StakingContract,
+        mut stale_entries: StakingEntries,
+    ) {
+        let (contract, entries) = contract.to_entries();
+        let entries = iter::once((StakingContract::contract_key(), Account::Staking(contract)))
+            .chain(
+                entries
+                    .into_iter()
+                    .map(|entry| (entry.key(), Account::StakingEntry(entry))),
+            );
+        for (key, account) in entries {
+            let key = AddressNibbles::from_key(address, &key);
+            // Staking contracts always compare as equal, so compare the serialized entries.
+            let unchanged = stale_entries.remove(&key).map_or(false, |old| {
+                old.serialize_to_vec() == account.serialize_to_vec()
+            });
+            if !unchanged {
+                self.tree.put_key_batch(txn, key, account);
+            }
+        }
+
+        // Remove the entries of validators and stakes that don't exist anymore.
+        for (key, _) in stale_entries {
+            self.tree.put_key_batch(txn, key, Account::INITIAL);
+        }
+    }
+
+    /// Returns the account at the given address to process a transaction or inherent on it. The
+    /// staking contract only holds the entries returned by `keys`, see `get_staking_entries`.
+    fn get_for_processing<K>(
+        &self,
+        txn: &db::Transaction,
+        address: &Address,
+        keys: K,
+    ) -> Result<(Account, Option<StakingEntries>), AccountError>
+    where
+        K: FnOnce(&StakingContract) -> StakingContractKeys,
+    {
+        if let Some(account) = self.tree.get(txn, address) {
+            return Ok((account, None));
+        }
+
+        match self.get_staking_contract_entry(txn, address) {
+            Some(contract) => {
+                let keys = keys(&contract);
+                let (contract, entries) =
+                    self.get_staking_entries(txn, address, contract, &keys)?;
+                Ok((Account::Staking(contract), Some(entries)))
+            }
+            None => Ok((Account::INITIAL, None)),
+        }
+    }
+
+    /// Puts an account that was returned by `get_for_processing` back into the tree.
+    fn put_for_processing(
+        &self,
+        txn: &mut WriteTransaction,
+        address: &Address,
+        account: Account,
+        staking_entries: Option<StakingEntries>,
+    ) -> Result<(), AccountError> {
+        match (account, staking_entries) {
+            (Account::Staking(contract), Some(entries)) => {
+                self.put_staking_contract(txn, address, contract, entries);
+                Ok(())
+            }
+            (account, _) => self.put_batch(txn, address, account),
         }
-        .unwrap_or(Account::INITIAL)
     }
 
     pub fn get_chunk(
@@ -58,6 +339,18 @@ impl Accounts {
         }
     }
 
+    /// Removes all accounts, so that the tree can be rebuilt from chunks with `commit_chunk`.
+    pub fn clear(&self, txn: &mut WriteTransaction) {
+        self.tree.clear(txn);
+    }
+
+    /// Adds the accounts of a chunk that was created by `get_chunk` on another tree. Once all
+    /// chunks of that tree were committed in order, both trees have the same hash.
+    pub fn commit_chunk(&self, txn: &mut WriteTransaction, chunk: &AccountsTreeChunk<Account>) {
+        self.tree.put_chunk_batch(txn, chunk);
+        self.tree.finalize_batch(txn);
+    }
+
     pub fn get_accounts_proof(
         &self,
         txn: &db::Transaction,
@@ -66,6 +359,32 @@ impl Accounts {
         self.tree.get_accounts_proof(txn, addresses)
     }
 
+    /// Returns a proof for the stake of a staker in the staking contract at `contract_address`,
+    /// i.e. for its active stake delegated to the given validator and its inactive stake. The
+    /// entries can be retrieved from the proof with the keys from `StakingContract::active_stake_key`
+    /// and `StakingContract::inactive_stake_key`.
+    pub fn get_staker_proof(
+        &self,
+        txn: &db::Transaction,
+        contract_address: &Address,
+        validator_id: &ValidatorId,
+        staker_address: &Address,
+    ) -> AccountsProof<Account> {
+        self.tree.get_proof(
+            txn,
+            vec![
+                AddressNibbles::from_key(
+                    contract_address,
+                    &StakingContract::active_stake_key(validator_id, staker_address),
+                ),
+                AddressNibbles::from_key(
+                    contract_address,
+                    &StakingContract::inactive_stake_key(staker_address),
+                ),
+            ],
+        )
+    }
+
     pub fn hash(&self, txn_option: Option<&db::Transaction>) -> Blake2bHash {
         match txn_option {
             Some(txn) => self.tree.root_hash(txn),
@@ -372,7 +691,10 @@ impl Accounts {
         ) -> Result<Option<Vec<u8>>, AccountError>,
     {
         // TODO Eliminate copy
-        let mut account = self.get(address, Some(txn));
+        let incoming = address == &transaction.recipient;
+        let (mut account, staking_entries) = self.get_for_processing(txn, address, |_| {
+            StakingContract::transaction_keys(transaction, incoming, receipt)
+        })?;
 
         // Check account type.
         if let Some(account_type) = account_type {
@@ -388,7 +710,7 @@ impl Accounts {
         let receipt = account_op(&mut account, transaction, block_height, receipt)?;
 
         // TODO Eliminate copy
-        self.tree.put_batch(txn, address, account);
+        self.put_for_processing(txn, address, account, staking_entries)?;
 
         Ok(receipt)
     }
@@ -422,7 +744,7 @@ impl Accounts {
             .flags
             .contains(TransactionFlags::CONTRACT_CREATION));
 
-        let recipient_account = self.get(&transaction.recipient, Some(txn));
+        let recipient_account = self.get(&transaction.recipient, Some(txn))?;
         let new_recipient_account = Account::new_contract(
             transaction.recipient_type,
             recipient_account.balance(),
@@ -464,7 +786,7 @@ impl Accounts {
             .flags
             .contains(TransactionFlags::CONTRACT_CREATION));
 
-        let recipient_account = self.get(&transaction.recipient, Some(txn));
+        let recipient_account = self.get(&transaction.recipient, Some(txn))?;
         if recipient_account.account_type() != transaction.recipient_type {
             return Err(AccountError::TypeMismatch {
                 expected: recipient_account.account_type(),
@@ -485,7 +807,19 @@ impl Accounts {
     ) -> Result<Vec<Receipt>, AccountError> {
         let mut receipts = Vec::new();
         for transaction in transactions {
-            let sender_account = self.get(&transaction.sender, Some(txn));
+            // The staking contract is never pruned, so only the entry of the contract itself is
+            // loaded here.
+            let sender_account = match self.tree.get(txn, &transaction.sender) {
+                Some(account) => account,
+                None => self
+                    .get_partial_staking_contract(
+                        &transaction.sender,
+                        &StakingContractKeys::default(),
+                        Some(txn),
+                    )?
+                    .map(Account::Staking)
+                    .unwrap_or(Account::INITIAL),
+            };
             if sender_account.is_to_be_pruned() {
                 // Produce receipt.
                 receipts.push(Receipt::PrunedAccount(PrunedAccount {
@@ -507,8 +841,7 @@ impl Accounts {
         pruned_accounts: Vec<&PrunedAccount>,
     ) -> Result<(), AccountError> {
         for pruned_account in pruned_accounts {
-            self.tree
-                .put_batch(txn, &pruned_account.address, pruned_account.account.clone());
+            self.put_batch(txn, &pruned_account.address, pruned_account.account.clone())?;
         }
         Ok(())
     }
@@ -550,13 +883,16 @@ impl Accounts {
         F: Fn(&mut Account, &Inherent, Option<&Vec<u8>>) -> Result<Option<Vec<u8>>, AccountError>,
     {
         // TODO Eliminate copy
-        let mut account = self.get(&inherent.target, Some(txn));
+        let (mut account, staking_entries) =
+            self.get_for_processing(txn, &inherent.target, |contract| {
+                contract.inherent_keys(inherent)
+            })?;
 
         // Apply inherent.
         let receipt = account_op(&mut account, inherent, receipt)?;
 
         // TODO Eliminate copy
-        self.tree.put_batch(txn, &inherent.target, account);
+        self.put_for_processing(txn, &inherent.target, account, staking_entries)?;
 
         Ok(receipt)
     }
@@ -1,7 +1,8 @@
 use std::marker::PhantomData;
 use std::str::FromStr;
 
-use account::AccountsTreeLeave;
+use account::{AccountError, AccountsTreeLeave};
+use database::cursor::{ReadCursor, WriteCursor};
 use database::{Database, Environment, Transaction, WriteTransaction};
 use hash::{Blake2bHash, Hash};
 use keys::Address;
@@ -45,13 +46,17 @@ impl<A: AccountsTreeLeave> AccountsTree<A> {
     }
 
     pub fn put_batch(&self, txn: &mut WriteTransaction, address: &Address, account: A) {
-        if account.is_initial() && self.get(txn, address).is_none() {
+        self.put_key_batch(txn, AddressNibbles::from(address), account);
+    }
+
+    /// Puts an account at an arbitrary key. Keys must not be prefixes of each other.
+    pub fn put_key_batch(&self, txn: &mut WriteTransaction, key: AddressNibbles, account: A) {
+        if account.is_initial() && self.get_key(txn, &key).is_none() {
             return;
         }
 
-        // Insert account into the tree at address.
-        let prefix = AddressNibbles::from(address);
-        self.insert_batch(txn, AddressNibbles::empty(), prefix, account, Vec::new());
+        // Insert account into the tree at key.
+        self.insert_batch(txn, AddressNibbles::empty(), key, account, Vec::new());
     }
 
     fn insert_batch(
@@ -172,6 +177,36 @@ impl<A: AccountsTreeLeave> AccountsTree<A> {
         }
     }
 
+    /// Removes all nodes from the tree, leaving an empty root.
+    pub fn clear(&self, txn: &mut WriteTransaction) {
+        {
+            let mut cursor = txn.write_cursor(&self.db);
+            let mut pos: Option<(AddressNibbles, AccountsTreeNode<A>)> = cursor.first();
+
+            while pos.is_some() {
+                cursor.remove();
+                pos = cursor.next();
+            }
+        }
+
+        let root = AddressNibbles::empty();
+        txn.put_reserve(
+            &self.db,
+            &root,
+            &AccountsTreeNode::<A>::new_branch(root.clone(), NO_CHILDREN),
+        );
+    }
+
+    /// Puts the terminal nodes of a chunk into the tree. The hashes are not updated until
+    /// `finalize_batch` is called.
+    pub fn put_chunk_batch(&self, txn: &mut WriteTransaction, chunk: &AccountsTreeChunk<A>) {
+        for node in chunk.terminal_nodes() {
+            if let AccountsTreeNode::TerminalNode { prefix, account } = node {
+                self.put_key_batch(txn, prefix.clone(), account.clone());
+            }
+        }
+    }
+
     pub fn finalize_batch(&self, txn: &mut WriteTransaction) {
         self.update_hashes(txn, &AddressNibbles::empty());
     }
@@ -194,11 +229,16 @@ impl<A: AccountsTreeLeave> AccountsTree<A> {
     }
 
     pub fn get_accounts_proof(&self, txn: &Transaction, addresses: &[Address]) -> AccountsProof<A> {
-        let mut prefixes = Vec::new();
-        for address in addresses {
-            prefixes.push(AddressNibbles::from(address));
-        }
-        // We sort the addresses to simplify traversal in post order (leftmost addresses first).
+        self.get_proof(txn, addresses.iter().map(AddressNibbles::from).collect())
+    }
+
+    /// Returns a proof for the accounts at the given keys.
+    pub fn get_proof(
+        &self,
+        txn: &Transaction,
+        mut prefixes: Vec<AddressNibbles>,
+    ) -> AccountsProof<A> {
+        // We sort the keys to simplify traversal in post order (leftmost keys first).
         prefixes.sort();
 
         let mut nodes = Vec::new();
@@ -267,14 +307,67 @@ impl<A: AccountsTreeLeave> AccountsTree<A> {
     }
 
     pub fn get(&self, txn: &Transaction, address: &Address) -> Option<A> {
-        if let AccountsTreeNode::TerminalNode { account, .. } =
-            txn.get(&self.db, &AddressNibbles::from(address))?
-        {
+        self.get_key(txn, &AddressNibbles::from(address))
+    }
+
+    pub fn get_key(&self, txn: &Transaction, key: &AddressNibbles) -> Option<A> {
+        if let AccountsTreeNode::TerminalNode { account, .. } = txn.get(&self.db, key)? {
             return Some(account);
         }
         None
     }
 
+    /// Returns all accounts whose key starts with the given prefix, ordered by key. Fails if a
+    /// node of the subtree is missing from the database.
+    pub fn get_with_prefix(
+        &self,
+        txn: &Transaction,
+        prefix: &AddressNibbles,
+    ) -> Result<Vec<(AddressNibbles, A)>, AccountError> {
+        let mut accounts = Vec::new();
+
+        // Descend to the topmost node whose prefix starts with the given prefix.
+        let mut node = match self.get_root(txn) {
+            Some(root) => root,
+            None => return Ok(accounts),
+        };
+        while !prefix.is_prefix_of(node.prefix()) {
+            if !node.prefix().is_prefix_of(prefix) {
+                return Ok(accounts);
+            }
+            match node.get_child_prefix(prefix) {
+                Some(child_prefix) => node = self.get_node(txn, &child_prefix)?,
+                None => return Ok(accounts),
+            }
+        }
+
+        // Collect the terminal nodes of its subtree.
+        let mut stack = vec![node];
+        while let Some(node) = stack.pop() {
+            match node {
+                AccountsTreeNode::BranchNode { children, prefix } => {
+                    for child in children.iter().flatten().rev() {
+                        stack.push(self.get_node(txn, &(&prefix + &child.suffix))?);
+                    }
+                }
+                AccountsTreeNode::TerminalNode { prefix, account } => {
+                    accounts.push((prefix, account));
+                }
+            }
+        }
+        Ok(accounts)
+    }
+
+    /// Returns the node with the given prefix, which a parent node references.
+    fn get_node(
+        &self,
+        txn: &Transaction,
+        prefix: &AddressNibbles,
+    ) -> Result<AccountsTreeNode<A>, AccountError> {
+        txn.get(&self.db, prefix)
+            .ok_or_else(|| AccountError::MissingTreeNode(prefix.to_string()))
+    }
+
     pub(crate) fn get_chunk(
         &self,
         txn: &Transaction,
@@ -285,7 +378,7 @@ impl<A: AccountsTreeLeave> AccountsTree<A> {
             self.get_terminal_nodes(txn, &AddressNibbles::from_str(start).ok()?, size)?;
         let last_node = chunk.pop();
         let proof = if let Some(node) = last_node {
-            self.get_accounts_proof(txn, &[node.prefix().to_address()?])
+            self.get_proof(txn, vec![node.prefix().clone()])
         } else {
             self.get_accounts_proof(
                 txn,
@@ -377,4 +470,48 @@ mod tests {
         assert_eq!(chunk.len(), 3);
         assert_eq!(chunk.verify(), true);
     }
+
+    #[test]
+    fn it_can_rebuild_tree_from_chunks() {
+        let env = database::volatile::VolatileEnvironment::new(10).unwrap();
+        let tree = AccountsTree::new(env.clone());
+        let mut txn = WriteTransaction::new(&env);
+        for (i, address) in ["0000", "1000", "1200", "1234", "f000"].iter().enumerate() {
+            let address = Address::from(&hex::decode(format!("{:0<40}", address)).unwrap()[..]);
+            let account = Account::Basic(account::BasicAccount {
+                balance: Coin::try_from(i as u64 + 1).unwrap(),
+            });
+            tree.put(&mut txn, &address, account);
+        }
+
+        // Start with a different account in the tree that is rebuilt.
+        let other_env = database::volatile::VolatileEnvironment::new(10).unwrap();
+        let other_tree = AccountsTree::new(other_env.clone());
+        let mut other_txn = WriteTransaction::new(&other_env);
+        other_tree.put(
+            &mut other_txn,
+            &Address::from([7u8; 20]),
+            Account::Basic(account::BasicAccount {
+                balance: Coin::try_from(7).unwrap(),
+            }),
+        );
+        other_tree.clear(&mut other_txn);
+        assert_eq!(other_tree.get(&other_txn, &Address::from([7u8; 20])), None);
+
+        let mut start = String::new();
+        loop {
+            let mut chunk = tree.get_chunk(&txn, &start, 2).unwrap();
+            assert!(chunk.verify());
+            assert_eq!(chunk.root(), tree.root_hash(&txn));
+
+            other_tree.put_chunk_batch(&mut other_txn, &chunk);
+            if chunk.len() < 2 {
+                break;
+            }
+            start = chunk.last_terminal_string().unwrap();
+        }
+        other_tree.finalize_batch(&mut other_txn);
+
+        assert_eq!(other_tree.root_hash(&other_txn), tree.root_hash(&txn));
+    }
 }
@@ -1,9 +1,9 @@
 use std::convert::TryFrom;
 
-use beserial::Serialize;
+use beserial::{Deserialize, Serialize};
 use nimiq_account::{
     Account, AccountTransactionInteraction, AccountType, BasicAccount, Inherent, InherentType,
-    PrunedAccount,
+    PrunedAccount, StakingContract, StakingContractEntry,
 };
 use nimiq_account::{Receipt, Receipts};
 use nimiq_accounts::Accounts;
@@ -11,9 +11,16 @@ use nimiq_database::volatile::VolatileEnvironment;
 use nimiq_database::ReadTransaction;
 use nimiq_database::WriteTransaction;
 use nimiq_keys::{Address, KeyPair, SecureGenerate};
+use nimiq_primitives::account::ValidatorId;
 use nimiq_primitives::coin::Coin;
 use nimiq_primitives::networks::NetworkId;
+use nimiq_transaction::account::staking_contract::{
+    IncomingStakingTransactionData, SelfStakingTransactionData,
+};
 use nimiq_transaction::{SignatureProof, Transaction};
+use nimiq_tree_primitives::address_nibbles::AddressNibbles;
+
+const VALIDATOR_KEY: &str = "003d4e4eb0fa2fee42501368dc41115f64741e9d9496bbc2fe4cfd407f10272eef87b839d6e25b0eb7338427d895e4209190b6c5aa580f134693623a30ebafdaf95a268b3b84a840fc45d06283d71fe4faa2c7d08cd431bbda165c53a50453015a49ca120626991ff9558be65a7958158387829d6e56e2861e80b85e8c795d93f907afb19e6e2e5aaed9a3158eac5a035189986ff5803dd18fa02bdf5535e5495ed96990665ec165b3ba86fc1a7f7dabeb0510e1823813bf5ab1a01b4fff00bcd0373bc265efa135f8755ebae72b645a890d27ce8af31417347bc3a1d9cf09db339b68d1c9a50bb9c00faeedbefe9bab5a63b580e5f79c4a30dc1bdacccec0fc6a08e0853518e88557001a612d4c30d2fbc2a126a066a94f299ac5ce61";
 
 #[test]
 fn it_can_commit_and_revert_a_block_body() {
@@ -34,7 +41,10 @@ fn it_can_commit_and_revert_a_block_body() {
 
     let receipts = Receipts::default();
 
-    assert_eq!(accounts.get(&address_validator, None).balance(), Coin::ZERO);
+    assert_eq!(
+        accounts.get(&address_validator, None).unwrap().balance(),
+        Coin::ZERO
+    );
 
     let mut txn = WriteTransaction::new(&env);
 
@@ -45,7 +55,7 @@ fn it_can_commit_and_revert_a_block_body() {
     txn.commit();
 
     assert_eq!(
-        accounts.get(&address_validator, None).balance(),
+        accounts.get(&address_validator, None).unwrap().balance(),
         Coin::from_u64_unchecked(10000)
     );
 
@@ -62,7 +72,10 @@ fn it_can_commit_and_revert_a_block_body() {
 
     let transactions = vec![tx];
 
-    assert_eq!(accounts.get(&address_recipient, None).balance(), Coin::ZERO);
+    assert_eq!(
+        accounts.get(&address_recipient, None).unwrap().balance(),
+        Coin::ZERO
+    );
 
     let mut txn = WriteTransaction::new(&env);
 
@@ -73,12 +86,12 @@ fn it_can_commit_and_revert_a_block_body() {
     txn.commit();
 
     assert_eq!(
-        accounts.get(&address_recipient, None).balance(),
+        accounts.get(&address_recipient, None).unwrap().balance(),
         Coin::from_u64_unchecked(10)
     );
 
     assert_eq!(
-        accounts.get(&address_validator, None).balance(),
+        accounts.get(&address_validator, None).unwrap().balance(),
         Coin::from_u64_unchecked(10000 + 10000 - 10)
     );
 
@@ -92,10 +105,13 @@ fn it_can_commit_and_revert_a_block_body() {
 
     txn.commit();
 
-    assert_eq!(accounts.get(&address_recipient, None).balance(), Coin::ZERO);
+    assert_eq!(
+        accounts.get(&address_recipient, None).unwrap().balance(),
+        Coin::ZERO
+    );
 
     assert_eq!(
-        accounts.get(&address_validator, None).balance(),
+        accounts.get(&address_validator, None).unwrap().balance(),
         Coin::from_u64_unchecked(10000)
     );
 
@@ -118,7 +134,7 @@ fn it_correctly_rewards_validators() {
 
     // Validator 1 mines first block.
     assert_eq!(
-        accounts.get(&address_validator_1, None).balance(),
+        accounts.get(&address_validator_1, None).unwrap().balance(),
         Coin::ZERO
     );
 
@@ -137,7 +153,7 @@ fn it_correctly_rewards_validators() {
 
     // Create transactions to Recipient 1 and Recipient 2.
     assert_eq!(
-        accounts.get(&address_validator_1, None).balance(),
+        accounts.get(&address_validator_1, None).unwrap().balance(),
         Coin::from_u64_unchecked(10000)
     );
 
@@ -169,7 +185,7 @@ fn it_correctly_rewards_validators() {
 
     // Validator 2 mines second block.
     assert_eq!(
-        accounts.get(&address_validator_2, None).balance(),
+        accounts.get(&address_validator_2, None).unwrap().balance(),
         Coin::ZERO
     );
 
@@ -189,18 +205,24 @@ fn it_correctly_rewards_validators() {
     txn.commit();
 
     assert_eq!(
-        accounts.get(&address_validator_1, None).balance(),
+        accounts.get(&address_validator_1, None).unwrap().balance(),
         Coin::from_u64_unchecked(10000) - value1 - fee1 - value2 - fee2
     );
 
     assert_eq!(
-        accounts.get(&address_validator_2, None).balance(),
+        accounts.get(&address_validator_2, None).unwrap().balance(),
         Coin::from_u64_unchecked(10000) + fee1 + fee2
     );
 
-    assert_eq!(accounts.get(&address_recipient_1, None).balance(), value1);
+    assert_eq!(
+        accounts.get(&address_recipient_1, None).unwrap().balance(),
+        value1
+    );
 
-    assert_eq!(accounts.get(&address_recipient_2, None).balance(), value2);
+    assert_eq!(
+        accounts.get(&address_recipient_2, None).unwrap().balance(),
+        value2
+    );
 }
 
 #[test]
@@ -231,9 +253,15 @@ fn it_checks_for_sufficient_funds() {
 
     let hash1 = accounts.hash(None);
 
-    assert_eq!(accounts.get(&address_sender, None).balance(), Coin::ZERO);
+    assert_eq!(
+        accounts.get(&address_sender, None).unwrap().balance(),
+        Coin::ZERO
+    );
 
-    assert_eq!(accounts.get(&address_recipient, None).balance(), Coin::ZERO);
+    assert_eq!(
+        accounts.get(&address_recipient, None).unwrap().balance(),
+        Coin::ZERO
+    );
 
     // Fails as address_sender does not have any funds.
     // Note: When the commit errors, we want to bracket the txn creation and the commit attempt.
@@ -246,9 +274,15 @@ fn it_checks_for_sufficient_funds() {
             .is_err());
     }
 
-    assert_eq!(accounts.get(&address_sender, None).balance(), Coin::ZERO);
+    assert_eq!(
+        accounts.get(&address_sender, None).unwrap().balance(),
+        Coin::ZERO
+    );
 
-    assert_eq!(accounts.get(&address_recipient, None).balance(), Coin::ZERO);
+    assert_eq!(
+        accounts.get(&address_recipient, None).unwrap().balance(),
+        Coin::ZERO
+    );
 
     assert_eq!(hash1, accounts.hash(None));
 
@@ -263,11 +297,14 @@ fn it_checks_for_sufficient_funds() {
     txn.commit();
 
     assert_eq!(
-        accounts.get(&address_sender, None).balance(),
+        accounts.get(&address_sender, None).unwrap().balance(),
         Coin::from_u64_unchecked(10000)
     );
 
-    assert_eq!(accounts.get(&address_recipient, None).balance(), Coin::ZERO);
+    assert_eq!(
+        accounts.get(&address_recipient, None).unwrap().balance(),
+        Coin::ZERO
+    );
 
     let hash2 = accounts.hash(None);
 
@@ -285,11 +322,14 @@ fn it_checks_for_sufficient_funds() {
     }
 
     assert_eq!(
-        accounts.get(&address_sender, None).balance(),
+        accounts.get(&address_sender, None).unwrap().balance(),
         Coin::from_u64_unchecked(10000)
     );
 
-    assert_eq!(accounts.get(&address_recipient, None).balance(), Coin::ZERO);
+    assert_eq!(
+        accounts.get(&address_recipient, None).unwrap().balance(),
+        Coin::ZERO
+    );
 
     assert_eq!(hash2, accounts.hash(None));
 
@@ -309,11 +349,14 @@ fn it_checks_for_sufficient_funds() {
     }
 
     assert_eq!(
-        accounts.get(&address_sender, None).balance(),
+        accounts.get(&address_sender, None).unwrap().balance(),
         Coin::from_u64_unchecked(10000)
     );
 
-    assert_eq!(accounts.get(&address_recipient, None).balance(), Coin::ZERO);
+    assert_eq!(
+        accounts.get(&address_recipient, None).unwrap().balance(),
+        Coin::ZERO
+    );
 
     assert_eq!(hash2, accounts.hash(None));
 }
@@ -398,7 +441,7 @@ fn it_correctly_prunes_account() {
     )
     .serialize_to_vec();
 
-    let mut pruned_account = accounts.get(&contract_address, None);
+    let mut pruned_account = accounts.get(&contract_address, None).unwrap();
 
     pruned_account
         .commit_outgoing_transaction(&tx_prune, 2, 2)
@@ -421,7 +464,7 @@ fn it_correctly_prunes_account() {
     txn.commit();
 
     // Check that the account was pruned correctly
-    let account_after_prune = accounts.get(&contract_address, None);
+    let account_after_prune = accounts.get(&contract_address, None).unwrap();
 
     assert_eq!(account_after_prune.account_type(), AccountType::Basic);
 
@@ -437,7 +480,7 @@ fn it_correctly_prunes_account() {
     txn.commit();
 
     // Check that the account was recovered correctly
-    if let Account::Vesting(vesting_contract) = accounts.get(&contract_address, None) {
+    if let Account::Vesting(vesting_contract) = accounts.get(&contract_address, None).unwrap() {
         assert_eq!(vesting_contract.balance, Coin::try_from(100).unwrap());
         assert_eq!(vesting_contract.owner, address);
     }
@@ -459,7 +502,7 @@ fn it_correctly_prunes_account() {
     txn.commit();
 
     // Check that the account is really gone
-    let account_after_prune = accounts.get(&contract_address, None);
+    let account_after_prune = accounts.get(&contract_address, None).unwrap();
 
     assert_eq!(account_after_prune.account_type(), AccountType::Basic);
 
@@ -579,3 +622,280 @@ fn can_generate_accounts_proof() {
         proof2.get_account(&address_recipient_2).unwrap()
     );
 }
+
+#[test]
+fn it_stores_the_staking_contract_as_separate_entries() {
+    let env = VolatileEnvironment::new(10).unwrap();
+    let accounts = Accounts::new(env.clone());
+
+    let contract_address = Address::from([1u8; Address::SIZE]);
+    let validator_id = ValidatorId::from([2u8; ValidatorId::SIZE]);
+    let staker_address_1 = Address::from([3u8; Address::SIZE]);
+    let staker_address_2 = Address::from([4u8; Address::SIZE]);
+
+    let mut contract = StakingContract::default();
+    contract
+        .create_validator(
+            validator_id.clone(),
+            Deserialize::deserialize_from_vec(&hex::decode(VALIDATOR_KEY).unwrap()).unwrap(),
+            staker_address_1.clone(),
+            None,
+            Coin::from_u64_unchecked(100_000_000),
+        )
+        .unwrap();
+    contract
+        .stake(
+            staker_address_1.clone(),
+            Coin::from_u64_unchecked(150),
+            &validator_id,
+        )
+        .unwrap();
+    contract
+        .stake(
+            staker_address_2.clone(),
+            Coin::from_u64_unchecked(250),
+            &validator_id,
+        )
+        .unwrap();
+
+    let mut txn = WriteTransaction::new(&env);
+    accounts.init(
+        &mut txn,
+        vec![(contract_address.clone(), Account::Staking(contract.clone()))],
+    );
+    txn.commit();
+
+    // The contract is reassembled from its entries.
+    match accounts.get(&contract_address, None).unwrap() {
+        Account::Staking(stored) => {
+            assert_eq!(stored.serialize_to_vec(), contract.serialize_to_vec());
+            assert_eq!(
+                stored.get_active_stake(&validator_id, &staker_address_2),
+                Some(Coin::from_u64_unchecked(250))
+            );
+        }
+        account => panic!("Expected the staking contract, got {:?}", account),
+    }
+
+    // The stake of a single staker can be proven.
+    let read_txn = ReadTransaction::new(&env);
+    let mut proof = accounts.get_staker_proof(
+        &read_txn,
+        &contract_address,
+        &validator_id,
+        &staker_address_1,
+    );
+    assert!(proof.verify());
+    assert_eq!(proof.root_hash(), accounts.hash(Some(&read_txn)));
+
+    let active_stake_key = AddressNibbles::from_key(
+        &contract_address,
+        &StakingContract::active_stake_key(&validator_id, &staker_address_1),
+    );
+    assert_eq!(
+        proof.get_account_by_key(&active_stake_key),
+        Some(Account::StakingEntry(StakingContractEntry::ActiveStake {
+            validator_id: validator_id.clone(),
+            staker_address: staker_address_1.clone(),
+            balance: Coin::from_u64_unchecked(150),
+        }))
+    );
+    let inactive_stake_key = AddressNibbles::from_key(
+        &contract_address,
+        &StakingContract::inactive_stake_key(&staker_address_1),
+    );
+    assert_eq!(proof.get_account_by_key(&inactive_stake_key), None);
+    drop(read_txn);
+
+    // Entries of stakes that don't exist anymore are removed.
+    let mut contract = StakingContract::default();
+    contract
+        .create_validator(
+            validator_id.clone(),
+            Deserialize::deserialize_from_vec(&hex::decode(VALIDATOR_KEY).unwrap()).unwrap(),
+            staker_address_1.clone(),
+            None,
+            Coin::from_u64_unchecked(100_000_000),
+        )
+        .unwrap();
+    contract
+        .stake(
+            staker_address_1.clone(),
+            Coin::from_u64_unchecked(150),
+            &validator_id,
+        )
+        .unwrap();
+
+    let mut txn = WriteTransaction::new(&env);
+    accounts.init(
+        &mut txn,
+        vec![(contract_address.clone(), Account::Staking(contract.clone()))],
+    );
+    txn.commit();
+
+    let read_txn = ReadTransaction::new(&env);
+    let mut proof = accounts.get_staker_proof(
+        &read_txn,
+        &contract_address,
+        &validator_id,
+        &staker_address_2,
+    );
+    assert!(proof.verify());
+    let active_stake_key = AddressNibbles::from_key(
+        &contract_address,
+        &StakingContract::active_stake_key(&validator_id, &staker_address_2),
+    );
+    assert_eq!(proof.get_account_by_key(&active_stake_key), None);
+
+    // The tree is the same as if the contract had been stored in this state right away.
+    let other_env = VolatileEnvironment::new(10).unwrap();
+    let other_accounts = Accounts::new(other_env.clone());
+    let mut txn = WriteTransaction::new(&other_env);
+    other_accounts.init(
+        &mut txn,
+        vec![(contract_address.clone(), Account::Staking(contract))],
+    );
+    txn.commit();
+    assert_eq!(accounts.hash(Some(&read_txn)), other_accounts.hash(None));
+}
+
+#[test]
+fn it_only_touches_the_staking_entries_of_a_transaction() {
+    let env = VolatileEnvironment::new(10).unwrap();
+    let accounts = Accounts::new(env.clone());
+
+    let contract_address = Address::from([1u8; Address::SIZE]);
+    let validator_id = ValidatorId::from([2u8; ValidatorId::SIZE]);
+    let other_staker_address = Address::from([3u8; Address::SIZE]);
+    let key_pair = KeyPair::generate_default_csprng();
+    let staker_address = Address::from(&key_pair);
+
+    let mut contract = StakingContract::default();
+    contract
+        .create_validator(
+            validator_id.clone(),
+            Deserialize::deserialize_from_vec(&hex::decode(VALIDATOR_KEY).unwrap()).unwrap(),
+            other_staker_address.clone(),
+            None,
+            Coin::from_u64_unchecked(100_000_000),
+        )
+        .unwrap();
+    contract
+        .stake(
+            other_staker_address.clone(),
+            Coin::from_u64_unchecked(250),
+            &validator_id,
+        )
+        .unwrap();
+
+    let mut txn = WriteTransaction::new(&env);
+    accounts.init(
+        &mut txn,
+        vec![
+            (contract_address.clone(), Account::Staking(contract.clone())),
+            (
+                staker_address.clone(),
+                Account::Basic(BasicAccount {
+                    balance: Coin::from_u64_unchecked(1000),
+                }),
+            ),
+        ],
+    );
+    txn.commit();
+    let initial_hash = accounts.hash(None);
+
+    let mut stake = Transaction::new_extended(
+        staker_address.clone(),
+        AccountType::Basic,
+        contract_address.clone(),
+        AccountType::Staking,
+        Coin::from_u64_unchecked(500),
+        Coin::ZERO,
+        IncomingStakingTransactionData::Stake {
+            validator_id: validator_id.clone(),
+            staker_address: None,
+        }
+        .serialize_to_vec(),
+        1,
+        NetworkId::Dummy,
+    );
+    stake.proof = SignatureProof::from(key_pair.public, key_pair.sign(&stake.serialize_content()))
+        .serialize_to_vec();
+
+    let mut retire = Transaction::new_extended(
+        contract_address.clone(),
+        AccountType::Staking,
+        contract_address.clone(),
+        AccountType::Staking,
+        Coin::from_u64_unchecked(200),
+        Coin::ZERO,
+        SelfStakingTransactionData::RetireStake(validator_id.clone()).serialize_to_vec(),
+        1,
+        NetworkId::Dummy,
+    );
+    retire.proof =
+        SignatureProof::from(key_pair.public, key_pair.sign(&retire.serialize_content()))
+            .serialize_to_vec();
+
+    let mut txn = WriteTransaction::new(&env);
+    let stake_receipts = accounts
+        .commit(&mut txn, &[stake.clone()], &[], 1, 1)
+        .unwrap();
+    let retire_receipts = accounts
+        .commit(&mut txn, &[retire.clone()], &[], 2, 2)
+        .unwrap();
+    txn.commit();
+
+    match accounts.get(&contract_address, None).unwrap() {
+        Account::Staking(stored) => {
+            assert_eq!(
+                stored.get_active_stake(&validator_id, &staker_address),
+                Some(Coin::from_u64_unchecked(300))
+            );
+            assert_eq!(
+                stored.get_active_stake(&validator_id, &other_staker_address),
+                Some(Coin::from_u64_unchecked(250))
+            );
+            assert_eq!(
+                stored.inactive_stake_by_address[&staker_address].balance,
+                Coin::from_u64_unchecked(200)
+            );
+        }
+        account => panic!("Expected the staking contract, got {:?}", account),
+    }
+
+    // The tree is the same as if the transactions had been applied to the whole contract.
+    let mut expected = contract;
+    expected.commit_incoming_transaction(&stake, 1, 1).unwrap();
+    expected.commit_outgoing_transaction(&retire, 2, 2).unwrap();
+    expected.commit_incoming_transaction(&retire, 2, 2).unwrap();
+
+    let other_env = VolatileEnvironment::new(10).unwrap();
+    let other_accounts = Accounts::new(other_env.clone());
+    let mut txn = WriteTransaction::new(&other_env);
+    other_accounts.init(
+        &mut txn,
+        vec![
+            (contract_address, Account::Staking(expected)),
+            (
+                staker_address,
+                Account::Basic(BasicAccount {
+                    balance: Coin::from_u64_unchecked(500),
+                }),
+            ),
+        ],
+    );
+    txn.commit();
+    assert_eq!(accounts.hash(None), other_accounts.hash(None));
+
+    // Reverting restores the original entries.
+    let mut txn = WriteTransaction::new(&env);
+    accounts
+        .revert(&mut txn, &[retire], &[], 2, 2, &retire_receipts)
+        .unwrap();
+    accounts
+        .revert(&mut txn, &[stake], &[], 1, 1, &stake_receipts)
+        .unwrap();
+    txn.commit();
+    assert_eq!(accounts.hash(None), initial_hash);
+}
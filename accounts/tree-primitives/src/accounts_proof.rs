@@ -53,6 +53,11 @@ impl<A: AccountsTreeLeave> AccountsProof<A> {
     }
 
     pub fn get_account(&self, address: &Address) -> Option<A> {
+        self.get_account_by_key(&AddressNibbles::from(address))
+    }
+
+    /// Returns the account at an arbitrary key, e.g. an entry of the staking contract.
+    pub fn get_account_by_key(&self, key: &AddressNibbles) -> Option<A> {
         assert!(
             self.verified,
             "AccountsProof must be verified before retrieving accounts. Call verify() first."
@@ -60,7 +65,7 @@ impl<A: AccountsTreeLeave> AccountsProof<A> {
 
         for node in &self.nodes {
             if let AccountsTreeNode::TerminalNode { prefix, account } = node {
-                if prefix == &AddressNibbles::from(address) {
+                if prefix == key {
                     return Some(account.clone());
                 }
             }
@@ -68,6 +73,37 @@ impl<A: AccountsTreeLeave> AccountsProof<A> {
         None
     }
 
+    /// Returns whether the proof proves the account at the given address, i.e. whether it contains
+    /// the terminal node of the account or, if there is no account, the branch node where the path
+    /// to the address ends. Only then does `get_account` returning `None` mean that there is no
+    /// account at the address.
+    pub fn proves(&self, address: &Address) -> bool {
+        assert!(
+            self.verified,
+            "AccountsProof must be verified before retrieving accounts. Call verify() first."
+        );
+
+        let key = AddressNibbles::from(address);
+        let mut prefix = AddressNibbles::empty();
+        loop {
+            let node = match self.nodes.iter().find(|node| node.prefix() == &prefix) {
+                Some(node) => node,
+                None => return false,
+            };
+
+            if node.is_terminal() {
+                return node.prefix() == &key;
+            }
+
+            match node.get_child_prefix(&key) {
+                Some(child_prefix) if child_prefix.len() <= prefix.len() => return false,
+                Some(child_prefix) if child_prefix.is_prefix_of(&key) => prefix = child_prefix,
+                // The path to the address ends at this node.
+                _ => return true,
+            }
+        }
+    }
+
     pub fn root_hash(&self) -> Blake2bHash {
         (&self.nodes[self.nodes.len() - 1]).hash()
     }
@@ -246,6 +282,27 @@ mod tests {
         assert_eq!(None, proof3.get_account(&address2));
         assert_eq!(None, proof3.get_account(&address3));
 
+        // A proof proves the accounts it contains and the absence of accounts whose path ends at
+        // one of its branch nodes.
+        let address5 = Address::from(
+            hex::decode("0021000000000000000000000000000000000000")
+                .unwrap()
+                .as_slice(),
+        );
+        let address6 = Address::from(
+            hex::decode("1000000000000000000000000000000000000000")
+                .unwrap()
+                .as_slice(),
+        );
+        assert!(proof2.proves(&address1));
+        assert!(proof2.proves(&address3));
+        assert!(!proof2.proves(&address2));
+        assert!(!proof2.proves(&address4));
+        assert!(proof3.proves(&address4));
+        assert!(proof3.proves(&address5));
+        assert!(proof3.proves(&address6));
+        assert!(!proof3.proves(&address1));
+
         // must return the correct root hash
         assert!(proof1.root_hash() == r1.hash());
     }
@@ -126,6 +126,12 @@ impl AddressNibbles {
         self.slice(start as usize, self.len())
     }
 
+    /// Returns the key of an entry that is stored below an address, e.g. an entry of the staking
+    /// contract. `key` is relative to the address.
+    pub fn from_key(address: &Address, key: &[u8]) -> AddressNibbles {
+        AddressNibbles::from(address) + AddressNibbles::from(key)
+    }
+
     pub fn to_address(&self) -> Option<Address> {
         if self.length != 40 {
             return None;
@@ -10,23 +10,39 @@ use nimiq_bls::KeyPair;
 use nimiq_hash::{Blake2bHash, Hash};
 use nimiq_mempool::Mempool;
 use nimiq_primitives::policy;
+use nimiq_signer::{LocalSigner, SignRequest, Signer, SignerError};
 
 /// Struct that contains all necessary information to actually produce blocks. It has the current
-/// blockchain store and state, the current mempool for this validator and the validator key for
-/// this validator.
+/// blockchain store and state, the current mempool for this validator and the signer for the
+/// validator key of this validator.
+#[derive(Clone)]
 pub struct BlockProducer {
     pub blockchain: Arc<Blockchain>,
     pub mempool: Option<Arc<Mempool>>,
-    pub validator_key: KeyPair,
+    pub signer: Arc<dyn Signer>,
 }
 
 impl BlockProducer {
     /// Creates a new BlockProducer struct given a blockchain, a mempool and a validator key.
     pub fn new(blockchain: Arc<Blockchain>, mempool: Arc<Mempool>, validator_key: KeyPair) -> Self {
+        Self::new_with_signer(
+            blockchain,
+            mempool,
+            Arc::new(LocalSigner::new(validator_key, None)),
+        )
+    }
+
+    /// Creates a new BlockProducer struct given a blockchain, a mempool and a signer for the
+    /// validator key.
+    pub fn new_with_signer(
+        blockchain: Arc<Blockchain>,
+        mempool: Arc<Mempool>,
+        signer: Arc<dyn Signer>,
+    ) -> Self {
         BlockProducer {
             blockchain,
             mempool: Some(mempool),
-            validator_key,
+            signer,
         }
     }
 
@@ -35,7 +51,7 @@ impl BlockProducer {
         BlockProducer {
             blockchain,
             mempool: None,
-            validator_key,
+            signer: Arc::new(LocalSigner::new(validator_key, None)),
         }
     }
 
@@ -55,7 +71,27 @@ impl BlockProducer {
         fork_proofs: Vec<ForkProof>,
         // Extra data for this block. It has no a priori use.
         extra_data: Vec<u8>,
-    ) -> MicroBlock {
+    ) -> Result<MicroBlock, SignerError> {
+        let (header, body) =
+            self.next_unsigned_micro_block(timestamp, view_number, fork_proofs, extra_data)?;
+        self.sign_micro_block(header, body, view_change_proof)
+    }
+
+    /// Creates the header and the body of the next micro block, without signing it. This allows
+    /// to check the header before it is signed with `sign_micro_block`.
+    // Note: Needs to be called with the Blockchain lock held.
+    pub fn next_unsigned_micro_block(
+        &self,
+        // The timestamp for the block.
+        timestamp: u64,
+        // The view number for the block.
+        view_number: u32,
+        // Proofs of any forks created by malicious validators. A fork proof may be submitted during
+        // the batch when it happened or in the next one, but not after that.
+        fork_proofs: Vec<ForkProof>,
+        // Extra data for this block. It has no a priori use.
+        extra_data: Vec<u8>,
+    ) -> Result<(MicroHeader, MicroBody), SignerError> {
         // Calculate the block number. It is simply the previous block number incremented by one.
         let block_number = self.blockchain.block_number() + 1;
 
@@ -68,11 +104,9 @@ impl BlockProducer {
 
         // Calculate the seed for this block by signing the previous block seed with the validator
         // key.
-        let seed = self
-            .blockchain
-            .head()
-            .seed()
-            .sign_next(&self.validator_key.secret_key);
+        let prev_seed = self.blockchain.head().seed().clone();
+        let seed = prev_seed
+            .sign_next_with(|_| self.signer.sign(&SignRequest::Seed(prev_seed.clone())))?;
 
         // Calculate the maximum allowed size for the micro block body.
         let max_size = MicroBlock::MAX_SIZE
@@ -142,18 +176,34 @@ impl BlockProducer {
             history_root,
         };
 
+        Ok((header, body))
+    }
+
+    /// Signs the header of a micro block created by `next_unsigned_micro_block` and returns the
+    /// finalized micro block.
+    pub fn sign_micro_block(
+        &self,
+        header: MicroHeader,
+        body: MicroBody,
+        // The view change proof. Only exists if one or more view changes happened for this block
+        // height.
+        view_change_proof: Option<ViewChangeProof>,
+    ) -> Result<MicroBlock, SignerError> {
         // Signs the block header using the validator key.
-        let signature = self.validator_key.sign(&header).compress();
+        let signature = self
+            .signer
+            .sign(&SignRequest::MicroBlock(header.clone()))?
+            .compress();
 
         // Returns the micro block.
-        MicroBlock {
+        Ok(MicroBlock {
             header,
             body: Some(body),
             justification: Some(MicroJustification {
                 signature,
                 view_change_proof,
             }),
-        }
+        })
     }
 
     /// Creates a proposal for the next macro block (checkpoint or election). It is just a proposal,
@@ -168,7 +218,7 @@ impl BlockProducer {
         view_number: u32,
         // Extra data for this block. It has no a priori use.
         extra_data: Vec<u8>,
-    ) -> MacroBlock {
+    ) -> Result<MacroBlock, SignerError> {
         // Calculate the block number. It is simply the previous block number incremented by one.
         let block_number = self.blockchain.block_number() + 1;
 
@@ -184,11 +234,9 @@ impl BlockProducer {
 
         // Calculate the seed for this block by signing the previous block seed with the validator
         // key.
-        let seed = self
-            .blockchain
-            .head()
-            .seed()
-            .sign_next(&self.validator_key.secret_key);
+        let prev_seed = self.blockchain.head().seed().clone();
+        let seed = prev_seed
+            .sign_next_with(|_| self.signer.sign(&SignRequest::Seed(prev_seed.clone())))?;
 
         // Create the header for the macro block without the state root and the transactions root.
         // We need several fields of this header in order to calculate the transactions and the
@@ -214,7 +262,12 @@ impl BlockProducer {
         let mut inherents: Vec<Inherent> = vec![];
 
         // All macro blocks are the end of a batch, so finalize the batch.
-        inherents.append(&mut self.blockchain.finalize_previous_batch(&state, &header));
+        inherents.append(
+            &mut self
+                .blockchain
+                .finalize_previous_batch(&state, &header)
+                .expect("Failed to finalize the previous batch during block production."),
+        );
 
         // If this is an election macro block, then it also is the end of an epoch. So finalize the
         // epoch.
@@ -270,11 +323,11 @@ impl BlockProducer {
         header.body_root = body.hash();
 
         // Returns the block proposal.
-        MacroBlock {
+        Ok(MacroBlock {
             header,
             body: Some(body),
             justification: None,
-        }
+        })
     }
 }
 
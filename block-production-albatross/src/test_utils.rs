@@ -14,6 +14,7 @@ use nimiq_genesis::NetworkId;
 use nimiq_hash::{Blake2bHash, Hash};
 use nimiq_nano_primitives::pk_tree_construct;
 use nimiq_primitives::policy;
+use nimiq_signer::{SignRequest, Signer};
 
 use crate::BlockProducer;
 
@@ -27,7 +28,7 @@ pub struct TemporaryBlockProducer {
 
 impl TemporaryBlockProducer {
     pub fn new() -> Self {
-        let env = VolatileEnvironment::new(10).unwrap();
+        let env = VolatileEnvironment::new(Blockchain::NUM_DATABASES).unwrap();
         let blockchain = Arc::new(Blockchain::new(env, NetworkId::UnitAlbatross).unwrap());
 
         let keypair = KeyPair::from(
@@ -48,11 +49,14 @@ impl TemporaryBlockProducer {
         let height = self.blockchain.block_number() + 1;
 
         let block = if policy::is_macro_block_at(height) {
-            let macro_block_proposal = self.producer.next_macro_block_proposal(
-                self.blockchain.time.now() + height as u64 * 1000,
-                0u32,
-                extra_data,
-            );
+            let macro_block_proposal = self
+                .producer
+                .next_macro_block_proposal(
+                    self.blockchain.time.now() + height as u64 * 1000,
+                    0u32,
+                    extra_data,
+                )
+                .unwrap();
             // Get validator set and make sure it exists.
             let validators = self
                 .blockchain
@@ -79,13 +83,17 @@ impl TemporaryBlockProducer {
                 Some(self.create_view_change_proof(view_number))
             };
 
-            Block::Micro(self.producer.next_micro_block(
-                self.blockchain.time.now() + height as u64 * 1000,
-                view_number,
-                view_change_proof,
-                vec![],
-                extra_data,
-            ))
+            Block::Micro(
+                self.producer
+                    .next_micro_block(
+                        self.blockchain.time.now() + height as u64 * 1000,
+                        view_number,
+                        view_change_proof,
+                        vec![],
+                        extra_data,
+                    )
+                    .unwrap(),
+            )
         };
 
         assert_eq!(self.push(block.clone()), Ok(PushResult::Extended));
@@ -172,13 +180,15 @@ pub fn fill_micro_blocks(producer: &BlockProducer, blockchain: &Arc<Blockchain>)
     let init_height = blockchain.block_number();
     let macro_block_number = policy::macro_block_after(init_height + 1);
     for i in (init_height + 1)..macro_block_number {
-        let last_micro_block = producer.next_micro_block(
-            blockchain.time.now() + i as u64 * 1000,
-            0,
-            None,
-            vec![],
-            vec![0x42],
-        );
+        let last_micro_block = producer
+            .next_micro_block(
+                blockchain.time.now() + i as u64 * 1000,
+                0,
+                None,
+                vec![],
+                vec![0x42],
+            )
+            .unwrap();
         assert_eq!(
             blockchain.push(Block::Micro(last_micro_block)),
             Ok(PushResult::Extended)
@@ -188,7 +198,7 @@ pub fn fill_micro_blocks(producer: &BlockProducer, blockchain: &Arc<Blockchain>)
 }
 
 pub fn sign_macro_block(
-    keypair: &KeyPair,
+    signer: &dyn Signer,
     header: MacroHeader,
     body: Option<MacroBody>,
 ) -> MacroBlock {
@@ -197,7 +207,7 @@ pub fn sign_macro_block(
 
     // Calculate the validator Merkle root (used in the nano sync).
     let validator_merkle_root =
-        pk_tree_construct(vec![keypair.public_key.public_key; policy::SLOTS as usize]);
+        pk_tree_construct(vec![signer.public_key().public_key; policy::SLOTS as usize]);
 
     // Create the precommit tendermint vote.
     let precommit = TendermintVote {
@@ -211,7 +221,9 @@ pub fn sign_macro_block(
     };
 
     // Create signed precommit.
-    let signed_precommit = keypair.secret_key.sign(&precommit);
+    let signed_precommit = signer
+        .sign(&SignRequest::TendermintVote(precommit))
+        .unwrap();
 
     // Create signers Bitset.
     let mut signers = BitSet::new();
@@ -285,17 +297,15 @@ pub fn produce_macro_blocks(
         fill_micro_blocks(producer, blockchain);
 
         let _next_block_height = blockchain.block_number() + 1;
-        let macro_block = producer.next_macro_block_proposal(
-            blockchain.time.now() + blockchain.block_number() as u64 * 1000,
-            0u32,
-            vec![],
-        );
+        let macro_block = producer
+            .next_macro_block_proposal(
+                blockchain.time.now() + blockchain.block_number() as u64 * 1000,
+                0u32,
+                vec![],
+            )
+            .unwrap();
 
-        let block = sign_macro_block(
-            &producer.validator_key,
-            macro_block.header,
-            macro_block.body,
-        );
+        let block = sign_macro_block(&*producer.signer, macro_block.header, macro_block.body);
         assert_eq!(
             blockchain.push(Block::Macro(block)),
             Ok(PushResult::Extended)
@@ -7,7 +7,11 @@ use nimiq_block_albatross::{
     ViewChange, ViewChangeProof,
 };
 use nimiq_block_production_albatross::BlockProducer;
-use nimiq_blockchain_albatross::{AbstractBlockchain, Blockchain, PushError, PushResult};
+use nimiq_blockchain_albatross::slash_event::SlashEventKind;
+use nimiq_blockchain_albatross::staking_diff::ValidatorEventKind;
+use nimiq_blockchain_albatross::{
+    AbstractBlockchain, Blockchain, PushError, PushResult, StakingDiffError,
+};
 use nimiq_bls::{AggregateSignature, KeyPair, SecretKey};
 use nimiq_collections::BitSet;
 use nimiq_database::volatile::VolatileEnvironment;
@@ -28,13 +32,15 @@ fn fill_micro_blocks(producer: &BlockProducer, blockchain: &Arc<Blockchain>) {
     let init_height = blockchain.block_number();
     let macro_block_number = policy::macro_block_after(init_height + 1);
     for i in (init_height + 1)..macro_block_number {
-        let last_micro_block = producer.next_micro_block(
-            blockchain.time.now() + i as u64 * 1000,
-            0,
-            None,
-            vec![],
-            vec![0x42],
-        );
+        let last_micro_block = producer
+            .next_micro_block(
+                blockchain.time.now() + i as u64 * 1000,
+                0,
+                None,
+                vec![],
+                vec![0x42],
+            )
+            .unwrap();
         assert_eq!(
             blockchain.push(Block::Micro(last_micro_block)),
             Ok(PushResult::Extended)
@@ -137,7 +143,7 @@ fn sign_view_change(
 
 #[test]
 fn it_can_produce_micro_blocks() {
-    let env = VolatileEnvironment::new(10).unwrap();
+    let env = VolatileEnvironment::new(Blockchain::NUM_DATABASES).unwrap();
     let blockchain = Arc::new(Blockchain::new(env, NetworkId::UnitAlbatross).unwrap());
     let mempool = Mempool::new(Arc::clone(&blockchain), MempoolConfig::default());
     let keypair =
@@ -145,7 +151,9 @@ fn it_can_produce_micro_blocks() {
     let producer = BlockProducer::new(Arc::clone(&blockchain), mempool, keypair.clone());
 
     // #1.0: Empty standard micro block
-    let block = producer.next_micro_block(blockchain.time.now(), 0, None, vec![], vec![0x41]);
+    let block = producer
+        .next_micro_block(blockchain.time.now(), 0, None, vec![], vec![0x41])
+        .unwrap();
     assert_eq!(
         blockchain.push(Block::Micro(block.clone())),
         Ok(PushResult::Extended)
@@ -169,13 +177,15 @@ fn it_can_produce_micro_blocks() {
     }
 
     // #2.0: Empty micro block with fork proof
-    let block = producer.next_micro_block(
-        blockchain.time.now() + 1000,
-        0,
-        None,
-        vec![fork_proof],
-        vec![0x41],
-    );
+    let block = producer
+        .next_micro_block(
+            blockchain.time.now() + 1000,
+            0,
+            None,
+            vec![fork_proof],
+            vec![0x41],
+        )
+        .unwrap();
     assert_eq!(
         blockchain.push(Block::Micro(block)),
         Ok(PushResult::Extended)
@@ -185,13 +195,15 @@ fn it_can_produce_micro_blocks() {
 
     // #2.1: Empty view-changed micro block (wrong prev_hash)
     let view_change = sign_view_change(VrfSeed::default(), 3, 1);
-    let block = producer.next_micro_block(
-        blockchain.time.now() + 2000,
-        1,
-        Some(view_change),
-        vec![],
-        vec![0x41],
-    );
+    let block = producer
+        .next_micro_block(
+            blockchain.time.now() + 2000,
+            1,
+            Some(view_change),
+            vec![],
+            vec![0x41],
+        )
+        .unwrap();
 
     // the block justification is ok, the view_change justification is not.
     assert_eq!(
@@ -201,24 +213,96 @@ fn it_can_produce_micro_blocks() {
 
     // #2.2: Empty view-changed micro block
     let view_change = sign_view_change(blockchain.head().seed().clone(), 3, 1);
-    let block = producer.next_micro_block(
-        blockchain.time.now() + 2000,
-        1,
-        Some(view_change),
-        vec![],
-        vec![0x41],
-    );
+    let block = producer
+        .next_micro_block(
+            blockchain.time.now() + 2000,
+            1,
+            Some(view_change),
+            vec![],
+            vec![0x41],
+        )
+        .unwrap();
     assert_eq!(
         blockchain.push(Block::Micro(block)),
         Ok(PushResult::Extended)
     );
     assert_eq!(blockchain.block_number(), 3);
     assert_eq!(blockchain.next_view_number(), 1);
+
+    // The fork proof and the view change are recorded in the slash log.
+    let slash_events = blockchain.get_slash_events(Some(1), None, 10, None);
+    assert_eq!(slash_events.len(), 2);
+    assert_eq!(slash_events[0].block_number, 2);
+    assert!(matches!(
+        slash_events[0].kind,
+        SlashEventKind::ForkProof {
+            event_block: 1,
+            view_number: 0,
+            ..
+        }
+    ));
+    assert_eq!(slash_events[1].block_number, 3);
+    assert!(matches!(
+        slash_events[1].kind,
+        SlashEventKind::ViewChange {
+            event_block: 3,
+            view_number: 0,
+            ..
+        }
+    ));
+
+    let validator_id = slash_events[0].validator_id.clone();
+    assert_eq!(
+        blockchain.get_slash_events(None, Some(&validator_id), 10, None),
+        slash_events
+            .iter()
+            .filter(|event| event.validator_id == validator_id)
+            .cloned()
+            .collect::<Vec<_>>()
+    );
+    assert!(blockchain
+        .get_slash_events(Some(2), None, 10, None)
+        .is_empty());
+
+    // The slash log can be read page by page.
+    let first_page = blockchain.get_slash_events(None, None, 1, None);
+    assert_eq!(first_page, slash_events[..1].to_vec());
+    assert_eq!(
+        blockchain.get_slash_events(None, None, 1, first_page.last()),
+        slash_events[1..].to_vec()
+    );
+    assert!(blockchain
+        .get_slash_events(None, None, 1, slash_events.last())
+        .is_empty());
+
+    // The slashes show up in the diff of the staking contract.
+    let diff = blockchain.get_staking_contract_diff(1, 3).unwrap();
+    assert_eq!(diff.validator_events.len(), 2);
+    for (event, slash_event) in diff.validator_events.iter().zip(slash_events.iter()) {
+        assert_eq!(event.block_number, slash_event.block_number);
+        assert_eq!(event.validator_id, slash_event.validator_id);
+        assert!(matches!(event.kind, ValidatorEventKind::Slashed { .. }));
+    }
+    assert!(diff.added_validators.is_empty());
+    assert!(diff.removed_validators.is_empty());
+    assert!(diff.stake_changes.is_empty());
+    assert_eq!(
+        blockchain
+            .get_staking_contract_diff(2, 3)
+            .unwrap()
+            .validator_events
+            .len(),
+        1
+    );
+    assert_eq!(
+        blockchain.get_staking_contract_diff(3, 4),
+        Err(StakingDiffError::InvalidBlockRange(3, 4))
+    );
 }
 
 #[test]
 fn it_can_produce_macro_blocks() {
-    let env = VolatileEnvironment::new(10).unwrap();
+    let env = VolatileEnvironment::new(Blockchain::NUM_DATABASES).unwrap();
     let blockchain = Arc::new(Blockchain::new(env, NetworkId::UnitAlbatross).unwrap());
     let mempool = Mempool::new(Arc::clone(&blockchain), MempoolConfig::default());
 
@@ -228,11 +312,13 @@ fn it_can_produce_macro_blocks() {
 
     fill_micro_blocks(&producer, &blockchain);
 
-    let macro_block = producer.next_macro_block_proposal(
-        blockchain.time.now() + blockchain.block_number() as u64 * 1000,
-        0u32,
-        vec![],
-    );
+    let macro_block = producer
+        .next_macro_block_proposal(
+            blockchain.time.now() + blockchain.block_number() as u64 * 1000,
+            0u32,
+            vec![],
+        )
+        .unwrap();
 
     let block = sign_macro_block(macro_block.header, macro_block.body);
     assert_eq!(
@@ -243,7 +329,7 @@ fn it_can_produce_macro_blocks() {
 
 #[test]
 fn it_can_produce_election_blocks() {
-    let env = VolatileEnvironment::new(10).unwrap();
+    let env = VolatileEnvironment::new(Blockchain::NUM_DATABASES).unwrap();
     let blockchain = Arc::new(Blockchain::new(env, NetworkId::UnitAlbatross).unwrap());
     let mempool = Mempool::new(Arc::clone(&blockchain), MempoolConfig::default());
 
@@ -256,11 +342,13 @@ fn it_can_produce_election_blocks() {
     while policy::epoch_at(blockchain.block_number()) < 2 {
         fill_micro_blocks(&producer, &blockchain);
 
-        let macro_block = producer.next_macro_block_proposal(
-            blockchain.time.now() + blockchain.block_number() as u64 * 1000,
-            0u32,
-            vec![0x42],
-        );
+        let macro_block = producer
+            .next_macro_block_proposal(
+                blockchain.time.now() + blockchain.block_number() as u64 * 1000,
+                0u32,
+                vec![0x42],
+            )
+            .unwrap();
 
         let block = sign_macro_block(macro_block.header, macro_block.body);
 
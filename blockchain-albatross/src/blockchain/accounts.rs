@@ -1,11 +1,16 @@
-use nimiq_account::Inherent;
+use beserial::Deserialize;
+use nimiq_account::{Inherent, InherentType};
 use nimiq_accounts::Accounts;
 use nimiq_block_albatross::{Block, MicroBlock, ViewChanges};
 use nimiq_database::WriteTransaction;
 use nimiq_primitives::policy;
+use nimiq_primitives::slots::SlashedSlot;
+use nimiq_transaction::account::staking_contract::IncomingStakingTransactionData;
+use nimiq_transaction::Transaction;
 
 use crate::blockchain_state::BlockchainState;
 use crate::history_store::ExtendedTransaction;
+use crate::slash_event::{SlashEvent, SlashEventKind};
 use crate::{Blockchain, PushError};
 
 /// Implements methods to handle the accounts.
@@ -28,7 +33,9 @@ impl Blockchain {
                 let mut inherents: Vec<Inherent> = vec![];
 
                 // Every macro block is the end of a batch, so we need to finalize the batch.
-                inherents.append(&mut self.finalize_previous_batch(state, &macro_block.header));
+                let (mut batch_inherents, reward_report) =
+                    self.finalize_previous_batch_with_report(state, &macro_block.header)?;
+                inherents.append(&mut batch_inherents);
 
                 // If this block is an election block, we also need to finalize the epoch.
                 if macro_block.is_election_block() {
@@ -55,6 +62,11 @@ impl Blockchain {
                 // as rebranching across this block is not possible.
                 self.chain_store.clear_receipts(txn);
 
+                // Store the report of the rewards that were distributed for the previous batch.
+                if let Some(reward_report) = reward_report {
+                    self.chain_store.put_reward_report(txn, &reward_report);
+                }
+
                 // Store the transactions and the inherents into the History tree.
                 let ext_txs = ExtendedTransaction::from(
                     macro_block.header.block_number,
@@ -103,6 +115,19 @@ impl Blockchain {
                 self.chain_store
                     .put_receipts(txn, micro_block.header.block_number, &receipts);
 
+                // Store the slash events into the slash log.
+                let slash_events = self.create_slash_events(
+                    micro_block.header.block_number,
+                    &body.transactions,
+                    &inherents,
+                    Some(Self::slash_causes(micro_block, &view_changes)),
+                )?;
+                self.chain_store.put_slash_events(
+                    txn,
+                    policy::epoch_at(micro_block.header.block_number),
+                    &slash_events,
+                );
+
                 // Store the transactions and the inherents into the History tree.
                 let ext_txs = ExtendedTransaction::from(
                     micro_block.header.block_number,
@@ -168,6 +193,19 @@ impl Blockchain {
             panic!("Failed to revert - {}", e);
         }
 
+        // Remove the slash events from the slash log.
+        let slash_events = self.create_slash_events(
+            micro_block.header.block_number,
+            &body.transactions,
+            &inherents,
+            Some(Self::slash_causes(micro_block, &view_changes)),
+        )?;
+        self.chain_store.remove_slash_events(
+            txn,
+            policy::epoch_at(micro_block.header.block_number),
+            &slash_events,
+        );
+
         // Remove the transactions from the History tree. For this you only need to calculate the
         // number of transactions that you want to remove.
         let num_txs = body.transactions.len() + inherents.len();
@@ -180,4 +218,115 @@ impl Blockchain {
 
         Ok(())
     }
+
+    /// Returns the cause of every slash inherent of a micro block: whether it was created for a
+    /// fork proof and the view number of the slashed block. The slash inherents are created for
+    /// the fork proofs first and then for the view changes.
+    fn slash_causes(
+        micro_block: &MicroBlock,
+        view_changes: &Option<ViewChanges>,
+    ) -> Vec<(bool, u32)> {
+        let body = micro_block.body.as_ref().unwrap();
+
+        let view_numbers = view_changes
+            .iter()
+            .flat_map(|view_changes| view_changes.first_view_number..view_changes.last_view_number);
+
+        body.fork_proofs
+            .iter()
+            .map(|fork_proof| (true, fork_proof.header1.view_number))
+            .chain(view_numbers.map(|view_number| (false, view_number)))
+            .collect()
+    }
+
+    /// Creates the slash log events of a block from its slash inherents and its unpark
+    /// transactions. If the causes of the slashes are given (see `slash_causes`), the slashes are
+    /// recorded as fork proofs and view changes. Otherwise, which is the case for blocks that were
+    /// synced from the history, they are recorded as plain slashes.
+    pub(crate) fn create_slash_events(
+        &self,
+        block_number: u32,
+        transactions: &[Transaction],
+        inherents: &[Inherent],
+        slash_causes: Option<Vec<(bool, u32)>>,
+    ) -> Result<Vec<SlashEvent>, PushError> {
+        let mut slots = vec![];
+
+        for inherent in inherents {
+            if inherent.ty != InherentType::Slash {
+                continue;
+            }
+
+            let slot: SlashedSlot = Deserialize::deserialize_from_vec(&inherent.data)
+                .map_err(|_| PushError::InvalidSlashInherents)?;
+            slots.push(slot);
+        }
+
+        let mut events = vec![];
+
+        match slash_causes {
+            Some(slash_causes) => {
+                if slash_causes.len() != slots.len() {
+                    return Err(PushError::InvalidSlashInherents);
+                }
+
+                for ((is_fork_proof, view_number), slot) in slash_causes.into_iter().zip(slots) {
+                    let kind = if is_fork_proof {
+                        SlashEventKind::ForkProof {
+                            slot: slot.slot,
+                            event_block: slot.event_block,
+                            view_number,
+                        }
+                    } else {
+                        SlashEventKind::ViewChange {
+                            slot: slot.slot,
+                            event_block: slot.event_block,
+                            view_number,
+                        }
+                    };
+
+                    events.push(SlashEvent {
+                        block_number,
+                        validator_id: slot.validator_id,
+                        kind,
+                    });
+                }
+            }
+            None => {
+                for slot in slots {
+                    events.push(SlashEvent {
+                        block_number,
+                        validator_id: slot.validator_id,
+                        kind: SlashEventKind::Slash {
+                            slot: slot.slot,
+                            event_block: slot.event_block,
+                        },
+                    });
+                }
+            }
+        }
+
+        // Unpark transactions are incoming transactions of the staking contract.
+        let validator_registry = self.validator_registry_address();
+
+        for transaction in transactions {
+            if Some(&transaction.recipient) != validator_registry
+                || transaction.sender == transaction.recipient
+            {
+                continue;
+            }
+
+            if let Ok(IncomingStakingTransactionData::UnparkValidator { validator_id, .. }) =
+                IncomingStakingTransactionData::parse(transaction)
+            {
+                events.push(SlashEvent {
+                    block_number,
+                    validator_id,
+                    kind: SlashEventKind::Unpark,
+                });
+            }
+        }
+
+        Ok(events)
+    }
 }
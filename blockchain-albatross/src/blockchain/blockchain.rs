@@ -1,3 +1,4 @@
+use std::collections::VecDeque;
 use std::sync::Arc;
 
 use parking_lot::{Mutex, RwLock};
@@ -16,6 +17,7 @@ use nimiq_primitives::slots::Validators;
 use nimiq_utils::observer::Notifier;
 use nimiq_utils::time::OffsetTime;
 
+use crate::blockchain::macro_sync::AccountsChunkCache;
 use crate::blockchain_state::BlockchainState;
 use crate::chain_info::ChainInfo;
 #[cfg(feature = "metrics")]
@@ -47,6 +49,9 @@ pub struct Blockchain {
     pub state: RwLock<BlockchainState>,
     // A write lock for the blockchain. Guarantees that only one thread writes to it at a time.
     pub(crate) push_lock: Mutex<()>,
+    // The chunks of the accounts trees at the last macro heads, which are sent to macro syncing
+    // nodes.
+    pub(crate) accounts_chunk_caches: Mutex<VecDeque<AccountsChunkCache>>,
     // The metrics for the blockchain. Needed for analysis.
     #[cfg(feature = "metrics")]
     pub(crate) metrics: BlockchainMetrics,
@@ -58,6 +63,11 @@ pub struct Blockchain {
 
 /// Implements methods to start a Blockchain.
 impl Blockchain {
+    /// The number of databases that the blockchain opens in its environment: the chain store, the
+    /// history store and the accounts tree. Environments have to allow at least this many
+    /// databases, plus the ones of any other stores that share the environment.
+    pub const NUM_DATABASES: u32 = 13;
+
     /// Creates a new blockchain from a given environment and network ID.
     pub fn new(env: Environment, network_id: NetworkId) -> Result<Self, BlockchainError> {
         // TODO `time` should be passed by the caller.
@@ -130,7 +140,7 @@ impl Blockchain {
             .ok_or(BlockchainError::FailedLoadingMainChain)?;
 
         // Check that chain/accounts state is consistent.
-        let accounts = Accounts::new(env.clone());
+        let accounts = Arc::new(Accounts::new(env.clone()));
 
         if main_chain.head.state_root() != &accounts.hash(None) {
             return Err(BlockchainError::InconsistentState);
@@ -227,6 +237,7 @@ impl Blockchain {
                 previous_slots: last_slots,
             }),
             push_lock: Mutex::new(()),
+            accounts_chunk_caches: Mutex::new(VecDeque::new()),
             #[cfg(feature = "metrics")]
             metrics: BlockchainMetrics::default(),
             genesis_supply,
@@ -254,7 +265,7 @@ impl Blockchain {
         let main_chain = ChainInfo::new(genesis_block, true);
 
         // Initialize accounts.
-        let accounts = Accounts::new(env.clone());
+        let accounts = Arc::new(Accounts::new(env.clone()));
         let mut txn = WriteTransaction::new(&env);
         accounts.init(&mut txn, genesis_accounts);
 
@@ -284,6 +295,7 @@ impl Blockchain {
                 previous_slots: Some(Validators::default()),
             }),
             push_lock: Mutex::new(()),
+            accounts_chunk_caches: Mutex::new(VecDeque::new()),
 
             #[cfg(feature = "metrics")]
             metrics: BlockchainMetrics::default(),
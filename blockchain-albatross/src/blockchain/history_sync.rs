@@ -242,6 +242,30 @@ impl Blockchain {
                 self.metrics.note_invalid_block();
                 return Err(PushError::AccountsError(e));
             }
+
+            // Store the slash events into the slash log. The history doesn't contain the fork
+            // proofs and view changes, so the slashes are recorded without their cause.
+            let slash_events = match self.create_slash_events(
+                block_numbers[i],
+                &block_transactions[i],
+                &block_inherents[i],
+                None,
+            ) {
+                Ok(slash_events) => slash_events,
+                Err(e) => {
+                    warn!("Rejecting block - invalid slash inherents: {:?}", e);
+                    txn.abort();
+                    #[cfg(feature = "metrics")]
+                    self.metrics.note_invalid_block();
+                    return Err(e);
+                }
+            };
+
+            self.chain_store.put_slash_events(
+                &mut txn,
+                policy::epoch_at(block_numbers[i]),
+                &slash_events,
+            );
         }
 
         // Macro blocks are final and receipts for the previous batch are no longer necessary
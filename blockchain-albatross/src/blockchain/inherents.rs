@@ -1,6 +1,6 @@
 use beserial::Serialize;
 use nimiq_account::inherent::AccountInherentInteraction;
-use nimiq_account::{Inherent, InherentType};
+use nimiq_account::{AccountError, Inherent, InherentType};
 use nimiq_block_albatross::{ForkProof, MacroHeader, ViewChanges};
 use nimiq_database::Transaction;
 use nimiq_genesis::NetworkInfo;
@@ -11,7 +11,7 @@ use nimiq_primitives::slots::SlashedSlot;
 use nimiq_vrf::{AliasMethod, VrfUseCase};
 
 use crate::blockchain_state::BlockchainState;
-use crate::reward::block_reward_for_batch;
+use crate::reward::{block_reward_for_batch, BatchRewardReport, StakerReward, ValidatorReward};
 use crate::{AbstractBlockchain, Blockchain};
 
 /// Implements methods that create inherents.
@@ -113,19 +113,31 @@ impl Blockchain {
     }
 
     /// Creates the inherents to finalize a batch. The inherents are for reward distribution and
-    /// updating the StakingContract.
+    /// updating the StakingContract. Fails if a validator that held slots in the batch is missing
+    /// from the StakingContract.
     pub fn finalize_previous_batch(
         &self,
         state: &BlockchainState,
         macro_header: &MacroHeader,
-    ) -> Vec<Inherent> {
+    ) -> Result<Vec<Inherent>, AccountError> {
+        self.finalize_previous_batch_with_report(state, macro_header)
+            .map(|(inherents, _)| inherents)
+    }
+
+    /// Creates the inherents to finalize a batch, like `finalize_previous_batch`, together with a
+    /// report of the distributed rewards. There is no report for the first batch.
+    pub fn finalize_previous_batch_with_report(
+        &self,
+        state: &BlockchainState,
+        macro_header: &MacroHeader,
+    ) -> Result<(Vec<Inherent>, Option<BatchRewardReport>), AccountError> {
         let prev_macro_info = &state.macro_info;
 
         let staking_contract = self.get_staking_contract();
 
         // Special case for first batch: Batch 0 is finalized by definition.
         if policy::batch_at(macro_header.block_number) - 1 == 0 {
-            return vec![];
+            return Ok((vec![], None));
         }
 
         // Get validator slots
@@ -148,7 +160,7 @@ impl Blockchain {
         // disabled_set (clears on epoch end) makes rewards being lost further if validator doesn't unpark
         let lost_rewards_set = staking_contract.previous_lost_rewards();
         let disabled_set = staking_contract.previous_disabled_slots();
-        let slashed_set = lost_rewards_set | disabled_set;
+        let slashed_set = &lost_rewards_set | &disabled_set;
 
         // Total reward for the previous batch
         let block_reward = block_reward_for_batch(
@@ -162,6 +174,14 @@ impl Blockchain {
 
         let reward_pot = block_reward + tx_fees;
 
+        // Shares of stakers below this minimum go to the reward address of their validator. Since
+        // every staker reward is at least this large, there are at most
+        // `MAX_STAKER_REWARDS_PER_BATCH` of them.
+        let min_staker_share = Coin::from_u64_unchecked(
+            (u64::from(reward_pot) + policy::MAX_STAKER_REWARDS_PER_BATCH - 1)
+                / policy::MAX_STAKER_REWARDS_PER_BATCH,
+        );
+
         // Distribute reward between all slots and calculate the remainder
         let slot_reward = reward_pot / policy::SLOTS as u64;
         let remainder = reward_pot % policy::SLOTS as u64;
@@ -175,8 +195,41 @@ impl Blockchain {
         // All accepted inherents.
         let mut inherents = Vec::new();
 
-        // Remember the number of eligible slots that a validator had (that was able to accept the inherent)
+        // Remember the number of eligible slots that a validator had (whose reward address was able
+        // to accept the inherent), together with the index of that inherent. Only reward addresses
+        // can receive the remainder, stakers can't.
         let mut num_eligible_slots_for_accepted_inherent = Vec::new();
+        let mut inherent_index_for_accepted_inherent = Vec::new();
+
+        // The rewards of all validators for the report, and the index into them for each accepted
+        // inherent of a reward address.
+        let mut validator_rewards = Vec::new();
+        let mut validator_index_for_accepted_inherent = Vec::new();
+
+        // Tests whether an account will accept a reward inherent. If it can't then the reward will
+        // be burned.
+        let accepts_reward = |inherent: &Inherent| {
+            let accepted = state
+                .accounts
+                .get(&inherent.target, None)
+                .and_then(|account| {
+                    account.check_inherent(
+                        inherent,
+                        macro_header.block_number,
+                        macro_header.timestamp,
+                    )
+                })
+                .is_ok();
+
+            if !accepted {
+                debug!(
+                    "{} can't accept epoch reward {}",
+                    inherent.target, inherent.value
+                );
+            }
+
+            accepted
+        };
 
         // Remember that the total amount of reward must be burned. The reward for a slot is burned
         // either because the slot was slashed or because the corresponding validator was unable to
@@ -217,45 +270,82 @@ impl Blockchain {
                 .checked_mul(num_slashed_slots as u64)
                 .expect("Overflow in reward");
 
+            // Split the reward between the reward address of the validator and, if the validator
+            // charges a commission, its stakers.
+            let mut payouts = staking_contract
+                .split_reward(&validator_slot.validator_id, reward, min_staker_share)?
+                .into_iter();
+            let (reward_address, validator_payout) = payouts.next().unwrap();
+
             // Create inherent for the reward
             let inherent = Inherent {
                 ty: InherentType::Reward,
-                target: staking_contract
-                    .get_validator(&validator_slot.validator_id)
-                    .unwrap()
-                    .reward_address
-                    .clone(),
-                value: reward,
+                target: reward_address,
+                value: validator_payout,
                 data: vec![],
             };
 
-            // Test whether account will accept inherent. If it can't then the reward will be
-            // burned.
-            let account = state.accounts.get(&inherent.target, None);
+            let mut validator_reward = ValidatorReward {
+                validator_id: validator_slot.validator_id.clone(),
+                reward_address: inherent.target.clone(),
+                first_slot: first_slot_number,
+                num_slots: validator_slot.num_slots(),
+                lost_reward_slots: (first_slot_number..last_slot_number)
+                    .filter(|&slot| lost_rewards_set.contains(slot as usize))
+                    .collect(),
+                disabled_slots: (first_slot_number..last_slot_number)
+                    .filter(|&slot| disabled_set.contains(slot as usize))
+                    .collect(),
+                num_eligible_slots,
+                reward: validator_payout,
+                accepted: true,
+                staker_rewards: vec![],
+            };
 
-            if account
-                .check_inherent(&inherent, macro_header.block_number, macro_header.timestamp)
-                .is_err()
-            {
-                debug!(
-                    "{} can't accept epoch reward {}",
-                    inherent.target, inherent.value
-                );
-                burned_reward += reward;
-            } else {
+            if accepts_reward(&inherent) {
                 num_eligible_slots_for_accepted_inherent.push(num_eligible_slots);
+                inherent_index_for_accepted_inherent.push(inherents.len());
+                validator_index_for_accepted_inherent.push(validator_rewards.len());
                 inherents.push(inherent);
+            } else {
+                burned_reward += validator_payout;
+                validator_reward.reward = Coin::ZERO;
+                validator_reward.accepted = false;
             }
 
+            // Create inherents for the shares of the stakers.
+            for (staker_address, share) in payouts {
+                let inherent = Inherent {
+                    ty: InherentType::Reward,
+                    target: staker_address.clone(),
+                    value: share,
+                    data: vec![],
+                };
+
+                let accepted = accepts_reward(&inherent);
+                if accepted {
+                    inherents.push(inherent);
+                } else {
+                    burned_reward += share;
+                }
+
+                validator_reward.staker_rewards.push(StakerReward {
+                    staker_address,
+                    reward: if accepted { share } else { Coin::ZERO },
+                    accepted,
+                });
+            }
+
+            validator_rewards.push(validator_reward);
+
             // Update first_slot_number for next iteration
             first_slot_number = last_slot_number;
         }
 
-        // Check that number of accepted inherents is equal to length of the map that gives us the
-        // corresponding number of slots for that staker (which should be equal to the number of
-        // validators that will receive rewards).
+        // Check that there is an inherent for each entry of the map that gives us the corresponding
+        // number of slots for that validator.
         assert_eq!(
-            inherents.len(),
+            inherent_index_for_accepted_inherent.len(),
             num_eligible_slots_for_accepted_inherent.len()
         );
 
@@ -266,7 +356,8 @@ impl Blockchain {
         // Randomly give remainder to one accepting slot. We don't bother to distribute it over all
         // accepting slots because the remainder is always at most SLOTS - 1 Lunas.
         let index = lookup.sample(&mut rng);
-        inherents[index].value += remainder;
+        inherents[inherent_index_for_accepted_inherent[index]].value += remainder;
+        validator_rewards[validator_index_for_accepted_inherent[index]].reward += remainder;
 
         // Create the inherent for the burned reward.
         if burned_reward > Coin::ZERO {
@@ -292,7 +383,18 @@ impl Blockchain {
             data: Vec::new(),
         });
 
-        inherents
+        let report = BatchRewardReport {
+            batch_number: policy::batch_at(macro_header.block_number) - 1,
+            block_number: macro_header.block_number,
+            block_reward,
+            transaction_fees: tx_fees,
+            slot_reward,
+            remainder,
+            burned_reward,
+            validators: validator_rewards,
+        };
+
+        Ok((inherents, Some(report)))
     }
 
     /// Creates the inherent to finalize an epoch. The inherent is for updating the StakingContract.
@@ -0,0 +1,481 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+
+use beserial::Serialize;
+
+use nimiq_account::Account;
+use nimiq_block_albatross::{Block, BlockError, MacroBlock};
+use nimiq_database::{ReadTransaction, WriteTransaction};
+use nimiq_hash::{Blake2bHash, Hash};
+use nimiq_primitives::coin::Coin;
+use nimiq_primitives::policy;
+use nimiq_primitives::slots::Validators;
+use nimiq_tree_primitives::accounts_tree_chunk::AccountsTreeChunk;
+
+use crate::chain_info::ChainInfo;
+use crate::history_store::{ExtTxData, ExtendedTransaction, HistoryStore};
+use crate::transaction_cache::TransactionCache;
+use crate::{AbstractBlockchain, Blockchain, BlockchainEvent, PushError, PushResult};
+
+/// The maximum number of accounts in a chunk of the accounts tree that is sent to macro syncing
+/// nodes.
+pub const ACCOUNTS_CHUNK_SIZE: usize = 1000;
+
+/// The number of parts that the accounts tree is split into for macro syncing. Each part contains
+/// the keys with the same first nibble, so that the parts can be downloaded in parallel.
+pub const ACCOUNTS_CHUNK_PARTS: usize = 16;
+
+/// Returns the prefix that the first chunk of a part is requested with. The first part starts at
+/// the beginning of the tree, all others at their first nibble.
+pub fn accounts_chunk_part_start(part: usize) -> String {
+    if part == 0 {
+        String::new()
+    } else {
+        format!("{:x}", part)
+    }
+}
+
+/// Returns the prefix that the chunk after the given one of a part is requested with, or `None`
+/// if the chunk is the last one of its part.
+pub fn next_accounts_chunk_prefix(
+    part: usize,
+    chunk: &AccountsTreeChunk<Account>,
+) -> Option<String> {
+    if chunk.len() < ACCOUNTS_CHUNK_SIZE {
+        // This is the end of the tree.
+        return None;
+    }
+
+    let tail_prefix = chunk.last_terminal_string()?;
+    if part + 1 < ACCOUNTS_CHUNK_PARTS && tail_prefix >= accounts_chunk_part_start(part + 1) {
+        return None;
+    }
+
+    Some(tail_prefix)
+}
+
+/// The maximum serialized size of the chunks of accounts trees that we keep. The caches of the
+/// oldest macro blocks are dropped first, but the cache of the newest macro block is always kept.
+const MAX_ACCOUNTS_CHUNK_CACHE_SIZE: usize = 256 * 1024 * 1024;
+
+/// The chunks of the accounts tree at a macro block, by the prefix they are requested with.
+pub(crate) struct AccountsChunkCache {
+    block_hash: Blake2bHash,
+    chunks: HashMap<String, AccountsTreeChunk<Account>>,
+    // The serialized size of the chunks.
+    size: usize,
+}
+
+/// Implements methods to sync the chain when a node is macro syncing. Macro syncing works like
+/// history syncing, but the node only gets the election macro blocks since its current election
+/// block plus the last macro block, the history of the epoch of the last macro block and the
+/// accounts tree at the last macro block. The accounts tree is transferred in chunks (see
+/// `get_accounts_chunk`), so the node never replays the history of past epochs. The history of
+/// the skipped epochs stays unknown (see `ChainStore::get_history_start`).
+/// Like history syncing, we rely on the assumption that the macro blocks were produced by honest
+/// validator sets. We only verify that the blocks form a chain and are justified by the validators
+/// of their epoch, and that the history and the accounts tree are the ones committed to in the
+/// last block.
+impl Blockchain {
+    /// Pushes a chain of macro blocks (election or checkpoint) on top of our current macro head and
+    /// replaces the accounts tree with the one at the last of these blocks. `ext_txs` must be the
+    /// history of the epoch of the last block and `accounts_chunks` must be the chunks of the
+    /// accounts tree at the last block, in order.
+    /// Everything is applied in a single database transaction, so the chain is either synced to
+    /// the last block or not changed at all.
+    pub fn push_macro_sync(
+        &self,
+        blocks: &[MacroBlock],
+        ext_txs: &[ExtendedTransaction],
+        accounts_chunks: &[AccountsTreeChunk<Account>],
+    ) -> Result<PushResult, PushError> {
+        // Only one push operation at a time.
+        let push_lock = self.push_lock.lock();
+
+        let read_txn = ReadTransaction::new(&self.env);
+
+        let last_block = blocks.last().ok_or(PushError::InvalidSuccessor)?;
+
+        // Check if we already know the last block.
+        if self
+            .chain_store
+            .get_chain_info(&last_block.hash(), false, Some(&read_txn))
+            .is_some()
+        {
+            return Ok(PushResult::Known);
+        }
+
+        // Get the chain info at the head of the current chain.
+        let head = self
+            .chain_store
+            .get_head(Some(&read_txn))
+            .ok_or(PushError::Orphan)?;
+
+        let mut prev_info = self
+            .chain_store
+            .get_chain_info(&head, false, Some(&read_txn))
+            .ok_or(PushError::Orphan)?;
+
+        // We can only macro sync on top of a macro block. Once we have pushed micro blocks, the
+        // chain must be extended normally.
+        if !prev_info.head.is_macro() {
+            return Err(PushError::InvalidSuccessor);
+        }
+
+        // Check that the blocks form a chain on top of our head and that each of them is justified
+        // by the validators of its epoch. Remember the validators elected by the last two election
+        // blocks.
+        let mut current_validators = self.current_validators().unwrap();
+        let mut previous_validators = None;
+        let mut prev_block = prev_info.head.unwrap_macro_ref();
+
+        for block in blocks {
+            Self::verify_macro_sync_block(block, prev_block, &current_validators)?;
+
+            if block.is_election_block() {
+                let validators = block
+                    .get_validators()
+                    .ok_or(PushError::InvalidBlock(BlockError::InvalidValidators))?;
+                previous_validators = Some(current_validators);
+                current_validators = validators;
+            }
+
+            prev_block = block;
+        }
+
+        // Check that we got the history of the epoch of the last block and nothing else.
+        let epoch_number = policy::epoch_at(last_block.header.block_number);
+        if ext_txs.iter().any(|ext_tx| {
+            policy::epoch_at(ext_tx.block_number) != epoch_number
+                || ext_tx.block_number > last_block.header.block_number
+        }) {
+            warn!("Rejecting macro sync - history of the wrong epoch");
+            return Err(PushError::InvalidBlock(BlockError::InvalidHistoryRoot));
+        }
+
+        let history_root = HistoryStore::get_root_from_ext_txs(ext_txs)
+            .ok_or(PushError::InvalidBlock(BlockError::InvalidHistoryRoot))?;
+
+        if last_block.header.history_root != history_root {
+            warn!("Rejecting macro sync - wrong history root");
+            return Err(PushError::InvalidBlock(BlockError::InvalidHistoryRoot));
+        }
+
+        // Create a new database write transaction.
+        let mut txn = WriteTransaction::new(&self.env);
+
+        // Replace the accounts tree and check it against the state root of the last block.
+        let state = self.state.read();
+
+        state.accounts.clear(&mut txn);
+        for chunk in accounts_chunks {
+            state.accounts.commit_chunk(&mut txn, chunk);
+        }
+
+        if state.accounts.hash(Some(&txn)) != last_block.header.state_root {
+            warn!("Rejecting macro sync - accounts tree doesn't match the state root");
+            txn.abort();
+            return Err(PushError::InvalidBlock(BlockError::AccountsHashMismatch));
+        }
+
+        // Calculate the cumulative transaction fees for the batch of the last block. This is
+        // necessary to create its chain info.
+        let mut cum_tx_fees = Coin::ZERO;
+
+        let last_batch = policy::batch_at(last_block.header.block_number);
+
+        for ext_tx in ext_txs.iter().rev() {
+            if policy::batch_at(ext_tx.block_number) != last_batch {
+                break;
+            }
+
+            if let ExtTxData::Basic(tx) = &ext_tx.data {
+                cum_tx_fees += tx.fee;
+            }
+        }
+
+        // Create the chain infos for the blocks and store them. The blocks are linked to each
+        // other as successors on the main chain, since we skip the blocks in between.
+        let mut chain_info = prev_info.clone();
+
+        for block in blocks {
+            let block_hash = block.hash();
+
+            prev_info.main_chain_successor = Some(block_hash.clone());
+            self.chain_store
+                .put_chain_info(&mut txn, &prev_info.head.hash(), &prev_info, false);
+
+            chain_info = ChainInfo {
+                on_main_chain: true,
+                main_chain_successor: None,
+                head: Block::Macro(block.clone()),
+                cum_tx_fees: Coin::ZERO,
+            };
+            if block_hash == last_block.hash() {
+                chain_info.cum_tx_fees = cum_tx_fees;
+            }
+
+            self.chain_store
+                .put_chain_info(&mut txn, &block_hash, &chain_info, true);
+
+            prev_info = chain_info.clone();
+        }
+
+        let block_hash = last_block.hash();
+
+        // Set the head of the chain store to the last block.
+        self.chain_store.set_head(&mut txn, &block_hash);
+
+        // Macro blocks are final and receipts for the previous batch are no longer necessary
+        // as rebranching across this block is not possible.
+        self.chain_store.clear_receipts(&mut txn);
+
+        // Store the extended transactions that we don't know yet into the History tree.
+        let head_block_number = state.macro_info.head.block_number();
+        let first_new_ext_tx = ext_txs
+            .iter()
+            .take_while(|ext_tx| ext_tx.block_number <= head_block_number)
+            .count();
+
+        let new_ext_txs = &ext_txs[first_new_ext_tx..];
+        self.history_store
+            .add_to_history(&mut txn, epoch_number, new_ext_txs);
+
+        // The history and the slash log of the blocks that we skipped before the epoch of the last
+        // block are unknown, so they are only complete from that epoch on.
+        if head_block_number + 1 < policy::first_block_of(epoch_number) {
+            self.chain_store
+                .set_history_start(&mut txn, policy::first_block_of(epoch_number));
+        }
+
+        // Rebuild the slash log of the new blocks from their history, like when history syncing.
+        let mut start = 0;
+        while start < new_ext_txs.len() {
+            let block_number = new_ext_txs[start].block_number;
+            let end = start
+                + new_ext_txs[start..]
+                    .iter()
+                    .take_while(|ext_tx| ext_tx.block_number == block_number)
+                    .count();
+            let (transactions, inherents) =
+                ExtendedTransaction::to(new_ext_txs[start..end].to_vec());
+
+            let slash_events =
+                match self.create_slash_events(block_number, &transactions, &inherents, None) {
+                    Ok(slash_events) => slash_events,
+                    Err(e) => {
+                        warn!("Rejecting macro sync - invalid slash inherents: {:?}", e);
+                        txn.abort();
+                        return Err(e);
+                    }
+                };
+            self.chain_store
+                .put_slash_events(&mut txn, epoch_number, &slash_events);
+
+            start = end;
+        }
+
+        // Check if we moved to a new epoch.
+        let last_election_block = blocks.iter().rev().find(|block| block.is_election_block());
+
+        // Get a write transaction to the current state.
+        drop(state);
+        let mut state = self.state.write();
+
+        // Update the blockchain state. The transactions of the blocks that we skipped are unknown,
+        // so the transaction cache starts again with the last block.
+        let mut transaction_cache = TransactionCache::new();
+        transaction_cache.push_block(&chain_info.head);
+
+        state.main_chain = chain_info.clone();
+        state.head_hash = block_hash.clone();
+        state.macro_info = chain_info;
+        state.macro_head_hash = block_hash.clone();
+        state.transaction_cache = transaction_cache;
+        if let Some(election_block) = last_election_block {
+            state.election_head = election_block.clone();
+            state.election_head_hash = election_block.hash();
+            state.previous_slots = previous_validators;
+            state.current_slots = Some(current_validators);
+        }
+
+        // Give up database transactions and push lock before creating notifications.
+        txn.commit();
+        drop(state);
+        drop(push_lock);
+
+        if last_block.is_election_block() {
+            self.notifier
+                .read()
+                .notify(BlockchainEvent::EpochFinalized(block_hash));
+        } else {
+            self.notifier
+                .read()
+                .notify(BlockchainEvent::Finalized(block_hash));
+        }
+
+        Ok(PushResult::Extended)
+    }
+
+    /// Returns a chunk of the accounts tree at the macro block with the given hash. The chunk
+    /// contains up to `ACCOUNTS_CHUNK_SIZE` accounts following the `start` prefix, which must be
+    /// the start of a part (see `accounts_chunk_part_start`) or the prefix following a chunk of
+    /// that part (see `next_accounts_chunk_prefix`).
+    ///
+    /// The chunks are served from caches that hold all chunks of the accounts tree at a macro
+    /// block (see `cache_accounts_chunks`). If there is no cache for the macro block yet, it is
+    /// created if the macro block is still our head. We keep the caches of the last macro blocks
+    /// up to a total size of `MAX_ACCOUNTS_CHUNK_CACHE_SIZE`, so that nodes can resume
+    /// downloading an accounts tree after our macro head moved on.
+    ///
+    /// Creating a cache reads the whole accounts tree, so this should be called from a thread
+    /// that may block.
+    pub fn get_accounts_chunk(
+        &self,
+        block_hash: &Blake2bHash,
+        start: &str,
+    ) -> Option<AccountsTreeChunk<Account>> {
+        if !self.cache_accounts_chunks(block_hash) {
+            return None;
+        }
+
+        self.accounts_chunk_caches
+            .lock()
+            .iter()
+            .find(|cache| &cache.block_hash == block_hash)
+            .and_then(|cache| cache.chunks.get(start).cloned())
+    }
+
+    /// Caches the chunks of the accounts tree at the macro block with the given hash and returns
+    /// whether they are cached. A cache can only be created while the macro block is our head,
+    /// so this should be called as soon as a macro block was pushed.
+    ///
+    /// The chunks are read from a snapshot of the database, so pushing blocks isn't blocked while
+    /// the cache is created. This reads the whole accounts tree, so it should be called from a
+    /// thread that may block.
+    pub fn cache_accounts_chunks(&self, block_hash: &Blake2bHash) -> bool {
+        let is_cached = |caches: &VecDeque<AccountsChunkCache>| {
+            caches.iter().any(|cache| &cache.block_hash == block_hash)
+        };
+
+        if is_cached(&*self.accounts_chunk_caches.lock()) {
+            return true;
+        }
+
+        let cache = match self.create_accounts_chunk_cache(block_hash) {
+            Some(cache) => cache,
+            None => return false,
+        };
+
+        let mut caches = self.accounts_chunk_caches.lock();
+
+        // The cache might have been created concurrently.
+        if !is_cached(&*caches) {
+            caches.push_back(cache);
+
+            let mut size: usize = caches.iter().map(|cache| cache.size).sum();
+            while caches.len() > 1 && size > MAX_ACCOUNTS_CHUNK_CACHE_SIZE {
+                size -= caches.pop_front().unwrap().size;
+            }
+        }
+
+        true
+    }
+
+    /// Creates the chunks of the accounts tree at the macro block with the given hash, which must
+    /// be our head in the snapshot of the database that the chunks are read from.
+    fn create_accounts_chunk_cache(&self, block_hash: &Blake2bHash) -> Option<AccountsChunkCache> {
+        // Don't hold the state lock while reading the accounts tree, since that would block
+        // pushing blocks.
+        let accounts = Arc::clone(&self.state.read().accounts);
+
+        let txn = ReadTransaction::new(&self.env);
+
+        // The snapshot only contains the accounts tree at the macro block if no blocks were pushed
+        // on top of it.
+        if &self.chain_store.get_head(Some(&txn))? != block_hash {
+            return None;
+        }
+
+        if let Block::Micro(_) = self
+            .chain_store
+            .get_chain_info(block_hash, false, Some(&txn))?
+            .head
+        {
+            return None;
+        }
+
+        let mut chunks = HashMap::new();
+        let mut size = 0;
+        for part in 0..ACCOUNTS_CHUNK_PARTS {
+            let mut start = accounts_chunk_part_start(part);
+            loop {
+                let chunk = accounts.get_chunk(&start, ACCOUNTS_CHUNK_SIZE, Some(&txn))?;
+                let next_prefix = next_accounts_chunk_prefix(part, &chunk);
+                size += chunk.serialized_size();
+                chunks.insert(start, chunk);
+                match next_prefix {
+                    Some(prefix) => start = prefix,
+                    None => break,
+                }
+            }
+        }
+
+        Some(AccountsChunkCache {
+            block_hash: block_hash.clone(),
+            chunks,
+            size,
+        })
+    }
+
+    /// Verifies that a macro block succeeds the previous macro block and is justified by the given
+    /// validators. The checks are the same as in `push_history_sync`.
+    fn verify_macro_sync_block(
+        block: &MacroBlock,
+        prev_block: &MacroBlock,
+        validators: &Validators,
+    ) -> Result<(), PushError> {
+        // Check the version
+        if block.header.version != policy::VERSION {
+            return Err(PushError::InvalidBlock(BlockError::UnsupportedVersion));
+        }
+
+        // Check that the block succeeds the previous block. The checks change depending if the
+        // previous block is an election block or not.
+        if prev_block.is_election_block() {
+            if block.header.parent_election_hash != prev_block.hash() {
+                return Err(PushError::Orphan);
+            }
+        } else if block.header.parent_election_hash != prev_block.header.parent_election_hash
+            || block.header.block_number <= prev_block.header.block_number
+        {
+            return Err(PushError::Orphan);
+        }
+
+        // Checks if the body exists.
+        let body = block
+            .body
+            .as_ref()
+            .ok_or(PushError::InvalidBlock(BlockError::MissingBody))?;
+
+        // Check the body root.
+        if body.hash::<Blake2bHash>() != block.header.body_root {
+            warn!("Rejecting block - Header body hash doesn't match real body hash");
+            return Err(PushError::InvalidBlock(BlockError::BodyHashMismatch));
+        }
+
+        // Checks if the justification exists.
+        let justification = block
+            .justification
+            .as_ref()
+            .ok_or(PushError::InvalidBlock(BlockError::NoJustification))?;
+
+        // Check the justification.
+        if !justification.verify(block.hash(), block.header.block_number, validators) {
+            warn!("Rejecting block - macro block with bad justification");
+            return Err(PushError::InvalidBlock(BlockError::InvalidJustification));
+        }
+
+        Ok(())
+    }
+}
@@ -2,7 +2,9 @@ pub mod accounts;
 pub mod blockchain;
 pub mod history_sync;
 pub mod inherents;
+pub mod macro_sync;
 pub mod push;
 pub mod slots;
+pub mod staking_diff;
 pub mod verify;
 pub mod wrappers;
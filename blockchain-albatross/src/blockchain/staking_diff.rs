@@ -0,0 +1,491 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::mem;
+
+use beserial::Deserialize;
+use nimiq_account::staking_contract::StakingContractKeys;
+use nimiq_account::{Inherent, InherentType, Receipt, StakingContract};
+use nimiq_database::{ReadTransaction, Transaction};
+use nimiq_hash::{Blake2bHash, Hash};
+use nimiq_keys::Address;
+use nimiq_primitives::account::ValidatorId;
+use nimiq_primitives::coin::Coin;
+use nimiq_primitives::policy;
+use nimiq_primitives::slots::SlashedSlot;
+use nimiq_transaction::account::staking_contract::{
+    IncomingStakingTransactionData, OutgoingStakingTransactionProof, SelfStakingTransactionData,
+};
+use nimiq_transaction::{SignatureProof, Transaction as BlockchainTransaction};
+
+use crate::history_store::ExtTxData;
+use crate::slash_event::SlashEventKind;
+use crate::staking_diff::{StakeChange, StakingContractDiff, ValidatorEvent, ValidatorEventKind};
+use crate::{AbstractBlockchain, Blockchain, StakingDiffError};
+
+/// Implements methods to compare states of the staking contract.
+impl Blockchain {
+    /// Returns the changes of the staking contract from the state after `from_block` to the state
+    /// after `to_block`. Fails if `from_block` is after `to_block` or `to_block` is after the head
+    /// of the chain.
+    ///
+    /// The diff is replayed from the transactions and inherents in the history store, and the
+    /// parking after `from_block` from the slash log of its epoch and the previous one. Fails if
+    /// these aren't complete, which is the case for the epochs that were skipped by macro syncing.
+    /// The stakes that are retired by dropping a validator are read from the receipts, which are
+    /// only kept for the current batch.
+    pub fn get_staking_contract_diff(
+        &self,
+        from_block: u32,
+        to_block: u32,
+    ) -> Result<StakingContractDiff, StakingDiffError> {
+        if from_block > to_block || to_block > self.block_number() {
+            return Err(StakingDiffError::InvalidBlockRange(from_block, to_block));
+        }
+        let staking_contract_address = self
+            .validator_registry_address()
+            .ok_or(StakingDiffError::NoStakingContract)?
+            .clone();
+
+        let read_txn = ReadTransaction::new(&self.env);
+        let txn: &Transaction = &read_txn;
+
+        let history_start = self.chain_store.get_history_start(Some(txn));
+        let epoch = policy::epoch_at(from_block);
+        let required_start = if epoch > 1 {
+            policy::first_block_of(epoch - 1)
+        } else {
+            0
+        };
+        if required_start < history_start {
+            return Err(StakingDiffError::HistoryUnavailable(history_start));
+        }
+        let mut builder = DiffBuilder {
+            staking_contract_address,
+            parking: self.parking_after(from_block, txn),
+            validator_events: vec![],
+            stakes: BTreeMap::new(),
+            incomplete_drops: vec![],
+        };
+
+        for block_number in from_block + 1..=to_block {
+            let ext_txs = self
+                .history_store
+                .get_block_transactions(block_number, Some(txn));
+
+            // The basic transactions come first, in the same order as in the block.
+            let mut index = 0;
+            for ext_tx in ext_txs {
+                match ext_tx.data {
+                    ExtTxData::Basic(transaction) => {
+                        builder.transaction(block_number, &transaction, || {
+                            self.drop_receipt(block_number, index, txn)
+                        })?;
+                        index += 1;
+                    }
+                    ExtTxData::Inherent(inherent) => builder.inherent(block_number, &inherent)?,
+                }
+            }
+        }
+
+        // Only the validators whose state changed are needed to build the diff, not their stakes.
+        let mut keys = StakingContractKeys::default();
+        for event in &builder.validator_events {
+            keys.validators.insert(event.validator_id.clone(), false);
+        }
+        let staking_contract = self.get_partial_staking_contract(&keys);
+
+        Ok(builder.build(from_block, to_block, &staking_contract))
+    }
+
+    // Returns the receipt of the outgoing staking transaction at `index` in the block.
+    fn drop_receipt(&self, block_number: u32, index: u16, txn: &Transaction) -> Option<Vec<u8>> {
+        let receipts = self.chain_store.get_receipts(block_number, Some(txn))?;
+        receipts
+            .receipts
+            .into_iter()
+            .find_map(|receipt| match receipt {
+                Receipt::Transaction {
+                    index: receipt_index,
+                    sender: true,
+                    data,
+                } if receipt_index == index => Some(data),
+                _ => None,
+            })
+    }
+
+    // Replays the slash log to find the validators that are parked after the given block. Since
+    // parking ends with the epoch after the one the validator was parked in, only the slash log of
+    // the current and the previous epoch is needed.
+    fn parking_after(&self, block_number: u32, txn: &Transaction) -> Parking {
+        let mut parking = Parking::default();
+        let epoch = policy::epoch_at(block_number);
+
+        for epoch in epoch.saturating_sub(1).max(1)..=epoch {
+            for event in self.chain_store.get_slash_events(epoch, Some(txn)) {
+                if event.block_number > block_number {
+                    break;
+                }
+                match event.kind {
+                    SlashEventKind::ForkProof { .. }
+                    | SlashEventKind::ViewChange { .. }
+                    | SlashEventKind::Slash { .. } => {
+                        parking.park(event.validator_id);
+                    }
+                    SlashEventKind::Unpark => parking.unpark(&event.validator_id),
+                }
+            }
+
+            if policy::election_block_of(epoch) <= block_number {
+                parking.finalize_epoch();
+            }
+        }
+
+        parking
+    }
+}
+
+/// The validators that are parked in the current and in the previous epoch, like in the staking
+/// contract.
+#[derive(Default)]
+struct Parking {
+    current_epoch: HashSet<ValidatorId>,
+    previous_epoch: HashSet<ValidatorId>,
+}
+
+impl Parking {
+    fn park(&mut self, validator_id: ValidatorId) {
+        self.current_epoch.insert(validator_id);
+    }
+
+    fn unpark(&mut self, validator_id: &ValidatorId) {
+        self.current_epoch.remove(validator_id);
+        self.previous_epoch.remove(validator_id);
+    }
+
+    // Returns the validators whose parking ended, which are retired if they are still active.
+    fn finalize_epoch(&mut self) -> HashSet<ValidatorId> {
+        let current_epoch = mem::take(&mut self.current_epoch);
+        mem::replace(&mut self.previous_epoch, current_epoch)
+    }
+}
+
+struct DiffBuilder {
+    staking_contract_address: Address,
+    parking: Parking,
+    validator_events: Vec<ValidatorEvent>,
+    stakes: BTreeMap<(Address, Option<ValidatorId>), (Coin, Coin)>,
+    incomplete_drops: Vec<ValidatorId>,
+}
+
+impl DiffBuilder {
+    fn event(&mut self, block_number: u32, validator_id: ValidatorId, kind: ValidatorEventKind) {
+        self.validator_events.push(ValidatorEvent {
+            block_number,
+            validator_id,
+            kind,
+        });
+    }
+
+    fn add_stake(
+        &mut self,
+        staker_address: &Address,
+        validator_id: Option<ValidatorId>,
+        value: Coin,
+    ) {
+        self.stakes
+            .entry((staker_address.clone(), validator_id))
+            .or_insert((Coin::ZERO, Coin::ZERO))
+            .0 += value;
+    }
+
+    fn remove_stake(
+        &mut self,
+        staker_address: &Address,
+        validator_id: Option<ValidatorId>,
+        value: Coin,
+    ) {
+        self.stakes
+            .entry((staker_address.clone(), validator_id))
+            .or_insert((Coin::ZERO, Coin::ZERO))
+            .1 += value;
+    }
+
+    // Applies a transaction of the chain. All transactions in the chain were valid when they were
+    // applied, so failing to parse one means that the history store is corrupted.
+    fn transaction<F>(
+        &mut self,
+        block_number: u32,
+        transaction: &BlockchainTransaction,
+        receipt: F,
+    ) -> Result<(), StakingDiffError>
+    where
+        F: FnOnce() -> Option<Vec<u8>>,
+    {
+        let invalid = || StakingDiffError::InvalidTransaction(block_number, transaction.hash());
+        let is_sender = transaction.sender == self.staking_contract_address;
+        let is_recipient = transaction.recipient == self.staking_contract_address;
+        let total_value = transaction.value + transaction.fee;
+
+        if is_sender && is_recipient {
+            let data = SelfStakingTransactionData::parse(transaction).map_err(|_| invalid())?;
+            let staker_address = SignatureProof::deserialize_from_vec(&transaction.proof)
+                .map_err(|_| invalid())?
+                .compute_signer();
+
+            match data {
+                SelfStakingTransactionData::RetireStake(validator_id) => {
+                    self.remove_stake(&staker_address, Some(validator_id), total_value);
+                    self.add_stake(&staker_address, None, transaction.value);
+                }
+                SelfStakingTransactionData::ReactivateStake(validator_id) => {
+                    self.remove_stake(&staker_address, None, total_value);
+                    self.add_stake(&staker_address, Some(validator_id), transaction.value);
+                }
+                SelfStakingTransactionData::RededicateStake {
+                    from_validator_id,
+                    to_validator_id,
+                } => {
+                    self.remove_stake(&staker_address, Some(from_validator_id), total_value);
+                    self.add_stake(&staker_address, Some(to_validator_id), transaction.value);
+                }
+            }
+        } else if is_recipient {
+            let data = IncomingStakingTransactionData::parse(transaction).map_err(|_| invalid())?;
+
+            match data {
+                IncomingStakingTransactionData::CreateValidator { .. } => {
+                    // The validator id is derived from the hash of the creation transaction.
+                    let validator_id: ValidatorId =
+                        transaction.hash::<Blake2bHash>().as_slice()[0..20].into();
+                    self.event(
+                        block_number,
+                        validator_id,
+                        ValidatorEventKind::Created {
+                            deposit: transaction.value,
+                        },
+                    );
+                }
+                IncomingStakingTransactionData::UpdateValidator { validator_id, .. } => {
+                    self.event(block_number, validator_id, ValidatorEventKind::Updated);
+                }
+                IncomingStakingTransactionData::RetireValidator { validator_id, .. } => {
+                    self.event(
+                        block_number,
+                        validator_id,
+                        ValidatorEventKind::Retired {
+                            automatically: false,
+                        },
+                    );
+                }
+                IncomingStakingTransactionData::ReactivateValidator { validator_id, .. } => {
+                    self.event(block_number, validator_id, ValidatorEventKind::Reactivated);
+                }
+                IncomingStakingTransactionData::UnparkValidator { validator_id, .. } => {
+                    self.parking.unpark(&validator_id);
+                    self.event(block_number, validator_id, ValidatorEventKind::Unparked);
+                }
+                IncomingStakingTransactionData::Stake {
+                    validator_id,
+                    staker_address,
+                } => {
+                    let staker_address =
+                        staker_address.unwrap_or_else(|| transaction.sender.clone());
+                    self.add_stake(&staker_address, Some(validator_id), transaction.value);
+                }
+            }
+        } else if is_sender {
+            let proof =
+                OutgoingStakingTransactionProof::parse(transaction).map_err(|_| invalid())?;
+
+            match proof {
+                OutgoingStakingTransactionProof::Unstake(proof) => {
+                    self.remove_stake(&proof.compute_signer(), None, total_value);
+                }
+                OutgoingStakingTransactionProof::DropValidator { validator_id, .. } => {
+                    // Dropping a validator also unparks it.
+                    self.parking.unpark(&validator_id);
+
+                    let retired_stakes = receipt().and_then(|receipt| {
+                        StakingContract::retired_stakes_from_drop_receipt(&receipt).ok()
+                    });
+                    match retired_stakes {
+                        Some(retired_stakes) => {
+                            for (staker_address, stake) in retired_stakes {
+                                self.remove_stake(
+                                    &staker_address,
+                                    Some(validator_id.clone()),
+                                    stake,
+                                );
+                                self.add_stake(&staker_address, None, stake);
+                            }
+                        }
+                        None => self.incomplete_drops.push(validator_id.clone()),
+                    }
+
+                    self.event(
+                        block_number,
+                        validator_id,
+                        ValidatorEventKind::Dropped {
+                            deposit: total_value,
+                        },
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn inherent(&mut self, block_number: u32, inherent: &Inherent) -> Result<(), StakingDiffError> {
+        if inherent.target != self.staking_contract_address {
+            return Ok(());
+        }
+
+        match inherent.ty {
+            InherentType::Slash => {
+                let slot: SlashedSlot = Deserialize::deserialize_from_vec(&inherent.data)
+                    .map_err(|_| StakingDiffError::InvalidInherent(block_number))?;
+                self.parking.park(slot.validator_id.clone());
+                self.event(
+                    block_number,
+                    slot.validator_id,
+                    ValidatorEventKind::Slashed {
+                        slot: slot.slot,
+                        event_block: slot.event_block,
+                    },
+                );
+            }
+            InherentType::FinalizeEpoch => {
+                // Whether the validators are still active is only known once all events are
+                // collected, so `build` removes the retirements of inactive validators again.
+                let mut expired: Vec<ValidatorId> =
+                    self.parking.finalize_epoch().into_iter().collect();
+                expired.sort();
+                for validator_id in expired {
+                    self.event(
+                        block_number,
+                        validator_id,
+                        ValidatorEventKind::Retired {
+                            automatically: true,
+                        },
+                    );
+                }
+            }
+            InherentType::FinalizeBatch | InherentType::Reward => {}
+        }
+
+        Ok(())
+    }
+
+    fn build(
+        mut self,
+        from_block: u32,
+        to_block: u32,
+        staking_contract: &StakingContract,
+    ) -> StakingContractDiff {
+        // Whether a validator was active after `from_block`. This is derived from its first
+        // change of state, or from the current state of the contract if that doesn't tell.
+        let mut active: HashMap<ValidatorId, bool> = HashMap::new();
+        for event in &self.validator_events {
+            if active.contains_key(&event.validator_id) {
+                continue;
+            }
+            let was_active = match event.kind {
+                ValidatorEventKind::Retired {
+                    automatically: false,
+                } => true,
+                ValidatorEventKind::Created { .. } | ValidatorEventKind::Dropped { .. } => false,
+                ValidatorEventKind::Reactivated
+                | ValidatorEventKind::Retired {
+                    automatically: true,
+                } => Self::was_active(staking_contract, &event.validator_id, from_block),
+                _ => continue,
+            };
+            active.insert(event.validator_id.clone(), was_active);
+        }
+
+        // Remove the automatic retirements of validators that weren't active anymore.
+        let mut validator_events = Vec::with_capacity(self.validator_events.len());
+        for event in self.validator_events {
+            let is_active = active.get(&event.validator_id).copied().unwrap_or_default();
+            let now_active = match event.kind {
+                ValidatorEventKind::Created { .. } | ValidatorEventKind::Reactivated => true,
+                ValidatorEventKind::Retired { automatically } => {
+                    if automatically && !is_active {
+                        continue;
+                    }
+                    false
+                }
+                ValidatorEventKind::Dropped { .. } => false,
+                _ => is_active,
+            };
+            active.insert(event.validator_id.clone(), now_active);
+            validator_events.push(event);
+        }
+
+        let created: HashSet<&ValidatorId> = validator_events
+            .iter()
+            .filter(|event| matches!(event.kind, ValidatorEventKind::Created { .. }))
+            .map(|event| &event.validator_id)
+            .collect();
+        let dropped: HashSet<&ValidatorId> = validator_events
+            .iter()
+            .filter(|event| matches!(event.kind, ValidatorEventKind::Dropped { .. }))
+            .map(|event| &event.validator_id)
+            .collect();
+        let mut added_validators: Vec<ValidatorId> = created
+            .difference(&dropped)
+            .map(|&validator_id| validator_id.clone())
+            .collect();
+        let mut removed_validators: Vec<ValidatorId> = dropped
+            .difference(&created)
+            .map(|&validator_id| validator_id.clone())
+            .collect();
+        added_validators.sort();
+        removed_validators.sort();
+
+        let stake_changes = self
+            .stakes
+            .into_iter()
+            .map(
+                |((staker_address, validator_id), (added, removed))| StakeChange {
+                    staker_address,
+                    validator_id,
+                    added,
+                    removed,
+                },
+            )
+            .collect();
+
+        self.incomplete_drops.sort();
+        self.incomplete_drops.dedup();
+
+        StakingContractDiff {
+            from_block,
+            to_block,
+            added_validators,
+            removed_validators,
+            validator_events,
+            stake_changes,
+            incomplete_drops: self.incomplete_drops,
+        }
+    }
+
+    // Returns whether the validator was active after the given block, according to the current
+    // state of the contract.
+    fn was_active(
+        staking_contract: &StakingContract,
+        validator_id: &ValidatorId,
+        block_number: u32,
+    ) -> bool {
+        if staking_contract
+            .active_validators_by_id
+            .contains_key(validator_id)
+        {
+            return true;
+        }
+        staking_contract
+            .inactive_validators_by_id
+            .get(validator_id)
+            .map_or(false, |validator| validator.retire_time > block_number)
+    }
+}
@@ -1,19 +1,24 @@
 use parking_lot::{MutexGuard, RwLockReadGuard};
 
+use nimiq_account::staking_contract::{StakingContractEntry, StakingContractKeys};
 use nimiq_account::{Account, StakingContract};
 use nimiq_block_albatross::Block;
-use nimiq_database::{Transaction, WriteTransaction};
+use nimiq_database::{ReadTransaction, Transaction, WriteTransaction};
 use nimiq_genesis::NetworkInfo;
-use nimiq_hash::Blake2bHash;
+use nimiq_hash::{Blake2bHash, Hash};
 use nimiq_keys::Address;
+use nimiq_primitives::account::ValidatorId;
 use nimiq_primitives::policy;
 use nimiq_transaction::{Transaction as BlockchainTransaction, TransactionReceipt};
+use nimiq_tree_primitives::accounts_proof::AccountsProof;
 use nimiq_utils::observer::{Listener, ListenerHandle};
 
 use crate::blockchain_state::BlockchainState;
 #[cfg(feature = "metrics")]
 use crate::chain_metrics::BlockchainMetrics;
-use crate::history_store::{ExtTxData, HistoryTreeChunk};
+use crate::history_store::{ExtTxData, HistoryTreeChunk, HistoryTreeProof};
+use crate::reward::BatchRewardReport;
+use crate::slash_event::SlashEvent;
 use crate::{Blockchain, BlockchainEvent, Direction};
 
 /// Implements several wrapper functions.
@@ -189,7 +194,12 @@ impl Blockchain {
             .validator_registry_address()
             .expect("No ValidatorRegistry");
 
-        let account = self.state.read().accounts.get(validator_registry, None);
+        let account = self
+            .state
+            .read()
+            .accounts
+            .get(validator_registry, None)
+            .expect("Corrupted store: Invalid staking contract entries");
 
         if let Account::Staking(x) = account {
             x
@@ -198,6 +208,68 @@ impl Blockchain {
         }
     }
 
+    /// Returns the staking contract with only the given validator, if it exists, and none of the
+    /// stakes. This is much cheaper than `get_staking_contract` if only the state of a single
+    /// validator is needed.
+    pub fn get_staking_contract_with_validator(
+        &self,
+        validator_id: &ValidatorId,
+    ) -> StakingContract {
+        let mut keys = StakingContractKeys::default();
+        keys.validators.insert(validator_id.clone(), false);
+        self.get_partial_staking_contract(&keys)
+    }
+
+    /// Returns the staking contract with only the entries in `keys`. Without any keys, only the
+    /// global state of the contract is loaded, e.g. its parking and slashed slots.
+    pub fn get_partial_staking_contract(&self, keys: &StakingContractKeys) -> StakingContract {
+        let validator_registry = NetworkInfo::from_network_id(self.network_id)
+            .validator_registry_address()
+            .expect("No ValidatorRegistry");
+
+        self.state
+            .read()
+            .accounts
+            .get_partial_staking_contract(validator_registry, keys, None)
+            .expect("Corrupted store: Invalid staking contract entries")
+            .expect("No staking contract")
+    }
+
+    /// Returns the entries of the staking contract whose keys start with `prefix`, e.g.
+    /// `StakingContract::VALIDATOR_PREFIX` for all validators without their stakes.
+    pub fn get_staking_entries_with_prefix(&self, prefix: &[u8]) -> Vec<StakingContractEntry> {
+        let validator_registry = NetworkInfo::from_network_id(self.network_id)
+            .validator_registry_address()
+            .expect("No ValidatorRegistry");
+
+        self.state
+            .read()
+            .accounts
+            .get_staking_entries_with_prefix(validator_registry, prefix, None)
+            .expect("Corrupted store: Missing accounts tree node")
+    }
+
+    /// Returns the report of the rewards that were distributed for the given batch, if the batch
+    /// has been finalized. The reports are only created when macro blocks are pushed one by one,
+    /// so there are none for the batches that were history or macro synced.
+    pub fn get_reward_report(&self, batch_number: u32) -> Option<BatchRewardReport> {
+        self.chain_store.get_reward_report(batch_number, None)
+    }
+
+    /// Returns up to `max` slash log events of the given epoch (or of all epochs), optionally only
+    /// those of the given validator, in chronological order. To get the next page, pass the last
+    /// event of the previous one as `start_after`.
+    pub fn get_slash_events(
+        &self,
+        epoch: Option<u32>,
+        validator_id: Option<&ValidatorId>,
+        max: usize,
+        start_after: Option<&SlashEvent>,
+    ) -> Vec<SlashEvent> {
+        self.chain_store
+            .get_slash_events_page(epoch, validator_id, max, start_after, None)
+    }
+
     pub fn write_transaction(&self) -> WriteTransaction {
         WriteTransaction::new(&self.env)
     }
@@ -210,7 +282,11 @@ impl Blockchain {
     }
 
     pub fn get_account(&self, address: &Address) -> Account {
-        self.state.read().accounts.get(address, None)
+        self.state
+            .read()
+            .accounts
+            .get(address, None)
+            .expect("Corrupted store: Invalid staking contract entries")
     }
 
     pub fn contains_tx_in_validity_window(&self, tx_hash: &Blake2bHash) -> bool {
@@ -230,14 +306,125 @@ impl Blockchain {
         &self.metrics
     }
 
-    // TODO: Implement this method. It is used in the rpc-server.
-    #[allow(unused_variables)]
+    /// Returns the receipts of the most recent basic transactions that were sent from (up to
+    /// `sender_limit`) and to (up to `recipient_limit`) the given address, newest first.
     pub fn get_transaction_receipts_by_address(
         &self,
         address: &Address,
         sender_limit: usize,
         recipient_limit: usize,
     ) -> Vec<TransactionReceipt> {
-        unimplemented!()
+        // The number of extended transactions that we fetch from the history store at once.
+        const BATCH_SIZE: usize = 100;
+
+        let mut receipts = vec![];
+        let mut num_sent = 0;
+        let mut num_received = 0;
+        let mut start_after = None;
+
+        while num_sent < sender_limit || num_received < recipient_limit {
+            let ext_txs = self.history_store.get_ext_tx_by_address(
+                address,
+                BATCH_SIZE,
+                start_after.as_ref(),
+                None,
+            );
+            let is_last_batch = ext_txs.len() < BATCH_SIZE;
+
+            for (position, ext_tx) in ext_txs {
+                start_after = Some(position);
+
+                let tx = match &ext_tx.data {
+                    ExtTxData::Basic(tx) => tx,
+                    ExtTxData::Inherent(_) => continue,
+                };
+
+                let is_sent = &tx.sender == address && num_sent < sender_limit;
+                let is_received = &tx.recipient == address && num_received < recipient_limit;
+                if !is_sent && !is_received {
+                    continue;
+                }
+
+                // We might not have the block if we synced this epoch without micro blocks.
+                let block_hash =
+                    match self
+                        .chain_store
+                        .get_block_at(ext_tx.block_number, false, None)
+                    {
+                        Some(block) => block.hash(),
+                        None => continue,
+                    };
+
+                if is_sent {
+                    num_sent += 1;
+                }
+                if is_received {
+                    num_received += 1;
+                }
+
+                receipts.push(TransactionReceipt {
+                    transaction_hash: tx.hash(),
+                    block_hash,
+                    block_height: ext_tx.block_number,
+                });
+            }
+
+            if is_last_batch {
+                break;
+            }
+        }
+
+        receipts
+    }
+
+    /// Returns a proof for the accounts with the given addresses at our head, which must have the
+    /// given hash. This allows nano nodes to verify accounts against the state root of the head.
+    pub fn get_accounts_proof(
+        &self,
+        block_hash: &Blake2bHash,
+        addresses: &[Address],
+    ) -> Option<AccountsProof<Account>> {
+        let state = self.state.read();
+
+        if &state.head_hash != block_hash {
+            return None;
+        }
+
+        let txn = ReadTransaction::new(&self.env);
+        Some(state.accounts.get_accounts_proof(&txn, addresses))
+    }
+
+    /// Returns a proof for the (up to `max`) most recent basic transactions of the given address
+    /// in the current epoch, together with the hash of our head. The proof can be verified against
+    /// the history root of the head. The proof is `None` if there are no such transactions.
+    pub fn prove_transactions_by_address(
+        &self,
+        address: &Address,
+        max: usize,
+    ) -> (Blake2bHash, Option<HistoryTreeProof>) {
+        let state = self.state.read();
+
+        let head_hash = state.head_hash.clone();
+        let epoch_number = policy::epoch_at(state.main_chain.head.block_number());
+
+        let txn = ReadTransaction::new(&self.env);
+
+        let hashes: Vec<Blake2bHash> = self
+            .history_store
+            .get_ext_tx_by_address(address, max, None, Some(&txn))
+            .into_iter()
+            .filter(|(position, _)| policy::epoch_at(position.block_number) == epoch_number)
+            .map(|(_, ext_tx)| ext_tx.tx_hash())
+            .collect();
+
+        if hashes.is_empty() {
+            return (head_hash, None);
+        }
+
+        let proof = self
+            .history_store
+            .prove(epoch_number, hashes.iter().collect(), Some(&txn));
+
+        (head_hash, proof)
     }
 }
@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 use nimiq_accounts::Accounts;
 use nimiq_block_albatross::MacroBlock;
 use nimiq_hash::Blake2bHash;
@@ -9,8 +11,8 @@ use crate::transaction_cache::TransactionCache;
 /// A struct that keeps the current state of the blockchain. It summarizes the information known to
 /// a validator at the head of the blockchain.
 pub struct BlockchainState {
-    // The accounts tree.
-    pub accounts: Accounts,
+    // The accounts tree. It is shared, so that it can be read without holding the state lock.
+    pub accounts: Arc<Accounts>,
     // The cache of transactions.
     pub transaction_cache: TransactionCache,
     // The chain info for the head of the main chain.
@@ -2,12 +2,15 @@ use nimiq_account::Receipts;
 use nimiq_block_albatross::Block;
 use nimiq_database::cursor::{ReadCursor, WriteCursor};
 use nimiq_database::{
-    Database, DatabaseFlags, Environment, ReadTransaction, Transaction, WriteTransaction,
+    Cursor, Database, DatabaseFlags, Environment, ReadTransaction, Transaction, WriteTransaction,
 };
 use nimiq_hash::Blake2bHash;
+use nimiq_primitives::account::ValidatorId;
 use nimiq_primitives::policy;
 
 use crate::chain_info::ChainInfo;
+use crate::reward::BatchRewardReport;
+use crate::slash_event::SlashEvent;
 use crate::Direction;
 
 #[derive(Debug)]
@@ -21,6 +24,10 @@ pub struct ChainStore {
     height_idx: Database,
     // A database of the transaction receipts for a block, by their corresponding block hashes.
     receipt_db: Database,
+    // A database of the reward reports for a batch, by their corresponding batch numbers.
+    reward_report_db: Database,
+    // A database of the slash events of an epoch, by their corresponding epoch numbers.
+    slash_event_db: Database,
 }
 
 impl ChainStore {
@@ -28,8 +35,11 @@ impl ChainStore {
     const BLOCK_DB_NAME: &'static str = "Block";
     const HEIGHT_IDX_NAME: &'static str = "HeightIndex";
     const RECEIPT_DB_NAME: &'static str = "Receipts";
+    const REWARD_REPORT_DB_NAME: &'static str = "RewardReports";
+    const SLASH_EVENT_DB_NAME: &'static str = "SlashEvents";
 
     const HEAD_KEY: &'static str = "head";
+    const HISTORY_START_KEY: &'static str = "historyStart";
 
     pub fn new(env: Environment) -> Self {
         let chain_db = env.open_database(Self::CHAIN_DB_NAME.to_string());
@@ -40,12 +50,22 @@ impl ChainStore {
         );
         let receipt_db = env
             .open_database_with_flags(Self::RECEIPT_DB_NAME.to_string(), DatabaseFlags::UINT_KEYS);
+        let reward_report_db = env.open_database_with_flags(
+            Self::REWARD_REPORT_DB_NAME.to_string(),
+            DatabaseFlags::UINT_KEYS,
+        );
+        let slash_event_db = env.open_database_with_flags(
+            Self::SLASH_EVENT_DB_NAME.to_string(),
+            DatabaseFlags::UINT_KEYS | DatabaseFlags::DUPLICATE_KEYS,
+        );
         ChainStore {
             env,
             chain_db,
             block_db,
             height_idx,
             receipt_db,
+            reward_report_db,
+            slash_event_db,
         }
     }
 
@@ -60,6 +80,22 @@ impl ChainStore {
         txn.put(&self.chain_db, ChainStore::HEAD_KEY, hash);
     }
 
+    /// Returns the number of the first block from which on the history and the slash log are
+    /// complete. This is the genesis block unless the node macro synced past some epochs.
+    pub fn get_history_start(&self, txn_option: Option<&Transaction>) -> u32 {
+        let history_start = match txn_option {
+            Some(txn) => txn.get(&self.chain_db, ChainStore::HISTORY_START_KEY),
+            None => {
+                ReadTransaction::new(&self.env).get(&self.chain_db, ChainStore::HISTORY_START_KEY)
+            }
+        };
+        history_start.unwrap_or(0)
+    }
+
+    pub fn set_history_start(&self, txn: &mut WriteTransaction, block_number: u32) {
+        txn.put(&self.chain_db, ChainStore::HISTORY_START_KEY, &block_number);
+    }
+
     pub fn get_chain_info(
         &self,
         hash: &Blake2bHash,
@@ -492,4 +528,147 @@ impl ChainStore {
             pos = cursor.next();
         }
     }
+
+    pub fn put_reward_report(&self, txn: &mut WriteTransaction, report: &BatchRewardReport) {
+        txn.put_reserve(&self.reward_report_db, &report.batch_number, report);
+    }
+
+    pub fn get_reward_report(
+        &self,
+        batch_number: u32,
+        txn_option: Option<&Transaction>,
+    ) -> Option<BatchRewardReport> {
+        let read_txn: ReadTransaction;
+        let txn = match txn_option {
+            Some(txn) => txn,
+            None => {
+                read_txn = ReadTransaction::new(&self.env);
+                &read_txn
+            }
+        };
+
+        txn.get(&self.reward_report_db, &batch_number)
+    }
+
+    pub fn put_slash_events(&self, txn: &mut WriteTransaction, epoch: u32, events: &[SlashEvent]) {
+        for event in events {
+            txn.put(&self.slash_event_db, &epoch, event);
+        }
+    }
+
+    pub fn remove_slash_events(
+        &self,
+        txn: &mut WriteTransaction,
+        epoch: u32,
+        events: &[SlashEvent],
+    ) {
+        for event in events {
+            txn.remove_item(&self.slash_event_db, &epoch, event);
+        }
+    }
+
+    /// Returns the slash events of the given epoch in chronological order.
+    pub fn get_slash_events(
+        &self,
+        epoch: u32,
+        txn_option: Option<&Transaction>,
+    ) -> Vec<SlashEvent> {
+        let read_txn: ReadTransaction;
+        let txn = match txn_option {
+            Some(txn) => txn,
+            None => {
+                read_txn = ReadTransaction::new(&self.env);
+                &read_txn
+            }
+        };
+
+        let mut cursor = txn.cursor(&self.slash_event_db);
+        let mut events = vec![];
+
+        let mut event = cursor.seek_key::<u32, SlashEvent>(&epoch);
+        while let Some(slash_event) = event {
+            events.push(slash_event);
+            event = cursor.next_duplicate::<u32, SlashEvent>().map(|(_, e)| e);
+        }
+
+        events
+    }
+
+    /// Returns up to `max` slash events in chronological order, starting after the given event.
+    /// If an epoch is given, only the events of that epoch are returned. If a validator is given,
+    /// only its events are returned.
+    pub fn get_slash_events_page(
+        &self,
+        epoch: Option<u32>,
+        validator_id: Option<&ValidatorId>,
+        max: usize,
+        start_after: Option<&SlashEvent>,
+        txn_option: Option<&Transaction>,
+    ) -> Vec<SlashEvent> {
+        let read_txn: ReadTransaction;
+        let txn = match txn_option {
+            Some(txn) => txn,
+            None => {
+                read_txn = ReadTransaction::new(&self.env);
+                &read_txn
+            }
+        };
+
+        let mut events = vec![];
+
+        if max == 0 {
+            return events;
+        }
+
+        let mut cursor = txn.cursor(&self.slash_event_db);
+
+        // Moves to the next event, staying within the epoch if one is given.
+        let next_event = |cursor: &mut Cursor| match epoch {
+            Some(_) => cursor
+                .next_duplicate::<u32, SlashEvent>()
+                .map(|(_, event)| event),
+            None => cursor.next::<u32, SlashEvent>().map(|(_, event)| event),
+        };
+
+        // Seek to the oldest event that we want to return.
+        let mut event = match start_after {
+            None => match epoch {
+                Some(epoch) => cursor.seek_key::<u32, SlashEvent>(&epoch),
+                None => cursor.first::<u32, SlashEvent>().map(|(_, event)| event),
+            },
+            Some(start_after) => {
+                let start_epoch = policy::epoch_at(start_after.block_number);
+
+                if epoch.map_or(false, |epoch| epoch != start_epoch) {
+                    return events;
+                }
+
+                // The event itself might not exist anymore (e.g. after a rebranch), so we seek to
+                // the oldest event that is equal to or newer than it.
+                match cursor.seek_key_nearest_value(&start_epoch, start_after) {
+                    Some(event) if &event == start_after => next_event(&mut cursor),
+                    Some(event) => Some(event),
+                    // All events of the epoch are older than `start_after`.
+                    None if epoch.is_some() => None,
+                    None => cursor
+                        .seek_range_key::<u32, SlashEvent>(&(start_epoch + 1))
+                        .map(|(_, event)| event),
+                }
+            }
+        };
+
+        while let Some(current) = event {
+            if validator_id.map_or(true, |validator_id| &current.validator_id == validator_id) {
+                events.push(current);
+
+                if events.len() >= max {
+                    break;
+                }
+            }
+
+            event = next_event(&mut cursor);
+        }
+
+        events
+    }
 }
@@ -75,6 +75,23 @@ pub enum PushError {
     InvalidFork,
     #[error("Blockchain error: {0}")]
     BlockchainError(#[from] BlockchainError),
+    #[error("Slash inherents don't match the fork proofs and view changes")]
+    InvalidSlashInherents,
+}
+
+/// An enum representing the errors when computing the changes of the staking contract.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum StakingDiffError {
+    #[error("Invalid block range: {0} to {1}")]
+    InvalidBlockRange(u32, u32),
+    #[error("No staking contract on this network")]
+    NoStakingContract,
+    #[error("Invalid staking transaction {1} in block #{0}")]
+    InvalidTransaction(u32, Blake2bHash),
+    #[error("Invalid slash inherent in block #{0}")]
+    InvalidInherent(u32),
+    #[error("History before block #{0} is not available")]
+    HistoryUnavailable(u32),
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd)]
@@ -0,0 +1,49 @@
+use std::borrow::Cow;
+use std::io;
+
+use beserial::{Deserialize, Serialize};
+use nimiq_database::{AsDatabaseBytes, FromDatabaseValue, IntoDatabaseValue};
+use nimiq_hash::Blake2bHash;
+
+/// The position of an extended transaction in the history of an address. It is stored as a value in
+/// the address index of the HistoryStore.
+/// Since the block number and the leaf index are serialized in big endian first, the database orders
+/// the positions of an address chronologically, and by their order in the block within a block. This
+/// allows us to iterate over the history of an address and to resume the iteration at a given
+/// position.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AddressHistoryPosition {
+    // The number of the block when the transaction happened.
+    pub block_number: u32,
+    // The index of the extended transaction in the history tree of its epoch. Within a block, it
+    // follows the order of the transactions in the block.
+    pub leaf_index: u32,
+    // The leaf hash of the extended transaction in the history tree.
+    pub leaf_hash: Blake2bHash,
+}
+
+impl IntoDatabaseValue for AddressHistoryPosition {
+    fn database_byte_size(&self) -> usize {
+        self.serialized_size()
+    }
+
+    fn copy_into_database(&self, mut bytes: &mut [u8]) {
+        Serialize::serialize(&self, &mut bytes).unwrap();
+    }
+}
+
+impl FromDatabaseValue for AddressHistoryPosition {
+    fn copy_from_database(bytes: &[u8]) -> io::Result<Self>
+    where
+        Self: Sized,
+    {
+        let mut cursor = io::Cursor::new(bytes);
+        Ok(Deserialize::deserialize(&mut cursor)?)
+    }
+}
+
+impl AsDatabaseBytes for AddressHistoryPosition {
+    fn as_database_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(self.serialize_to_vec())
+    }
+}
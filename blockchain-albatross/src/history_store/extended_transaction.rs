@@ -6,6 +6,7 @@ use beserial::{Deserialize, ReadBytesExt, Serialize, SerializingError, WriteByte
 use nimiq_account::Inherent;
 use nimiq_database::{FromDatabaseValue, IntoDatabaseValue};
 use nimiq_hash::{Blake2bHash, Hash};
+use nimiq_keys::Address;
 use nimiq_transaction::Transaction as BlockchainTransaction;
 
 use crate::history_store::HistoryTreeHash;
@@ -94,6 +95,17 @@ impl ExtendedTransaction {
         }
     }
 
+    /// Returns the addresses whose transaction history contains this extended transaction. For
+    /// basic transactions these are the sender and the recipient. Inherents aren't part of the
+    /// transaction history of any address.
+    pub fn addresses(&self) -> Vec<&Address> {
+        match &self.data {
+            ExtTxData::Basic(tx) if tx.sender == tx.recipient => vec![&tx.sender],
+            ExtTxData::Basic(tx) => vec![&tx.sender, &tx.recipient],
+            ExtTxData::Inherent(_) => vec![],
+        }
+    }
+
     /// Returns the hash of the underlying transaction.
     pub fn tx_hash(&self) -> Blake2bHash {
         match &self.data {
@@ -7,12 +7,16 @@ use merkle_mountain_range::mmr::proof::RangeProof;
 use merkle_mountain_range::mmr::MerkleMountainRange;
 use merkle_mountain_range::store::memory::MemoryStore;
 
-use nimiq_database::{Database, Environment, ReadTransaction, Transaction, WriteTransaction};
+use nimiq_database::{
+    Database, DatabaseFlags, Environment, ReadTransaction, Transaction, WriteTransaction,
+};
 use nimiq_hash::Blake2bHash;
+use nimiq_keys::Address;
 
 use crate::history_store::mmr_store::MMRStore;
 use crate::history_store::{
-    ExtendedTransaction, HistoryTreeChunk, HistoryTreeHash, HistoryTreeProof,
+    AddressHistoryPosition, ExtendedTransaction, HistoryTreeChunk, HistoryTreeHash,
+    HistoryTreeProof,
 };
 use nimiq_database::cursor::ReadCursor;
 
@@ -37,6 +41,12 @@ pub struct HistoryStore {
     leaf_idx_db: Database,
     // A database of all leaf hashes indexed by the block number where the transaction appears.
     block_db: Database,
+    // A database of the positions (block number, leaf index and leaf hash) of all basic
+    // transactions indexed by their sender and recipient. This way we can get the history of an
+    // address without scanning whole epochs.
+    // The index is only filled for transactions that are added to the history, so databases that
+    // were created before it existed need to be resynced to get the history of addresses.
+    address_db: Database,
 }
 
 impl HistoryStore {
@@ -45,6 +55,7 @@ impl HistoryStore {
     const LEAF_HASH_DB_NAME: &'static str = "LeafHashesByHash";
     const LEAF_IDX_DB_NAME: &'static str = "LeafIndexesByHash";
     const BLOCK_DB_NAME: &'static str = "LeafHashesByBlock";
+    const ADDRESS_DB_NAME: &'static str = "LeafHashesByAddress";
 
     /// Creates a new HistoryStore.
     pub fn new(env: Environment) -> Self {
@@ -53,6 +64,10 @@ impl HistoryStore {
         let leaf_hash_db = env.open_database(Self::LEAF_HASH_DB_NAME.to_string());
         let leaf_idx_db = env.open_database(Self::LEAF_IDX_DB_NAME.to_string());
         let block_db = env.open_database(Self::BLOCK_DB_NAME.to_string());
+        let address_db = env.open_database_with_flags(
+            Self::ADDRESS_DB_NAME.to_string(),
+            DatabaseFlags::DUPLICATE_KEYS | DatabaseFlags::DUP_FIXED_SIZE_VALUES,
+        );
 
         HistoryStore {
             env,
@@ -61,6 +76,7 @@ impl HistoryStore {
             leaf_hash_db,
             leaf_idx_db,
             block_db,
+            address_db,
         }
     }
 
@@ -255,6 +271,79 @@ impl HistoryStore {
         ext_txs
     }
 
+    /// Gets up to `max` basic transactions that were sent from or to the given address, newest first.
+    /// Transactions of the same block are returned in the reverse order of the block.
+    /// If `start_after` is given, the iteration starts with the first extended transaction that is
+    /// older than that position. Each extended transaction is returned together with its position,
+    /// which can be used to continue the iteration.
+    pub fn get_ext_tx_by_address(
+        &self,
+        address: &Address,
+        max: usize,
+        start_after: Option<&AddressHistoryPosition>,
+        txn_option: Option<&Transaction>,
+    ) -> Vec<(AddressHistoryPosition, ExtendedTransaction)> {
+        let read_txn: ReadTransaction;
+        let txn = match txn_option {
+            Some(txn) => txn,
+            None => {
+                read_txn = ReadTransaction::new(&self.env);
+                &read_txn
+            }
+        };
+
+        let mut ext_txs = vec![];
+
+        if max == 0 {
+            return ext_txs;
+        }
+
+        let mut cursor = txn.cursor(&self.address_db);
+
+        // Seek to the newest position that we want to return.
+        let mut position = match start_after {
+            None => cursor
+                .seek_key::<Address, AddressHistoryPosition>(address)
+                .and_then(|_| cursor.last_duplicate::<AddressHistoryPosition>()),
+            Some(start_after) => {
+                // The position itself might not exist anymore (e.g. after a rebranch), so we seek
+                // to the oldest position that is equal to or newer than it and go back from there.
+                match cursor.seek_key_nearest_value(address, start_after) {
+                    Some(_) => cursor
+                        .prev_duplicate::<Address, AddressHistoryPosition>()
+                        .map(|(_, position)| position),
+                    // All positions are older than `start_after`.
+                    None => cursor
+                        .seek_key::<Address, AddressHistoryPosition>(address)
+                        .and_then(|_| cursor.last_duplicate::<AddressHistoryPosition>()),
+                }
+            }
+        };
+
+        while let Some(current) = position {
+            // Skip positions whose transaction is missing from the history instead of failing
+            // the whole query.
+            match self.get_extended_tx(&current.leaf_hash, Some(txn)) {
+                Some(ext_tx) => ext_txs.push((current, ext_tx)),
+                None => warn!(
+                    "Extended transaction {} of address {} is missing from the history",
+                    current.leaf_hash, address
+                ),
+            }
+
+            if ext_txs.len() >= max {
+                break;
+            }
+
+            // Get previous position.
+            position = cursor
+                .prev_duplicate::<Address, AddressHistoryPosition>()
+                .map(|(_, position)| position);
+        }
+
+        ext_txs
+    }
+
     /// Gets all extended transactions for a given epoch.
     pub fn get_epoch_transactions(
         &self,
@@ -510,6 +599,16 @@ impl HistoryStore {
         txn.put(&self.leaf_hash_db, &tx_hash, leaf_hash);
         txn.put(&self.leaf_idx_db, &tx_hash, &leaf_index);
         txn.put(&self.block_db, &block_number, leaf_hash);
+
+        let position = AddressHistoryPosition {
+            block_number,
+            leaf_index,
+            leaf_hash: leaf_hash.clone(),
+        };
+
+        for address in ext_tx.addresses() {
+            txn.put(&self.address_db, address, &position);
+        }
     }
 
     /// Removes a extended transaction from the extended transaction database.
@@ -535,6 +634,16 @@ impl HistoryStore {
         txn.remove_item(&self.leaf_idx_db, &tx_hash, &leaf_index);
         txn.remove_item(&self.block_db, &block_number, leaf_hash);
 
+        let position = AddressHistoryPosition {
+            block_number,
+            leaf_index,
+            leaf_hash: leaf_hash.clone(),
+        };
+
+        for address in ext_tx.addresses() {
+            txn.remove_item(&self.address_db, address, &position);
+        }
+
         txn.remove(&self.ext_tx_db, leaf_hash);
     }
 
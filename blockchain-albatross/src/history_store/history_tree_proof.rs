@@ -1,3 +1,5 @@
+use std::fmt::{self, Debug, Formatter};
+
 use merkle_mountain_range::mmr::proof::Proof;
 
 use beserial::{
@@ -16,6 +18,15 @@ pub struct HistoryTreeProof {
     pub history: Vec<ExtendedTransaction>,
 }
 
+impl Debug for HistoryTreeProof {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_struct("HistoryTreeProof")
+            .field("positions", &self.positions)
+            .field("history", &self.history)
+            .finish()
+    }
+}
+
 impl HistoryTreeProof {
     /// Verifies the Merkle proof. It will return None if the verification encounters an error.
     pub fn verify(&self, expected_root: Blake2bHash) -> Option<bool> {
@@ -1,9 +1,11 @@
+pub use address_history_position::AddressHistoryPosition;
 pub use extended_transaction::*;
 pub use history_store::HistoryStore;
 pub use history_tree_chunk::{HistoryTreeChunk, CHUNK_SIZE};
 pub use history_tree_hash::HistoryTreeHash;
 pub use history_tree_proof::HistoryTreeProof;
 
+mod address_history_position;
 mod extended_transaction;
 mod history_store;
 mod history_tree_chunk;
@@ -5,6 +5,10 @@ extern crate log;
 
 pub use abstract_blockchain::AbstractBlockchain;
 pub use blockchain::blockchain::Blockchain;
+pub use blockchain::macro_sync::{
+    accounts_chunk_part_start, next_accounts_chunk_prefix, ACCOUNTS_CHUNK_PARTS,
+    ACCOUNTS_CHUNK_SIZE,
+};
 pub use chain_info::ChainInfo;
 pub use chain_ordering::ChainOrdering;
 pub use error::*;
@@ -21,4 +25,6 @@ pub(crate) mod chain_store;
 pub(crate) mod error;
 pub(crate) mod history_store;
 pub mod reward;
+pub mod slash_event;
+pub mod staking_diff;
 pub mod transaction_cache;
@@ -1,9 +1,97 @@
 use std::convert::TryInto;
+use std::io;
 
+use beserial::{Deserialize, Serialize};
 use nimiq_block_albatross::MacroHeader;
+use nimiq_database::{FromDatabaseValue, IntoDatabaseValue};
+use nimiq_keys::Address;
+use nimiq_primitives::account::ValidatorId;
 use nimiq_primitives::coin::Coin;
 use nimiq_primitives::policy;
 
+/// A report of the rewards for a batch. It records how the reward inherents of the macro block that
+/// finalized the batch were computed, so that validators can audit their earnings and penalties.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct BatchRewardReport {
+    /// The batch that the rewards are for.
+    pub batch_number: u32,
+    /// The macro block that distributed the rewards.
+    pub block_number: u32,
+    /// The newly minted coins for the batch.
+    pub block_reward: Coin,
+    /// The transaction fees of the batch.
+    pub transaction_fees: Coin,
+    /// The reward of a single slot, i.e. the block reward and transaction fees divided by the
+    /// number of slots.
+    pub slot_reward: Coin,
+    /// The part of the reward that can't be divided between the slots. It is given to a random
+    /// validator.
+    pub remainder: Coin,
+    /// The reward that was burned, either because slots lost their reward or because a reward
+    /// address didn't accept the reward.
+    pub burned_reward: Coin,
+    /// The rewards of the validators, in the order of their slots.
+    #[beserial(len_type(u16))]
+    pub validators: Vec<ValidatorReward>,
+}
+
+/// The reward of a single validator in a `BatchRewardReport`.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ValidatorReward {
+    pub validator_id: ValidatorId,
+    pub reward_address: Address,
+    /// The first slot of the validator. The validator has the slots
+    /// `first_slot..first_slot + num_slots`.
+    pub first_slot: u16,
+    pub num_slots: u16,
+    /// The slots that lost their reward because the validator was slashed during the batch.
+    #[beserial(len_type(u16))]
+    pub lost_reward_slots: Vec<u16>,
+    /// The slots that lost their reward because they were disabled at the end of the batch.
+    #[beserial(len_type(u16))]
+    pub disabled_slots: Vec<u16>,
+    /// The number of slots that received a reward.
+    pub num_eligible_slots: u16,
+    /// The reward paid to the reward address, including the remainder if the validator got it.
+    /// It is zero if the reward was burned.
+    pub reward: Coin,
+    /// Whether the reward address accepted the reward. If it didn't, the reward was burned.
+    pub accepted: bool,
+    /// The shares of the stakers, if the validator charges a commission.
+    #[beserial(len_type(u32))]
+    pub staker_rewards: Vec<StakerReward>,
+}
+
+/// The share of a staker in the reward of a validator that charges a commission.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct StakerReward {
+    pub staker_address: Address,
+    /// The reward paid to the staker. It is zero if the reward was burned.
+    pub reward: Coin,
+    /// Whether the staker address accepted the reward. If it didn't, the reward was burned.
+    pub accepted: bool,
+}
+
+impl IntoDatabaseValue for BatchRewardReport {
+    fn database_byte_size(&self) -> usize {
+        self.serialized_size()
+    }
+
+    fn copy_into_database(&self, mut bytes: &mut [u8]) {
+        Serialize::serialize(&self, &mut bytes).unwrap();
+    }
+}
+
+impl FromDatabaseValue for BatchRewardReport {
+    fn copy_from_database(bytes: &[u8]) -> io::Result<Self>
+    where
+        Self: Sized,
+    {
+        let mut cursor = io::Cursor::new(bytes);
+        Ok(Deserialize::deserialize(&mut cursor)?)
+    }
+}
+
 /// Parses the genesis supply and timestamp from the genesis block. We require both values to
 /// calculate the block rewards.
 pub fn genesis_parameters(genesis_block: &MacroHeader) -> (Coin, u64) {
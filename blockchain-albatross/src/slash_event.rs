@@ -0,0 +1,179 @@
+use std::borrow::Cow;
+use std::io;
+
+use beserial::{Deserialize, ReadBytesExt, Serialize, SerializingError, WriteBytesExt};
+use nimiq_database::{AsDatabaseBytes, FromDatabaseValue, IntoDatabaseValue};
+use nimiq_primitives::account::ValidatorId;
+
+/// An event in the slash log. It records that a validator was slashed (and thereby parked) or that
+/// it unparked itself. The events are stored by epoch in the ChainStore, so that it is possible to
+/// find out why a validator lost its rewards after the epoch is over.
+/// Since the block number is serialized in big endian first, the database orders the events of an
+/// epoch chronologically.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SlashEvent {
+    // The number of the block that contains the fork proof, the view change or the unpark
+    // transaction.
+    pub block_number: u32,
+    // The id of the validator.
+    pub validator_id: ValidatorId,
+    // What happened to the validator.
+    pub kind: SlashEventKind,
+}
+
+/// The kind of a `SlashEvent`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SlashEventKind {
+    /// The validator produced two different blocks for the same slot. The slot is slashed and the
+    /// validator is parked.
+    ForkProof {
+        slot: u16,
+        event_block: u32,
+        view_number: u32,
+    },
+    /// The validator didn't produce a block for its slot in time, which caused a view change. The
+    /// slot is slashed and the validator is parked.
+    ViewChange {
+        slot: u16,
+        event_block: u32,
+        view_number: u32,
+    },
+    /// The validator sent an unpark transaction, so it won't be retired at the end of the epoch.
+    Unpark,
+    /// The slot was slashed and the validator is parked, either for a fork proof or for a view
+    /// change. This is recorded for blocks that were synced from the history, which only contains
+    /// the slash inherents and not the fork proofs and view changes that caused them.
+    Slash { slot: u16, event_block: u32 },
+}
+
+impl SlashEventKind {
+    const FORK_PROOF_TAG: u8 = 0;
+    const VIEW_CHANGE_TAG: u8 = 1;
+    const UNPARK_TAG: u8 = 2;
+    const SLASH_TAG: u8 = 3;
+}
+
+impl Serialize for SlashEvent {
+    fn serialize<W: WriteBytesExt>(&self, writer: &mut W) -> Result<usize, SerializingError> {
+        let mut size = 0;
+        size += Serialize::serialize(&self.block_number, writer)?;
+        size += Serialize::serialize(&self.validator_id, writer)?;
+        match &self.kind {
+            SlashEventKind::ForkProof {
+                slot,
+                event_block,
+                view_number,
+            } => {
+                size += Serialize::serialize(&SlashEventKind::FORK_PROOF_TAG, writer)?;
+                size += Serialize::serialize(slot, writer)?;
+                size += Serialize::serialize(event_block, writer)?;
+                size += Serialize::serialize(view_number, writer)?;
+            }
+            SlashEventKind::ViewChange {
+                slot,
+                event_block,
+                view_number,
+            } => {
+                size += Serialize::serialize(&SlashEventKind::VIEW_CHANGE_TAG, writer)?;
+                size += Serialize::serialize(slot, writer)?;
+                size += Serialize::serialize(event_block, writer)?;
+                size += Serialize::serialize(view_number, writer)?;
+            }
+            SlashEventKind::Unpark => {
+                size += Serialize::serialize(&SlashEventKind::UNPARK_TAG, writer)?;
+            }
+            SlashEventKind::Slash { slot, event_block } => {
+                size += Serialize::serialize(&SlashEventKind::SLASH_TAG, writer)?;
+                size += Serialize::serialize(slot, writer)?;
+                size += Serialize::serialize(event_block, writer)?;
+            }
+        }
+        Ok(size)
+    }
+
+    fn serialized_size(&self) -> usize {
+        let mut size = 0;
+        size += Serialize::serialized_size(&self.block_number);
+        size += Serialize::serialized_size(&self.validator_id);
+        size += /*tag*/ 1;
+        match &self.kind {
+            SlashEventKind::ForkProof {
+                slot,
+                event_block,
+                view_number,
+            }
+            | SlashEventKind::ViewChange {
+                slot,
+                event_block,
+                view_number,
+            } => {
+                size += Serialize::serialized_size(slot);
+                size += Serialize::serialized_size(event_block);
+                size += Serialize::serialized_size(view_number);
+            }
+            SlashEventKind::Unpark => {}
+            SlashEventKind::Slash { slot, event_block } => {
+                size += Serialize::serialized_size(slot);
+                size += Serialize::serialized_size(event_block);
+            }
+        }
+        size
+    }
+}
+
+impl Deserialize for SlashEvent {
+    fn deserialize<R: ReadBytesExt>(reader: &mut R) -> Result<Self, SerializingError> {
+        let block_number = Deserialize::deserialize(reader)?;
+        let validator_id = Deserialize::deserialize(reader)?;
+        let tag: u8 = Deserialize::deserialize(reader)?;
+        let kind = match tag {
+            SlashEventKind::FORK_PROOF_TAG => SlashEventKind::ForkProof {
+                slot: Deserialize::deserialize(reader)?,
+                event_block: Deserialize::deserialize(reader)?,
+                view_number: Deserialize::deserialize(reader)?,
+            },
+            SlashEventKind::VIEW_CHANGE_TAG => SlashEventKind::ViewChange {
+                slot: Deserialize::deserialize(reader)?,
+                event_block: Deserialize::deserialize(reader)?,
+                view_number: Deserialize::deserialize(reader)?,
+            },
+            SlashEventKind::UNPARK_TAG => SlashEventKind::Unpark,
+            SlashEventKind::SLASH_TAG => SlashEventKind::Slash {
+                slot: Deserialize::deserialize(reader)?,
+                event_block: Deserialize::deserialize(reader)?,
+            },
+            _ => return Err(SerializingError::InvalidValue),
+        };
+        Ok(SlashEvent {
+            block_number,
+            validator_id,
+            kind,
+        })
+    }
+}
+
+impl IntoDatabaseValue for SlashEvent {
+    fn database_byte_size(&self) -> usize {
+        self.serialized_size()
+    }
+
+    fn copy_into_database(&self, mut bytes: &mut [u8]) {
+        Serialize::serialize(&self, &mut bytes).unwrap();
+    }
+}
+
+impl FromDatabaseValue for SlashEvent {
+    fn copy_from_database(bytes: &[u8]) -> io::Result<Self>
+    where
+        Self: Sized,
+    {
+        let mut cursor = io::Cursor::new(bytes);
+        Ok(Deserialize::deserialize(&mut cursor)?)
+    }
+}
+
+impl AsDatabaseBytes for SlashEvent {
+    fn as_database_bytes(&self) -> Cow<[u8]> {
+        Cow::Owned(self.serialize_to_vec())
+    }
+}
@@ -0,0 +1,73 @@
+use nimiq_keys::Address;
+use nimiq_primitives::account::ValidatorId;
+use nimiq_primitives::coin::Coin;
+
+/// The changes of the staking contract between the states after two blocks. It is created by
+/// `Blockchain::get_staking_contract_diff` from the transactions and inherents of the blocks in
+/// between, so that staking pools can reconcile their books without keeping snapshots of the
+/// contract.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StakingContractDiff {
+    /// The diff starts with the state after this block.
+    pub from_block: u32,
+    /// The diff ends with the state after this block.
+    pub to_block: u32,
+    /// The validators that were created and not dropped again.
+    pub added_validators: Vec<ValidatorId>,
+    /// The validators that existed before and were dropped.
+    pub removed_validators: Vec<ValidatorId>,
+    /// The changes of the validators in chronological order.
+    pub validator_events: Vec<ValidatorEvent>,
+    /// The changes of the stakes, ordered by staker address and validator.
+    pub stake_changes: Vec<StakeChange>,
+    /// The validators that were dropped in a block whose receipts were already pruned. Dropping a
+    /// validator retires the stakes of its stakers, which is missing from `stake_changes` for these
+    /// validators.
+    pub incomplete_drops: Vec<ValidatorId>,
+}
+
+/// A change of a single validator in a `StakingContractDiff`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ValidatorEvent {
+    pub block_number: u32,
+    pub validator_id: ValidatorId,
+    pub kind: ValidatorEventKind,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ValidatorEventKind {
+    /// The validator was created with the given deposit.
+    Created {
+        deposit: Coin,
+    },
+    /// The key, reward address or commission of the validator was changed.
+    Updated,
+    /// The validator was retired, either by itself or automatically because it was still parked at
+    /// the end of the epoch after it was parked.
+    Retired {
+        automatically: bool,
+    },
+    Reactivated,
+    /// The validator was dropped and its deposit was paid out.
+    Dropped {
+        deposit: Coin,
+    },
+    /// A slot of the validator was slashed. This parks the validator and disables the slot.
+    Slashed {
+        slot: u16,
+        event_block: u32,
+    },
+    Unparked,
+}
+
+/// The stake that was added to and removed from a staker, either its active stake with a
+/// validator or its inactive stake.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StakeChange {
+    pub staker_address: Address,
+    /// The validator of the active stake, or `None` for the inactive stake.
+    pub validator_id: Option<ValidatorId>,
+    pub added: Coin,
+    /// The removed stake, including the fees that were paid from it.
+    pub removed: Coin,
+}
@@ -0,0 +1,113 @@
+use nimiq_account::{Inherent, InherentType};
+use nimiq_blockchain_albatross::{Blockchain, ExtendedTransaction, HistoryStore};
+use nimiq_database::volatile::VolatileEnvironment;
+use nimiq_database::WriteTransaction;
+use nimiq_keys::Address;
+use nimiq_primitives::coin::Coin;
+use nimiq_primitives::networks::NetworkId;
+use nimiq_transaction::Transaction;
+
+fn create_transaction(sender: &Address, recipient: &Address, value: u64) -> Transaction {
+    Transaction::new_basic(
+        sender.clone(),
+        recipient.clone(),
+        Coin::from_u64_unchecked(value),
+        Coin::ZERO,
+        1,
+        NetworkId::UnitAlbatross,
+    )
+}
+
+#[test]
+fn it_can_index_transactions_by_address() {
+    let env = VolatileEnvironment::new(Blockchain::NUM_DATABASES).unwrap();
+    let history_store = HistoryStore::new(env.clone());
+
+    let alice = Address::from([1u8; Address::SIZE]);
+    let bob = Address::from([2u8; Address::SIZE]);
+    let charlie = Address::from([3u8; Address::SIZE]);
+
+    let ext_txs_1 = ExtendedTransaction::from(
+        1,
+        0,
+        vec![
+            create_transaction(&alice, &bob, 10),
+            create_transaction(&bob, &charlie, 20),
+        ],
+        vec![],
+    );
+    let ext_txs_2 = ExtendedTransaction::from(
+        2,
+        0,
+        vec![
+            create_transaction(&alice, &charlie, 30),
+            create_transaction(&charlie, &alice, 5),
+        ],
+        vec![Inherent {
+            ty: InherentType::Reward,
+            target: alice.clone(),
+            value: Coin::from_u64_unchecked(40),
+            data: vec![],
+        }],
+    );
+
+    let mut txn = WriteTransaction::new(&env);
+    history_store.add_to_history(&mut txn, 0, &ext_txs_1);
+    history_store.add_to_history(&mut txn, 0, &ext_txs_2);
+    txn.commit();
+
+    // Alice has three basic transactions, newest first and in the reverse order of the block
+    // within a block. The inherent isn't part of her transaction history.
+    let history = history_store.get_ext_tx_by_address(&alice, 10, None, None);
+    assert_eq!(history.len(), 3);
+    assert_eq!(history[0].1.tx_hash(), ext_txs_2[1].tx_hash());
+    assert_eq!(history[1].1.tx_hash(), ext_txs_2[0].tx_hash());
+    assert_eq!(history[2].1.tx_hash(), ext_txs_1[0].tx_hash());
+    assert_eq!(history[0].0.block_number, 2);
+    assert_eq!(history[2].0.block_number, 1);
+
+    // Paginate through the history of alice. Every page is full except for the last one.
+    let page_1 = history_store.get_ext_tx_by_address(&alice, 2, None, None);
+    assert_eq!(page_1.len(), 2);
+    assert_eq!(page_1[1].0, history[1].0);
+    let page_2 = history_store.get_ext_tx_by_address(&alice, 2, Some(&page_1[1].0), None);
+    assert_eq!(page_2.len(), 1);
+    assert_eq!(page_2[0].0, history[2].0);
+
+    assert_eq!(
+        history_store
+            .get_ext_tx_by_address(&bob, 10, None, None)
+            .len(),
+        2
+    );
+    assert_eq!(
+        history_store
+            .get_ext_tx_by_address(&charlie, 10, None, None)
+            .len(),
+        3
+    );
+
+    // Removing the last block from the history also removes it from the index.
+    let mut txn = WriteTransaction::new(&env);
+    history_store.remove_partial_history(&mut txn, 0, ext_txs_2.len());
+    txn.commit();
+
+    let history = history_store.get_ext_tx_by_address(&alice, 10, None, None);
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].0.block_number, 1);
+    assert_eq!(
+        history_store
+            .get_ext_tx_by_address(&charlie, 10, None, None)
+            .len(),
+        1
+    );
+
+    // Removing the epoch clears the index.
+    let mut txn = WriteTransaction::new(&env);
+    history_store.remove_history(&mut txn, 0);
+    txn.commit();
+
+    assert!(history_store
+        .get_ext_tx_by_address(&bob, 10, None, None)
+        .is_empty());
+}
@@ -6,7 +6,10 @@ use nimiq_block_albatross::{
     TendermintProposal, TendermintStep, TendermintVote,
 };
 use nimiq_block_production_albatross::BlockProducer;
-use nimiq_blockchain_albatross::{AbstractBlockchain, Blockchain, PushResult};
+use nimiq_blockchain_albatross::{
+    accounts_chunk_part_start, next_accounts_chunk_prefix, AbstractBlockchain, Blockchain,
+    PushResult, StakingDiffError, ACCOUNTS_CHUNK_PARTS,
+};
 use nimiq_bls::{AggregateSignature, KeyPair, SecretKey};
 use nimiq_collections::bitset::BitSet;
 use nimiq_database::volatile::VolatileEnvironment;
@@ -23,13 +26,15 @@ fn fill_micro_blocks(producer: &BlockProducer, blockchain: &Arc<Blockchain>) {
     let init_height = blockchain.block_number();
     let macro_block_number = policy::macro_block_after(init_height + 1);
     for i in (init_height + 1)..macro_block_number {
-        let last_micro_block = producer.next_micro_block(
-            blockchain.time.now() + i as u64 * 1000,
-            0,
-            None,
-            vec![],
-            vec![0x42],
-        );
+        let last_micro_block = producer
+            .next_micro_block(
+                blockchain.time.now() + i as u64 * 1000,
+                0,
+                None,
+                vec![],
+                vec![0x42],
+            )
+            .unwrap();
         assert_eq!(
             blockchain.push(Block::Micro(last_micro_block)),
             Ok(PushResult::Extended)
@@ -47,11 +52,13 @@ fn produce_macro_blocks(num_macro: usize, producer: &BlockProducer, blockchain:
         assert!(slots.is_some());
 
         let next_block_height = blockchain.block_number() + 1;
-        let macro_block_proposal = producer.next_macro_block_proposal(
-            blockchain.time.now() + next_block_height as u64 * 1000,
-            0u32,
-            vec![],
-        );
+        let macro_block_proposal = producer
+            .next_macro_block_proposal(
+                blockchain.time.now() + next_block_height as u64 * 1000,
+                0u32,
+                vec![],
+            )
+            .unwrap();
 
         let block = sign_macro_block(
             TendermintProposal {
@@ -127,7 +134,7 @@ fn it_can_history_sync() {
     let num_macro_blocks = (BATCHES_PER_EPOCH + 1) as usize;
 
     // Create a blockchain to produce the macro blocks.
-    let env = VolatileEnvironment::new(10).unwrap();
+    let env = VolatileEnvironment::new(Blockchain::NUM_DATABASES).unwrap();
     let blockchain = Arc::new(Blockchain::new(env, NetworkId::UnitAlbatross).unwrap());
 
     // Produce the blocks.
@@ -151,7 +158,7 @@ fn it_can_history_sync() {
         .get_epoch_transactions(policy::epoch_at(checkpoint_block.block_number()), None);
 
     // Create a second blockchain to push these blocks.
-    let env2 = VolatileEnvironment::new(10).unwrap();
+    let env2 = VolatileEnvironment::new(Blockchain::NUM_DATABASES).unwrap();
     let blockchain2 = Arc::new(Blockchain::new(env2, NetworkId::UnitAlbatross).unwrap());
 
     // Push blocks using history sync.
@@ -167,3 +174,110 @@ fn it_can_history_sync() {
 }
 
 // TODO: Test using blocks with transactions.
+
+#[test]
+fn it_can_macro_sync() {
+    // The minimum number of macro blocks necessary so that we have one election block and one
+    // checkpoint block to push.
+    let num_macro_blocks = (BATCHES_PER_EPOCH + 1) as usize;
+
+    // Create a blockchain to produce the macro blocks.
+    let env = VolatileEnvironment::new(Blockchain::NUM_DATABASES).unwrap();
+    let blockchain = Arc::new(Blockchain::new(env, NetworkId::UnitAlbatross).unwrap());
+
+    // Produce the blocks.
+    let keypair =
+        KeyPair::from(SecretKey::deserialize_from_vec(&hex::decode(SECRET_KEY).unwrap()).unwrap());
+    let producer = BlockProducer::new_without_mempool(Arc::clone(&blockchain), keypair);
+    produce_macro_blocks(num_macro_blocks, &producer, &blockchain);
+
+    // Get the latest election and checkpoint blocks and the history of the checkpoint's epoch.
+    let election_block = blockchain.state().election_head.clone();
+    let checkpoint_block = blockchain
+        .state()
+        .macro_info
+        .head
+        .unwrap_macro_ref()
+        .clone();
+
+    let checkpoint_txs = blockchain
+        .history_store
+        .get_epoch_transactions(policy::epoch_at(checkpoint_block.header.block_number), None);
+
+    // Get the accounts tree at the checkpoint block in chunks, part by part.
+    let mut accounts_chunks = vec![];
+    for part in 0..ACCOUNTS_CHUNK_PARTS {
+        let mut start = accounts_chunk_part_start(part);
+        loop {
+            let chunk = blockchain
+                .get_accounts_chunk(&checkpoint_block.hash(), &start)
+                .unwrap();
+            let next_prefix = next_accounts_chunk_prefix(part, &chunk);
+            accounts_chunks.push(chunk);
+            match next_prefix {
+                Some(prefix) => start = prefix,
+                None => break,
+            }
+        }
+    }
+
+    // Only prefixes that start a chunk are served.
+    assert!(blockchain
+        .get_accounts_chunk(&checkpoint_block.hash(), "abc")
+        .is_none());
+
+    // Create a second blockchain to push these blocks.
+    let env2 = VolatileEnvironment::new(Blockchain::NUM_DATABASES).unwrap();
+    let blockchain2 = Arc::new(Blockchain::new(env2, NetworkId::UnitAlbatross).unwrap());
+
+    // Push blocks using macro sync.
+    assert_eq!(
+        blockchain2.push_macro_sync(
+            &[election_block, checkpoint_block.clone()],
+            &checkpoint_txs,
+            &accounts_chunks
+        ),
+        Ok(PushResult::Extended)
+    );
+    assert_eq!(blockchain2.head_hash(), checkpoint_block.hash());
+    assert_eq!(
+        blockchain2.state().accounts.hash(None),
+        checkpoint_block.header.state_root
+    );
+
+    // The second blockchain can follow the chain normally afterwards.
+    let micro_block = producer
+        .next_micro_block(
+            blockchain.time.now() + (checkpoint_block.header.block_number as u64 + 1) * 1000,
+            0,
+            None,
+            vec![],
+            vec![0x42],
+        )
+        .unwrap();
+    assert_eq!(
+        blockchain.push(Block::Micro(micro_block.clone())),
+        Ok(PushResult::Extended)
+    );
+    assert_eq!(
+        blockchain2.push(Block::Micro(micro_block)),
+        Ok(PushResult::Extended)
+    );
+
+    // The accounts tree at the checkpoint block is still served after the micro block.
+    assert!(blockchain
+        .get_accounts_chunk(&checkpoint_block.hash(), "")
+        .is_some());
+
+    // The second blockchain skipped the history of the first epoch, which staking diffs need.
+    let checkpoint_number = checkpoint_block.header.block_number;
+    assert!(blockchain
+        .get_staking_contract_diff(1, checkpoint_number)
+        .is_ok());
+    assert_eq!(
+        blockchain2.get_staking_contract_diff(1, checkpoint_number),
+        Err(StakingDiffError::HistoryUnavailable(
+            policy::first_block_of(2)
+        ))
+    );
+}
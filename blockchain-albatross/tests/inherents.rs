@@ -16,7 +16,7 @@ use std::sync::Arc;
 
 #[test]
 fn it_can_create_batch_finalization_inherents() {
-    let env = VolatileEnvironment::new(10).unwrap();
+    let env = VolatileEnvironment::new(Blockchain::NUM_DATABASES).unwrap();
     let blockchain = Arc::new(Blockchain::new(env, NetworkId::UnitAlbatross).unwrap());
 
     let validator_registry_addr = NetworkInfo::from_network_id(blockchain.network_id)
@@ -40,7 +40,9 @@ fn it_can_create_batch_finalization_inherents() {
     };
 
     // Simple case. Expect 1x FinalizeBatch, 1x Reward to validator
-    let inherents = blockchain.finalize_previous_batch(&blockchain.state(), &macro_header);
+    let inherents = blockchain
+        .finalize_previous_batch(&blockchain.state(), &macro_header)
+        .unwrap();
     assert_eq!(inherents.len(), 2);
 
     let validator = blockchain
@@ -97,7 +99,9 @@ fn it_can_create_batch_finalization_inherents() {
         .is_ok());
     txn.commit();
 
-    let inherents = blockchain.finalize_previous_batch(&blockchain.state(), &macro_header);
+    let inherents = blockchain
+        .finalize_previous_batch(&blockchain.state(), &macro_header)
+        .unwrap();
     assert_eq!(inherents.len(), 3);
     let one_slot_reward = 8_74999 / policy::SLOTS as u64;
     let mut got_reward = false;
@@ -128,4 +132,31 @@ fn it_can_create_batch_finalization_inherents() {
         }
     }
     assert!(got_reward && got_slash && got_finalize_batch);
+
+    // The report of the batch rewards matches the inherents.
+    let (report_inherents, report) = blockchain
+        .finalize_previous_batch_with_report(&blockchain.state(), &macro_header)
+        .unwrap();
+    assert_eq!(report_inherents, inherents);
+
+    let report = report.unwrap();
+    assert_eq!(report.batch_number, policy::batch_at(42) - 1);
+    assert_eq!(report.block_number, 42);
+    assert_eq!(
+        report.burned_reward,
+        Coin::from_u64_unchecked(one_slot_reward)
+    );
+    assert_eq!(report.validators.len(), 1);
+
+    let validator_reward = &report.validators[0];
+    assert_eq!(validator_reward.validator_id, validator.id);
+    assert_eq!(validator_reward.reward_address, validator.reward_address);
+    assert_eq!(validator_reward.first_slot, 0);
+    assert_eq!(validator_reward.num_slots, policy::SLOTS);
+    assert_eq!(validator_reward.num_eligible_slots, policy::SLOTS - 1);
+    assert_eq!(
+        validator_reward.reward,
+        Coin::from_u64_unchecked(8_74999 - one_slot_reward as u64)
+    );
+    assert!(validator_reward.accepted);
 }
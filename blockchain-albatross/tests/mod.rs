@@ -5,8 +5,10 @@ use nimiq_block_production_albatross::test_utils::TemporaryBlockProducer;
 use nimiq_blockchain_albatross::{ForkEvent, PushError, PushResult};
 use nimiq_primitives::policy;
 
+mod history_store;
 mod history_sync;
 mod inherents;
+mod proofs;
 mod signed;
 
 #[test]
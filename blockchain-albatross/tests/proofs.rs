@@ -0,0 +1,51 @@
+use nimiq_block_production_albatross::test_utils::TemporaryBlockProducer;
+use nimiq_blockchain_albatross::AbstractBlockchain;
+use nimiq_hash::Blake2bHash;
+use nimiq_keys::Address;
+
+#[test]
+fn it_can_prove_accounts_at_the_head() {
+    let producer = TemporaryBlockProducer::new();
+    producer.next_block(0, vec![]);
+    producer.next_block(0, vec![]);
+
+    let blockchain = &producer.blockchain;
+    let head = blockchain.head();
+
+    let addresses = vec![
+        Address::from([1u8; Address::SIZE]),
+        Address::from([2u8; Address::SIZE]),
+    ];
+
+    // The proof can be verified against the state root of the head.
+    let mut proof = blockchain
+        .get_accounts_proof(&head.hash(), &addresses)
+        .unwrap();
+    assert!(proof.verify());
+    assert_eq!(&proof.root_hash(), head.state_root());
+    for address in &addresses {
+        assert!(proof.get_account(address).is_none());
+    }
+
+    // We can only prove accounts at our head.
+    assert!(blockchain
+        .get_accounts_proof(&Blake2bHash::default(), &addresses)
+        .is_none());
+    assert!(blockchain
+        .get_accounts_proof(head.parent_hash(), &addresses)
+        .is_none());
+}
+
+#[test]
+fn it_returns_no_transactions_proof_without_transactions() {
+    let producer = TemporaryBlockProducer::new();
+    producer.next_block(0, vec![]);
+
+    let address = Address::from([1u8; Address::SIZE]);
+    let (head_hash, proof) = producer
+        .blockchain
+        .prove_transactions_by_address(&address, 10);
+
+    assert_eq!(head_hash, producer.blockchain.head_hash());
+    assert!(proof.is_none());
+}
@@ -69,7 +69,7 @@ fn test_view_change_single_signature() {
 /// only sign the `block_hash`, this would work, but `SignedMessage` adds a prefix byte.
 fn test_replay() {
     // Create a blockchain to have access to the validator slots.
-    let env = VolatileEnvironment::new(10).unwrap();
+    let env = VolatileEnvironment::new(Blockchain::NUM_DATABASES).unwrap();
     let blockchain = Arc::new(Blockchain::new(env, NetworkId::UnitAlbatross).unwrap());
 
     // load key pair
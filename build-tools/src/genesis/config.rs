@@ -47,6 +47,9 @@ pub struct GenesisValidator {
 
     #[serde(deserialize_with = "deserialize_bls_public_key")]
     pub validator_key: BlsPublicKey,
+
+    #[serde(default)]
+    pub commission: Option<u16>,
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -150,6 +150,7 @@ impl GenesisBuilder {
             reward_address,
             balance,
             validator_key,
+            commission: None,
         });
         self
     }
@@ -303,6 +304,7 @@ impl GenesisBuilder {
                 validator.validator_id.clone(),
                 validator.validator_key.compress(),
                 validator.reward_address.clone(),
+                validator.commission,
                 validator.balance,
             )?;
         }
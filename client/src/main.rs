@@ -4,7 +4,7 @@ use futures::StreamExt;
 pub use nimiq::{
     client::{Client, Consensus},
     config::command_line::CommandLine,
-    config::config::ClientConfig,
+    config::config::{ClientConfig, ConsensusConfig, RpcServerConfig},
     config::config_file::ConfigFile,
     error::Error,
     extras::{
@@ -12,6 +12,7 @@ pub use nimiq::{
         logging::{initialize_logging, log_error_cause_chain},
         panic::initialize_panic_reporting,
     },
+    nano_client::NanoClient,
 };
 
 async fn main_inner() -> Result<(), Error> {
@@ -46,6 +47,11 @@ async fn main_inner() -> Result<(), Error> {
     let rpc_config = config.rpc_server.clone();
     let _metrics_config = config.metrics_server.clone();
 
+    // Nano nodes have their own client.
+    if config.consensus == ConsensusConfig::Nano {
+        return run_nano_client(config, rpc_config, config_file.log.statistics).await;
+    }
+
     // Create client from config.
     log::info!("Initializing client");
     let mut client: Client = Client::from_config(config).await?;
@@ -133,6 +139,65 @@ async fn main_inner() -> Result<(), Error> {
     }
 }
 
+async fn run_nano_client(
+    config: ClientConfig,
+    rpc_config: Option<RpcServerConfig>,
+    statistics_interval: u64,
+) -> Result<(), Error> {
+    // Create client from config.
+    log::info!("Initializing nano client");
+    let mut client = NanoClient::from_config(config).await?;
+    log::info!("Nano client initialized");
+
+    // Initialize RPC server
+    if let Some(rpc_config) = rpc_config {
+        use nimiq::extras::rpc_server::initialize_nano_rpc_server;
+        let rpc_server = initialize_nano_rpc_server(&client, rpc_config)
+            .expect("Failed to initialize RPC server");
+        tokio::spawn(async move { rpc_server.run().await });
+    }
+
+    // Start consensus.
+    let consensus = client.consensus().unwrap();
+
+    log::info!("Spawning nano consensus");
+    tokio::spawn(async move { consensus.for_each(|_| async {}).await });
+    let consensus = client.consensus_proxy();
+
+    // Nano nodes don't have anything else to do, so we only show the statistics.
+    let show_statistics = statistics_interval != 0;
+    let statistics_interval = if show_statistics {
+        statistics_interval
+    } else {
+        10
+    };
+
+    // Run periodically
+    let mut interval = tokio::time::interval(Duration::from_secs(statistics_interval));
+    loop {
+        interval.tick().await;
+
+        if show_statistics {
+            match client.network().network_info().await {
+                Ok(network_info) => {
+                    let head = client.blockchain_head();
+
+                    log::info!(
+                        "Consensus established: {:?} - Head: #{} - {}, Peers: {}",
+                        consensus.is_established(),
+                        head.block_number(),
+                        head.hash(),
+                        network_info.num_peers()
+                    );
+                }
+                Err(err) => {
+                    log::error!("Error retrieving NetworkInfo: {:?}", err);
+                }
+            };
+        }
+    }
+}
+
 #[tokio::main]
 async fn main() {
     if let Err(e) = main_inner().await {
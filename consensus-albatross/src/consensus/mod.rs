@@ -11,7 +11,7 @@ use tokio::sync::broadcast::{
 };
 
 use block_albatross::Block;
-use blockchain_albatross::Blockchain;
+use blockchain_albatross::{AbstractBlockchain, Blockchain};
 use database::Environment;
 use mempool::{Mempool, ReturnCode};
 use network_interface::network::Network;
@@ -22,6 +22,7 @@ use crate::consensus::head_requests::{HeadRequests, HeadRequestsResult};
 use crate::consensus_agent::ConsensusAgent;
 use crate::sync::block_queue::{BlockQueue, BlockQueueConfig, BlockQueueEvent, BlockTopic};
 use crate::sync::request_component::BlockRequestComponent;
+use crate::sync::status::{SyncProgress, SyncStage, SyncStatus};
 
 mod head_requests;
 mod request_response;
@@ -46,6 +47,7 @@ pub struct ConsensusProxy<N: Network> {
     pub network: Arc<N>,
     pub mempool: Arc<Mempool>,
     established_flag: Arc<AtomicBool>,
+    sync_progress: SyncProgress,
 }
 
 impl<N: Network> Clone for ConsensusProxy<N> {
@@ -55,6 +57,7 @@ impl<N: Network> Clone for ConsensusProxy<N> {
             network: Arc::clone(&self.network),
             mempool: Arc::clone(&self.mempool),
             established_flag: Arc::clone(&self.established_flag),
+            sync_progress: self.sync_progress.clone(),
         }
     }
 }
@@ -75,6 +78,10 @@ impl<N: Network> ConsensusProxy<N> {
     pub fn is_established(&self) -> bool {
         self.established_flag.load(Ordering::Acquire)
     }
+
+    pub fn sync_status(&self) -> SyncStatus {
+        self.sync_progress.status(self.blockchain.block_number())
+    }
 }
 
 pub enum ConsensusEvent<N: Network> {
@@ -108,6 +115,7 @@ pub struct Consensus<N: Network> {
     established_flag: Arc<AtomicBool>,
     head_requests: Option<HeadRequests<N::PeerType>>,
     head_requests_time: Option<Instant>,
+    sync_progress: SyncProgress,
 
     min_peers: usize,
 }
@@ -209,11 +217,19 @@ impl<N: Network> Consensus<N> {
             established_flag: Arc::new(AtomicBool::new(false)),
             head_requests: None,
             head_requests_time: None,
+            sync_progress: SyncProgress::default(),
 
             min_peers,
         }
     }
 
+    /// Reports the sync progress through the given `SyncProgress`, which the sync protocol (e.g.
+    /// `HistorySync::progress`) reports its progress to as well.
+    pub fn with_sync_progress(mut self, sync_progress: SyncProgress) -> Self {
+        self.sync_progress = sync_progress;
+        self
+    }
+
     pub fn subscribe_events(&self) -> BroadcastReceiver<ConsensusEvent<N>> {
         self.events.subscribe()
     }
@@ -232,9 +248,38 @@ impl<N: Network> Consensus<N> {
             network: Arc::clone(&self.network),
             mempool: Arc::clone(&self.mempool),
             established_flag: Arc::clone(&self.established_flag),
+            sync_progress: self.sync_progress.clone(),
         }
     }
 
+    pub fn sync_status(&self) -> SyncStatus {
+        self.sync_progress.status(self.blockchain.block_number())
+    }
+
+    /// Reports our stage to the sync progress. Once we have peers that we are macro synced with,
+    /// we catch up through the block queue until consensus is established.
+    fn update_sync_progress(&self) {
+        let stage = if self.is_established() {
+            Some(SyncStage::Following)
+        } else if self.block_queue.num_peers() > 0 {
+            Some(SyncStage::BlockQueue)
+        } else {
+            None
+        };
+
+        let block_queue_target = self
+            .block_queue
+            .buffered_blocks()
+            .last()
+            .map(|(block_number, _)| block_number);
+
+        self.sync_progress.update_consensus(
+            stage,
+            block_queue_target,
+            self.blockchain.block_number(),
+        );
+    }
+
     /// Forcefully sets consensus established, should be used for tests only.
     pub fn force_established(&mut self) {
         trace!("Consensus forcefully established.");
@@ -332,6 +377,9 @@ impl<N: Network> Stream for Consensus<N> {
             };
         }
 
+        // Report the changes of the previous poll, which might have returned early.
+        self.update_sync_progress();
+
         // 1. Poll and advance block queue
         while let Poll::Ready(Some(event)) = self.block_queue.poll_next_unpin(cx) {
             match event {
@@ -373,6 +421,8 @@ impl<N: Network> Stream for Consensus<N> {
             }
         }
 
+        self.update_sync_progress();
+
         Poll::Pending
     }
 }
@@ -4,12 +4,13 @@ use futures::StreamExt;
 
 use crate::messages::handlers::Handle;
 use crate::messages::{
-    RequestBatchSet, RequestBlock, RequestBlockHashes, RequestHead, RequestHistoryChunk,
-    RequestMissingBlocks,
+    RequestAccountsProof, RequestAccountsTreeChunk, RequestBatchSet, RequestBlock,
+    RequestBlockHashes, RequestHead, RequestHistoryChunk, RequestMissingBlocks,
+    RequestTransactionsProof,
 };
 use crate::Consensus;
 
-use blockchain_albatross::Blockchain;
+use blockchain_albatross::{Blockchain, BlockchainEvent};
 use network_interface::prelude::{Network, Peer};
 
 impl<N: Network> Consensus<N> {
@@ -113,5 +114,92 @@ impl<N: Network> Consensus<N> {
                 }
             }
         });
+
+        let blockchain = Arc::clone(blockchain_outer);
+        let mut stream = network.receive_from_all::<RequestAccountsTreeChunk>();
+        tokio::spawn(async move {
+            while let Some((msg, peer)) = stream.next().await {
+                trace!(
+                    "[REQUEST_ACCOUNTS_TREE_CHUNK] for block {}, prefix {:?} received from {:?}",
+                    msg.block_hash,
+                    msg.start_prefix,
+                    peer.id()
+                );
+
+                // Creating the chunks reads the whole accounts tree, which must not block the
+                // executor.
+                let blockchain = Arc::clone(&blockchain);
+                let response = tokio::task::spawn_blocking(move || msg.handle(&blockchain))
+                    .await
+                    .expect("Creating accounts tree chunks panicked");
+
+                if let Some(response) = response {
+                    // We do not care about the result.
+                    let _ = peer.send(&response).await;
+                }
+            }
+        });
+
+        // The accounts tree at a macro block can only be read until the next micro block is
+        // pushed, so we cache its chunks right away for the nodes that macro sync to it.
+        let blockchain = Arc::clone(blockchain_outer);
+        let mut blockchain_events = blockchain.notifier.write().as_stream();
+        tokio::spawn(async move {
+            while let Some(event) = blockchain_events.next().await {
+                match event {
+                    BlockchainEvent::Finalized(block_hash)
+                    | BlockchainEvent::EpochFinalized(block_hash) => {
+                        let blockchain = Arc::clone(&blockchain);
+                        let is_cached = tokio::task::spawn_blocking(move || {
+                            blockchain.cache_accounts_chunks(&block_hash)
+                        })
+                        .await
+                        .expect("Creating accounts tree chunks panicked");
+
+                        if !is_cached {
+                            debug!(
+                                "Macro block was superseded before its accounts tree was cached"
+                            );
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        });
+
+        let blockchain = Arc::clone(blockchain_outer);
+        let mut stream = network.receive_from_all::<RequestAccountsProof>();
+        tokio::spawn(async move {
+            while let Some((msg, peer)) = stream.next().await {
+                trace!(
+                    "[REQUEST_ACCOUNTS_PROOF] for {} addresses at block {} received from {:?}",
+                    msg.addresses.len(),
+                    msg.block_hash,
+                    peer.id()
+                );
+
+                if let Some(response) = msg.handle(&blockchain) {
+                    // We do not care about the result.
+                    let _ = peer.send(&response).await;
+                }
+            }
+        });
+
+        let blockchain = Arc::clone(blockchain_outer);
+        let mut stream = network.receive_from_all::<RequestTransactionsProof>();
+        tokio::spawn(async move {
+            while let Some((msg, peer)) = stream.next().await {
+                trace!(
+                    "[REQUEST_TRANSACTIONS_PROOF] for address {} received from {:?}",
+                    msg.address,
+                    peer.id()
+                );
+
+                if let Some(response) = msg.handle(&blockchain) {
+                    // We do not care about the result.
+                    let _ = peer.send(&response).await;
+                }
+            }
+        });
     }
 }
@@ -4,13 +4,17 @@ use std::time::Duration;
 
 use parking_lot::RwLock;
 
+use account::Account;
 use block_albatross::Block;
 use hash::Blake2bHash;
+use keys::Address;
 use network_interface::peer::Peer;
 use network_interface::request_response::{RequestError, RequestResponse};
 use nimiq_subscription::Subscription;
+use tree_primitives::accounts_proof::AccountsProof;
 
 use crate::messages::*;
+use crate::zkp::ZKProof;
 
 pub struct ConsensusAgentState {
     local_subscription: Subscription,
@@ -35,6 +39,11 @@ pub struct ConsensusAgent<P: Peer> {
     block_requests: RequestResponse<P, RequestBlock, ResponseBlock>,
     missing_block_requests: RequestResponse<P, RequestMissingBlocks, ResponseBlocks>,
     head_requests: RequestResponse<P, RequestHead, HeadResponse>,
+    accounts_chunk_requests: RequestResponse<P, RequestAccountsTreeChunk, AccountsChunk>,
+    zkp_requests: RequestResponse<P, RequestZKP, ZKPResponse>,
+    accounts_proof_requests: RequestResponse<P, RequestAccountsProof, ResponseAccountsProof>,
+    transactions_proof_requests:
+        RequestResponse<P, RequestTransactionsProof, ResponseTransactionsProof>,
 }
 
 impl<P: Peer> Debug for ConsensusAgent<P> {
@@ -55,6 +64,10 @@ impl<P: Peer> ConsensusAgent<P> {
         let block_requests = RequestResponse::new(Arc::clone(&peer), timeout);
         let missing_block_requests = RequestResponse::new(Arc::clone(&peer), timeout);
         let head_requests = RequestResponse::new(Arc::clone(&peer), timeout);
+        let accounts_chunk_requests = RequestResponse::new(Arc::clone(&peer), timeout);
+        let zkp_requests = RequestResponse::new(Arc::clone(&peer), timeout);
+        let accounts_proof_requests = RequestResponse::new(Arc::clone(&peer), timeout);
+        let transactions_proof_requests = RequestResponse::new(Arc::clone(&peer), timeout);
 
         ConsensusAgent {
             peer,
@@ -68,6 +81,10 @@ impl<P: Peer> ConsensusAgent<P> {
             block_requests,
             missing_block_requests,
             head_requests,
+            accounts_chunk_requests,
+            zkp_requests,
+            accounts_proof_requests,
+            transactions_proof_requests,
         }
     }
 
@@ -162,4 +179,64 @@ impl<P: Peer> ConsensusAgent<P> {
 
         result.map(|response_blocks| response_blocks.hash)
     }
+
+    pub async fn request_accounts_chunk(
+        &self,
+        block_hash: Blake2bHash,
+        start_prefix: String,
+    ) -> Result<AccountsChunk, RequestError> {
+        let result = self
+            .accounts_chunk_requests
+            .request(RequestAccountsTreeChunk {
+                block_hash,
+                start_prefix,
+                request_identifier: 0, // will automatically be set at a later point
+            })
+            .await;
+
+        result
+    }
+
+    pub async fn request_zkp(&self) -> Result<Option<ZKProof>, RequestError> {
+        let result = self
+            .zkp_requests
+            .request(RequestZKP {
+                request_identifier: 0, // will automatically be set at a later point
+            })
+            .await;
+
+        result.map(|response| response.proof)
+    }
+
+    pub async fn request_accounts_proof(
+        &self,
+        block_hash: Blake2bHash,
+        addresses: Vec<Address>,
+    ) -> Result<Option<AccountsProof<Account>>, RequestError> {
+        let result = self
+            .accounts_proof_requests
+            .request(RequestAccountsProof {
+                block_hash,
+                addresses,
+                request_identifier: 0, // will automatically be set at a later point
+            })
+            .await;
+
+        result.map(|response| response.proof)
+    }
+
+    pub async fn request_transactions_proof(
+        &self,
+        address: Address,
+    ) -> Result<ResponseTransactionsProof, RequestError> {
+        let result = self
+            .transactions_proof_requests
+            .request(RequestTransactionsProof {
+                address,
+                request_identifier: 0, // will automatically be set at a later point
+            })
+            .await;
+
+        result
+    }
 }
@@ -1,6 +1,8 @@
 use thiserror::Error;
 
-use blockchain_albatross::BlockchainError;
+use blockchain_albatross::{BlockchainError, PushError};
+use nano_sync::NanoZKPError;
+use network_interface::request_response::RequestError;
 
 #[derive(Debug, Error)]
 pub enum Error {
@@ -18,3 +20,49 @@ pub enum SyncError {
 
 #[derive(Debug, Error)]
 pub enum BlockQueueError {}
+
+#[derive(Debug, Error)]
+pub enum MacroSyncError {
+    #[error("Request failed: {0}")]
+    Request(#[from] RequestError),
+    #[error("Peer sent an invalid response")]
+    InvalidResponse,
+    #[error("Peer's macro head changed during the sync")]
+    MacroHeadChanged,
+    #[error("Push failed: {0}")]
+    Push(#[from] PushError),
+}
+
+#[derive(Debug, Error)]
+pub enum ZKPSyncError {
+    #[error("Request failed: {0}")]
+    Request(#[from] RequestError),
+    #[error("Peer doesn't have a proof")]
+    NoProof,
+    #[error("Invalid proof: {0}")]
+    InvalidProof(#[from] NanoZKPError),
+    #[error("Proof verification failed")]
+    VerificationFailed,
+    #[error("Push failed: {0}")]
+    Push(#[from] PushError),
+}
+
+#[derive(Debug, Error)]
+pub enum NanoSyncError {
+    #[error("Request failed: {0}")]
+    Request(#[from] RequestError),
+    #[error("Peer sent an invalid response")]
+    InvalidResponse,
+    #[error("ZKP sync failed: {0}")]
+    ZKP(#[from] ZKPSyncError),
+    #[error("Push failed: {0}")]
+    Push(#[from] PushError),
+}
+
+#[derive(Debug, Error)]
+pub enum NanoRequestError {
+    #[error("Too many addresses")]
+    TooManyAddresses,
+    #[error("No peer sent a valid response")]
+    NoValidResponse,
+}
@@ -9,17 +9,24 @@ extern crate beserial_derive;
 extern crate futures;
 #[macro_use]
 extern crate log;
+extern crate nimiq_account as account;
 extern crate nimiq_block_albatross as block_albatross;
 extern crate nimiq_blockchain_albatross as blockchain_albatross;
 extern crate nimiq_collections as collections;
 extern crate nimiq_database as database;
 extern crate nimiq_hash as hash;
+extern crate nimiq_keys as keys;
 extern crate nimiq_macros as macros;
 extern crate nimiq_mempool as mempool;
 extern crate nimiq_messages as network_messages;
+extern crate nimiq_nano_blockchain as nano_blockchain;
+#[cfg(feature = "zkp-prover")]
+extern crate nimiq_nano_primitives as nano_primitives;
+extern crate nimiq_nano_sync as nano_sync;
 extern crate nimiq_network_interface as network_interface;
 extern crate nimiq_primitives as primitives;
 extern crate nimiq_transaction as transaction;
+extern crate nimiq_tree_primitives as tree_primitives;
 extern crate nimiq_utils as utils;
 #[macro_use]
 extern crate pin_project;
@@ -31,4 +38,6 @@ pub mod consensus;
 pub mod consensus_agent;
 pub mod error;
 pub mod messages;
+pub mod nano;
 pub mod sync;
+pub mod zkp;
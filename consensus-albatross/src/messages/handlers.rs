@@ -6,6 +6,9 @@ use nimiq_genesis::NetworkInfo;
 use primitives::policy;
 use std::sync::Arc;
 
+/// The maximum number of transactions that we prove for a transactions proof request.
+const MAX_PROVEN_TRANSACTIONS: usize = 100;
+
 /// This trait defines the behaviour when receiving a message and how to generate the response.
 pub trait Handle<Response> {
     fn handle(&self, blockchain: &Arc<Blockchain>) -> Option<Response>;
@@ -173,3 +176,38 @@ impl Handle<HeadResponse> for RequestHead {
         Some(response)
     }
 }
+
+impl Handle<AccountsChunk> for RequestAccountsTreeChunk {
+    fn handle(&self, blockchain: &Arc<Blockchain>) -> Option<AccountsChunk> {
+        let chunk = blockchain.get_accounts_chunk(&self.block_hash, &self.start_prefix);
+        let response = AccountsChunk {
+            chunk,
+            request_identifier: self.get_request_identifier(),
+        };
+        Some(response)
+    }
+}
+
+impl Handle<ResponseAccountsProof> for RequestAccountsProof {
+    fn handle(&self, blockchain: &Arc<Blockchain>) -> Option<ResponseAccountsProof> {
+        let proof = blockchain.get_accounts_proof(&self.block_hash, &self.addresses);
+        let response = ResponseAccountsProof {
+            proof,
+            request_identifier: self.get_request_identifier(),
+        };
+        Some(response)
+    }
+}
+
+impl Handle<ResponseTransactionsProof> for RequestTransactionsProof {
+    fn handle(&self, blockchain: &Arc<Blockchain>) -> Option<ResponseTransactionsProof> {
+        let (block_hash, proof) =
+            blockchain.prove_transactions_by_address(&self.address, MAX_PROVEN_TRANSACTIONS);
+        let response = ResponseTransactionsProof {
+            block_hash,
+            proof,
+            request_identifier: self.get_request_identifier(),
+        };
+        Some(response)
+    }
+}
@@ -1,11 +1,16 @@
+use account::Account;
 use beserial::{Deserialize, Serialize};
 use block_albatross::{Block, MacroBlock};
-use blockchain_albatross::HistoryTreeChunk;
+use blockchain_albatross::{HistoryTreeChunk, HistoryTreeProof};
 use hash::Blake2bHash;
+use keys::Address;
 use network_interface::message::*;
 use std::fmt::Debug;
+use tree_primitives::accounts_proof::AccountsProof;
+use tree_primitives::accounts_tree_chunk::AccountsTreeChunk;
 
 use crate::request_response;
+use crate::zkp::ZKProof;
 
 pub(crate) mod handlers;
 mod request_response;
@@ -16,6 +21,14 @@ The consensus module uses the following messages:
 201 RequestResponseMessage<BlockHashes>
 202 RequestResponseMessage<RequestEpoch>
 203 RequestResponseMessage<Epoch>
+212 RequestResponseMessage<RequestAccountsTreeChunk>
+213 RequestResponseMessage<AccountsChunk>
+214 RequestResponseMessage<RequestZKP>
+215 RequestResponseMessage<ZKPResponse>
+216 RequestResponseMessage<RequestAccountsProof>
+217 RequestResponseMessage<ResponseAccountsProof>
+218 RequestResponseMessage<RequestTransactionsProof>
+219 RequestResponseMessage<ResponseTransactionsProof>
 */
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -223,3 +236,112 @@ request_response!(HeadResponse);
 impl Message for HeadResponse {
     const TYPE_ID: u64 = 211;
 }
+
+/// Requests a chunk of the accounts tree at a macro block. The chunk starts after the given prefix,
+/// which is the start of a part of the tree or the prefix following the previous chunk of that
+/// part (see `Blockchain::get_accounts_chunk`).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RequestAccountsTreeChunk {
+    pub block_hash: Blake2bHash,
+    #[beserial(len_type(u8))]
+    pub start_prefix: String,
+    pub request_identifier: u32,
+}
+request_response!(RequestAccountsTreeChunk);
+
+impl Message for RequestAccountsTreeChunk {
+    const TYPE_ID: u64 = 212;
+}
+
+/// This message contains a chunk of the accounts tree. The chunk is `None` if the peer doesn't
+/// serve the accounts tree at the requested block (anymore) or the prefix doesn't start a chunk.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AccountsChunk {
+    pub chunk: Option<AccountsTreeChunk<Account>>,
+    pub request_identifier: u32,
+}
+request_response!(AccountsChunk);
+
+impl Message for AccountsChunk {
+    const TYPE_ID: u64 = 213;
+}
+
+/// Requests the latest election-chain proof of the peer.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RequestZKP {
+    pub request_identifier: u32,
+}
+request_response!(RequestZKP);
+
+impl Message for RequestZKP {
+    const TYPE_ID: u64 = 214;
+}
+
+/// This message contains the latest election-chain proof of the peer, or `None` if the peer
+/// doesn't generate proofs.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ZKPResponse {
+    pub proof: Option<ZKProof>,
+    pub request_identifier: u32,
+}
+request_response!(ZKPResponse);
+
+impl Message for ZKPResponse {
+    const TYPE_ID: u64 = 215;
+}
+
+/// Requests a proof for the accounts with the given addresses at the head of the peer, which must
+/// have the given hash.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RequestAccountsProof {
+    pub block_hash: Blake2bHash,
+    #[beserial(len_type(u16, limit = 128))]
+    pub addresses: Vec<Address>,
+    pub request_identifier: u32,
+}
+request_response!(RequestAccountsProof);
+
+impl Message for RequestAccountsProof {
+    const TYPE_ID: u64 = 216;
+}
+
+/// This message contains the requested accounts proof. The proof is `None` if the requested block
+/// is not the head of the peer anymore.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ResponseAccountsProof {
+    pub proof: Option<AccountsProof<Account>>,
+    pub request_identifier: u32,
+}
+request_response!(ResponseAccountsProof);
+
+impl Message for ResponseAccountsProof {
+    const TYPE_ID: u64 = 217;
+}
+
+/// Requests a proof for the most recent transactions of an address in the current epoch of the
+/// peer.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RequestTransactionsProof {
+    pub address: Address,
+    pub request_identifier: u32,
+}
+request_response!(RequestTransactionsProof);
+
+impl Message for RequestTransactionsProof {
+    const TYPE_ID: u64 = 218;
+}
+
+/// This message contains the requested transactions proof, which can be verified against the
+/// history root of the block with the given hash. The proof is `None` if the address has no
+/// transactions in the current epoch.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ResponseTransactionsProof {
+    pub block_hash: Blake2bHash,
+    pub proof: Option<HistoryTreeProof>,
+    pub request_identifier: u32,
+}
+request_response!(ResponseTransactionsProof);
+
+impl Message for ResponseTransactionsProof {
+    const TYPE_ID: u64 = 219;
+}
@@ -0,0 +1,448 @@
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use futures::future::BoxFuture;
+use futures::stream::BoxStream;
+use futures::task::{Context, Poll};
+use futures::{FutureExt, Stream, StreamExt};
+use parking_lot::RwLock;
+use tokio::sync::broadcast::{
+    self, channel as broadcast_channel, Receiver as BroadcastReceiver, Sender as BroadcastSender,
+};
+
+use account::Account;
+use block_albatross::Block;
+use blockchain_albatross::{AbstractBlockchain, PushError};
+use hash::Blake2bHash;
+use keys::Address;
+use nano_blockchain::NanoBlockchain;
+use network_interface::prelude::{CloseReason, Network, NetworkEvent, Peer};
+use primitives::policy;
+
+use crate::consensus::ConsensusEvent;
+use crate::consensus_agent::ConsensusAgent;
+use crate::error::{NanoSyncError, ZKPSyncError};
+use crate::messages::{BlockHashType, RequestBlockHashesFilter};
+use crate::sync::block_queue::BlockTopic;
+use crate::sync::status::{SyncClusterStatus, SyncProgress, SyncStage, SyncStatus};
+use crate::zkp::sync_with_zkp;
+
+pub use self::proxy::NanoConsensusProxy;
+use self::proxy::WatchedAccounts;
+
+mod proxy;
+
+type SyncFuture<TPeer> =
+    BoxFuture<'static, (Arc<ConsensusAgent<TPeer>>, Result<(), NanoSyncError>)>;
+
+/// The consensus of nano nodes. It keeps a `NanoBlockchain` up to date without storing any state:
+///
+/// 1. For every peer, we jump to its latest election block with its election-chain proof (see
+///    `sync_with_zkp`) and push the remaining macro blocks of the peer with `push_macro`. Peers
+///    that don't generate proofs are only used for the second step.
+/// 2. Once we are synced with a peer, consensus is established and we follow the blocks that are
+///    announced on the network. Missing blocks are requested from our synced peers.
+/// 3. The accounts of the watched addresses are requested with a proof for every new head (see
+///    `NanoConsensusProxy`).
+pub struct NanoConsensus<N: Network> {
+    pub blockchain: Arc<RwLock<NanoBlockchain>>,
+    pub network: Arc<N>,
+
+    network_event_rx: broadcast::Receiver<NetworkEvent<N::PeerType>>,
+    block_stream: BoxStream<'static, Block>,
+
+    /// The agents that we haven't synced with yet.
+    pending_agents: VecDeque<Arc<ConsensusAgent<N::PeerType>>>,
+    /// The agents that we are synced with. They are shared with the proxy.
+    synced_agents: Arc<RwLock<Vec<Arc<ConsensusAgent<N::PeerType>>>>>,
+    /// The sync with the agent that we are currently syncing with.
+    current_sync: Option<SyncFuture<N::PeerType>>,
+
+    /// The pending request for the blocks that are missing before an announced block.
+    missing_blocks: Option<BoxFuture<'static, Vec<Block>>>,
+    /// The pending request for the accounts of the watched addresses.
+    watched_accounts_request:
+        Option<BoxFuture<'static, (Blake2bHash, Vec<Address>, Option<Vec<Account>>)>>,
+    watched_accounts: Arc<RwLock<WatchedAccounts>>,
+
+    events: BroadcastSender<ConsensusEvent<N>>,
+    established_flag: Arc<AtomicBool>,
+    /// The sync progress. It is shared with the proxy.
+    sync_progress: SyncProgress,
+}
+
+impl<N: Network> NanoConsensus<N> {
+    const MAX_BLOCK_HASHES: u16 = 1000;
+
+    pub async fn from_network(
+        blockchain: Arc<RwLock<NanoBlockchain>>,
+        network: Arc<N>,
+        network_event_rx: broadcast::Receiver<NetworkEvent<N::PeerType>>,
+    ) -> Self {
+        let block_stream = network
+            .subscribe::<BlockTopic>(&BlockTopic::default())
+            .await
+            .unwrap()
+            .map(|(block, _peer_id)| block)
+            .boxed();
+
+        Self::new(blockchain, network, network_event_rx, block_stream)
+    }
+
+    pub fn new(
+        blockchain: Arc<RwLock<NanoBlockchain>>,
+        network: Arc<N>,
+        network_event_rx: broadcast::Receiver<NetworkEvent<N::PeerType>>,
+        block_stream: BoxStream<'static, Block>,
+    ) -> Self {
+        let (tx, _rx) = broadcast_channel(256);
+
+        NanoConsensus {
+            blockchain,
+            network,
+            network_event_rx,
+            block_stream,
+            pending_agents: VecDeque::new(),
+            synced_agents: Arc::new(RwLock::new(Vec::new())),
+            current_sync: None,
+            missing_blocks: None,
+            watched_accounts_request: None,
+            watched_accounts: Arc::new(RwLock::new(WatchedAccounts::default())),
+            events: tx,
+            established_flag: Arc::new(AtomicBool::new(false)),
+            sync_progress: SyncProgress::default(),
+        }
+    }
+
+    pub fn subscribe_events(&self) -> BroadcastReceiver<ConsensusEvent<N>> {
+        self.events.subscribe()
+    }
+
+    pub fn is_established(&self) -> bool {
+        self.established_flag.load(Ordering::Acquire)
+    }
+
+    pub fn sync_status(&self) -> SyncStatus {
+        self.sync_progress
+            .status(self.blockchain.read().block_number())
+    }
+
+    pub fn num_agents(&self) -> usize {
+        self.synced_agents.read().len()
+    }
+
+    pub fn proxy(&self) -> NanoConsensusProxy<N> {
+        NanoConsensusProxy {
+            blockchain: Arc::clone(&self.blockchain),
+            network: Arc::clone(&self.network),
+            agents: Arc::clone(&self.synced_agents),
+            watched_accounts: Arc::clone(&self.watched_accounts),
+            established_flag: Arc::clone(&self.established_flag),
+            sync_progress: self.sync_progress.clone(),
+        }
+    }
+
+    async fn sync_with_agent(
+        blockchain: Arc<RwLock<NanoBlockchain>>,
+        sync_progress: SyncProgress,
+        agent: Arc<ConsensusAgent<N::PeerType>>,
+    ) -> Result<(), NanoSyncError> {
+        // Jump to the latest election block that the peer has a proof for.
+        match sync_with_zkp(&blockchain, &agent).await {
+            Ok(_) | Err(ZKPSyncError::NoProof) => {}
+            Err(e) => return Err(e.into()),
+        }
+
+        // Push the election blocks after the proof (if the peer didn't prove them yet) and the
+        // latest checkpoint block of the peer.
+        let election_head_hash = blockchain.read().election_head_hash();
+        let block_hashes = agent
+            .request_block_hashes(
+                vec![election_head_hash],
+                Self::MAX_BLOCK_HASHES,
+                RequestBlockHashesFilter::ElectionAndLatestCheckpoint,
+            )
+            .await?;
+
+        // Report the blocks of the peer as its clusters. We only know the number of the latest
+        // checkpoint block once we received it.
+        let election_head_number = blockchain.read().election_head().header.block_number;
+        let num_election_blocks = block_hashes
+            .hashes
+            .iter()
+            .filter(|(ty, _)| *ty == BlockHashType::Election)
+            .count();
+        let has_checkpoint = block_hashes.hashes.len() > num_election_blocks;
+        let clusters =
+            SyncClusterStatus::for_peer(election_head_number, num_election_blocks, has_checkpoint);
+        let mut target_block = if has_checkpoint || num_election_blocks == 0 {
+            None
+        } else {
+            Some(election_head_number + num_election_blocks as u32 * policy::EPOCH_LENGTH)
+        };
+
+        for (ty, hash) in block_hashes.hashes {
+            sync_progress.update_history(
+                SyncStage::History,
+                target_block,
+                0,
+                0,
+                clusters.clone(),
+                blockchain.read().block_number(),
+            );
+
+            let block = match agent.request_block(hash.clone()).await? {
+                Some(block) if block.is_macro() && block.hash() == hash => block,
+                _ => return Err(NanoSyncError::InvalidResponse),
+            };
+
+            if ty == BlockHashType::Checkpoint {
+                target_block = Some(block.block_number());
+            }
+
+            // The peer doesn't know our election head if it starts with an older block. It is
+            // behind us, so we are synced with it.
+            let macro_head_number = blockchain.read().macro_head().header.block_number;
+            if block.block_number() <= macro_head_number {
+                return Ok(());
+            }
+
+            blockchain.write().push_macro(block)?;
+        }
+
+        Ok(())
+    }
+
+    fn start_sync(&mut self, agent: Arc<ConsensusAgent<N::PeerType>>) {
+        let blockchain = Arc::clone(&self.blockchain);
+        let sync_progress = self.sync_progress.clone();
+        let future = async move {
+            let result = Self::sync_with_agent(blockchain, sync_progress, Arc::clone(&agent)).await;
+            (agent, result)
+        }
+        .boxed();
+        self.current_sync = Some(future);
+    }
+
+    /// Reports that we are following the chain once consensus is established. Before that, the
+    /// progress is reported by the sync with the current peer.
+    fn update_sync_progress(&self) {
+        let stage = if self.is_established() {
+            Some(SyncStage::Following)
+        } else {
+            None
+        };
+        self.sync_progress
+            .update_consensus(stage, None, self.blockchain.read().block_number());
+    }
+
+    /// Pushes an announced block. If we miss the blocks before it, they are requested from one of
+    /// our synced peers.
+    fn push_block(&mut self, block: Block) {
+        let result = self.blockchain.write().push(block.clone());
+
+        match result {
+            Ok(_) => {}
+            Err(PushError::Orphan) if self.missing_blocks.is_none() => {
+                let agent = match self.synced_agents.read().first() {
+                    Some(agent) => Arc::clone(agent),
+                    None => return,
+                };
+
+                let blockchain = self.blockchain.read();
+                let locators = vec![blockchain.head_hash(), blockchain.macro_head_hash()];
+                drop(blockchain);
+
+                let future = async move {
+                    let mut blocks = agent
+                        .request_missing_blocks(block.hash(), locators)
+                        .await
+                        .unwrap_or_default();
+                    blocks.push(block);
+                    blocks
+                }
+                .boxed();
+                self.missing_blocks = Some(future);
+            }
+            Err(e) => debug!("Failed to push announced block: {}", e),
+        }
+    }
+
+    /// Requests the accounts of the watched addresses if they weren't requested at our head yet.
+    fn request_watched_accounts(&mut self) {
+        if self.watched_accounts_request.is_some() {
+            return;
+        }
+
+        let head_hash = self.blockchain.read().head_hash();
+        let addresses: Vec<Address> = {
+            let watched_accounts = self.watched_accounts.read();
+            if watched_accounts.accounts.is_empty()
+                || watched_accounts.block_hash.as_ref() == Some(&head_hash)
+            {
+                return;
+            }
+            watched_accounts.accounts.keys().cloned().collect()
+        };
+
+        let blockchain = Arc::clone(&self.blockchain);
+        let agents = self.synced_agents.read().clone();
+        let future = async move {
+            let mut accounts = Vec::with_capacity(addresses.len());
+            for chunk in addresses.chunks(NanoConsensusProxy::<N>::MAX_ADDRESSES) {
+                match NanoConsensusProxy::<N>::request_accounts(
+                    &blockchain,
+                    &agents,
+                    chunk.to_vec(),
+                )
+                .await
+                {
+                    Ok(chunk_accounts) => accounts.extend(chunk_accounts),
+                    Err(_) => return (head_hash, addresses, None),
+                }
+            }
+            (head_hash, addresses, Some(accounts))
+        }
+        .boxed();
+        self.watched_accounts_request = Some(future);
+    }
+
+    fn on_agent_left(&mut self, peer: &Arc<N::PeerType>) -> Option<ConsensusEvent<N>> {
+        // If we are currently syncing with the peer, the sync fails with a request error.
+        self.pending_agents.retain(|agent| &agent.peer != peer);
+
+        let mut synced_agents = self.synced_agents.write();
+        let num_synced_agents = synced_agents.len();
+        synced_agents.retain(|agent| &agent.peer != peer);
+
+        if synced_agents.len() == num_synced_agents {
+            return None;
+        }
+
+        // We lose consensus once we lose all synced peers.
+        if synced_agents.is_empty() && self.is_established() {
+            warn!("Lost consensus!");
+            self.established_flag.swap(false, Ordering::Release);
+            self.update_sync_progress();
+            return Some(ConsensusEvent::Lost);
+        }
+
+        Some(ConsensusEvent::PeerLeft)
+    }
+}
+
+impl<N: Network> Stream for NanoConsensus<N> {
+    type Item = ConsensusEvent<N>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        macro_rules! return_event {
+            ($event:expr) => {
+                self.events.send($event.clone()).ok(); // Ignore result.
+                return Poll::Ready(Some($event));
+            };
+        }
+
+        // 1. Keep track of our peers.
+        while let Poll::Ready(Some(result)) = self.network_event_rx.poll_next_unpin(cx) {
+            match result {
+                Ok(NetworkEvent::PeerLeft(peer)) => {
+                    if let Some(event) = self.on_agent_left(&peer) {
+                        return_event!(event);
+                    }
+                }
+                Ok(NetworkEvent::PeerJoined(peer)) => {
+                    let agent = Arc::new(ConsensusAgent::new(peer));
+                    self.pending_agents.push_back(agent);
+                }
+                Err(_) => return Poll::Ready(None),
+            }
+        }
+
+        // 2. Sync with the peers one after the other.
+        loop {
+            if self.current_sync.is_none() {
+                match self.pending_agents.pop_front() {
+                    Some(agent) => self.start_sync(agent),
+                    None => break,
+                }
+            }
+
+            let (agent, result) = match self
+                .current_sync
+                .as_mut()
+                .expect("current_sync is set")
+                .poll_unpin(cx)
+            {
+                Poll::Ready(result) => result,
+                Poll::Pending => break,
+            };
+            self.current_sync = None;
+
+            match result {
+                Ok(()) => {
+                    debug!("Nano synced with {:?}", agent.peer.id());
+                    self.synced_agents.write().push(agent);
+
+                    if !self.is_established() {
+                        info!("Consensus established.");
+                        self.established_flag.swap(true, Ordering::Release);
+                        self.update_sync_progress();
+                        return_event!(ConsensusEvent::Established);
+                    }
+                }
+                Err(e) => {
+                    log::error!("Nano sync with {:?} failed: {}", agent.peer.id(), e);
+                    agent.peer.close(CloseReason::Other);
+                }
+            }
+        }
+
+        // 3. Follow the announced blocks once consensus is established.
+        while let Poll::Ready(Some(block)) = self.block_stream.poll_next_unpin(cx) {
+            if self.is_established() {
+                self.push_block(block);
+            }
+        }
+
+        // Pushing an announced block may have started a request for missing blocks, which must be
+        // polled so that we are woken up once it completes.
+        if let Some(ref mut missing_blocks) = self.missing_blocks {
+            if let Poll::Ready(blocks) = missing_blocks.poll_unpin(cx) {
+                self.missing_blocks = None;
+                let mut blockchain = self.blockchain.write();
+                for block in blocks {
+                    if let Err(e) = blockchain.push(block) {
+                        debug!("Failed to push missing block: {}", e);
+                        break;
+                    }
+                }
+            }
+        }
+
+        // 4. Keep the accounts of the watched addresses up to date.
+        if self.is_established() {
+            self.request_watched_accounts();
+        }
+
+        if let Some(ref mut request) = self.watched_accounts_request {
+            if let Poll::Ready((block_hash, addresses, accounts)) = request.poll_unpin(cx) {
+                self.watched_accounts_request = None;
+
+                if let Some(accounts) = accounts {
+                    let mut watched_accounts = self.watched_accounts.write();
+                    for (address, account) in addresses.into_iter().zip(accounts) {
+                        // The address might have been unwatched in the meantime.
+                        if let Some(entry) = watched_accounts.accounts.get_mut(&address) {
+                            *entry = Some(account);
+                        }
+                    }
+                    watched_accounts.block_hash = Some(block_hash);
+                }
+            }
+        }
+
+        Poll::Pending
+    }
+}
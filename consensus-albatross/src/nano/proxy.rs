@@ -0,0 +1,211 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use parking_lot::RwLock;
+
+use account::Account;
+use blockchain_albatross::{AbstractBlockchain, ExtTxData, ExtendedTransaction};
+use hash::Blake2bHash;
+use keys::Address;
+use nano_blockchain::NanoBlockchain;
+use network_interface::network::Network;
+use transaction::Transaction;
+
+use crate::consensus::TransactionTopic;
+use crate::consensus_agent::ConsensusAgent;
+use crate::error::NanoRequestError;
+use crate::sync::status::{SyncProgress, SyncStatus};
+
+/// The accounts of the watched addresses, as proven at the block with the given hash.
+#[derive(Default)]
+pub(crate) struct WatchedAccounts {
+    pub(crate) block_hash: Option<Blake2bHash>,
+    pub(crate) accounts: HashMap<Address, Option<Account>>,
+}
+
+/// Gives access to the state of the chain for nano nodes. Nothing is stored locally, every account
+/// and transaction is requested from our synced peers together with a proof, which is verified
+/// against the blocks of our `NanoBlockchain`.
+pub struct NanoConsensusProxy<N: Network> {
+    pub blockchain: Arc<RwLock<NanoBlockchain>>,
+    pub network: Arc<N>,
+    pub(crate) agents: Arc<RwLock<Vec<Arc<ConsensusAgent<N::PeerType>>>>>,
+    pub(crate) watched_accounts: Arc<RwLock<WatchedAccounts>>,
+    pub(crate) established_flag: Arc<AtomicBool>,
+    pub(crate) sync_progress: SyncProgress,
+}
+
+impl<N: Network> Clone for NanoConsensusProxy<N> {
+    fn clone(&self) -> Self {
+        Self {
+            blockchain: Arc::clone(&self.blockchain),
+            network: Arc::clone(&self.network),
+            agents: Arc::clone(&self.agents),
+            watched_accounts: Arc::clone(&self.watched_accounts),
+            established_flag: Arc::clone(&self.established_flag),
+            sync_progress: self.sync_progress.clone(),
+        }
+    }
+}
+
+impl<N: Network> NanoConsensusProxy<N> {
+    /// The maximum number of addresses in an accounts proof request.
+    pub const MAX_ADDRESSES: usize = 128;
+
+    pub async fn send_transaction(&self, tx: Transaction) -> Result<(), N::Error> {
+        self.network.publish(&TransactionTopic::default(), tx).await
+    }
+
+    pub fn is_established(&self) -> bool {
+        self.established_flag.load(Ordering::Acquire)
+    }
+
+    pub fn sync_status(&self) -> SyncStatus {
+        self.sync_progress
+            .status(self.blockchain.read().block_number())
+    }
+
+    /// Requests the accounts with the given addresses at our head and verifies them against its
+    /// state root. Addresses without an account have the initial (empty) account.
+    pub async fn get_accounts(
+        &self,
+        addresses: Vec<Address>,
+    ) -> Result<Vec<Account>, NanoRequestError> {
+        let agents = self.agents.read().clone();
+        Self::request_accounts(&self.blockchain, &agents, addresses).await
+    }
+
+    /// Requests the most recent transactions of the given address in the current epoch and
+    /// verifies them against the history root of one of our blocks. Older transactions can't be
+    /// verified by nano nodes.
+    ///
+    /// The proof only shows that the returned transactions are part of the history, so a peer can
+    /// still omit transactions without being detected. Peers that claim that there are no
+    /// transactions are skipped, and no transactions are returned only if none of the peers sent
+    /// a valid proof.
+    pub async fn get_transactions_by_address(
+        &self,
+        address: Address,
+    ) -> Result<Vec<ExtendedTransaction>, NanoRequestError> {
+        let agents = self.agents.read().clone();
+        let mut has_no_transactions = false;
+
+        for agent in agents {
+            let response = match agent.request_transactions_proof(address.clone()).await {
+                Ok(response) => response,
+                Err(_) => continue,
+            };
+
+            let proof = match response.proof {
+                Some(proof) => proof,
+                None => {
+                    has_no_transactions = true;
+                    continue;
+                }
+            };
+
+            // The peer might be ahead of us, in which case we can't verify the proof.
+            if let Err(e) = self.blockchain.read().check_tx(response.block_hash, &proof) {
+                debug!(
+                    "Invalid transactions proof from {:?}: {}",
+                    agent.peer.id(),
+                    e
+                );
+                continue;
+            }
+
+            if !proof
+                .history
+                .iter()
+                .all(|ext_tx| Self::involves(ext_tx, &address))
+            {
+                debug!(
+                    "Transactions proof from {:?} contains unrelated transactions",
+                    agent.peer.id()
+                );
+                continue;
+            }
+
+            return Ok(proof.history);
+        }
+
+        if has_no_transactions {
+            return Ok(vec![]);
+        }
+
+        Err(NanoRequestError::NoValidResponse)
+    }
+
+    /// Adds an address to the watched addresses. The accounts of the watched addresses are
+    /// requested for every new head, so that they can be returned without a request.
+    pub fn watch_address(&self, address: Address) {
+        let mut watched_accounts = self.watched_accounts.write();
+        if !watched_accounts.accounts.contains_key(&address) {
+            watched_accounts.accounts.insert(address, None);
+            // Make sure that the new account is requested.
+            watched_accounts.block_hash = None;
+        }
+    }
+
+    pub fn unwatch_address(&self, address: &Address) {
+        self.watched_accounts.write().accounts.remove(address);
+    }
+
+    /// Returns the account of a watched address, if it was already requested.
+    pub fn get_watched_account(&self, address: &Address) -> Option<Account> {
+        self.watched_accounts
+            .read()
+            .accounts
+            .get(address)
+            .cloned()
+            .flatten()
+    }
+
+    pub(crate) async fn request_accounts(
+        blockchain: &RwLock<NanoBlockchain>,
+        agents: &[Arc<ConsensusAgent<N::PeerType>>],
+        addresses: Vec<Address>,
+    ) -> Result<Vec<Account>, NanoRequestError> {
+        if addresses.len() > Self::MAX_ADDRESSES {
+            return Err(NanoRequestError::TooManyAddresses);
+        }
+
+        let block_hash = blockchain.read().head_hash();
+
+        for agent in agents {
+            // The proof is `None` if the peer has another head than us.
+            let mut proof = match agent
+                .request_accounts_proof(block_hash.clone(), addresses.clone())
+                .await
+            {
+                Ok(Some(proof)) => proof,
+                _ => continue,
+            };
+
+            if let Err(e) =
+                blockchain
+                    .read()
+                    .check_accounts_proof(&block_hash, &addresses, &mut proof)
+            {
+                debug!("Invalid accounts proof from {:?}: {}", agent.peer.id(), e);
+                continue;
+            }
+
+            let accounts = addresses
+                .iter()
+                .map(|address| proof.get_account(address).unwrap_or(Account::INITIAL))
+                .collect();
+            return Ok(accounts);
+        }
+
+        Err(NanoRequestError::NoValidResponse)
+    }
+
+    fn involves(ext_tx: &ExtendedTransaction, address: &Address) -> bool {
+        match &ext_tx.data {
+            ExtTxData::Basic(tx) => &tx.sender == address || &tx.recipient == address,
+            ExtTxData::Inherent(inherent) => &inherent.target == address,
+        }
+    }
+}
@@ -18,12 +18,15 @@ use utils::math::CeilingDiv;
 
 use crate::consensus_agent::ConsensusAgent;
 use crate::messages::{BatchSetInfo, BlockHashType, HistoryChunk, RequestBlockHashesFilter};
+use crate::sync::status::{SyncClusterStatus, SyncProgress, SyncStage};
 use crate::sync::sync_queue::SyncQueue;
 
 struct PendingBatchSet {
     block: MacroBlock,
     history_len: usize,
     history: Vec<ExtendedTransaction>,
+    /// The number of history chunks that we download for this batch set.
+    num_chunks: usize,
 }
 impl PendingBatchSet {
     fn is_complete(&self) -> bool {
@@ -38,6 +41,7 @@ impl PendingBatchSet {
 pub struct BatchSet {
     block: MacroBlock,
     history: Vec<ExtendedTransaction>,
+    num_chunks: usize,
 }
 
 struct SyncCluster<TPeer: Peer> {
@@ -49,6 +53,12 @@ struct SyncCluster<TPeer: Peer> {
 
     pending_batch_sets: VecDeque<PendingBatchSet>,
 
+    /// The number of history chunks of the pending batch sets and how many of them we downloaded.
+    num_chunks: usize,
+    num_downloaded_chunks: usize,
+    /// The number of the latest block that we received.
+    last_block_number: Option<u32>,
+
     adopted_batch_set: bool,
     blockchain: Arc<Blockchain>,
 }
@@ -89,6 +99,9 @@ impl<TPeer: Peer + 'static> SyncCluster<TPeer> {
             batch_set_queue,
             history_queue,
             pending_batch_sets: VecDeque::with_capacity(Self::NUM_PENDING_BATCH_SETS),
+            num_chunks: 0,
+            num_downloaded_chunks: 0,
+            last_block_number: None,
             adopted_batch_set: false,
             blockchain,
         }
@@ -103,11 +116,14 @@ impl<TPeer: Peer + 'static> SyncCluster<TPeer> {
             return Err(SyncClusterResult::Outdated);
         }
 
+        self.last_block_number = Some(epoch.block.header.block_number);
+
         // Prepare pending info.
         let mut pending_batch_set = PendingBatchSet {
             block: epoch.block,
             history_len: epoch.history_len as usize,
             history: Vec::new(),
+            num_chunks: 0,
         };
 
         // If the block is in the same epoch, add already known history.
@@ -128,11 +144,13 @@ impl<TPeer: Peer + 'static> SyncCluster<TPeer> {
         }
 
         // Queue history chunks for the given epoch for download.
-        let history_chunk_ids = (start_index
+        let history_chunk_ids: Vec<_> = (start_index
             ..((epoch.history_len as usize).ceiling_div(CHUNK_SIZE)))
             .map(|i| (epoch_number, i))
             .collect();
         debug!("Requesting history for ids: {:?}", history_chunk_ids);
+        pending_batch_set.num_chunks = history_chunk_ids.len();
+        self.num_chunks += history_chunk_ids.len();
         self.history_queue.add_ids(history_chunk_ids);
 
         // We keep the epoch in pending_epochs while the history is downloading.
@@ -163,6 +181,7 @@ impl<TPeer: Peer + 'static> SyncCluster<TPeer> {
         // Add the received history chunk to the pending epoch.
         let mut chunk = chunk.history;
         epoch.history.append(&mut chunk);
+        self.num_downloaded_chunks += 1;
 
         Ok(())
     }
@@ -200,6 +219,15 @@ impl<TPeer: Peer + 'static> SyncCluster<TPeer> {
         )
     }
 
+    fn status(&self, checkpoint: bool) -> SyncClusterStatus {
+        SyncClusterStatus {
+            first_epoch: self.epoch_offset as u32,
+            num_blocks: self.ids.len(),
+            num_peers: self.batch_set_queue.num_peers(),
+            checkpoint,
+        }
+    }
+
     fn remove_front(&mut self, at: usize) {
         let mut new_cluster = self.split_off(at);
         new_cluster.adopted_batch_set = self.adopted_batch_set;
@@ -236,9 +264,12 @@ impl<TPeer: Peer + 'static> Stream for SyncCluster<TPeer> {
                     // Emit finished epochs.
                     if self.pending_batch_sets[0].is_complete() {
                         let epoch = self.pending_batch_sets.pop_front().unwrap();
+                        self.num_chunks -= epoch.num_chunks;
+                        self.num_downloaded_chunks -= epoch.num_chunks;
                         let epoch = BatchSet {
                             block: epoch.block,
                             history: epoch.history,
+                            num_chunks: epoch.num_chunks,
                         };
                         return Poll::Ready(Some(Ok(epoch)));
                     }
@@ -335,6 +366,9 @@ pub struct HistorySync<TNetwork: Network> {
     epoch_sync_clusters: Vec<SyncCluster<TNetwork::PeerType>>,
     checkpoint_sync_clusters: Vec<SyncCluster<TNetwork::PeerType>>,
     agents: HashMap<Arc<TNetwork::PeerType>, (Arc<ConsensusAgent<TNetwork::PeerType>>, usize)>,
+    progress: SyncProgress,
+    /// The number of history chunks of the batch sets that we pushed.
+    num_synced_chunks: usize,
 }
 
 impl<TNetwork: Network> HistorySync<TNetwork> {
@@ -351,9 +385,16 @@ impl<TNetwork: Network> HistorySync<TNetwork> {
             epoch_sync_clusters: Vec::new(),
             checkpoint_sync_clusters: Vec::new(),
             agents: HashMap::new(),
+            progress: SyncProgress::default(),
+            num_synced_chunks: 0,
         }
     }
 
+    /// Returns the sync progress, which is kept up to date while the sync runs.
+    pub fn progress(&self) -> SyncProgress {
+        self.progress.clone()
+    }
+
     pub fn agents(&self) -> impl Iterator<Item = &Arc<ConsensusAgent<TNetwork::PeerType>>> {
         self.agents.values().map(|(agent, _)| agent)
     }
@@ -560,10 +601,68 @@ impl<TNetwork: Network> HistorySync<TNetwork> {
     }
 }
 
+impl<TNetwork: Network> HistorySync<TNetwork> {
+    fn update_progress(&self) {
+        let stage =
+            if self.epoch_sync_clusters.is_empty() && !self.checkpoint_sync_clusters.is_empty() {
+                SyncStage::BatchSets
+            } else {
+                SyncStage::History
+            };
+
+        let epoch_clusters = self.epoch_sync_clusters.iter();
+        let checkpoint_clusters = self.checkpoint_sync_clusters.iter();
+
+        // Epoch clusters end at an election block, checkpoint clusters at the block we received.
+        let target_block = epoch_clusters
+            .clone()
+            .filter(|cluster| !cluster.ids.is_empty())
+            .map(|cluster| {
+                policy::election_block_of((cluster.epoch_offset + cluster.ids.len() - 1) as u32)
+            })
+            .chain(
+                checkpoint_clusters
+                    .clone()
+                    .filter_map(|cluster| cluster.last_block_number),
+            )
+            .max();
+
+        let (downloaded_chunks, total_chunks) = epoch_clusters
+            .clone()
+            .chain(checkpoint_clusters.clone())
+            .fold(
+                (self.num_synced_chunks, self.num_synced_chunks),
+                |(downloaded, total), cluster| {
+                    (
+                        downloaded + cluster.num_downloaded_chunks,
+                        total + cluster.num_chunks,
+                    )
+                },
+            );
+
+        let clusters = epoch_clusters
+            .map(|cluster| cluster.status(false))
+            .chain(checkpoint_clusters.map(|cluster| cluster.status(true)))
+            .collect();
+
+        self.progress.update_history(
+            stage,
+            target_block,
+            downloaded_chunks,
+            total_chunks,
+            clusters,
+            self.blockchain.block_number(),
+        );
+    }
+}
+
 impl<TNetwork: Network> Stream for HistorySync<TNetwork> {
     type Item = Arc<ConsensusAgent<TNetwork::PeerType>>;
 
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        // Report the changes of the previous poll, which might have returned early.
+        self.update_progress();
+
         while let Poll::Ready(Some(result)) = self.network_event_rx.poll_next_unpin(cx) {
             match result {
                 Ok(NetworkEvent::PeerLeft(peer)) => {
@@ -624,10 +723,16 @@ impl<TNetwork: Network> Stream for HistorySync<TNetwork> {
                 .expect("sync_clusters no empty");
 
             let result = match ready!(best_cluster.poll_next_unpin(cx)) {
-                Some(Ok(epoch)) => SyncClusterResult::from(
-                    self.blockchain
-                        .push_history_sync(Block::Macro(epoch.block), &epoch.history),
-                ),
+                Some(Ok(epoch)) => {
+                    let result = SyncClusterResult::from(
+                        self.blockchain
+                            .push_history_sync(Block::Macro(epoch.block), &epoch.history),
+                    );
+                    if result == SyncClusterResult::EpochSuccessful {
+                        self.num_synced_chunks += epoch.num_chunks;
+                    }
+                    result
+                }
                 Some(Err(_)) => SyncClusterResult::Error,
                 None => SyncClusterResult::NoMoreEpochs,
             };
@@ -721,10 +826,16 @@ impl<TNetwork: Network> Stream for HistorySync<TNetwork> {
                 result = SyncClusterResult::NoMoreEpochs;
             } else {
                 result = match ready!(best_cluster.poll_next_unpin(cx)) {
-                    Some(Ok(batch)) => SyncClusterResult::from(
-                        self.blockchain
-                            .push_history_sync(Block::Macro(batch.block), &batch.history),
-                    ),
+                    Some(Ok(batch)) => {
+                        let result = SyncClusterResult::from(
+                            self.blockchain
+                                .push_history_sync(Block::Macro(batch.block), &batch.history),
+                        );
+                        if result == SyncClusterResult::EpochSuccessful {
+                            self.num_synced_chunks += batch.num_chunks;
+                        }
+                        result
+                    }
                     Some(Err(e)) => e,
                     None => SyncClusterResult::NoMoreEpochs,
                 };
@@ -772,6 +883,8 @@ impl<TNetwork: Network> Stream for HistorySync<TNetwork> {
             }
         }
 
+        self.update_progress();
+
         Poll::Pending
     }
 }
@@ -812,7 +925,7 @@ mod tests {
             }
         }
 
-        let env1 = VolatileEnvironment::new(10).unwrap();
+        let env1 = VolatileEnvironment::new(Blockchain::NUM_DATABASES).unwrap();
         let blockchain = Arc::new(Blockchain::new(env1, NetworkId::UnitAlbatross).unwrap());
 
         let mut hub = MockHub::default();
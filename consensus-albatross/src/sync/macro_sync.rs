@@ -0,0 +1,356 @@
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::sync::{Arc, Weak};
+
+use futures::future::BoxFuture;
+use futures::task::{Context, Poll};
+use futures::{FutureExt, Stream, StreamExt};
+use tokio::sync::broadcast;
+
+use block_albatross::Block;
+use blockchain_albatross::{AbstractBlockchain, Blockchain, CHUNK_SIZE};
+use hash::Blake2bHash;
+use network_interface::prelude::{CloseReason, Network, NetworkEvent, Peer};
+use primitives::policy;
+use utils::math::CeilingDiv;
+
+use crate::consensus_agent::ConsensusAgent;
+use crate::error::MacroSyncError;
+use crate::messages::RequestBlockHashesFilter;
+use crate::sync::state_sync::{AccountsChunkStore, AccountsTreeSync};
+use crate::sync::status::{SyncClusterStatus, SyncProgress, SyncStage};
+
+type SyncFuture<TPeer> =
+    BoxFuture<'static, (Arc<ConsensusAgent<TPeer>>, Result<(), MacroSyncError>)>;
+
+/// Syncs the chain by only downloading macro blocks. For every peer, we request the election
+/// blocks after our election head and its latest checkpoint block. We then download these blocks,
+/// the history of the epoch of the last block and the accounts tree at the last block, and push
+/// everything with `Blockchain::push_macro_sync`. The history of all other epochs is skipped.
+///
+/// Peers are synced with one after the other. A peer is emitted by the stream once we don't get
+/// any new macro blocks from it anymore. The accounts tree is downloaded from all peers that we
+/// know of (see `AccountsTreeSync`).
+pub struct MacroSync<TNetwork: Network> {
+    blockchain: Arc<Blockchain>,
+    network_event_rx: broadcast::Receiver<NetworkEvent<TNetwork::PeerType>>,
+    /// The agents that we haven't synced with yet.
+    pending_agents: VecDeque<Arc<ConsensusAgent<TNetwork::PeerType>>>,
+    /// The agents that we are synced with.
+    synced_agents: Vec<Weak<ConsensusAgent<TNetwork::PeerType>>>,
+    /// The sync with the agent that we are currently syncing with.
+    current_sync: Option<SyncFuture<TNetwork::PeerType>>,
+    accounts_chunk_store: Arc<AccountsChunkStore>,
+    progress: SyncProgress,
+}
+
+impl<TNetwork: Network> MacroSync<TNetwork> {
+    const MAX_BLOCK_HASHES: u16 = 1000;
+
+    pub fn new(
+        blockchain: Arc<Blockchain>,
+        network_event_rx: broadcast::Receiver<NetworkEvent<TNetwork::PeerType>>,
+    ) -> Self {
+        let accounts_chunk_store = Arc::new(AccountsChunkStore::new(blockchain.env.clone()));
+        Self {
+            blockchain,
+            network_event_rx,
+            pending_agents: VecDeque::new(),
+            synced_agents: Vec::new(),
+            current_sync: None,
+            accounts_chunk_store,
+            progress: SyncProgress::default(),
+        }
+    }
+
+    /// Returns the sync progress, which is kept up to date while the sync runs.
+    pub fn progress(&self) -> SyncProgress {
+        self.progress.clone()
+    }
+
+    async fn sync_with_agent(
+        blockchain: Arc<Blockchain>,
+        accounts_chunk_store: Arc<AccountsChunkStore>,
+        progress: SyncProgress,
+        agent: Arc<ConsensusAgent<TNetwork::PeerType>>,
+        other_agents: Vec<Weak<ConsensusAgent<TNetwork::PeerType>>>,
+    ) -> Result<(), MacroSyncError> {
+        loop {
+            // We can only macro sync on top of a macro block. If we already pushed micro blocks
+            // (e.g. after a restart), the remaining blocks are synced by the block queue.
+            if let Block::Micro(_) = blockchain.head() {
+                return Ok(());
+            }
+
+            let hashes = Self::request_macro_block_hashes(&blockchain, &agent).await?;
+
+            // We are synced with this peer if it doesn't know any macro block that we don't know.
+            let last_hash = match hashes.last() {
+                Some(hash) if !blockchain.contains(hash, true) => hash.clone(),
+                _ => return Ok(()),
+            };
+
+            // If we didn't finish downloading an accounts tree before (e.g. because we were
+            // restarted), we first try to sync to the block of that tree. Peers keep serving the
+            // accounts trees of their last macro blocks for a while, so we can resume the download
+            // even though the peer has moved on. Otherwise, the download is started over below.
+            if let Some(target_hash) = accounts_chunk_store.get_target() {
+                if target_hash != last_hash {
+                    match Self::sync_to_block(
+                        &blockchain,
+                        &accounts_chunk_store,
+                        &progress,
+                        &agent,
+                        &other_agents,
+                        &hashes,
+                        target_hash.clone(),
+                    )
+                    .await
+                    {
+                        Ok(true) => continue,
+                        Ok(false) => {}
+                        Err(e) => debug!(
+                            "Can't resume the download of the accounts tree at {}: {}",
+                            target_hash, e
+                        ),
+                    }
+                }
+            }
+
+            let synced = Self::sync_to_block(
+                &blockchain,
+                &accounts_chunk_store,
+                &progress,
+                &agent,
+                &other_agents,
+                &hashes,
+                last_hash,
+            )
+            .await?;
+            if !synced {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Syncs to the macro block with the given hash. `hashes` are the hashes of the election blocks
+    /// after our election head that the peer sent us, which must include all election blocks
+    /// before the target block. Returns `false` if the target block doesn't succeed our macro
+    /// head.
+    async fn sync_to_block(
+        blockchain: &Blockchain,
+        accounts_chunk_store: &Arc<AccountsChunkStore>,
+        progress: &SyncProgress,
+        agent: &Arc<ConsensusAgent<TNetwork::PeerType>>,
+        other_agents: &[Weak<ConsensusAgent<TNetwork::PeerType>>],
+        hashes: &[Blake2bHash],
+        target_hash: Blake2bHash,
+    ) -> Result<bool, MacroSyncError> {
+        // Get the target block first, so that we don't download the other blocks from a peer
+        // that is behind us.
+        let target_epoch = agent.request_epoch(target_hash.clone()).await?;
+        let target_block = target_epoch.block;
+        if target_block.hash() != target_hash {
+            return Err(MacroSyncError::InvalidResponse);
+        }
+
+        let block_number = target_block.header.block_number;
+        if block_number <= blockchain.macro_head().header.block_number {
+            return Ok(false);
+        }
+
+        // Download the election blocks before the target block.
+        let election_head_number = blockchain.election_head().header.block_number;
+        let num_election_blocks = ((policy::election_block_before(block_number)
+            - election_head_number)
+            / policy::EPOCH_LENGTH) as usize;
+        let election_hashes = hashes
+            .get(..num_election_blocks)
+            .ok_or(MacroSyncError::InvalidResponse)?;
+
+        // Report the blocks that we sync to as the clusters of the peer. If the target block is an
+        // election block, it belongs to the election blocks.
+        let clusters = if target_block.is_election_block() {
+            SyncClusterStatus::for_peer(election_head_number, num_election_blocks + 1, false)
+        } else {
+            SyncClusterStatus::for_peer(election_head_number, num_election_blocks, true)
+        };
+        let num_history_chunks = (target_epoch.history_len as usize).ceiling_div(CHUNK_SIZE);
+        let report_progress = |stage, downloaded_chunks| {
+            progress.update_history(
+                stage,
+                Some(block_number),
+                downloaded_chunks,
+                num_history_chunks,
+                clusters.clone(),
+                blockchain.block_number(),
+            )
+        };
+        report_progress(SyncStage::History, 0);
+
+        let mut blocks = Vec::with_capacity(num_election_blocks + 1);
+        for hash in election_hashes {
+            match agent.request_block(hash.clone()).await? {
+                Some(Block::Macro(block)) if block.hash() == *hash => blocks.push(block),
+                _ => return Err(MacroSyncError::InvalidResponse),
+            }
+        }
+
+        let epoch_number = policy::epoch_at(block_number);
+        let state_root = target_block.header.state_root.clone();
+        blocks.push(target_block);
+        report_progress(SyncStage::BatchSets, 0);
+
+        // Download the history of the epoch of the target block. The peer may have pushed blocks
+        // after it, so we can't verify the chunks against the history root of the target block.
+        // Instead, we drop the transactions of later blocks and the history is verified as a whole
+        // when it is pushed.
+        let mut history = Vec::with_capacity(target_epoch.history_len as usize);
+        for chunk_index in 0..num_history_chunks {
+            let chunk = agent
+                .request_history_chunk(epoch_number, chunk_index)
+                .await?
+                .chunk
+                .ok_or(MacroSyncError::InvalidResponse)?;
+            history.extend(chunk.history);
+            report_progress(SyncStage::BatchSets, chunk_index + 1);
+        }
+        history.retain(|ext_tx| ext_tx.block_number <= block_number);
+
+        let mut peers = vec![Arc::downgrade(agent)];
+        peers.extend(other_agents.iter().cloned());
+        let accounts_chunks = AccountsTreeSync::new(
+            Arc::clone(accounts_chunk_store),
+            target_hash,
+            state_root,
+            peers,
+        )
+        .sync()
+        .await?;
+
+        // The downloaded chunks are not needed anymore, even if they turn out to be invalid.
+        let result = blockchain.push_macro_sync(&blocks, &history, &accounts_chunks);
+        accounts_chunk_store.clear();
+        result?;
+
+        debug!(
+            "Macro synced to block #{} with {:?}",
+            block_number,
+            agent.peer.id()
+        );
+        Ok(true)
+    }
+
+    /// Requests the hashes of the election blocks after our election head and of the latest
+    /// checkpoint block of the peer. The peer sends up to `MAX_BLOCK_HASHES` hashes at a time and
+    /// only adds the checkpoint block if there is room for it, so we request the hashes page by
+    /// page, continuing after the last election block of the previous page.
+    async fn request_macro_block_hashes(
+        blockchain: &Blockchain,
+        agent: &ConsensusAgent<TNetwork::PeerType>,
+    ) -> Result<Vec<Blake2bHash>, MacroSyncError> {
+        let mut hashes = Vec::new();
+        let mut locator = blockchain.election_head_hash();
+
+        loop {
+            let block_hashes = agent
+                .request_block_hashes(
+                    vec![locator],
+                    Self::MAX_BLOCK_HASHES,
+                    RequestBlockHashesFilter::ElectionAndLatestCheckpoint,
+                )
+                .await?;
+
+            let is_last_page = block_hashes.hashes.len() < Self::MAX_BLOCK_HASHES as usize;
+            hashes.extend(block_hashes.hashes.into_iter().map(|(_, hash)| hash));
+
+            match hashes.last() {
+                Some(hash) if !is_last_page => locator = hash.clone(),
+                _ => return Ok(hashes),
+            }
+        }
+    }
+
+    fn start_sync(&mut self, agent: Arc<ConsensusAgent<TNetwork::PeerType>>) {
+        let blockchain = Arc::clone(&self.blockchain);
+        let accounts_chunk_store = Arc::clone(&self.accounts_chunk_store);
+        let progress = self.progress.clone();
+
+        // Forget about the synced agents that were dropped.
+        self.synced_agents
+            .retain(|agent| Weak::upgrade(agent).is_some());
+        let other_agents = self
+            .synced_agents
+            .iter()
+            .cloned()
+            .chain(self.pending_agents.iter().map(Arc::downgrade))
+            .collect();
+
+        let future = async move {
+            let result = Self::sync_with_agent(
+                blockchain,
+                accounts_chunk_store,
+                progress,
+                Arc::clone(&agent),
+                other_agents,
+            )
+            .await;
+            (agent, result)
+        }
+        .boxed();
+        self.current_sync = Some(future);
+    }
+}
+
+impl<TNetwork: Network> Stream for MacroSync<TNetwork> {
+    type Item = Arc<ConsensusAgent<TNetwork::PeerType>>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        while let Poll::Ready(Some(result)) = self.network_event_rx.poll_next_unpin(cx) {
+            match result {
+                Ok(NetworkEvent::PeerLeft(peer)) => {
+                    // If we are currently syncing with the peer, the sync fails with a request
+                    // error.
+                    self.pending_agents.retain(|agent| agent.peer != peer);
+                }
+                Ok(NetworkEvent::PeerJoined(peer)) => {
+                    let agent = Arc::new(ConsensusAgent::new(peer));
+                    self.pending_agents.push_back(agent);
+                }
+                Err(_) => return Poll::Ready(None),
+            }
+        }
+
+        loop {
+            if self.current_sync.is_none() {
+                match self.pending_agents.pop_front() {
+                    Some(agent) => self.start_sync(agent),
+                    None => return Poll::Pending,
+                }
+            }
+
+            let (agent, result) = ready!(self
+                .current_sync
+                .as_mut()
+                .expect("current_sync is set")
+                .poll_unpin(cx));
+            self.current_sync = None;
+
+            match result {
+                Ok(()) => {
+                    self.synced_agents.push(Arc::downgrade(&agent));
+                    return Poll::Ready(Some(agent));
+                }
+                Err(MacroSyncError::MacroHeadChanged) => {
+                    // The peer produced a new macro block while we were syncing. Try again later.
+                    debug!("Macro head of {:?} changed, retrying", agent.peer.id());
+                    self.pending_agents.push_back(agent);
+                }
+                Err(e) => {
+                    log::error!("Macro sync with {:?} failed: {}", agent.peer.id(), e);
+                    agent.peer.close(CloseReason::Other);
+                }
+            }
+        }
+    }
+}
@@ -1,4 +1,7 @@
 pub mod block_queue;
 pub mod history;
+pub mod macro_sync;
 pub mod request_component;
+mod state_sync;
+pub mod status;
 mod sync_queue;
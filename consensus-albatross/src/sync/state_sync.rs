@@ -0,0 +1,366 @@
+use std::str::FromStr;
+use std::sync::{Arc, Weak};
+
+use futures::{FutureExt, StreamExt};
+
+use account::Account;
+use blockchain_albatross::{
+    accounts_chunk_part_start, next_accounts_chunk_prefix, ACCOUNTS_CHUNK_PARTS,
+    ACCOUNTS_CHUNK_SIZE,
+};
+use database::cursor::{ReadCursor, WriteCursor};
+use database::{Database, Environment, ReadTransaction, WriteTransaction};
+use hash::Blake2bHash;
+use network_interface::peer::Peer;
+use tree_primitives::accounts_tree_chunk::AccountsTreeChunk;
+use tree_primitives::address_nibbles::AddressNibbles;
+
+use crate::consensus_agent::ConsensusAgent;
+use crate::error::MacroSyncError;
+use crate::sync::sync_queue::SyncQueue;
+
+/// Persists the accounts tree chunks that were downloaded for a macro block, so that the download
+/// can be resumed after a restart. Chunks are stored by the prefix they were requested with.
+pub struct AccountsChunkStore {
+    env: Environment,
+    database: Database,
+}
+
+impl AccountsChunkStore {
+    const DB_NAME: &'static str = "AccountsSyncChunks";
+    const TARGET_KEY: &'static str = "target";
+
+    pub fn new(env: Environment) -> Self {
+        let database = env.open_database(Self::DB_NAME.to_string());
+        AccountsChunkStore { env, database }
+    }
+
+    /// Returns the hash of the block whose accounts tree is being downloaded.
+    pub fn get_target(&self) -> Option<Blake2bHash> {
+        ReadTransaction::new(&self.env).get(&self.database, Self::TARGET_KEY)
+    }
+
+    /// Sets the block whose accounts tree is being downloaded. Chunks of any other block are
+    /// removed.
+    pub fn set_target(&self, block_hash: &Blake2bHash) {
+        if self.get_target().as_ref() == Some(block_hash) {
+            return;
+        }
+
+        let mut txn = WriteTransaction::new(&self.env);
+        Self::remove_all(&mut txn, &self.database);
+        txn.put(&self.database, Self::TARGET_KEY, block_hash);
+        txn.commit();
+    }
+
+    pub fn get_chunk(&self, start_prefix: &str) -> Option<AccountsTreeChunk<Account>> {
+        ReadTransaction::new(&self.env).get(&self.database, &Self::chunk_key(start_prefix))
+    }
+
+    pub fn put_chunk(&self, start_prefix: &str, chunk: &AccountsTreeChunk<Account>) {
+        let mut txn = WriteTransaction::new(&self.env);
+        txn.put_reserve(&self.database, &Self::chunk_key(start_prefix), chunk);
+        txn.commit();
+    }
+
+    /// Removes the target and all chunks.
+    pub fn clear(&self) {
+        let mut txn = WriteTransaction::new(&self.env);
+        Self::remove_all(&mut txn, &self.database);
+        txn.commit();
+    }
+
+    fn remove_all(txn: &mut WriteTransaction, database: &Database) {
+        let mut cursor = txn.write_cursor(database);
+        let mut pos: Option<(Vec<u8>, Vec<u8>)> = cursor.first();
+
+        while pos.is_some() {
+            cursor.remove();
+            pos = cursor.next();
+        }
+    }
+
+    fn chunk_key(start_prefix: &str) -> String {
+        format!("chunk-{}", start_prefix)
+    }
+}
+
+/// Identifies a chunk of the accounts tree at a macro block. It contains everything needed to
+/// request and verify the chunk, since the request function of a `SyncQueue` can't capture state.
+#[derive(Clone, Debug)]
+pub struct AccountsChunkId {
+    block_hash: Blake2bHash,
+    state_root: Blake2bHash,
+    part: usize,
+    start_prefix: String,
+}
+
+impl AccountsChunkId {
+    /// Verifies that a chunk is the one identified by this id: It must be a valid chunk of the
+    /// accounts tree with our state root, contain at most `ACCOUNTS_CHUNK_SIZE` accounts and only
+    /// accounts following the start prefix. A full chunk must end with an account, since the next
+    /// chunk is requested with its key.
+    fn verify(&self, chunk: &mut AccountsTreeChunk<Account>) -> bool {
+        if chunk.len() > ACCOUNTS_CHUNK_SIZE || !chunk.verify() || chunk.root() != self.state_root {
+            return false;
+        }
+
+        if chunk.len() == ACCOUNTS_CHUNK_SIZE && !chunk.tail().is_terminal() {
+            return false;
+        }
+
+        let start = match AddressNibbles::from_str(&self.start_prefix) {
+            Ok(start) => start,
+            Err(_) => return false,
+        };
+
+        // The accounts are sorted, so it's enough to check the first one. The tail of the last
+        // chunk can be a branch node that proves that there are no more accounts.
+        match chunk
+            .terminal_nodes()
+            .into_iter()
+            .find(|node| node.is_terminal())
+        {
+            Some(node) => &start < node.prefix(),
+            None => true,
+        }
+    }
+}
+
+/// Downloads the accounts tree at a macro block from several peers in parallel. The key space is
+/// split into `ACCOUNTS_CHUNK_PARTS` parts by the first nibble of the keys, and the chunks of all
+/// parts are requested through a `SyncQueue`. Each chunk is verified against the state root of the block
+/// before it is persisted to the `AccountsChunkStore`, so a sync that is interrupted resumes with
+/// the chunks that are still missing.
+///
+/// The last chunk of a part usually contains accounts of the next part as well. They are committed
+/// twice, which doesn't change the tree.
+pub struct AccountsTreeSync<TPeer: Peer> {
+    store: Arc<AccountsChunkStore>,
+    queue: SyncQueue<TPeer, AccountsChunkId, (AccountsChunkId, AccountsTreeChunk<Account>)>,
+    num_pending_parts: usize,
+}
+
+impl<TPeer: Peer + 'static> AccountsTreeSync<TPeer> {
+    const NUM_PENDING_CHUNKS: usize = 16;
+
+    pub fn new(
+        store: Arc<AccountsChunkStore>,
+        block_hash: Blake2bHash,
+        state_root: Blake2bHash,
+        peers: Vec<Weak<ConsensusAgent<TPeer>>>,
+    ) -> Self {
+        store.set_target(&block_hash);
+
+        // Resume the parts that we already downloaded chunks for.
+        let mut ids = Vec::new();
+        for part in 0..ACCOUNTS_CHUNK_PARTS {
+            let (_, next_prefix) = Self::get_stored_part(&store, part);
+            if let Some(start_prefix) = next_prefix {
+                ids.push(AccountsChunkId {
+                    block_hash: block_hash.clone(),
+                    state_root: state_root.clone(),
+                    part,
+                    start_prefix,
+                });
+            }
+        }
+        debug!(
+            "Downloading the accounts tree at {}, {} of {} parts missing",
+            block_hash,
+            ids.len(),
+            ACCOUNTS_CHUNK_PARTS
+        );
+
+        let num_pending_parts = ids.len();
+        let queue = SyncQueue::new(
+            ids,
+            peers,
+            Self::NUM_PENDING_CHUNKS,
+            |id: AccountsChunkId, peer| {
+                async move {
+                    let mut chunk = peer
+                        .request_accounts_chunk(id.block_hash.clone(), id.start_prefix.clone())
+                        .await
+                        .ok()?
+                        .chunk?;
+
+                    if !id.verify(&mut chunk) {
+                        debug!("Received invalid accounts chunk from {:?}", peer.peer.id());
+                        return None;
+                    }
+
+                    Some((id, chunk))
+                }
+                .boxed()
+            },
+        );
+
+        Self {
+            store,
+            queue,
+            num_pending_parts,
+        }
+    }
+
+    /// Downloads the missing chunks and returns all chunks of the accounts tree. Fails if none of
+    /// the peers could send us a valid chunk, e.g. because they moved on to a new macro block.
+    pub async fn sync(mut self) -> Result<Vec<AccountsTreeChunk<Account>>, MacroSyncError> {
+        while self.num_pending_parts > 0 {
+            match self.queue.next().await {
+                Some(Ok((id, chunk))) => self.on_chunk_received(id, chunk),
+                Some(Err(_)) | None => return Err(MacroSyncError::MacroHeadChanged),
+            }
+        }
+
+        let chunks = (0..ACCOUNTS_CHUNK_PARTS)
+            .flat_map(|part| Self::get_stored_part(&self.store, part).0)
+            .collect();
+        Ok(chunks)
+    }
+
+    fn on_chunk_received(&mut self, id: AccountsChunkId, chunk: AccountsTreeChunk<Account>) {
+        self.store.put_chunk(&id.start_prefix, &chunk);
+
+        match next_accounts_chunk_prefix(id.part, &chunk) {
+            Some(start_prefix) => self
+                .queue
+                .add_ids(vec![AccountsChunkId { start_prefix, ..id }]),
+            None => self.num_pending_parts -= 1,
+        }
+    }
+
+    /// Returns the stored chunks of a part and the prefix to request the next chunk with, or `None`
+    /// if the part is complete.
+    fn get_stored_part(
+        store: &AccountsChunkStore,
+        part: usize,
+    ) -> (Vec<AccountsTreeChunk<Account>>, Option<String>) {
+        let mut chunks = Vec::new();
+        let mut start_prefix = accounts_chunk_part_start(part);
+
+        while let Some(chunk) = store.get_chunk(&start_prefix) {
+            let next_prefix = next_accounts_chunk_prefix(part, &chunk);
+            chunks.push(chunk);
+            match next_prefix {
+                Some(prefix) => start_prefix = prefix,
+                None => return (chunks, None),
+            }
+        }
+
+        (chunks, Some(start_prefix))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use blockchain_albatross::{AbstractBlockchain, Blockchain};
+    use nimiq_database::volatile::VolatileEnvironment;
+    use nimiq_genesis::NetworkId;
+    use nimiq_network_mock::MockPeer;
+
+    #[test]
+    fn it_resumes_from_stored_chunks() {
+        let env = VolatileEnvironment::new(Blockchain::NUM_DATABASES + 1).unwrap();
+        let blockchain = Blockchain::new(env.clone(), NetworkId::UnitAlbatross).unwrap();
+        let store = AccountsChunkStore::new(env);
+
+        // The genesis accounts fit into a single chunk, which completes the first part.
+        let block_hash = blockchain.macro_head_hash();
+        let chunk = blockchain.get_accounts_chunk(&block_hash, "").unwrap();
+        store.set_target(&block_hash);
+        store.put_chunk("", &chunk);
+
+        let (chunks, next_prefix) = AccountsTreeSync::<MockPeer>::get_stored_part(&store, 0);
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(next_prefix, None);
+
+        let (chunks, next_prefix) = AccountsTreeSync::<MockPeer>::get_stored_part(&store, 1);
+        assert!(chunks.is_empty());
+        assert_eq!(next_prefix, Some("1".to_string()));
+
+        // Chunks of another block are dropped.
+        store.set_target(&Blake2bHash::default());
+        let (chunks, next_prefix) = AccountsTreeSync::<MockPeer>::get_stored_part(&store, 0);
+        assert!(chunks.is_empty());
+        assert_eq!(next_prefix, Some(String::new()));
+    }
+
+    #[test]
+    fn it_rejects_chunks_that_were_not_requested() {
+        let env = VolatileEnvironment::new(Blockchain::NUM_DATABASES).unwrap();
+        let blockchain = Blockchain::new(env, NetworkId::UnitAlbatross).unwrap();
+
+        let block_hash = blockchain.macro_head_hash();
+        let state_root = blockchain.macro_head().header.state_root;
+        let mut chunk = blockchain.get_accounts_chunk(&block_hash, "").unwrap();
+        let chunk_id = |state_root: &Blake2bHash, start_prefix: String| AccountsChunkId {
+            block_hash: block_hash.clone(),
+            state_root: state_root.clone(),
+            part: 0,
+            start_prefix,
+        };
+
+        assert!(chunk_id(&state_root, String::new()).verify(&mut chunk));
+
+        // The chunk must belong to the accounts tree with our state root.
+        assert!(!chunk_id(&Blake2bHash::default(), String::new()).verify(&mut chunk));
+
+        // The chunk must not contain accounts up to the start prefix.
+        let tail_prefix = chunk.last_terminal_string().unwrap();
+        assert!(!chunk_id(&state_root, tail_prefix).verify(&mut chunk));
+    }
+
+    #[tokio::test]
+    async fn it_resumes_a_partial_download_after_a_restart() {
+        let env = VolatileEnvironment::new(Blockchain::NUM_DATABASES + 1).unwrap();
+        let blockchain = Blockchain::new(env.clone(), NetworkId::UnitAlbatross).unwrap();
+        let block_hash = blockchain.macro_head_hash();
+        let state_root = blockchain.macro_head().header.state_root;
+        let chunk_id = |part| AccountsChunkId {
+            block_hash: block_hash.clone(),
+            state_root: state_root.clone(),
+            part,
+            start_prefix: accounts_chunk_part_start(part),
+        };
+
+        // Download the first part and restart.
+        let store = Arc::new(AccountsChunkStore::new(env.clone()));
+        let mut sync = AccountsTreeSync::<MockPeer>::new(
+            store,
+            block_hash.clone(),
+            state_root.clone(),
+            vec![],
+        );
+        assert_eq!(sync.num_pending_parts, ACCOUNTS_CHUNK_PARTS);
+
+        let chunk = blockchain.get_accounts_chunk(&block_hash, "").unwrap();
+        sync.on_chunk_received(chunk_id(0), chunk);
+        drop(sync);
+
+        // After the restart, only the other parts are missing.
+        let store = Arc::new(AccountsChunkStore::new(env));
+        assert_eq!(store.get_target(), Some(block_hash.clone()));
+        let mut sync = AccountsTreeSync::<MockPeer>::new(
+            store,
+            block_hash.clone(),
+            state_root.clone(),
+            vec![],
+        );
+        assert_eq!(sync.num_pending_parts, ACCOUNTS_CHUNK_PARTS - 1);
+
+        for part in 1..ACCOUNTS_CHUNK_PARTS {
+            let id = chunk_id(part);
+            let chunk = blockchain
+                .get_accounts_chunk(&block_hash, &id.start_prefix)
+                .unwrap();
+            sync.on_chunk_received(id, chunk);
+        }
+
+        // The download is complete and contains the chunks of all parts.
+        let chunks = sync.sync().await.unwrap();
+        assert_eq!(chunks.len(), ACCOUNTS_CHUNK_PARTS);
+    }
+}
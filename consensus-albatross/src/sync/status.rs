@@ -0,0 +1,303 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use parking_lot::RwLock;
+
+use primitives::policy;
+
+/// The stages that a node goes through while syncing, in order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SyncStage {
+    /// Downloading the election blocks and the history of the epochs that we are missing. Macro
+    /// syncing and nano nodes only download the election blocks.
+    History,
+    /// Downloading the latest checkpoint block and the history of the current epoch up to it.
+    /// Macro syncing also downloads the accounts tree at the block.
+    BatchSets,
+    /// Catching up with the blocks after the latest macro block through the block queue.
+    BlockQueue,
+    /// Synced, following the blocks that are announced on the network.
+    Following,
+}
+
+impl Default for SyncStage {
+    fn default() -> Self {
+        SyncStage::History
+    }
+}
+
+/// A cluster of peers that agree on the election blocks (or the checkpoint block) that we
+/// download from them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SyncClusterStatus {
+    /// The epoch of the first block of the cluster.
+    pub first_epoch: u32,
+    /// The number of blocks in the cluster.
+    pub num_blocks: usize,
+    pub num_peers: usize,
+    /// Whether the cluster consists of a checkpoint block instead of election blocks. Checkpoint
+    /// clusters always have a single block.
+    pub checkpoint: bool,
+}
+
+impl SyncClusterStatus {
+    /// Returns the clusters of a sync that downloads the macro blocks of a single peer: the given
+    /// number of election blocks after our election head at `election_head_number` and, if
+    /// `checkpoint` is set, the checkpoint block of the epoch after them.
+    pub(crate) fn for_peer(
+        election_head_number: u32,
+        num_election_blocks: usize,
+        checkpoint: bool,
+    ) -> Vec<Self> {
+        let first_epoch = policy::epoch_at(election_head_number) + 1;
+
+        let mut clusters = Vec::new();
+        if num_election_blocks > 0 {
+            clusters.push(SyncClusterStatus {
+                first_epoch,
+                num_blocks: num_election_blocks,
+                num_peers: 1,
+                checkpoint: false,
+            });
+        }
+        if checkpoint {
+            clusters.push(SyncClusterStatus {
+                first_epoch: first_epoch + num_election_blocks as u32,
+                num_blocks: 1,
+                num_peers: 1,
+                checkpoint: true,
+            });
+        }
+        clusters
+    }
+}
+
+/// A snapshot of the sync progress, see `SyncProgress::status`.
+#[derive(Clone, Debug)]
+pub struct SyncStatus {
+    pub stage: SyncStage,
+    /// The number of our head block.
+    pub block_number: u32,
+    /// The epoch that we are syncing to, if we know it.
+    pub target_epoch: Option<u32>,
+    /// The block that we are syncing to, if we know it.
+    pub target_block: Option<u32>,
+    /// The number of history chunks that we downloaded, out of `total_chunks` that we know of so
+    /// far. Chunks become known once the batch set that they belong to is downloaded.
+    pub downloaded_chunks: usize,
+    pub total_chunks: usize,
+    pub clusters: Vec<SyncClusterStatus>,
+    /// The estimated time until we reach the target block, based on our progress so far.
+    pub eta: Option<Duration>,
+}
+
+impl SyncStatus {
+    /// The share of the blocks up to the target block that we have, between 0 and 1.
+    pub fn progress(&self) -> Option<f64> {
+        self.target_block.map(|target_block| {
+            if target_block == 0 || self.block_number >= target_block {
+                1.0
+            } else {
+                f64::from(self.block_number) / f64::from(target_block)
+            }
+        })
+    }
+}
+
+#[derive(Default)]
+struct Progress {
+    /// Set by the history sync.
+    history_stage: SyncStage,
+    target_epoch: Option<u32>,
+    target_block: Option<u32>,
+    downloaded_chunks: usize,
+    total_chunks: usize,
+    clusters: Vec<SyncClusterStatus>,
+
+    /// Set by the consensus once we have synced peers. Takes precedence over the history stage.
+    consensus_stage: Option<SyncStage>,
+    block_queue_target: Option<u32>,
+
+    /// When and at which block we started syncing towards the current target, and the target.
+    start: Option<(Instant, u32, u32)>,
+}
+
+impl Progress {
+    fn stage(&self) -> SyncStage {
+        self.consensus_stage.unwrap_or(self.history_stage)
+    }
+
+    fn target_block(&self) -> Option<u32> {
+        match self.stage() {
+            SyncStage::Following => None,
+            SyncStage::BlockQueue => self.block_queue_target.or(self.target_block),
+            _ => self.target_block,
+        }
+    }
+
+    fn update_start(&mut self, block_number: u32) {
+        let target_block = match self.target_block() {
+            Some(target_block) if self.stage() != SyncStage::Following => target_block,
+            _ => {
+                self.start = None;
+                return;
+            }
+        };
+
+        // Our speed towards a previous target doesn't tell us how long it takes to reach a new
+        // one, e.g. if the block queue catches up faster than the history sync.
+        if self.start.map(|(_, _, start_target)| start_target) != Some(target_block) {
+            self.start = Some((Instant::now(), block_number, target_block));
+        }
+    }
+
+    fn eta(&self, block_number: u32) -> Option<Duration> {
+        let (start_time, start_block, _) = self.start?;
+        let target_block = self.target_block()?;
+
+        if block_number >= target_block {
+            return Some(Duration::from_secs(0));
+        }
+        if block_number <= start_block {
+            return None;
+        }
+
+        let remaining = f64::from(target_block - block_number);
+        let done = f64::from(block_number - start_block);
+        Some(start_time.elapsed().mul_f64(remaining / done))
+    }
+}
+
+/// Keeps track of the sync progress. It is updated by the sync protocol (the `HistorySync` or the
+/// `MacroSync`) and the `Consensus`, or by the `NanoConsensus`, and can be cloned to read it from
+/// elsewhere, e.g. from the `ConsensusProxy`.
+#[derive(Clone, Default)]
+pub struct SyncProgress {
+    inner: Arc<RwLock<Progress>>,
+}
+
+impl SyncProgress {
+    pub fn status(&self, block_number: u32) -> SyncStatus {
+        let progress = self.inner.read();
+
+        SyncStatus {
+            stage: progress.stage(),
+            block_number,
+            target_epoch: progress.target_epoch,
+            target_block: progress.target_block(),
+            downloaded_chunks: progress.downloaded_chunks,
+            total_chunks: progress.total_chunks,
+            clusters: progress.clusters.clone(),
+            eta: progress.eta(block_number),
+        }
+    }
+
+    /// The target epoch is the last epoch of the given clusters.
+    pub(crate) fn update_history(
+        &self,
+        stage: SyncStage,
+        target_block: Option<u32>,
+        downloaded_chunks: usize,
+        total_chunks: usize,
+        clusters: Vec<SyncClusterStatus>,
+        block_number: u32,
+    ) {
+        let mut progress = self.inner.write();
+        progress.history_stage = stage;
+        progress.target_epoch = clusters
+            .iter()
+            .filter(|cluster| cluster.num_blocks > 0)
+            .map(|cluster| cluster.first_epoch + cluster.num_blocks as u32 - 1)
+            .max();
+        progress.target_block = target_block;
+        progress.downloaded_chunks = downloaded_chunks;
+        progress.total_chunks = total_chunks;
+        progress.clusters = clusters;
+        progress.update_start(block_number);
+    }
+
+    pub(crate) fn update_consensus(
+        &self,
+        stage: Option<SyncStage>,
+        block_queue_target: Option<u32>,
+        block_number: u32,
+    ) {
+        let mut progress = self.inner.write();
+        progress.consensus_stage = stage;
+        progress.block_queue_target = block_queue_target;
+        progress.update_start(block_number);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_prefers_the_consensus_stage() {
+        let progress = SyncProgress::default();
+        progress.update_history(
+            SyncStage::BatchSets,
+            Some(250),
+            3,
+            5,
+            vec![SyncClusterStatus {
+                first_epoch: 2,
+                num_blocks: 1,
+                num_peers: 3,
+                checkpoint: true,
+            }],
+            100,
+        );
+
+        let status = progress.status(100);
+        assert_eq!(status.stage, SyncStage::BatchSets);
+        assert_eq!(status.target_epoch, Some(2));
+        assert_eq!(status.target_block, Some(250));
+        assert_eq!(status.progress(), Some(0.4));
+
+        progress.update_consensus(Some(SyncStage::BlockQueue), Some(260), 250);
+        let status = progress.status(250);
+        assert_eq!(status.stage, SyncStage::BlockQueue);
+        assert_eq!(status.target_block, Some(260));
+
+        progress.update_consensus(Some(SyncStage::Following), None, 260);
+        let status = progress.status(260);
+        assert_eq!(status.stage, SyncStage::Following);
+        assert_eq!(status.target_block, None);
+        assert!(status.eta.is_none());
+    }
+
+    #[test]
+    fn it_estimates_the_remaining_time() {
+        let progress = SyncProgress::default();
+        progress.update_history(SyncStage::History, Some(400), 0, 0, vec![], 0);
+
+        // We don't know our speed before making progress.
+        assert!(progress.status(0).eta.is_none());
+
+        assert_eq!(progress.status(400).eta, Some(Duration::from_secs(0)));
+        assert!(progress.status(100).eta.is_some());
+
+        // We start over once the target changes.
+        progress.update_consensus(Some(SyncStage::BlockQueue), Some(450), 400);
+        assert!(progress.status(420).eta.is_some());
+        assert!(progress.status(400).eta.is_none());
+    }
+
+    #[test]
+    fn it_reports_the_clusters_of_a_peer() {
+        let clusters = SyncClusterStatus::for_peer(policy::election_block_of(2), 3, true);
+        assert_eq!(clusters.len(), 2);
+        assert_eq!(clusters[0].first_epoch, 3);
+        assert_eq!(clusters[0].num_blocks, 3);
+        assert_eq!(clusters[1].first_epoch, 6);
+        assert!(clusters[1].checkpoint);
+
+        let progress = SyncProgress::default();
+        progress.update_history(SyncStage::History, None, 0, 0, clusters, 0);
+        assert_eq!(progress.status(0).target_epoch, Some(6));
+
+        assert!(SyncClusterStatus::for_peer(0, 0, false).is_empty());
+    }
+}
@@ -0,0 +1,92 @@
+use std::sync::Arc;
+
+use futures::StreamExt;
+use parking_lot::RwLock;
+
+use beserial::{Deserialize, Serialize};
+use block_albatross::{Block, MacroBlock};
+use blockchain_albatross::{AbstractBlockchain, PushResult};
+use nano_blockchain::NanoBlockchain;
+use nano_sync::NanoZKP;
+use network_interface::prelude::{Network, Peer};
+
+use crate::consensus_agent::ConsensusAgent;
+use crate::error::ZKPSyncError;
+use crate::messages::{RequestZKP, ZKPResponse};
+
+pub use self::store::ZKPStore;
+
+#[cfg(feature = "zkp-prover")]
+pub mod prover;
+pub mod store;
+
+/// A zero-knowledge proof that there is a valid chain of election blocks from the genesis block to
+/// `block`. It allows nano nodes to sync to the latest election block without downloading any of
+/// the blocks in between.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ZKProof {
+    /// The election block that the proof ends at.
+    pub block: MacroBlock,
+    /// The serialized proof (see `NanoZKP::serialize_proof`).
+    #[beserial(len_type(u32))]
+    pub proof: Vec<u8>,
+}
+
+/// Answers the proof requests of other nodes with the latest proof in the given store. Nodes that
+/// don't generate proofs answer with an empty response, so that the requesting node can move on
+/// to the next peer immediately.
+pub fn init_zkp_requests<N: Network>(network: &Arc<N>, store: Arc<ZKPStore>) {
+    let mut stream = network.receive_from_all::<RequestZKP>();
+    tokio::spawn(async move {
+        while let Some((msg, peer)) = stream.next().await {
+            trace!("[REQUEST_ZKP] received from {:?}", peer.id());
+
+            let response = ZKPResponse {
+                proof: store.get_proof(),
+                request_identifier: msg.request_identifier,
+            };
+
+            // We do not care about the result.
+            let _ = peer.send(&response).await;
+        }
+    });
+}
+
+/// Requests the latest proof from a peer, verifies it and pushes its election block into the nano
+/// blockchain. Returns `PushResult::Known` if the proof doesn't get us any further than our current
+/// election head. A proof that can't be verified, e.g. because the verification panicked on a
+/// malformed proof, fails the sync with the peer.
+pub async fn sync_with_zkp<P: Peer>(
+    blockchain: &RwLock<NanoBlockchain>,
+    agent: &ConsensusAgent<P>,
+) -> Result<PushResult, ZKPSyncError> {
+    let zkp = agent.request_zkp().await?.ok_or(ZKPSyncError::NoProof)?;
+
+    let election_head_number = blockchain.read().election_head().header.block_number;
+    if zkp.block.header.block_number <= election_head_number {
+        return Ok(PushResult::Known);
+    }
+
+    let proof = NanoZKP::deserialize_proof(&zkp.proof)?;
+
+    // Verify the proof on a blocking task and without holding a lock on the blockchain, since it
+    // takes up to a second.
+    let genesis_block = blockchain.read().genesis_block.clone();
+    let block = Block::Macro(zkp.block);
+    let block = tokio::task::spawn_blocking(move || {
+        NanoBlockchain::verify_zkp(&genesis_block, &block, proof).map(|_| block)
+    })
+    .await
+    .map_err(|_| ZKPSyncError::VerificationFailed)??;
+
+    let mut blockchain = blockchain.write();
+
+    // We may have synced further while verifying.
+    if block.block_number() <= blockchain.election_head().header.block_number {
+        return Ok(PushResult::Known);
+    }
+
+    let result = blockchain.push_verified_zkp(block)?;
+
+    Ok(result)
+}
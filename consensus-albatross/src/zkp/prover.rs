@@ -0,0 +1,224 @@
+use std::sync::Arc;
+
+use ark_mnt6_753::G2Projective;
+use futures::StreamExt;
+
+use block_albatross::MacroBlock;
+use blockchain_albatross::{AbstractBlockchain, Blockchain, BlockchainEvent};
+use nano_primitives::{state_commitment, MacroBlock as NanoMacroBlock};
+use nano_sync::{NanoZKP, NanoZKPError};
+use primitives::policy;
+
+use crate::zkp::{ZKPStore, ZKProof};
+
+/// Generates the election-chain proof for every new election block and stores it in the
+/// `ZKPStore`, from where it is served to nano nodes. Each proof is built on top of the proof for
+/// the previous election block, so the prover catches up epoch by epoch if it falls behind (e.g.
+/// after a restart).
+///
+/// Generating a proof takes many hours and needs the proving keys in the `proving_keys` directory.
+/// Intermediate proofs are cached in the `proofs` directory, so an interrupted proof isn't started
+/// from scratch.
+pub struct ZKPProver;
+
+impl ZKPProver {
+    pub fn spawn(blockchain: Arc<Blockchain>, store: Arc<ZKPStore>) {
+        let mut blockchain_events = blockchain.notifier.write().as_stream();
+
+        tokio::spawn(async move {
+            // Prove the epochs that were finalized while we weren't running.
+            Self::prove_in_background(&blockchain, &store).await;
+
+            while let Some(event) = blockchain_events.next().await {
+                if let BlockchainEvent::EpochFinalized(_) = event {
+                    Self::prove_in_background(&blockchain, &store).await;
+                }
+            }
+        });
+    }
+
+    async fn prove_in_background(blockchain: &Arc<Blockchain>, store: &Arc<ZKPStore>) {
+        let blockchain = Arc::clone(blockchain);
+        let store = Arc::clone(store);
+
+        let result =
+            tokio::task::spawn_blocking(move || Self::prove_missing_epochs(&blockchain, &store))
+                .await
+                .expect("ZKP prover panicked");
+
+        if let Err(e) = result {
+            error!("Failed to generate the election-chain proof: {}", e);
+        }
+    }
+
+    /// Generates the proofs for all election blocks after the one of the stored proof, up to our
+    /// current election head.
+    fn prove_missing_epochs(blockchain: &Blockchain, store: &ZKPStore) -> Result<(), NanoZKPError> {
+        let genesis_block =
+            Self::get_election_block(blockchain, 0).expect("The genesis block is missing");
+
+        let (mut prev_block, mut prev_proof) = match store.get_proof() {
+            Some(zkp) => {
+                let proof = NanoZKP::deserialize_proof(&zkp.proof)?;
+                (zkp.block, Some(proof))
+            }
+            None => (genesis_block.clone(), None),
+        };
+
+        let genesis_data = Self::state_commitment(&genesis_block);
+
+        let election_head_number = blockchain.election_head().header.block_number;
+
+        while prev_block.header.block_number < election_head_number {
+            let block_number = prev_block.header.block_number + policy::EPOCH_LENGTH;
+
+            // Blocks of skipped epochs are missing after a macro sync. We can't prove anything
+            // after them.
+            let block = match Self::get_election_block(blockchain, block_number) {
+                Some(block) => block,
+                None => {
+                    warn!(
+                        "Can't prove epoch of block #{}, it is missing",
+                        block_number
+                    );
+                    return Ok(());
+                }
+            };
+
+            info!(
+                "Generating the election-chain proof for block #{}",
+                block_number
+            );
+
+            let proof = NanoZKP::prove(
+                Self::public_keys(&prev_block),
+                <[u8; 32]>::from(prev_block.hash()),
+                Self::public_keys(&block),
+                Self::nano_block(&block),
+                prev_proof.map(|proof| (proof, genesis_data.clone())),
+                true,
+                false,
+            )?;
+
+            store.put_proof(&ZKProof {
+                block: block.clone(),
+                proof: NanoZKP::serialize_proof(&proof)?,
+            });
+
+            info!(
+                "Generated the election-chain proof for block #{}",
+                block_number
+            );
+
+            prev_block = block;
+            prev_proof = Some(proof);
+        }
+
+        Ok(())
+    }
+
+    fn get_election_block(blockchain: &Blockchain, block_number: u32) -> Option<MacroBlock> {
+        blockchain
+            .get_block_at(block_number, true, None)
+            .filter(|block| block.is_election())
+            .map(|block| block.unwrap_macro())
+    }
+
+    fn public_keys(block: &MacroBlock) -> Vec<G2Projective> {
+        block
+            .get_validators()
+            .expect("Election block without validators")
+            .to_pks()
+            .iter()
+            .map(|pk| pk.public_key)
+            .collect()
+    }
+
+    fn state_commitment(block: &MacroBlock) -> Vec<u8> {
+        state_commitment(
+            block.header.block_number,
+            <[u8; 32]>::from(block.hash()),
+            Self::public_keys(block),
+        )
+    }
+
+    /// Converts a block into the representation used by the circuits.
+    fn nano_block(block: &MacroBlock) -> NanoMacroBlock {
+        let justification = block
+            .justification
+            .as_ref()
+            .expect("Election block without justification");
+
+        let mut signer_bitmap = vec![false; policy::SLOTS as usize];
+        for signer in justification.sig.signers.iter() {
+            signer_bitmap[signer] = true;
+        }
+
+        NanoMacroBlock {
+            block_number: block.header.block_number,
+            round_number: justification.round,
+            header_hash: <[u8; 32]>::from(block.hash()),
+            signature: justification.sig.signature.0.signature,
+            signer_bitmap,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use nano_sync::utils::create_test_blocks;
+
+    use super::*;
+
+    /// Proves the first epoch of the test blocks of nano-sync and verifies the proof after
+    /// serializing it like the `ZKPStore` does. Generating the parameters (if they don't exist yet) and the proof
+    /// takes hours, so this test only runs when it's asked for.
+    #[test]
+    #[ignore]
+    fn it_can_prove_and_verify_an_epoch() {
+        if !Path::new("verifying_keys/merger_wrapper.bin").exists() {
+            NanoZKP::setup().unwrap();
+        }
+
+        let (initial_pks, initial_header_hash, final_pks, block, _) = create_test_blocks(0);
+        let final_block_number = block.block_number;
+        let final_header_hash = block.header_hash;
+
+        let proof = NanoZKP::prove(
+            initial_pks.clone(),
+            initial_header_hash,
+            final_pks.clone(),
+            block,
+            None,
+            false,
+            false,
+        )
+        .unwrap();
+        let proof = NanoZKP::deserialize_proof(&NanoZKP::serialize_proof(&proof).unwrap()).unwrap();
+
+        assert!(NanoZKP::verify(
+            0,
+            initial_header_hash,
+            initial_pks.clone(),
+            final_block_number,
+            final_header_hash,
+            final_pks.clone(),
+            proof.clone(),
+        )
+        .unwrap());
+
+        // The proof doesn't hold for another block.
+        assert!(!NanoZKP::verify(
+            0,
+            initial_header_hash,
+            initial_pks,
+            final_block_number,
+            [0; 32],
+            final_pks,
+            proof,
+        )
+        .unwrap());
+    }
+}
@@ -0,0 +1,88 @@
+use std::io;
+
+use beserial::{Deserialize, Serialize};
+use database::{
+    Database, Environment, FromDatabaseValue, IntoDatabaseValue, ReadTransaction, WriteTransaction,
+};
+
+use crate::zkp::ZKProof;
+
+/// Persists the latest election-chain proof, so that it doesn't have to be generated again after a
+/// restart. Only the proof for the latest election block is kept, since every proof covers the
+/// whole chain up to its block.
+pub struct ZKPStore {
+    env: Environment,
+    database: Database,
+}
+
+impl ZKPStore {
+    const DB_NAME: &'static str = "NanoZKP";
+    const PROOF_KEY: &'static str = "proof";
+
+    pub fn new(env: Environment) -> Self {
+        let database = env.open_database(Self::DB_NAME.to_string());
+        ZKPStore { env, database }
+    }
+
+    pub fn get_proof(&self) -> Option<ZKProof> {
+        ReadTransaction::new(&self.env).get(&self.database, Self::PROOF_KEY)
+    }
+
+    pub fn put_proof(&self, proof: &ZKProof) {
+        let mut txn = WriteTransaction::new(&self.env);
+        txn.put_reserve(&self.database, Self::PROOF_KEY, proof);
+        txn.commit();
+    }
+}
+
+impl IntoDatabaseValue for ZKProof {
+    fn database_byte_size(&self) -> usize {
+        self.serialized_size()
+    }
+
+    fn copy_into_database(&self, mut bytes: &mut [u8]) {
+        Serialize::serialize(&self, &mut bytes).unwrap();
+    }
+}
+
+impl FromDatabaseValue for ZKProof {
+    fn copy_from_database(bytes: &[u8]) -> io::Result<Self>
+    where
+        Self: Sized,
+    {
+        let mut cursor = io::Cursor::new(bytes);
+        Ok(Deserialize::deserialize(&mut cursor)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use blockchain_albatross::{AbstractBlockchain, Blockchain};
+    use nimiq_database::volatile::VolatileEnvironment;
+    use nimiq_genesis::NetworkId;
+
+    #[test]
+    fn it_stores_the_latest_proof() {
+        let env = VolatileEnvironment::new(Blockchain::NUM_DATABASES + 1).unwrap();
+        let blockchain = Blockchain::new(env.clone(), NetworkId::UnitAlbatross).unwrap();
+        let store = ZKPStore::new(env);
+
+        assert!(store.get_proof().is_none());
+
+        let block = blockchain.election_head();
+        store.put_proof(&ZKProof {
+            block: block.clone(),
+            proof: vec![1, 2, 3],
+        });
+        store.put_proof(&ZKProof {
+            block: block.clone(),
+            proof: vec![4, 5],
+        });
+
+        let zkp = store.get_proof().unwrap();
+        assert_eq!(zkp.block.hash(), block.hash());
+        assert_eq!(zkp.proof, vec![4, 5]);
+    }
+}
@@ -103,7 +103,7 @@ impl<P: Peer> Stream for MockRequestComponent<P> {
 async fn send_single_micro_block_to_block_queue() {
     let keypair =
         KeyPair::from(SecretKey::deserialize_from_vec(&hex::decode(SECRET_KEY).unwrap()).unwrap());
-    let env = VolatileEnvironment::new(10).unwrap();
+    let env = VolatileEnvironment::new(Blockchain::NUM_DATABASES).unwrap();
     let blockchain = Arc::new(Blockchain::new(env, NetworkId::UnitAlbatross).unwrap());
     let mempool = Mempool::new(Arc::clone(&blockchain), MempoolConfig::default());
     let producer = BlockProducer::new(Arc::clone(&blockchain), Arc::clone(&mempool), keypair);
@@ -118,8 +118,11 @@ async fn send_single_micro_block_to_block_queue() {
     );
 
     // push one micro block to the queue
-    let block =
-        Block::Micro(producer.next_micro_block(blockchain.time.now(), 0, None, vec![], vec![0x42]));
+    let block = Block::Micro(
+        producer
+            .next_micro_block(blockchain.time.now(), 0, None, vec![], vec![0x42])
+            .unwrap(),
+    );
     tx.send(block).await.unwrap();
 
     assert_eq!(blockchain.block_number(), 0);
@@ -136,8 +139,8 @@ async fn send_single_micro_block_to_block_queue() {
 async fn send_two_micro_blocks_out_of_order() {
     let keypair =
         KeyPair::from(SecretKey::deserialize_from_vec(&hex::decode(SECRET_KEY).unwrap()).unwrap());
-    let env1 = VolatileEnvironment::new(10).unwrap();
-    let env2 = VolatileEnvironment::new(10).unwrap();
+    let env1 = VolatileEnvironment::new(Blockchain::NUM_DATABASES).unwrap();
+    let env2 = VolatileEnvironment::new(Blockchain::NUM_DATABASES).unwrap();
     let blockchain1 = Arc::new(Blockchain::new(env1, NetworkId::UnitAlbatross).unwrap());
     let blockchain2 = Arc::new(Blockchain::new(env2, NetworkId::UnitAlbatross).unwrap());
     let mempool = Mempool::new(Arc::clone(&blockchain2), MempoolConfig::default());
@@ -153,21 +156,17 @@ async fn send_two_micro_blocks_out_of_order() {
         rx.boxed(),
     );
 
-    let block1 = Block::Micro(producer.next_micro_block(
-        blockchain2.time.now(),
-        0,
-        None,
-        vec![],
-        vec![0x42],
-    ));
+    let block1 = Block::Micro(
+        producer
+            .next_micro_block(blockchain2.time.now(), 0, None, vec![], vec![0x42])
+            .unwrap(),
+    );
     blockchain2.push(block1.clone()).unwrap(); // push it, so the producer actually produces a block at height 2
-    let block2 = Block::Micro(producer.next_micro_block(
-        blockchain2.time.now() + 1000,
-        0,
-        None,
-        vec![],
-        vec![0x42],
-    ));
+    let block2 = Block::Micro(
+        producer
+            .next_micro_block(blockchain2.time.now() + 1000, 0, None, vec![], vec![0x42])
+            .unwrap(),
+    );
 
     // send block2 first
     tx.send(block2.clone()).await.unwrap();
@@ -208,8 +207,8 @@ async fn send_block_with_gap_and_respond_to_missing_request() {
 
     let keypair =
         KeyPair::from(SecretKey::deserialize_from_vec(&hex::decode(SECRET_KEY).unwrap()).unwrap());
-    let env1 = VolatileEnvironment::new(10).unwrap();
-    let env2 = VolatileEnvironment::new(10).unwrap();
+    let env1 = VolatileEnvironment::new(Blockchain::NUM_DATABASES).unwrap();
+    let env2 = VolatileEnvironment::new(Blockchain::NUM_DATABASES).unwrap();
     let blockchain1 = Arc::new(Blockchain::new(env1, NetworkId::UnitAlbatross).unwrap());
     let blockchain2 = Arc::new(Blockchain::new(env2, NetworkId::UnitAlbatross).unwrap());
     let mempool = Mempool::new(Arc::clone(&blockchain2), MempoolConfig::default());
@@ -225,21 +224,17 @@ async fn send_block_with_gap_and_respond_to_missing_request() {
         rx.boxed(),
     );
 
-    let block1 = Block::Micro(producer.next_micro_block(
-        blockchain2.time.now(),
-        0,
-        None,
-        vec![],
-        vec![0x42],
-    ));
+    let block1 = Block::Micro(
+        producer
+            .next_micro_block(blockchain2.time.now(), 0, None, vec![], vec![0x42])
+            .unwrap(),
+    );
     blockchain2.push(block1.clone()).unwrap(); // push it, so the producer actually produces a block at height 2
-    let block2 = Block::Micro(producer.next_micro_block(
-        blockchain2.time.now() + 1000,
-        0,
-        None,
-        vec![],
-        vec![0x42],
-    ));
+    let block2 = Block::Micro(
+        producer
+            .next_micro_block(blockchain2.time.now() + 1000, 0, None, vec![], vec![0x42])
+            .unwrap(),
+    );
 
     // send block2 first
     tx.send(block2.clone()).await.unwrap();
@@ -30,7 +30,7 @@ async fn peers_can_sync() {
     let mut hub = MockHub::default();
 
     // Setup first peer.
-    let env1 = VolatileEnvironment::new(10).unwrap();
+    let env1 = VolatileEnvironment::new(Blockchain::NUM_DATABASES).unwrap();
     let blockchain1 = Arc::new(Blockchain::new(env1.clone(), NetworkId::UnitAlbatross).unwrap());
     let mempool1 = Mempool::new(Arc::clone(&blockchain1), MempoolConfig::default());
 
@@ -61,7 +61,7 @@ async fn peers_can_sync() {
     .await;
 
     // Setup second peer (not synced yet).
-    let env2 = VolatileEnvironment::new(10).unwrap();
+    let env2 = VolatileEnvironment::new(Blockchain::NUM_DATABASES).unwrap();
     let blockchain2 = Arc::new(Blockchain::new(env2.clone(), NetworkId::UnitAlbatross).unwrap());
     let mempool2 = Mempool::new(Arc::clone(&blockchain2), MempoolConfig::default());
 
@@ -93,7 +93,7 @@ async fn peers_can_sync() {
 
     // FIXME: Add more tests
     //    // Setup third peer (not synced yet).
-    //    let env3 = VolatileEnvironment::new(10).unwrap();
+    //    let env3 = VolatileEnvironment::new(Blockchain::NUM_DATABASES).unwrap();
     //    let blockchain3 = Arc::new(Blockchain::new(env3.clone(), NetworkId::UnitAlbatross).unwrap());
     //    let mempool3 = Mempool::new(Arc::clone(&blockchain3), MempoolConfig::default());
     //
@@ -165,7 +165,7 @@ async fn sync_ingredients() {
     let mut hub = MockHub::default();
 
     // Setup first peer.
-    let env1 = VolatileEnvironment::new(10).unwrap();
+    let env1 = VolatileEnvironment::new(Blockchain::NUM_DATABASES).unwrap();
     let blockchain1 = Arc::new(Blockchain::new(env1.clone(), NetworkId::UnitAlbatross).unwrap());
     let mempool1 = Mempool::new(Arc::clone(&blockchain1), MempoolConfig::default());
 
@@ -195,7 +195,7 @@ async fn sync_ingredients() {
     .await;
 
     // Setup second peer (not synced yet).
-    let env2 = VolatileEnvironment::new(10).unwrap();
+    let env2 = VolatileEnvironment::new(Blockchain::NUM_DATABASES).unwrap();
     let blockchain2 = Arc::new(Blockchain::new(env2.clone(), NetworkId::UnitAlbatross).unwrap());
     let mempool2 = Mempool::new(Arc::clone(&blockchain2), MempoolConfig::default());
 
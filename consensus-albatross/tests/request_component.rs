@@ -28,7 +28,7 @@ struct Node {
 
 impl Node {
     pub async fn new(hub: &mut MockHub) -> Self {
-        let env = VolatileEnvironment::new(10).unwrap();
+        let env = VolatileEnvironment::new(Blockchain::NUM_DATABASES).unwrap();
 
         let blockchain = Arc::new(Blockchain::new(env.clone(), NetworkId::UnitAlbatross).unwrap());
 
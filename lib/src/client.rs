@@ -15,6 +15,10 @@ use nimiq_network_libp2p::{
 };
 use nimiq_utils::time::OffsetTime;
 
+#[cfg(feature = "validator")]
+use nimiq_signer::{LocalSigner, RemoteSigner, Signer};
+#[cfg(feature = "validator")]
+use nimiq_validator::duty::DutyTracker;
 #[cfg(feature = "validator")]
 use nimiq_validator::validator::Validator as AbstractValidator;
 #[cfg(feature = "validator")]
@@ -22,9 +26,13 @@ use nimiq_validator_network::network_impl::ValidatorNetworkImpl;
 #[cfg(feature = "wallet")]
 use nimiq_wallet::WalletStore;
 
-use crate::config::config::ClientConfig;
+use crate::config::config::{ClientConfig, ConsensusConfig, NetworkConfig as ClientNetworkConfig};
 use crate::error::Error;
 use nimiq_consensus_albatross::sync::history::HistorySync;
+use nimiq_consensus_albatross::sync::macro_sync::MacroSync;
+#[cfg(feature = "zkp-prover")]
+use nimiq_consensus_albatross::zkp::prover::ZKPProver;
+use nimiq_consensus_albatross::zkp::{init_zkp_requests, ZKPStore};
 use nimiq_network_libp2p::libp2p::futures::StreamExt;
 
 /// Alias for the Consensus and Validator specialized over libp2p network
@@ -56,55 +64,90 @@ pub(crate) struct ClientInner {
     wallet_store: Arc<WalletStore>,
 }
 
+/// Creates the libp2p network from the config. It doesn't listen or connect to any peers yet (see
+/// `start_network`), so that its events can be subscribed to first.
+pub(crate) async fn create_network(config: &ClientConfig) -> Result<Arc<Network>, Error> {
+    // Get network info (i.e. which specific blokchain we're on)
+    if !config.network_id.is_albatross() {
+        return Err(Error::config_error(&format!(
+            "{} is not compatible with Albatross",
+            config.network_id
+        )));
+    }
+    let network_info = NetworkInfo::from_network_id(config.network_id);
+
+    // Initialize clock
+    let time = Arc::new(OffsetTime::new());
+
+    // Load identity keypair from file store
+    let identity_keypair = config.storage.identity_keypair()?;
+    log::info!("Identity public key: {:?}", identity_keypair.public());
+
+    // Generate peer contact from identity keypair and services/protocols
+    let mut peer_contact = PeerContact::new(
+        config.network.listen_addresses.clone(),
+        identity_keypair.public(),
+        Services::all(), // TODO
+        None,
+    );
+    peer_contact.set_current_time();
+
+    // Setup libp2p network
+    let mut network_config = NetworkConfig::new(
+        identity_keypair,
+        peer_contact,
+        network_info.genesis_hash().clone(),
+    );
+    if let Some(min_peers) = config.network.min_peers {
+        network_config.min_peers = min_peers;
+    }
+
+    log::debug!("listen_addresses = {:?}", config.network.listen_addresses);
+
+    Ok(Arc::new(Network::new(time, network_config).await))
+}
+
+/// Tells the network to listen on the configured addresses and to connect to the seed nodes.
+pub(crate) async fn start_network(
+    network: &Network,
+    config: &ClientNetworkConfig,
+) -> Result<(), Error> {
+    // Tell the network to listen on the given addresses
+    network
+        .listen_on_addresses(config.listen_addresses.clone())
+        .await;
+
+    // Tell the network to connect to seed nodes
+    for seed in &config.seeds {
+        log::debug!("Dialing seed: {:?}", seed);
+        network.dial_address(seed.address.clone()).await?;
+    }
+
+    Ok(())
+}
+
 impl ClientInner {
     async fn from_config(
         config: ClientConfig,
     ) -> Result<(Self, Consensus, Option<Validator>), Error> {
-        // Get network info (i.e. which specific blokchain we're on)
-        if !config.network_id.is_albatross() {
-            return Err(Error::config_error(&format!(
-                "{} is not compatible with Albatross",
-                config.network_id
-            )));
+        if config.consensus == ConsensusConfig::Nano {
+            return Err(Error::config_error(
+                "Nano consensus is only supported by the NanoClient",
+            ));
         }
-        let network_info = NetworkInfo::from_network_id(config.network_id);
-
-        // Initialize clock
-        let time = Arc::new(OffsetTime::new());
-
-        // Load identity keypair from file store
-        let identity_keypair = config.storage.identity_keypair()?;
-        log::info!("Identity public key: {:?}", identity_keypair.public());
-
-        // Generate peer contact from identity keypair and services/protocols
-        let mut peer_contact = PeerContact::new(
-            config.network.listen_addresses.clone(),
-            identity_keypair.public(),
-            Services::all(), // TODO
-            None,
-        );
-        peer_contact.set_current_time();
-
-        // Setup libp2p network
-        let mut network_config = NetworkConfig::new(
-            identity_keypair,
-            peer_contact,
-            network_info.genesis_hash().clone(),
-        );
-        if let Some(min_peers) = config.network.min_peers {
-            network_config.min_peers = min_peers;
-        }
-
-        log::debug!("listen_addresses = {:?}", config.network.listen_addresses);
 
-        let network = Arc::new(Network::new(Arc::clone(&time), network_config).await);
+        let network = create_network(&config).await?;
 
         // Start buffering network events as early as possible
         let nw_events = network.subscribe_events();
 
-        // Load validator key (before we give away ownership of the storage config)
+        // Load validator key (before we give away ownership of the storage config), unless a remote
+        // signer holds it.
         #[cfg(feature = "validator")]
-        let validator_key = config.storage.validator_key()?;
+        let validator_key = match config.validator.as_ref() {
+            Some(validator_config) if validator_config.remote_signer.is_some() => None,
+            _ => Some(config.storage.validator_key()?),
+        };
 
         // Open database
         let environment =
@@ -118,70 +161,97 @@ impl ClientInner {
         #[cfg(feature = "wallet")]
         let wallet_store = Arc::new(WalletStore::new(environment.clone()));
 
-        let sync = HistorySync::<Network>::new(Arc::clone(&blockchain), nw_events);
+        // Serve the election-chain proof to nano nodes. We only have one if we generate it.
+        let zkp_store = Arc::new(ZKPStore::new(environment.clone()));
+        init_zkp_requests(&network, Arc::clone(&zkp_store));
+        if config.zkp_prover {
+            #[cfg(feature = "zkp-prover")]
+            ZKPProver::spawn(Arc::clone(&blockchain), zkp_store);
+            #[cfg(not(feature = "zkp-prover"))]
+            log::warn!("Client is compiled without zkp-prover and thus can't generate proofs.");
+        }
+
+        let (sync, sync_progress) = match config.consensus {
+            ConsensusConfig::Full => {
+                let sync = HistorySync::<Network>::new(Arc::clone(&blockchain), nw_events);
+                let sync_progress = sync.progress();
+                (sync.boxed(), sync_progress)
+            }
+            ConsensusConfig::MacroSync => {
+                let sync = MacroSync::<Network>::new(Arc::clone(&blockchain), nw_events);
+                let sync_progress = sync.progress();
+                (sync.boxed(), sync_progress)
+            }
+            ConsensusConfig::Nano => unreachable!("Nano consensus is rejected above"),
+        };
 
         let consensus = Consensus::from_network(
             environment.clone(),
             blockchain,
             mempool,
             Arc::clone(&network),
-            sync.boxed(),
+            sync,
         )
-        .await;
+        .await
+        .with_sync_progress(sync_progress);
 
-        // Tell the network to listen on the given addresses
-        network
-            .listen_on_addresses(config.network.listen_addresses)
-            .await;
-
-        // Tell the network to connect to seed nodes
-        for seed in &config.network.seeds {
-            log::debug!("Dialing seed: {:?}", seed);
-            network.dial_address(seed.address.clone()).await?;
-        }
+        start_network(&network, &config.network).await?;
 
         #[cfg(feature = "validator")]
         let validator = {
             if let Some(config) = &config.validator {
-                #[cfg(not(feature = "wallet"))]
-                let validator_wallet_key = {
-                    log::warn!("Client is compiled without wallet and thus can't load the wallet account for the validator.");
-                    None
-                };
+                let signer: Arc<dyn Signer> = if let Some(remote_signer) = &config.remote_signer {
+                    log::info!("Connecting to remote signer at {}", remote_signer.address);
+                    Arc::new(RemoteSigner::connect(remote_signer.clone()).map_err(|e| {
+                        Error::config_error(format!("Failed to connect to remote signer: {}", e))
+                    })?)
+                } else {
+                    #[cfg(not(feature = "wallet"))]
+                    let validator_wallet_key = {
+                        log::warn!("Client is compiled without wallet and thus can't load the wallet account for the validator.");
+                        None
+                    };
 
-                #[cfg(feature = "wallet")]
-                let validator_wallet_key = {
-                    if let Some(wallet_account) = &config.wallet_account {
-                        let address = wallet_account.parse().map_err(|_| {
-                            Error::config_error(format!(
-                                "Failed to parse validator wallet address: {}",
-                                wallet_account
-                            ))
-                        })?;
-                        let locked = wallet_store.get(&address, None).ok_or_else(|| {
-                            Error::config_error(format!(
-                                "Could not find wallet account: {}",
-                                wallet_account
-                            ))
-                        })?;
-                        let unlocked = locked
-                            .unlock(
-                                config
-                                    .wallet_password
-                                    .clone()
-                                    .unwrap_or_default()
-                                    .as_bytes(),
-                            )
-                            .map_err(|_| {
+                    #[cfg(feature = "wallet")]
+                    let validator_wallet_key = {
+                        if let Some(wallet_account) = &config.wallet_account {
+                            let address = wallet_account.parse().map_err(|_| {
                                 Error::config_error(format!(
-                                    "Failed to unlock validator wallet account: {}",
+                                    "Failed to parse validator wallet address: {}",
                                     wallet_account
                                 ))
                             })?;
-                        Some(unlocked.key_pair.clone())
-                    } else {
-                        None
-                    }
+                            let locked = wallet_store.get(&address, None).ok_or_else(|| {
+                                Error::config_error(format!(
+                                    "Could not find wallet account: {}",
+                                    wallet_account
+                                ))
+                            })?;
+                            let unlocked = locked
+                                .unlock(
+                                    config
+                                        .wallet_password
+                                        .clone()
+                                        .unwrap_or_default()
+                                        .as_bytes(),
+                                )
+                                .map_err(|_| {
+                                    Error::config_error(format!(
+                                        "Failed to unlock validator wallet account: {}",
+                                        wallet_account
+                                    ))
+                                })?;
+                            Some(unlocked.key_pair.clone())
+                        } else {
+                            None
+                        }
+                    };
+
+                    // The validator key is always loaded if there is no remote signer.
+                    Arc::new(LocalSigner::new(
+                        validator_key.unwrap(),
+                        validator_wallet_key,
+                    ))
                 };
 
                 let validator_network = Arc::new(ValidatorNetworkImpl::new(Arc::clone(&network)));
@@ -189,8 +259,8 @@ impl ClientInner {
                 let validator = Validator::new(
                     &consensus,
                     validator_network,
-                    validator_key,
-                    validator_wallet_key,
+                    signer,
+                    config.auto_unpark.clone(),
                 );
 
                 Some(validator)
@@ -287,6 +357,15 @@ impl Client {
         self.validator.take()
     }
 
+    /// Returns the duty tracker of the *Validator* or `None`. The duty tracker stays usable after
+    /// the validator was taken.
+    #[cfg(feature = "validator")]
+    pub fn validator_duty_tracker(&self) -> Option<Arc<DutyTracker>> {
+        self.validator
+            .as_ref()
+            .map(|validator| validator.duty_tracker())
+    }
+
     /// Returns the database environment.
     pub fn environment(&self) -> Environment {
         self.inner.environment.clone()
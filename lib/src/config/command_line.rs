@@ -53,7 +53,7 @@ pub struct CommandLine {
     #[structopt(long)]
     pub passive: bool,
 
-    /// Configure consensus type, one of full (default), or macro-sync
+    /// Configure consensus type, one of full (default), macro-sync or nano
     ///
     /// # Examples
     ///
@@ -15,12 +15,24 @@ use nimiq_database::{
     volatile::VolatileEnvironment,
     Environment,
 };
-use nimiq_mempool::{filter::Rules as MempoolRules, MempoolConfig};
+#[cfg(feature = "validator")]
+use nimiq_keys::{
+    KeyPair as AuthKeyPair, PrivateKey as AuthPrivateKey, PublicKey as AuthPublicKey,
+};
+use nimiq_mempool::{
+    filter::Rules as MempoolRules, policy::PolicyConfig as MempoolPolicyConfig, MempoolConfig,
+};
 use nimiq_network_libp2p::{Keypair as IdentityKeypair, Multiaddr};
 use nimiq_primitives::networks::NetworkId;
+#[cfg(feature = "rpc-server")]
+use nimiq_rpc_server::auth::{MethodGroup, RpcUser};
+#[cfg(feature = "validator")]
+use nimiq_signer::{RemoteSignerConfig, SignerAddress};
 use nimiq_utils::file_store::FileStore;
 #[cfg(feature = "validator")]
 use nimiq_utils::key_rng::SecureGenerate;
+#[cfg(feature = "validator")]
+use nimiq_validator::unpark::AutoUnparkConfig;
 
 use crate::{
     client::Client,
@@ -37,7 +49,11 @@ use crate::{
 ///
 /// # Notes
 ///
-/// core-rs / Albatross is currently only supporting full consensus.
+/// `MacroSync` only downloads the macro blocks and the accounts tree at the latest macro block and
+/// afterwards behaves like `Full`.
+///
+/// `Nano` only follows the block headers and requests the accounts and transactions it needs
+/// together with proofs from other nodes. It doesn't store anything and can't run a validator.
 ///
 /// # ToDo
 ///
@@ -47,6 +63,7 @@ use crate::{
 pub enum ConsensusConfig {
     Full,
     MacroSync,
+    Nano,
 }
 
 impl Default for ConsensusConfig {
@@ -146,6 +163,14 @@ pub struct ValidatorConfig {
     pub wallet_account: Option<String>,
     #[builder(default)]
     pub wallet_password: Option<String>,
+    /// If set, the validator sends unpark transactions on its own when it gets parked. This
+    /// requires a wallet account.
+    #[builder(default)]
+    pub auto_unpark: Option<AutoUnparkConfig>,
+    /// If set, the validator key and the wallet key are kept by this signer instead of being
+    /// loaded into the client.
+    #[builder(default)]
+    pub remote_signer: Option<RemoteSignerConfig>,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
@@ -208,8 +233,8 @@ pub struct DatabaseConfig {
     #[builder(default = "50 * 1024 * 1024")]
     size: usize,
 
-    /// Max number of DBs. Recommended: 10
-    #[builder(default = "12")]
+    /// Max number of DBs. Recommended: 20
+    #[builder(default = "20")]
     max_dbs: u32,
 
     /// Additional LMDB flags
@@ -221,7 +246,7 @@ impl Default for DatabaseConfig {
     fn default() -> Self {
         Self {
             size: 50 * 1024 * 1024,
-            max_dbs: 12,
+            max_dbs: 20,
             flags: LmdbFlags::NOMETASYNC,
         }
     }
@@ -435,6 +460,26 @@ pub struct RpcServerConfig {
     /// If specified, require HTTP basic auth with these credentials
     #[builder(setter(strip_option))]
     pub credentials: Option<Credentials>,
+
+    /// The users of the RPC server, each with the method groups granted by its role. This replaces
+    /// `credentials` and `allowed_methods` if not empty.
+    ///
+    #[builder(default)]
+    pub users: Vec<RpcUser>,
+
+    /// Whether to accept WebSocket connections. Subscriptions are only available over WebSocket.
+    ///
+    /// Default: `true`
+    ///
+    #[builder(default = "true")]
+    pub enable_websocket: bool,
+
+    /// The maximum number of requests in a batch.
+    ///
+    /// Default: `1000`
+    ///
+    #[builder(default = "consts::RPC_DEFAULT_MAX_BATCH_SIZE")]
+    pub max_batch_size: usize,
 }
 
 #[cfg(feature = "metrics-server")]
@@ -479,6 +524,14 @@ pub struct ClientConfig {
     #[builder(default)]
     pub consensus: ConsensusConfig,
 
+    /// Generates the election-chain proof after each election block, so that it can be served to
+    /// nano nodes. This needs the `zkp-prover` feature.
+    ///
+    /// Default is `false`.
+    ///
+    #[builder(default)]
+    pub zkp_prover: bool,
+
     /// The `ProtocolConfig` that determines how the client accepts incoming connections. This
     /// will also determine how the client advertises itself to the network.
     ///
@@ -593,6 +646,12 @@ impl ClientConfigBuilder {
         self.consensus(ConsensusConfig::MacroSync)
     }
 
+    /// Sets the client to run as a nano node, which only follows the block headers.
+    ///
+    pub fn nano(&mut self) -> &mut Self {
+        self.consensus(ConsensusConfig::Nano)
+    }
+
     /*
     /// Sets the reverse proxy configuration. You need to set this if you run your node behind
     /// a reverse proxy.
@@ -623,10 +682,17 @@ impl ClientConfigBuilder {
 
     /// Sets the mempool filter rules
     pub fn mempool(&mut self, filter_rules: MempoolRules, filter_limit: usize) -> &mut Self {
-        self.mempool = Some(MempoolConfig {
-            filter_rules,
-            filter_limit,
-        });
+        let mempool = self.mempool.get_or_insert_with(MempoolConfig::default);
+        mempool.filter_rules = filter_rules;
+        mempool.filter_limit = filter_limit;
+        self
+    }
+
+    /// Sets the mempool admission policy
+    pub fn mempool_policy(&mut self, policy: MempoolPolicyConfig) -> &mut Self {
+        self.mempool
+            .get_or_insert_with(MempoolConfig::default)
+            .policy = policy;
         self
     }
 
@@ -641,6 +707,8 @@ impl ClientConfigBuilder {
         self.validator = Some(Some(ValidatorConfig {
             wallet_account: Some(wallet_account),
             wallet_password,
+            auto_unpark: None,
+            remote_signer: None,
         }));
         self
     }
@@ -670,6 +738,7 @@ impl ClientConfigBuilder {
 
         // Configure consensus
         self.consensus(config_file.consensus.consensus_type);
+        self.zkp_prover(config_file.consensus.zkp_prover);
 
         // Configure network
         self.network_id(config_file.consensus.network);
@@ -696,6 +765,11 @@ impl ClientConfigBuilder {
         // Configure database
         self.database(config_file.database.clone());
 
+        // Configure mempool
+        if let Some(mempool_config) = &config_file.mempool {
+            self.mempool = Some(mempool_config.clone().into());
+        }
+
         // Configure RPC server
         #[cfg(feature = "rpc-server")]
         {
@@ -729,6 +803,41 @@ impl ClientConfigBuilder {
                     }
                 };
 
+                let users = rpc_config
+                    .users
+                    .iter()
+                    .map(|user| {
+                        let groups = rpc_config
+                            .roles
+                            .get(&user.role)
+                            .ok_or_else(|| {
+                                Error::config_error(format!("Unknown RPC role: {}", user.role))
+                            })?
+                            .iter()
+                            .map(|group| {
+                                group
+                                    .parse::<MethodGroup>()
+                                    .map_err(|e| Error::config_error(e.to_string()))
+                            })
+                            .collect::<Result<Vec<MethodGroup>, Error>>()?;
+
+                        if user.password.is_none() && user.token.is_none() {
+                            return Err(Error::config_error(format!(
+                                "RPC user {} needs a password or a token",
+                                user.name
+                            )));
+                        }
+
+                        Ok(RpcUser {
+                            name: user.name.clone(),
+                            password: user.password.clone(),
+                            token: user.token.clone(),
+                            groups,
+                            rate_limit: user.rate_limit,
+                        })
+                    })
+                    .collect::<Result<Vec<RpcUser>, Error>>()?;
+
                 self.rpc_server = Some(Some(RpcServerConfig {
                     bind_to,
                     port: rpc_config.port.unwrap_or(consts::RPC_DEFAULT_PORT),
@@ -736,6 +845,11 @@ impl ClientConfigBuilder {
                     allow_ips,
                     allowed_methods: Some(rpc_config.methods.clone()),
                     credentials,
+                    users,
+                    enable_websocket: rpc_config.websocket.unwrap_or(true),
+                    max_batch_size: rpc_config
+                        .max_batch_size
+                        .unwrap_or(consts::RPC_DEFAULT_MAX_BATCH_SIZE),
                 }));
             }
         }
@@ -769,6 +883,22 @@ impl ClientConfigBuilder {
                 self.validator = Some(Some(ValidatorConfig {
                     wallet_account: validator_config.wallet_account.to_owned(),
                     wallet_password: validator_config.wallet_password.to_owned(),
+                    auto_unpark: validator_config.auto_unpark.as_ref().map(|auto_unpark| {
+                        AutoUnparkConfig {
+                            fee: auto_unpark.fee.unwrap_or_default(),
+                            retry_interval: auto_unpark
+                                .retry_interval
+                                .unwrap_or(AutoUnparkConfig::DEFAULT_RETRY_INTERVAL),
+                            max_transactions_per_epoch: auto_unpark
+                                .max_transactions_per_epoch
+                                .unwrap_or(AutoUnparkConfig::DEFAULT_MAX_TRANSACTIONS_PER_EPOCH),
+                        }
+                    }),
+                    remote_signer: validator_config
+                        .remote_signer
+                        .as_ref()
+                        .map(Self::remote_signer_config)
+                        .transpose()?,
                 }));
             }
         }
@@ -776,6 +906,38 @@ impl ClientConfigBuilder {
         Ok(self)
     }
 
+    #[cfg(feature = "validator")]
+    fn remote_signer_config(
+        settings: &config_file::RemoteSignerSettings,
+    ) -> Result<RemoteSignerConfig, Error> {
+        let address = settings.address.parse::<SignerAddress>().map_err(|_| {
+            Error::config_error(format!(
+                "Invalid remote signer address: {}",
+                settings.address
+            ))
+        })?;
+        let auth_key = settings
+            .auth_key
+            .parse::<AuthPrivateKey>()
+            .map_err(|_| Error::config_error("Invalid remote signer authentication key"))?;
+        let server_key = settings.server_key.parse::<AuthPublicKey>().map_err(|_| {
+            Error::config_error(format!(
+                "Invalid remote signer server key: {}",
+                settings.server_key
+            ))
+        })?;
+
+        Ok(RemoteSignerConfig {
+            address,
+            auth_key: AuthKeyPair::from(auth_key),
+            server_key,
+            timeout: settings
+                .timeout
+                .map(std::time::Duration::from_secs)
+                .unwrap_or(RemoteSignerConfig::DEFAULT_TIMEOUT),
+        })
+    }
+
     /// Applies settings from the command line
     pub fn command_line(&mut self, command_line: &CommandLine) -> Result<&mut Self, Error> {
         // Set consensus type
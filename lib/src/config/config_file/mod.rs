@@ -10,7 +10,8 @@ use thiserror::Error;
 
 use nimiq_mempool::{
     filter::{MempoolFilter, Rules as MempoolRules},
-    MempoolConfig,
+    policy::PolicyConfig as MempoolPolicyConfig,
+    MempoolConfig, SIZE_MAX as MEMPOOL_SIZE_MAX,
 };
 use nimiq_peer_address::{address, protocol}; // TODO: probably not needed anymore
 use nimiq_primitives::{coin::Coin, networks::NetworkId};
@@ -177,6 +178,8 @@ pub struct ConsensusSettings {
     pub consensus_type: ConsensusType,
     #[serde(default)]
     pub network: Network,
+    #[serde(default)]
+    pub zkp_prover: bool,
 }
 
 #[derive(Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
@@ -184,6 +187,7 @@ pub struct ConsensusSettings {
 pub enum ConsensusType {
     Full,
     MacroSync,
+    Nano,
 }
 
 impl Default for ConsensusType {
@@ -203,6 +207,7 @@ impl FromStr for ConsensusType {
         Ok(match s.to_lowercase().as_str() {
             "full" => Self::Full,
             "macro-sync" => Self::MacroSync,
+            "nano" => Self::Nano,
             _ => return Err(ConsensusTypeParseError(s.to_string())),
         })
     }
@@ -213,6 +218,7 @@ impl From<ConsensusType> for config::ConsensusConfig {
         match consensus_type {
             ConsensusType::Full => Self::Full,
             ConsensusType::MacroSync => Self::MacroSync,
+            ConsensusType::Nano => Self::Nano,
         }
     }
 }
@@ -276,6 +282,24 @@ pub struct RpcServerSettings {
     pub methods: Vec<String>,
     pub username: Option<String>,
     pub password: Option<String>,
+    pub websocket: Option<bool>,
+    pub max_batch_size: Option<usize>,
+    /// The method groups granted by each role.
+    #[serde(default)]
+    pub roles: HashMap<String, Vec<String>>,
+    #[serde(default)]
+    pub users: Vec<RpcUserSettings>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RpcUserSettings {
+    pub name: String,
+    pub role: String,
+    pub password: Option<String>,
+    pub token: Option<String>,
+    /// Maximum number of requests per minute.
+    pub rate_limit: Option<usize>,
 }
 
 #[derive(Clone, Debug, Deserialize, Default)]
@@ -362,7 +386,7 @@ impl Default for DatabaseSettings {
         DatabaseSettings {
             path: None,
             size: Some(1024 * 1024 * 50),
-            max_dbs: Some(10),
+            max_dbs: Some(20),
             no_lmdb_sync: None,
         }
     }
@@ -373,6 +397,8 @@ impl Default for DatabaseSettings {
 pub struct MempoolSettings {
     pub filter: Option<MempoolFilterSettings>,
     pub blacklist_limit: Option<usize>,
+    pub size_limit: Option<usize>,
+    pub policy: Option<MempoolPolicyConfig>,
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -420,6 +446,8 @@ impl From<MempoolSettings> for MempoolConfig {
                 .blacklist_limit
                 .unwrap_or(MempoolFilter::DEFAULT_BLACKLIST_SIZE),
             filter_rules: mempool.filter.map(MempoolRules::from).unwrap_or_default(),
+            policy: mempool.policy.unwrap_or_default(),
+            size_max: mempool.size_limit.unwrap_or(MEMPOOL_SIZE_MAX),
         }
     }
 }
@@ -451,4 +479,23 @@ pub struct ValidatorSettings {
     pub validator_key: Option<String>,
     pub wallet_account: Option<String>,
     pub wallet_password: Option<String>,
+    pub auto_unpark: Option<AutoUnparkSettings>,
+    pub remote_signer: Option<RemoteSignerSettings>,
+}
+
+#[derive(Clone, Debug, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct AutoUnparkSettings {
+    pub fee: Option<Coin>,
+    pub retry_interval: Option<u32>,
+    pub max_transactions_per_epoch: Option<u32>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RemoteSignerSettings {
+    pub address: String,
+    pub auth_key: String,
+    pub server_key: String,
+    pub timeout: Option<u64>,
 }
@@ -1,9 +1,11 @@
 use std::{collections::HashSet, iter::FromIterator, sync::Arc};
 
+use nimiq_rpc_server::auth::Authorizer;
 use nimiq_rpc_server::dispatchers::*;
+use nimiq_rpc_server::{Config, Server as _Server};
 
 use nimiq_jsonrpc_core::Credentials;
-use nimiq_jsonrpc_server::{AllowListDispatcher, Config, ModularDispatcher, Server as _Server};
+use nimiq_jsonrpc_server::{AllowListDispatcher, ModularDispatcher};
 
 use nimiq_wallet::WalletStore;
 
@@ -11,33 +13,16 @@ use crate::client::Client;
 use crate::config::config::RpcServerConfig;
 use crate::config::consts::default_bind;
 use crate::error::Error;
+use crate::nano_client::NanoClient;
 
-pub type Server = _Server<AllowListDispatcher<ModularDispatcher>>;
+pub type Server =
+    _Server<AllowListDispatcher<ModularDispatcher>, AllowListDispatcher<ModularDispatcher>>;
 
 pub fn initialize_rpc_server(
     client: &Client,
     config: RpcServerConfig,
     wallet_store: Arc<WalletStore>,
 ) -> Result<Server, Error> {
-    let ip = config.bind_to.unwrap_or_else(default_bind);
-    log::info!("Initializing RPC server: {}:{}", ip, config.port);
-
-    // Configure RPC server
-    let basic_auth = config.credentials.map(|credentials| Credentials {
-        username: credentials.username,
-        password: credentials.password,
-    });
-
-    let allowed_methods = config.allowed_methods.unwrap_or_default();
-    let allowed_methods = if allowed_methods.is_empty() {
-        None
-    } else {
-        Some(HashSet::from_iter(allowed_methods))
-    };
-
-    // TODO: Pass this to the rpc server config
-    let _corsdomain = config.corsdomain.unwrap_or_default();
-
     let mut dispatcher = ModularDispatcher::default();
 
     /*
@@ -49,6 +34,13 @@ pub fn initialize_rpc_server(
     }
     */
 
+    #[cfg(feature = "validator")]
+    {
+        if let Some(duty_tracker) = client.validator_duty_tracker() {
+            dispatcher.add(ValidatorDispatcher::new(duty_tracker));
+        }
+    }
+
     //dispatcher.add(NetworkDispatcher::new(client.consensus()));
     let wallet_dispatcher = WalletDispatcher::new(wallet_store);
     let unlocked_wallets = Arc::clone(&wallet_dispatcher.unlocked_wallets);
@@ -60,14 +52,90 @@ pub fn initialize_rpc_server(
     ));
     dispatcher.add(wallet_dispatcher);
     dispatcher.add(MempoolDispatcher::new(client.mempool()));
+    dispatcher.add(DiscoverDispatcher::new());
+
+    // Subscriptions belong to the WebSocket connection that they were created on.
+    let blockchain = client.blockchain();
+    let mempool = client.mempool();
+    let connection_dispatcher = move || {
+        let mut dispatcher = ModularDispatcher::default();
+        dispatcher.add(SubscriptionDispatcher::new(
+            Arc::clone(&blockchain),
+            Arc::clone(&mempool),
+        ));
+        dispatcher
+    };
+
+    create_server(config, dispatcher, connection_dispatcher)
+}
+
+/// Initializes the RPC server of nano nodes, which only offers the methods of the `NanoDispatcher`.
+pub fn initialize_nano_rpc_server(
+    client: &NanoClient,
+    config: RpcServerConfig,
+) -> Result<Server, Error> {
+    let mut dispatcher = ModularDispatcher::default();
+
+    dispatcher.add(NanoDispatcher::new(client.consensus_proxy()));
+    dispatcher.add(DiscoverDispatcher::new());
+
+    create_server(config, dispatcher, ModularDispatcher::default)
+}
+
+fn create_server<F>(
+    config: RpcServerConfig,
+    dispatcher: ModularDispatcher,
+    connection_dispatcher: F,
+) -> Result<Server, Error>
+where
+    F: Fn() -> ModularDispatcher + Send + Sync + 'static,
+{
+    let ip = config.bind_to.unwrap_or_else(default_bind);
+    log::info!("Initializing RPC server: {}:{}", ip, config.port);
+
+    // Configure RPC server
+    let basic_auth = config.credentials.map(|credentials| Credentials {
+        username: credentials.username,
+        password: credentials.password,
+    });
+
+    let allowed_methods = config.allowed_methods.unwrap_or_default();
+    let allowed_methods = if allowed_methods.is_empty() {
+        None
+    } else {
+        Some(HashSet::from_iter(allowed_methods))
+    };
+
+    // Configure users and roles. The server checks each request against the method groups and the
+    // rate limit of the user that sent it.
+    let authorizer = if config.users.is_empty() {
+        None
+    } else {
+        if basic_auth.is_some() || allowed_methods.is_some() {
+            return Err(Error::config_error(
+                "RPC users can't be combined with username, password or methods.",
+            ));
+        }
+        Some(Authorizer::new(config.users).map_err(|e| Error::config_error(e.to_string()))?)
+    };
+
+    // TODO: Pass this to the rpc server config
+    let _corsdomain = config.corsdomain.unwrap_or_default();
+
+    let connection_allowed_methods = allowed_methods.clone();
 
     Ok(Server::new(
         Config {
             bind_to: (config.bind_to.unwrap_or_else(default_bind), config.port).into(),
-            enable_websocket: false,
-            ip_whitelist: None,
+            enable_websocket: config.enable_websocket,
+            ip_whitelist: config.allow_ips.map(HashSet::from_iter),
             basic_auth,
+            authorizer,
+            max_batch_size: config.max_batch_size,
         },
         AllowListDispatcher::new(dispatcher, allowed_methods),
+        Box::new(move || {
+            AllowListDispatcher::new(connection_dispatcher(), connection_allowed_methods.clone())
+        }),
     ))
 }
@@ -2,4 +2,5 @@ pub mod client;
 pub mod config;
 pub mod error;
 pub mod extras;
+pub mod nano_client;
 pub mod prelude;
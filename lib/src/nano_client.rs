@@ -0,0 +1,84 @@
+use std::sync::Arc;
+
+use parking_lot::RwLock;
+
+use nimiq_block_albatross::Block;
+use nimiq_blockchain_albatross::AbstractBlockchain;
+use nimiq_consensus_albatross::nano::{
+    NanoConsensus as AbstractNanoConsensus, NanoConsensusProxy as AbstractNanoConsensusProxy,
+};
+use nimiq_nano_blockchain::NanoBlockchain;
+use nimiq_network_interface::network::Network as NetworkInterface;
+use nimiq_network_libp2p::Network;
+
+use crate::client::{create_network, start_network};
+use crate::config::config::{ClientConfig, ConsensusConfig};
+use crate::error::Error;
+
+/// Alias for the nano consensus specialized over libp2p network
+pub type NanoConsensus = AbstractNanoConsensus<Network>;
+pub type NanoConsensusProxy = AbstractNanoConsensusProxy<Network>;
+
+/// Entry point for the Nimiq client API of nano nodes.
+///
+/// Nano nodes only follow the block headers in a `NanoBlockchain` and don't store anything. The
+/// accounts and transactions are requested from other nodes through the *Nano consensus proxy*.
+/// There is no mempool, wallet or validator.
+///
+pub struct NanoClient {
+    network: Arc<Network>,
+    consensus: Option<NanoConsensus>,
+    consensus_proxy: NanoConsensusProxy,
+}
+
+impl NanoClient {
+    pub async fn from_config(config: ClientConfig) -> Result<Self, Error> {
+        if config.consensus != ConsensusConfig::Nano {
+            return Err(Error::config_error(
+                "The NanoClient only supports nano consensus",
+            ));
+        }
+
+        let network = create_network(&config).await?;
+
+        // Start buffering network events as early as possible
+        let nw_events = network.subscribe_events();
+
+        let blockchain = Arc::new(RwLock::new(NanoBlockchain::new(config.network_id)));
+
+        let consensus =
+            NanoConsensus::from_network(blockchain, Arc::clone(&network), nw_events).await;
+
+        start_network(&network, &config.network).await?;
+
+        Ok(NanoClient {
+            network,
+            consensus_proxy: consensus.proxy(),
+            consensus: Some(consensus),
+        })
+    }
+
+    pub fn consensus(&mut self) -> Option<NanoConsensus> {
+        self.consensus.take()
+    }
+
+    /// Returns a reference to the *Nano consensus proxy*.
+    pub fn consensus_proxy(&self) -> NanoConsensusProxy {
+        self.consensus_proxy.clone()
+    }
+
+    /// Returns a reference to the *Network* stack
+    pub fn network(&self) -> Arc<Network> {
+        Arc::clone(&self.network)
+    }
+
+    /// Returns a reference to the nano blockchain
+    pub fn blockchain(&self) -> Arc<RwLock<NanoBlockchain>> {
+        Arc::clone(&self.consensus_proxy.blockchain)
+    }
+
+    /// Returns the blockchain head
+    pub fn blockchain_head(&self) -> Block {
+        self.consensus_proxy.blockchain.read().head()
+    }
+}
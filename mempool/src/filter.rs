@@ -1,7 +1,7 @@
 use collections::LimitHashSet;
 use nimiq_hash::Blake2bHash;
 use primitives::coin::Coin;
-use transaction::{Transaction, TransactionFlags};
+use transaction::Transaction;
 
 #[derive(Debug)]
 pub struct MempoolFilter {
@@ -29,22 +29,17 @@ impl MempoolFilter {
         self
     }
 
+    pub fn clear_blacklist(&mut self) -> &mut Self {
+        self.blacklist.clear();
+        self
+    }
+
     pub fn blacklisted(&self, hash: &Blake2bHash) -> bool {
         self.blacklist.contains(hash)
     }
 
-    pub fn accepts_transaction(&self, tx: &Transaction) -> bool {
-        tx.fee >= self.rules.tx_fee &&
-             tx.value >= self.rules.tx_value &&
-             // Unchecked addition of coins.
-             tx.value + tx.fee >= self.rules.tx_value_total &&
-             tx.fee_per_byte() >= self.rules.tx_fee_per_byte && (
-                !tx.flags.contains(TransactionFlags::CONTRACT_CREATION) || (
-                    tx.fee >= self.rules.contract_fee ||
-                        tx.fee_per_byte() >= self.rules.contract_fee_per_byte ||
-                        tx.value >= self.rules.contract_value
-                )
-         )
+    pub fn rules(&self) -> &Rules {
+        &self.rules
     }
 
     pub fn accepts_recipient_balance(
@@ -27,14 +27,17 @@ use transaction::{Transaction, TransactionFlags};
 use utils::observer::{weak_listener, Notifier};
 
 use crate::filter::{MempoolFilter, Rules};
+use crate::policy::{CompositePolicy, MempoolPolicy, PolicyConfig, PolicyContext, PolicyRejection};
 
 pub mod filter;
+pub mod policy;
 
 pub struct Mempool {
     blockchain: Arc<Blockchain>,
     pub notifier: RwLock<Notifier<'static, MempoolEvent>>,
     state: RwLock<MempoolState>,
     mut_lock: Mutex<()>,
+    size_max: usize,
 }
 
 struct MempoolState {
@@ -43,6 +46,8 @@ struct MempoolState {
     transactions_by_recipient: HashMap<Address, BTreeSet<Arc<Transaction>>>,
     transactions_sorted_fee: BTreeSet<Arc<Transaction>>, // sorted by fee, ascending
     filter: MempoolFilter,
+    policy: CompositePolicy,
+    policy_config: PolicyConfig,
 }
 
 #[derive(Debug, Clone, PartialOrd, Ord, PartialEq, Eq)]
@@ -51,12 +56,19 @@ pub enum MempoolEvent {
     TransactionRestored(Arc<Transaction>),
     TransactionMined(Arc<Transaction>),
     TransactionEvicted(Arc<Transaction>),
+    /// A transaction was removed to make room for transactions with a higher fee per byte.
+    TransactionDropped(Arc<Transaction>),
+    /// A transaction (first) was replaced by a transaction of the same sender with the same
+    /// validity start height and a higher fee (second).
+    TransactionReplaced(Arc<Transaction>, Arc<Transaction>),
 }
 
 #[derive(Debug, Clone)]
 pub struct MempoolConfig {
     pub filter_rules: Rules,
     pub filter_limit: usize,
+    pub policy: PolicyConfig,
+    pub size_max: usize,
 }
 
 impl Default for MempoolConfig {
@@ -64,6 +76,8 @@ impl Default for MempoolConfig {
         MempoolConfig {
             filter_rules: Rules::default(),
             filter_limit: MempoolFilter::DEFAULT_BLACKLIST_SIZE,
+            policy: PolicyConfig::default(),
+            size_max: SIZE_MAX,
         }
     }
 }
@@ -78,9 +92,12 @@ impl Mempool {
                 transactions_by_sender: HashMap::new(),
                 transactions_by_recipient: HashMap::new(),
                 transactions_sorted_fee: BTreeSet::new(),
+                policy: config.policy.build(config.filter_rules.clone()),
+                policy_config: config.policy,
                 filter: MempoolFilter::new(config.filter_rules, config.filter_limit),
             }),
             mut_lock: Mutex::new(()),
+            size_max: config.size_max,
         });
 
         // register listener to blockchain through weak reference
@@ -97,6 +114,21 @@ impl Mempool {
         self.state.read().filter.blacklisted(hash)
     }
 
+    /// Returns the configuration of the current admission policy.
+    pub fn policy_config(&self) -> PolicyConfig {
+        self.state.read().policy_config.clone()
+    }
+
+    /// Replaces the admission policy. Transactions that are already in the mempool are not
+    /// re-checked against the new policy. The blacklist is cleared, since the transactions on it
+    /// might be acceptable under the new policy.
+    pub fn set_policy(&self, config: PolicyConfig) {
+        let mut state = self.state.write();
+        state.policy = config.build(state.filter.rules().clone());
+        state.filter.clear_blacklist();
+        state.policy_config = config;
+    }
+
     pub fn push_transaction(&self, mut transaction: Transaction) -> ReturnCode {
         let hash: Blake2bHash = transaction.hash();
 
@@ -109,20 +141,57 @@ impl Mempool {
         // Transactions that are invalidated by the new transaction are stored here.
         let mut txs_to_remove = Vec::new();
 
+        // The transaction that is replaced by the new transaction, if any.
+        let replaced_tx: Option<Arc<Transaction>>;
+
         {
             let state = self.state.upgradable_read();
 
-            // Check transaction against rules and blacklist
-            if !state.filter.accepts_transaction(&transaction) || state.filter.blacklisted(&hash) {
-                let mut state = RwLockUpgradableReadGuard::upgrade(state);
-                state.filter.blacklist(hash);
-                trace!(
-                    "Transaction was filtered: {}",
-                    transaction.hash::<Blake2bHash>()
-                );
+            // Check transaction against blacklist and admission policy
+            if state.filter.blacklisted(&hash) {
+                trace!("Transaction was filtered: {}", hash);
                 return ReturnCode::Filtered;
             }
 
+            // Check if the transaction replaces a pending transaction of the same sender with the
+            // same validity start height. If there are several, the one with the lowest fee is
+            // replaced. This is checked before the admission policy, since a replacement doesn't
+            // add a transaction to the mempool.
+            let txs_by_sender_opt = state.transactions_by_sender.get(&transaction.sender);
+            replaced_tx = txs_by_sender_opt
+                .and_then(|transactions| {
+                    transactions
+                        .iter()
+                        .filter(|tx| tx.validity_start_height == transaction.validity_start_height)
+                        .min_by_key(|tx| tx.fee)
+                })
+                .filter(|tx| Self::is_replacement(&transaction, tx))
+                .cloned();
+
+            let context = PolicyContext {
+                mempool_size: state.transactions_by_hash.len(),
+                mempool_size_max: self.size_max,
+                sender_pending: txs_by_sender_opt.map_or(0, |txs| txs.len()),
+                is_replacement: replaced_tx.is_some(),
+            };
+            match state.policy.check(&transaction, &context) {
+                Ok(()) => {}
+                Err(PolicyRejection::Filtered) => {
+                    let mut state = RwLockUpgradableReadGuard::upgrade(state);
+                    state.filter.blacklist(hash.clone());
+                    trace!("Transaction was filtered: {}", hash);
+                    return ReturnCode::Filtered;
+                }
+                Err(PolicyRejection::FeeTooLow) => {
+                    trace!("Transaction was rejected by policy: {}", hash);
+                    return ReturnCode::FeeTooLow;
+                }
+                Err(PolicyRejection::SenderLimitExceeded) => {
+                    trace!("Transaction exceeds the limit of its sender: {}", hash);
+                    return ReturnCode::SenderLimitExceeded;
+                }
+            }
+
             // Check if we already know this transaction.
             if state.transactions_by_hash.contains_key(&hash) {
                 return ReturnCode::Known;
@@ -133,12 +202,23 @@ impl Mempool {
                 return ReturnCode::Invalid;
             }
 
+            // If the mempool is full, only accept transactions that pay more than the lowest fee.
+            let size = state.transactions_by_hash.len() - replaced_tx.iter().count();
+            if size >= self.size_max {
+                match state.transactions_sorted_fee.iter().next() {
+                    Some(lowest_tx) if transaction.fee_per_byte() > lowest_tx.fee_per_byte() => {}
+                    _ => return ReturnCode::FeeTooLow,
+                }
+            }
+
             // Check limit for free transactions.
-            let txs_by_sender_opt = state.transactions_by_sender.get(&transaction.sender);
             if transaction.fee_per_byte() < TRANSACTION_RELAY_FEE_MIN {
                 let mut num_free_tx = 0;
                 if let Some(transactions) = txs_by_sender_opt {
                     for tx in transactions {
+                        if replaced_tx.as_ref() == Some(tx) {
+                            continue;
+                        }
                         if tx.fee_per_byte() < TRANSACTION_RELAY_FEE_MIN {
                             num_free_tx += 1;
                             if num_free_tx >= FREE_TRANSACTIONS_PER_SENDER_MAX {
@@ -239,6 +319,11 @@ impl Mempool {
                 if transaction.cmp(tx) == Ordering::Greater {
                     break;
                 }
+                // Skip the transaction that is replaced by the new one.
+                if replaced_tx.as_ref() == Some(tx) {
+                    tx_opt = tx_iter.next_back();
+                    continue;
+                }
                 // Reject the transaction, if after the intrinsic check, the balance went too low
                 if sender_account
                     .commit_outgoing_transaction(tx, block_height, timestamp)
@@ -279,6 +364,10 @@ impl Mempool {
             // Finally, check the remaining transactions with lower fee/byte and evict them if necessary.
             // tx_opt already contains the first lower/fee byte transaction to check (if there is one remaining).
             while let Some(tx) = tx_opt {
+                if replaced_tx.as_ref() == Some(tx) {
+                    tx_opt = tx_iter.next_back();
+                    continue;
+                }
                 if tx_count < TRANSACTIONS_PER_SENDER_MAX {
                     if sender_account
                         .commit_outgoing_transaction(tx, block_height, timestamp)
@@ -297,10 +386,13 @@ impl Mempool {
 
         let tx_arc = Arc::new(transaction);
 
-        let mut removed_transactions;
+        let dropped_transactions;
         {
             // Transaction is valid, add it to the mempool.
             let mut state = self.state.write();
+            if let Some(tx) = &replaced_tx {
+                Self::remove_transaction(&mut state, tx);
+            }
             Self::add_transaction(&mut state, hash.clone(), tx_arc.clone());
 
             // Evict transactions that were invalidated by the new transaction.
@@ -308,15 +400,8 @@ impl Mempool {
                 Self::remove_transaction(&mut *state, tx);
             }
 
-            // Rename variable.
-            removed_transactions = txs_to_remove;
-
-            // Remove the lowest fee transaction if mempool max size is reached.
-            if state.transactions_sorted_fee.len() > SIZE_MAX {
-                let tx = state.transactions_sorted_fee.iter().next().unwrap().clone();
-                Self::remove_transaction(&mut state, &tx);
-                removed_transactions.push(tx);
-            }
+            // Remove the lowest fee transactions if mempool max size is reached.
+            dropped_transactions = Self::drop_lowest_fee_transactions(&mut state, self.size_max);
         }
 
         // Drop the lock on blockchain::push
@@ -325,15 +410,31 @@ impl Mempool {
         // Tell listeners about the new transaction we received.
         self.notifier
             .read()
-            .notify(MempoolEvent::TransactionAdded(hash, tx_arc));
+            .notify(MempoolEvent::TransactionAdded(hash, Arc::clone(&tx_arc)));
+
+        // Tell listeners about the transaction that was replaced.
+        if let Some(tx) = replaced_tx {
+            trace!("Transaction replaced: {:?}", tx);
+            self.notifier
+                .read()
+                .notify(MempoolEvent::TransactionReplaced(tx, tx_arc));
+        }
 
         // Tell listeners about the transactions we evicted.
-        for tx in removed_transactions {
+        for tx in txs_to_remove {
             self.notifier
                 .read()
                 .notify(MempoolEvent::TransactionEvicted(tx));
         }
 
+        // Tell listeners about the transactions we dropped.
+        for tx in dropped_transactions {
+            trace!("Transaction dropped: {:?}", tx);
+            self.notifier
+                .read()
+                .notify(MempoolEvent::TransactionDropped(tx));
+        }
+
         ReturnCode::Accepted
     }
 
@@ -578,6 +679,7 @@ impl Mempool {
 
         let mut removed_transactions = Vec::new();
         let mut restored_transactions = Vec::new();
+        let dropped_transactions;
         let block_height = self.blockchain.block_number() + 1;
         let timestamp = self.blockchain.timestamp();
 
@@ -669,19 +771,8 @@ impl Mempool {
                 }
             }
 
-            // Evict lowest fee transactions if the mempool has grown too large.
-            let size = state.transactions_sorted_fee.len();
-            if size > SIZE_MAX {
-                let mut txs_to_remove = Vec::with_capacity(size - SIZE_MAX);
-                let mut iter = state.transactions_sorted_fee.iter();
-                for _ in 0..size - SIZE_MAX {
-                    txs_to_remove.push(iter.next().unwrap().clone());
-                }
-                for tx in txs_to_remove.iter() {
-                    Self::remove_transaction(&mut state, tx);
-                }
-                removed_transactions.extend(txs_to_remove);
-            }
+            // Drop lowest fee transactions if the mempool has grown too large.
+            dropped_transactions = Self::drop_lowest_fee_transactions(&mut state, self.size_max);
         }
 
         // Notify listeners.
@@ -696,6 +787,42 @@ impl Mempool {
                 .read()
                 .notify(MempoolEvent::TransactionRestored(tx));
         }
+
+        for tx in dropped_transactions {
+            self.notifier
+                .read()
+                .notify(MempoolEvent::TransactionDropped(tx));
+        }
+    }
+
+    /// Checks if `new_tx` pays enough to replace `old_tx`. The replacement has to pay a higher fee
+    /// and at least `REPLACE_BY_FEE_FACTOR` times the fee per byte of the replaced transaction.
+    fn is_replacement(new_tx: &Transaction, old_tx: &Transaction) -> bool {
+        new_tx.fee > old_tx.fee
+            && new_tx.fee_per_byte() >= old_tx.fee_per_byte() * REPLACE_BY_FEE_FACTOR
+    }
+
+    /// Removes the transactions with the lowest fee per byte until the mempool has at most
+    /// `size_max` transactions.
+    fn drop_lowest_fee_transactions(
+        state: &mut MempoolState,
+        size_max: usize,
+    ) -> Vec<Arc<Transaction>> {
+        let size = state.transactions_sorted_fee.len();
+        if size <= size_max {
+            return Vec::new();
+        }
+
+        let txs_to_drop: Vec<Arc<Transaction>> = state
+            .transactions_sorted_fee
+            .iter()
+            .take(size - size_max)
+            .cloned()
+            .collect();
+        for tx in txs_to_drop.iter() {
+            Self::remove_transaction(state, tx);
+        }
+        txs_to_drop
     }
 
     fn add_transaction(state: &mut MempoolState, hash: Blake2bHash, tx: Arc<Transaction>) {
@@ -805,6 +932,7 @@ pub enum ReturnCode {
     Accepted,
     Known,
     Filtered,
+    SenderLimitExceeded,
 }
 
 /// Fee threshold in sat/byte below which transactions are considered "free".
@@ -816,5 +944,8 @@ const TRANSACTIONS_PER_SENDER_MAX: u32 = 500;
 /// Maximum number of "free" transactions per sender.
 const FREE_TRANSACTIONS_PER_SENDER_MAX: u32 = 10;
 
-/// Maximum number of transactions in the mempool.
+/// Default maximum number of transactions in the mempool.
 pub const SIZE_MAX: usize = 100_000;
+
+/// Minimum factor by which a replacement has to increase the fee per byte of the replaced transaction.
+const REPLACE_BY_FEE_FACTOR: f64 = 1.1;
@@ -0,0 +1,372 @@
+use std::collections::HashSet;
+use std::fmt::Debug;
+
+use keys::Address;
+use primitives::account::AccountType;
+use primitives::coin::Coin;
+use transaction::{Transaction, TransactionFlags};
+
+use crate::filter::Rules;
+
+/// The state of the mempool that a policy can take into account when deciding whether to admit a
+/// transaction.
+#[derive(Debug, Clone)]
+pub struct PolicyContext {
+    /// The number of transactions currently in the mempool.
+    pub mempool_size: usize,
+    /// The maximum number of transactions in the mempool.
+    pub mempool_size_max: usize,
+    /// The number of transactions of the same sender that are already in the mempool.
+    pub sender_pending: usize,
+    /// Whether the transaction replaces a transaction of the same sender in the mempool, so that
+    /// neither the mempool nor the pending transactions of the sender grow.
+    pub is_replacement: bool,
+}
+
+impl PolicyContext {
+    /// Returns how full the mempool is, as a number between 0 and 1.
+    pub fn fill_ratio(&self) -> f64 {
+        if self.mempool_size_max == 0 {
+            return 1.0;
+        }
+        (self.mempool_size as f64 / self.mempool_size_max as f64).min(1.0)
+    }
+}
+
+/// The reason why a policy rejected a transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PolicyRejection {
+    /// The transaction is never acceptable under this policy. It will be blacklisted until the
+    /// policy is replaced.
+    Filtered,
+    /// The transaction is not acceptable in the current state of the mempool, but might be later.
+    FeeTooLow,
+    /// The sender already has the maximum number of pending transactions in the mempool.
+    SenderLimitExceeded,
+}
+
+/// An admission policy for the mempool. Policies are checked before a transaction is verified
+/// against the account state and can be combined using a `CompositePolicy`.
+pub trait MempoolPolicy: Debug + Send + Sync {
+    fn check(
+        &self,
+        transaction: &Transaction,
+        context: &PolicyContext,
+    ) -> Result<(), PolicyRejection>;
+}
+
+/// A policy that only admits transactions that are admitted by all of its policies.
+#[derive(Debug, Default)]
+pub struct CompositePolicy {
+    policies: Vec<Box<dyn MempoolPolicy>>,
+}
+
+impl CompositePolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with<P: MempoolPolicy + 'static>(mut self, policy: P) -> Self {
+        self.policies.push(Box::new(policy));
+        self
+    }
+
+    pub fn push(&mut self, policy: Box<dyn MempoolPolicy>) {
+        self.policies.push(policy);
+    }
+}
+
+impl MempoolPolicy for CompositePolicy {
+    fn check(
+        &self,
+        transaction: &Transaction,
+        context: &PolicyContext,
+    ) -> Result<(), PolicyRejection> {
+        for policy in self.policies.iter() {
+            policy.check(transaction, context)?;
+        }
+        Ok(())
+    }
+}
+
+/// The static fee and value rules of the mempool filter.
+#[derive(Debug, Clone)]
+pub struct RulesPolicy {
+    rules: Rules,
+}
+
+impl RulesPolicy {
+    pub fn new(rules: Rules) -> Self {
+        RulesPolicy { rules }
+    }
+}
+
+impl MempoolPolicy for RulesPolicy {
+    fn check(&self, tx: &Transaction, _context: &PolicyContext) -> Result<(), PolicyRejection> {
+        let accepted = tx.fee >= self.rules.tx_fee &&
+            tx.value >= self.rules.tx_value &&
+            // Unchecked addition of coins.
+            tx.value + tx.fee >= self.rules.tx_value_total &&
+            tx.fee_per_byte() >= self.rules.tx_fee_per_byte && (
+                !tx.flags.contains(TransactionFlags::CONTRACT_CREATION) || (
+                    tx.fee >= self.rules.contract_fee ||
+                        tx.fee_per_byte() >= self.rules.contract_fee_per_byte ||
+                        tx.value >= self.rules.contract_value
+                )
+            );
+
+        if accepted {
+            Ok(())
+        } else {
+            Err(PolicyRejection::Filtered)
+        }
+    }
+}
+
+/// Limits the number of pending transactions per sender.
+#[derive(Debug, Clone)]
+pub struct SenderLimitPolicy {
+    max_per_sender: usize,
+}
+
+impl SenderLimitPolicy {
+    pub fn new(max_per_sender: usize) -> Self {
+        SenderLimitPolicy { max_per_sender }
+    }
+}
+
+impl MempoolPolicy for SenderLimitPolicy {
+    fn check(&self, _tx: &Transaction, context: &PolicyContext) -> Result<(), PolicyRejection> {
+        if !context.is_replacement && context.sender_pending >= self.max_per_sender {
+            return Err(PolicyRejection::SenderLimitExceeded);
+        }
+        Ok(())
+    }
+}
+
+/// Requires a minimum fee per byte that rises linearly from `min_fee_per_byte` to
+/// `max_fee_per_byte` once the mempool is filled beyond `threshold`.
+#[derive(Debug, Clone)]
+pub struct DynamicFeePolicy {
+    config: DynamicFeeConfig,
+}
+
+impl DynamicFeePolicy {
+    pub fn new(config: DynamicFeeConfig) -> Self {
+        DynamicFeePolicy { config }
+    }
+
+    /// Returns the minimum fee per byte for the given fill ratio of the mempool.
+    pub fn min_fee_per_byte(&self, fill_ratio: f64) -> f64 {
+        let threshold = self.config.threshold.max(0.0).min(1.0);
+        if fill_ratio <= threshold || threshold >= 1.0 {
+            return self.config.min_fee_per_byte;
+        }
+
+        let progress = (fill_ratio - threshold) / (1.0 - threshold);
+        self.config.min_fee_per_byte
+            + progress * (self.config.max_fee_per_byte - self.config.min_fee_per_byte).max(0.0)
+    }
+}
+
+impl MempoolPolicy for DynamicFeePolicy {
+    fn check(&self, tx: &Transaction, context: &PolicyContext) -> Result<(), PolicyRejection> {
+        // Replacements have to pay the minimum fee, too, even though they don't grow the mempool.
+        if tx.fee_per_byte() < self.min_fee_per_byte(context.fill_ratio()) {
+            return Err(PolicyRejection::FeeTooLow);
+        }
+        Ok(())
+    }
+}
+
+/// Rejects transactions from or to denied addresses. If the allow list is not empty, only
+/// transactions sent from allowed addresses are admitted.
+#[derive(Debug, Clone, Default)]
+pub struct AddressListPolicy {
+    allow: HashSet<Address>,
+    deny: HashSet<Address>,
+}
+
+impl AddressListPolicy {
+    pub fn new(allow: HashSet<Address>, deny: HashSet<Address>) -> Self {
+        AddressListPolicy { allow, deny }
+    }
+}
+
+impl MempoolPolicy for AddressListPolicy {
+    fn check(&self, tx: &Transaction, _context: &PolicyContext) -> Result<(), PolicyRejection> {
+        if self.deny.contains(&tx.sender) || self.deny.contains(&tx.recipient) {
+            return Err(PolicyRejection::Filtered);
+        }
+        if !self.allow.is_empty() && !self.allow.contains(&tx.sender) {
+            return Err(PolicyRejection::Filtered);
+        }
+        Ok(())
+    }
+}
+
+/// The kinds of transactions that can have their own rules.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransactionKind {
+    /// Transactions sent to or from the staking contract.
+    Staking,
+    /// Transactions that create an HTLC contract.
+    HtlcCreation,
+    /// Transactions that create a vesting contract.
+    VestingCreation,
+}
+
+impl TransactionKind {
+    pub fn matches(self, tx: &Transaction) -> bool {
+        let is_creation = tx.flags.contains(TransactionFlags::CONTRACT_CREATION);
+        match self {
+            TransactionKind::Staking => {
+                tx.recipient_type == AccountType::Staking || tx.sender_type == AccountType::Staking
+            }
+            TransactionKind::HtlcCreation => is_creation && tx.recipient_type == AccountType::HTLC,
+            TransactionKind::VestingCreation => {
+                is_creation && tx.recipient_type == AccountType::Vesting
+            }
+        }
+    }
+}
+
+/// Applies additional rules to one kind of transactions.
+#[derive(Debug, Clone)]
+pub struct TransactionKindPolicy {
+    kind: TransactionKind,
+    rules: TransactionKindRules,
+}
+
+impl TransactionKindPolicy {
+    pub fn new(kind: TransactionKind, rules: TransactionKindRules) -> Self {
+        TransactionKindPolicy { kind, rules }
+    }
+}
+
+impl MempoolPolicy for TransactionKindPolicy {
+    fn check(&self, tx: &Transaction, _context: &PolicyContext) -> Result<(), PolicyRejection> {
+        if !self.kind.matches(tx) {
+            return Ok(());
+        }
+
+        if !self.rules.allowed
+            || tx.fee < self.rules.min_fee
+            || tx.fee_per_byte() < self.rules.min_fee_per_byte
+            || tx.value < self.rules.min_value
+        {
+            return Err(PolicyRejection::Filtered);
+        }
+        Ok(())
+    }
+}
+
+/// The configuration of the mempool admission policy. The static `Rules` of the mempool filter are
+/// always applied, all other policies are only applied if they are configured.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(
+    feature = "serde-derive",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default, deny_unknown_fields)
+)]
+pub struct PolicyConfig {
+    /// The maximum number of pending transactions per sender.
+    pub max_per_sender: Option<usize>,
+    /// The minimum fee per byte, depending on how full the mempool is.
+    pub fee: Option<DynamicFeeConfig>,
+    /// If not empty, only transactions from these addresses are admitted.
+    pub allow: Vec<Address>,
+    /// Transactions from or to these addresses are rejected.
+    pub deny: Vec<Address>,
+    /// Rules for transactions sent to or from the staking contract.
+    pub staking: Option<TransactionKindRules>,
+    /// Rules for transactions that create an HTLC contract.
+    pub htlc_creation: Option<TransactionKindRules>,
+    /// Rules for transactions that create a vesting contract.
+    pub vesting_creation: Option<TransactionKindRules>,
+}
+
+impl PolicyConfig {
+    /// Builds the admission policy described by this configuration, on top of the given filter rules.
+    pub fn build(&self, rules: Rules) -> CompositePolicy {
+        let mut policy = CompositePolicy::new().with(RulesPolicy::new(rules));
+
+        if !self.allow.is_empty() || !self.deny.is_empty() {
+            policy.push(Box::new(AddressListPolicy::new(
+                self.allow.iter().cloned().collect(),
+                self.deny.iter().cloned().collect(),
+            )));
+        }
+
+        if let Some(max_per_sender) = self.max_per_sender {
+            policy.push(Box::new(SenderLimitPolicy::new(max_per_sender)));
+        }
+
+        if let Some(fee) = &self.fee {
+            policy.push(Box::new(DynamicFeePolicy::new(fee.clone())));
+        }
+
+        let kinds = [
+            (TransactionKind::Staking, &self.staking),
+            (TransactionKind::HtlcCreation, &self.htlc_creation),
+            (TransactionKind::VestingCreation, &self.vesting_creation),
+        ];
+        for (kind, rules) in kinds.iter() {
+            if let Some(rules) = rules {
+                policy.push(Box::new(TransactionKindPolicy::new(*kind, rules.clone())));
+            }
+        }
+
+        policy
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde-derive",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default, deny_unknown_fields)
+)]
+pub struct DynamicFeeConfig {
+    /// The minimum fee per byte while the mempool is filled up to `threshold`.
+    pub min_fee_per_byte: f64,
+    /// The minimum fee per byte when the mempool is full.
+    pub max_fee_per_byte: f64,
+    /// The fill ratio (between 0 and 1) after which the minimum fee starts to rise.
+    pub threshold: f64,
+}
+
+impl Default for DynamicFeeConfig {
+    fn default() -> Self {
+        DynamicFeeConfig {
+            min_fee_per_byte: 0.0,
+            max_fee_per_byte: 2.0,
+            threshold: 0.5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde-derive",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default, deny_unknown_fields)
+)]
+pub struct TransactionKindRules {
+    /// Whether transactions of this kind are admitted at all.
+    pub allowed: bool,
+    pub min_fee: Coin,
+    pub min_fee_per_byte: f64,
+    pub min_value: Coin,
+}
+
+impl Default for TransactionKindRules {
+    fn default() -> Self {
+        TransactionKindRules {
+            allowed: true,
+            min_fee: Coin::ZERO,
+            min_fee_per_byte: 0.0,
+            min_value: Coin::ZERO,
+        }
+    }
+}
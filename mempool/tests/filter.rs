@@ -2,7 +2,7 @@ use std::convert::TryFrom;
 
 use nimiq_hash::{Blake2bHash, Hash};
 use nimiq_keys::Address;
-use nimiq_mempool::filter::{MempoolFilter, Rules};
+use nimiq_mempool::filter::MempoolFilter;
 use nimiq_primitives::coin::Coin;
 use nimiq_primitives::networks::NetworkId;
 use nimiq_transaction::Transaction;
@@ -26,24 +26,3 @@ fn it_can_blacklist_transactions() {
     f.remove(&hash);
     assert!(!f.blacklisted(&hash));
 }
-
-#[test]
-fn it_accepts_and_rejects_transactions() {
-    let mut s: Rules = Rules::default();
-    s.tx_fee = Coin::try_from(1).unwrap();
-
-    let f = MempoolFilter::new(s, MempoolFilter::DEFAULT_BLACKLIST_SIZE);
-
-    let mut tx = Transaction::new_basic(
-        Address::from([32u8; Address::SIZE]),
-        Address::from([213u8; Address::SIZE]),
-        Coin::try_from(0).unwrap(),
-        Coin::try_from(0).unwrap(),
-        0,
-        NetworkId::Main,
-    );
-
-    assert!(!f.accepts_transaction(&tx));
-    tx.fee = Coin::try_from(1).unwrap();
-    assert!(f.accepts_transaction(&tx));
-}
@@ -1,3 +1,4 @@
+use std::collections::HashSet;
 use std::sync::Arc;
 
 use beserial::{Deserialize, Serialize};
@@ -8,6 +9,7 @@ use nimiq_database::WriteTransaction;
 use nimiq_hash::Hash;
 use nimiq_keys::Address;
 use nimiq_keys::{KeyPair, SecureGenerate};
+use nimiq_mempool::policy::PolicyConfig;
 use nimiq_mempool::{Mempool, MempoolConfig, ReturnCode};
 use nimiq_primitives::coin::Coin;
 use nimiq_primitives::networks::NetworkId;
@@ -17,7 +19,7 @@ const BASIC_TRANSACTION: &str = "000222666efadc937148a6d61589ce6d4aeecca97fda4c3
 
 #[test]
 fn push_same_tx_twice() {
-    let env = VolatileEnvironment::new(10).unwrap();
+    let env = VolatileEnvironment::new(Blockchain::NUM_DATABASES).unwrap();
 
     let blockchain = Arc::new(Blockchain::new(env.clone(), NetworkId::UnitAlbatross).unwrap());
 
@@ -67,7 +69,7 @@ fn push_same_tx_twice() {
 
 #[test]
 fn push_tx_with_wrong_signature() {
-    let env = VolatileEnvironment::new(10).unwrap();
+    let env = VolatileEnvironment::new(Blockchain::NUM_DATABASES).unwrap();
 
     let blockchain = Arc::new(Blockchain::new(env, NetworkId::UnitAlbatross).unwrap());
 
@@ -85,7 +87,7 @@ fn push_tx_with_wrong_signature() {
 
 #[test]
 fn push_tx_with_insufficient_balance() {
-    let env = VolatileEnvironment::new(10).unwrap();
+    let env = VolatileEnvironment::new(Blockchain::NUM_DATABASES).unwrap();
 
     let blockchain = Arc::new(Blockchain::new(env, NetworkId::UnitAlbatross).unwrap());
 
@@ -100,7 +102,7 @@ fn push_tx_with_insufficient_balance() {
 
 #[test]
 fn push_and_get_valid_tx() {
-    let env = VolatileEnvironment::new(10).unwrap();
+    let env = VolatileEnvironment::new(Blockchain::NUM_DATABASES).unwrap();
 
     let blockchain = Arc::new(Blockchain::new(env.clone(), NetworkId::UnitAlbatross).unwrap());
 
@@ -158,9 +160,70 @@ fn push_and_get_valid_tx() {
     assert_eq!(Arc::new(tx_copy), t2.unwrap());
 }
 
+#[test]
+fn accept_filtered_tx_after_policy_change() {
+    let env = VolatileEnvironment::new(Blockchain::NUM_DATABASES).unwrap();
+
+    let blockchain = Arc::new(Blockchain::new(env.clone(), NetworkId::UnitAlbatross).unwrap());
+
+    let mempool = Mempool::new(blockchain.clone(), MempoolConfig::default());
+
+    let keypair_a = KeyPair::generate_default_csprng();
+
+    let address_a = Address::from(&keypair_a.public);
+
+    let address_b = Address::from([2u8; Address::SIZE]);
+
+    // Give a reward to address_a.
+    let reward = Inherent {
+        ty: InherentType::Reward,
+        target: address_a.clone(),
+        value: Coin::from_u64_unchecked(10000),
+        data: vec![],
+    };
+
+    let mut txn = WriteTransaction::new(&env);
+
+    blockchain
+        .state()
+        .accounts
+        .commit(&mut txn, &[], &[reward], 1, 1)
+        .unwrap();
+
+    txn.commit();
+
+    // Generate and sign transaction from address_a
+    let mut tx = Transaction::new_basic(
+        address_a,
+        address_b.clone(),
+        Coin::from_u64_unchecked(10),
+        Coin::from_u64_unchecked(0),
+        1,
+        NetworkId::UnitAlbatross,
+    );
+
+    let signature_proof =
+        SignatureProof::from(keypair_a.public, keypair_a.sign(&tx.serialize_content()));
+
+    tx.proof = signature_proof.serialize_to_vec();
+
+    // The transaction is filtered while its recipient is denied.
+    mempool.set_policy(PolicyConfig {
+        deny: vec![address_b],
+        ..Default::default()
+    });
+    assert_eq!(mempool.push_transaction(tx.clone()), ReturnCode::Filtered);
+    assert!(mempool.is_filtered(&tx.hash()));
+
+    // Replacing the policy clears the blacklist.
+    mempool.set_policy(PolicyConfig::default());
+    assert!(!mempool.is_filtered(&tx.hash()));
+    assert_eq!(mempool.push_transaction(tx), ReturnCode::Accepted);
+}
+
 #[test]
 fn push_and_get_two_tx_same_user() {
-    let env = VolatileEnvironment::new(10).unwrap();
+    let env = VolatileEnvironment::new(Blockchain::NUM_DATABASES).unwrap();
 
     let blockchain = Arc::new(Blockchain::new(env.clone(), NetworkId::UnitAlbatross).unwrap());
 
@@ -239,7 +302,7 @@ fn push_and_get_two_tx_same_user() {
 
 #[test]
 fn reject_free_tx_beyond_limit() {
-    let env = VolatileEnvironment::new(10).unwrap();
+    let env = VolatileEnvironment::new(Blockchain::NUM_DATABASES).unwrap();
 
     let blockchain = Arc::new(Blockchain::new(env.clone(), NetworkId::UnitAlbatross).unwrap());
 
@@ -291,3 +354,276 @@ fn reject_free_tx_beyond_limit() {
         }
     }
 }
+
+#[test]
+fn replace_tx_with_higher_fee() {
+    let env = VolatileEnvironment::new(Blockchain::NUM_DATABASES).unwrap();
+
+    let blockchain = Arc::new(Blockchain::new(env.clone(), NetworkId::UnitAlbatross).unwrap());
+
+    let mempool = Mempool::new(blockchain.clone(), MempoolConfig::default());
+
+    let keypair_a = KeyPair::generate_default_csprng();
+
+    let address_a = Address::from(&keypair_a.public);
+
+    let address_b = Address::from([2u8; Address::SIZE]);
+
+    // Give a reward to address_a.
+    let reward = Inherent {
+        ty: InherentType::Reward,
+        target: address_a.clone(),
+        value: Coin::from_u64_unchecked(10000),
+        data: vec![],
+    };
+
+    let mut txn = WriteTransaction::new(&env);
+
+    blockchain
+        .state()
+        .accounts
+        .commit(&mut txn, &[], &[reward], 1, 1)
+        .unwrap();
+
+    txn.commit();
+
+    let sign = |mut tx: Transaction| {
+        let signature_proof =
+            SignatureProof::from(keypair_a.public, keypair_a.sign(&tx.serialize_content()));
+        tx.proof = signature_proof.serialize_to_vec();
+        tx
+    };
+
+    // Push the original transaction.
+    let tx1 = sign(Transaction::new_basic(
+        address_a.clone(),
+        address_b.clone(),
+        Coin::from_u64_unchecked(10),
+        Coin::from_u64_unchecked(200),
+        1,
+        NetworkId::UnitAlbatross,
+    ));
+    let hash1 = tx1.hash();
+    assert_eq!(mempool.push_transaction(tx1), ReturnCode::Accepted);
+
+    // A transaction with the same validity start height and a slightly higher fee does not replace
+    // the original transaction.
+    let tx2 = sign(Transaction::new_basic(
+        address_a.clone(),
+        address_b.clone(),
+        Coin::from_u64_unchecked(10),
+        Coin::from_u64_unchecked(201),
+        1,
+        NetworkId::UnitAlbatross,
+    ));
+    let hash2 = tx2.hash();
+    assert_eq!(mempool.push_transaction(tx2), ReturnCode::Accepted);
+    assert!(mempool.contains(&hash1));
+    assert!(mempool.contains(&hash2));
+
+    // A transaction with the same validity start height and a sufficiently higher fee replaces the
+    // transaction with the lowest fee.
+    let tx3 = sign(Transaction::new_basic(
+        address_a.clone(),
+        address_b.clone(),
+        Coin::from_u64_unchecked(10),
+        Coin::from_u64_unchecked(300),
+        1,
+        NetworkId::UnitAlbatross,
+    ));
+    let hash3 = tx3.hash();
+    assert_eq!(mempool.push_transaction(tx3), ReturnCode::Accepted);
+    assert!(!mempool.contains(&hash1));
+    assert!(mempool.contains(&hash2));
+    assert!(mempool.contains(&hash3));
+
+    // A payment to another recipient with the same validity start height replaces the transaction
+    // with the lowest fee as well.
+    let tx4 = sign(Transaction::new_basic(
+        address_a.clone(),
+        Address::from([3u8; Address::SIZE]),
+        Coin::from_u64_unchecked(10),
+        Coin::from_u64_unchecked(400),
+        1,
+        NetworkId::UnitAlbatross,
+    ));
+    let hash4 = tx4.hash();
+    assert_eq!(mempool.push_transaction(tx4), ReturnCode::Accepted);
+    assert!(!mempool.contains(&hash2));
+    assert!(mempool.contains(&hash3));
+    assert!(mempool.contains(&hash4));
+
+    // Replacements are accepted even if the sender has reached its limit of pending transactions.
+    mempool.set_policy(PolicyConfig {
+        max_per_sender: Some(2),
+        ..Default::default()
+    });
+    let tx5 = sign(Transaction::new_basic(
+        address_a,
+        address_b,
+        Coin::from_u64_unchecked(10),
+        Coin::from_u64_unchecked(350),
+        1,
+        NetworkId::UnitAlbatross,
+    ));
+    let hash5 = tx5.hash();
+    assert_eq!(mempool.push_transaction(tx5), ReturnCode::Accepted);
+    assert!(!mempool.contains(&hash3));
+    assert!(mempool.contains(&hash4));
+    assert!(mempool.contains(&hash5));
+}
+
+#[test]
+fn drop_lowest_fee_tx_if_full() {
+    let env = VolatileEnvironment::new(Blockchain::NUM_DATABASES).unwrap();
+
+    let blockchain = Arc::new(Blockchain::new(env.clone(), NetworkId::UnitAlbatross).unwrap());
+
+    let config = MempoolConfig {
+        size_max: 2,
+        ..Default::default()
+    };
+    let mempool = Mempool::new(blockchain.clone(), config);
+
+    let keypair_a = KeyPair::generate_default_csprng();
+
+    let address_a = Address::from(&keypair_a.public);
+
+    let address_b = Address::from([2u8; Address::SIZE]);
+
+    // Give a reward to address_a.
+    let reward = Inherent {
+        ty: InherentType::Reward,
+        target: address_a.clone(),
+        value: Coin::from_u64_unchecked(10000),
+        data: vec![],
+    };
+
+    let mut txn = WriteTransaction::new(&env);
+
+    blockchain
+        .state()
+        .accounts
+        .commit(&mut txn, &[], &[reward], 1, 1)
+        .unwrap();
+
+    txn.commit();
+
+    let mut hashes = vec![];
+    // The fees are too close to each other for the transactions to replace each other.
+    for fee in [200, 205, 195, 210].iter() {
+        let mut tx = Transaction::new_basic(
+            address_a.clone(),
+            address_b.clone(),
+            Coin::from_u64_unchecked(10),
+            Coin::from_u64_unchecked(*fee),
+            1,
+            NetworkId::UnitAlbatross,
+        );
+
+        let signature_proof =
+            SignatureProof::from(keypair_a.public, keypair_a.sign(&tx.serialize_content()));
+
+        tx.proof = signature_proof.serialize_to_vec();
+
+        hashes.push(tx.hash());
+
+        let expected = if *fee == 195 {
+            ReturnCode::FeeTooLow
+        } else {
+            ReturnCode::Accepted
+        };
+        assert_eq!(mempool.push_transaction(tx), expected);
+    }
+
+    // The transaction with the lowest fee was rejected, the one with the second lowest fee was
+    // dropped to make room for the one with the highest fee.
+    assert!(!mempool.contains(&hashes[0]));
+    assert!(mempool.contains(&hashes[1]));
+    assert!(!mempool.contains(&hashes[2]));
+    assert!(mempool.contains(&hashes[3]));
+}
+
+#[test]
+fn get_transactions_by_addresses() {
+    let env = VolatileEnvironment::new(Blockchain::NUM_DATABASES).unwrap();
+
+    let blockchain = Arc::new(Blockchain::new(env.clone(), NetworkId::UnitAlbatross).unwrap());
+
+    let mempool = Mempool::new(blockchain.clone(), MempoolConfig::default());
+
+    let keypair_a = KeyPair::generate_default_csprng();
+
+    let address_a = Address::from(&keypair_a.public);
+
+    let address_b = Address::from([2u8; Address::SIZE]);
+
+    let address_c = Address::from([3u8; Address::SIZE]);
+
+    // Give a reward to address_a.
+    let reward = Inherent {
+        ty: InherentType::Reward,
+        target: address_a.clone(),
+        value: Coin::from_u64_unchecked(10000),
+        data: vec![],
+    };
+
+    let mut txn = WriteTransaction::new(&env);
+
+    blockchain
+        .state()
+        .accounts
+        .commit(&mut txn, &[], &[reward], 0, 0)
+        .unwrap();
+
+    txn.commit();
+
+    // Push two transactions from address_a to address_b.
+    for validity_start_height in 0..2 {
+        let mut tx = Transaction::new_basic(
+            address_a.clone(),
+            address_b.clone(),
+            Coin::from_u64_unchecked(10),
+            Coin::from_u64_unchecked(0),
+            validity_start_height,
+            NetworkId::UnitAlbatross,
+        );
+
+        let signature_proof =
+            SignatureProof::from(keypair_a.public, keypair_a.sign(&tx.serialize_content()));
+
+        tx.proof = signature_proof.serialize_to_vec();
+
+        assert_eq!(mempool.push_transaction(tx), ReturnCode::Accepted);
+    }
+
+    let addresses =
+        |address: &Address| -> HashSet<Address> { vec![address.clone()].into_iter().collect() };
+
+    // Transactions are found by sender and by recipient.
+    assert_eq!(
+        mempool
+            .get_transactions_by_addresses(addresses(&address_a), 10)
+            .len(),
+        2
+    );
+    assert_eq!(
+        mempool
+            .get_transactions_by_addresses(addresses(&address_b), 10)
+            .len(),
+        2
+    );
+
+    // Unrelated addresses don't match.
+    assert!(mempool
+        .get_transactions_by_addresses(addresses(&address_c), 10)
+        .is_empty());
+
+    // The number of returned transactions is limited.
+    assert_eq!(
+        mempool
+            .get_transactions_by_addresses(addresses(&address_b), 1)
+            .len(),
+        1
+    );
+}
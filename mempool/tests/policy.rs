@@ -0,0 +1,158 @@
+use std::convert::TryFrom;
+
+use nimiq_keys::Address;
+use nimiq_mempool::filter::Rules;
+use nimiq_mempool::policy::{
+    DynamicFeeConfig, MempoolPolicy, PolicyConfig, PolicyContext, PolicyRejection, RulesPolicy,
+    TransactionKindRules,
+};
+use nimiq_primitives::account::AccountType;
+use nimiq_primitives::coin::Coin;
+use nimiq_primitives::networks::NetworkId;
+use nimiq_transaction::{Transaction, TransactionFlags};
+
+fn context(mempool_size: usize, sender_pending: usize) -> PolicyContext {
+    PolicyContext {
+        mempool_size,
+        mempool_size_max: 100,
+        sender_pending,
+        is_replacement: false,
+    }
+}
+
+fn basic_transaction(fee: u64) -> Transaction {
+    Transaction::new_basic(
+        Address::from([32u8; Address::SIZE]),
+        Address::from([213u8; Address::SIZE]),
+        Coin::try_from(100).unwrap(),
+        Coin::try_from(fee).unwrap(),
+        0,
+        NetworkId::Main,
+    )
+}
+
+#[test]
+fn it_accepts_and_rejects_transactions() {
+    let mut s: Rules = Rules::default();
+    s.tx_fee = Coin::try_from(1).unwrap();
+
+    let p = RulesPolicy::new(s);
+
+    let mut tx = basic_transaction(0);
+    assert_eq!(p.check(&tx, &context(0, 0)), Err(PolicyRejection::Filtered));
+    tx.fee = Coin::try_from(1).unwrap();
+    assert_eq!(p.check(&tx, &context(0, 0)), Ok(()));
+}
+
+#[test]
+fn it_limits_transactions_per_sender() {
+    let config = PolicyConfig {
+        max_per_sender: Some(2),
+        ..Default::default()
+    };
+    let p = config.build(Rules::default());
+
+    let tx = basic_transaction(0);
+    assert_eq!(p.check(&tx, &context(0, 1)), Ok(()));
+    assert_eq!(
+        p.check(&tx, &context(0, 2)),
+        Err(PolicyRejection::SenderLimitExceeded)
+    );
+
+    // Replacements don't add to the pending transactions of the sender.
+    let replacement = PolicyContext {
+        is_replacement: true,
+        ..context(0, 2)
+    };
+    assert_eq!(p.check(&tx, &replacement), Ok(()));
+}
+
+#[test]
+fn it_raises_the_fee_as_the_mempool_fills() {
+    let config = PolicyConfig {
+        fee: Some(DynamicFeeConfig {
+            min_fee_per_byte: 0.0,
+            max_fee_per_byte: 10.0,
+            threshold: 0.5,
+        }),
+        ..Default::default()
+    };
+    let p = config.build(Rules::default());
+
+    // Without a proof, the transaction is serialized in the extended format with 69 bytes, so
+    // this is a fee of 1 luna per byte.
+    let tx = basic_transaction(69);
+    assert_eq!(p.check(&tx, &context(0, 0)), Ok(()));
+    assert_eq!(p.check(&tx, &context(50, 0)), Ok(()));
+    assert_eq!(p.check(&tx, &context(54, 0)), Ok(()));
+    assert_eq!(
+        p.check(&tx, &context(60, 0)),
+        Err(PolicyRejection::FeeTooLow)
+    );
+    assert_eq!(
+        p.check(&tx, &context(100, 0)),
+        Err(PolicyRejection::FeeTooLow)
+    );
+    // Replacements have to pay the minimum fee, too.
+    let replacement = PolicyContext {
+        is_replacement: true,
+        ..context(60, 0)
+    };
+    assert_eq!(p.check(&tx, &replacement), Err(PolicyRejection::FeeTooLow));
+}
+
+#[test]
+fn it_applies_address_lists() {
+    let tx = basic_transaction(0);
+
+    let config = PolicyConfig {
+        deny: vec![tx.recipient.clone()],
+        ..Default::default()
+    };
+    let p = config.build(Rules::default());
+    assert_eq!(p.check(&tx, &context(0, 0)), Err(PolicyRejection::Filtered));
+
+    let config = PolicyConfig {
+        allow: vec![Address::from([1u8; Address::SIZE])],
+        ..Default::default()
+    };
+    let p = config.build(Rules::default());
+    assert_eq!(p.check(&tx, &context(0, 0)), Err(PolicyRejection::Filtered));
+
+    let config = PolicyConfig {
+        allow: vec![tx.sender.clone()],
+        ..Default::default()
+    };
+    let p = config.build(Rules::default());
+    assert_eq!(p.check(&tx, &context(0, 0)), Ok(()));
+}
+
+#[test]
+fn it_applies_rules_per_transaction_kind() {
+    let config = PolicyConfig {
+        htlc_creation: Some(TransactionKindRules {
+            allowed: false,
+            ..Default::default()
+        }),
+        vesting_creation: Some(TransactionKindRules {
+            min_value: Coin::try_from(1000).unwrap(),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let p = config.build(Rules::default());
+
+    // Basic transactions are not affected.
+    let tx = basic_transaction(0);
+    assert_eq!(p.check(&tx, &context(0, 0)), Ok(()));
+
+    let mut tx = basic_transaction(0);
+    tx.recipient_type = AccountType::HTLC;
+    tx.flags = TransactionFlags::CONTRACT_CREATION;
+    assert_eq!(p.check(&tx, &context(0, 0)), Err(PolicyRejection::Filtered));
+
+    tx.recipient_type = AccountType::Vesting;
+    assert_eq!(p.check(&tx, &context(0, 0)), Err(PolicyRejection::Filtered));
+    tx.value = Coin::try_from(1000).unwrap();
+    assert_eq!(p.check(&tx, &context(0, 0)), Ok(()));
+}
@@ -7,6 +7,7 @@ extern crate nimiq_blockchain_albatross as blockchain_albatross;
 extern crate nimiq_consensus_albatross as consensus_albatross;
 extern crate nimiq_mempool as mempool;
 extern crate nimiq_network_albatross as network;
+extern crate nimiq_validator as validator;
 
 use std::fs::File;
 use std::io::Read;
@@ -27,6 +28,8 @@ use crate::error::Error;
 pub use crate::metrics::chain::{AbstractChainMetrics, AlbatrossChainMetrics};
 use crate::metrics::mempool::MempoolMetrics;
 use crate::metrics::network::NetworkMetrics;
+pub use crate::metrics::sync::SyncMetrics;
+pub use crate::metrics::validator::{ValidatorDutyMetrics, ValidatorUnparkMetrics};
 
 macro_rules! attributes {
     // Empty attributes.
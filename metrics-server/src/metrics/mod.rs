@@ -1,3 +1,5 @@
 pub(crate) mod chain;
 pub(crate) mod mempool;
 pub(crate) mod network;
+pub(crate) mod sync;
+pub(crate) mod validator;
@@ -0,0 +1,67 @@
+use std::io;
+use std::sync::Arc;
+
+use blockchain_albatross::{AbstractBlockchain, Blockchain};
+use consensus_albatross::sync::status::{SyncProgress, SyncStage};
+
+use crate::server;
+use crate::server::SerializationType;
+
+pub struct SyncMetrics {
+    blockchain: Arc<Blockchain>,
+    progress: SyncProgress,
+}
+
+impl SyncMetrics {
+    pub fn new(blockchain: Arc<Blockchain>, progress: SyncProgress) -> Self {
+        SyncMetrics {
+            blockchain,
+            progress,
+        }
+    }
+}
+
+impl server::Metrics for SyncMetrics {
+    fn metrics(
+        &self,
+        serializer: &mut server::MetricsSerializer<SerializationType>,
+    ) -> Result<(), io::Error> {
+        let status = self.progress.status(self.blockchain.block_number());
+
+        for (stage, name) in &[
+            (SyncStage::History, "history"),
+            (SyncStage::BatchSets, "batch_sets"),
+            (SyncStage::BlockQueue, "block_queue"),
+            (SyncStage::Following, "following"),
+        ] {
+            serializer.metric_with_attributes(
+                "sync_stage",
+                (status.stage == *stage) as u8,
+                attributes! {"stage" => name},
+            )?;
+        }
+
+        serializer.metric("sync_block_number", status.block_number)?;
+        if let Some(target_block) = status.target_block {
+            serializer.metric("sync_target_block", target_block)?;
+        }
+        if let Some(target_epoch) = status.target_epoch {
+            serializer.metric("sync_target_epoch", target_epoch)?;
+        }
+        serializer.metric("sync_downloaded_chunks", status.downloaded_chunks)?;
+        serializer.metric("sync_total_chunks", status.total_chunks)?;
+        if let Some(eta) = status.eta {
+            serializer.metric("sync_eta_seconds", eta.as_secs())?;
+        }
+
+        for cluster in &status.clusters {
+            serializer.metric_with_attributes(
+                "sync_cluster_peers",
+                cluster.num_peers,
+                attributes! {"first_epoch" => cluster.first_epoch, "checkpoint" => cluster.checkpoint},
+            )?;
+        }
+
+        Ok(())
+    }
+}
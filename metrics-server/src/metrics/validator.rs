@@ -0,0 +1,107 @@
+use std::io;
+use std::sync::Arc;
+
+use validator::duty::DutyTracker;
+use validator::validator_metrics::ValidatorMetrics;
+
+use crate::server;
+use crate::server::SerializationType;
+
+pub struct ValidatorDutyMetrics {
+    duty_tracker: Arc<DutyTracker>,
+}
+
+impl ValidatorDutyMetrics {
+    pub fn new(duty_tracker: Arc<DutyTracker>) -> Self {
+        ValidatorDutyMetrics { duty_tracker }
+    }
+}
+
+impl server::Metrics for ValidatorDutyMetrics {
+    fn metrics(
+        &self,
+        serializer: &mut server::MetricsSerializer<SerializationType>,
+    ) -> Result<(), io::Error> {
+        let report = self.duty_tracker.report();
+
+        serializer.metric("validator_active", report.slot_band.is_some() as u8)?;
+        serializer.metric("validator_slots", report.num_slots)?;
+        serializer.metric("validator_upcoming_slots", report.upcoming_slots.len())?;
+
+        serializer.metric_with_attributes(
+            "validator_blocks",
+            report.counts.produced_blocks,
+            attributes! {"outcome" => "produced"},
+        )?;
+        serializer.metric_with_attributes(
+            "validator_blocks",
+            report.counts.missed_blocks,
+            attributes! {"outcome" => "missed"},
+        )?;
+
+        serializer.metric_with_attributes(
+            "validator_votes",
+            report.counts.signed_votes,
+            attributes! {"outcome" => "signed"},
+        )?;
+        serializer.metric_with_attributes(
+            "validator_votes",
+            report.counts.included_votes,
+            attributes! {"outcome" => "included"},
+        )?;
+        serializer.metric_with_attributes(
+            "validator_votes",
+            report.counts.missed_votes,
+            attributes! {"outcome" => "missed"},
+        )?;
+
+        Ok(())
+    }
+}
+
+pub struct ValidatorUnparkMetrics {
+    metrics: Arc<ValidatorMetrics>,
+}
+
+impl ValidatorUnparkMetrics {
+    pub fn new(metrics: Arc<ValidatorMetrics>) -> Self {
+        ValidatorUnparkMetrics { metrics }
+    }
+}
+
+impl server::Metrics for ValidatorUnparkMetrics {
+    fn metrics(
+        &self,
+        serializer: &mut server::MetricsSerializer<SerializationType>,
+    ) -> Result<(), io::Error> {
+        serializer.metric("validator_parked", self.metrics.parked_count())?;
+
+        serializer.metric_with_attributes(
+            "validator_unparks",
+            self.metrics.unpark_sent_count(),
+            attributes! {"outcome" => "sent"},
+        )?;
+        serializer.metric_with_attributes(
+            "validator_unparks",
+            self.metrics.unpark_included_count(),
+            attributes! {"outcome" => "included"},
+        )?;
+        serializer.metric_with_attributes(
+            "validator_unparks",
+            self.metrics.unpark_dropped_count(),
+            attributes! {"outcome" => "dropped"},
+        )?;
+        serializer.metric_with_attributes(
+            "validator_unparks",
+            self.metrics.unpark_rejected_count(),
+            attributes! {"outcome" => "rejected"},
+        )?;
+        serializer.metric_with_attributes(
+            "validator_unparks",
+            self.metrics.unpark_limit_reached_count(),
+            attributes! {"outcome" => "limit_reached"},
+        )?;
+
+        Ok(())
+    }
+}
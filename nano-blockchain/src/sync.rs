@@ -12,6 +12,19 @@ impl NanoBlockchain {
     /// This brings the node from the genesis block all the way to the most recent election block.
     /// It is the default way to sync for a nano node.
     pub fn push_zkp(&mut self, block: Block, proof: NanoProof) -> Result<PushResult, PushError> {
+        Self::verify_zkp(&self.genesis_block, &block, proof)?;
+
+        self.push_verified_zkp(block)
+    }
+
+    /// Verifies an election block and a proof that there is a valid chain between the given
+    /// genesis block and that block. Verifying the proof takes up to a second, so this doesn't
+    /// need a blockchain and can be done before locking it (see `push_verified_zkp`).
+    pub fn verify_zkp(
+        genesis_block: &Block,
+        block: &Block,
+        proof: NanoProof,
+    ) -> Result<(), PushError> {
         // Must be an election block.
         assert!(block.is_election());
 
@@ -31,12 +44,11 @@ impl NanoBlockchain {
         }
 
         // Prepare the inputs to verify the proof.
-        let initial_block_number = self.genesis_block.block_number();
+        let initial_block_number = genesis_block.block_number();
 
-        let initial_header_hash = <[u8; 32]>::from(self.genesis_block.hash());
+        let initial_header_hash = <[u8; 32]>::from(genesis_block.hash());
 
-        let initial_public_keys = self
-            .genesis_block
+        let initial_public_keys = genesis_block
             .validators()
             .unwrap()
             .to_pks()
@@ -71,6 +83,14 @@ impl NanoBlockchain {
             return Err(PushError::InvalidZKP);
         }
 
+        Ok(())
+    }
+
+    /// Pushes an election block whose proof was verified with `verify_zkp`.
+    pub fn push_verified_zkp(&mut self, block: Block) -> Result<PushResult, PushError> {
+        // Must be an election block.
+        assert!(block.is_election());
+
         // At this point we know that the block is correct. We just have to push it.
 
         // Get write transaction for ChainStore.
@@ -1,6 +1,8 @@
 use nimiq_account::Account;
 use nimiq_blockchain_albatross::{AbstractBlockchain, HistoryTreeProof};
 use nimiq_hash::Blake2bHash;
+use nimiq_keys::Address;
+use nimiq_tree_primitives::accounts_proof::AccountsProof;
 use nimiq_tree_primitives::accounts_tree_chunk::AccountsTreeChunk;
 
 use crate::blockchain::NanoBlockchain;
@@ -35,12 +37,47 @@ impl NanoBlockchain {
         Ok(())
     }
 
+    /// Verify a Merkle proof for a set of accounts. It checks if the accounts are part of the
+    /// Accounts Tree at the block with the given hash and that the proof proves either the account
+    /// or its absence for every one of the given addresses. It returns Ok if the proof is valid.
+    pub fn check_accounts_proof(
+        &self,
+        block_hash: &Blake2bHash,
+        addresses: &[Address],
+        accounts_proof: &mut AccountsProof<Account>,
+    ) -> Result<(), NanoError> {
+        // Get the block.
+        let block = self
+            .get_block(block_hash, false, None)
+            .ok_or(NanoError::MissingBlock)?;
+
+        // Verify the accounts proof. This must come first, since an empty proof has no root.
+        if !accounts_proof.verify() {
+            return Err(NanoError::WrongProof);
+        }
+
+        // Check the root of the accounts proof against the state root.
+        if &accounts_proof.root_hash() != block.state_root() {
+            return Err(NanoError::WrongProof);
+        }
+
+        // Otherwise a missing account can't be told apart from an account that was left out.
+        if !addresses
+            .iter()
+            .all(|address| accounts_proof.proves(address))
+        {
+            return Err(NanoError::WrongProof);
+        }
+
+        Ok(())
+    }
+
     /// Verify a Merkle proof for a transaction. It checks if the transaction is part of the History
     /// Tree at the block with the given hash. It returns Ok if the proof is valid.
     pub fn check_tx(
-        &mut self,
+        &self,
         block_hash: Blake2bHash,
-        tx_proof: HistoryTreeProof,
+        tx_proof: &HistoryTreeProof,
     ) -> Result<(), NanoError> {
         // Get the block.
         let block = self
@@ -3,7 +3,7 @@ use std::io;
 use ark_groth16::Proof;
 use ark_mnt6_753::MNT6_753;
 use ark_relations::r1cs::SynthesisError;
-use ark_serialize::SerializationError;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, SerializationError};
 use thiserror::Error;
 
 #[cfg(feature = "prover")]
@@ -19,6 +19,20 @@ pub struct NanoZKP;
 /// This is the proof type for the NanoZKP. It is just an alias, for convenience.
 pub type NanoProof = Proof<MNT6_753>;
 
+impl NanoZKP {
+    /// Serializes a proof, so that it can be stored or sent to other nodes.
+    pub fn serialize_proof(proof: &NanoProof) -> Result<Vec<u8>, NanoZKPError> {
+        let mut bytes = Vec::with_capacity(proof.serialized_size());
+        proof.serialize(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// Deserializes a proof that was serialized with `serialize_proof`.
+    pub fn deserialize_proof(bytes: &[u8]) -> Result<NanoProof, NanoZKPError> {
+        Ok(NanoProof::deserialize(bytes)?)
+    }
+}
+
 #[derive(Error, Debug)]
 pub enum NanoZKPError {
     #[error("filesystem error")]
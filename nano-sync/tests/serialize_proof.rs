@@ -0,0 +1,23 @@
+use ark_ec::ProjectiveCurve;
+use ark_groth16::Proof;
+use ark_mnt6_753::{G1Projective, G2Projective};
+use ark_std::{test_rng, UniformRand};
+
+use nimiq_nano_sync::{NanoProof, NanoZKP};
+
+#[test]
+fn it_can_serialize_proofs() {
+    let rng = &mut test_rng();
+
+    let proof: NanoProof = Proof {
+        a: G1Projective::rand(rng).into_affine(),
+        b: G2Projective::rand(rng).into_affine(),
+        c: G1Projective::rand(rng).into_affine(),
+    };
+
+    let bytes = NanoZKP::serialize_proof(&proof).unwrap();
+    assert_eq!(NanoZKP::deserialize_proof(&bytes).unwrap(), proof);
+
+    // Truncated proofs are rejected.
+    assert!(NanoZKP::deserialize_proof(&bytes[..bytes.len() - 1]).is_err());
+}
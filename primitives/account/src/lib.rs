@@ -25,6 +25,7 @@ use beserial::{
 use hash::{Hash, HashOutput, Hasher, SerializeContent};
 use keys::Address;
 pub use primitives::account::AccountType;
+use primitives::account::ValidatorId;
 use primitives::coin::{Coin, CoinConvertError, CoinParseError};
 use transaction::{Transaction, TransactionError};
 
@@ -33,7 +34,7 @@ use crate::inherent::AccountInherentInteraction;
 pub use self::basic_account::BasicAccount;
 pub use self::htlc_contract::HashedTimeLockedContract;
 pub use self::inherent::{Inherent, InherentType};
-pub use self::staking_contract::StakingContract;
+pub use self::staking_contract::{StakingContract, StakingContractEntry};
 pub use self::vesting_contract::VestingContract;
 
 pub mod basic_account;
@@ -113,6 +114,7 @@ macro_rules! invoke_account_instance {
             Account::Vesting(account) => account.$name($( $arg ),*),
             Account::HTLC(account) => account.$name($( $arg ),*),
             Account::Staking(account) => account.$name($( $arg ),*),
+            Account::StakingEntry(_) => Err(AccountError::InvalidStakingEntry),
         }
     }
 }
@@ -129,6 +131,10 @@ pub enum Account {
     HTLC(HashedTimeLockedContract),
     #[cfg_attr(feature = "serde-derive", serde(skip))]
     Staking(StakingContract),
+    /// A validator or stake of the staking contract, which is stored in its own entry of the
+    /// accounts tree. Entries are never returned as accounts.
+    #[cfg_attr(feature = "serde-derive", serde(skip))]
+    StakingEntry(StakingContractEntry),
 }
 
 impl Account {
@@ -136,6 +142,10 @@ impl Account {
         balance: Coin::ZERO,
     });
 
+    /// The serialized type of staking contract entries. It follows the values of `AccountType`,
+    /// since entries don't have an account type of their own.
+    const STAKING_ENTRY_TYPE: u8 = 4;
+
     pub fn new_basic(balance: Coin) -> Account {
         Account::Basic(BasicAccount { balance })
     }
@@ -145,7 +155,7 @@ impl Account {
             Account::Basic(_) => AccountType::Basic,
             Account::Vesting(_) => AccountType::Vesting,
             Account::HTLC(_) => AccountType::HTLC,
-            Account::Staking(_) => AccountType::Staking,
+            Account::Staking(_) | Account::StakingEntry(_) => AccountType::Staking,
         }
     }
 
@@ -155,12 +165,13 @@ impl Account {
             Account::Vesting(ref account) => account.balance,
             Account::HTLC(ref account) => account.balance,
             Account::Staking(ref account) => account.balance,
+            Account::StakingEntry(ref entry) => entry.balance(),
         }
     }
 
     pub fn is_to_be_pruned(&self) -> bool {
         match *self {
-            Account::Basic(_) | Account::Staking(_) => false,
+            Account::Basic(_) | Account::Staking(_) | Account::StakingEntry(_) => false,
             _ => self.balance() == Coin::ZERO,
         }
     }
@@ -213,6 +224,7 @@ impl Account {
             Account::Staking(_) => {
                 StakingContract::check_incoming_transaction(transaction, block_height, time)
             }
+            Account::StakingEntry(_) => Err(AccountError::InvalidStakingEntry),
         }
     }
 }
@@ -384,7 +396,11 @@ impl AccountInherentInteraction for Account {
 impl Serialize for Account {
     fn serialize<W: WriteBytesExt>(&self, writer: &mut W) -> Result<usize, SerializingError> {
         let mut size: usize = 0;
-        size += Serialize::serialize(&self.account_type(), writer)?;
+        let account_type = match *self {
+            Account::StakingEntry(_) => Account::STAKING_ENTRY_TYPE,
+            _ => u8::from(self.account_type()),
+        };
+        size += Serialize::serialize(&account_type, writer)?;
 
         match *self {
             Account::Basic(ref account) => {
@@ -399,6 +415,9 @@ impl Serialize for Account {
             Account::Staking(ref account) => {
                 size += Serialize::serialize(&account, writer)?;
             }
+            Account::StakingEntry(ref entry) => {
+                size += Serialize::serialize(entry, writer)?;
+            }
         }
 
         Ok(size)
@@ -420,6 +439,9 @@ impl Serialize for Account {
             Account::Staking(ref account) => {
                 size += Serialize::serialized_size(&account);
             }
+            Account::StakingEntry(ref entry) => {
+                size += Serialize::serialized_size(entry);
+            }
         }
 
         size
@@ -428,8 +450,14 @@ impl Serialize for Account {
 
 impl Deserialize for Account {
     fn deserialize<R: ReadBytesExt>(reader: &mut R) -> Result<Self, SerializingError> {
-        let account_type: AccountType = Deserialize::deserialize(reader)?;
+        let account_type: u8 = Deserialize::deserialize(reader)?;
+        if account_type == Account::STAKING_ENTRY_TYPE {
+            let entry: StakingContractEntry = Deserialize::deserialize(reader)?;
+            return Ok(Account::StakingEntry(entry));
+        }
 
+        let account_type =
+            AccountType::try_from(account_type).map_err(|_| SerializingError::InvalidValue)?;
         match account_type {
             AccountType::Basic => {
                 let account: BasicAccount = Deserialize::deserialize(reader)?;
@@ -721,6 +749,12 @@ pub enum AccountError {
     CoinConvert(#[from] CoinConvertError),
     #[error("Invalid inherent")]
     InvalidInherent,
+    #[error("Staking contract entries can't be used as accounts")]
+    InvalidStakingEntry,
+    #[error("Missing accounts tree node: {0}")]
+    MissingTreeNode(String),
+    #[error("Unknown validator: {0}")]
+    UnknownValidator(ValidatorId),
 }
 
 /// A small wrapper over a list of accounts with addresses. This is only used to have method
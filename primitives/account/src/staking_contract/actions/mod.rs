@@ -84,6 +84,7 @@ impl AccountTransactionInteraction for StakingContract {
                 IncomingStakingTransactionData::CreateValidator {
                     validator_key,
                     reward_address,
+                    commission,
                     ..
                 } => {
                     // Create validator id from creation tx hash
@@ -93,6 +94,7 @@ impl AccountTransactionInteraction for StakingContract {
                         validator_id,
                         validator_key,
                         reward_address,
+                        commission,
                         transaction.value,
                     )?;
                     None
@@ -102,6 +104,7 @@ impl AccountTransactionInteraction for StakingContract {
                     old_validator_key: _,
                     new_validator_key,
                     new_reward_address,
+                    new_commission,
                     signature,
                     ..
                 } => {
@@ -112,6 +115,7 @@ impl AccountTransactionInteraction for StakingContract {
                         &validator_id,
                         new_validator_key,
                         new_reward_address,
+                        new_commission,
                     )?;
                     Some(receipt.serialize_to_vec())
                 }
@@ -145,7 +149,8 @@ impl AccountTransactionInteraction for StakingContract {
                 } => {
                     let staker_address =
                         staker_address.unwrap_or_else(|| transaction.sender.clone());
-                    self.stake(staker_address, transaction.value, &validator_id)?;
+                    self.stake(staker_address.clone(), transaction.value, &validator_id)?;
+                    self.add_recent_stake(&validator_id, &staker_address, transaction.value);
                     None
                 }
             };
@@ -166,7 +171,12 @@ impl AccountTransactionInteraction for StakingContract {
                         .map(|receipt| receipt.serialize_to_vec()))
                 }
                 SelfStakingTransactionData::ReactivateStake(validator_id) => {
-                    self.reactivate_recipient(staker_address, transaction.value, &validator_id)?;
+                    self.reactivate_recipient(
+                        staker_address.clone(),
+                        transaction.value,
+                        &validator_id,
+                    )?;
+                    self.add_recent_stake(&validator_id, &staker_address, transaction.value);
                     Ok(None)
                 }
                 SelfStakingTransactionData::RededicateStake {
@@ -174,10 +184,11 @@ impl AccountTransactionInteraction for StakingContract {
                     to_validator_id,
                 } => {
                     self.rededicate_stake_receiver(
-                        staker_address,
+                        staker_address.clone(),
                         transaction.value,
                         &to_validator_id,
                     )?;
+                    self.add_recent_stake(&to_validator_id, &staker_address, transaction.value);
                     Ok(None)
                 }
             }
@@ -234,6 +245,11 @@ impl AccountTransactionInteraction for StakingContract {
                 } => {
                     let staker_address_ref = staker_address.as_ref().unwrap_or(&transaction.sender);
                     self.revert_stake(staker_address_ref, transaction.value, &validator_id)?;
+                    self.revert_add_recent_stake(
+                        &validator_id,
+                        staker_address_ref,
+                        transaction.value,
+                    );
                 }
             }
         } else {
@@ -253,16 +269,26 @@ impl AccountTransactionInteraction for StakingContract {
                         transaction.value,
                         &validator_key,
                     )?;
+                    self.revert_add_recent_stake(
+                        &validator_key,
+                        &staker_address,
+                        transaction.value,
+                    );
                 }
                 SelfStakingTransactionData::RededicateStake {
                     from_validator_id: _,
                     to_validator_id,
                 } => {
                     self.revert_rededicate_stake_receiver(
-                        staker_address,
+                        staker_address.clone(),
                         transaction.value,
                         &to_validator_id,
                     )?;
+                    self.revert_add_recent_stake(
+                        &to_validator_id,
+                        &staker_address,
+                        transaction.value,
+                    );
                 }
             }
         }
@@ -289,7 +315,7 @@ impl AccountTransactionInteraction for StakingContract {
                         .ok_or(AccountError::InvalidForSender)?;
 
                     // Check unstaking delay.
-                    if block_height < policy::election_block_after(inactive_stake.retire_time) {
+                    if block_height < Self::withdrawable_at(inactive_stake.retire_time) {
                         return Err(AccountError::InvalidForSender);
                     }
 
@@ -302,7 +328,7 @@ impl AccountTransactionInteraction for StakingContract {
                         .ok_or(AccountError::InvalidForSender)?;
 
                     // Check unstaking delay.
-                    if block_height < policy::election_block_after(inactive_validator.retire_time) {
+                    if block_height < Self::droppable_at(inactive_validator.retire_time) {
                         return Err(AccountError::InvalidForSender);
                     }
 
@@ -605,6 +631,15 @@ impl AccountInherentInteraction for StakingContract {
                 let _old_lost_rewards =
                     mem::replace(&mut self.previous_lost_rewards, current_lost_rewards);
 
+                // Added stakes. They are only swapped at the end of a batch, since an election
+                // block also finalizes a batch.
+                if inherent.ty == InherentType::FinalizeBatch {
+                    let current_added_stakes =
+                        mem::replace(&mut self.current_added_stakes, BTreeMap::new());
+                    let _old_added_stakes =
+                        mem::replace(&mut self.previous_added_stakes, current_added_stakes);
+                }
+
                 // Parking sets and disabled slots are only swapped on epoch changes.
                 if inherent.ty == InherentType::FinalizeEpoch {
                     // Swap lists around.
@@ -71,6 +71,37 @@ impl StakingContract {
         Ok(())
     }
 
+    /// Remembers stake that was added to a validator in the current batch. It only earns staker
+    /// rewards for the batches that it was held for from their start.
+    pub(super) fn add_recent_stake(
+        &mut self,
+        validator_id: &ValidatorId,
+        staker_address: &Address,
+        value: Coin,
+    ) {
+        *self
+            .current_added_stakes
+            .entry((validator_id.clone(), staker_address.clone()))
+            .or_insert(Coin::ZERO) += value;
+    }
+
+    /// Reverts `add_recent_stake`. Since macro blocks can't be reverted, the stake was always
+    /// added in the current batch.
+    pub(super) fn revert_add_recent_stake(
+        &mut self,
+        validator_id: &ValidatorId,
+        staker_address: &Address,
+        value: Coin,
+    ) {
+        let key = (validator_id.clone(), staker_address.clone());
+        if let Some(stake) = self.current_added_stakes.get_mut(&key) {
+            *stake = stake.checked_sub(value).unwrap_or(Coin::ZERO);
+            if stake.is_zero() {
+                self.current_added_stakes.remove(&key);
+            }
+        }
+    }
+
     pub(super) fn rededicate_stake_sender(
         &mut self,
         staker_address: Address,
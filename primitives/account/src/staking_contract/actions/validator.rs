@@ -26,6 +26,7 @@ pub(super) struct UnparkReceipt {
 #[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
 pub(super) struct UpdateValidatorReceipt {
     old_reward_address: Address,
+    old_commission: Option<u16>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
@@ -37,6 +38,7 @@ pub(super) struct RetirementReceipt {
 #[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
 pub(super) struct DropValidatorReceipt {
     reward_address: Address,
+    commission: Option<u16>,
     #[beserial(len_type(u32))]
     retirement_by_address: BTreeMap<Address, RetirementReceipt>,
     retire_time: u32,
@@ -50,7 +52,7 @@ pub(super) struct InactiveValidatorReceipt {
 
 /// Actions concerning a validator are:
 /// 1. Create: Creates a validator entry.
-/// 2. Update: Updates reward address and key of the validator entry.
+/// 2. Update: Updates reward address, key and commission of the validator entry.
 /// 3. Retire: Inactivates a validator entry (also starts a cooldown period used for Drop).
 /// 4. Re-activate: Re-activates a validator entry.
 /// 5. Drop: Drops a validator entry (validator must have been inactive for the cooldown period).
@@ -85,6 +87,7 @@ impl StakingContract {
         validator_id: ValidatorId,
         validator_key: BlsPublicKey,
         reward_address: Address,
+        commission: Option<u16>,
         initial_stake: Coin,
     ) -> Result<(), AccountError> {
         if self.active_validators_by_id.contains_key(&validator_id)
@@ -101,6 +104,7 @@ impl StakingContract {
             initial_stake,
             reward_address,
             validator_key,
+            commission,
         ));
 
         self.active_validators_sorted.insert(Arc::clone(&validator));
@@ -134,16 +138,21 @@ impl StakingContract {
         validator_id: &ValidatorId,
         new_validator_key: Option<BlsPublicKey>,
         new_reward_address: Option<Address>,
+        new_commission: Option<Option<u16>>,
     ) -> Result<UpdateValidatorReceipt, AccountError> {
         let mut entry = self
             .remove_validator(validator_id)
             .ok_or(AccountError::InvalidForRecipient)?;
 
         let old_reward_address = entry.as_validator().reward_address.clone();
-        entry.update_validator(new_reward_address, new_validator_key);
+        let old_commission = entry.as_validator().commission;
+        entry.update_validator(new_reward_address, new_validator_key, new_commission);
         self.restore_validator(entry)?;
 
-        Ok(UpdateValidatorReceipt { old_reward_address })
+        Ok(UpdateValidatorReceipt {
+            old_reward_address,
+            old_commission,
+        })
     }
 
     /// Reverts updating validator key.
@@ -157,12 +166,29 @@ impl StakingContract {
             .remove_validator(&validator_id)
             .ok_or(AccountError::InvalidForRecipient)?;
 
-        entry.update_validator(Some(receipt.old_reward_address), Some(old_validator_key));
+        entry.update_validator(
+            Some(receipt.old_reward_address),
+            Some(old_validator_key),
+            Some(receipt.old_commission),
+        );
         self.restore_validator(entry)?;
 
         Ok(())
     }
 
+    /// Returns the stakes that were retired when a validator was dropped, given the receipt of the
+    /// drop transaction.
+    pub fn retired_stakes_from_drop_receipt(
+        receipt: &[u8],
+    ) -> Result<Vec<(Address, Coin)>, AccountError> {
+        let receipt: DropValidatorReceipt = Deserialize::deserialize_from_vec(receipt)?;
+        Ok(receipt
+            .retirement_by_address
+            .into_iter()
+            .map(|(staker_address, retirement)| (staker_address, retirement.stake))
+            .collect())
+    }
+
     /// Drops a validator entry.
     /// This can be used to drop inactive validators.
     /// The validator must have been inactive for at least one macro block.
@@ -213,6 +239,7 @@ impl StakingContract {
 
         Ok(DropValidatorReceipt {
             reward_address: validator.reward_address.clone(),
+            commission: validator.commission,
             retirement_by_address,
             retire_time: inactive_validator.retire_time,
             unpark_receipt,
@@ -252,6 +279,7 @@ impl StakingContract {
                     balance: total_value,
                     reward_address: receipt.reward_address,
                     validator_key,
+                    commission: receipt.commission,
                     active_stake_by_address: RwLock::new(active_stake_by_address),
                 }),
                 retire_time: receipt.retire_time,
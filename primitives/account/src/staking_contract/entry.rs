@@ -0,0 +1,491 @@
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::Arc;
+
+use beserial::{Deserialize, ReadBytesExt, Serialize, SerializingError, WriteBytesExt};
+use hash::{Blake2bHash, Hash};
+use keys::Address;
+use primitives::account::ValidatorId;
+use primitives::coin::Coin;
+use primitives::slots::SlashedSlot;
+use transaction::account::staking_contract::{
+    IncomingStakingTransactionData, OutgoingStakingTransactionProof, SelfStakingTransactionData,
+};
+use transaction::Transaction;
+
+use crate::staking_contract::{InactiveStake, InactiveValidator, StakingContract, Validator};
+use crate::{AccountError, Inherent, InherentType};
+
+/// An entry of the staking contract in the accounts tree. Every validator and every stake is stored
+/// in its own entry under the address of the staking contract, so that a staking transaction only
+/// changes the entries it touches and the stake of a single staker can be proven.
+///
+/// The entry of the contract itself only holds the global state of the contract, i.e. its balance,
+/// parking, lost rewards, disabled slots and the stakes added in the current and previous batch.
+#[derive(Clone, Debug)]
+pub enum StakingContractEntry {
+    /// An active or inactive validator without the stakes delegated to it. Inactive validators have
+    /// a retire time.
+    Validator {
+        validator: Validator,
+        retire_time: Option<u32>,
+    },
+    /// The stake that a staker delegated to a validator.
+    ActiveStake {
+        validator_id: ValidatorId,
+        staker_address: Address,
+        balance: Coin,
+    },
+    /// The inactive stake of a staker.
+    InactiveStake {
+        staker_address: Address,
+        stake: InactiveStake,
+    },
+}
+
+impl StakingContractEntry {
+    const VALIDATOR_TAG: u8 = 0;
+    const ACTIVE_STAKE_TAG: u8 = 1;
+    const INACTIVE_STAKE_TAG: u8 = 2;
+
+    /// Returns the key of this entry, relative to the address of the staking contract.
+    pub fn key(&self) -> Vec<u8> {
+        match self {
+            StakingContractEntry::Validator { validator, .. } => {
+                StakingContract::validator_key(&validator.id)
+            }
+            StakingContractEntry::ActiveStake {
+                validator_id,
+                staker_address,
+                ..
+            } => StakingContract::active_stake_key(validator_id, staker_address),
+            StakingContractEntry::InactiveStake { staker_address, .. } => {
+                StakingContract::inactive_stake_key(staker_address)
+            }
+        }
+    }
+
+    /// Returns the coins held by this entry.
+    pub fn balance(&self) -> Coin {
+        match self {
+            StakingContractEntry::Validator { validator, .. } => validator.balance,
+            StakingContractEntry::ActiveStake { balance, .. } => *balance,
+            StakingContractEntry::InactiveStake { stake, .. } => stake.balance,
+        }
+    }
+}
+
+/// The entries of the staking contract that a transaction or inherent reads or writes, apart from
+/// the entry of the contract itself.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StakingContractKeys {
+    /// The validators, and whether all stakes delegated to them are needed as well. In that case,
+    /// the inactive stakes of their stakers are needed too.
+    pub validators: BTreeMap<ValidatorId, bool>,
+    /// The active stakes, by validator and staker.
+    pub active_stakes: BTreeSet<(ValidatorId, Address)>,
+    /// The inactive stakes, by staker.
+    pub inactive_stakes: BTreeSet<Address>,
+}
+
+impl StakingContractKeys {
+    fn add_validator(&mut self, validator_id: ValidatorId) {
+        self.validators.entry(validator_id).or_insert(false);
+    }
+
+    fn add_active_stake(&mut self, validator_id: ValidatorId, staker_address: Address) {
+        self.add_validator(validator_id.clone());
+        self.active_stakes.insert((validator_id, staker_address));
+    }
+}
+
+impl StakingContract {
+    /// The key of the entry of the contract itself, relative to the contract address.
+    pub const CONTRACT_KEY: u8 = 0x00;
+    /// The prefix of the keys of validator entries.
+    pub const VALIDATOR_PREFIX: u8 = 0x01;
+    /// The prefix of the keys of active stake entries.
+    pub const ACTIVE_STAKE_PREFIX: u8 = 0x02;
+    /// The prefix of the keys of inactive stake entries.
+    pub const INACTIVE_STAKE_PREFIX: u8 = 0x03;
+
+    pub fn contract_key() -> Vec<u8> {
+        vec![Self::CONTRACT_KEY]
+    }
+
+    pub fn validator_key(validator_id: &ValidatorId) -> Vec<u8> {
+        let mut key = Vec::with_capacity(1 + ValidatorId::SIZE);
+        key.push(Self::VALIDATOR_PREFIX);
+        key.extend_from_slice(validator_id.as_slice());
+        key
+    }
+
+    pub fn active_stake_key(validator_id: &ValidatorId, staker_address: &Address) -> Vec<u8> {
+        let mut key = Self::active_stakes_prefix(validator_id);
+        key.extend_from_slice(staker_address.as_bytes());
+        key
+    }
+
+    /// The prefix of the keys of all stakes delegated to the given validator.
+    pub fn active_stakes_prefix(validator_id: &ValidatorId) -> Vec<u8> {
+        let mut prefix = Vec::with_capacity(1 + ValidatorId::SIZE);
+        prefix.push(Self::ACTIVE_STAKE_PREFIX);
+        prefix.extend_from_slice(validator_id.as_slice());
+        prefix
+    }
+
+    pub fn inactive_stake_key(staker_address: &Address) -> Vec<u8> {
+        let mut key = Vec::with_capacity(1 + Address::SIZE);
+        key.push(Self::INACTIVE_STAKE_PREFIX);
+        key.extend_from_slice(staker_address.as_bytes());
+        key
+    }
+
+    /// Returns the entries that committing or reverting the given transaction touches. The
+    /// contract is the recipient of the transaction if `incoming` is set and its sender otherwise.
+    /// Reverting a `DropValidator` transaction needs its receipt to find the retired stakes.
+    ///
+    /// If the transaction is malformed, no entries are returned, since processing it fails anyway.
+    pub fn transaction_keys(
+        transaction: &Transaction,
+        incoming: bool,
+        receipt: Option<&Vec<u8>>,
+    ) -> StakingContractKeys {
+        let mut keys = StakingContractKeys::default();
+        match Self::collect_transaction_keys(&mut keys, transaction, incoming, receipt) {
+            Ok(()) => keys,
+            Err(_) => StakingContractKeys::default(),
+        }
+    }
+
+    fn collect_transaction_keys(
+        keys: &mut StakingContractKeys,
+        transaction: &Transaction,
+        incoming: bool,
+        receipt: Option<&Vec<u8>>,
+    ) -> Result<(), AccountError> {
+        if transaction.sender == transaction.recipient {
+            let data: SelfStakingTransactionData =
+                Deserialize::deserialize(&mut &transaction.data[..])?;
+            let staker_address = Self::get_self_signer(transaction)?;
+
+            // Self transactions are processed on both sides, so we always return the entries of
+            // both.
+            match data {
+                SelfStakingTransactionData::RetireStake(validator_id)
+                | SelfStakingTransactionData::ReactivateStake(validator_id) => {
+                    keys.add_active_stake(validator_id, staker_address.clone());
+                    keys.inactive_stakes.insert(staker_address);
+                }
+                SelfStakingTransactionData::RededicateStake {
+                    from_validator_id,
+                    to_validator_id,
+                } => {
+                    keys.add_active_stake(from_validator_id, staker_address.clone());
+                    keys.add_active_stake(to_validator_id, staker_address);
+                }
+            }
+        } else if incoming {
+            let data: IncomingStakingTransactionData =
+                Deserialize::deserialize(&mut &transaction.data[..])?;
+
+            match data {
+                IncomingStakingTransactionData::CreateValidator { .. } => {
+                    // The validator id is derived from the hash of the creation transaction.
+                    let validator_id: ValidatorId =
+                        transaction.hash::<Blake2bHash>().as_slice()[0..20].into();
+                    keys.add_validator(validator_id);
+                }
+                IncomingStakingTransactionData::UpdateValidator { validator_id, .. }
+                | IncomingStakingTransactionData::RetireValidator { validator_id, .. }
+                | IncomingStakingTransactionData::ReactivateValidator { validator_id, .. }
+                | IncomingStakingTransactionData::UnparkValidator { validator_id, .. } => {
+                    keys.add_validator(validator_id);
+                }
+                IncomingStakingTransactionData::Stake {
+                    validator_id,
+                    staker_address,
+                } => {
+                    let staker_address =
+                        staker_address.unwrap_or_else(|| transaction.sender.clone());
+                    keys.add_active_stake(validator_id, staker_address);
+                }
+            }
+        } else {
+            let proof: OutgoingStakingTransactionProof =
+                Deserialize::deserialize(&mut &transaction.proof[..])?;
+
+            match proof {
+                OutgoingStakingTransactionProof::Unstake(proof) => {
+                    keys.inactive_stakes.insert(proof.compute_signer());
+                }
+                OutgoingStakingTransactionProof::DropValidator { validator_id, .. } => {
+                    // Dropping a validator retires all stakes delegated to it.
+                    keys.validators.insert(validator_id, true);
+                    if let Some(receipt) = receipt {
+                        for (staker_address, _) in Self::retired_stakes_from_drop_receipt(receipt)?
+                        {
+                            keys.inactive_stakes.insert(staker_address);
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the entries that committing or reverting the given inherent touches. This contract
+    /// only needs to hold the entry of the contract itself, since finalizing an epoch retires the
+    /// validators that were parked in the previous epoch.
+    pub fn inherent_keys(&self, inherent: &Inherent) -> StakingContractKeys {
+        let mut keys = StakingContractKeys::default();
+        match inherent.ty {
+            InherentType::Slash => {
+                // A malformed slash inherent fails anyway.
+                if let Ok(slot) = SlashedSlot::deserialize_from_vec(&inherent.data) {
+                    keys.add_validator(slot.validator_id);
+                }
+            }
+            InherentType::FinalizeEpoch => {
+                for validator_id in self.previous_epoch_parking.iter() {
+                    keys.add_validator(validator_id.clone());
+                }
+            }
+            InherentType::FinalizeBatch | InherentType::Reward => {}
+        }
+        keys
+    }
+
+    /// Splits the contract into the entry of the contract itself, which doesn't contain any
+    /// validators or stakes, and the entries of its validators and stakes.
+    pub fn to_entries(&self) -> (StakingContract, Vec<StakingContractEntry>) {
+        let contract = StakingContract {
+            balance: self.balance,
+            current_epoch_parking: self.current_epoch_parking.clone(),
+            previous_epoch_parking: self.previous_epoch_parking.clone(),
+            current_lost_rewards: self.current_lost_rewards.clone(),
+            previous_lost_rewards: self.previous_lost_rewards.clone(),
+            current_disabled_slots: self.current_disabled_slots.clone(),
+            previous_disabled_slots: self.previous_disabled_slots.clone(),
+            current_added_stakes: self.current_added_stakes.clone(),
+            previous_added_stakes: self.previous_added_stakes.clone(),
+            ..Default::default()
+        };
+
+        let mut entries = Vec::new();
+        let validators = self
+            .active_validators_by_id
+            .values()
+            .map(|validator| (validator, None))
+            .chain(
+                self.inactive_validators_by_id
+                    .values()
+                    .map(|inactive| (&inactive.validator, Some(inactive.retire_time))),
+            );
+        for (validator, retire_time) in validators {
+            entries.push(StakingContractEntry::Validator {
+                validator: Validator::new(
+                    validator.id.clone(),
+                    validator.balance,
+                    validator.reward_address.clone(),
+                    validator.validator_key.clone(),
+                    validator.commission,
+                ),
+                retire_time,
+            });
+            for (staker_address, balance) in validator.active_stake_by_address.read().iter() {
+                entries.push(StakingContractEntry::ActiveStake {
+                    validator_id: validator.id.clone(),
+                    staker_address: staker_address.clone(),
+                    balance: *balance,
+                });
+            }
+        }
+
+        for (staker_address, stake) in self.inactive_stake_by_address.iter() {
+            entries.push(StakingContractEntry::InactiveStake {
+                staker_address: staker_address.clone(),
+                stake: stake.clone(),
+            });
+        }
+
+        (contract, entries)
+    }
+
+    /// Reassembles a contract from the entry of the contract itself and the entries of its
+    /// validators and stakes. Fails if a stake is delegated to a validator that doesn't exist.
+    pub fn from_entries<I: IntoIterator<Item = StakingContractEntry>>(
+        mut contract: StakingContract,
+        entries: I,
+    ) -> Result<StakingContract, SerializingError> {
+        let mut validators = Vec::new();
+        let mut active_stakes: HashMap<ValidatorId, BTreeMap<Address, Coin>> = HashMap::new();
+
+        for entry in entries {
+            match entry {
+                StakingContractEntry::Validator {
+                    validator,
+                    retire_time,
+                } => validators.push((validator, retire_time)),
+                StakingContractEntry::ActiveStake {
+                    validator_id,
+                    staker_address,
+                    balance,
+                } => {
+                    active_stakes
+                        .entry(validator_id)
+                        .or_default()
+                        .insert(staker_address, balance);
+                }
+                StakingContractEntry::InactiveStake {
+                    staker_address,
+                    stake,
+                } => {
+                    contract
+                        .inactive_stake_by_address
+                        .insert(staker_address, stake);
+                }
+            }
+        }
+
+        for (mut validator, retire_time) in validators {
+            *validator.active_stake_by_address.get_mut() =
+                active_stakes.remove(&validator.id).unwrap_or_default();
+            let validator = Arc::new(validator);
+
+            match retire_time {
+                None => {
+                    contract
+                        .active_validators_sorted
+                        .insert(Arc::clone(&validator));
+                    contract
+                        .active_validators_by_id
+                        .insert(validator.id.clone(), validator);
+                }
+                Some(retire_time) => {
+                    contract.inactive_validators_by_id.insert(
+                        validator.id.clone(),
+                        InactiveValidator {
+                            validator,
+                            retire_time,
+                        },
+                    );
+                }
+            }
+        }
+
+        if !active_stakes.is_empty() {
+            return Err(SerializingError::InvalidValue);
+        }
+
+        Ok(contract)
+    }
+}
+
+impl Serialize for StakingContractEntry {
+    fn serialize<W: WriteBytesExt>(&self, writer: &mut W) -> Result<usize, SerializingError> {
+        let mut size = 0;
+        match self {
+            StakingContractEntry::Validator {
+                validator,
+                retire_time,
+            } => {
+                size += Serialize::serialize(&Self::VALIDATOR_TAG, writer)?;
+                size += Serialize::serialize(validator, writer)?;
+                size += Serialize::serialize(retire_time, writer)?;
+            }
+            StakingContractEntry::ActiveStake {
+                validator_id,
+                staker_address,
+                balance,
+            } => {
+                size += Serialize::serialize(&Self::ACTIVE_STAKE_TAG, writer)?;
+                size += Serialize::serialize(validator_id, writer)?;
+                size += Serialize::serialize(staker_address, writer)?;
+                size += Serialize::serialize(balance, writer)?;
+            }
+            StakingContractEntry::InactiveStake {
+                staker_address,
+                stake,
+            } => {
+                size += Serialize::serialize(&Self::INACTIVE_STAKE_TAG, writer)?;
+                size += Serialize::serialize(staker_address, writer)?;
+                size += Serialize::serialize(stake, writer)?;
+            }
+        }
+        Ok(size)
+    }
+
+    fn serialized_size(&self) -> usize {
+        let mut size = /*tag*/ 1;
+        match self {
+            StakingContractEntry::Validator {
+                validator,
+                retire_time,
+            } => {
+                size += Serialize::serialized_size(validator);
+                size += Serialize::serialized_size(retire_time);
+            }
+            StakingContractEntry::ActiveStake {
+                validator_id,
+                staker_address,
+                balance,
+            } => {
+                size += Serialize::serialized_size(validator_id);
+                size += Serialize::serialized_size(staker_address);
+                size += Serialize::serialized_size(balance);
+            }
+            StakingContractEntry::InactiveStake {
+                staker_address,
+                stake,
+            } => {
+                size += Serialize::serialized_size(staker_address);
+                size += Serialize::serialized_size(stake);
+            }
+        }
+        size
+    }
+}
+
+impl Deserialize for StakingContractEntry {
+    fn deserialize<R: ReadBytesExt>(reader: &mut R) -> Result<Self, SerializingError> {
+        let tag: u8 = Deserialize::deserialize(reader)?;
+        match tag {
+            Self::VALIDATOR_TAG => Ok(StakingContractEntry::Validator {
+                validator: Deserialize::deserialize(reader)?,
+                retire_time: Deserialize::deserialize(reader)?,
+            }),
+            Self::ACTIVE_STAKE_TAG => Ok(StakingContractEntry::ActiveStake {
+                validator_id: Deserialize::deserialize(reader)?,
+                staker_address: Deserialize::deserialize(reader)?,
+                balance: Deserialize::deserialize(reader)?,
+            }),
+            Self::INACTIVE_STAKE_TAG => Ok(StakingContractEntry::InactiveStake {
+                staker_address: Deserialize::deserialize(reader)?,
+                stake: Deserialize::deserialize(reader)?,
+            }),
+            _ => Err(SerializingError::InvalidValue),
+        }
+    }
+}
+
+// Entries are compared by their serialization, since validators only compare by key and balance.
+impl PartialEq for StakingContractEntry {
+    fn eq(&self, other: &StakingContractEntry) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for StakingContractEntry {}
+
+impl PartialOrd for StakingContractEntry {
+    fn partial_cmp(&self, other: &StakingContractEntry) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for StakingContractEntry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.serialize_to_vec().cmp(&other.serialize_to_vec())
+    }
+}
@@ -20,9 +20,11 @@ use vrf::{AliasMethod, VrfSeed, VrfUseCase};
 
 use crate::AccountError;
 
+pub use self::entry::{StakingContractEntry, StakingContractKeys};
 pub use self::validator::*;
 
 pub mod actions;
+pub mod entry;
 pub mod validator;
 
 /// Struct represent an inactive staker. An inactive staker is a staker that got its stake not
@@ -35,6 +37,28 @@ pub struct InactiveStake {
     pub retire_time: u32,
 }
 
+/// A stake or validator deposit that was retired and waits to be withdrawn from the staking
+/// contract.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PendingUnstake {
+    pub kind: PendingUnstakeKind,
+    // The coins that can be withdrawn. For a validator, this is its deposit without the stakes
+    // delegated to it.
+    pub balance: Coin,
+    // The block number when the stake or validator became inactive.
+    pub retire_time: u32,
+    // The first block at which the `Unstake` or `DropValidator` transaction is valid.
+    pub withdrawable_at: u32,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PendingUnstakeKind {
+    /// An inactive stake, withdrawn with an `Unstake` transaction.
+    Stake,
+    /// The deposit of an inactive validator, withdrawn with a `DropValidator` transaction.
+    Validator(ValidatorId),
+}
+
 /// A receipt for slash inherents. It shows whether a given slot or validator was newly disabled,
 /// lost rewards or parked by a specific slash inherent. This is necessary to be able to revert
 /// slash inherents.
@@ -47,6 +71,9 @@ struct SlashReceipt {
 
 /// The struct representing the staking contract. The staking contract is a special contract that
 /// handles many functions related to validators and staking.
+///
+/// In the accounts tree, the contract is not stored as a single account. Its validators and stakes
+/// are stored in separate entries, see `StakingContractEntry`.
 #[derive(Debug)]
 pub struct StakingContract {
     // The total amount of coins staked.
@@ -76,6 +103,11 @@ pub struct StakingContract {
     // The validator slots, searchable by the validator public key, that were disabled (i.e. are no
     // longer eligible to produce blocks) at the end of the previous batch.
     pub previous_disabled_slots: BTreeMap<ValidatorId, BTreeSet<u16>>,
+    // The stakes that were added to a validator during the current batch, searchable by the
+    // validator id and the staker address. They are not eligible for staker rewards yet.
+    pub current_added_stakes: BTreeMap<(ValidatorId, Address), Coin>,
+    // The stakes that were added to a validator during the previous batch.
+    pub previous_added_stakes: BTreeMap<(ValidatorId, Address), Coin>,
 }
 
 impl StakingContract {
@@ -104,6 +136,94 @@ impl StakingContract {
             .cloned()
     }
 
+    /// Returns the first block at which a stake that became inactive at `retire_time` can be
+    /// unstaked. This is the micro block after the next election block, since macro blocks don't
+    /// contain transactions.
+    pub fn withdrawable_at(retire_time: u32) -> u32 {
+        policy::election_block_after(retire_time) + 1
+    }
+
+    /// Returns the first block at which a validator that became inactive at `retire_time` can be
+    /// dropped. Unlike stakes, it must also wait for the rewards of the last batch in which it
+    /// could have held slots, which are distributed in the macro block after its deposit became
+    /// withdrawable.
+    pub fn droppable_at(retire_time: u32) -> u32 {
+        policy::macro_block_after(Self::withdrawable_at(retire_time)) + 1
+    }
+
+    /// Returns the stakes that were added to a validator in the previous or the current batch, by
+    /// staker address. They were not held for the whole previous batch, so they don't earn a share
+    /// of its rewards.
+    pub fn recently_added_stakes(&self, validator_id: &ValidatorId) -> BTreeMap<Address, Coin> {
+        let mut stakes = BTreeMap::new();
+        let added_stakes = self
+            .previous_added_stakes
+            .iter()
+            .chain(self.current_added_stakes.iter());
+        for ((id, staker_address), stake) in added_stakes {
+            if id == validator_id {
+                *stakes.entry(staker_address.clone()).or_insert(Coin::ZERO) += *stake;
+            }
+        }
+        stakes
+    }
+
+    /// Splits the reward of a validator for the previous batch between its reward address and its
+    /// stakers, see `Validator::split_reward`. Fails if the validator doesn't exist.
+    pub fn split_reward(
+        &self,
+        validator_id: &ValidatorId,
+        reward: Coin,
+        min_share: Coin,
+    ) -> Result<Vec<(Address, Coin)>, AccountError> {
+        let validator = self
+            .get_validator(validator_id)
+            .ok_or_else(|| AccountError::UnknownValidator(validator_id.clone()))?;
+
+        Ok(validator.split_reward(reward, &self.recently_added_stakes(validator_id), min_share))
+    }
+
+    /// Get the inactive stake of a staker and the deposits of the inactive validators with the
+    /// given reward address, together with the block from which they can be withdrawn.
+    ///
+    /// The contract doesn't record who owns the deposit of a validator. Anyone with its validator
+    /// key can drop it and send the deposit to any address, so attributing the deposit to the
+    /// reward address is only a heuristic that holds if the validator is run by its reward address.
+    pub fn get_pending_unstakes(&self, address: &Address) -> Vec<PendingUnstake> {
+        let mut pending_unstakes = Vec::new();
+
+        if let Some(inactive_stake) = self.inactive_stake_by_address.get(address) {
+            pending_unstakes.push(PendingUnstake {
+                kind: PendingUnstakeKind::Stake,
+                balance: inactive_stake.balance,
+                retire_time: inactive_stake.retire_time,
+                withdrawable_at: Self::withdrawable_at(inactive_stake.retire_time),
+            });
+        }
+
+        for (validator_id, inactive_validator) in self.inactive_validators_by_id.iter() {
+            let validator = &inactive_validator.validator;
+            if &validator.reward_address != address {
+                continue;
+            }
+
+            let staker_stake: Coin = validator
+                .active_stake_by_address
+                .read()
+                .values()
+                .cloned()
+                .sum();
+            pending_unstakes.push(PendingUnstake {
+                kind: PendingUnstakeKind::Validator(validator_id.clone()),
+                balance: validator.balance - staker_stake,
+                retire_time: inactive_validator.retire_time,
+                withdrawable_at: Self::droppable_at(inactive_validator.retire_time),
+            });
+        }
+
+        pending_unstakes
+    }
+
     /// Allows to modify both active and inactive validators.
     /// It returns a validator entry, which subsumes active and inactive validators.
     /// It also allows for deferred error handling after re-adding the validator using `restore_validator`.
@@ -293,6 +413,10 @@ impl Serialize for StakingContract {
             size += SerializeWithLength::serialize::<u16, _>(slots, writer)?;
         }
 
+        // Added stakes.
+        size += SerializeWithLength::serialize::<u32, _>(&self.current_added_stakes, writer)?;
+        size += SerializeWithLength::serialize::<u32, _>(&self.previous_added_stakes, writer)?;
+
         // Collect remaining inactive stakes.
         let mut inactive_stakes = Vec::new();
         for (staker_address, inactive_stake) in self.inactive_stake_by_address.iter() {
@@ -346,6 +470,10 @@ impl Serialize for StakingContract {
             size += SerializeWithLength::serialized_size::<u16>(slots);
         }
 
+        // Added stakes.
+        size += SerializeWithLength::serialized_size::<u32>(&self.current_added_stakes);
+        size += SerializeWithLength::serialized_size::<u32>(&self.previous_added_stakes);
+
         size += Serialize::serialized_size(&0u32);
         for (staker_address, inactive_stake) in self.inactive_stake_by_address.iter() {
             size += Serialize::serialized_size(staker_address);
@@ -407,6 +535,10 @@ impl Deserialize for StakingContract {
             previous_disabled_slots.insert(key, value);
         }
 
+        // Added stakes.
+        let current_added_stakes = DeserializeWithLength::deserialize::<u32, _>(reader)?;
+        let previous_added_stakes = DeserializeWithLength::deserialize::<u32, _>(reader)?;
+
         let num_inactive_stakes: u32 = Deserialize::deserialize(reader)?;
         for _ in 0..num_inactive_stakes {
             let staker_address = Deserialize::deserialize(reader)?;
@@ -426,6 +558,8 @@ impl Deserialize for StakingContract {
             previous_lost_rewards,
             current_disabled_slots,
             previous_disabled_slots,
+            current_added_stakes,
+            previous_added_stakes,
         })
     }
 }
@@ -465,6 +599,8 @@ impl Default for StakingContract {
             previous_lost_rewards: Default::default(),
             current_disabled_slots: Default::default(),
             previous_disabled_slots: Default::default(),
+            current_added_stakes: Default::default(),
+            previous_added_stakes: Default::default(),
             inactive_stake_by_address: HashMap::new(),
         }
     }
@@ -495,6 +631,8 @@ impl Clone for StakingContract {
             previous_lost_rewards: self.previous_lost_rewards.clone(),
             current_disabled_slots: self.current_disabled_slots.clone(),
             previous_disabled_slots: self.previous_disabled_slots.clone(),
+            current_added_stakes: self.current_added_stakes.clone(),
+            previous_added_stakes: self.previous_added_stakes.clone(),
             inactive_stake_by_address: self.inactive_stake_by_address.clone(),
         }
     }
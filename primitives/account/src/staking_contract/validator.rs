@@ -14,6 +14,7 @@ use bls::CompressedPublicKey as BlsPublicKey;
 use keys::Address;
 use primitives::account::ValidatorId;
 use primitives::coin::Coin;
+use primitives::policy;
 
 use crate::{Account, AccountError};
 
@@ -30,34 +31,41 @@ pub struct Validator {
     pub reward_address: Address,
     // The validator key.
     pub validator_key: BlsPublicKey,
+    // The commission of the validator in basis points. If it is set, the rewards of the validator
+    // are shared with its stakers. Otherwise, all rewards are paid out to the reward address.
+    pub commission: Option<u16>,
     // A binary tree that stores the address and coins staked of all the stakers that delegated to
     // this validator.
     pub active_stake_by_address: RwLock<BTreeMap<Address, Coin>>,
 }
 
 impl Validator {
-    /// Creates a new validator given a stake, a validator key and a reward address. It will be
-    /// created without any stakers delegated to it.
+    /// Creates a new validator given a stake, a validator key, a reward address and an optional
+    /// commission. It will be created without any stakers delegated to it.
     pub fn new(
         id: ValidatorId,
         initial_balance: Coin,
         reward_address: Address,
         validator_key: BlsPublicKey,
+        commission: Option<u16>,
     ) -> Self {
         Validator {
             id,
             balance: initial_balance,
             reward_address,
             validator_key,
+            commission,
             active_stake_by_address: Default::default(),
         }
     }
 
-    /// Allows updating the reward address and/or the validator key of a specific validator.
+    /// Allows updating the reward address, the validator key and/or the commission of a specific
+    /// validator.
     pub fn update_validator(
         &self,
         new_reward_address: Option<Address>,
         new_validator_key: Option<BlsPublicKey>,
+        new_commission: Option<Option<u16>>,
     ) -> Self {
         let active_stake_by_address = mem::take(self.active_stake_by_address.write().deref_mut());
         Validator {
@@ -65,10 +73,61 @@ impl Validator {
             balance: self.balance,
             reward_address: new_reward_address.unwrap_or_else(|| self.reward_address.clone()),
             validator_key: new_validator_key.unwrap_or_else(|| self.validator_key.clone()),
+            commission: new_commission.unwrap_or(self.commission),
             active_stake_by_address: RwLock::new(active_stake_by_address),
         }
     }
 
+    /// Splits a reward of this validator between its reward address and its stakers. The first
+    /// entry is always the payout to the reward address.
+    ///
+    /// Without a commission, the whole reward goes to the reward address. With a commission, the
+    /// validator keeps the commission and each staker gets a share of the rest that is
+    /// proportional to its active stake, without the `recent_stakes` it added after the start of
+    /// the rewarded batch. The share of the validator's own stake and of recent stakes and any
+    /// rounding remainder go to the reward address. Shares below `min_share` go to the reward
+    /// address as well, which bounds the number of payouts. Zero shares are always omitted.
+    pub fn split_reward(
+        &self,
+        reward: Coin,
+        recent_stakes: &BTreeMap<Address, Coin>,
+        min_share: Coin,
+    ) -> Vec<(Address, Coin)> {
+        let commission = match self.commission {
+            Some(commission) => commission,
+            None => return vec![(self.reward_address.clone(), reward)],
+        };
+
+        let reward_u128 = u64::from(reward) as u128;
+        let balance = u64::from(self.balance) as u128;
+        let commission_reward =
+            reward_u128 * commission as u128 / policy::MAX_VALIDATOR_COMMISSION as u128;
+        let stakers_reward = reward_u128 - commission_reward;
+
+        let mut payouts = vec![(self.reward_address.clone(), Coin::ZERO)];
+        let mut paid_to_stakers = Coin::ZERO;
+
+        if balance > 0 {
+            for (staker_address, stake) in self.active_stake_by_address.read().iter() {
+                let eligible_stake = match recent_stakes.get(staker_address) {
+                    Some(recent_stake) => stake.checked_sub(*recent_stake).unwrap_or(Coin::ZERO),
+                    None => *stake,
+                };
+                // The share is at most the reward, since the stake is at most the balance.
+                let share = Coin::from_u64_unchecked(
+                    (stakers_reward * u64::from(eligible_stake) as u128 / balance) as u64,
+                );
+                if !share.is_zero() && share >= min_share {
+                    payouts.push((staker_address.clone(), share));
+                    paid_to_stakers += share;
+                }
+            }
+        }
+
+        payouts[0].1 = reward - paid_to_stakers;
+        payouts
+    }
+
     /// Updates the balance of a given validator.
     fn with_balance(&self, balance: Coin) -> Self {
         let active_stake_by_address = mem::take(self.active_stake_by_address.write().deref_mut());
@@ -77,6 +136,7 @@ impl Validator {
             balance,
             reward_address: self.reward_address.clone(),
             validator_key: self.validator_key.clone(),
+            commission: self.commission,
             active_stake_by_address: RwLock::new(active_stake_by_address),
         }
     }
@@ -122,6 +182,10 @@ impl Validator {
     }
 }
 
+/// The commission is part of the serialized validator, in the accounts tree as well as in the
+/// genesis accounts and in the receipts of validator transactions. Data written before validators
+/// had a commission can't be read anymore, so existing databases must be deleted and resynced from
+/// a genesis block built with this format.
 impl Serialize for Validator {
     fn serialize<W: WriteBytesExt>(&self, writer: &mut W) -> Result<usize, SerializingError> {
         let mut size = 0;
@@ -129,6 +193,7 @@ impl Serialize for Validator {
         size += Serialize::serialize(&self.balance, writer)?;
         size += Serialize::serialize(&self.reward_address, writer)?;
         size += Serialize::serialize(&self.validator_key, writer)?;
+        size += Serialize::serialize(&self.commission, writer)?;
         size += SerializeWithLength::serialize::<u32, _>(
             self.active_stake_by_address.read().deref(),
             writer,
@@ -142,6 +207,7 @@ impl Serialize for Validator {
         size += Serialize::serialized_size(&self.balance);
         size += Serialize::serialized_size(&self.reward_address);
         size += Serialize::serialized_size(&self.validator_key);
+        size += Serialize::serialized_size(&self.commission);
         size += SerializeWithLength::serialized_size::<u32>(
             self.active_stake_by_address.read().deref(),
         );
@@ -155,6 +221,7 @@ impl Deserialize for Validator {
         let balance = Deserialize::deserialize(reader)?;
         let reward_address = Deserialize::deserialize(reader)?;
         let validator_key = Deserialize::deserialize(reader)?;
+        let commission = Deserialize::deserialize(reader)?;
         let active_stake_by_address: BTreeMap<Address, Coin> =
             DeserializeWithLength::deserialize::<u32, _>(reader)?;
         Ok(Validator {
@@ -162,6 +229,7 @@ impl Deserialize for Validator {
             balance,
             reward_address,
             validator_key,
+            commission,
             active_stake_by_address: RwLock::new(active_stake_by_address),
         })
     }
@@ -198,6 +266,7 @@ impl Clone for Validator {
             balance: self.balance,
             reward_address: self.reward_address.clone(),
             validator_key: self.validator_key.clone(),
+            commission: self.commission,
             active_stake_by_address: RwLock::new(self.active_stake_by_address.read().clone()),
         }
     }
@@ -269,11 +338,13 @@ impl ValidatorEntry {
         &mut self,
         new_reward_address: Option<Address>,
         new_validator_key: Option<BlsPublicKey>,
+        new_commission: Option<Option<u16>>,
     ) {
-        self.replace(Ok(Arc::new(
-            self.as_validator()
-                .update_validator(new_reward_address, new_validator_key),
-        )))
+        self.replace(Ok(Arc::new(self.as_validator().update_validator(
+            new_reward_address,
+            new_validator_key,
+            new_commission,
+        ))))
     }
 
     /// This function will only change the validator entry if add_stake is successful.
@@ -1,9 +1,11 @@
+use std::collections::BTreeMap;
 use std::convert::TryInto;
 
 use rand::thread_rng;
 
 use beserial::{Deserialize, Serialize};
 use nimiq_account::inherent::{AccountInherentInteraction, Inherent, InherentType};
+use nimiq_account::staking_contract::{PendingUnstake, PendingUnstakeKind};
 use nimiq_account::{AccountError, AccountTransactionInteraction, AccountType, StakingContract};
 use nimiq_bls::CompressedPublicKey as BlsPublicKey;
 use nimiq_bls::KeyPair as BlsKeyPair;
@@ -14,6 +16,7 @@ use nimiq_keys::{Address, KeyPair, PrivateKey};
 use nimiq_primitives::account::ValidatorId;
 use nimiq_primitives::coin::Coin;
 use nimiq_primitives::networks::NetworkId;
+use nimiq_primitives::policy;
 use nimiq_primitives::slots::SlashedSlot;
 use nimiq_transaction::account::staking_contract::{
     IncomingStakingTransactionData, OutgoingStakingTransactionProof, SelfStakingTransactionData,
@@ -22,9 +25,10 @@ use nimiq_transaction::account::AccountTransactionVerification;
 use nimiq_transaction::{SignatureProof, Transaction, TransactionError};
 use nimiq_utils::key_rng::SecureGenerate;
 
-const CONTRACT_1: &str = "00000000000000000000000000000000000000000000000000000000000000000000";
+const CONTRACT_1: &str =
+    "000000000000000000000000000000000000000000000000000000000000000000000000000000000000";
 const CONTRACT_2: &str =
-    "0000000023c346000000000155555555555555555555555555555555555555550000000023c346000303030303030303030303030303030303030303003d4e4eb0fa2fee42501368dc41115f64741e9d9496bbc2fe4cfd407f10272eef87b839d6e25b0eb7338427d895e4209190b6c5aa580f134693623a30ebafdaf95a268b3b84a840fc45d06283d71fe4faa2c7d08cd431bbda165c53a50453015a49ca120626991ff9558be65a7958158387829d6e56e2861e80b85e8c795d93f907afb19e6e2e5aaed9a3158eac5a035189986ff5803dd18fa02bdf5535e5495ed96990665ec165b3ba86fc1a7f7dabeb0510e1823813bf5ab1a01b4fff00bcd0373bc265efa135f8755ebae72b645a890d27ce8af31417347bc3a1d9cf09db339b68d1c9a50bb9c00faeedbefe9bab5a63b580e5f79c4a30dc1bdacccec0fc6a08e0853518e88557001a612d4c30d2fbc2a126a066a94f299ac5ce61000000020202020202020202020202020202020202020202000000000bebc2005e5e5e5e5e5e5e5e5e5e5e5e5e5e5e5e5e5e5e5e0000000005f5e10000000000000000000000000000000000000000000000";
+    "0000000023c346000000000155555555555555555555555555555555555555550000000023c346000303030303030303030303030303030303030303003d4e4eb0fa2fee42501368dc41115f64741e9d9496bbc2fe4cfd407f10272eef87b839d6e25b0eb7338427d895e4209190b6c5aa580f134693623a30ebafdaf95a268b3b84a840fc45d06283d71fe4faa2c7d08cd431bbda165c53a50453015a49ca120626991ff9558be65a7958158387829d6e56e2861e80b85e8c795d93f907afb19e6e2e5aaed9a3158eac5a035189986ff5803dd18fa02bdf5535e5495ed96990665ec165b3ba86fc1a7f7dabeb0510e1823813bf5ab1a01b4fff00bcd0373bc265efa135f8755ebae72b645a890d27ce8af31417347bc3a1d9cf09db339b68d1c9a50bb9c00faeedbefe9bab5a63b580e5f79c4a30dc1bdacccec0fc6a08e0853518e88557001a612d4c30d2fbc2a126a066a94f299ac5ce61000000020202020202020202020202020202020202020202000000000bebc2005e5e5e5e5e5e5e5e5e5e5e5e5e5e5e5e5e5e5e5e0000000005f5e100000000000000000000000000000000000000000000000000000000000000";
 const VALIDATOR_ID: &str = "5555555555555555555555555555555555555555";
 const VALIDATOR_KEY: &str = "003d4e4eb0fa2fee42501368dc41115f64741e9d9496bbc2fe4cfd407f10272eef87b839d6e25b0eb7338427d895e4209190b6c5aa580f134693623a30ebafdaf95a268b3b84a840fc45d06283d71fe4faa2c7d08cd431bbda165c53a50453015a49ca120626991ff9558be65a7958158387829d6e56e2861e80b85e8c795d93f907afb19e6e2e5aaed9a3158eac5a035189986ff5803dd18fa02bdf5535e5495ed96990665ec165b3ba86fc1a7f7dabeb0510e1823813bf5ab1a01b4fff00bcd0373bc265efa135f8755ebae72b645a890d27ce8af31417347bc3a1d9cf09db339b68d1c9a50bb9c00faeedbefe9bab5a63b580e5f79c4a30dc1bdacccec0fc6a08e0853518e88557001a612d4c30d2fbc2a126a066a94f299ac5ce61";
 const VALIDATOR_SECRET_KEY: &str =
@@ -149,6 +153,7 @@ fn it_can_verify_validator_and_staking_transaction() {
             validator_key: validator_key.clone(),
             proof_of_knowledge: keypair.sign(&validator_key.serialize_to_vec()).compress(),
             reward_address: Address::from([3u8; 20]),
+            commission: None,
         },
         100_000_000,
     );
@@ -171,6 +176,7 @@ fn it_can_verify_validator_and_staking_transaction() {
             validator_key,
             proof_of_knowledge: invalid_pok.compress(),
             reward_address: Address::from([3u8; 20]),
+            commission: None,
         },
         100_000_000,
     );
@@ -213,6 +219,7 @@ fn it_can_apply_validator_and_staking_transaction() {
             validator_key: bls_pair.public_key.compress(),
             proof_of_knowledge,
             reward_address: Default::default(),
+            commission: None,
         },
         150_000_000,
     );
@@ -809,6 +816,238 @@ fn it_can_apply_unstaking_transaction() {
     );
 }
 
+#[test]
+fn it_reports_pending_unstakes() {
+    let key_pair = ed25519_key_pair();
+    let address = Address::from(&key_pair);
+    let bls_pair = bls_key_pair();
+    let validator_id: ValidatorId = [0u8; 20].into();
+    let mut contract = make_sample_contract(&validator_id, &key_pair, &bls_pair);
+
+    assert_eq!(contract.get_pending_unstakes(&address), vec![]);
+
+    // Block 2: Retire a third of the stake
+    let tx_1 = make_self_transaction(
+        SelfStakingTransactionData::RetireStake(validator_id.clone()),
+        50_000_000,
+    );
+    assert_eq!(contract.commit_outgoing_transaction(&tx_1, 2, 0), Ok(None));
+    assert_eq!(contract.commit_incoming_transaction(&tx_1, 2, 0), Ok(None));
+
+    // Block 3: Retire the validator
+    let tx_2 = make_signed_incoming_transaction(
+        IncomingStakingTransactionData::RetireValidator {
+            validator_id: validator_id.clone(),
+            signature: Default::default(),
+        },
+        0,
+        &bls_pair,
+    );
+    assert_eq!(contract.commit_incoming_transaction(&tx_2, 3, 0), Ok(None));
+
+    // Election blocks don't contain transactions, so the stake is withdrawable in the block after.
+    let withdrawable_at = policy::election_block_after(2) + 1;
+    // The validator also waits for the rewards of the last batch of the epoch.
+    let droppable_at = policy::macro_block_after(withdrawable_at) + 1;
+    assert_eq!(
+        contract.get_pending_unstakes(&address),
+        vec![
+            PendingUnstake {
+                kind: PendingUnstakeKind::Stake,
+                balance: Coin::from_u64_unchecked(50_000_000),
+                retire_time: 2,
+                withdrawable_at,
+            },
+            PendingUnstake {
+                kind: PendingUnstakeKind::Validator(validator_id),
+                balance: Coin::from_u64_unchecked(150_000_000),
+                retire_time: 3,
+                withdrawable_at: droppable_at,
+            },
+        ]
+    );
+    assert_eq!(
+        contract.get_pending_unstakes(&Address::from([1u8; 20])),
+        vec![]
+    );
+
+    // The unstaking delay ends exactly at the reported block.
+    let tx_3 = make_unstake_transaction(&key_pair, 50_000_000 - 100);
+    assert_eq!(
+        contract.check_outgoing_transaction(&tx_3, withdrawable_at - 1, 0),
+        Err(AccountError::InvalidForSender)
+    );
+    assert_eq!(
+        contract.check_outgoing_transaction(&tx_3, withdrawable_at, 0),
+        Ok(())
+    );
+}
+
+#[test]
+fn it_splits_rewards_with_stakers_by_commission() {
+    let validator_id: ValidatorId = [1u8; 20].into();
+    let reward_address = Address::from([1u8; 20]);
+    let staker_address1 = Address::from([2u8; 20]);
+    let staker_address2 = Address::from([3u8; 20]);
+
+    let bls_pair = BlsKeyPair::generate_default_csprng();
+
+    let mut contract = make_empty_contract();
+    contract
+        .create_validator(
+            validator_id.clone(),
+            bls_pair.public_key.compress(),
+            reward_address.clone(),
+            None,
+            Coin::from_u64_unchecked(100_000_000),
+        )
+        .unwrap();
+    contract
+        .stake(
+            staker_address1.clone(),
+            Coin::from_u64_unchecked(200_000_000),
+            &validator_id,
+        )
+        .unwrap();
+    contract
+        .stake(
+            staker_address2.clone(),
+            Coin::from_u64_unchecked(100_000_000),
+            &validator_id,
+        )
+        .unwrap();
+
+    // Without a commission, the whole reward goes to the reward address.
+    let no_recent_stakes = BTreeMap::new();
+    let reward = Coin::from_u64_unchecked(1_000_001);
+    let validator = contract.get_validator(&validator_id).unwrap();
+    assert_eq!(
+        validator.split_reward(reward, &no_recent_stakes, Coin::ZERO),
+        vec![(reward_address.clone(), reward)]
+    );
+
+    // With a commission of 10%, the stakers share the remaining 90% according to their stake. The
+    // validator gets the commission, the share of its own stake and the rounding remainder.
+    let validator = validator.update_validator(None, None, Some(Some(1_000)));
+    assert_eq!(
+        validator.split_reward(reward, &no_recent_stakes, Coin::ZERO),
+        vec![
+            (reward_address.clone(), Coin::from_u64_unchecked(325_001)),
+            (staker_address1.clone(), Coin::from_u64_unchecked(450_000)),
+            (staker_address2.clone(), Coin::from_u64_unchecked(225_000)),
+        ]
+    );
+
+    // Recently added stake doesn't earn a share, it goes to the reward address instead.
+    let mut recent_stakes = BTreeMap::new();
+    recent_stakes.insert(
+        staker_address1.clone(),
+        Coin::from_u64_unchecked(100_000_000),
+    );
+    assert_eq!(
+        validator.split_reward(reward, &recent_stakes, Coin::ZERO),
+        vec![
+            (reward_address.clone(), Coin::from_u64_unchecked(550_001)),
+            (staker_address1.clone(), Coin::from_u64_unchecked(225_000)),
+            (staker_address2.clone(), Coin::from_u64_unchecked(225_000)),
+        ]
+    );
+
+    // Shares below the minimum share go to the reward address as well.
+    assert_eq!(
+        validator.split_reward(reward, &no_recent_stakes, Coin::from_u64_unchecked(300_000)),
+        vec![
+            (reward_address.clone(), Coin::from_u64_unchecked(550_001)),
+            (staker_address1, Coin::from_u64_unchecked(450_000)),
+        ]
+    );
+
+    // With the maximum commission, the validator keeps all rewards.
+    let validator = validator.update_validator(None, None, Some(Some(10_000)));
+    assert_eq!(
+        validator.split_reward(reward, &no_recent_stakes, Coin::ZERO),
+        vec![(reward_address, reward)]
+    );
+}
+
+#[test]
+fn it_excludes_recently_added_stakes_from_staker_rewards() {
+    let validator_id: ValidatorId = [1u8; 20].into();
+    let reward_address = Address::from([1u8; 20]);
+    let staker_address = Address::from_any_str(STAKER_ADDRESS).unwrap();
+
+    let bls_pair = BlsKeyPair::generate_default_csprng();
+
+    let mut contract = make_empty_contract();
+    contract
+        .create_validator(
+            validator_id.clone(),
+            bls_pair.public_key.compress(),
+            reward_address.clone(),
+            Some(0),
+            Coin::from_u64_unchecked(100_000_000),
+        )
+        .unwrap();
+
+    // Block 2: Stake.
+    let tx = make_incoming_transaction(
+        IncomingStakingTransactionData::Stake {
+            validator_id: validator_id.clone(),
+            staker_address: None,
+        },
+        100_000_000,
+    );
+    assert_eq!(contract.commit_incoming_transaction(&tx, 2, 0), Ok(None));
+
+    let reward = Coin::from_u64_unchecked(1_000);
+    let split_reward = |contract: &StakingContract| {
+        contract
+            .split_reward(&validator_id, reward, Coin::ZERO)
+            .unwrap()
+    };
+    assert_eq!(
+        split_reward(&contract),
+        vec![(reward_address.clone(), reward)]
+    );
+
+    // Reverting the stake transaction also forgets the added stake.
+    let mut reverted_contract = contract.clone();
+    assert_eq!(
+        reverted_contract.revert_incoming_transaction(&tx, 2, 0, None),
+        Ok(())
+    );
+    assert!(reverted_contract.current_added_stakes.is_empty());
+
+    // The stake isn't eligible for the rewards of the batch after it was added either.
+    let finalize_batch = Inherent {
+        ty: InherentType::FinalizeBatch,
+        target: Default::default(),
+        value: Coin::ZERO,
+        data: vec![],
+    };
+    assert_eq!(contract.commit_inherent(&finalize_batch, 32, 0), Ok(None));
+    assert_eq!(
+        split_reward(&contract),
+        vec![(reward_address.clone(), reward)]
+    );
+
+    // Once it was held for a whole batch, it earns a share.
+    assert_eq!(contract.commit_inherent(&finalize_batch, 64, 0), Ok(None));
+    assert_eq!(
+        split_reward(&contract),
+        vec![
+            (reward_address, Coin::from_u64_unchecked(500)),
+            (staker_address, Coin::from_u64_unchecked(500)),
+        ]
+    );
+
+    // Rewards of unknown validators can't be split.
+    assert_eq!(
+        contract.split_reward(&ValidatorId::default(), reward, Coin::ZERO),
+        Err(AccountError::UnknownValidator(ValidatorId::default()))
+    );
+}
+
 #[test]
 fn it_can_apply_rededicate_stake_tx() {
     let validator_id1: ValidatorId = [1u8; 20].into();
@@ -823,6 +1062,7 @@ fn it_can_apply_rededicate_stake_tx() {
             validator_id1.clone(),
             bls_pair1.public_key.compress(),
             [1; 20].into(),
+            None,
             Coin::from_u64_unchecked(0),
         )
         .unwrap();
@@ -831,6 +1071,7 @@ fn it_can_apply_rededicate_stake_tx() {
             validator_id2.clone(),
             bls_pair2.public_key.compress(),
             [2; 20].into(),
+            None,
             Coin::from_u64_unchecked(0),
         )
         .unwrap();
@@ -1681,6 +1922,7 @@ fn it_can_build_a_validator_set() {
             validator_id1.clone(),
             validator_key1.clone(),
             staker1.clone(),
+            None,
             Coin::from_u64_unchecked(100_000_000),
         )
         .unwrap();
@@ -1721,6 +1963,7 @@ fn it_can_build_a_validator_set() {
             validator_id2.clone(),
             validator_key2.clone(),
             staker2.clone(),
+            None,
             Coin::from_u64_unchecked(100_000_000),
         )
         .unwrap();
@@ -1729,6 +1972,7 @@ fn it_can_build_a_validator_set() {
             validator_id3.clone(),
             validator_key3.clone(),
             staker3.clone(),
+            None,
             Coin::from_u64_unchecked(100_000_000),
         )
         .unwrap();
@@ -1835,6 +2079,7 @@ fn it_can_apply_validator_signalling() {
             validator_key: validator_key.clone(),
             proof_of_knowledge: bls_pair.sign(&validator_key.serialize_to_vec()).compress(),
             reward_address: Address::from([3u8; 20]),
+            commission: None,
         },
         100_000_000,
     );
@@ -1900,6 +2145,7 @@ fn it_can_apply_validator_signalling() {
             new_proof_of_knowledge: Some(
                 bls_pair2.sign(&validator_key.serialize_to_vec()).compress(),
             ),
+            new_commission: None,
             signature: Default::default(),
             new_reward_address: None,
         },
@@ -1922,6 +2168,7 @@ fn it_can_apply_validator_signalling() {
                     .sign(&validator_key2.serialize_to_vec())
                     .compress(),
             ),
+            new_commission: None,
             signature: Default::default(),
             new_reward_address: None,
         },
@@ -1944,6 +2191,7 @@ fn it_can_apply_validator_signalling() {
                     .sign(&validator_key2.serialize_to_vec())
                     .compress(),
             ),
+            new_commission: None,
             signature: Default::default(),
             new_reward_address: None,
         },
@@ -2052,6 +2300,7 @@ fn it_can_apply_validator_signalling() {
             old_validator_key: validator_key.clone(),
             new_validator_key: None,
             new_proof_of_knowledge: None,
+            new_commission: None,
             signature: Default::default(),
             new_reward_address: Some(Address::from([4u8; 20])),
         },
@@ -2266,6 +2515,90 @@ fn it_can_apply_validator_signalling() {
     assert_eq!(validator.reward_address, Address::from([3u8; 20]));
 }
 
+#[test]
+fn it_restores_the_commission_when_reverting_a_drop() {
+    let bls_pair = bls_key_pair();
+    let validator_id: ValidatorId = [0; 20].into();
+    let mut contract = make_empty_contract();
+    contract
+        .create_validator(
+            validator_id.clone(),
+            bls_pair.public_key.compress(),
+            Address::from([3u8; 20]),
+            Some(500),
+            Coin::from_u64_unchecked(100_000_000),
+        )
+        .unwrap();
+
+    let tx = make_signed_incoming_transaction(
+        IncomingStakingTransactionData::RetireValidator {
+            validator_id: validator_id.clone(),
+            signature: Default::default(),
+        },
+        0,
+        &bls_pair,
+    );
+    assert_eq!(contract.commit_incoming_transaction(&tx, 2, 0), Ok(None));
+
+    let tx = make_drop_transaction(validator_id.clone(), &bls_pair, 99_999_900);
+    let receipt = contract.commit_outgoing_transaction(&tx, 3000, 0).unwrap();
+    assert!(contract.get_validator(&validator_id).is_none());
+
+    assert_eq!(
+        contract.revert_outgoing_transaction(&tx, 3000, 0, receipt.as_ref()),
+        Ok(())
+    );
+    let validator = contract.get_validator(&validator_id).unwrap();
+    assert_eq!(validator.commission, Some(500));
+    assert_eq!(validator.reward_address, Address::from([3u8; 20]));
+}
+
+#[test]
+fn it_can_remove_the_commission_of_a_validator() {
+    let bls_pair = bls_key_pair();
+    let validator_id: ValidatorId = [0; 20].into();
+    let mut contract = make_empty_contract();
+    contract
+        .create_validator(
+            validator_id.clone(),
+            bls_pair.public_key.compress(),
+            Address::from([3u8; 20]),
+            Some(500),
+            Coin::from_u64_unchecked(100_000_000),
+        )
+        .unwrap();
+
+    let tx = make_signed_incoming_transaction(
+        IncomingStakingTransactionData::UpdateValidator {
+            validator_id: validator_id.clone(),
+            old_validator_key: bls_pair.public_key.compress(),
+            new_validator_key: None,
+            new_proof_of_knowledge: None,
+            new_reward_address: None,
+            new_commission: Some(None),
+            signature: Default::default(),
+        },
+        0,
+        &bls_pair,
+    );
+    assert_eq!(AccountType::verify_incoming_transaction(&tx), Ok(()));
+
+    let receipt = contract.commit_incoming_transaction(&tx, 2, 0).unwrap();
+    assert_eq!(
+        contract.get_validator(&validator_id).unwrap().commission,
+        None
+    );
+
+    assert_eq!(
+        contract.revert_incoming_transaction(&tx, 2, 0, receipt.as_ref()),
+        Ok(())
+    );
+    assert_eq!(
+        contract.get_validator(&validator_id).unwrap().commission,
+        Some(500)
+    );
+}
+
 #[test]
 fn it_can_manage_stake() {
     let bls_pair = bls_key_pair();
@@ -2289,6 +2622,7 @@ fn it_can_manage_stake() {
             validator_id.clone(),
             validator_key,
             Address::from([3u8; 20]),
+            None,
             Coin::from_u64_unchecked(100_000_000),
         )
         .unwrap();
@@ -2582,6 +2916,7 @@ fn it_can_manage_stake() {
             validator_id2.clone(),
             validator_key2,
             Address::from([3u8; 20]),
+            None,
             Coin::from_u64_unchecked(100_000_000),
         )
         .unwrap();
@@ -3264,6 +3599,7 @@ fn it_can_manage_stake() {
             validator_id2.clone(),
             validator_key2,
             Address::from([3u8; 20]),
+            None,
             Coin::from_u64_unchecked(100_000_000),
         )
         .unwrap();
@@ -3657,6 +3993,7 @@ fn make_sample_contract(
             validator_id.clone(),
             bls_pair.public_key.compress(),
             Address::from(key_pair),
+            None,
             Coin::from_u64_unchecked(150_000_000),
         )
         .unwrap();
@@ -141,13 +141,14 @@ pub struct TendermintIdentifier {
 // * round_number
 //
 // that can be included plain text as the proof alongside it also contains it.
-#[derive(Debug, Clone, Eq, PartialEq)]
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub struct TendermintVote {
     /// MacroHeader hash of the proposed macro block
     pub proposal_hash: Option<Blake2bHash>,
     /// Identifier to this votes aggregation
     pub id: TendermintIdentifier,
     /// The merkle root of validators is required for consensus.
+    #[beserial(len_type(u16))]
     pub validator_merkle_root: Vec<u8>,
 }
 
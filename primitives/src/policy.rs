@@ -45,6 +45,14 @@ pub const MIN_STAKE: u64 = 1;
 /// in proof-of-work.
 pub const MIN_VALIDATOR_STAKE: u64 = 100_000_000;
 
+/// Maximum commission a validator can charge on the rewards of its stakers, in basis points
+/// (1/100 of a percent). A validator that charges the maximum commission keeps all of its rewards.
+pub const MAX_VALIDATOR_COMMISSION: u16 = 10_000;
+
+/// Maximum number of reward inherents for stakers at the end of a batch. Staker shares smaller than
+/// the total reward of the batch divided by this number go to the reward address of the validator.
+pub const MAX_STAKER_REWARDS_PER_BATCH: u64 = 1024;
+
 /// Total supply in units.
 pub const TOTAL_SUPPLY: u64 = 2_100_000_000_000_000;
 
@@ -49,4 +49,16 @@ impl Subscription {
             }
         }
     }
+
+    /// Like `matches_transaction`, but an address subscription also matches transactions that are
+    /// sent to one of the addresses.
+    pub fn matches_sender_or_recipient(&self, transaction: &Transaction) -> bool {
+        match self {
+            Subscription::Addresses(addresses) => {
+                addresses.contains(&transaction.sender)
+                    || addresses.contains(&transaction.recipient)
+            }
+            _ => self.matches_transaction(transaction),
+        }
+    }
 }
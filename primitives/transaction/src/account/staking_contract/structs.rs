@@ -5,6 +5,7 @@ use keys::Address;
 use crate::SignatureProof;
 use crate::{AccountType, Transaction, TransactionError};
 use primitives::account::ValidatorId;
+use primitives::policy;
 
 /// We need to distinguish three types of transactions:
 /// 1. Incoming transactions, which include:
@@ -60,6 +61,8 @@ pub enum IncomingStakingTransactionData {
         validator_key: BlsPublicKey,
         proof_of_knowledge: BlsSignature,
         reward_address: Address,
+        // If a commission is given, the rewards are shared with the stakers of the validator.
+        commission: Option<u16>,
     },
     UpdateValidator {
         validator_id: ValidatorId,
@@ -67,6 +70,9 @@ pub enum IncomingStakingTransactionData {
         new_validator_key: Option<BlsPublicKey>,
         new_proof_of_knowledge: Option<BlsSignature>,
         new_reward_address: Option<Address>,
+        // `Some(None)` removes the commission of the validator. Both levels of the option are
+        // serialized, so this is encoded differently from the other optional fields.
+        new_commission: Option<Option<u16>>,
         signature: BlsSignature,
     },
     RetireValidator {
@@ -106,10 +112,13 @@ impl IncomingStakingTransactionData {
             IncomingStakingTransactionData::CreateValidator {
                 validator_key,
                 proof_of_knowledge,
+                commission,
                 ..
             } => {
                 // Check proof of knowledge.
                 verify_proof_of_knowledge(validator_key, proof_of_knowledge)?;
+
+                verify_commission(*commission)?;
             }
             IncomingStakingTransactionData::UpdateValidator {
                 validator_id: _,
@@ -118,15 +127,21 @@ impl IncomingStakingTransactionData {
                 new_proof_of_knowledge,
                 signature,
                 new_reward_address,
+                new_commission,
             } => {
                 // Check signature and proof of knowledge.
                 verify_transaction_signature(transaction, old_validator_key, signature)?;
 
                 // Do not allow updates without any effect.
-                if new_validator_key.is_none() && new_reward_address.is_none() {
+                if new_validator_key.is_none()
+                    && new_reward_address.is_none()
+                    && new_commission.is_none()
+                {
                     return Err(TransactionError::InvalidData);
                 }
 
+                verify_commission(new_commission.flatten())?;
+
                 if let (Some(new_validator_key), Some(new_proof_of_knowledge)) =
                     (new_validator_key, new_proof_of_knowledge)
                 {
@@ -185,12 +200,14 @@ impl Serialize for IncomingStakingTransactionData {
                 validator_key,
                 proof_of_knowledge,
                 reward_address,
+                commission,
             } => {
                 size +=
                     Serialize::serialize(&IncomingStakingTransactionType::CreateValidator, writer)?;
                 size += Serialize::serialize(validator_key, writer)?;
                 size += Serialize::serialize(proof_of_knowledge, writer)?;
                 size += Serialize::serialize(reward_address, writer)?;
+                size += Serialize::serialize(commission, writer)?;
             }
             IncomingStakingTransactionData::UpdateValidator {
                 validator_id,
@@ -198,6 +215,7 @@ impl Serialize for IncomingStakingTransactionData {
                 new_validator_key,
                 new_proof_of_knowledge,
                 new_reward_address,
+                new_commission,
                 signature,
             } => {
                 size +=
@@ -215,6 +233,7 @@ impl Serialize for IncomingStakingTransactionData {
                 if let Some(new_reward_address) = new_reward_address {
                     size += Serialize::serialize(new_reward_address, writer)?;
                 }
+                size += Serialize::serialize(new_commission, writer)?;
                 size += Serialize::serialize(signature, writer)?;
             }
             IncomingStakingTransactionData::RetireValidator {
@@ -265,12 +284,14 @@ impl Serialize for IncomingStakingTransactionData {
                 validator_key,
                 proof_of_knowledge,
                 reward_address,
+                commission,
             } => {
                 size +=
                     Serialize::serialized_size(&IncomingStakingTransactionType::CreateValidator);
                 size += Serialize::serialized_size(validator_key);
                 size += Serialize::serialized_size(proof_of_knowledge);
                 size += Serialize::serialized_size(reward_address);
+                size += Serialize::serialized_size(commission);
             }
             IncomingStakingTransactionData::UpdateValidator {
                 validator_id,
@@ -278,6 +299,7 @@ impl Serialize for IncomingStakingTransactionData {
                 new_validator_key,
                 new_proof_of_knowledge,
                 new_reward_address,
+                new_commission,
                 signature,
             } => {
                 size +=
@@ -295,6 +317,7 @@ impl Serialize for IncomingStakingTransactionData {
                 if let Some(new_reward_address) = new_reward_address {
                     size += Serialize::serialized_size(new_reward_address);
                 }
+                size += Serialize::serialized_size(new_commission);
                 size += Serialize::serialized_size(signature);
             }
             IncomingStakingTransactionData::RetireValidator {
@@ -347,6 +370,7 @@ impl Deserialize for IncomingStakingTransactionData {
                     validator_key: Deserialize::deserialize(reader)?,
                     proof_of_knowledge: Deserialize::deserialize(reader)?,
                     reward_address: Deserialize::deserialize(reader)?,
+                    commission: Deserialize::deserialize(reader)?,
                 })
             }
             IncomingStakingTransactionType::UpdateValidator => {
@@ -365,6 +389,7 @@ impl Deserialize for IncomingStakingTransactionData {
                 if updates_address {
                     new_reward_address = Some(Deserialize::deserialize(reader)?);
                 }
+                let new_commission = Deserialize::deserialize(reader)?;
                 let signature = Deserialize::deserialize(reader)?;
 
                 Ok(IncomingStakingTransactionData::UpdateValidator {
@@ -373,6 +398,7 @@ impl Deserialize for IncomingStakingTransactionData {
                     new_validator_key,
                     new_proof_of_knowledge,
                     new_reward_address,
+                    new_commission,
                     signature,
                 })
             }
@@ -651,6 +677,17 @@ pub fn verify_transaction_signature(
     Ok(())
 }
 
+/// Checks that a validator commission doesn't exceed the maximum commission.
+pub fn verify_commission(commission: Option<u16>) -> Result<(), TransactionError> {
+    match commission {
+        Some(commission) if commission > policy::MAX_VALIDATOR_COMMISSION => {
+            warn!("Validator commission above maximum");
+            Err(TransactionError::InvalidData)
+        }
+        _ => Ok(()),
+    }
+}
+
 /// Important: Currently, the proof of knowledge of the secret key is a signature of the public key.
 /// If an attacker A ever tricks a validator B into signing a message with content `pk_A - pk_B`,
 /// where `pk_X` is X's BLS public key, A will be able to sign aggregate messages that are valid for
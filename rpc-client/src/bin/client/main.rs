@@ -10,10 +10,16 @@ use nimiq_rpc_client::Client;
 use nimiq_rpc_interface::{
     blockchain::BlockchainInterface,
     consensus::ConsensusInterface,
+    openrpc,
     types::{BlockNumberOrHash, OrLatest, ValidityStartHeight},
+    validator::ValidatorInterface,
     wallet::WalletInterface,
 };
 
+use crate::offline::{BuildCommand, SignCommand};
+
+mod offline;
+
 #[derive(Debug, StructOpt)]
 struct Opt {
     #[structopt(short)]
@@ -25,6 +31,14 @@ struct Opt {
     #[structopt(short = "P")]
     password: Option<String>,
 
+    /// API token to authenticate batch requests with.
+    #[structopt(short = "T")]
+    token: Option<String>,
+
+    /// The maximum number of requests that are sent to the node in one batch.
+    #[structopt(long)]
+    max_batch_size: Option<usize>,
+
     #[structopt(subcommand)]
     command: Command,
 }
@@ -44,6 +58,40 @@ enum Command {
     /// Lists the current stakes from the staking contract.
     Stakes {},
 
+    /// Show the inactive stake and inactive validator deposits of an address and when they can be
+    /// withdrawn.
+    PendingUnstakes { address: Address },
+
+    /// Show the rewards that were distributed for a batch. The rewards for a batch are
+    /// distributed at the end of the following batch.
+    Rewards { batch_number: u32 },
+
+    /// Show the slash and unpark events, optionally only for one validator or epoch.
+    SlashEvents {
+        validator_id: Option<ValidatorId>,
+
+        /// Only show the events of this epoch.
+        #[structopt(long)]
+        epoch: Option<u32>,
+
+        /// The maximum number of events to show.
+        #[structopt(long)]
+        max: Option<u16>,
+
+        /// The cursor of the page to show, as returned with the previous page.
+        #[structopt(long)]
+        cursor: Option<String>,
+    },
+
+    /// Show how the staking contract changed between two blocks.
+    StakingDiff { from_block: u32, to_block: u32 },
+
+    /// Show the upcoming slots, missed blocks and votes of the validator of the node.
+    ValidatorDuties {},
+
+    /// Show the sync progress of the node.
+    SyncStatus {},
+
     /// Follow the head of the blockchain.
     Follow {
         /// Show the full block instead of only the hash.
@@ -57,6 +105,9 @@ enum Command {
     /// Create, sign and send transactions.
     #[structopt(name = "tx")]
     Transaction(TransactionCommand),
+
+    /// Print the OpenRPC document that describes the RPC API. This doesn't connect to the node.
+    Schema {},
 }
 
 #[derive(Debug, StructOpt)]
@@ -194,6 +245,25 @@ enum TransactionCommand {
         #[structopt(long = "dry")]
         dry: bool,
     },
+
+    /// Builds an unsigned transaction and outputs it as hex string. This doesn't connect to the node.
+    Build(BuildCommand),
+
+    /// Signs a transaction with local key files and outputs it as hex string. This doesn't connect
+    /// to the node.
+    Sign(SignCommand),
+
+    /// Decodes and prints a transaction. This doesn't connect to the node.
+    Inspect {
+        /// The hex-encoded transaction.
+        transaction: String,
+    },
+
+    /// Sends a signed transaction to the node.
+    Broadcast {
+        /// The hex-encoded transaction.
+        transaction: String,
+    },
 }
 
 impl Command {
@@ -232,6 +302,50 @@ impl Command {
                 println!("{:#?}", stakes);
             }
 
+            Command::PendingUnstakes { address } => {
+                let pending_unstakes = client.blockchain.get_pending_unstakes(address).await?;
+                println!("{:#?}", pending_unstakes);
+            }
+
+            Command::Rewards { batch_number } => {
+                let rewards = client.blockchain.get_batch_rewards(batch_number).await?;
+                println!("{:#?}", rewards);
+            }
+
+            Command::SlashEvents {
+                validator_id,
+                epoch,
+                max,
+                cursor,
+            } => {
+                let events = client
+                    .blockchain
+                    .get_slash_events(validator_id, epoch, max, cursor)
+                    .await?;
+                println!("{:#?}", events);
+            }
+
+            Command::StakingDiff {
+                from_block,
+                to_block,
+            } => {
+                let diff = client
+                    .blockchain
+                    .get_staking_diff(from_block, to_block)
+                    .await?;
+                println!("{:#?}", diff);
+            }
+
+            Command::ValidatorDuties {} => {
+                let duties = client.validator.get_validator_duties().await?;
+                println!("{:#?}", duties);
+            }
+
+            Command::SyncStatus {} => {
+                let status = client.consensus.sync_status().await?;
+                println!("{:#?}", status);
+            }
+
             Command::Follow { block: show_block } => {
                 let mut stream = client.blockchain.head_subscribe().await?;
 
@@ -468,7 +582,20 @@ impl Command {
                         println!("{}", txid);
                     }
                 }
+
+                TransactionCommand::Broadcast { transaction } => {
+                    let txid = client.consensus.send_raw_transaction(transaction).await?;
+                    println!("{}", txid);
+                }
+
+                TransactionCommand::Build(_)
+                | TransactionCommand::Sign(_)
+                | TransactionCommand::Inspect { .. } => {
+                    unreachable!("Offline commands are run without connecting")
+                }
             },
+
+            Command::Schema {} => unreachable!("The schema is printed without connecting"),
         }
 
         Ok(())
@@ -476,6 +603,25 @@ impl Command {
 }
 
 async fn run_app(opt: Opt) -> Result<(), Error> {
+    let command = match opt.command {
+        Command::Schema {} => {
+            println!("{}", serde_json::to_string_pretty(&openrpc::document())?);
+            return Ok(());
+        }
+        Command::Transaction(TransactionCommand::Build(command)) => {
+            println!("{}", offline::encode_transaction(&command.build()?));
+            return Ok(());
+        }
+        Command::Transaction(TransactionCommand::Sign(command)) => {
+            println!("{}", offline::encode_transaction(&command.sign()?));
+            return Ok(());
+        }
+        Command::Transaction(TransactionCommand::Inspect { transaction }) => {
+            return offline::inspect(&transaction);
+        }
+        command => command,
+    };
+
     let url = opt
         .url
         .as_deref()
@@ -491,9 +637,15 @@ async fn run_app(opt: Opt) -> Result<(), Error> {
         _ => bail!("Both username and password needs to be specified."),
     };
 
-    let client = Client::new(url, credentials).await?;
+    let mut client = Client::new(url, credentials).await?;
+    if let Some(token) = opt.token {
+        client = client.with_token(token);
+    }
+    if let Some(max_batch_size) = opt.max_batch_size {
+        client = client.with_max_batch_size(max_batch_size);
+    }
 
-    opt.command.run(client).await?;
+    command.run(client).await?;
 
     Ok(())
 }
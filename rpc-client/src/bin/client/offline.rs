@@ -0,0 +1,1262 @@
+//! Building, signing and inspecting transactions without a node, so that the keys never leave the
+//! machine they are stored on.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail, Error};
+use structopt::StructOpt;
+
+use beserial::{Deserialize, Serialize};
+use nimiq_bls::KeyPair as BlsKeyPair;
+use nimiq_hash::{Blake2bHash, Blake2bHasher, Hash, Hasher, Sha256Hasher};
+use nimiq_keys::{Address, KeyPair, PrivateKey};
+use nimiq_primitives::account::{AccountType, ValidatorId};
+use nimiq_primitives::coin::Coin;
+use nimiq_primitives::networks::NetworkId;
+use nimiq_transaction::account::htlc_contract::{
+    AnyHash, CreationTransactionData as HtlcCreationData, HashAlgorithm, ProofType,
+};
+use nimiq_transaction::account::staking_contract::{
+    IncomingStakingTransactionData, OutgoingStakingTransactionProof, SelfStakingTransactionData,
+};
+use nimiq_transaction::account::vesting_contract::CreationTransactionData as VestingCreationData;
+use nimiq_transaction::{SignatureProof, Transaction, TransactionFlags};
+use nimiq_transaction_builder::recipient::staking_contract::StakingRecipientBuilder;
+use nimiq_transaction_builder::{
+    fill_in_staking_contract_address, Recipient, TransactionBuilder, TransactionProofBuilder,
+};
+use nimiq_wallet::WalletAccount;
+
+/// Options shared by all transactions.
+#[derive(Debug, StructOpt)]
+pub struct TransactionOptions {
+    #[structopt(short, long, default_value = "0")]
+    fee: Coin,
+
+    /// The absolute block number from which on the transaction is valid.
+    #[structopt(short, long)]
+    validity_start_height: u32,
+
+    /// The network the transaction is valid for.
+    #[structopt(short, long, default_value = "devalbatross")]
+    network: NetworkId,
+
+    /// The address of the staking contract. Defaults to the staking contract of the network.
+    #[structopt(long)]
+    staking_contract: Option<Address>,
+}
+
+impl TransactionOptions {
+    fn staking_contract(&self) -> Address {
+        fill_in_staking_contract_address(self.staking_contract.clone(), self.network)
+    }
+
+    fn staking_recipient<F: FnOnce(&mut StakingRecipientBuilder)>(&self, f: F) -> Recipient {
+        let mut recipient = Recipient::new_staking_builder(Some(self.staking_contract()));
+        f(&mut recipient);
+        // The staking data is always set by `f`.
+        recipient.generate().unwrap()
+    }
+
+    /// Builds the unsigned transaction.
+    fn build(
+        &self,
+        sender: Address,
+        sender_type: AccountType,
+        recipient: Recipient,
+        value: Coin,
+    ) -> Result<Transaction, Error> {
+        let mut builder = TransactionBuilder::new();
+        builder
+            .with_sender(sender)
+            .with_sender_type(sender_type)
+            .with_recipient(recipient)
+            .with_value(value)
+            .with_fee(self.fee)
+            .with_validity_start_height(self.validity_start_height)
+            .with_network_id(self.network);
+
+        Ok(builder.generate()?.preliminary_transaction().clone())
+    }
+}
+
+/// Builds an unsigned transaction. Sign it with `tx sign`.
+#[derive(Debug, StructOpt)]
+pub enum BuildCommand {
+    /// A transaction from a basic account to a basic account.
+    Basic {
+        sender: Address,
+
+        recipient: Address,
+
+        value: Coin,
+
+        #[structopt(flatten)]
+        options: TransactionOptions,
+    },
+
+    /// A transaction that creates an HTLC contract.
+    Htlc {
+        /// The account that funds the contract.
+        sender: Address,
+
+        /// The amount of NIM locked in the contract.
+        value: Coin,
+
+        /// The address that gets the funds after the timeout. Defaults to `sender`.
+        #[structopt(long)]
+        htlc_sender: Option<Address>,
+
+        /// The address that can claim the funds with the pre-image.
+        #[structopt(long)]
+        htlc_recipient: Address,
+
+        /// The result of hashing the pre-image `hash_count` times.
+        #[structopt(long)]
+        hash_root: AnyHash,
+
+        #[structopt(long, default_value = "1")]
+        hash_count: u8,
+
+        /// Either `blake2b` or `sha256`.
+        #[structopt(long, default_value = "blake2b", parse(try_from_str = parse_hash_algorithm))]
+        hash_algorithm: HashAlgorithm,
+
+        /// The block number after which `htlc_sender` can take back the funds.
+        #[structopt(long)]
+        timeout: u64,
+
+        #[structopt(flatten)]
+        options: TransactionOptions,
+    },
+
+    /// A transaction that creates a vesting contract.
+    Vesting {
+        /// The account that funds the contract.
+        sender: Address,
+
+        /// The owner of the vested funds.
+        owner: Address,
+
+        value: Coin,
+
+        #[structopt(long, default_value = "0")]
+        start_time: u64,
+
+        #[structopt(long)]
+        time_step: u64,
+
+        /// The amount released at each step. Defaults to `value`.
+        #[structopt(long)]
+        step_amount: Option<Coin>,
+
+        /// The amount that is vested. Defaults to `value`.
+        #[structopt(long)]
+        total_amount: Option<Coin>,
+
+        #[structopt(flatten)]
+        options: TransactionOptions,
+    },
+
+    /// A transaction that pays out funds from an HTLC or a vesting contract.
+    Redeem {
+        contract: Address,
+
+        /// Either `htlc` or `vesting`.
+        #[structopt(parse(try_from_str = parse_contract_type))]
+        contract_type: AccountType,
+
+        recipient: Address,
+
+        value: Coin,
+
+        #[structopt(flatten)]
+        options: TransactionOptions,
+    },
+
+    /// Stakes for a validator.
+    Stake {
+        sender: Address,
+
+        validator_id: ValidatorId,
+
+        value: Coin,
+
+        /// The owner of the stake. Defaults to `sender`.
+        #[structopt(long)]
+        staker_address: Option<Address>,
+
+        #[structopt(flatten)]
+        options: TransactionOptions,
+    },
+
+    /// Moves active stake from one validator to another.
+    Rededicate {
+        from_validator_id: ValidatorId,
+
+        to_validator_id: ValidatorId,
+
+        value: Coin,
+
+        #[structopt(flatten)]
+        options: TransactionOptions,
+    },
+
+    /// Retires stake, so that it can be unstaked after the cooldown.
+    Retire {
+        validator_id: ValidatorId,
+
+        value: Coin,
+
+        #[structopt(flatten)]
+        options: TransactionOptions,
+    },
+
+    /// Re-activates retired stake for a validator.
+    Reactivate {
+        validator_id: ValidatorId,
+
+        value: Coin,
+
+        #[structopt(flatten)]
+        options: TransactionOptions,
+    },
+
+    /// Pays out retired stake.
+    Unstake {
+        recipient: Address,
+
+        value: Coin,
+
+        #[structopt(flatten)]
+        options: TransactionOptions,
+    },
+
+    /// Creates a validator. The validator key is needed for its proof of knowledge.
+    NewValidator {
+        sender: Address,
+
+        reward_address: Address,
+
+        /// The initial stake.
+        value: Coin,
+
+        /// The file with the BLS key of the validator.
+        #[structopt(long, parse(from_os_str))]
+        validator_key: PathBuf,
+
+        /// The commission in basis points. If it is set, the rewards are shared with the stakers.
+        #[structopt(long)]
+        commission: Option<u16>,
+
+        #[structopt(flatten)]
+        options: TransactionOptions,
+    },
+
+    /// Updates the key, the reward address or the commission of a validator.
+    UpdateValidator {
+        /// The account that pays the fee.
+        sender: Address,
+
+        validator_id: ValidatorId,
+
+        /// The file with the current BLS key of the validator.
+        #[structopt(long, parse(from_os_str))]
+        validator_key: PathBuf,
+
+        /// The file with the new BLS key of the validator.
+        #[structopt(long, parse(from_os_str))]
+        new_validator_key: Option<PathBuf>,
+
+        #[structopt(long)]
+        new_reward_address: Option<Address>,
+
+        /// The new commission in basis points.
+        #[structopt(long)]
+        new_commission: Option<u16>,
+
+        /// Removes the commission, so that the whole reward goes to the reward address again.
+        #[structopt(long, conflicts_with = "new-commission")]
+        remove_commission: bool,
+
+        #[structopt(flatten)]
+        options: TransactionOptions,
+    },
+
+    /// Retires a validator.
+    RetireValidator {
+        /// The account that pays the fee.
+        sender: Address,
+
+        validator_id: ValidatorId,
+
+        #[structopt(flatten)]
+        options: TransactionOptions,
+    },
+
+    /// Re-activates a retired validator.
+    ReactivateValidator {
+        /// The account that pays the fee.
+        sender: Address,
+
+        validator_id: ValidatorId,
+
+        #[structopt(flatten)]
+        options: TransactionOptions,
+    },
+
+    /// Unparks a validator.
+    UnparkValidator {
+        /// The account that pays the fee.
+        sender: Address,
+
+        validator_id: ValidatorId,
+
+        #[structopt(flatten)]
+        options: TransactionOptions,
+    },
+
+    /// Drops a retired validator and pays out its initial stake.
+    DropValidator {
+        recipient: Address,
+
+        value: Coin,
+
+        #[structopt(flatten)]
+        options: TransactionOptions,
+    },
+}
+
+impl BuildCommand {
+    pub fn build(self) -> Result<Transaction, Error> {
+        match self {
+            BuildCommand::Basic {
+                sender,
+                recipient,
+                value,
+                options,
+            } => options.build(
+                sender,
+                AccountType::Basic,
+                Recipient::new_basic(recipient),
+                value,
+            ),
+
+            BuildCommand::Htlc {
+                sender,
+                value,
+                htlc_sender,
+                htlc_recipient,
+                hash_root,
+                hash_count,
+                hash_algorithm,
+                timeout,
+                options,
+            } => {
+                let mut recipient = Recipient::new_htlc_builder();
+                recipient
+                    .with_sender(htlc_sender.unwrap_or_else(|| sender.clone()))
+                    .with_recipient(htlc_recipient)
+                    .with_hash(hash_root, hash_count, hash_algorithm)
+                    .with_timeout(timeout);
+                options.build(sender, AccountType::Basic, recipient.generate()?, value)
+            }
+
+            BuildCommand::Vesting {
+                sender,
+                owner,
+                value,
+                start_time,
+                time_step,
+                step_amount,
+                total_amount,
+                options,
+            } => {
+                let mut recipient = Recipient::new_vesting_builder(owner);
+                recipient
+                    .with_start_time(start_time)
+                    .with_time_step(time_step)
+                    .with_step_amount(step_amount.unwrap_or(value))
+                    .with_total_amount(total_amount.unwrap_or(value));
+                options.build(sender, AccountType::Basic, recipient.generate()?, value)
+            }
+
+            BuildCommand::Redeem {
+                contract,
+                contract_type,
+                recipient,
+                value,
+                options,
+            } => options.build(
+                contract,
+                contract_type,
+                Recipient::new_basic(recipient),
+                value,
+            ),
+
+            BuildCommand::Stake {
+                sender,
+                validator_id,
+                value,
+                staker_address,
+                options,
+            } => {
+                let recipient = options.staking_recipient(|recipient| {
+                    recipient.stake(&validator_id, staker_address);
+                });
+                options.build(sender, AccountType::Basic, recipient, value)
+            }
+
+            BuildCommand::Rededicate {
+                from_validator_id,
+                to_validator_id,
+                value,
+                options,
+            } => {
+                let recipient = options.staking_recipient(|recipient| {
+                    recipient.rededicate_stake(&from_validator_id, &to_validator_id);
+                });
+                options.build(
+                    options.staking_contract(),
+                    AccountType::Staking,
+                    recipient,
+                    value,
+                )
+            }
+
+            BuildCommand::Retire {
+                validator_id,
+                value,
+                options,
+            } => {
+                let recipient = options.staking_recipient(|recipient| {
+                    recipient.retire_stake(&validator_id);
+                });
+                options.build(
+                    options.staking_contract(),
+                    AccountType::Staking,
+                    recipient,
+                    value,
+                )
+            }
+
+            BuildCommand::Reactivate {
+                validator_id,
+                value,
+                options,
+            } => {
+                let recipient = options.staking_recipient(|recipient| {
+                    recipient.reactivate_stake(&validator_id);
+                });
+                options.build(
+                    options.staking_contract(),
+                    AccountType::Staking,
+                    recipient,
+                    value,
+                )
+            }
+
+            BuildCommand::Unstake {
+                recipient,
+                value,
+                options,
+            } => options.build(
+                options.staking_contract(),
+                AccountType::Staking,
+                Recipient::new_basic(recipient),
+                value,
+            ),
+
+            BuildCommand::NewValidator {
+                sender,
+                reward_address,
+                value,
+                validator_key,
+                commission,
+                options,
+            } => {
+                let validator_key = load_validator_key(&validator_key)?;
+                let recipient = options.staking_recipient(|recipient| {
+                    recipient.create_validator(&validator_key, reward_address, commission);
+                });
+                options.build(sender, AccountType::Basic, recipient, value)
+            }
+
+            BuildCommand::UpdateValidator {
+                sender,
+                validator_id,
+                validator_key,
+                new_validator_key,
+                new_reward_address,
+                new_commission,
+                remove_commission,
+                options,
+            } => {
+                let validator_key = load_validator_key(&validator_key)?;
+                let new_validator_key = new_validator_key
+                    .map(|path| load_validator_key(&path))
+                    .transpose()?;
+                let new_commission = if remove_commission {
+                    Some(None)
+                } else {
+                    new_commission.map(Some)
+                };
+                let recipient = options.staking_recipient(|recipient| {
+                    recipient.update_validator(
+                        &validator_id,
+                        &validator_key.public_key,
+                        new_validator_key.as_ref(),
+                        new_reward_address,
+                        new_commission,
+                    );
+                });
+                options.build(sender, AccountType::Basic, recipient, Coin::ZERO)
+            }
+
+            BuildCommand::RetireValidator {
+                sender,
+                validator_id,
+                options,
+            } => {
+                let recipient = options.staking_recipient(|recipient| {
+                    recipient.retire_validator(&validator_id);
+                });
+                options.build(sender, AccountType::Basic, recipient, Coin::ZERO)
+            }
+
+            BuildCommand::ReactivateValidator {
+                sender,
+                validator_id,
+                options,
+            } => {
+                let recipient = options.staking_recipient(|recipient| {
+                    recipient.reactivate_validator(&validator_id);
+                });
+                options.build(sender, AccountType::Basic, recipient, Coin::ZERO)
+            }
+
+            BuildCommand::UnparkValidator {
+                sender,
+                validator_id,
+                options,
+            } => {
+                let recipient = options.staking_recipient(|recipient| {
+                    recipient.unpark_validator(&validator_id);
+                });
+                options.build(sender, AccountType::Basic, recipient, Coin::ZERO)
+            }
+
+            BuildCommand::DropValidator {
+                recipient,
+                value,
+                options,
+            } => options.build(
+                options.staking_contract(),
+                AccountType::Staking,
+                Recipient::new_basic(recipient),
+                value,
+            ),
+        }
+    }
+}
+
+/// How the funds of an HTLC are paid out.
+#[derive(Debug, Clone, Copy)]
+pub enum HtlcResolution {
+    /// The recipient claims the funds with the pre-image.
+    RegularTransfer,
+    /// Sender and recipient agree to pay out the funds before the timeout.
+    EarlyResolve,
+    /// The sender takes back the funds after the timeout.
+    TimeoutResolve,
+}
+
+impl FromStr for HtlcResolution {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "regular-transfer" => HtlcResolution::RegularTransfer,
+            "early-resolve" => HtlcResolution::EarlyResolve,
+            "timeout-resolve" => HtlcResolution::TimeoutResolve,
+            _ => bail!("Invalid HTLC resolution: {}", s),
+        })
+    }
+}
+
+/// Signs a transaction built by `tx build`. Which keys are needed depends on the transaction.
+#[derive(Debug, StructOpt)]
+pub struct SignCommand {
+    /// The hex-encoded transaction.
+    transaction: String,
+
+    /// The file with the private key of the sender, or of the staker for staking contract
+    /// transactions, or of the HTLC recipient for regular transfers and early resolves.
+    #[structopt(short, long, parse(from_os_str))]
+    key: Option<PathBuf>,
+
+    /// The file with the BLS key of the validator, for validator transactions.
+    #[structopt(long, parse(from_os_str))]
+    validator_key: Option<PathBuf>,
+
+    /// The validator to drop. The transaction must have been built with `drop-validator`.
+    #[structopt(long)]
+    drop_validator: Option<ValidatorId>,
+
+    /// How to pay out an HTLC: `regular-transfer`, `early-resolve` or `timeout-resolve`.
+    #[structopt(long)]
+    htlc: Option<HtlcResolution>,
+
+    /// The file with the private key of the HTLC sender, for early resolves.
+    #[structopt(long, parse(from_os_str))]
+    htlc_sender_key: Option<PathBuf>,
+
+    /// The pre-image of the HTLC, for regular transfers.
+    #[structopt(long)]
+    pre_image: Option<AnyHash>,
+
+    /// How often the pre-image is hashed to get the hash root of the HTLC.
+    #[structopt(long, default_value = "1")]
+    hash_count: u8,
+
+    /// Either `blake2b` or `sha256`.
+    #[structopt(long, default_value = "blake2b", parse(try_from_str = parse_hash_algorithm))]
+    hash_algorithm: HashAlgorithm,
+}
+
+impl SignCommand {
+    fn wallet(&self) -> Result<WalletAccount, Error> {
+        let path = self
+            .key
+            .as_ref()
+            .ok_or_else(|| anyhow!("This transaction needs a key (--key)"))?;
+        load_wallet(path)
+    }
+
+    fn validator_key(&self) -> Result<BlsKeyPair, Error> {
+        let path = self
+            .validator_key
+            .as_ref()
+            .ok_or_else(|| anyhow!("This transaction needs a validator key (--validator-key)"))?;
+        load_validator_key(path)
+    }
+
+    pub fn sign(self) -> Result<Transaction, Error> {
+        let transaction = decode_transaction(&self.transaction)?;
+
+        let transaction = match TransactionProofBuilder::new(transaction) {
+            TransactionProofBuilder::Basic(builder)
+            | TransactionProofBuilder::Vesting(builder)
+            | TransactionProofBuilder::StakingSelf(builder) => {
+                let mut transaction = builder.transaction;
+                self.wallet()?.sign_transaction(&mut transaction);
+                transaction
+            }
+
+            TransactionProofBuilder::Signalling(mut builder) => {
+                builder.sign_with_validator_key_pair(&self.validator_key()?);
+                // The validator signature was just set, so the data is complete.
+                let mut transaction = builder.generate().unwrap().unwrap_basic().transaction;
+                self.wallet()?.sign_transaction(&mut transaction);
+                transaction
+            }
+
+            TransactionProofBuilder::Staking(mut builder) => {
+                match &self.drop_validator {
+                    Some(validator_id) => {
+                        builder.drop_validator(validator_id, &self.validator_key()?)
+                    }
+                    None => builder.unstake(&self.wallet()?.key_pair),
+                };
+                // The proof was just set.
+                builder.generate().unwrap()
+            }
+
+            TransactionProofBuilder::Htlc(mut builder) => {
+                let resolution = self
+                    .htlc
+                    .ok_or_else(|| anyhow!("This transaction needs an HTLC resolution (--htlc)"))?;
+                let signature = self.wallet()?.create_signature_proof(&builder.transaction);
+
+                match resolution {
+                    HtlcResolution::RegularTransfer => {
+                        let pre_image = self.pre_image.clone().ok_or_else(|| {
+                            anyhow!("A regular transfer needs the pre-image (--pre-image)")
+                        })?;
+                        let hash_root =
+                            hash_root(self.hash_algorithm, pre_image.clone(), self.hash_count);
+                        builder.regular_transfer(
+                            self.hash_algorithm,
+                            pre_image,
+                            self.hash_count,
+                            hash_root,
+                            signature,
+                        );
+                    }
+                    HtlcResolution::EarlyResolve => {
+                        let path = self.htlc_sender_key.as_ref().ok_or_else(|| {
+                            anyhow!(
+                                "An early resolve needs the HTLC sender key (--htlc-sender-key)"
+                            )
+                        })?;
+                        let sender_signature =
+                            load_wallet(path)?.create_signature_proof(&builder.transaction);
+                        builder.early_resolve(sender_signature, signature);
+                    }
+                    HtlcResolution::TimeoutResolve => {
+                        builder.timeout_resolve(signature);
+                    }
+                }
+                // The proof was just set.
+                builder.generate().unwrap()
+            }
+        };
+
+        transaction.verify(transaction.network_id)?;
+        Ok(transaction)
+    }
+}
+
+/// Prints the fields of a transaction, with its data and proof decoded.
+pub fn inspect(transaction: &str) -> Result<(), Error> {
+    let transaction = decode_transaction(transaction)?;
+
+    let hash: Blake2bHash = transaction.hash();
+    println!("Hash:                  {}", hash);
+    println!(
+        "Sender:                {} ({:?})",
+        transaction.sender.to_user_friendly_address(),
+        transaction.sender_type
+    );
+    println!(
+        "Recipient:             {} ({:?})",
+        transaction.recipient.to_user_friendly_address(),
+        transaction.recipient_type
+    );
+    println!("Value:                 {}", transaction.value);
+    println!("Fee:                   {}", transaction.fee);
+    println!(
+        "Validity start height: {}",
+        transaction.validity_start_height
+    );
+    println!("Network:               {}", transaction.network_id);
+    println!("Flags:                 {:?}", transaction.flags);
+    println!("Data:                  {}", describe_data(&transaction));
+    println!("Proof:                 {}", describe_proof(&transaction));
+    match transaction.verify(transaction.network_id) {
+        Ok(()) => println!("Valid:                 yes"),
+        Err(e) => println!("Valid:                 no ({})", e),
+    }
+
+    Ok(())
+}
+
+fn describe_data(transaction: &Transaction) -> String {
+    if transaction
+        .flags
+        .contains(TransactionFlags::CONTRACT_CREATION)
+    {
+        match transaction.recipient_type {
+            AccountType::HTLC => return describe(HtlcCreationData::parse(transaction)),
+            AccountType::Vesting => return describe(VestingCreationData::parse(transaction)),
+            _ => {}
+        }
+    }
+
+    if transaction.recipient_type == AccountType::Staking {
+        return if transaction.sender == transaction.recipient {
+            describe(SelfStakingTransactionData::parse(transaction))
+        } else {
+            describe(IncomingStakingTransactionData::parse(transaction))
+        };
+    }
+
+    if transaction.data.is_empty() {
+        "-".to_string()
+    } else {
+        hex::encode(&transaction.data)
+    }
+}
+
+fn describe_proof(transaction: &Transaction) -> String {
+    if transaction.proof.is_empty() {
+        return "unsigned".to_string();
+    }
+
+    match transaction.sender_type {
+        AccountType::Staking if transaction.sender != transaction.recipient => {
+            describe(OutgoingStakingTransactionProof::parse(transaction))
+        }
+        AccountType::HTLC => match ProofType::deserialize_from_vec(&transaction.proof) {
+            Ok(proof_type) => format!("{:?} {}", proof_type, hex::encode(&transaction.proof[1..])),
+            Err(e) => format!("invalid ({})", e),
+        },
+        _ => match SignatureProof::deserialize_from_vec(&transaction.proof) {
+            Ok(proof) => format!(
+                "signed by {}",
+                proof.compute_signer().to_user_friendly_address()
+            ),
+            Err(e) => format!("invalid ({})", e),
+        },
+    }
+}
+
+fn describe<T: std::fmt::Debug, E: std::fmt::Display>(result: Result<T, E>) -> String {
+    match result {
+        Ok(value) => format!("{:#?}", value),
+        Err(e) => format!("invalid ({})", e),
+    }
+}
+
+pub fn encode_transaction(transaction: &Transaction) -> String {
+    hex::encode(transaction.serialize_to_vec())
+}
+
+fn decode_transaction(transaction: &str) -> Result<Transaction, Error> {
+    let raw = hex::decode(transaction.trim())?;
+    Ok(Transaction::deserialize_from_vec(&raw)?)
+}
+
+/// Returns the hash root of an HTLC, i.e. the pre-image hashed `hash_count` times.
+fn hash_root(hash_algorithm: HashAlgorithm, pre_image: AnyHash, hash_count: u8) -> AnyHash {
+    let mut hash: [u8; 32] = pre_image.into();
+    for _ in 0..hash_count {
+        hash = match hash_algorithm {
+            HashAlgorithm::Blake2b => Blake2bHasher::default().digest(&hash[..]).into(),
+            HashAlgorithm::Sha256 => Sha256Hasher::default().digest(&hash[..]).into(),
+        };
+    }
+    hash.into()
+}
+
+/// Reads a key file, which either contains the hex-encoded key, as printed by `nimiq-address` and
+/// `nimiq-bls`, or the serialized key, like the key files of the node.
+fn read_key_file(path: &Path) -> Result<Vec<u8>, Error> {
+    let content = fs::read(path)?;
+    let decoded = std::str::from_utf8(&content)
+        .ok()
+        .and_then(|s| hex::decode(s.trim()).ok());
+    Ok(decoded.unwrap_or(content))
+}
+
+fn load_wallet(path: &Path) -> Result<WalletAccount, Error> {
+    let private_key = PrivateKey::deserialize_from_vec(&read_key_file(path)?)?;
+    Ok(WalletAccount::from(KeyPair::from(private_key)))
+}
+
+fn load_validator_key(path: &Path) -> Result<BlsKeyPair, Error> {
+    Ok(BlsKeyPair::deserialize_from_vec(&read_key_file(path)?)?)
+}
+
+fn parse_hash_algorithm(s: &str) -> Result<HashAlgorithm, Error> {
+    Ok(match s {
+        "blake2b" => HashAlgorithm::Blake2b,
+        "sha256" => HashAlgorithm::Sha256,
+        _ => bail!("Invalid hash algorithm: {}", s),
+    })
+}
+
+fn parse_contract_type(s: &str) -> Result<AccountType, Error> {
+    Ok(match s {
+        "htlc" => AccountType::HTLC,
+        "vesting" => AccountType::Vesting,
+        _ => bail!("Invalid contract type: {}", s),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use tempdir::TempDir;
+
+    use nimiq_keys::SecureGenerate;
+    use nimiq_primitives::policy;
+
+    use super::*;
+
+    /// The key files of the accounts that take part in the transactions.
+    struct Keys {
+        dir: TempDir,
+        sender: WalletAccount,
+        recipient: WalletAccount,
+        validator: BlsKeyPair,
+    }
+
+    impl Keys {
+        fn new() -> Self {
+            let keys = Keys {
+                dir: TempDir::new("offline").unwrap(),
+                sender: WalletAccount::generate(),
+                recipient: WalletAccount::generate(),
+                validator: BlsKeyPair::generate_default_csprng(),
+            };
+            fs::write(
+                keys.sender_key(),
+                hex::encode(keys.sender.key_pair.private.serialize_to_vec()),
+            )
+            .unwrap();
+            fs::write(
+                keys.recipient_key(),
+                hex::encode(keys.recipient.key_pair.private.serialize_to_vec()),
+            )
+            .unwrap();
+            // Like the key files of the node.
+            fs::write(keys.validator_key(), keys.validator.serialize_to_vec()).unwrap();
+            keys
+        }
+
+        fn sender_key(&self) -> PathBuf {
+            self.dir.path().join("sender.key")
+        }
+
+        fn recipient_key(&self) -> PathBuf {
+            self.dir.path().join("recipient.key")
+        }
+
+        fn validator_key(&self) -> PathBuf {
+            self.dir.path().join("validator.key")
+        }
+    }
+
+    fn options() -> TransactionOptions {
+        TransactionOptions {
+            fee: Coin::from_u64_unchecked(10),
+            validity_start_height: 1,
+            network: NetworkId::DevAlbatross,
+            staking_contract: None,
+        }
+    }
+
+    fn sign_command(transaction: &Transaction) -> SignCommand {
+        SignCommand {
+            transaction: encode_transaction(transaction),
+            key: None,
+            validator_key: None,
+            drop_validator: None,
+            htlc: None,
+            htlc_sender_key: None,
+            pre_image: None,
+            hash_count: 1,
+            hash_algorithm: HashAlgorithm::Blake2b,
+        }
+    }
+
+    /// Signs the transaction and checks that the encoded transaction decodes to a valid one.
+    fn sign_and_verify(sign: SignCommand) -> Transaction {
+        let transaction = sign.sign().unwrap();
+        let decoded = decode_transaction(&encode_transaction(&transaction)).unwrap();
+        assert_eq!(decoded, transaction);
+        decoded.verify(NetworkId::DevAlbatross).unwrap();
+        decoded
+    }
+
+    fn value(value: u64) -> Coin {
+        Coin::from_u64_unchecked(value)
+    }
+
+    fn validator_id() -> ValidatorId {
+        ValidatorId::from([1u8; ValidatorId::SIZE])
+    }
+
+    #[test]
+    fn it_signs_basic_transactions() {
+        let keys = Keys::new();
+        let transaction = BuildCommand::Basic {
+            sender: keys.sender.address.clone(),
+            recipient: keys.recipient.address.clone(),
+            value: value(100),
+            options: options(),
+        }
+        .build()
+        .unwrap();
+        assert!(transaction.proof.is_empty());
+
+        let transaction = sign_and_verify(SignCommand {
+            key: Some(keys.sender_key()),
+            ..sign_command(&transaction)
+        });
+        assert_eq!(transaction.sender, keys.sender.address);
+        assert_eq!(transaction.fee, value(10));
+    }
+
+    #[test]
+    fn it_rejects_signatures_of_the_wrong_key() {
+        let keys = Keys::new();
+        let transaction = BuildCommand::Basic {
+            sender: keys.sender.address.clone(),
+            recipient: keys.recipient.address.clone(),
+            value: value(100),
+            options: options(),
+        }
+        .build()
+        .unwrap();
+
+        let sign = SignCommand {
+            key: Some(keys.recipient_key()),
+            ..sign_command(&transaction)
+        };
+        assert!(sign.sign().is_err());
+    }
+
+    #[test]
+    fn it_signs_htlc_transactions() {
+        let keys = Keys::new();
+        let pre_image = AnyHash::from([2u8; AnyHash::SIZE]);
+        let transaction = BuildCommand::Htlc {
+            sender: keys.sender.address.clone(),
+            value: value(100),
+            htlc_sender: None,
+            htlc_recipient: keys.recipient.address.clone(),
+            hash_root: hash_root(HashAlgorithm::Sha256, pre_image.clone(), 2),
+            hash_count: 2,
+            hash_algorithm: HashAlgorithm::Sha256,
+            timeout: 100,
+            options: options(),
+        }
+        .build()
+        .unwrap();
+        let transaction = sign_and_verify(SignCommand {
+            key: Some(keys.sender_key()),
+            ..sign_command(&transaction)
+        });
+        let contract = transaction.recipient.clone();
+
+        let redeem = |recipient: &WalletAccount| {
+            BuildCommand::Redeem {
+                contract: contract.clone(),
+                contract_type: AccountType::HTLC,
+                recipient: recipient.address.clone(),
+                value: value(90),
+                options: options(),
+            }
+            .build()
+            .unwrap()
+        };
+
+        let transaction = redeem(&keys.recipient);
+        sign_and_verify(SignCommand {
+            key: Some(keys.recipient_key()),
+            htlc: Some(HtlcResolution::RegularTransfer),
+            pre_image: Some(pre_image),
+            hash_count: 2,
+            hash_algorithm: HashAlgorithm::Sha256,
+            ..sign_command(&transaction)
+        });
+
+        let transaction = redeem(&keys.recipient);
+        sign_and_verify(SignCommand {
+            key: Some(keys.recipient_key()),
+            htlc: Some(HtlcResolution::EarlyResolve),
+            htlc_sender_key: Some(keys.sender_key()),
+            ..sign_command(&transaction)
+        });
+
+        let transaction = redeem(&keys.sender);
+        sign_and_verify(SignCommand {
+            key: Some(keys.sender_key()),
+            htlc: Some(HtlcResolution::TimeoutResolve),
+            ..sign_command(&transaction)
+        });
+    }
+
+    #[test]
+    fn it_signs_vesting_transactions() {
+        let keys = Keys::new();
+        let transaction = BuildCommand::Vesting {
+            sender: keys.sender.address.clone(),
+            owner: keys.recipient.address.clone(),
+            value: value(100),
+            start_time: 0,
+            time_step: 10,
+            step_amount: Some(value(50)),
+            total_amount: None,
+            options: options(),
+        }
+        .build()
+        .unwrap();
+        let transaction = sign_and_verify(SignCommand {
+            key: Some(keys.sender_key()),
+            ..sign_command(&transaction)
+        });
+
+        let transaction = BuildCommand::Redeem {
+            contract: transaction.recipient,
+            contract_type: AccountType::Vesting,
+            recipient: keys.recipient.address.clone(),
+            value: value(50),
+            options: options(),
+        }
+        .build()
+        .unwrap();
+        sign_and_verify(SignCommand {
+            key: Some(keys.recipient_key()),
+            ..sign_command(&transaction)
+        });
+    }
+
+    #[test]
+    fn it_signs_staker_transactions() {
+        let keys = Keys::new();
+        let signed_by_sender = |transaction: Transaction| {
+            sign_and_verify(SignCommand {
+                key: Some(keys.sender_key()),
+                ..sign_command(&transaction)
+            })
+        };
+
+        signed_by_sender(
+            BuildCommand::Stake {
+                sender: keys.sender.address.clone(),
+                validator_id: validator_id(),
+                value: value(policy::MIN_STAKE),
+                staker_address: Some(keys.recipient.address.clone()),
+                options: options(),
+            }
+            .build()
+            .unwrap(),
+        );
+
+        signed_by_sender(
+            BuildCommand::Rededicate {
+                from_validator_id: validator_id(),
+                to_validator_id: ValidatorId::from([2u8; ValidatorId::SIZE]),
+                value: value(100),
+                options: options(),
+            }
+            .build()
+            .unwrap(),
+        );
+
+        signed_by_sender(
+            BuildCommand::Retire {
+                validator_id: validator_id(),
+                value: value(100),
+                options: options(),
+            }
+            .build()
+            .unwrap(),
+        );
+
+        signed_by_sender(
+            BuildCommand::Reactivate {
+                validator_id: validator_id(),
+                value: value(100),
+                options: options(),
+            }
+            .build()
+            .unwrap(),
+        );
+
+        let transaction = signed_by_sender(
+            BuildCommand::Unstake {
+                recipient: keys.sender.address.clone(),
+                value: value(100),
+                options: options(),
+            }
+            .build()
+            .unwrap(),
+        );
+        assert_eq!(transaction.sender_type, AccountType::Staking);
+    }
+
+    #[test]
+    fn it_signs_validator_transactions() {
+        let keys = Keys::new();
+        let signed_by_validator = |transaction: Transaction| {
+            sign_and_verify(SignCommand {
+                key: Some(keys.sender_key()),
+                validator_key: Some(keys.validator_key()),
+                ..sign_command(&transaction)
+            })
+        };
+
+        let transaction = signed_by_validator(
+            BuildCommand::NewValidator {
+                sender: keys.sender.address.clone(),
+                reward_address: keys.recipient.address.clone(),
+                value: value(policy::MIN_VALIDATOR_STAKE),
+                validator_key: keys.validator_key(),
+                commission: Some(100),
+                options: options(),
+            }
+            .build()
+            .unwrap(),
+        );
+        assert!(!transaction.flags.contains(TransactionFlags::SIGNALLING));
+
+        let transaction = signed_by_validator(
+            BuildCommand::UpdateValidator {
+                sender: keys.sender.address.clone(),
+                validator_id: validator_id(),
+                validator_key: keys.validator_key(),
+                new_validator_key: None,
+                new_reward_address: Some(keys.sender.address.clone()),
+                new_commission: None,
+                remove_commission: true,
+                options: options(),
+            }
+            .build()
+            .unwrap(),
+        );
+        assert!(transaction.flags.contains(TransactionFlags::SIGNALLING));
+
+        signed_by_validator(
+            BuildCommand::RetireValidator {
+                sender: keys.sender.address.clone(),
+                validator_id: validator_id(),
+                options: options(),
+            }
+            .build()
+            .unwrap(),
+        );
+
+        signed_by_validator(
+            BuildCommand::ReactivateValidator {
+                sender: keys.sender.address.clone(),
+                validator_id: validator_id(),
+                options: options(),
+            }
+            .build()
+            .unwrap(),
+        );
+
+        signed_by_validator(
+            BuildCommand::UnparkValidator {
+                sender: keys.sender.address.clone(),
+                validator_id: validator_id(),
+                options: options(),
+            }
+            .build()
+            .unwrap(),
+        );
+
+        let transaction = BuildCommand::DropValidator {
+            recipient: keys.recipient.address.clone(),
+            value: value(policy::MIN_VALIDATOR_STAKE),
+            options: options(),
+        }
+        .build()
+        .unwrap();
+        sign_and_verify(SignCommand {
+            validator_key: Some(keys.validator_key()),
+            drop_validator: Some(validator_id()),
+            ..sign_command(&transaction)
+        });
+    }
+
+    #[test]
+    fn it_reads_hex_and_binary_key_files() {
+        let keys = Keys::new();
+        assert_eq!(
+            load_wallet(&keys.sender_key()).unwrap().address,
+            keys.sender.address
+        );
+        assert_eq!(
+            load_validator_key(&keys.validator_key())
+                .unwrap()
+                .public_key,
+            keys.validator.public_key
+        );
+    }
+}
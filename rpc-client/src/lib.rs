@@ -1,30 +1,159 @@
 pub use url::Url;
 
+use serde_json::Value;
+
 pub use nimiq_jsonrpc_client::websocket::Error;
 use nimiq_jsonrpc_client::{websocket::WebsocketClient, ArcClient};
 pub use nimiq_jsonrpc_core::Credentials;
+use nimiq_jsonrpc_core::Response;
+pub use nimiq_rpc_interface::batch::{Batch, BatchError, BatchResponses};
+use nimiq_rpc_interface::types::{Block, OrLatest};
 
 pub use nimiq_rpc_interface::{
     blockchain::BlockchainProxy, consensus::ConsensusProxy, mempool::MempoolProxy,
-    wallet::WalletProxy,
+    validator::ValidatorProxy, wallet::WalletProxy,
 };
 
+/// The maximum number of requests per batch that a node accepts by default.
+pub const DEFAULT_MAX_BATCH_SIZE: usize = 1000;
+
 pub struct Client {
     pub blockchain: BlockchainProxy<ArcClient<WebsocketClient>>,
     pub consensus: ConsensusProxy<ArcClient<WebsocketClient>>,
     pub mempool: MempoolProxy<ArcClient<WebsocketClient>>,
+    pub validator: ValidatorProxy<ArcClient<WebsocketClient>>,
     pub wallet: WalletProxy<ArcClient<WebsocketClient>>,
+    /// Batches are sent as HTTP POST requests, since the WebSocket client only sends single
+    /// requests.
+    http: reqwest::Client,
+    http_url: Url,
+    basic_auth: Option<(String, String)>,
+    /// The API token that batches authenticate with as bearer auth.
+    token: Option<String>,
+    max_batch_size: usize,
 }
 
 impl Client {
     pub async fn new(url: Url, credentials: Option<Credentials>) -> Result<Self, Error> {
+        let http_url = http_url(&url);
+        let basic_auth = credentials
+            .as_ref()
+            .map(|credentials| (credentials.username.clone(), credentials.password.clone()));
         let client = ArcClient::new(WebsocketClient::new(url, credentials).await?);
 
         Ok(Self {
             blockchain: BlockchainProxy::new(client.clone()),
             consensus: ConsensusProxy::new(client.clone()),
             mempool: MempoolProxy::new(client.clone()),
-            wallet: WalletProxy::new(client.clone()),
+            validator: ValidatorProxy::new(client.clone()),
+            wallet: WalletProxy::new(client),
+            http: reqwest::Client::new(),
+            http_url,
+            basic_auth,
+            token: None,
+            max_batch_size: DEFAULT_MAX_BATCH_SIZE,
         })
     }
+
+    /// Authenticates batches with the given API token instead of the credentials.
+    pub fn with_token(mut self, token: String) -> Self {
+        self.token = Some(token);
+        self
+    }
+
+    /// Sets the maximum number of requests that are sent in one batch. This must not exceed the
+    /// limit of the node.
+    pub fn with_max_batch_size(mut self, max_batch_size: usize) -> Self {
+        self.max_batch_size = max_batch_size.max(1);
+        self
+    }
+
+    /// Sends all requests of the batch in one round trip. The node limits the size of batches.
+    pub async fn send_batch(&mut self, batch: &Batch) -> Result<BatchResponses, BatchError> {
+        let mut request = self.http.post(self.http_url.clone()).json(batch.requests());
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        } else if let Some((username, password)) = &self.basic_auth {
+            request = request.basic_auth(username, Some(password));
+        }
+
+        let response = request
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| BatchError::Transport(e.to_string()))?;
+        let body = response
+            .bytes()
+            .await
+            .map_err(|e| BatchError::Transport(e.to_string()))?;
+
+        // Batches that the node rejects as a whole are answered with a single error response.
+        match serde_json::from_slice::<Value>(&body)? {
+            Value::Array(responses) => Ok(BatchResponses::new(
+                batch,
+                serde_json::from_value::<Vec<Response>>(Value::Array(responses))?,
+            )),
+            response => {
+                let response: Response = serde_json::from_value(response)?;
+                Err(BatchError::Rpc(format!("{:?}", response.error)))
+            }
+        }
+    }
+
+    /// Fetches the blocks with the given numbers, in as few round trips as the maximum batch size
+    /// allows.
+    pub async fn blocks_by_number(
+        &mut self,
+        block_numbers: &[u32],
+        include_transactions: bool,
+    ) -> Result<Vec<Result<Block, BatchError>>, BatchError> {
+        let mut blocks = Vec::with_capacity(block_numbers.len());
+
+        for chunk in block_numbers.chunks(self.max_batch_size) {
+            let mut batch = Batch::new();
+            for &block_number in chunk {
+                batch
+                    .add(
+                        "blockByNumber",
+                        &serde_json::json!({
+                            "blockNumber": OrLatest::Value(block_number),
+                            "includeTransactions": include_transactions,
+                        }),
+                    )
+                    .expect("The parameters can always be serialized");
+            }
+
+            let responses = self.send_batch(&batch).await?;
+            blocks.extend((0..batch.len()).map(|index| responses.get(index)));
+        }
+
+        Ok(blocks)
+    }
+}
+
+/// Returns the URL of the HTTP endpoint of the node that serves the WebSocket endpoint `url`.
+fn http_url(url: &Url) -> Url {
+    let mut http_url = url.clone();
+    let scheme = match url.scheme() {
+        "ws" => "http",
+        "wss" => "https",
+        scheme => scheme,
+    };
+    // Switching between these schemes can't fail.
+    http_url.set_scheme(scheme).ok();
+    http_url.set_path("/");
+    http_url
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_derives_the_http_url() {
+        let url = |url: &str| http_url(&url.parse().unwrap()).to_string();
+
+        assert_eq!(url("ws://127.0.0.1:8648/ws"), "http://127.0.0.1:8648/");
+        assert_eq!(url("wss://example.com/ws"), "https://example.com/");
+    }
 }
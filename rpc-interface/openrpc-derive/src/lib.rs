@@ -0,0 +1,177 @@
+//! Generates the OpenRPC description of an RPC interface trait.
+//!
+//! `#[openrpc]` must be the first attribute of the trait, so that it sees the methods before
+//! `async_trait` rewrites them. It leaves the trait unchanged and adds a function `openrpc_methods`
+//! next to it, which describes every method of the trait. Method and parameter names are converted
+//! to camelCase, like the JSON-RPC proxies and services do. Methods marked with `#[stream]` are
+//! described by the items of the stream they return.
+//!
+//! The parameter and result types must implement `nimiq_rpc_interface::openrpc::RpcSchema`.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{
+    parse_macro_input, Attribute, FnArg, GenericArgument, ItemTrait, Lit, Meta, Pat, PathArguments,
+    ReturnType, TraitItem, TraitItemMethod, Type,
+};
+
+#[proc_macro_attribute]
+pub fn openrpc(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    let item_trait = parse_macro_input!(item as ItemTrait);
+
+    let methods = item_trait
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            TraitItem::Method(method) => Some(describe_method(method)),
+            _ => None,
+        })
+        .collect::<Result<Vec<_>, syn::Error>>();
+    let methods = match methods {
+        Ok(methods) => methods,
+        Err(e) => return e.to_compile_error().into(),
+    };
+
+    let vis = &item_trait.vis;
+    let doc = format!(
+        "Returns the OpenRPC description of the methods of `{}`.",
+        item_trait.ident
+    );
+
+    let tokens = quote! {
+        #item_trait
+
+        #[doc = #doc]
+        #vis fn openrpc_methods(
+            gen: &mut crate::openrpc::SchemaGenerator,
+        ) -> Vec<crate::openrpc::Method> {
+            vec![#(#methods),*]
+        }
+    };
+    tokens.into()
+}
+
+fn describe_method(method: &TraitItemMethod) -> Result<TokenStream2, syn::Error> {
+    let name = to_camel_case(&method.sig.ident.to_string());
+    let is_stream = method.attrs.iter().any(|attr| attr.path.is_ident("stream"));
+
+    let description = match doc_comment(&method.attrs) {
+        Some(doc) => quote! { Some(#doc.to_string()) },
+        None => quote! { None },
+    };
+
+    let mut params = vec![];
+    for arg in method.sig.inputs.iter() {
+        if let FnArg::Typed(pat_type) = arg {
+            let param_name = match &*pat_type.pat {
+                Pat::Ident(pat_ident) => to_camel_case(&pat_ident.ident.to_string()),
+                pat => return Err(syn::Error::new_spanned(pat, "expected a parameter name")),
+            };
+            let ty = &pat_type.ty;
+            params.push(quote! {
+                crate::openrpc::ContentDescriptor::new::<#ty>(gen, #param_name)
+            });
+        }
+    }
+
+    let mut result = result_type(&method.sig.output)?;
+    if is_stream {
+        result = last_type_argument(result, "BoxStream")?;
+    }
+
+    Ok(quote! {
+        crate::openrpc::Method {
+            name: #name.to_string(),
+            description: #description,
+            params: vec![#(#params),*],
+            result: crate::openrpc::ContentDescriptor::new::<#result>(gen, "result"),
+            stream: #is_stream,
+        }
+    })
+}
+
+/// Returns `T` for a method returning `Result<T, Self::Error>`.
+fn result_type(output: &ReturnType) -> Result<&Type, syn::Error> {
+    match output {
+        ReturnType::Type(_, ty) => first_type_argument(ty, "Result"),
+        ReturnType::Default => Err(syn::Error::new_spanned(
+            output,
+            "RPC methods must return a `Result`",
+        )),
+    }
+}
+
+fn type_arguments<'a>(
+    ty: &'a Type,
+    expected: &str,
+) -> Result<impl Iterator<Item = &'a Type>, syn::Error> {
+    let error = || syn::Error::new_spanned(ty, format!("expected `{}<...>`", expected));
+
+    let segment = match ty {
+        Type::Path(type_path) => type_path.path.segments.last().ok_or_else(error)?,
+        _ => return Err(error()),
+    };
+    if segment.ident != expected {
+        return Err(error());
+    }
+
+    match &segment.arguments {
+        PathArguments::AngleBracketed(arguments) => {
+            Ok(arguments.args.iter().filter_map(|argument| match argument {
+                GenericArgument::Type(ty) => Some(ty),
+                _ => None,
+            }))
+        }
+        _ => Err(error()),
+    }
+}
+
+fn first_type_argument<'a>(ty: &'a Type, expected: &str) -> Result<&'a Type, syn::Error> {
+    type_arguments(ty, expected)?
+        .next()
+        .ok_or_else(|| syn::Error::new_spanned(ty, "missing type argument"))
+}
+
+fn last_type_argument<'a>(ty: &'a Type, expected: &str) -> Result<&'a Type, syn::Error> {
+    type_arguments(ty, expected)?
+        .last()
+        .ok_or_else(|| syn::Error::new_spanned(ty, "missing type argument"))
+}
+
+/// Joins the lines of the doc comment, if there is one.
+fn doc_comment(attrs: &[Attribute]) -> Option<String> {
+    let lines: Vec<String> = attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("doc"))
+        .filter_map(|attr| match attr.parse_meta() {
+            Ok(Meta::NameValue(meta)) => match meta.lit {
+                Lit::Str(doc) => Some(doc.value().trim().to_string()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect();
+
+    if lines.is_empty() {
+        None
+    } else {
+        Some(lines.join("\n"))
+    }
+}
+
+fn to_camel_case(name: &str) -> String {
+    let mut camel_case = String::with_capacity(name.len());
+    let mut upper = false;
+    for c in name.chars() {
+        if c == '_' {
+            upper = !camel_case.is_empty();
+        } else if upper {
+            camel_case.extend(c.to_uppercase());
+            upper = false;
+        } else {
+            camel_case.push(c);
+        }
+    }
+    camel_case
+}
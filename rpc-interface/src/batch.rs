@@ -0,0 +1,148 @@
+//! Batches of requests that are sent in a single round trip.
+//!
+//! As specified by JSON-RPC 2.0, a batch is an array of request objects and it is answered with the
+//! array of their responses, in any order. Each request gets its own response, so a failed request
+//! doesn't affect the others.
+
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use thiserror::Error;
+
+use nimiq_jsonrpc_core::{Request, Response};
+
+#[derive(Debug, Error)]
+pub enum BatchError {
+    #[error("No response for request {0}")]
+    MissingResponse(usize),
+
+    #[error("Request failed: {0}")]
+    Rpc(String),
+
+    #[error("Invalid result: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("Sending the batch failed: {0}")]
+    Transport(String),
+}
+
+/// A batch of requests. The requests are identified by their index in the batch.
+#[derive(Debug, Default)]
+pub struct Batch {
+    requests: Vec<Request>,
+}
+
+impl Batch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a request to the batch and returns its index.
+    pub fn add<P: Serialize>(&mut self, method: &str, params: &P) -> Result<usize, BatchError> {
+        let index = self.requests.len();
+        self.requests.push(Request::new(
+            method.to_string(),
+            Some(serde_json::to_value(params)?),
+            Some(Value::from(index)),
+        ));
+        Ok(index)
+    }
+
+    pub fn requests(&self) -> &[Request] {
+        &self.requests
+    }
+
+    pub fn len(&self) -> usize {
+        self.requests.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.requests.is_empty()
+    }
+}
+
+/// The responses to a batch of requests.
+#[derive(Debug)]
+pub struct BatchResponses {
+    responses: Vec<Option<Response>>,
+}
+
+impl BatchResponses {
+    /// Assigns the responses to the requests of `batch`. Responses that don't belong to a request
+    /// of the batch are ignored.
+    pub fn new(batch: &Batch, responses: Vec<Response>) -> Self {
+        let mut by_index: Vec<Option<Response>> =
+            std::iter::repeat_with(|| None).take(batch.len()).collect();
+        for response in responses {
+            let index = response.id.as_u64().map(|index| index as usize);
+            if let Some(slot) = index.and_then(|index| by_index.get_mut(index)) {
+                *slot = Some(response);
+            }
+        }
+
+        BatchResponses {
+            responses: by_index,
+        }
+    }
+
+    /// Returns the result of the request with the given index.
+    pub fn get<R: DeserializeOwned>(&self, index: usize) -> Result<R, BatchError> {
+        let response = self
+            .responses
+            .get(index)
+            .and_then(Option::as_ref)
+            .ok_or(BatchError::MissingResponse(index))?;
+
+        if let Some(error) = &response.error {
+            return Err(BatchError::Rpc(format!("{:?}", error)));
+        }
+
+        Ok(serde_json::from_value(
+            response.result.clone().unwrap_or(Value::Null),
+        )?)
+    }
+
+    pub fn len(&self) -> usize {
+        self.responses.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.responses.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nimiq_jsonrpc_core::RpcError;
+
+    use super::*;
+
+    #[test]
+    fn it_assigns_responses_to_requests() {
+        let mut batch = Batch::new();
+        let first = batch.add("blockNumber", &()).unwrap();
+        let second = batch.add("blockByNumber", &(1u32, false)).unwrap();
+        let third = batch.add("blockNumber", &()).unwrap();
+
+        let responses = BatchResponses::new(
+            &batch,
+            vec![
+                Response::new_error(
+                    Value::from(second),
+                    RpcError::internal_error(Some("Block not found".to_string())),
+                ),
+                Response::new_success(Value::from(first), Value::from(42)),
+                Response::new_success(Value::from(7), Value::from(0)),
+            ],
+        );
+
+        assert_eq!(responses.get::<u32>(first).unwrap(), 42);
+        assert!(matches!(
+            responses.get::<u32>(second),
+            Err(BatchError::Rpc(_))
+        ));
+        assert!(matches!(
+            responses.get::<u32>(third),
+            Err(BatchError::MissingResponse(2))
+        ));
+    }
+}
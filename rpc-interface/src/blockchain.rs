@@ -4,9 +4,14 @@ use futures::stream::BoxStream;
 use nimiq_account::Account;
 use nimiq_hash::Blake2bHash;
 use nimiq_keys::Address;
+use nimiq_primitives::account::ValidatorId;
 
-use crate::types::{Block, OrLatest, SlashedSlots, Slot, Stakes, Transaction};
+use crate::types::{
+    BatchRewards, Block, OrLatest, PendingUnstake, RawTransactionInfo, SlashEventsPage,
+    SlashedSlots, Slot, Stakes, StakingDiff, Transaction, TransactionReceipt, TransactionsPage,
+};
 
+#[nimiq_openrpc_derive::openrpc]
 #[cfg_attr(
     feature = "proxy",
     nimiq_jsonrpc_derive::proxy(name = "BlockchainProxy", rename_all = "camelCase")
@@ -42,17 +47,61 @@ pub trait BlockchainInterface {
     // TODO: Previously called `slot_state`. Where is this used?
     async fn slashed_slots(&mut self) -> Result<SlashedSlots, Self::Error>;
 
-    async fn get_raw_transaction_info(&mut self, raw_tx: String) -> Result<(), Self::Error>;
+    async fn get_raw_transaction_info(
+        &mut self,
+        raw_tx: String,
+    ) -> Result<RawTransactionInfo, Self::Error>;
 
     async fn get_transaction_by_hash(
         &mut self,
         hash: Blake2bHash,
     ) -> Result<Transaction, Self::Error>;
 
-    async fn get_transaction_receipt(&mut self, hash: Blake2bHash) -> Result<(), Self::Error>;
+    async fn get_transaction_receipt(
+        &mut self,
+        hash: Blake2bHash,
+    ) -> Result<TransactionReceipt, Self::Error>;
+
+    /// Returns a page of the transactions sent from or to the given address, newest first. Pass the
+    /// `nextCursor` of a page to get the next one. Nodes only index the transactions that they
+    /// synced with an address index, older databases need to be resynced.
+    async fn get_transactions_by_address(
+        &mut self,
+        address: Address,
+        max: Option<u16>,
+        cursor: Option<String>,
+    ) -> Result<TransactionsPage, Self::Error>;
 
     async fn list_stakes(&mut self) -> Result<Stakes, Self::Error>;
 
+    async fn get_pending_unstakes(
+        &mut self,
+        address: Address,
+    ) -> Result<Vec<PendingUnstake>, Self::Error>;
+
+    /// Returns how the rewards of the given batch were distributed. The server only knows this
+    /// for the batches whose macro blocks it processed itself, not for the ones it synced.
+    async fn get_batch_rewards(&mut self, batch_number: u32) -> Result<BatchRewards, Self::Error>;
+
+    /// Returns a page of the slash log, oldest first, optionally only the events of the given
+    /// validator or epoch. Pass the `nextCursor` of a page to get the next one.
+    async fn get_slash_events(
+        &mut self,
+        validator_id: Option<ValidatorId>,
+        epoch_number: Option<u32>,
+        max: Option<u16>,
+        cursor: Option<String>,
+    ) -> Result<SlashEventsPage, Self::Error>;
+
+    /// Returns the changes of the staking contract from the state after `from_block` to the state
+    /// after `to_block`. The server limits how many blocks the range may span, and fails if it
+    /// doesn't know the history of the range, e.g. because it macro synced past it.
+    async fn get_staking_diff(
+        &mut self,
+        from_block: u32,
+        to_block: u32,
+    ) -> Result<StakingDiff, Self::Error>;
+
     #[stream]
     async fn head_subscribe(&mut self) -> Result<BoxStream<'static, Blake2bHash>, Self::Error>;
 
@@ -5,8 +5,9 @@ use nimiq_keys::Address;
 use nimiq_primitives::account::ValidatorId;
 use nimiq_primitives::coin::Coin;
 
-use crate::types::ValidityStartHeight;
+use crate::types::{SyncStatus, ValidityStartHeight};
 
+#[nimiq_openrpc_derive::openrpc]
 #[cfg_attr(
     feature = "proxy",
     nimiq_jsonrpc_derive::proxy(name = "ConsensusProxy", rename_all = "camelCase")
@@ -15,6 +16,8 @@ use crate::types::ValidityStartHeight;
 pub trait ConsensusInterface {
     type Error;
 
+    async fn sync_status(&mut self) -> Result<SyncStatus, Self::Error>;
+
     async fn send_raw_transaction(&mut self, raw_tx: String) -> Result<Blake2bHash, Self::Error>;
 
     async fn create_basic_transaction(
@@ -134,6 +137,7 @@ pub trait ConsensusInterface {
         wallet: Address,
         reward_address: Address,
         validator_secret_key: String,
+        commission: Option<u16>,
         value: Coin,
         fee: Coin,
         validity_start_height: ValidityStartHeight,
@@ -144,6 +148,7 @@ pub trait ConsensusInterface {
         wallet: Address,
         reward_address: Address,
         validator_secret_key: String,
+        commission: Option<u16>,
         value: Coin,
         fee: Coin,
         validity_start_height: ValidityStartHeight,
@@ -156,6 +161,8 @@ pub trait ConsensusInterface {
         new_reward_address: Option<Address>,
         old_validator_secret_key: String,
         new_validator_secret_key: Option<String>,
+        new_commission: Option<u16>,
+        remove_commission: bool,
         fee: Coin,
         validity_start_height: ValidityStartHeight,
     ) -> Result<String, Self::Error>;
@@ -167,6 +174,8 @@ pub trait ConsensusInterface {
         new_reward_address: Option<Address>,
         old_validator_secret_key: String,
         new_validator_secret_key: Option<String>,
+        new_commission: Option<u16>,
+        remove_commission: bool,
         fee: Coin,
         validity_start_height: ValidityStartHeight,
     ) -> Result<Blake2bHash, Self::Error>;
@@ -1,7 +1,13 @@
+pub mod batch;
 pub mod blockchain;
 pub mod consensus;
 pub mod error;
 pub mod mempool;
+pub mod nano;
+pub mod openrpc;
+mod schema;
 mod serde_helpers;
+pub mod subscription;
 pub mod types;
+pub mod validator;
 pub mod wallet;
@@ -1,7 +1,12 @@
 use async_trait::async_trait;
 
 use nimiq_hash::Blake2bHash;
+use nimiq_keys::Address;
+use nimiq_mempool::policy::PolicyConfig;
 
+use crate::types::{AddressTransactions, HashOrTx, MempoolInfo, MempoolTransaction};
+
+#[nimiq_openrpc_derive::openrpc]
 #[cfg_attr(
     feature = "proxy",
     nimiq_jsonrpc_derive::proxy(name = "MempoolProxy", rename_all = "camelCase")
@@ -10,12 +15,27 @@ use nimiq_hash::Blake2bHash;
 pub trait MempoolInterface {
     type Error;
 
-    async fn get_transaction(&mut self, txid: Blake2bHash) -> Result<Option<()>, Self::Error>;
+    async fn get_transaction(
+        &mut self,
+        txid: Blake2bHash,
+    ) -> Result<Option<MempoolTransaction>, Self::Error>;
 
-    async fn mempool_content(&mut self, include_transactions: bool)
-        -> Result<Vec<()>, Self::Error>;
+    async fn mempool_content(
+        &mut self,
+        include_transactions: bool,
+    ) -> Result<Vec<HashOrTx>, Self::Error>;
 
-    async fn mempool(&mut self) -> Result<(), Self::Error>;
+    async fn mempool(&mut self) -> Result<MempoolInfo, Self::Error>;
 
     async fn get_mempool_transaction(&mut self) -> Result<(), Self::Error>;
+
+    async fn get_transactions_by_addresses(
+        &mut self,
+        addresses: Vec<Address>,
+        max_transactions: Option<usize>,
+    ) -> Result<Vec<AddressTransactions>, Self::Error>;
+
+    async fn get_mempool_policy(&mut self) -> Result<PolicyConfig, Self::Error>;
+
+    async fn set_mempool_policy(&mut self, policy: PolicyConfig) -> Result<(), Self::Error>;
 }
@@ -0,0 +1,38 @@
+use async_trait::async_trait;
+
+use nimiq_account::Account;
+use nimiq_hash::Blake2bHash;
+use nimiq_keys::Address;
+
+use crate::types::Transaction;
+
+/// The methods that are available on nano nodes. Accounts and transactions are requested from the
+/// network and verified, so these methods fail if the node doesn't have consensus.
+#[nimiq_openrpc_derive::openrpc]
+#[cfg_attr(
+    feature = "proxy",
+    nimiq_jsonrpc_derive::proxy(name = "NanoProxy", rename_all = "camelCase")
+)]
+#[async_trait]
+pub trait NanoInterface {
+    type Error;
+
+    async fn block_number(&mut self) -> Result<u32, Self::Error>;
+
+    async fn epoch_number(&mut self) -> Result<u32, Self::Error>;
+
+    async fn is_consensus_established(&mut self) -> Result<bool, Self::Error>;
+
+    async fn get_account(&mut self, account: Address) -> Result<Account, Self::Error>;
+
+    async fn get_recent_transactions_by_address(
+        &mut self,
+        address: Address,
+    ) -> Result<Vec<Transaction>, Self::Error>;
+
+    async fn watch_address(&mut self, address: Address) -> Result<(), Self::Error>;
+
+    async fn unwatch_address(&mut self, address: Address) -> Result<(), Self::Error>;
+
+    async fn send_raw_transaction(&mut self, raw_tx: String) -> Result<Blake2bHash, Self::Error>;
+}
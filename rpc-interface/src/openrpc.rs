@@ -0,0 +1,312 @@
+//! Generates the OpenRPC[1] document of the RPC API from the interface traits.
+//!
+//! [1] https://spec.open-rpc.org
+
+use schemars::{
+    gen::SchemaSettings,
+    schema::{InstanceType, Schema, SchemaObject},
+    JsonSchema, Map,
+};
+use serde::{Deserialize, Serialize};
+
+pub use schemars::gen::SchemaGenerator;
+
+use crate::{blockchain, consensus, mempool, nano, schema, subscription, types, validator, wallet};
+
+/// The version of the OpenRPC specification that the document follows.
+pub const OPENRPC_VERSION: &str = "1.2.6";
+
+/// The name of the method that returns the OpenRPC document.
+pub const DISCOVER_METHOD: &str = "rpc.discover";
+
+/// A type that can be used as parameter or result of an RPC method.
+pub trait RpcSchema {
+    fn rpc_schema(gen: &mut SchemaGenerator) -> Schema;
+
+    /// Whether a parameter of this type can be omitted.
+    fn is_optional() -> bool {
+        false
+    }
+}
+
+macro_rules! impl_rpc_schema {
+    ($($ty: ty),* $(,)?) => {
+        $(
+            impl RpcSchema for $ty {
+                fn rpc_schema(gen: &mut SchemaGenerator) -> Schema {
+                    gen.subschema_for::<$ty>()
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_rpc_schema_with {
+    ($($ty: ty => $with: ty),* $(,)?) => {
+        $(
+            impl RpcSchema for $ty {
+                fn rpc_schema(gen: &mut SchemaGenerator) -> Schema {
+                    gen.subschema_for::<$with>()
+                }
+            }
+        )*
+    };
+}
+
+impl_rpc_schema!(
+    bool,
+    u8,
+    u16,
+    u32,
+    u64,
+    usize,
+    String,
+    serde_json::Value,
+    types::Block,
+    types::HashOrTx,
+    types::MempoolInfo,
+    types::MempoolTransaction,
+    types::AddressTransactions,
+    types::BatchRewards,
+    types::PendingUnstake,
+    types::RawTransactionInfo,
+    types::SlashEvent,
+    types::SlashEventsPage,
+    types::SlashedSlots,
+    types::Slot,
+    types::Stakes,
+    types::StakingDiff,
+    types::SyncStatus,
+    types::SubscriptionFilter,
+    types::SubscriptionNotification,
+    types::Transaction,
+    types::TransactionReceipt,
+    types::TransactionsPage,
+    types::ValidatorDuties,
+    types::OrLatest<u32>,
+    wallet::ReturnAccount,
+    wallet::ReturnSignature,
+    OpenRpcDocument,
+);
+
+impl_rpc_schema_with!(
+    nimiq_hash::Blake2bHash => schema::Hash,
+    nimiq_keys::Address => schema::Address,
+    nimiq_keys::PublicKey => schema::PublicKey,
+    nimiq_keys::Signature => schema::Signature,
+    nimiq_primitives::account::ValidatorId => schema::ValidatorId,
+    nimiq_primitives::coin::Coin => schema::Coin,
+    nimiq_account::Account => schema::AccountState,
+    nimiq_mempool::policy::PolicyConfig => schema::PolicyConfig,
+    types::ValidityStartHeight => schema::ValidityStartHeight,
+);
+
+impl RpcSchema for () {
+    fn rpc_schema(_gen: &mut SchemaGenerator) -> Schema {
+        SchemaObject {
+            instance_type: Some(InstanceType::Null.into()),
+            ..Default::default()
+        }
+        .into()
+    }
+}
+
+impl<T: RpcSchema> RpcSchema for Option<T> {
+    fn rpc_schema(gen: &mut SchemaGenerator) -> Schema {
+        let schema = SchemaObject {
+            subschemas: Some(Box::new(schemars::schema::SubschemaValidation {
+                any_of: Some(vec![T::rpc_schema(gen), <()>::rpc_schema(gen)]),
+                ..Default::default()
+            })),
+            ..Default::default()
+        };
+        schema.into()
+    }
+
+    fn is_optional() -> bool {
+        true
+    }
+}
+
+impl<T: RpcSchema> RpcSchema for Vec<T> {
+    fn rpc_schema(gen: &mut SchemaGenerator) -> Schema {
+        let schema = SchemaObject {
+            instance_type: Some(InstanceType::Array.into()),
+            array: Some(Box::new(schemars::schema::ArrayValidation {
+                items: Some(T::rpc_schema(gen).into()),
+                ..Default::default()
+            })),
+            ..Default::default()
+        };
+        schema.into()
+    }
+}
+
+/// An OpenRPC document, see https://spec.open-rpc.org/#openrpc-object.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenRpcDocument {
+    pub openrpc: String,
+    pub info: Info,
+    pub methods: Vec<Method>,
+    pub components: Components,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Info {
+    pub title: String,
+    pub version: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Method {
+    pub name: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    pub params: Vec<ContentDescriptor>,
+
+    pub result: ContentDescriptor,
+
+    /// Whether the method returns a stream of results. Streams are only available over WebSocket
+    /// and `result` describes each item of the stream. This is not part of the OpenRPC
+    /// specification.
+    #[serde(
+        rename = "x-stream",
+        default,
+        skip_serializing_if = "std::ops::Not::not"
+    )]
+    pub stream: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ContentDescriptor {
+    pub name: String,
+
+    pub required: bool,
+
+    #[schemars(with = "serde_json::Value")]
+    pub schema: Schema,
+}
+
+impl ContentDescriptor {
+    pub fn new<T: RpcSchema>(gen: &mut SchemaGenerator, name: &str) -> Self {
+        ContentDescriptor {
+            name: name.to_string(),
+            required: !T::is_optional(),
+            schema: T::rpc_schema(gen),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Components {
+    #[schemars(with = "Map<String, serde_json::Value>")]
+    pub schemas: Map<String, Schema>,
+}
+
+/// Generates the OpenRPC document of all methods of the RPC API.
+pub fn document() -> OpenRpcDocument {
+    let mut gen = SchemaSettings::draft07()
+        .with(|settings| settings.definitions_path = "#/components/schemas/".to_string())
+        .into_generator();
+
+    let mut methods = vec![];
+    methods.extend(blockchain::openrpc_methods(&mut gen));
+    methods.extend(consensus::openrpc_methods(&mut gen));
+    methods.extend(mempool::openrpc_methods(&mut gen));
+    methods.extend(wallet::openrpc_methods(&mut gen));
+    methods.extend(validator::openrpc_methods(&mut gen));
+    methods.extend(subscription::openrpc_methods(&mut gen));
+
+    // Nano nodes serve some of the methods of full nodes as well, which are only described once.
+    for method in nano::openrpc_methods(&mut gen) {
+        if !methods.iter().any(|other| other.name == method.name) {
+            methods.push(method);
+        }
+    }
+
+    methods.push(Method {
+        name: DISCOVER_METHOD.to_string(),
+        description: Some("Returns the OpenRPC document of this API.".to_string()),
+        params: vec![],
+        result: ContentDescriptor::new::<OpenRpcDocument>(&mut gen, "result"),
+        stream: false,
+    });
+
+    OpenRpcDocument {
+        openrpc: OPENRPC_VERSION.to_string(),
+        info: Info {
+            title: "Nimiq JSON-RPC API".to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+        },
+        methods,
+        components: Components {
+            schemas: gen.definitions().clone(),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_describes_all_interfaces() {
+        let document = document();
+        let method = |name: &str| {
+            document
+                .methods
+                .iter()
+                .find(|method| method.name == name)
+                .unwrap_or_else(|| panic!("Missing method {}", name))
+        };
+
+        assert_eq!(method("blockByNumber").params.len(), 2);
+        assert!(method("getTransactionsByAddress").params[0].required);
+        assert!(!method("getTransactionsByAddress").params[1].required);
+        assert!(method("subscribe").stream);
+        assert!(!method("sendRawTransaction").stream);
+        method("listAccounts");
+        method("getMempoolPolicy");
+        method("getValidatorDuties");
+        method("syncStatus");
+        method("getRecentTransactionsByAddress");
+        method("watchAddress");
+        assert!(method("getPendingUnstakes").params[0].required);
+        method(DISCOVER_METHOD);
+
+        for name in ["Block", "Transaction", "Hash", "Address", "Coin"].iter() {
+            assert!(
+                document.components.schemas.contains_key(*name),
+                "Missing schema {}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn it_describes_every_method_once() {
+        let document = document();
+        for (i, method) in document.methods.iter().enumerate() {
+            assert!(
+                document.methods[..i]
+                    .iter()
+                    .all(|other| other.name != method.name),
+                "Method {} is described twice",
+                method.name
+            );
+        }
+    }
+
+    #[test]
+    fn it_references_the_component_schemas() {
+        let json = serde_json::to_string(&document()).unwrap();
+        assert!(json.contains("\"$ref\":\"#/components/schemas/Block\""));
+    }
+}
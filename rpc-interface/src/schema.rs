@@ -0,0 +1,251 @@
+//! JSON schemas for the types from other crates that are used by the RPC API. They are used with
+//! `#[schemars(with = "...")]` and describe the types as they are serialized, see `serde_helpers`.
+
+use schemars::{
+    gen::SchemaGenerator,
+    schema::{ArrayValidation, InstanceType, Metadata, Schema, SchemaObject, StringValidation},
+    JsonSchema,
+};
+
+fn with_description(mut schema: SchemaObject, description: &str) -> Schema {
+    schema.metadata = Some(Box::new(Metadata {
+        description: Some(description.to_string()),
+        ..Default::default()
+    }));
+    schema.into()
+}
+
+fn string_schema(description: &str, pattern: &str) -> Schema {
+    let schema = SchemaObject {
+        instance_type: Some(InstanceType::String.into()),
+        string: Some(Box::new(StringValidation {
+            pattern: Some(pattern.to_string()),
+            ..Default::default()
+        })),
+        ..Default::default()
+    };
+    with_description(schema, description)
+}
+
+fn integer_schema(description: &str, format: &str) -> Schema {
+    let schema = SchemaObject {
+        instance_type: Some(InstanceType::Integer.into()),
+        format: Some(format.to_string()),
+        ..Default::default()
+    };
+    with_description(schema, description)
+}
+
+fn array_schema<T: JsonSchema>(gen: &mut SchemaGenerator, description: &str) -> Schema {
+    let schema = SchemaObject {
+        instance_type: Some(InstanceType::Array.into()),
+        array: Some(Box::new(ArrayValidation {
+            items: Some(gen.subschema_for::<T>().into()),
+            ..Default::default()
+        })),
+        ..Default::default()
+    };
+    with_description(schema, description)
+}
+
+macro_rules! string_type {
+    ($name: ident, $description: expr, $pattern: expr) => {
+        pub struct $name;
+
+        impl JsonSchema for $name {
+            fn schema_name() -> String {
+                stringify!($name).to_string()
+            }
+
+            fn json_schema(_gen: &mut SchemaGenerator) -> Schema {
+                string_schema($description, $pattern)
+            }
+        }
+    };
+}
+
+string_type!(Hash, "Hex-encoded 32 byte Blake2b hash.", "^[0-9a-f]{64}$");
+string_type!(
+    Address,
+    "User friendly address (NQ-address).",
+    "^NQ[0-9]{2}( [0-9A-Z]{4}){8}$"
+);
+string_type!(AddressHex, "Hex-encoded 20 byte address.", "^[0-9a-f]{40}$");
+string_type!(
+    ValidatorId,
+    "Hex-encoded 20 byte validator ID.",
+    "^[0-9a-f]{40}$"
+);
+string_type!(
+    BlsPublicKey,
+    "Hex-encoded compressed BLS public key.",
+    "^[0-9a-f]*$"
+);
+string_type!(
+    BlsSignature,
+    "Hex-encoded compressed BLS signature.",
+    "^[0-9a-f]*$"
+);
+string_type!(
+    VrfSeed,
+    "Hex-encoded VRF seed, which is a compressed BLS signature.",
+    "^[0-9a-f]*$"
+);
+string_type!(
+    PublicKey,
+    "Hex-encoded Ed25519 public key.",
+    "^[0-9a-f]{64}$"
+);
+string_type!(
+    PrivateKey,
+    "Hex-encoded Ed25519 private key.",
+    "^[0-9a-f]{64}$"
+);
+string_type!(Hex, "Hex-encoded bytes.", "^[0-9a-f]*$");
+string_type!(
+    ValidityStartHeight,
+    "An absolute block number, or a block number relative to the current head if prefixed by `+`.",
+    "^\\+?[0-9]+$"
+);
+
+pub struct Coin;
+
+impl JsonSchema for Coin {
+    fn schema_name() -> String {
+        "Coin".to_string()
+    }
+
+    fn json_schema(_gen: &mut SchemaGenerator) -> Schema {
+        integer_schema("An amount in Luna (1 NIM = 100000 Luna).", "uint64")
+    }
+}
+
+pub struct AccountType;
+
+impl JsonSchema for AccountType {
+    fn schema_name() -> String {
+        "AccountType".to_string()
+    }
+
+    fn json_schema(_gen: &mut SchemaGenerator) -> Schema {
+        integer_schema(
+            "The type of an account: 0 = basic, 1 = vesting, 2 = HTLC, 3 = staking.",
+            "uint8",
+        )
+    }
+}
+
+pub struct BitSet;
+
+impl JsonSchema for BitSet {
+    fn schema_name() -> String {
+        "BitSet".to_string()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        array_schema::<u32>(gen, "The indices of the set bits, in ascending order.")
+    }
+}
+
+pub struct Signature;
+
+impl JsonSchema for Signature {
+    fn schema_name() -> String {
+        "Signature".to_string()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        array_schema::<u8>(gen, "The bytes of an Ed25519 signature.")
+    }
+}
+
+pub struct AccountState;
+
+impl JsonSchema for AccountState {
+    fn schema_name() -> String {
+        "AccountState".to_string()
+    }
+
+    fn json_schema(_gen: &mut SchemaGenerator) -> Schema {
+        let schema = SchemaObject {
+            instance_type: Some(InstanceType::Object.into()),
+            ..Default::default()
+        };
+        with_description(
+            schema,
+            "The state of an account, keyed by its type (`Basic`, `Vesting` or `HTLC`).",
+        )
+    }
+}
+
+#[derive(JsonSchema)]
+#[allow(dead_code)]
+pub struct MultiSignature {
+    #[schemars(with = "BlsSignature")]
+    signature: (),
+    #[schemars(with = "BitSet")]
+    signers: (),
+}
+
+/// See `nimiq_block_albatross::TendermintProof`.
+#[derive(JsonSchema)]
+#[allow(dead_code)]
+pub struct TendermintProof {
+    /// The round when the block was completed.
+    round: u32,
+    /// The aggregated signature of the precommits of the validators for this block.
+    sig: MultiSignature,
+}
+
+/// See `nimiq_block_albatross::ViewChangeProof`.
+#[derive(JsonSchema)]
+#[allow(dead_code)]
+pub struct ViewChangeProof {
+    /// The aggregated signature of the validators for the view change.
+    sig: MultiSignature,
+}
+
+/// See `nimiq_mempool::policy::PolicyConfig`. All fields are optional.
+#[derive(JsonSchema)]
+#[allow(dead_code)]
+pub struct PolicyConfig {
+    /// The maximum number of pending transactions per sender.
+    max_per_sender: Option<usize>,
+    /// The minimum fee per byte, depending on how full the mempool is.
+    fee: Option<DynamicFeeConfig>,
+    /// If not empty, only transactions from these addresses are admitted.
+    #[schemars(with = "Option<Vec<Address>>")]
+    allow: (),
+    /// Transactions from or to these addresses are rejected.
+    #[schemars(with = "Option<Vec<Address>>")]
+    deny: (),
+    /// Rules for transactions sent to or from the staking contract.
+    staking: Option<TransactionKindRules>,
+    /// Rules for transactions that create an HTLC contract.
+    htlc_creation: Option<TransactionKindRules>,
+    /// Rules for transactions that create a vesting contract.
+    vesting_creation: Option<TransactionKindRules>,
+}
+
+#[derive(JsonSchema)]
+#[allow(dead_code)]
+pub struct DynamicFeeConfig {
+    /// The minimum fee per byte while the mempool is filled up to `threshold`. Defaults to 0.
+    min_fee_per_byte: Option<f64>,
+    /// The minimum fee per byte when the mempool is full. Defaults to 2.
+    max_fee_per_byte: Option<f64>,
+    /// The fill ratio (between 0 and 1) after which the minimum fee starts to rise. Defaults to 0.5.
+    threshold: Option<f64>,
+}
+
+#[derive(JsonSchema)]
+#[allow(dead_code)]
+pub struct TransactionKindRules {
+    /// Whether transactions of this kind are admitted at all. Defaults to true.
+    allowed: Option<bool>,
+    #[schemars(with = "Option<Coin>")]
+    min_fee: (),
+    min_fee_per_byte: Option<f64>,
+    #[schemars(with = "Option<Coin>")]
+    min_value: (),
+}
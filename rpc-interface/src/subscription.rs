@@ -0,0 +1,25 @@
+use async_trait::async_trait;
+
+use futures::stream::BoxStream;
+
+use crate::types::{SubscriptionFilter, SubscriptionNotification};
+
+/// Subscriptions to blockchain and mempool events. These are only available over WebSocket and
+/// belong to the connection that they were created on. They end when the connection is closed.
+#[nimiq_openrpc_derive::openrpc]
+#[cfg_attr(
+    feature = "proxy",
+    nimiq_jsonrpc_derive::proxy(name = "SubscriptionProxy", rename_all = "camelCase")
+)]
+#[async_trait]
+pub trait SubscriptionInterface {
+    type Error;
+
+    #[stream]
+    async fn subscribe(
+        &mut self,
+        filter: SubscriptionFilter,
+    ) -> Result<BoxStream<'static, SubscriptionNotification>, Self::Error>;
+
+    async fn unsubscribe(&mut self, subscription_id: String) -> Result<bool, Self::Error>;
+}
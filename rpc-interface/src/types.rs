@@ -7,10 +7,20 @@ use std::{
     str::FromStr,
 };
 
+use schemars::{
+    gen::SchemaGenerator,
+    schema::{InstanceType, Schema, SchemaObject, SubschemaValidation},
+    JsonSchema,
+};
 use serde::{de::Deserializer, ser::Serializer, Deserialize, Serialize};
 use serde_with::{DeserializeFromStr, SerializeDisplay};
 
+use nimiq_account::staking_contract;
 use nimiq_block_albatross::{TendermintProof, ViewChangeProof};
+use nimiq_blockchain_albatross::reward::{BatchRewardReport, StakerReward, ValidatorReward};
+use nimiq_blockchain_albatross::staking_diff::{
+    self, StakingContractDiff, ValidatorEvent, ValidatorEventKind,
+};
 use nimiq_blockchain_albatross::{AbstractBlockchain, Blockchain};
 use nimiq_bls::{CompressedPublicKey, CompressedSignature};
 use nimiq_collections::BitSet;
@@ -23,6 +33,7 @@ use nimiq_transaction::account::htlc_contract::AnyHash;
 use nimiq_vrf::VrfSeed;
 
 use crate::error::Error;
+use crate::schema;
 use nimiq_primitives::account::ValidatorId;
 
 #[derive(Clone, Debug)]
@@ -73,11 +84,12 @@ pub enum BlockType {
     Micro,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct Block {
     pub block_type: BlockType,
 
+    #[schemars(with = "schema::Hash")]
     pub hash: Blake2bHash,
 
     pub block_number: u32,
@@ -88,12 +100,16 @@ pub struct Block {
 
     pub epoch: u32,
 
+    #[schemars(with = "schema::Hash")]
     pub parent_hash: Blake2bHash,
 
+    #[schemars(with = "schema::VrfSeed")]
     pub seed: VrfSeed,
 
+    #[schemars(with = "schema::Hash")]
     pub state_root: Blake2bHash,
 
+    #[schemars(with = "schema::Hash")]
     pub body_root: Blake2bHash,
 
     pub timestamp: u64,
@@ -102,18 +118,20 @@ pub struct Block {
     pub additional_fields: BlockAdditionalFields,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase", tag = "type")]
 pub enum BlockAdditionalFields {
     Macro {
         is_election_block: bool,
 
+        #[schemars(with = "schema::Hash")]
         parent_election_hash: Blake2bHash,
 
         // None if not an election block
         slots: Option<Vec<Slots>>,
 
         #[serde(skip_serializing_if = "Option::is_none")]
+        #[schemars(with = "Option<schema::BitSet>")]
         lost_reward_set: Option<BitSet>,
 
         #[serde(skip_serializing_if = "Option::is_none")]
@@ -121,6 +139,7 @@ pub enum BlockAdditionalFields {
     },
     Micro {
         #[serde(with = "crate::serde_helpers::hex")]
+        #[schemars(with = "schema::Hex")]
         extra_data: Vec<u8>,
 
         #[serde(skip_serializing_if = "Option::is_none")]
@@ -139,12 +158,13 @@ pub enum BlockAdditionalFields {
 
 /// Likely no longer necessary and can be repalced by TendermintProof directly as
 /// the vote count in terms of slots is encoded in there.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct MacroJustification {
     pub votes: u16,
 
     #[serde(flatten)]
+    #[schemars(with = "schema::TendermintProof")]
     pub tendermint_proof: TendermintProof,
 }
 
@@ -165,15 +185,17 @@ impl MacroJustification {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct Slots {
     pub first_slot_number: u16,
 
     pub num_slots: u16,
 
+    #[schemars(with = "schema::ValidatorId")]
     pub validator_id: ValidatorId,
 
+    #[schemars(with = "schema::BlsPublicKey")]
     pub public_key: CompressedPublicKey,
 }
 
@@ -194,11 +216,13 @@ impl Slots {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct MicroJustification {
+    #[schemars(with = "schema::BlsSignature")]
     signature: CompressedSignature,
 
+    #[schemars(with = "Option<schema::ViewChangeProof>")]
     view_change_proof: Option<ViewChangeProof>,
 }
 
@@ -211,13 +235,15 @@ impl From<nimiq_block_albatross::MicroJustification> for MicroJustification {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct Slot {
     pub slot_number: u16,
 
+    #[schemars(with = "schema::ValidatorId")]
     pub validator_id: ValidatorId,
 
+    #[schemars(with = "schema::BlsPublicKey")]
     pub public_key: CompressedPublicKey,
 }
 
@@ -237,12 +263,14 @@ impl Slot {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ForkProof {
     pub block_number: u32,
     pub view_number: u32,
+    #[schemars(with = "schema::Hash")]
     pub parent_hash: Blake2bHash,
+    #[schemars(with = "[schema::Hash; 2]")]
     pub hashes: [Blake2bHash; 2],
 }
 
@@ -259,9 +287,10 @@ impl From<nimiq_block_albatross::ForkProof> for ForkProof {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct Transaction {
+    #[schemars(with = "schema::Hash")]
     pub hash: Blake2bHash,
 
     pub block_number: u32,
@@ -270,15 +299,20 @@ pub struct Transaction {
 
     pub confirmations: u32,
 
+    #[schemars(with = "schema::Address")]
     pub from: Address,
 
+    #[schemars(with = "schema::Address")]
     pub to: Address,
 
+    #[schemars(with = "schema::Coin")]
     pub value: Coin,
 
+    #[schemars(with = "schema::Coin")]
     pub fee: Coin,
 
     #[serde(with = "crate::serde_helpers::hex")]
+    #[schemars(with = "schema::Hex")]
     pub data: Vec<u8>,
 
     pub flags: u8,
@@ -286,6 +320,7 @@ pub struct Transaction {
     pub validity_start_height: u32,
 
     #[serde(with = "crate::serde_helpers::hex")]
+    #[schemars(with = "schema::Hex")]
     pub proof: Vec<u8>,
 }
 
@@ -313,6 +348,297 @@ impl Transaction {
     }
 }
 
+/// A transaction that is pending in the mempool, i.e. that is not included in a block yet.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MempoolTransaction {
+    #[schemars(with = "schema::Hash")]
+    pub hash: Blake2bHash,
+
+    #[schemars(with = "schema::Address")]
+    pub from: Address,
+
+    #[schemars(with = "schema::Address")]
+    pub to: Address,
+
+    #[schemars(with = "schema::Coin")]
+    pub value: Coin,
+
+    #[schemars(with = "schema::Coin")]
+    pub fee: Coin,
+
+    pub fee_per_byte: f64,
+
+    #[serde(with = "crate::serde_helpers::hex")]
+    #[schemars(with = "schema::Hex")]
+    pub data: Vec<u8>,
+
+    pub flags: u8,
+
+    pub validity_start_height: u32,
+
+    #[serde(with = "crate::serde_helpers::hex")]
+    #[schemars(with = "schema::Hex")]
+    pub proof: Vec<u8>,
+}
+
+impl MempoolTransaction {
+    pub fn from_transaction(transaction: &nimiq_transaction::Transaction) -> Self {
+        MempoolTransaction {
+            hash: transaction.hash(),
+            from: transaction.sender.clone(),
+            to: transaction.recipient.clone(),
+            value: transaction.value,
+            fee: transaction.fee,
+            fee_per_byte: transaction.fee_per_byte(),
+            data: transaction.data.clone(),
+            flags: transaction.flags.bits() as u8,
+            validity_start_height: transaction.validity_start_height,
+            proof: transaction.proof.clone(),
+        }
+    }
+}
+
+/// Either only the hash of a transaction or the full transaction, depending on whether the caller
+/// requested full transactions or not.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum HashOrTx {
+    Hash(#[schemars(with = "schema::Hash")] Blake2bHash),
+    Tx(MempoolTransaction),
+}
+
+/// The fee-per-byte thresholds (in Luna per byte) used to group the mempool transactions into
+/// buckets. A transaction is counted in the first bucket whose threshold it reaches.
+pub const MEMPOOL_FEE_BUCKETS: [u32; 14] = [
+    10000, 5000, 2000, 1000, 500, 200, 100, 50, 20, 10, 5, 2, 1, 0,
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MempoolFeeBucket {
+    /// The minimum fee per byte of the transactions in this bucket.
+    pub min_fee_per_byte: u32,
+
+    /// The number of transactions in this bucket.
+    pub count: usize,
+}
+
+/// A histogram of the transactions in the mempool grouped by their fee per byte.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MempoolInfo {
+    /// Total number of transactions in the mempool.
+    pub total: usize,
+
+    /// Non-empty fee buckets, ordered by descending fee per byte.
+    pub buckets: Vec<MempoolFeeBucket>,
+}
+
+impl MempoolInfo {
+    pub fn from_txs<'a, I>(transactions: I) -> Self
+    where
+        I: IntoIterator<Item = &'a nimiq_transaction::Transaction>,
+    {
+        let mut counts = [0usize; MEMPOOL_FEE_BUCKETS.len()];
+        let mut total = 0;
+
+        for transaction in transactions {
+            let fee_per_byte = transaction.fee_per_byte();
+            // The last bucket has a threshold of 0, so every transaction falls into some bucket.
+            if let Some(i) = MEMPOOL_FEE_BUCKETS
+                .iter()
+                .position(|min_fee_per_byte| fee_per_byte >= f64::from(*min_fee_per_byte))
+            {
+                counts[i] += 1;
+            }
+            total += 1;
+        }
+
+        let buckets = MEMPOOL_FEE_BUCKETS
+            .iter()
+            .zip(counts.iter())
+            .filter(|(_, count)| **count > 0)
+            .map(|(min_fee_per_byte, count)| MempoolFeeBucket {
+                min_fee_per_byte: *min_fee_per_byte,
+                count: *count,
+            })
+            .collect();
+
+        MempoolInfo { total, buckets }
+    }
+}
+
+/// The pending transactions in the mempool that are sent from or to an address.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AddressTransactions {
+    #[schemars(with = "schema::Address")]
+    pub address: Address,
+
+    pub transactions: Vec<MempoolTransaction>,
+}
+
+/// A page of the transaction history of an address.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionsPage {
+    /// The transactions of this page, newest first.
+    pub transactions: Vec<Transaction>,
+
+    /// An opaque cursor that can be passed to the next request to get the next (older) page. This is
+    /// `None` if there are no more transactions.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+/// Describes where and how firmly a transaction is included in the blockchain.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionReceipt {
+    #[schemars(with = "schema::Hash")]
+    pub transaction_hash: Blake2bHash,
+
+    #[schemars(with = "schema::Hash")]
+    pub block_hash: Blake2bHash,
+
+    pub block_number: u32,
+
+    pub timestamp: u64,
+
+    pub confirmations: u32,
+
+    /// Whether a macro block was produced after the block that includes the transaction. If so,
+    /// the transaction can't be reverted anymore.
+    pub is_finalized: bool,
+}
+
+/// Information about a serialized transaction that was decoded and checked, but not broadcast.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RawTransactionInfo {
+    pub transaction: MempoolTransaction,
+
+    /// Whether the transaction could be included in the next block.
+    pub is_valid: bool,
+
+    /// Whether the transaction is already included in the blockchain.
+    pub is_known: bool,
+
+    /// The reason why the transaction is invalid, if it is.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Selects the events that a WebSocket subscription is notified about.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum SubscriptionFilter {
+    /// Every new head block, including the blocks of a rebranch.
+    #[serde(rename_all = "camelCase")]
+    Heads {
+        #[serde(default)]
+        include_transactions: bool,
+    },
+    /// Every new macro block.
+    MacroBlocks,
+    /// Every new election block.
+    ElectionBlocks,
+    /// Every transaction included in a new head block that is sent from or to one of the addresses.
+    /// If no addresses are given, all transactions are matched.
+    Transactions {
+        #[serde(default)]
+        #[schemars(with = "Vec<schema::Address>")]
+        addresses: Vec<Address>,
+    },
+    /// Every mempool event that concerns a transaction sent from or to one of the addresses. If no
+    /// addresses are given, all mempool events are matched.
+    Mempool {
+        #[serde(default)]
+        #[schemars(with = "Vec<schema::Address>")]
+        addresses: Vec<Address>,
+    },
+}
+
+/// A notification sent to a WebSocket subscription.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscriptionNotification {
+    /// The ID to pass to `unsubscribe` to end the subscription. IDs are random, so that they can't
+    /// be guessed.
+    pub subscription_id: String,
+
+    pub event: SubscriptionEvent,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", tag = "type", content = "data")]
+pub enum SubscriptionEvent {
+    /// The first notification of every subscription. It tells the subscriber its subscription ID.
+    Subscribed,
+    Block(Block),
+    Transaction(Transaction),
+    Mempool(MempoolEvent),
+}
+
+/// An event of the mempool, see `nimiq_mempool::MempoolEvent`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum MempoolEvent {
+    TransactionAdded {
+        transaction: MempoolTransaction,
+    },
+    TransactionRestored {
+        transaction: MempoolTransaction,
+    },
+    TransactionMined {
+        transaction: MempoolTransaction,
+    },
+    TransactionEvicted {
+        transaction: MempoolTransaction,
+    },
+    TransactionDropped {
+        transaction: MempoolTransaction,
+    },
+    TransactionReplaced {
+        replaced: MempoolTransaction,
+        replacement: MempoolTransaction,
+    },
+}
+
+impl MempoolEvent {
+    pub fn from_event(event: &nimiq_mempool::MempoolEvent) -> Self {
+        use nimiq_mempool::MempoolEvent as Event;
+
+        let tx = |transaction: &nimiq_transaction::Transaction| {
+            MempoolTransaction::from_transaction(transaction)
+        };
+        match event {
+            Event::TransactionAdded(_, transaction) => MempoolEvent::TransactionAdded {
+                transaction: tx(transaction),
+            },
+            Event::TransactionRestored(transaction) => MempoolEvent::TransactionRestored {
+                transaction: tx(transaction),
+            },
+            Event::TransactionMined(transaction) => MempoolEvent::TransactionMined {
+                transaction: tx(transaction),
+            },
+            Event::TransactionEvicted(transaction) => MempoolEvent::TransactionEvicted {
+                transaction: tx(transaction),
+            },
+            Event::TransactionDropped(transaction) => MempoolEvent::TransactionDropped {
+                transaction: tx(transaction),
+            },
+            Event::TransactionReplaced(replaced, replacement) => {
+                MempoolEvent::TransactionReplaced {
+                    replaced: tx(replaced),
+                    replacement: tx(replacement),
+                }
+            }
+        }
+    }
+}
+
 impl Block {
     pub fn from_block(
         blockchain: &Blockchain,
@@ -413,32 +739,37 @@ impl Block {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Account {
     pub id: String,
 
+    #[schemars(with = "schema::Address")]
     pub address: Address,
 
+    #[schemars(with = "schema::Coin")]
     pub balance: Coin,
 
     #[serde(rename = "type", with = "crate::serde_helpers::account_type")]
+    #[schemars(with = "schema::AccountType")]
     pub ty: AccountType,
 
     #[serde(flatten)]
     pub account_additional_fields: AccountAdditionalFields,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase", tag = "type")]
 pub enum AccountAdditionalFields {
     /// Additional account information for vesting contracts.
     VestingContract {
         /// Hex-encoded 20 byte address of the owner of the vesting contract.
         #[serde(with = "crate::serde_helpers::address_hex")]
+        #[schemars(with = "schema::AddressHex")]
         owner: Address,
 
         /// User friendly address (NQ-address) of the owner of the vesting contract.
         #[serde(with = "crate::serde_helpers::address_friendly")]
+        #[schemars(with = "schema::Address")]
         owner_address: Address,
 
         /// The block that the vesting contracted commenced.
@@ -448,9 +779,11 @@ pub enum AccountAdditionalFields {
         vesting_step_blocks: u32,
 
         /// The amount (in Luna) released every vestingStepBlocks blocks.
+        #[schemars(with = "schema::Coin")]
         vesting_step_amount: Coin,
 
         /// The total amount (in smallest unit) that was provided at the contract creation.
+        #[schemars(with = "schema::Coin")]
         vesting_total_amount: Coin,
     },
 
@@ -458,22 +791,27 @@ pub enum AccountAdditionalFields {
     HTLC {
         /// Hex-encoded 20 byte address of the sender of the HTLC.
         #[serde(with = "crate::serde_helpers::address_hex")]
+        #[schemars(with = "schema::AddressHex")]
         sender: Address,
 
         /// User friendly address (NQ-address) of the sender of the HTLC.
         #[serde(with = "crate::serde_helpers::address_friendly")]
+        #[schemars(with = "schema::Address")]
         sender_address: Address,
 
         /// Hex-encoded 20 byte address of the recipient of the HTLC.
         #[serde(with = "crate::serde_helpers::address_hex")]
+        #[schemars(with = "schema::AddressHex")]
         recipient: Address,
 
         /// User friendly address (NQ-address) of the recipient of the HTLC.
         #[serde(with = "crate::serde_helpers::address_friendly")]
+        #[schemars(with = "schema::Address")]
         recipient_address: Address,
 
         /// Hex-encoded 32 byte hash root.
         #[serde(with = "serde_with::rust::display_fromstr")]
+        #[schemars(with = "schema::Hash")]
         hash_root: AnyHash,
 
         /// Number of hashes this HTLC is split into
@@ -483,6 +821,7 @@ pub enum AccountAdditionalFields {
         timeout: u32,
 
         /// The total amount (in smallest unit) that was provided at the contract creation.
+        #[schemars(with = "schema::Coin")]
         total_amount: Coin,
     },
     Staking {
@@ -531,6 +870,41 @@ where
     }
 }
 
+impl<T> JsonSchema for OrLatest<T>
+where
+    T: JsonSchema,
+{
+    fn schema_name() -> String {
+        format!("OrLatest_{}", T::schema_name())
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        let latest = SchemaObject {
+            instance_type: Some(InstanceType::String.into()),
+            enum_values: Some(vec!["latest".into()]),
+            ..Default::default()
+        };
+        let value_string = SchemaObject {
+            instance_type: Some(InstanceType::String.into()),
+            ..Default::default()
+        };
+
+        // The value is parsed from a string, e.g. `"42"` or `"latest"`.
+        SchemaObject {
+            subschemas: Some(Box::new(SubschemaValidation {
+                any_of: Some(vec![
+                    gen.subschema_for::<T>(),
+                    latest.into(),
+                    value_string.into(),
+                ]),
+                ..Default::default()
+            })),
+            ..Default::default()
+        }
+        .into()
+    }
+}
+
 impl<T> Display for OrLatest<T>
 where
     T: Display,
@@ -543,17 +917,19 @@ where
     }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct SlashedSlots {
     pub block_number: u32,
 
+    #[schemars(with = "schema::BitSet")]
     pub current: BitSet,
 
+    #[schemars(with = "schema::BitSet")]
     pub previous: BitSet,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct Stakes {
     pub active_validators: Vec<Validator>,
@@ -563,27 +939,37 @@ pub struct Stakes {
     pub inactive_stakes: Vec<Stake>,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
 pub struct Stake {
+    #[schemars(with = "schema::Address")]
     pub staker_address: Address,
 
+    #[schemars(with = "schema::Coin")]
     pub balance: Coin,
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub retire_time: Option<u32>,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct Validator {
+    #[schemars(with = "schema::ValidatorId")]
     pub id: ValidatorId,
 
+    #[schemars(with = "schema::BlsPublicKey")]
     pub public_key: CompressedPublicKey,
 
+    #[schemars(with = "schema::Coin")]
     pub balance: Coin,
 
+    #[schemars(with = "schema::Address")]
     pub reward_address: Address,
 
+    /// The commission in basis points, if the validator shares its rewards with its stakers.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commission: Option<u16>,
+
     pub stakes: Vec<Stake>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -600,6 +986,7 @@ impl Validator {
             public_key: validator.validator_key.clone(),
             balance: validator.balance,
             reward_address: validator.reward_address.clone(),
+            commission: validator.commission,
             stakes: validator
                 .active_stake_by_address
                 .read()
@@ -623,6 +1010,539 @@ impl Validator {
     }
 }
 
+/// An inactive stake or the deposit of an inactive validator that waits to be withdrawn.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingUnstake {
+    /// The inactive validator whose deposit is pending. Not set for an inactive stake. Deposits
+    /// are listed for the reward address of the validator, which may not be the one who can drop
+    /// it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schemars(with = "Option<schema::ValidatorId>")]
+    pub validator_id: Option<ValidatorId>,
+
+    #[schemars(with = "schema::Coin")]
+    pub balance: Coin,
+
+    pub retire_time: u32,
+
+    /// The first block at which the `Unstake` or `DropValidator` transaction is valid.
+    pub withdrawable_at: u32,
+}
+
+impl From<staking_contract::PendingUnstake> for PendingUnstake {
+    fn from(pending_unstake: staking_contract::PendingUnstake) -> Self {
+        let validator_id = match pending_unstake.kind {
+            staking_contract::PendingUnstakeKind::Stake => None,
+            staking_contract::PendingUnstakeKind::Validator(validator_id) => Some(validator_id),
+        };
+
+        PendingUnstake {
+            validator_id,
+            balance: pending_unstake.balance,
+            retire_time: pending_unstake.retire_time,
+            withdrawable_at: pending_unstake.withdrawable_at,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchRewards {
+    pub batch_number: u32,
+
+    pub block_number: u32,
+
+    #[schemars(with = "schema::Coin")]
+    pub block_reward: Coin,
+
+    #[schemars(with = "schema::Coin")]
+    pub transaction_fees: Coin,
+
+    #[schemars(with = "schema::Coin")]
+    pub slot_reward: Coin,
+
+    #[schemars(with = "schema::Coin")]
+    pub remainder: Coin,
+
+    #[schemars(with = "schema::Coin")]
+    pub burned_reward: Coin,
+
+    pub validators: Vec<ValidatorRewards>,
+}
+
+impl From<BatchRewardReport> for BatchRewards {
+    fn from(report: BatchRewardReport) -> Self {
+        BatchRewards {
+            batch_number: report.batch_number,
+            block_number: report.block_number,
+            block_reward: report.block_reward,
+            transaction_fees: report.transaction_fees,
+            slot_reward: report.slot_reward,
+            remainder: report.remainder,
+            burned_reward: report.burned_reward,
+            validators: report
+                .validators
+                .into_iter()
+                .map(ValidatorRewards::from)
+                .collect(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidatorRewards {
+    #[schemars(with = "schema::ValidatorId")]
+    pub validator_id: ValidatorId,
+
+    #[schemars(with = "schema::Address")]
+    pub reward_address: Address,
+
+    pub first_slot: u16,
+
+    pub num_slots: u16,
+
+    pub lost_reward_slots: Vec<u16>,
+
+    pub disabled_slots: Vec<u16>,
+
+    pub num_eligible_slots: u16,
+
+    #[schemars(with = "schema::Coin")]
+    pub reward: Coin,
+
+    pub accepted: bool,
+
+    pub staker_rewards: Vec<StakerRewards>,
+}
+
+impl From<ValidatorReward> for ValidatorRewards {
+    fn from(reward: ValidatorReward) -> Self {
+        ValidatorRewards {
+            validator_id: reward.validator_id,
+            reward_address: reward.reward_address,
+            first_slot: reward.first_slot,
+            num_slots: reward.num_slots,
+            lost_reward_slots: reward.lost_reward_slots,
+            disabled_slots: reward.disabled_slots,
+            num_eligible_slots: reward.num_eligible_slots,
+            reward: reward.reward,
+            accepted: reward.accepted,
+            staker_rewards: reward
+                .staker_rewards
+                .into_iter()
+                .map(StakerRewards::from)
+                .collect(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct StakerRewards {
+    #[schemars(with = "schema::Address")]
+    pub staker_address: Address,
+
+    #[schemars(with = "schema::Coin")]
+    pub reward: Coin,
+
+    pub accepted: bool,
+}
+
+impl From<StakerReward> for StakerRewards {
+    fn from(reward: StakerReward) -> Self {
+        StakerRewards {
+            staker_address: reward.staker_address,
+            reward: reward.reward,
+            accepted: reward.accepted,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SlashEvent {
+    pub block_number: u32,
+
+    pub epoch_number: u32,
+
+    #[schemars(with = "schema::ValidatorId")]
+    pub validator_id: ValidatorId,
+
+    pub kind: SlashEventKind,
+
+    /// The slashed slot. Not set for unpark events.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slot: Option<u16>,
+
+    /// The block at which the slashable action occurred. Not set for unpark events.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event_block: Option<u32>,
+
+    /// The view number at which the slashable action occurred. Only set for fork proof and view
+    /// change events.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub view_number: Option<u32>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum SlashEventKind {
+    ForkProof,
+    ViewChange,
+    Unpark,
+    /// A fork proof or a view change. The node synced the block from the history, which doesn't
+    /// contain the cause of the slash.
+    Slash,
+}
+
+impl From<nimiq_blockchain_albatross::slash_event::SlashEvent> for SlashEvent {
+    fn from(event: nimiq_blockchain_albatross::slash_event::SlashEvent) -> Self {
+        use nimiq_blockchain_albatross::slash_event::SlashEventKind as Kind;
+
+        let (kind, slot, event_block, view_number) = match event.kind {
+            Kind::ForkProof {
+                slot,
+                event_block,
+                view_number,
+            } => (
+                SlashEventKind::ForkProof,
+                Some(slot),
+                Some(event_block),
+                Some(view_number),
+            ),
+            Kind::ViewChange {
+                slot,
+                event_block,
+                view_number,
+            } => (
+                SlashEventKind::ViewChange,
+                Some(slot),
+                Some(event_block),
+                Some(view_number),
+            ),
+            Kind::Unpark => (SlashEventKind::Unpark, None, None, None),
+            Kind::Slash { slot, event_block } => {
+                (SlashEventKind::Slash, Some(slot), Some(event_block), None)
+            }
+        };
+
+        SlashEvent {
+            block_number: event.block_number,
+            epoch_number: policy::epoch_at(event.block_number),
+            validator_id: event.validator_id,
+            kind,
+            slot,
+            event_block,
+            view_number,
+        }
+    }
+}
+
+/// A page of the slash log.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SlashEventsPage {
+    /// The events of this page, oldest first.
+    pub events: Vec<SlashEvent>,
+
+    /// An opaque cursor that can be passed to the next request to get the next (newer) page. This
+    /// is `None` if there are no more events.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+/// The changes of the staking contract between the states after two blocks.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct StakingDiff {
+    pub from_block: u32,
+
+    pub to_block: u32,
+
+    #[schemars(with = "Vec<schema::ValidatorId>")]
+    pub added_validators: Vec<ValidatorId>,
+
+    #[schemars(with = "Vec<schema::ValidatorId>")]
+    pub removed_validators: Vec<ValidatorId>,
+
+    pub validator_events: Vec<StakingValidatorEvent>,
+
+    pub stake_changes: Vec<StakeChange>,
+
+    /// Validators that were dropped in a block whose receipts were already pruned. The stakes that
+    /// were retired by dropping them are missing from the stake changes.
+    #[schemars(with = "Vec<schema::ValidatorId>")]
+    pub incomplete_drops: Vec<ValidatorId>,
+}
+
+impl From<StakingContractDiff> for StakingDiff {
+    fn from(diff: StakingContractDiff) -> Self {
+        StakingDiff {
+            from_block: diff.from_block,
+            to_block: diff.to_block,
+            added_validators: diff.added_validators,
+            removed_validators: diff.removed_validators,
+            validator_events: diff
+                .validator_events
+                .into_iter()
+                .map(StakingValidatorEvent::from)
+                .collect(),
+            stake_changes: diff
+                .stake_changes
+                .into_iter()
+                .map(StakeChange::from)
+                .collect(),
+            incomplete_drops: diff.incomplete_drops,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct StakingValidatorEvent {
+    pub block_number: u32,
+
+    #[schemars(with = "schema::ValidatorId")]
+    pub validator_id: ValidatorId,
+
+    pub kind: StakingValidatorEventKind,
+
+    /// The deposit of the validator. Only set for created and dropped validators.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schemars(with = "Option<schema::Coin>")]
+    pub deposit: Option<Coin>,
+
+    /// Whether the validator was retired automatically because it was parked. Only set for
+    /// retirements.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub automatically: Option<bool>,
+
+    /// The slashed slot. Only set for slashes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slot: Option<u16>,
+
+    /// The block at which the slashable action occurred. Only set for slashes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event_block: Option<u32>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum StakingValidatorEventKind {
+    Created,
+    Updated,
+    Retired,
+    Reactivated,
+    Dropped,
+    Slashed,
+    Unparked,
+}
+
+impl From<ValidatorEvent> for StakingValidatorEvent {
+    fn from(event: ValidatorEvent) -> Self {
+        let mut result = StakingValidatorEvent {
+            block_number: event.block_number,
+            validator_id: event.validator_id,
+            kind: StakingValidatorEventKind::Updated,
+            deposit: None,
+            automatically: None,
+            slot: None,
+            event_block: None,
+        };
+
+        result.kind = match event.kind {
+            ValidatorEventKind::Created { deposit } => {
+                result.deposit = Some(deposit);
+                StakingValidatorEventKind::Created
+            }
+            ValidatorEventKind::Updated => StakingValidatorEventKind::Updated,
+            ValidatorEventKind::Retired { automatically } => {
+                result.automatically = Some(automatically);
+                StakingValidatorEventKind::Retired
+            }
+            ValidatorEventKind::Reactivated => StakingValidatorEventKind::Reactivated,
+            ValidatorEventKind::Dropped { deposit } => {
+                result.deposit = Some(deposit);
+                StakingValidatorEventKind::Dropped
+            }
+            ValidatorEventKind::Slashed { slot, event_block } => {
+                result.slot = Some(slot);
+                result.event_block = Some(event_block);
+                StakingValidatorEventKind::Slashed
+            }
+            ValidatorEventKind::Unparked => StakingValidatorEventKind::Unparked,
+        };
+
+        result
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct StakeChange {
+    #[schemars(with = "schema::Address")]
+    pub staker_address: Address,
+
+    /// The validator of the active stake. Not set for the inactive stake.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schemars(with = "Option<schema::ValidatorId>")]
+    pub validator_id: Option<ValidatorId>,
+
+    #[schemars(with = "schema::Coin")]
+    pub added: Coin,
+
+    /// The removed stake, including the fees that were paid from it.
+    #[schemars(with = "schema::Coin")]
+    pub removed: Coin,
+}
+
+impl From<staking_diff::StakeChange> for StakeChange {
+    fn from(change: staking_diff::StakeChange) -> Self {
+        StakeChange {
+            staker_address: change.staker_address,
+            validator_id: change.validator_id,
+            added: change.added,
+            removed: change.removed,
+        }
+    }
+}
+
+/// The duties of the validator of this node since it was started.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidatorDuties {
+    /// The slot band of the validator in the current epoch. Not set if the validator isn't active.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slot_band: Option<u16>,
+
+    pub num_slots: u16,
+
+    /// The views in which the validator produces or proposes the next block.
+    pub upcoming_slots: Vec<UpcomingSlot>,
+
+    pub produced_blocks: u64,
+
+    pub missed_blocks: u64,
+
+    pub signed_votes: u64,
+
+    /// The number of votes that are part of a proof in the chain.
+    pub included_votes: u64,
+
+    /// The number of votes that are missing from a proof in the chain.
+    pub missed_votes: u64,
+
+    /// The most recent micro block slots, oldest first.
+    pub recent_blocks: Vec<BlockDuty>,
+
+    /// The most recent votes, oldest first.
+    pub recent_votes: Vec<VoteDuty>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UpcomingSlot {
+    pub block_number: u32,
+
+    pub view_number: u32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockDuty {
+    pub block_number: u32,
+
+    pub view_number: u32,
+
+    /// Whether the validator produced the block or the view was changed away from it.
+    pub produced: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct VoteDuty {
+    pub kind: VoteKind,
+
+    pub block_number: u32,
+
+    /// The Tendermint round, or the new view number for view changes.
+    pub round: u32,
+
+    pub signed: bool,
+
+    /// Whether the vote is part of the proof in the chain. Not set if there is no proof for the
+    /// vote in the chain.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub included: Option<bool>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum VoteKind {
+    ViewChange,
+    Prevote,
+    Precommit,
+}
+
+/// The sync progress of the node.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncStatus {
+    pub stage: SyncStage,
+
+    /// The block number of our head.
+    pub block_number: u32,
+
+    /// The epoch that we are syncing to. Not set if it isn't known yet.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_epoch: Option<u32>,
+
+    /// The block that we are syncing to. Not set if it isn't known yet or if we are synced.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_block: Option<u32>,
+
+    /// The share of the blocks up to the target block that we have, between 0 and 1.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub progress: Option<f64>,
+
+    /// The number of history chunks that we downloaded, out of the `totalChunks` that we know of.
+    pub downloaded_chunks: usize,
+
+    pub total_chunks: usize,
+
+    /// The clusters of peers that we download the blocks from.
+    pub clusters: Vec<SyncCluster>,
+
+    /// The estimated number of seconds until we reach the target block.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub eta: Option<u64>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum SyncStage {
+    History,
+    BatchSets,
+    BlockQueue,
+    Following,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncCluster {
+    /// The epoch of the first block of the cluster.
+    pub first_epoch: u32,
+
+    pub num_blocks: usize,
+
+    pub num_peers: usize,
+
+    /// Whether the cluster consists of a checkpoint block instead of election blocks.
+    pub checkpoint: bool,
+}
+
 #[derive(Copy, Clone, Debug, SerializeDisplay, DeserializeFromStr)]
 pub enum ValidityStartHeight {
     Absolute(u32),
@@ -0,0 +1,15 @@
+use async_trait::async_trait;
+
+use crate::types::ValidatorDuties;
+
+#[nimiq_openrpc_derive::openrpc]
+#[cfg_attr(
+    feature = "proxy",
+    nimiq_jsonrpc_derive::proxy(name = "ValidatorProxy", rename_all = "camelCase")
+)]
+#[async_trait]
+pub trait ValidatorInterface {
+    type Error;
+
+    async fn get_validator_duties(&mut self) -> Result<ValidatorDuties, Self::Error>;
+}
@@ -1,22 +1,32 @@
 use async_trait::async_trait;
 
+use schemars::JsonSchema;
+
 use nimiq_keys::{Address, PrivateKey, PublicKey, Signature};
 
-#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+use crate::schema;
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ReturnSignature {
+    #[schemars(with = "schema::PublicKey")]
     pub public_key: PublicKey,
+    #[schemars(with = "schema::Signature")]
     pub signature: Signature,
 }
 
-#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ReturnAccount {
+    #[schemars(with = "schema::Address")]
     pub address: Address,
+    #[schemars(with = "schema::PublicKey")]
     pub public_key: PublicKey,
+    #[schemars(with = "schema::PrivateKey")]
     pub private_key: PrivateKey,
 }
 
+#[nimiq_openrpc_derive::openrpc]
 #[cfg_attr(
     feature = "proxy",
     nimiq_jsonrpc_derive::proxy(name = "WalletProxy", rename_all = "camelCase")
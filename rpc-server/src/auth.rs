@@ -0,0 +1,450 @@
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use nimiq_utils::rate_limit::RateLimit;
+
+/// A group of RPC methods that can be granted to a role.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MethodGroup {
+    /// Read-only access to the blockchain and the mempool, including subscriptions.
+    BlockchainRead,
+    /// Submitting signed transactions to the mempool.
+    MempoolSubmit,
+    /// Managing the wallets of the node and sending transactions signed by them.
+    Wallet,
+    /// Administration of the node and of its validator.
+    ValidatorAdmin,
+}
+
+impl MethodGroup {
+    pub const ALL: [MethodGroup; 4] = [
+        MethodGroup::BlockchainRead,
+        MethodGroup::MempoolSubmit,
+        MethodGroup::Wallet,
+        MethodGroup::ValidatorAdmin,
+    ];
+
+    /// Returns the names of the RPC methods in this group.
+    pub fn methods(self) -> &'static [&'static str] {
+        match self {
+            MethodGroup::BlockchainRead => &[
+                "blockNumber",
+                "epochNumber",
+                "batchNumber",
+                "blockByHash",
+                "blockByNumber",
+                "getSlotAt",
+                "slashedSlots",
+                "getRawTransactionInfo",
+                "getTransactionByHash",
+                "getTransactionReceipt",
+                "getTransactionsByAddress",
+                "listStakes",
+                "getPendingUnstakes",
+                "getStakingDiff",
+                "getSlashEvents",
+                "getBatchRewards",
+                "headSubscribe",
+                "getAccount",
+                "getTransaction",
+                "mempoolContent",
+                "mempool",
+                "getMempoolTransaction",
+                "getTransactionsByAddresses",
+                "getMempoolPolicy",
+                "subscribe",
+                "unsubscribe",
+                "syncStatus",
+                "isConsensusEstablished",
+                "getRecentTransactionsByAddress",
+                "watchAddress",
+                "unwatchAddress",
+                "rpc.discover",
+            ],
+            MethodGroup::MempoolSubmit => &["sendRawTransaction"],
+            MethodGroup::Wallet => &[
+                "importRawKey",
+                "listAccounts",
+                "lockAccount",
+                "createAccount",
+                "unlockAccount",
+                "sign",
+                "verifySignature",
+                "createBasicTransaction",
+                "sendBasicTransaction",
+                "createStakeTransaction",
+                "sendStakeTransaction",
+                "createRededicateTransaction",
+                "sendRededicateTransaction",
+                "createRetireTransaction",
+                "sendRetireTransaction",
+                "createReactivateTransaction",
+                "sendReactivateTransaction",
+                "createUnstakeTransaction",
+                "sendUnstakeTransaction",
+            ],
+            MethodGroup::ValidatorAdmin => &[
+                "setMempoolPolicy",
+                "createNewValidatorTransaction",
+                "sendNewValidatorTransaction",
+                "createUpdateValidatorTransaction",
+                "sendUpdateValidatorTransaction",
+                "createRetireValidatorTransaction",
+                "sendRetireValidatorTransaction",
+                "createReactivateValidatorTransaction",
+                "sendReactivateValidatorTransaction",
+                "createDropValidatorTransaction",
+                "sendDropValidatorTransaction",
+                "createUnparkValidatorTransaction",
+                "sendUnparkValidatorTransaction",
+                "getValidatorDuties",
+            ],
+        }
+    }
+
+    pub fn contains(self, method: &str) -> bool {
+        self.methods().contains(&method)
+    }
+}
+
+impl FromStr for MethodGroup {
+    type Err = AuthError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "blockchain-read" => Ok(MethodGroup::BlockchainRead),
+            "mempool-submit" => Ok(MethodGroup::MempoolSubmit),
+            "wallet" => Ok(MethodGroup::Wallet),
+            "validator-admin" => Ok(MethodGroup::ValidatorAdmin),
+            _ => Err(AuthError::UnknownMethodGroup(s.to_string())),
+        }
+    }
+}
+
+/// A user of the RPC server.
+#[derive(Debug, Clone)]
+pub struct RpcUser {
+    pub name: String,
+    /// The password for HTTP basic auth.
+    pub password: Option<String>,
+    /// The API token for bearer auth.
+    pub token: Option<String>,
+    /// The method groups granted by the role of the user.
+    pub groups: Vec<MethodGroup>,
+    /// The maximum number of requests per minute.
+    pub rate_limit: Option<usize>,
+}
+
+impl RpcUser {
+    pub fn is_allowed(&self, method: &str) -> bool {
+        self.groups.iter().any(|group| group.contains(method))
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum AuthError {
+    #[error("Invalid credentials")]
+    InvalidCredentials,
+
+    #[error("User {0} is not allowed to call {1}")]
+    MethodNotAllowed(String, String),
+
+    #[error("Rate limit exceeded for user {0}")]
+    RateLimitExceeded(String),
+
+    #[error("Unknown method group: {0}")]
+    UnknownMethodGroup(String),
+
+    #[error("Duplicate RPC user: {0}")]
+    DuplicateUser(String),
+
+    #[error("RPC user {0} has the same token as another user")]
+    DuplicateToken(String),
+}
+
+/// Authenticates RPC users and checks whether they are allowed to call a method.
+pub struct Authorizer {
+    users: Vec<RpcUser>,
+    rate_limits: Mutex<HashMap<String, RateLimit>>,
+}
+
+impl Authorizer {
+    /// Creates an authorizer for the given users. The names and tokens of the users must be unique,
+    /// since they identify the user, e.g. for its rate limit.
+    pub fn new(users: Vec<RpcUser>) -> Result<Self, AuthError> {
+        let mut names = HashSet::new();
+        let mut tokens = HashSet::new();
+        for user in &users {
+            if !names.insert(user.name.as_str()) {
+                return Err(AuthError::DuplicateUser(user.name.clone()));
+            }
+            if let Some(token) = &user.token {
+                if !tokens.insert(token.as_str()) {
+                    return Err(AuthError::DuplicateToken(user.name.clone()));
+                }
+            }
+        }
+
+        let rate_limits = users
+            .iter()
+            .filter_map(|user| {
+                user.rate_limit
+                    .map(|limit| (user.name.clone(), RateLimit::new_per_minute(limit)))
+            })
+            .collect();
+
+        Ok(Authorizer {
+            users,
+            rate_limits: Mutex::new(rate_limits),
+        })
+    }
+
+    pub fn users(&self) -> &[RpcUser] {
+        &self.users
+    }
+
+    /// Returns the user with the given basic auth credentials.
+    pub fn authenticate_basic(
+        &self,
+        username: &str,
+        password: &str,
+    ) -> Result<&RpcUser, AuthError> {
+        self.users
+            .iter()
+            .find(|user| {
+                user.name == username
+                    && user
+                        .password
+                        .as_ref()
+                        .map_or(false, |expected| constant_time_eq(expected, password))
+            })
+            .ok_or(AuthError::InvalidCredentials)
+    }
+
+    /// Returns the user with the given API token.
+    pub fn authenticate_token(&self, token: &str) -> Result<&RpcUser, AuthError> {
+        self.users
+            .iter()
+            .find(|user| {
+                user.token
+                    .as_ref()
+                    .map_or(false, |expected| constant_time_eq(expected, token))
+            })
+            .ok_or(AuthError::InvalidCredentials)
+    }
+
+    /// Returns the user that authenticates with the given value of the `Authorization` header,
+    /// which is either basic auth or an API token as bearer auth.
+    pub fn authenticate(&self, authorization: &str) -> Result<&RpcUser, AuthError> {
+        if let Some(token) = authorization.strip_prefix("Bearer ") {
+            return self.authenticate_token(token.trim());
+        }
+
+        let (username, password) =
+            parse_basic_auth(authorization).ok_or(AuthError::InvalidCredentials)?;
+        self.authenticate_basic(&username, &password)
+    }
+
+    /// Checks whether the user is allowed to call the method and counts the request against the
+    /// rate limit of the user.
+    pub fn authorize(&self, user: &RpcUser, method: &str) -> Result<(), AuthError> {
+        if !user.is_allowed(method) {
+            return Err(AuthError::MethodNotAllowed(
+                user.name.clone(),
+                method.to_string(),
+            ));
+        }
+
+        if let Some(rate_limit) = self.rate_limits.lock().get_mut(&user.name) {
+            if !rate_limit.note_single() {
+                return Err(AuthError::RateLimitExceeded(user.name.clone()));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Compares two secrets in time that only depends on their length, so that the comparison doesn't
+/// reveal how many leading bytes of a guess are correct.
+fn constant_time_eq(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Parses the username and password from the value of a basic auth `Authorization` header.
+pub(crate) fn parse_basic_auth(authorization: &str) -> Option<(String, String)> {
+    let encoded = authorization.strip_prefix("Basic ")?;
+    let decoded = String::from_utf8(base64::decode(encoded.trim()).ok()?).ok()?;
+    let mut parts = decoded.splitn(2, ':');
+    let username = parts.next()?.to_string();
+    let password = parts.next()?.to_string();
+    Some((username, password))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user(name: &str, groups: Vec<MethodGroup>, rate_limit: Option<usize>) -> RpcUser {
+        RpcUser {
+            name: name.to_string(),
+            password: Some(format!("{}-password", name)),
+            token: Some(format!("{}-token", name)),
+            groups,
+            rate_limit,
+        }
+    }
+
+    #[test]
+    fn it_authenticates_users() {
+        let authorizer = Authorizer::new(vec![
+            user("reader", vec![MethodGroup::BlockchainRead], None),
+            user("admin", MethodGroup::ALL.to_vec(), None),
+        ])
+        .unwrap();
+
+        assert_eq!(
+            authorizer
+                .authenticate_basic("reader", "reader-password")
+                .unwrap()
+                .name,
+            "reader"
+        );
+        assert_eq!(
+            authorizer.authenticate_token("admin-token").unwrap().name,
+            "admin"
+        );
+        assert_eq!(
+            authorizer
+                .authenticate_basic("reader", "admin-password")
+                .unwrap_err(),
+            AuthError::InvalidCredentials
+        );
+        assert_eq!(
+            authorizer.authenticate_token("unknown").unwrap_err(),
+            AuthError::InvalidCredentials
+        );
+    }
+
+    #[test]
+    fn it_authenticates_authorization_headers() {
+        let authorizer = Authorizer::new(vec![
+            user("reader", vec![MethodGroup::BlockchainRead], None),
+            user("admin", MethodGroup::ALL.to_vec(), None),
+        ])
+        .unwrap();
+
+        let basic = format!("Basic {}", base64::encode("reader:reader-password"));
+        assert_eq!(authorizer.authenticate(&basic).unwrap().name, "reader");
+        assert_eq!(
+            authorizer.authenticate("Bearer admin-token").unwrap().name,
+            "admin"
+        );
+
+        let wrong_password = format!("Basic {}", base64::encode("reader:admin-password"));
+        assert_eq!(
+            authorizer.authenticate(&wrong_password).unwrap_err(),
+            AuthError::InvalidCredentials
+        );
+        assert_eq!(
+            authorizer.authenticate("Bearer unknown").unwrap_err(),
+            AuthError::InvalidCredentials
+        );
+        assert_eq!(
+            authorizer.authenticate("Digest admin").unwrap_err(),
+            AuthError::InvalidCredentials
+        );
+    }
+
+    #[test]
+    fn it_rejects_duplicate_users() {
+        assert_eq!(
+            Authorizer::new(vec![
+                user("reader", vec![MethodGroup::BlockchainRead], None),
+                user("reader", MethodGroup::ALL.to_vec(), None),
+            ])
+            .err(),
+            Some(AuthError::DuplicateUser("reader".to_string()))
+        );
+
+        let mut admin = user("admin", MethodGroup::ALL.to_vec(), None);
+        admin.token = Some("reader-token".to_string());
+        assert_eq!(
+            Authorizer::new(vec![
+                user("reader", vec![MethodGroup::BlockchainRead], None),
+                admin,
+            ])
+            .err(),
+            Some(AuthError::DuplicateToken("admin".to_string()))
+        );
+    }
+
+    #[test]
+    fn it_compares_secrets() {
+        assert!(constant_time_eq("secret", "secret"));
+        assert!(!constant_time_eq("secret", "secreT"));
+        assert!(!constant_time_eq("secret", "secret2"));
+        assert!(!constant_time_eq("", "secret"));
+    }
+
+    #[test]
+    fn it_parses_basic_auth() {
+        let header = format!("Basic {}", base64::encode("alice:secret:with:colons"));
+        assert_eq!(
+            parse_basic_auth(&header),
+            Some(("alice".to_string(), "secret:with:colons".to_string()))
+        );
+        assert_eq!(parse_basic_auth("Bearer token"), None);
+        assert_eq!(parse_basic_auth("Basic not-base64!"), None);
+    }
+
+    #[test]
+    fn it_checks_method_groups() {
+        let reader = user("reader", vec![MethodGroup::BlockchainRead], None);
+        let authorizer = Authorizer::new(vec![reader.clone()]).unwrap();
+
+        assert!(authorizer.authorize(&reader, "blockNumber").is_ok());
+        assert_eq!(
+            authorizer.authorize(&reader, "sendRawTransaction"),
+            Err(AuthError::MethodNotAllowed(
+                "reader".to_string(),
+                "sendRawTransaction".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn it_groups_every_method() {
+        for method in nimiq_rpc_interface::openrpc::document().methods {
+            assert!(
+                MethodGroup::ALL
+                    .iter()
+                    .any(|group| group.contains(&method.name)),
+                "Method {} is not in any group",
+                method.name
+            );
+        }
+    }
+
+    #[test]
+    fn it_limits_the_request_rate() {
+        let reader = user("reader", vec![MethodGroup::BlockchainRead], Some(2));
+        let authorizer = Authorizer::new(vec![reader.clone()]).unwrap();
+
+        assert!(authorizer.authorize(&reader, "blockNumber").is_ok());
+        assert!(authorizer.authorize(&reader, "blockNumber").is_ok());
+        assert_eq!(
+            authorizer.authorize(&reader, "blockNumber"),
+            Err(AuthError::RateLimitExceeded("reader".to_string()))
+        );
+    }
+}
@@ -3,18 +3,44 @@ use std::sync::Arc;
 use async_trait::async_trait;
 use futures::stream::{BoxStream, StreamExt};
 
-use nimiq_account::Account;
-use nimiq_blockchain_albatross::{AbstractBlockchain, Blockchain, BlockchainEvent};
-use nimiq_hash::Blake2bHash;
+use beserial::{Deserialize, Serialize};
+use nimiq_account::staking_contract::{StakingContractEntry, StakingContractKeys};
+use nimiq_account::{Account, AccountTransactionInteraction, StakingContract};
+use nimiq_blockchain_albatross::{
+    slash_event, AbstractBlockchain, AddressHistoryPosition, Blockchain, BlockchainEvent,
+    ExtTxData, ExtendedTransaction,
+};
+use nimiq_hash::{Blake2bHash, Hash};
 use nimiq_keys::Address;
+use nimiq_primitives::account::ValidatorId;
 use nimiq_primitives::policy;
 use nimiq_rpc_interface::{
     blockchain::BlockchainInterface,
-    types::{Block, OrLatest, SlashedSlots, Slot, Stake, Stakes, Transaction, Validator},
+    types::{
+        BatchRewards, Block, MempoolTransaction, OrLatest, PendingUnstake, RawTransactionInfo,
+        SlashEvent, SlashEventsPage, SlashedSlots, Slot, Stake, Stakes, StakingDiff, Transaction,
+        TransactionReceipt, TransactionsPage, Validator,
+    },
 };
+use nimiq_transaction::TransactionFlags;
 
 use crate::error::Error;
 
+/// The number of transactions returned by `get_transactions_by_address` if no maximum is given.
+const DEFAULT_TRANSACTIONS_PER_PAGE: u16 = 100;
+
+/// The maximum number of transactions returned by `get_transactions_by_address`.
+const MAX_TRANSACTIONS_PER_PAGE: u16 = 500;
+
+/// The number of events returned by `get_slash_events` if no maximum is given.
+const DEFAULT_SLASH_EVENTS_PER_PAGE: u16 = 100;
+
+/// The maximum number of events returned by `get_slash_events`.
+const MAX_SLASH_EVENTS_PER_PAGE: u16 = 500;
+
+/// The maximum number of blocks that `get_staking_diff` replays in one call.
+const MAX_STAKING_DIFF_BLOCKS: u32 = 4 * policy::EPOCH_LENGTH;
+
 pub struct BlockchainDispatcher {
     blockchain: Arc<Blockchain>,
 }
@@ -23,6 +49,83 @@ impl BlockchainDispatcher {
     pub fn new(blockchain: Arc<Blockchain>) -> Self {
         Self { blockchain }
     }
+
+    /// Returns the extended transaction of the basic transaction with the given hash.
+    fn get_extended_transaction(&self, hash: &Blake2bHash) -> Result<ExtendedTransaction, Error> {
+        // Get all the extended transactions that correspond to this hash.
+        let mut extended_tx_vec = self.blockchain.history_store.get_ext_tx_by_hash(hash, None);
+
+        // There should never be more than one extended transaction with the same hash.
+        if extended_tx_vec.len() > 1 {
+            return Err(Error::MultipleTransactionsFound(hash.clone()));
+        }
+
+        // Unpack the transaction or raise an error.
+        match extended_tx_vec.pop() {
+            Some(extended_tx) if !extended_tx.is_inherent() => Ok(extended_tx),
+            _ => Err(Error::TransactionNotFound(hash.clone())),
+        }
+    }
+
+    /// Checks whether the given transaction could be included in the next block. Returns the reason
+    /// if it can't.
+    fn check_transaction(
+        &self,
+        transaction: &nimiq_transaction::Transaction,
+    ) -> Result<(), String> {
+        // Intrinsic transaction verification.
+        transaction
+            .verify(self.blockchain.network_id)
+            .map_err(|e| e.to_string())?;
+
+        // Check if transaction is valid at the next block height.
+        let block_height = self.blockchain.block_number() + 1;
+        if !transaction.is_valid_at(block_height) {
+            return Err(format!(
+                "Transaction is not valid at block height {}",
+                block_height
+            ));
+        }
+
+        let timestamp = self.blockchain.timestamp();
+
+        // Check the recipient account type. A contract creation must change the account type and
+        // vice versa.
+        let mut recipient_account = self.blockchain.get_account(&transaction.recipient);
+        let is_contract_creation = transaction
+            .flags
+            .contains(TransactionFlags::CONTRACT_CREATION);
+        let is_type_change = recipient_account.account_type() != transaction.recipient_type;
+        if is_contract_creation != is_type_change {
+            return Err("Recipient account type mismatch".to_string());
+        }
+
+        // Check the transaction against a copy of the recipient account.
+        recipient_account
+            .commit_incoming_transaction(transaction, block_height, timestamp)
+            .map_err(|e| e.to_string())?;
+        if is_contract_creation {
+            Account::new_contract(
+                transaction.recipient_type,
+                recipient_account.balance(),
+                transaction,
+                block_height,
+                timestamp,
+            )
+            .map_err(|e| e.to_string())?;
+        }
+
+        // Check the transaction against a copy of the sender account.
+        let mut sender_account = self.blockchain.get_account(&transaction.sender);
+        if sender_account.account_type() != transaction.sender_type {
+            return Err("Sender account type mismatch".to_string());
+        }
+        sender_account
+            .commit_outgoing_transaction(transaction, block_height, timestamp)
+            .map_err(|e| e.to_string())?;
+
+        Ok(())
+    }
 }
 
 #[nimiq_jsonrpc_derive::service(rename_all = "camelCase")]
@@ -105,7 +208,10 @@ impl BlockchainInterface for BlockchainDispatcher {
     async fn slashed_slots(&mut self) -> Result<SlashedSlots, Error> {
         // FIXME: Race condition
         let block_number = self.blockchain.block_number();
-        let staking_contract = self.blockchain.get_staking_contract();
+        // The slashed slots are part of the global state of the contract.
+        let staking_contract = self
+            .blockchain
+            .get_partial_staking_contract(&StakingContractKeys::default());
 
         let current_slashed_set =
             staking_contract.current_lost_rewards() & staking_contract.current_disabled_slots();
@@ -120,29 +226,35 @@ impl BlockchainInterface for BlockchainDispatcher {
         })
     }
 
-    async fn get_raw_transaction_info(&mut self, _raw_tx: String) -> Result<(), Error> {
-        Err(Error::NotImplemented)
-    }
-
-    async fn get_transaction_by_hash(&mut self, hash: Blake2bHash) -> Result<Transaction, Error> {
-        // TODO: Check mempool for the transaction, too
+    async fn get_raw_transaction_info(
+        &mut self,
+        raw_tx: String,
+    ) -> Result<RawTransactionInfo, Error> {
+        let transaction: nimiq_transaction::Transaction =
+            Deserialize::deserialize_from_vec(&hex::decode(&raw_tx)?)?;
 
-        // Get all the extended transactions that correspond to this hash.
-        let mut extended_tx_vec = self
+        let is_known = self
             .blockchain
-            .history_store
-            .get_ext_tx_by_hash(&hash, None);
-
-        // If we got more than 2 extended transactions, then we panic. This shouldn't happen.
-        assert!(extended_tx_vec.len() < 2);
+            .contains_tx_in_validity_window(&transaction.hash());
 
-        // Unpack the transaction or raise an error.
-        let extended_tx = if extended_tx_vec.is_empty() {
-            return Err(Error::TransactionNotFound(hash));
+        let error = if is_known {
+            Some("Transaction is already included in the blockchain".to_string())
         } else {
-            extended_tx_vec.pop().unwrap()
+            self.check_transaction(&transaction).err()
         };
 
+        Ok(RawTransactionInfo {
+            transaction: MempoolTransaction::from_transaction(&transaction),
+            is_valid: error.is_none(),
+            is_known,
+            error,
+        })
+    }
+
+    async fn get_transaction_by_hash(&mut self, hash: Blake2bHash) -> Result<Transaction, Error> {
+        // TODO: Check mempool for the transaction, too
+        let extended_tx = self.get_extended_transaction(&hash)?;
+
         let transaction = extended_tx.unwrap_basic(); // Because we found the extended_tx above, this cannot be None
 
         Ok(Transaction::from_blockchain(
@@ -153,8 +265,88 @@ impl BlockchainInterface for BlockchainDispatcher {
         ))
     }
 
-    async fn get_transaction_receipt(&mut self, _hash: Blake2bHash) -> Result<(), Error> {
-        Err(Error::NotImplemented)
+    async fn get_transaction_receipt(
+        &mut self,
+        hash: Blake2bHash,
+    ) -> Result<TransactionReceipt, Error> {
+        let extended_tx = self.get_extended_transaction(&hash)?;
+
+        let block_hash = self
+            .blockchain
+            .chain_store
+            .get_block_at(extended_tx.block_number, false, None)
+            .ok_or_else(|| Error::BlockNotFound(extended_tx.block_number.into()))?
+            .hash();
+
+        // A transaction is final once a macro block at or after its block has been produced.
+        let macro_head_number = self.blockchain.macro_head().header.block_number;
+
+        Ok(TransactionReceipt {
+            transaction_hash: hash,
+            block_hash,
+            block_number: extended_tx.block_number,
+            timestamp: extended_tx.block_time,
+            confirmations: self
+                .blockchain
+                .block_number()
+                .saturating_sub(extended_tx.block_number),
+            is_finalized: extended_tx.block_number <= macro_head_number,
+        })
+    }
+
+    async fn get_transactions_by_address(
+        &mut self,
+        address: Address,
+        max: Option<u16>,
+        cursor: Option<String>,
+    ) -> Result<TransactionsPage, Error> {
+        let max = max
+            .unwrap_or(DEFAULT_TRANSACTIONS_PER_PAGE)
+            .min(MAX_TRANSACTIONS_PER_PAGE) as usize;
+
+        // The cursor is the hex-encoded position of the last transaction of the previous page.
+        let start_after: Option<AddressHistoryPosition> = match cursor {
+            Some(cursor) => Some(Deserialize::deserialize_from_vec(&hex::decode(&cursor)?)?),
+            None => None,
+        };
+
+        let ext_txs = self.blockchain.history_store.get_ext_tx_by_address(
+            &address,
+            max,
+            start_after.as_ref(),
+            None,
+        );
+
+        // If we got a full page, there might be more transactions.
+        let next_cursor = if ext_txs.len() == max {
+            ext_txs
+                .last()
+                .map(|(position, _)| hex::encode(position.serialize_to_vec()))
+        } else {
+            None
+        };
+
+        let head_height = self.blockchain.block_number();
+
+        // The address index only contains basic transactions, so every page is full unless it is the
+        // last one.
+        let transactions = ext_txs
+            .into_iter()
+            .filter_map(|(_, ext_tx)| match ext_tx.data {
+                ExtTxData::Basic(tx) => Some(Transaction::from_blockchain(
+                    tx,
+                    ext_tx.block_number,
+                    ext_tx.block_time,
+                    head_height,
+                )),
+                ExtTxData::Inherent(_) => None,
+            })
+            .collect();
+
+        Ok(TransactionsPage {
+            transactions,
+            next_cursor,
+        })
     }
 
     async fn list_stakes(&mut self) -> Result<Stakes, Error> {
@@ -189,6 +381,116 @@ impl BlockchainInterface for BlockchainDispatcher {
         })
     }
 
+    async fn get_slash_events(
+        &mut self,
+        validator_id: Option<ValidatorId>,
+        epoch_number: Option<u32>,
+        max: Option<u16>,
+        cursor: Option<String>,
+    ) -> Result<SlashEventsPage, Error> {
+        let max = max
+            .unwrap_or(DEFAULT_SLASH_EVENTS_PER_PAGE)
+            .min(MAX_SLASH_EVENTS_PER_PAGE) as usize;
+
+        // The cursor is the hex-encoded last event of the previous page.
+        let start_after: Option<slash_event::SlashEvent> = match cursor {
+            Some(cursor) => Some(Deserialize::deserialize_from_vec(&hex::decode(&cursor)?)?),
+            None => None,
+        };
+
+        let events = self.blockchain.get_slash_events(
+            epoch_number,
+            validator_id.as_ref(),
+            max,
+            start_after.as_ref(),
+        );
+
+        // If we got a full page, there might be more events.
+        let next_cursor = if events.len() == max {
+            events
+                .last()
+                .map(|event| hex::encode(event.serialize_to_vec()))
+        } else {
+            None
+        };
+
+        Ok(SlashEventsPage {
+            events: events.into_iter().map(SlashEvent::from).collect(),
+            next_cursor,
+        })
+    }
+
+    async fn get_staking_diff(
+        &mut self,
+        from_block: u32,
+        to_block: u32,
+    ) -> Result<StakingDiff, Error> {
+        if to_block.saturating_sub(from_block) > MAX_STAKING_DIFF_BLOCKS {
+            return Err(Error::BlockRangeTooLong(
+                from_block,
+                to_block,
+                MAX_STAKING_DIFF_BLOCKS,
+            ));
+        }
+
+        // Every block in the range is replayed from the history store, so this runs on a thread
+        // for blocking tasks.
+        let blockchain = Arc::clone(&self.blockchain);
+        let diff = tokio::task::spawn_blocking(move || {
+            blockchain.get_staking_contract_diff(from_block, to_block)
+        })
+        .await??;
+
+        Ok(StakingDiff::from(diff))
+    }
+
+    async fn get_pending_unstakes(
+        &mut self,
+        address: Address,
+    ) -> Result<Vec<PendingUnstake>, Error> {
+        // Only load the inactive stake of the address and the inactive validators that it receives
+        // the rewards of, with their stakes, instead of the whole contract.
+        let mut keys = StakingContractKeys::default();
+        keys.inactive_stakes.insert(address.clone());
+        for entry in self
+            .blockchain
+            .get_staking_entries_with_prefix(&[StakingContract::VALIDATOR_PREFIX])
+        {
+            if let StakingContractEntry::Validator {
+                validator,
+                retire_time: Some(_),
+            } = entry
+            {
+                if validator.reward_address == address {
+                    keys.validators.insert(validator.id.clone(), true);
+                }
+            }
+        }
+
+        Ok(self
+            .blockchain
+            .get_partial_staking_contract(&keys)
+            .get_pending_unstakes(&address)
+            .into_iter()
+            .map(PendingUnstake::from)
+            .collect())
+    }
+
+    async fn get_batch_rewards(&mut self, batch_number: u32) -> Result<BatchRewards, Error> {
+        if let Some(report) = self.blockchain.get_reward_report(batch_number) {
+            return Ok(BatchRewards::from(report));
+        }
+
+        // The rewards of a batch are distributed by the macro block of the next batch. If we have
+        // that block, we synced it without creating the report.
+        let macro_head_number = self.blockchain.macro_head().header.block_number;
+        if batch_number > 0 && batch_number < policy::batch_at(macro_head_number) {
+            Err(Error::RewardReportUnavailable(batch_number))
+        } else {
+            Err(Error::RewardReportNotFound(batch_number))
+        }
+    }
+
     #[stream]
     async fn head_subscribe(&mut self) -> Result<BoxStream<'static, Blake2bHash>, Error> {
         Ok(self
@@ -211,3 +513,126 @@ impl BlockchainInterface for BlockchainDispatcher {
         Ok(self.blockchain.get_account(&account))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use nimiq_account::{Inherent, InherentType};
+    use nimiq_database::{volatile::VolatileEnvironment, WriteTransaction};
+    use nimiq_keys::{KeyPair, SecureGenerate};
+    use nimiq_primitives::{account::AccountType, coin::Coin, networks::NetworkId};
+    use nimiq_transaction::SignatureProof;
+
+    use super::*;
+
+    fn dispatcher(funded: &Address) -> BlockchainDispatcher {
+        let env = VolatileEnvironment::new(Blockchain::NUM_DATABASES).unwrap();
+        let blockchain = Arc::new(Blockchain::new(env.clone(), NetworkId::UnitAlbatross).unwrap());
+
+        let reward = Inherent {
+            ty: InherentType::Reward,
+            target: funded.clone(),
+            value: Coin::from_u64_unchecked(10000),
+            data: vec![],
+        };
+        let mut txn = WriteTransaction::new(&env);
+        blockchain
+            .state()
+            .accounts
+            .commit(&mut txn, &[], &[reward], 0, 0)
+            .unwrap();
+        txn.commit();
+
+        BlockchainDispatcher::new(blockchain)
+    }
+
+    fn sign(keypair: &KeyPair, transaction: &mut nimiq_transaction::Transaction) {
+        let signature = keypair.sign(&transaction.serialize_content());
+        transaction.proof = SignatureProof::from(keypair.public, signature).serialize_to_vec();
+    }
+
+    #[test]
+    fn it_checks_the_sender_and_recipient_accounts() {
+        let keypair = KeyPair::generate_default_csprng();
+        let sender = Address::from(&keypair.public);
+        let recipient = Address::from([2u8; Address::SIZE]);
+        let dispatcher = dispatcher(&sender);
+
+        let mut transaction = nimiq_transaction::Transaction::new_basic(
+            sender.clone(),
+            recipient.clone(),
+            Coin::from_u64_unchecked(10),
+            Coin::ZERO,
+            1,
+            NetworkId::UnitAlbatross,
+        );
+        sign(&keypair, &mut transaction);
+        assert_eq!(dispatcher.check_transaction(&transaction), Ok(()));
+
+        // The sender can't afford the transaction.
+        let mut transaction = nimiq_transaction::Transaction::new_basic(
+            sender.clone(),
+            recipient.clone(),
+            Coin::from_u64_unchecked(20000),
+            Coin::ZERO,
+            1,
+            NetworkId::UnitAlbatross,
+        );
+        sign(&keypair, &mut transaction);
+        assert!(dispatcher.check_transaction(&transaction).is_err());
+
+        // The recipient is a basic account, but the transaction expects a contract.
+        let mut transaction = nimiq_transaction::Transaction::new_basic(
+            sender,
+            recipient,
+            Coin::from_u64_unchecked(10),
+            Coin::ZERO,
+            1,
+            NetworkId::UnitAlbatross,
+        );
+        transaction.recipient_type = AccountType::Vesting;
+        sign(&keypair, &mut transaction);
+        assert_eq!(
+            dispatcher.check_transaction(&transaction),
+            Err("Recipient account type mismatch".to_string())
+        );
+    }
+
+    #[test]
+    fn it_checks_contract_creations() {
+        let keypair = KeyPair::generate_default_csprng();
+        let sender = Address::from(&keypair.public);
+        let dispatcher = dispatcher(&sender);
+
+        // The contract data is not a valid vesting contract.
+        let mut transaction = nimiq_transaction::Transaction::new_contract_creation(
+            vec![1, 2, 3],
+            sender,
+            AccountType::Basic,
+            AccountType::Vesting,
+            Coin::from_u64_unchecked(10),
+            Coin::ZERO,
+            1,
+            NetworkId::UnitAlbatross,
+        );
+        sign(&keypair, &mut transaction);
+        assert!(dispatcher.check_transaction(&transaction).is_err());
+    }
+
+    #[test]
+    fn it_rejects_transactions_that_are_not_yet_valid() {
+        let keypair = KeyPair::generate_default_csprng();
+        let sender = Address::from(&keypair.public);
+        let dispatcher = dispatcher(&sender);
+
+        let mut transaction = nimiq_transaction::Transaction::new_basic(
+            sender,
+            Address::from([2u8; Address::SIZE]),
+            Coin::from_u64_unchecked(10),
+            Coin::ZERO,
+            100,
+            NetworkId::UnitAlbatross,
+        );
+        sign(&keypair, &mut transaction);
+        assert!(dispatcher.check_transaction(&transaction).is_err());
+    }
+}
@@ -5,6 +5,7 @@ use parking_lot::RwLock;
 
 use beserial::{Deserialize, Serialize};
 use nimiq_bls::{KeyPair as BlsKeyPair, SecretKey as BlsSecretKey};
+use nimiq_consensus_albatross::sync::status;
 use nimiq_consensus_albatross::ConsensusProxy;
 use nimiq_hash::{Blake2bHash, Hash};
 use nimiq_keys::{Address, KeyPair};
@@ -14,7 +15,10 @@ use nimiq_primitives::{account::ValidatorId, coin::Coin, networks::NetworkId};
 use nimiq_transaction::Transaction;
 use nimiq_transaction_builder::TransactionBuilder;
 
-use nimiq_rpc_interface::{consensus::ConsensusInterface, types::ValidityStartHeight};
+use nimiq_rpc_interface::{
+    consensus::ConsensusInterface,
+    types::{SyncCluster, SyncStage, SyncStatus, ValidityStartHeight},
+};
 
 use crate::{error::Error, wallets::UnlockedWallets};
 use nimiq_blockchain_albatross::AbstractBlockchain;
@@ -75,6 +79,38 @@ fn transaction_to_hex_string(transaction: &Transaction) -> String {
 impl ConsensusInterface for ConsensusDispatcher {
     type Error = Error;
 
+    async fn sync_status(&mut self) -> Result<SyncStatus, Error> {
+        let status = self.consensus.sync_status();
+
+        let stage = match status.stage {
+            status::SyncStage::History => SyncStage::History,
+            status::SyncStage::BatchSets => SyncStage::BatchSets,
+            status::SyncStage::BlockQueue => SyncStage::BlockQueue,
+            status::SyncStage::Following => SyncStage::Following,
+        };
+
+        Ok(SyncStatus {
+            stage,
+            block_number: status.block_number,
+            target_epoch: status.target_epoch,
+            target_block: status.target_block,
+            progress: status.progress(),
+            downloaded_chunks: status.downloaded_chunks,
+            total_chunks: status.total_chunks,
+            clusters: status
+                .clusters
+                .into_iter()
+                .map(|cluster| SyncCluster {
+                    first_epoch: cluster.first_epoch,
+                    num_blocks: cluster.num_blocks,
+                    num_peers: cluster.num_peers,
+                    checkpoint: cluster.checkpoint,
+                })
+                .collect(),
+            eta: status.eta.map(|eta| eta.as_secs()),
+        })
+    }
+
     async fn send_raw_transaction(&mut self, raw_tx: String) -> Result<Blake2bHash, Error> {
         let tx = Deserialize::deserialize_from_vec(&hex::decode(&raw_tx)?)?;
         self.push_transaction(tx).await
@@ -320,6 +356,7 @@ impl ConsensusInterface for ConsensusDispatcher {
         wallet: Address,
         reward_address: Address,
         validator_secret_key: String,
+        commission: Option<u16>,
         value: Coin,
         fee: Coin,
         validity_start_height: ValidityStartHeight,
@@ -334,6 +371,7 @@ impl ConsensusInterface for ConsensusDispatcher {
             &self.get_wallet_keypair(&wallet)?,
             reward_address,
             &validator_keypair,
+            commission,
             value,
             fee,
             self.validity_start_height(validity_start_height),
@@ -348,6 +386,7 @@ impl ConsensusInterface for ConsensusDispatcher {
         wallet: Address,
         reward_address: Address,
         validator_secret_key: String,
+        commission: Option<u16>,
         value: Coin,
         fee: Coin,
         validity_start_height: ValidityStartHeight,
@@ -357,12 +396,12 @@ impl ConsensusInterface for ConsensusDispatcher {
                 wallet,
                 reward_address,
                 validator_secret_key,
+                commission,
                 value,
                 fee,
                 validity_start_height,
             )
-            .await
-            .unwrap();
+            .await?;
         self.send_raw_transaction(raw_tx).await
     }
 
@@ -373,6 +412,8 @@ impl ConsensusInterface for ConsensusDispatcher {
         new_reward_address: Option<Address>,
         old_validator_secret_key: String,
         new_validator_secret_key: Option<String>,
+        new_commission: Option<u16>,
+        remove_commission: bool,
         fee: Coin,
         validity_start_height: ValidityStartHeight,
     ) -> Result<String, Error> {
@@ -390,6 +431,13 @@ impl ConsensusInterface for ConsensusDispatcher {
             _ => None,
         };
 
+        // `removeCommission` clears the commission, so it can't be combined with a new one.
+        let new_commission = match (new_commission, remove_commission) {
+            (Some(_), true) => return Err(Error::InvalidTransactionParameters),
+            (_, true) => Some(None),
+            (new_commission, false) => new_commission.map(Some),
+        };
+
         let transaction = TransactionBuilder::new_update_validator(
             None,
             &self.get_wallet_keypair(&wallet)?,
@@ -397,6 +445,7 @@ impl ConsensusInterface for ConsensusDispatcher {
             new_reward_address,
             &old_validator_keypair,
             new_validator_keypair.as_ref(),
+            new_commission,
             fee,
             self.validity_start_height(validity_start_height),
             self.network_id(),
@@ -412,6 +461,8 @@ impl ConsensusInterface for ConsensusDispatcher {
         new_reward_address: Option<Address>,
         old_validator_secret_key: String,
         new_validator_secret_key: Option<String>,
+        new_commission: Option<u16>,
+        remove_commission: bool,
         fee: Coin,
         validity_start_height: ValidityStartHeight,
     ) -> Result<Blake2bHash, Error> {
@@ -422,11 +473,12 @@ impl ConsensusInterface for ConsensusDispatcher {
                 new_reward_address,
                 old_validator_secret_key,
                 new_validator_secret_key,
+                new_commission,
+                remove_commission,
                 fee,
                 validity_start_height,
             )
-            .await
-            .unwrap();
+            .await?;
         self.send_raw_transaction(raw_tx).await
     }
 
@@ -0,0 +1,57 @@
+use async_trait::async_trait;
+use serde_json::Value;
+use tokio::sync::mpsc;
+
+use nimiq_jsonrpc_core::{Request, Response};
+use nimiq_jsonrpc_server::{Dispatcher, Message};
+use nimiq_rpc_interface::openrpc::{self, OpenRpcDocument, DISCOVER_METHOD};
+
+/// Serves the OpenRPC document of the API from the `rpc.discover` method.
+///
+/// The name of this method can't be expressed by a service derived from an interface trait, so
+/// this implements the dispatcher directly.
+pub struct DiscoverDispatcher {
+    document: Value,
+}
+
+impl DiscoverDispatcher {
+    pub fn new() -> Self {
+        Self::from_document(&openrpc::document())
+    }
+
+    pub fn from_document(document: &OpenRpcDocument) -> Self {
+        DiscoverDispatcher {
+            document: serde_json::to_value(document)
+                .expect("The OpenRPC document can always be serialized"),
+        }
+    }
+}
+
+impl Default for DiscoverDispatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Dispatcher for DiscoverDispatcher {
+    async fn dispatch(
+        &mut self,
+        request: Request,
+        _tx: Option<&mpsc::Sender<Message>>,
+        _id: u64,
+    ) -> Option<Response> {
+        // Requests without an ID are notifications and don't get a response.
+        request
+            .id
+            .map(|id| Response::new_success(id, self.document.clone()))
+    }
+
+    fn match_method(&self, name: &str) -> bool {
+        name == DISCOVER_METHOD
+    }
+
+    fn method_names(&self) -> Vec<&str> {
+        vec![DISCOVER_METHOD]
+    }
+}
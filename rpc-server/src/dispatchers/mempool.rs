@@ -1,14 +1,28 @@
+use std::collections::HashSet;
 use std::sync::Arc;
 
 use async_trait::async_trait;
 use parking_lot::RwLock;
 
-use nimiq_hash::Blake2bHash;
-use nimiq_mempool::Mempool;
-use nimiq_rpc_interface::mempool::MempoolInterface;
+use nimiq_hash::{Blake2bHash, Hash};
+use nimiq_keys::Address;
+use nimiq_mempool::{policy::PolicyConfig, Mempool};
+use nimiq_rpc_interface::{
+    mempool::MempoolInterface,
+    types::{AddressTransactions, HashOrTx, MempoolInfo, MempoolTransaction},
+};
 
 use crate::{error::Error, wallets::UnlockedWallets};
 
+/// The maximum number of transactions returned per address if the caller doesn't specify a limit.
+const DEFAULT_MAX_TRANSACTIONS_PER_ADDRESS: usize = 100;
+
+/// The maximum number of transactions returned per address.
+const MAX_TRANSACTIONS_PER_ADDRESS: usize = 500;
+
+/// The maximum number of addresses that can be queried in a single request.
+const MAX_ADDRESSES_PER_REQUEST: usize = 100;
+
 #[allow(dead_code)]
 pub struct MempoolDispatcher {
     mempool: Arc<Mempool>,
@@ -35,23 +49,90 @@ impl MempoolDispatcher {
 impl MempoolInterface for MempoolDispatcher {
     type Error = Error;
 
-    async fn get_transaction(&mut self, _txid: Blake2bHash) -> Result<Option<()>, Error> {
-        /*Ok(self.mempool
-        .get_transaction(&txid)
-        // TODO: We can return a `Arc<Transaction>`, if we implement `serde::Serialize` for it.
-        .map(|tx| Transaction::clone(&tx)))*/
-        Err(Error::NotImplemented)
+    async fn get_transaction(
+        &mut self,
+        txid: Blake2bHash,
+    ) -> Result<Option<MempoolTransaction>, Error> {
+        Ok(self
+            .mempool
+            .get_transaction(&txid)
+            .map(|tx| MempoolTransaction::from_transaction(&tx)))
     }
 
-    async fn mempool_content(&mut self, _include_transactions: bool) -> Result<Vec<()>, Error> {
-        Err(Error::NotImplemented)
+    async fn mempool_content(
+        &mut self,
+        include_transactions: bool,
+    ) -> Result<Vec<HashOrTx>, Error> {
+        Ok(self
+            .mempool
+            .get_transactions(usize::MAX, 0.0)
+            .into_iter()
+            .map(|tx| {
+                if include_transactions {
+                    HashOrTx::Tx(MempoolTransaction::from_transaction(&tx))
+                } else {
+                    HashOrTx::Hash(tx.hash())
+                }
+            })
+            .collect())
     }
 
-    async fn mempool(&mut self) -> Result<(), Error> {
-        Err(Error::NotImplemented)
+    async fn mempool(&mut self) -> Result<MempoolInfo, Error> {
+        let transactions = self.mempool.get_transactions(usize::MAX, 0.0);
+        Ok(MempoolInfo::from_txs(
+            transactions.iter().map(|tx| tx.as_ref()),
+        ))
     }
 
     async fn get_mempool_transaction(&mut self) -> Result<(), Error> {
         Err(Error::NotImplemented)
     }
+
+    async fn get_transactions_by_addresses(
+        &mut self,
+        addresses: Vec<Address>,
+        max_transactions: Option<usize>,
+    ) -> Result<Vec<AddressTransactions>, Error> {
+        if addresses.len() > MAX_ADDRESSES_PER_REQUEST {
+            return Err(Error::TooManyAddresses(
+                addresses.len(),
+                MAX_ADDRESSES_PER_REQUEST,
+            ));
+        }
+
+        let max_transactions = max_transactions
+            .unwrap_or(DEFAULT_MAX_TRANSACTIONS_PER_ADDRESS)
+            .min(MAX_TRANSACTIONS_PER_ADDRESS);
+
+        // Query the mempool for each address separately, such that every address gets its own list
+        // of pending transactions.
+        Ok(addresses
+            .into_iter()
+            .map(|address| {
+                let mut addresses = HashSet::new();
+                addresses.insert(address.clone());
+
+                let transactions = self
+                    .mempool
+                    .get_transactions_by_addresses(addresses, max_transactions)
+                    .iter()
+                    .map(|tx| MempoolTransaction::from_transaction(tx))
+                    .collect();
+
+                AddressTransactions {
+                    address,
+                    transactions,
+                }
+            })
+            .collect())
+    }
+
+    async fn get_mempool_policy(&mut self) -> Result<PolicyConfig, Error> {
+        Ok(self.mempool.policy_config())
+    }
+
+    async fn set_mempool_policy(&mut self, policy: PolicyConfig) -> Result<(), Error> {
+        self.mempool.set_policy(policy);
+        Ok(())
+    }
 }
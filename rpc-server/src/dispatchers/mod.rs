@@ -1,9 +1,19 @@
 mod blockchain;
 mod consensus;
+mod discover;
 mod mempool;
+mod nano;
+mod subscription;
+#[cfg(feature = "validator")]
+mod validator;
 mod wallet;
 
 pub use blockchain::BlockchainDispatcher;
 pub use consensus::ConsensusDispatcher;
+pub use discover::DiscoverDispatcher;
 pub use mempool::MempoolDispatcher;
+pub use nano::NanoDispatcher;
+pub use subscription::SubscriptionDispatcher;
+#[cfg(feature = "validator")]
+pub use validator::ValidatorDispatcher;
 pub use wallet::WalletDispatcher;
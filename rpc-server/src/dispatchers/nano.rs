@@ -0,0 +1,100 @@
+use async_trait::async_trait;
+
+use beserial::Deserialize;
+use nimiq_account::Account;
+use nimiq_blockchain_albatross::{AbstractBlockchain, ExtTxData};
+use nimiq_consensus_albatross::nano::NanoConsensusProxy;
+use nimiq_hash::{Blake2bHash, Hash};
+use nimiq_keys::Address;
+use nimiq_network_libp2p::Network;
+use nimiq_primitives::policy;
+use nimiq_transaction::Transaction as BlockchainTransaction;
+
+use nimiq_rpc_interface::{nano::NanoInterface, types::Transaction};
+
+use crate::error::Error;
+
+pub struct NanoDispatcher {
+    consensus: NanoConsensusProxy<Network>,
+}
+
+impl NanoDispatcher {
+    pub fn new(consensus: NanoConsensusProxy<Network>) -> Self {
+        Self { consensus }
+    }
+
+    fn block_number(&self) -> u32 {
+        self.consensus.blockchain.read().block_number()
+    }
+}
+
+#[nimiq_jsonrpc_derive::service(rename_all = "camelCase")]
+#[async_trait]
+impl NanoInterface for NanoDispatcher {
+    type Error = Error;
+
+    async fn block_number(&mut self) -> Result<u32, Error> {
+        Ok(NanoDispatcher::block_number(self))
+    }
+
+    async fn epoch_number(&mut self) -> Result<u32, Error> {
+        Ok(policy::epoch_at(NanoDispatcher::block_number(self)))
+    }
+
+    async fn is_consensus_established(&mut self) -> Result<bool, Error> {
+        Ok(self.consensus.is_established())
+    }
+
+    async fn get_account(&mut self, account: Address) -> Result<Account, Error> {
+        // The accounts of watched addresses are kept up to date, so we don't need to request them.
+        if let Some(account) = self.consensus.get_watched_account(&account) {
+            return Ok(account);
+        }
+
+        let mut accounts = self.consensus.get_accounts(vec![account]).await?;
+        Ok(accounts.pop().expect("One account per address"))
+    }
+
+    async fn get_recent_transactions_by_address(
+        &mut self,
+        address: Address,
+    ) -> Result<Vec<Transaction>, Error> {
+        let ext_txs = self.consensus.get_transactions_by_address(address).await?;
+
+        let head_height = NanoDispatcher::block_number(self);
+
+        // Inherents are part of the history of an address too, but they can't be represented as
+        // transactions.
+        let transactions = ext_txs
+            .into_iter()
+            .filter_map(|ext_tx| match ext_tx.data {
+                ExtTxData::Basic(tx) => Some(Transaction::from_blockchain(
+                    tx,
+                    ext_tx.block_number,
+                    ext_tx.block_time,
+                    head_height,
+                )),
+                ExtTxData::Inherent(_) => None,
+            })
+            .collect();
+
+        Ok(transactions)
+    }
+
+    async fn watch_address(&mut self, address: Address) -> Result<(), Error> {
+        self.consensus.watch_address(address);
+        Ok(())
+    }
+
+    async fn unwatch_address(&mut self, address: Address) -> Result<(), Error> {
+        self.consensus.unwatch_address(&address);
+        Ok(())
+    }
+
+    async fn send_raw_transaction(&mut self, raw_tx: String) -> Result<Blake2bHash, Error> {
+        let tx: BlockchainTransaction = Deserialize::deserialize_from_vec(&hex::decode(&raw_tx)?)?;
+        let txid = tx.hash::<Blake2bHash>();
+        self.consensus.send_transaction(tx).await?;
+        Ok(txid)
+    }
+}
@@ -0,0 +1,397 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use futures::channel::mpsc;
+use futures::future;
+use futures::stream::{self, AbortHandle, Abortable, BoxStream, StreamExt};
+use parking_lot::Mutex;
+
+use nimiq_blockchain_albatross::{AbstractBlockchain, Blockchain, BlockchainEvent};
+use nimiq_keys::Address;
+use nimiq_mempool::{Mempool, MempoolEvent};
+use nimiq_rpc_interface::{
+    subscription::SubscriptionInterface,
+    types::{
+        Block, MempoolEvent as MempoolEventInfo, SubscriptionEvent, SubscriptionFilter,
+        SubscriptionNotification, Transaction,
+    },
+};
+use nimiq_subscription::Subscription;
+use nimiq_utils::observer::ListenerHandle;
+
+use crate::error::Error;
+
+/// The maximum number of events that are buffered for a subscription. If the client doesn't read
+/// its notifications fast enough and the buffer fills up, the subscription is closed.
+const MAX_BUFFERED_EVENTS: usize = 1024;
+
+/// The notifier that a subscription is registered with.
+enum Listener {
+    Blockchain(ListenerHandle),
+    Mempool(ListenerHandle),
+}
+
+struct ActiveSubscription {
+    listener: Listener,
+    abort_handle: AbortHandle,
+}
+
+/// The subscriptions of a single WebSocket connection. The server creates a dispatcher for each
+/// connection and drops it when the connection is closed, which ends all of its subscriptions.
+pub struct SubscriptionDispatcher {
+    blockchain: Arc<Blockchain>,
+    mempool: Arc<Mempool>,
+    subscriptions: HashMap<String, ActiveSubscription>,
+}
+
+impl SubscriptionDispatcher {
+    pub fn new(blockchain: Arc<Blockchain>, mempool: Arc<Mempool>) -> Self {
+        SubscriptionDispatcher {
+            blockchain,
+            mempool,
+            subscriptions: HashMap::new(),
+        }
+    }
+
+    /// Returns a new random subscription ID.
+    fn new_subscription_id(&self) -> String {
+        loop {
+            let id = hex::encode(rand::random::<[u8; 16]>());
+            if !self.subscriptions.contains_key(&id) {
+                return id;
+            }
+        }
+    }
+
+    /// Deregisters the listener of the subscription and ends its stream.
+    fn cancel(&self, subscription: ActiveSubscription) {
+        match subscription.listener {
+            Listener::Blockchain(handle) => self.blockchain.notifier.write().deregister(handle),
+            Listener::Mempool(handle) => self.mempool.notifier.write().deregister(handle),
+        }
+        subscription.abort_handle.abort();
+    }
+
+    /// Returns a listener that forwards events to the given sender. If the buffer of the channel is
+    /// full, the channel is closed, which ends the stream of the subscription once the client has
+    /// read the buffered events.
+    fn bounded_listener<E: Clone + Send + 'static>(
+        tx: mpsc::Sender<E>,
+    ) -> impl Fn(&E) + Send + Sync {
+        let tx = Mutex::new(tx);
+        move |event: &E| {
+            let mut tx = tx.lock();
+            // Sending also fails if the stream was dropped. The listener is removed when the
+            // subscription is cancelled.
+            if let Err(e) = tx.try_send(event.clone()) {
+                if e.is_full() {
+                    log::warn!(
+                        "Closing a subscription whose client doesn't keep up with its events"
+                    );
+                    tx.close_channel();
+                }
+            }
+        }
+    }
+
+    /// Registers a listener for blockchain events and returns its handle and a stream of the events.
+    fn blockchain_events(&self) -> (ListenerHandle, mpsc::Receiver<BlockchainEvent>) {
+        let (tx, rx) = mpsc::channel(MAX_BUFFERED_EVENTS);
+        let handle = self
+            .blockchain
+            .notifier
+            .write()
+            .register(Self::bounded_listener(tx));
+        (handle, rx)
+    }
+
+    /// Registers a listener for mempool events and returns its handle and a stream of the events.
+    fn mempool_events(&self) -> (ListenerHandle, mpsc::Receiver<MempoolEvent>) {
+        let (tx, rx) = mpsc::channel(MAX_BUFFERED_EVENTS);
+        let handle = self
+            .mempool
+            .notifier
+            .write()
+            .register(Self::bounded_listener(tx));
+        (handle, rx)
+    }
+
+    /// Returns the blocks that were added to the main chain by the given event.
+    fn new_head_blocks(
+        blockchain: &Blockchain,
+        event: BlockchainEvent,
+    ) -> Vec<nimiq_block_albatross::Block> {
+        match event {
+            BlockchainEvent::Extended(hash)
+            | BlockchainEvent::Finalized(hash)
+            | BlockchainEvent::EpochFinalized(hash) => blockchain
+                .get_block(&hash, true, None)
+                .into_iter()
+                .collect(),
+            BlockchainEvent::Rebranched(_, new_branch) => {
+                new_branch.into_iter().map(|(_, block)| block).collect()
+            }
+        }
+    }
+
+    /// Returns a subscription that matches transactions sent from or to the given addresses, or all
+    /// transactions if no addresses are given.
+    fn address_subscription(addresses: Vec<Address>) -> Subscription {
+        if addresses.is_empty() {
+            Subscription::Any
+        } else {
+            Subscription::Addresses(addresses.into_iter().collect::<HashSet<_>>())
+        }
+    }
+
+    /// Checks if a mempool event concerns a transaction that matches the subscription.
+    fn mempool_event_matches(subscription: &Subscription, event: &MempoolEvent) -> bool {
+        match event {
+            MempoolEvent::TransactionAdded(_, tx)
+            | MempoolEvent::TransactionRestored(tx)
+            | MempoolEvent::TransactionMined(tx)
+            | MempoolEvent::TransactionEvicted(tx)
+            | MempoolEvent::TransactionDropped(tx) => subscription.matches_sender_or_recipient(tx),
+            MempoolEvent::TransactionReplaced(replaced, replacement) => {
+                subscription.matches_sender_or_recipient(replaced)
+                    || subscription.matches_sender_or_recipient(replacement)
+            }
+        }
+    }
+
+    /// Returns the transactions of a block that match the subscription.
+    fn matching_transactions(
+        blockchain: &Blockchain,
+        subscription: &Subscription,
+        block: &nimiq_block_albatross::Block,
+    ) -> Vec<SubscriptionEvent> {
+        let block_number = block.block_number();
+        let timestamp = block.timestamp();
+        let head_height = blockchain.block_number();
+
+        block
+            .transactions()
+            .map(|transactions| {
+                transactions
+                    .iter()
+                    .filter(|tx| subscription.matches_sender_or_recipient(tx))
+                    .map(|tx| {
+                        SubscriptionEvent::Transaction(Transaction::from_blockchain(
+                            tx.clone(),
+                            block_number,
+                            timestamp,
+                            head_height,
+                        ))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Registers a listener for blockchain events and returns a stream of the events that `f`
+    /// creates for each new head block.
+    fn head_block_events<F>(&self, f: F) -> (Listener, BoxStream<'static, SubscriptionEvent>)
+    where
+        F: Fn(&Blockchain, nimiq_block_albatross::Block) -> Vec<SubscriptionEvent> + Send + 'static,
+    {
+        let (handle, events) = self.blockchain_events();
+        let blockchain = Arc::clone(&self.blockchain);
+        let events = events
+            .flat_map(move |event| {
+                let events: Vec<SubscriptionEvent> = Self::new_head_blocks(&blockchain, event)
+                    .into_iter()
+                    .flat_map(|block| f(&blockchain, block))
+                    .collect();
+                stream::iter(events)
+            })
+            .boxed();
+        (Listener::Blockchain(handle), events)
+    }
+
+    /// Registers a listener for mempool events and returns a stream of the events that match the
+    /// subscription.
+    fn mempool_event_stream(
+        &self,
+        subscription: Subscription,
+    ) -> (Listener, BoxStream<'static, SubscriptionEvent>) {
+        let (handle, events) = self.mempool_events();
+        let events = events
+            .filter(move |event| future::ready(Self::mempool_event_matches(&subscription, event)))
+            .map(|event| SubscriptionEvent::Mempool(MempoolEventInfo::from_event(&event)))
+            .boxed();
+        (Listener::Mempool(handle), events)
+    }
+}
+
+#[nimiq_jsonrpc_derive::service(rename_all = "camelCase")]
+#[async_trait]
+impl SubscriptionInterface for SubscriptionDispatcher {
+    type Error = Error;
+
+    #[stream]
+    async fn subscribe(
+        &mut self,
+        filter: SubscriptionFilter,
+    ) -> Result<BoxStream<'static, SubscriptionNotification>, Error> {
+        let (listener, events) = match filter {
+            SubscriptionFilter::Heads {
+                include_transactions,
+            } => self.head_block_events(move |blockchain, block| {
+                vec![SubscriptionEvent::Block(Block::from_block(
+                    blockchain,
+                    block,
+                    include_transactions,
+                ))]
+            }),
+            SubscriptionFilter::MacroBlocks => self.head_block_events(|blockchain, block| {
+                if block.is_macro() {
+                    vec![SubscriptionEvent::Block(Block::from_block(
+                        blockchain, block, false,
+                    ))]
+                } else {
+                    vec![]
+                }
+            }),
+            SubscriptionFilter::ElectionBlocks => self.head_block_events(|blockchain, block| {
+                if block.is_election() {
+                    vec![SubscriptionEvent::Block(Block::from_block(
+                        blockchain, block, false,
+                    ))]
+                } else {
+                    vec![]
+                }
+            }),
+            SubscriptionFilter::Transactions { addresses } => {
+                let subscription = Self::address_subscription(addresses);
+                self.head_block_events(move |blockchain, block| {
+                    Self::matching_transactions(blockchain, &subscription, &block)
+                })
+            }
+            SubscriptionFilter::Mempool { addresses } => {
+                self.mempool_event_stream(Self::address_subscription(addresses))
+            }
+        };
+
+        let subscription_id = self.new_subscription_id();
+
+        let (abort_handle, abort_registration) = AbortHandle::new_pair();
+        self.subscriptions.insert(
+            subscription_id.clone(),
+            ActiveSubscription {
+                listener,
+                abort_handle,
+            },
+        );
+
+        let events = stream::once(future::ready(SubscriptionEvent::Subscribed)).chain(events);
+        Ok(Abortable::new(events, abort_registration)
+            .map(move |event| SubscriptionNotification {
+                subscription_id: subscription_id.clone(),
+                event,
+            })
+            .boxed())
+    }
+
+    async fn unsubscribe(&mut self, subscription_id: String) -> Result<bool, Error> {
+        match self.subscriptions.remove(&subscription_id) {
+            Some(subscription) => {
+                self.cancel(subscription);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+}
+
+impl Drop for SubscriptionDispatcher {
+    fn drop(&mut self) {
+        for (_, subscription) in std::mem::take(&mut self.subscriptions) {
+            self.cancel(subscription);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+
+    use nimiq_database::volatile::VolatileEnvironment;
+    use nimiq_mempool::MempoolConfig;
+    use nimiq_primitives::networks::NetworkId;
+
+    use super::*;
+
+    fn dispatcher() -> SubscriptionDispatcher {
+        let env = VolatileEnvironment::new(Blockchain::NUM_DATABASES).unwrap();
+        let blockchain = Arc::new(Blockchain::new(env, NetworkId::UnitAlbatross).unwrap());
+        let mempool = Mempool::new(Arc::clone(&blockchain), MempoolConfig::default());
+        SubscriptionDispatcher::new(blockchain, mempool)
+    }
+
+    /// Subscribes to head blocks and returns the subscription ID and the stream.
+    fn subscribe(
+        dispatcher: &mut SubscriptionDispatcher,
+    ) -> (String, BoxStream<'static, SubscriptionNotification>) {
+        let filter = SubscriptionFilter::Heads {
+            include_transactions: false,
+        };
+        let mut stream = block_on(dispatcher.subscribe(filter)).unwrap();
+
+        let notification = block_on(stream.next()).unwrap();
+        assert!(matches!(notification.event, SubscriptionEvent::Subscribed));
+        (notification.subscription_id, stream)
+    }
+
+    #[test]
+    fn it_uses_random_subscription_ids() {
+        let mut dispatcher = dispatcher();
+
+        let (id_1, _stream_1) = subscribe(&mut dispatcher);
+        let (id_2, _stream_2) = subscribe(&mut dispatcher);
+        assert_ne!(id_1, id_2);
+        assert_eq!(id_1.len(), 32);
+
+        assert!(!block_on(dispatcher.unsubscribe("0".to_string())).unwrap());
+        assert!(block_on(dispatcher.unsubscribe(id_1.clone())).unwrap());
+        assert!(!block_on(dispatcher.unsubscribe(id_1)).unwrap());
+    }
+
+    #[test]
+    fn it_ends_the_stream_on_unsubscribe() {
+        let mut dispatcher = dispatcher();
+
+        let (id, mut stream) = subscribe(&mut dispatcher);
+        assert!(block_on(dispatcher.unsubscribe(id)).unwrap());
+        assert!(block_on(stream.next()).is_none());
+    }
+
+    #[test]
+    fn it_ends_all_subscriptions_when_dropped() {
+        let mut dispatcher = dispatcher();
+
+        let (_, mut stream_1) = subscribe(&mut dispatcher);
+        let (_, mut stream_2) = subscribe(&mut dispatcher);
+
+        // This happens when the connection of the subscriptions is closed.
+        drop(dispatcher);
+
+        assert!(block_on(stream_1.next()).is_none());
+        assert!(block_on(stream_2.next()).is_none());
+    }
+
+    #[test]
+    fn it_closes_subscriptions_that_fall_behind() {
+        let (tx, rx) = mpsc::channel(MAX_BUFFERED_EVENTS);
+        let listener = SubscriptionDispatcher::bounded_listener(tx);
+
+        for i in 0..2 * MAX_BUFFERED_EVENTS {
+            listener(&i);
+        }
+
+        // The channel has room for one additional event per sender.
+        let events: Vec<usize> = block_on(rx.collect());
+        assert_eq!(events.len(), MAX_BUFFERED_EVENTS + 1);
+        assert_eq!(events[0], 0);
+    }
+}
@@ -0,0 +1,78 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use nimiq_rpc_interface::{
+    types::{BlockDuty, UpcomingSlot, ValidatorDuties, VoteDuty, VoteKind},
+    validator::ValidatorInterface,
+};
+use nimiq_validator::duty::{self, BlockOutcome, DutyTracker};
+use nimiq_validator::slashing_protection::SigningKind;
+
+use crate::error::Error;
+
+pub struct ValidatorDispatcher {
+    duty_tracker: Arc<DutyTracker>,
+}
+
+impl ValidatorDispatcher {
+    pub fn new(duty_tracker: Arc<DutyTracker>) -> Self {
+        ValidatorDispatcher { duty_tracker }
+    }
+}
+
+#[nimiq_jsonrpc_derive::service(rename_all = "camelCase")]
+#[async_trait]
+impl ValidatorInterface for ValidatorDispatcher {
+    type Error = Error;
+
+    async fn get_validator_duties(&mut self) -> Result<ValidatorDuties, Error> {
+        let report = self.duty_tracker.report();
+
+        Ok(ValidatorDuties {
+            slot_band: report.slot_band,
+            num_slots: report.num_slots,
+            upcoming_slots: report
+                .upcoming_slots
+                .into_iter()
+                .map(|slot| UpcomingSlot {
+                    block_number: slot.block_number,
+                    view_number: slot.view_number,
+                })
+                .collect(),
+            produced_blocks: report.counts.produced_blocks,
+            missed_blocks: report.counts.missed_blocks,
+            signed_votes: report.counts.signed_votes,
+            included_votes: report.counts.included_votes,
+            missed_votes: report.counts.missed_votes,
+            recent_blocks: report
+                .recent_blocks
+                .into_iter()
+                .map(|duty| BlockDuty {
+                    block_number: duty.block_number,
+                    view_number: duty.view_number,
+                    produced: duty.outcome == BlockOutcome::Produced,
+                })
+                .collect(),
+            recent_votes: report.recent_votes.into_iter().map(vote_duty).collect(),
+        })
+    }
+}
+
+fn vote_duty(vote: duty::VoteDuty) -> VoteDuty {
+    let kind = match vote.kind {
+        SigningKind::ViewChange => VoteKind::ViewChange,
+        SigningKind::TendermintPrevote => VoteKind::Prevote,
+        SigningKind::TendermintPrecommit => VoteKind::Precommit,
+        // The duty tracker only records votes.
+        SigningKind::MicroBlock | SigningKind::TendermintProposal => unreachable!(),
+    };
+
+    VoteDuty {
+        kind,
+        block_number: vote.block_number,
+        round: vote.round,
+        signed: vote.signed,
+        included: vote.included,
+    }
+}
@@ -52,6 +52,30 @@ pub enum Error {
     #[error("Transaction not found: {0}")]
     TransactionNotFound(Blake2bHash),
 
+    #[error("Multiple transactions found with hash: {0}")]
+    MultipleTransactionsFound(Blake2bHash),
+
+    #[error("No reward report for batch: {0}")]
+    RewardReportNotFound(u32),
+
+    #[error("Reward report for batch {0} is not available, because this node synced the batch")]
+    RewardReportUnavailable(u32),
+
+    #[error("Requested {0} addresses, but at most {1} are allowed")]
+    TooManyAddresses(usize, usize),
+
+    #[error("Block range {0} to {1} spans more than {2} blocks")]
+    BlockRangeTooLong(u32, u32, u32),
+
+    #[error("{0}")]
+    StakingDiff(#[from] nimiq_blockchain_albatross::StakingDiffError),
+
+    #[error("{0}")]
+    Task(#[from] tokio::task::JoinError),
+
+    #[error("Nano request failed: {0}")]
+    NanoRequest(#[from] nimiq_consensus_albatross::error::NanoRequestError),
+
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 }
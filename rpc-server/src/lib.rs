@@ -1,6 +1,8 @@
+pub mod auth;
 pub mod dispatchers;
 pub mod error;
+pub mod server;
 pub mod wallets;
 
 pub use error::Error;
-pub use nimiq_jsonrpc_server::{Config, Server};
+pub use server::{Config, Server};
@@ -0,0 +1,527 @@
+use std::collections::HashSet;
+use std::convert::Infallible;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use bytes::Bytes;
+use futures::{SinkExt, StreamExt};
+use serde::Serialize;
+use serde_json::Value;
+use tokio::sync::{mpsc, Mutex};
+use warp::http::StatusCode;
+use warp::ws::{WebSocket, Ws};
+use warp::{Filter, Reply};
+
+use nimiq_jsonrpc_core::{Credentials, Request, Response, RpcError};
+use nimiq_jsonrpc_server::{Dispatcher, Message};
+
+use crate::auth::{parse_basic_auth, Authorizer, RpcUser};
+
+/// The number of messages that can be queued for a WebSocket connection.
+const WEBSOCKET_QUEUE_SIZE: usize = 64;
+
+pub struct Config {
+    pub bind_to: SocketAddr,
+    /// Whether to accept WebSocket connections on `/ws`.
+    pub enable_websocket: bool,
+    /// If specified, only accept connections from these IP addresses.
+    pub ip_whitelist: Option<HashSet<IpAddr>>,
+    /// If specified, require HTTP basic auth with these credentials.
+    pub basic_auth: Option<Credentials>,
+    /// If specified, clients have to authenticate as one of its users, either with HTTP basic auth
+    /// or with an API token as bearer auth. Each request is then checked against the method groups
+    /// and the rate limit of the user. This can't be combined with `basic_auth`.
+    pub authorizer: Option<Authorizer>,
+    /// The maximum number of requests in a batch.
+    pub max_batch_size: usize,
+}
+
+/// Creates the dispatcher for the methods whose state belongs to a single WebSocket connection,
+/// e.g. subscriptions.
+pub type ConnectionDispatcherFactory<C> = Box<dyn Fn() -> C + Send + Sync>;
+
+/// The JSON-RPC server. It accepts requests as HTTP POST requests on `/` and as messages of
+/// WebSocket connections on `/ws`.
+///
+/// A request can also be a batch, i.e. an array of requests, which is answered with the array of
+/// their responses, as specified by JSON-RPC 2.0. The requests of a batch are dispatched one after
+/// the other and each of them is authorized on its own.
+///
+/// The requests are dispatched to a dispatcher that is shared by all clients. Each WebSocket
+/// connection additionally gets its own dispatcher from the `ConnectionDispatcherFactory`, which
+/// handles the methods that it matches. It is dropped when the connection is closed, so that the
+/// state of a connection can neither leak nor be accessed from other connections.
+pub struct Server<D, C> {
+    inner: Arc<Inner<D, C>>,
+}
+
+struct Inner<D, C> {
+    config: Config,
+    dispatcher: Mutex<D>,
+    connection_dispatcher: ConnectionDispatcherFactory<C>,
+    next_id: AtomicU64,
+}
+
+/// The state of a WebSocket connection.
+struct Connection<C> {
+    dispatcher: C,
+    tx: mpsc::Sender<Message>,
+}
+
+impl<D: Dispatcher, C: Dispatcher> Server<D, C> {
+    pub fn new(
+        config: Config,
+        dispatcher: D,
+        connection_dispatcher: ConnectionDispatcherFactory<C>,
+    ) -> Self {
+        Server {
+            inner: Arc::new(Inner {
+                config,
+                dispatcher: Mutex::new(dispatcher),
+                connection_dispatcher,
+                next_id: AtomicU64::new(1),
+            }),
+        }
+    }
+
+    pub async fn run(&self) {
+        let inner = Arc::clone(&self.inner);
+        let with_inner = warp::any().map(move || Arc::clone(&inner));
+
+        let http = warp::post()
+            .and(warp::path::end())
+            .and(with_inner.clone())
+            .and(warp::addr::remote())
+            .and(warp::header::optional::<String>("authorization"))
+            .and(warp::body::bytes())
+            .and_then(Self::handle_http);
+
+        let websocket = warp::path("ws")
+            .and(warp::path::end())
+            .and(with_inner)
+            .and(warp::addr::remote())
+            .and(warp::header::optional::<String>("authorization"))
+            .and(warp::ws())
+            .and_then(Self::handle_websocket_upgrade);
+
+        warp::serve(http.or(websocket))
+            .run(self.inner.config.bind_to)
+            .await;
+    }
+
+    async fn handle_http(
+        inner: Arc<Inner<D, C>>,
+        remote: Option<SocketAddr>,
+        authorization: Option<String>,
+        body: Bytes,
+    ) -> Result<Box<dyn Reply>, Infallible> {
+        let user = match inner.check_access(remote, authorization.as_deref()) {
+            Ok(user) => user,
+            Err(status) => return Ok(Box::new(status)),
+        };
+
+        match inner.handle_raw_request(&body, user.as_ref(), None).await {
+            Some(response) => Ok(Box::new(warp::reply::with_header(
+                response,
+                "content-type",
+                "application/json",
+            ))),
+            // Notifications don't get a response.
+            None => Ok(Box::new(StatusCode::NO_CONTENT)),
+        }
+    }
+
+    async fn handle_websocket_upgrade(
+        inner: Arc<Inner<D, C>>,
+        remote: Option<SocketAddr>,
+        authorization: Option<String>,
+        ws: Ws,
+    ) -> Result<Box<dyn Reply>, Infallible> {
+        if !inner.config.enable_websocket {
+            return Ok(Box::new(StatusCode::NOT_FOUND));
+        }
+        let user = match inner.check_access(remote, authorization.as_deref()) {
+            Ok(user) => user,
+            Err(status) => return Ok(Box::new(status)),
+        };
+
+        Ok(Box::new(ws.on_upgrade(move |websocket| {
+            Self::handle_websocket(inner, websocket, user)
+        })))
+    }
+
+    async fn handle_websocket(
+        inner: Arc<Inner<D, C>>,
+        websocket: WebSocket,
+        user: Option<RpcUser>,
+    ) {
+        let (mut ws_tx, mut ws_rx) = websocket.split();
+        let (tx, mut rx) = mpsc::channel::<Message>(WEBSOCKET_QUEUE_SIZE);
+
+        // Responses and notifications are sent through the channel, since notifications are sent
+        // from the tasks that forward the streams of the connection.
+        tokio::spawn(async move {
+            while let Some(message) = rx.recv().await {
+                if ws_tx.send(message).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut connection = Connection {
+            dispatcher: (inner.connection_dispatcher)(),
+            tx,
+        };
+
+        while let Some(Ok(message)) = ws_rx.next().await {
+            if message.is_close() {
+                break;
+            }
+
+            // Pings are answered by the WebSocket implementation and binary messages are ignored.
+            let request = match message.to_str() {
+                Ok(request) => request,
+                Err(()) => continue,
+            };
+
+            if let Some(response) = inner
+                .handle_raw_request(request.as_bytes(), user.as_ref(), Some(&mut connection))
+                .await
+            {
+                if connection.tx.send(Message::text(response)).await.is_err() {
+                    break;
+                }
+            }
+        }
+
+        // Dropping the connection drops its dispatcher, which cancels its subscriptions.
+        drop(connection);
+    }
+}
+
+impl<D: Dispatcher, C: Dispatcher> Inner<D, C> {
+    /// Checks whether the client is allowed to connect and returns the user that it authenticated
+    /// as, if there are users. On failure, the HTTP status code of the response is returned.
+    fn check_access(
+        &self,
+        remote: Option<SocketAddr>,
+        authorization: Option<&str>,
+    ) -> Result<Option<RpcUser>, StatusCode> {
+        if let Some(ip_whitelist) = &self.config.ip_whitelist {
+            match remote {
+                Some(remote) if ip_whitelist.contains(&remote.ip()) => {}
+                _ => return Err(StatusCode::FORBIDDEN),
+            }
+        }
+
+        if let Some(credentials) = &self.config.basic_auth {
+            match authorization.and_then(parse_basic_auth) {
+                Some((username, password))
+                    if username == credentials.username && password == credentials.password => {}
+                _ => return Err(StatusCode::UNAUTHORIZED),
+            }
+        }
+
+        if let Some(authorizer) = &self.config.authorizer {
+            return match authorization.map(|authorization| authorizer.authenticate(authorization)) {
+                Some(Ok(user)) => Ok(Some(user.clone())),
+                _ => Err(StatusCode::UNAUTHORIZED),
+            };
+        }
+
+        Ok(None)
+    }
+
+    /// Parses and dispatches a request or a batch of requests and returns the serialized response,
+    /// if there is one.
+    async fn handle_raw_request(
+        &self,
+        request: &[u8],
+        user: Option<&RpcUser>,
+        connection: Option<&mut Connection<C>>,
+    ) -> Option<String> {
+        match serde_json::from_slice::<Value>(request) {
+            Ok(Value::Array(requests)) => self.dispatch_batch(requests, user, connection).await,
+            Ok(request) => self
+                .dispatch_value(request, user, connection)
+                .await
+                .map(|response| serialize_response(&response)),
+            Err(e) => Some(serialize_response(&Response::new_error(
+                Value::Null,
+                RpcError::parse_error(Some(e.to_string())),
+            ))),
+        }
+    }
+
+    /// Dispatches the requests of a batch and returns the serialized array of their responses.
+    /// Notifications don't get a response, so a batch of notifications isn't answered at all.
+    async fn dispatch_batch(
+        &self,
+        requests: Vec<Value>,
+        user: Option<&RpcUser>,
+        mut connection: Option<&mut Connection<C>>,
+    ) -> Option<String> {
+        // Invalid batches are answered with a single error, not with an array.
+        if requests.is_empty() {
+            return Some(serialize_response(&Response::new_error(
+                Value::Null,
+                RpcError::invalid_request(Some("Empty batch".to_string())),
+            )));
+        }
+        if requests.len() > self.config.max_batch_size {
+            return Some(serialize_response(&Response::new_error(
+                Value::Null,
+                RpcError::invalid_request(Some(format!(
+                    "Batch of {} requests exceeds the maximum batch size of {}",
+                    requests.len(),
+                    self.config.max_batch_size
+                ))),
+            )));
+        }
+
+        let mut responses = Vec::with_capacity(requests.len());
+        for request in requests {
+            if let Some(response) = self
+                .dispatch_value(request, user, connection.as_deref_mut())
+                .await
+            {
+                responses.push(response);
+            }
+        }
+
+        if responses.is_empty() {
+            None
+        } else {
+            Some(serialize_response(&responses))
+        }
+    }
+
+    /// Dispatches a request that was parsed as JSON, but not yet as a request object.
+    async fn dispatch_value(
+        &self,
+        request: Value,
+        user: Option<&RpcUser>,
+        connection: Option<&mut Connection<C>>,
+    ) -> Option<Response> {
+        match serde_json::from_value::<Request>(request) {
+            Ok(request) => self.dispatch(request, user, connection).await,
+            Err(e) => Some(Response::new_error(
+                Value::Null,
+                RpcError::invalid_request(Some(e.to_string())),
+            )),
+        }
+    }
+
+    /// Dispatches a request, if the user is allowed to make it.
+    async fn dispatch(
+        &self,
+        request: Request,
+        user: Option<&RpcUser>,
+        connection: Option<&mut Connection<C>>,
+    ) -> Option<Response> {
+        if let (Some(authorizer), Some(user)) = (&self.config.authorizer, user) {
+            if let Err(e) = authorizer.authorize(user, &request.method) {
+                log::debug!("Rejected RPC request: {}", e);
+                // Notifications don't get a response, not even an error.
+                return request.id.map(|id| {
+                    Response::new_error(id, RpcError::invalid_request(Some(e.to_string())))
+                });
+            }
+        }
+
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+
+        match connection {
+            Some(connection) if connection.dispatcher.match_method(&request.method) => {
+                connection
+                    .dispatcher
+                    .dispatch(request, Some(&connection.tx), id)
+                    .await
+            }
+            Some(connection) => {
+                self.dispatcher
+                    .lock()
+                    .await
+                    .dispatch(request, Some(&connection.tx), id)
+                    .await
+            }
+            None => {
+                self.dispatcher
+                    .lock()
+                    .await
+                    .dispatch(request, None, id)
+                    .await
+            }
+        }
+    }
+}
+
+fn serialize_response<T: Serialize>(response: &T) -> String {
+    serde_json::to_string(response).expect("Responses can always be serialized")
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+    use serde_json::json;
+
+    use crate::auth::MethodGroup;
+    use crate::dispatchers::DiscoverDispatcher;
+
+    use super::*;
+
+    fn inner(users: Vec<RpcUser>) -> Inner<DiscoverDispatcher, DiscoverDispatcher> {
+        Inner {
+            config: Config {
+                bind_to: ([127, 0, 0, 1], 8648).into(),
+                enable_websocket: true,
+                ip_whitelist: None,
+                basic_auth: None,
+                authorizer: Some(Authorizer::new(users).unwrap()),
+                max_batch_size: 3,
+            },
+            dispatcher: Mutex::new(DiscoverDispatcher::new()),
+            connection_dispatcher: Box::new(DiscoverDispatcher::new),
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    fn reader(rate_limit: Option<usize>) -> RpcUser {
+        RpcUser {
+            name: "reader".to_string(),
+            password: None,
+            token: Some("reader-token".to_string()),
+            groups: vec![MethodGroup::BlockchainRead],
+            rate_limit,
+        }
+    }
+
+    fn request(method: &str) -> Request {
+        Request::new(method.to_string(), None, Some(Value::from(1)))
+    }
+
+    #[test]
+    fn it_requires_users_to_authenticate() {
+        let inner = inner(vec![reader(None)]);
+
+        assert_eq!(
+            inner.check_access(None, None).unwrap_err(),
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            inner
+                .check_access(None, Some("Bearer wrong-token"))
+                .unwrap_err(),
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            inner
+                .check_access(None, Some("Bearer reader-token"))
+                .unwrap()
+                .unwrap()
+                .name,
+            "reader"
+        );
+    }
+
+    #[test]
+    fn it_checks_the_method_groups_of_the_user() {
+        let user = reader(None);
+        let inner = inner(vec![user.clone()]);
+
+        let response =
+            block_on(inner.dispatch(request("rpc.discover"), Some(&user), None)).unwrap();
+        assert!(response.error.is_none());
+
+        let response =
+            block_on(inner.dispatch(request("sendRawTransaction"), Some(&user), None)).unwrap();
+        assert!(response.error.is_some());
+    }
+
+    #[test]
+    fn it_limits_the_request_rate_of_the_user() {
+        let user = reader(Some(1));
+        let inner = inner(vec![user.clone()]);
+
+        let response =
+            block_on(inner.dispatch(request("rpc.discover"), Some(&user), None)).unwrap();
+        assert!(response.error.is_none());
+
+        let response =
+            block_on(inner.dispatch(request("rpc.discover"), Some(&user), None)).unwrap();
+        assert!(response.error.is_some());
+    }
+
+    fn handle(inner: &Inner<DiscoverDispatcher, DiscoverDispatcher>, request: Value) -> Value {
+        let user = reader(None);
+        let response =
+            block_on(inner.handle_raw_request(request.to_string().as_bytes(), Some(&user), None))
+                .unwrap();
+        serde_json::from_str(&response).unwrap()
+    }
+
+    #[test]
+    fn it_answers_batches_with_an_array_of_responses() {
+        let inner = inner(vec![reader(None)]);
+
+        let responses = handle(
+            &inner,
+            json!([
+                {"jsonrpc": "2.0", "method": "rpc.discover", "id": 1},
+                {"jsonrpc": "2.0", "method": "rpc.discover"},
+                {"jsonrpc": "2.0", "method": "sendRawTransaction", "params": ["00"], "id": 2},
+            ]),
+        );
+
+        // The notification doesn't get a response.
+        let responses = responses.as_array().unwrap();
+        assert_eq!(responses.len(), 2);
+        assert_eq!(responses[0]["id"], 1);
+        assert!(responses[0]["error"].is_null());
+        assert_eq!(responses[1]["id"], 2);
+        assert!(!responses[1]["error"].is_null());
+    }
+
+    #[test]
+    fn it_answers_invalid_requests_of_a_batch_with_errors() {
+        let inner = inner(vec![reader(None)]);
+
+        let responses = handle(
+            &inner,
+            json!([1, {"jsonrpc": "2.0", "method": "rpc.discover", "id": 1}]),
+        );
+
+        let responses = responses.as_array().unwrap();
+        assert_eq!(responses.len(), 2);
+        assert!(responses[0]["id"].is_null());
+        assert!(!responses[0]["error"].is_null());
+        assert!(responses[1]["error"].is_null());
+    }
+
+    #[test]
+    fn it_rejects_empty_and_oversized_batches() {
+        let inner = inner(vec![reader(None)]);
+
+        let response = handle(&inner, json!([]));
+        assert!(!response["error"].is_null());
+
+        let request = json!({"jsonrpc": "2.0", "method": "rpc.discover", "id": 1});
+        let response = handle(&inner, json!([request, request, request, request]));
+        assert!(!response["error"].is_null());
+    }
+
+    #[test]
+    fn it_does_not_answer_batches_of_notifications() {
+        let user = reader(None);
+        let inner = inner(vec![user.clone()]);
+        let request = json!([
+            {"jsonrpc": "2.0", "method": "rpc.discover"},
+            {"jsonrpc": "2.0", "method": "rpc.discover"},
+        ]);
+
+        let response =
+            block_on(inner.handle_raw_request(request.to_string().as_bytes(), Some(&user), None));
+        assert!(response.is_none());
+    }
+}
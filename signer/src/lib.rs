@@ -0,0 +1,95 @@
+#[macro_use]
+extern crate beserial_derive;
+#[macro_use]
+extern crate log;
+extern crate nimiq_block_albatross as block_albatross;
+extern crate nimiq_bls as bls;
+extern crate nimiq_database as database;
+extern crate nimiq_hash as hash;
+extern crate nimiq_keys as keys;
+
+use std::io;
+use std::sync::Arc;
+
+use thiserror::Error;
+
+use beserial::SerializingError;
+use nimiq_transaction::Transaction;
+
+pub use crate::local::LocalSigner;
+pub use crate::remote::{RemoteSigner, RemoteSignerConfig};
+pub use crate::request::SignRequest;
+pub use crate::server::SignerServer;
+pub use crate::transaction_policy::TransactionPolicy;
+pub use crate::transport::{SignerAddress, SignerListener};
+
+use crate::slashing_protection::SlashingProtectionError;
+
+mod local;
+pub mod protocol;
+mod remote;
+mod request;
+mod server;
+pub mod slashing_protection;
+mod transaction_policy;
+mod transport;
+
+/// Signs messages with the keys of a validator: the BLS signing key and, optionally, the wallet
+/// key that pays for the validator's transactions.
+///
+/// A remote signer blocks until the signer answers, so async code should sign with
+/// `sign_blocking`.
+pub trait Signer: Send + Sync {
+    /// The public key of the signing key.
+    fn public_key(&self) -> &bls::PublicKey;
+
+    /// Signs a message with the signing key.
+    fn sign(&self, request: &SignRequest) -> Result<bls::Signature, SignerError>;
+
+    /// The public key of the wallet key, if there is one.
+    fn wallet_public_key(&self) -> Option<&keys::PublicKey>;
+
+    /// Signs a transaction with the wallet key.
+    fn sign_transaction(&self, transaction: &Transaction) -> Result<keys::Signature, SignerError>;
+}
+
+/// Signs `request` on a thread for blocking tasks, so that waiting for a remote signer doesn't
+/// stall the executor.
+pub async fn sign_blocking(
+    signer: Arc<dyn Signer>,
+    request: SignRequest,
+) -> Result<bls::Signature, SignerError> {
+    tokio::task::spawn_blocking(move || signer.sign(&request))
+        .await
+        .map_err(|_| SignerError::TaskFailed)?
+}
+
+#[derive(Debug, Error)]
+pub enum SignerError {
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Serialization error: {0}")]
+    Serialization(#[from] SerializingError),
+
+    #[error("Unsupported protocol version: {0}")]
+    UnsupportedVersion(u8),
+
+    #[error("Authentication failed")]
+    AuthenticationFailed,
+
+    #[error("Unexpected message")]
+    UnexpectedMessage,
+
+    #[error("No wallet key")]
+    NoWalletKey,
+
+    #[error("{0}")]
+    SlashingProtection(#[from] SlashingProtectionError),
+
+    #[error("The signer's transaction policy doesn't allow signing this transaction")]
+    TransactionRefused,
+
+    #[error("Signing task failed")]
+    TaskFailed,
+}
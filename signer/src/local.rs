@@ -0,0 +1,40 @@
+use nimiq_transaction::Transaction;
+
+use crate::{SignRequest, Signer, SignerError};
+
+/// A signer that keeps the keys in the memory of this process.
+#[derive(Clone)]
+pub struct LocalSigner {
+    signing_key: bls::KeyPair,
+    wallet_key: Option<keys::KeyPair>,
+}
+
+impl LocalSigner {
+    pub fn new(signing_key: bls::KeyPair, wallet_key: Option<keys::KeyPair>) -> Self {
+        LocalSigner {
+            signing_key,
+            wallet_key,
+        }
+    }
+}
+
+impl Signer for LocalSigner {
+    fn public_key(&self) -> &bls::PublicKey {
+        &self.signing_key.public_key
+    }
+
+    fn sign(&self, request: &SignRequest) -> Result<bls::Signature, SignerError> {
+        Ok(self.signing_key.sign_hash(request.hash()))
+    }
+
+    fn wallet_public_key(&self) -> Option<&keys::PublicKey> {
+        self.wallet_key
+            .as_ref()
+            .map(|wallet_key| &wallet_key.public)
+    }
+
+    fn sign_transaction(&self, transaction: &Transaction) -> Result<keys::Signature, SignerError> {
+        let wallet_key = self.wallet_key.as_ref().ok_or(SignerError::NoWalletKey)?;
+        Ok(wallet_key.sign(&transaction.serialize_content()))
+    }
+}
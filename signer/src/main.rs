@@ -0,0 +1,178 @@
+extern crate nimiq_bls as bls;
+extern crate nimiq_database as database;
+extern crate nimiq_keys as keys;
+
+use std::process::exit;
+use std::str::FromStr;
+
+use clap::{crate_authors, crate_version, App, Arg};
+use failure::Error;
+use failure::Fail;
+
+use beserial::Deserialize;
+use database::lmdb::{open, LmdbEnvironment};
+use nimiq_primitives::account::ValidatorId;
+use nimiq_primitives::coin::Coin;
+use nimiq_signer::slashing_protection::SlashingProtection;
+use nimiq_signer::{LocalSigner, SignerAddress, SignerListener, SignerServer, TransactionPolicy};
+
+// A stand-in signer for tests and development setups. The keys are given on the command line,
+// so don't use this for keys that matter. Authentication keys can be created with `nimiq-address`.
+fn run_app() -> Result<(), Error> {
+    let matches = App::new("Nimiq signer")
+        .version(crate_version!())
+        .author(crate_authors!())
+        .about("Signs messages for a remote validator.")
+        .arg(
+            Arg::with_name("listen")
+                .short("l")
+                .long("listen")
+                .value_name("ADDRESS")
+                .help("Listen on ADDRESS, e.g. 127.0.0.1:8649 or unix:/path/to/socket.")
+                .takes_value(true)
+                .required(true),
+        )
+        .arg(
+            Arg::with_name("validator_key")
+                .short("k")
+                .long("validator-key")
+                .value_name("SECRET_KEY")
+                .help("The validator's BLS secret key as hex.")
+                .takes_value(true)
+                .required(true),
+        )
+        .arg(
+            Arg::with_name("wallet_key")
+                .short("w")
+                .long("wallet-key")
+                .value_name("PRIVATE_KEY")
+                .help("The validator's wallet private key as hex.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("validator_id")
+                .long("validator-id")
+                .value_name("VALIDATOR_ID")
+                .help("Sign unpark transactions for the validator with this id, as hex. Without it, or --sign-all-transactions, no transactions are signed.")
+                .takes_value(true)
+                .conflicts_with("sign_all_transactions"),
+        )
+        .arg(
+            Arg::with_name("max_unpark_fee")
+                .long("max-unpark-fee")
+                .value_name("NIM")
+                .help("The maximum fee of the unpark transactions that are signed. Defaults to 0.")
+                .takes_value(true)
+                .requires("validator_id"),
+        )
+        .arg(
+            Arg::with_name("sign_all_transactions")
+                .long("sign-all-transactions")
+                .help("Sign all transactions. This trusts the validator with the funds of the wallet key."),
+        )
+        .arg(
+            Arg::with_name("database")
+                .short("d")
+                .long("database")
+                .value_name("PATH")
+                .help("Path to the signer's slashing protection database. Use `nimiq-slashing-protection` to import the validator's high-water marks into it.")
+                .takes_value(true)
+                .required(true),
+        )
+        .arg(
+            Arg::with_name("auth_key")
+                .short("a")
+                .long("auth-key")
+                .value_name("PRIVATE_KEY")
+                .help("The private key with which the signer authenticates itself, as hex.")
+                .takes_value(true)
+                .required(true),
+        )
+        .arg(
+            Arg::with_name("client_key")
+                .short("c")
+                .long("client-key")
+                .value_name("PUBLIC_KEY")
+                .help("The public key of a validator that may connect, as hex. Can be repeated.")
+                .takes_value(true)
+                .multiple(true)
+                .required(true),
+        )
+        .get_matches();
+
+    let address = SignerAddress::from_str(matches.value_of("listen").ok_or(AppError::Listen)?)?;
+    let signing_key = bls::KeyPair::from(bls::SecretKey::deserialize_from_vec(&hex::decode(
+        matches
+            .value_of("validator_key")
+            .ok_or(AppError::ValidatorKey)?,
+    )?)?);
+    let wallet_key = match matches.value_of("wallet_key") {
+        Some(wallet_key) => Some(keys::KeyPair::from(keys::PrivateKey::from_str(wallet_key)?)),
+        None => None,
+    };
+    let transaction_policy = match matches.value_of("validator_id") {
+        Some(validator_id) => TransactionPolicy::Unpark {
+            validator_id: ValidatorId::from_str(validator_id)?,
+            max_fee: match matches.value_of("max_unpark_fee") {
+                Some(max_fee) => Coin::from_str(max_fee)?,
+                None => Coin::ZERO,
+            },
+        },
+        None if matches.is_present("sign_all_transactions") => TransactionPolicy::SignAll,
+        None => TransactionPolicy::RefuseAll,
+    };
+    let auth_key = keys::KeyPair::from(keys::PrivateKey::from_str(
+        matches.value_of("auth_key").ok_or(AppError::AuthKey)?,
+    )?);
+    let client_keys = matches
+        .values_of("client_key")
+        .ok_or(AppError::ClientKey)?
+        .map(keys::PublicKey::from_str)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let env = LmdbEnvironment::new(
+        matches.value_of("database").ok_or(AppError::DatabasePath)?,
+        10 * 1024 * 1024,
+        SlashingProtection::NUM_DATABASES,
+        open::Flags::empty(),
+    )?;
+
+    let listener = SignerListener::bind(&address)?;
+    println!("Listening on {}", listener.local_address()?);
+    println!("Authentication key: {}", auth_key.public.to_hex());
+
+    SignerServer::new(
+        LocalSigner::new(signing_key, wallet_key),
+        SlashingProtection::new(env),
+        transaction_policy,
+        auth_key,
+        client_keys,
+    )
+    .serve(listener)?;
+
+    Ok(())
+}
+
+fn main() {
+    exit(match run_app() {
+        Ok(_) => 0,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            1
+        }
+    });
+}
+
+#[derive(Debug, Fail)]
+enum AppError {
+    #[fail(display = "Listen address is missing")]
+    Listen,
+    #[fail(display = "Validator key is missing")]
+    ValidatorKey,
+    #[fail(display = "Database path is missing")]
+    DatabasePath,
+    #[fail(display = "Authentication key is missing")]
+    AuthKey,
+    #[fail(display = "Client key is missing")]
+    ClientKey,
+}
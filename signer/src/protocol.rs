@@ -0,0 +1,357 @@
+//! The protocol between a validator and a remote signer.
+//!
+//! Both sides own an Ed25519 authentication key and know the public key of the other side. When a
+//! connection is opened, each side proves that it owns its key by signing the nonces of both
+//! sides. Afterwards, every message is signed together with these nonces and a counter, so that
+//! messages can neither be tampered with nor replayed. The messages are not encrypted, since they
+//! only contain public keys, signatures and messages that the validator publishes anyway.
+//!
+//! The validator sends the messages it wants to have signed, not their hashes. The signer checks
+//! the slashable ones against its own slashing protection and refuses to sign them if they
+//! conflict with something it signed before. Transactions are only signed if the signer's
+//! `TransactionPolicy` allows them.
+//!
+//! Every message is sent as a big-endian `u32` length followed by the serialized message.
+
+use std::io::{Read, Write};
+
+use rand::{thread_rng, RngCore};
+
+use beserial::{Deserialize, Serialize};
+use nimiq_macros::{add_hex_io_fns_typed_arr, create_typed_array};
+use nimiq_utils::tagged_signing::TaggedSignable;
+
+use nimiq_transaction::Transaction;
+
+use crate::slashing_protection::SlashingProtectionError;
+use crate::{SignRequest, SignerError};
+
+/// The version of the protocol. Both sides must use the same version.
+pub const PROTOCOL_VERSION: u8 = 3;
+
+/// The maximum size of a single message.
+const MAX_MESSAGE_SIZE: usize = 1024 * 1024;
+
+create_typed_array!(HandshakeNonce, u8, 32);
+add_hex_io_fns_typed_arr!(HandshakeNonce, HandshakeNonce::SIZE);
+
+impl HandshakeNonce {
+    pub fn generate() -> Self {
+        let mut nonce = Self::default();
+
+        thread_rng().fill_bytes(&mut nonce.0);
+
+        nonce
+    }
+}
+
+/// The side of a connection.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[repr(u8)]
+pub enum Role {
+    Client = 0,
+    Server = 1,
+}
+
+impl Role {
+    fn other(self) -> Self {
+        match self {
+            Role::Client => Role::Server,
+            Role::Server => Role::Client,
+        }
+    }
+}
+
+/// Opens the handshake. Sent by the client.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ClientHello {
+    pub version: u8,
+    pub nonce: HandshakeNonce,
+}
+
+/// Answers the `ClientHello` and authenticates the server.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ServerHello {
+    pub version: u8,
+    pub nonce: HandshakeNonce,
+    pub public_key: keys::PublicKey,
+    pub signature: keys::Signature,
+}
+
+/// Authenticates the client and completes the handshake.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ClientAuth {
+    pub public_key: keys::PublicKey,
+    pub signature: keys::Signature,
+}
+
+/// What each side signs during the handshake to prove that it owns its authentication key.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct HandshakeProof {
+    role: Role,
+    client_nonce: HandshakeNonce,
+    server_nonce: HandshakeNonce,
+}
+
+impl TaggedSignable for HandshakeProof {
+    const TAG: u8 = 0x04;
+}
+
+/// What each side signs for every message after the handshake.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct MessageProof {
+    role: Role,
+    client_nonce: HandshakeNonce,
+    server_nonce: HandshakeNonce,
+    counter: u64,
+    #[beserial(len_type(u32))]
+    payload: Vec<u8>,
+}
+
+impl TaggedSignable for MessageProof {
+    const TAG: u8 = 0x05;
+}
+
+/// A message after the handshake, together with its signature.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct AuthenticatedMessage {
+    counter: u64,
+    #[beserial(len_type(u32))]
+    payload: Vec<u8>,
+    signature: keys::Signature,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[repr(u8)]
+pub enum SignerRequest {
+    #[beserial(discriminant = 0)]
+    PublicKeys,
+    #[beserial(discriminant = 1)]
+    Sign(SignRequest),
+    #[beserial(discriminant = 2)]
+    SignTransaction(Transaction),
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[repr(u8)]
+pub enum SignerResponse {
+    #[beserial(discriminant = 0)]
+    PublicKeys {
+        public_key: bls::PublicKey,
+        wallet_public_key: Option<keys::PublicKey>,
+    },
+    #[beserial(discriminant = 1)]
+    Signature(bls::Signature),
+    #[beserial(discriminant = 2)]
+    WalletSignature(keys::Signature),
+    #[beserial(discriminant = 3)]
+    NoWalletKey,
+    /// Signing the message could get the validator slashed.
+    #[beserial(discriminant = 4)]
+    Refused(SlashingProtectionError),
+    /// The signer's transaction policy doesn't allow signing the transaction.
+    #[beserial(discriminant = 5)]
+    TransactionRefused,
+}
+
+fn write_message<S: Write, M: Serialize>(stream: &mut S, message: &M) -> Result<(), SignerError> {
+    let data = message.serialize_to_vec();
+    stream.write_all(&(data.len() as u32).to_be_bytes())?;
+    stream.write_all(&data)?;
+    stream.flush()?;
+    Ok(())
+}
+
+fn read_message<S: Read, M: Deserialize>(stream: &mut S) -> Result<M, SignerError> {
+    let mut length = [0u8; 4];
+    stream.read_exact(&mut length)?;
+    let length = u32::from_be_bytes(length) as usize;
+    if length > MAX_MESSAGE_SIZE {
+        return Err(SignerError::UnexpectedMessage);
+    }
+
+    let mut data = vec![0u8; length];
+    stream.read_exact(&mut data)?;
+    Ok(Deserialize::deserialize_from_vec(&data)?)
+}
+
+/// An authenticated connection between a validator and a signer.
+pub struct Channel<S> {
+    stream: S,
+    role: Role,
+    key_pair: keys::KeyPair,
+    peer_public_key: keys::PublicKey,
+    client_nonce: HandshakeNonce,
+    server_nonce: HandshakeNonce,
+    send_counter: u64,
+    receive_counter: u64,
+}
+
+impl<S: Read + Write> Channel<S> {
+    /// Performs the handshake on the client side. The signer must authenticate with
+    /// `server_public_key`.
+    pub fn connect(
+        mut stream: S,
+        key_pair: keys::KeyPair,
+        server_public_key: &keys::PublicKey,
+    ) -> Result<Self, SignerError> {
+        let client_nonce = HandshakeNonce::generate();
+        write_message(
+            &mut stream,
+            &ClientHello {
+                version: PROTOCOL_VERSION,
+                nonce: client_nonce.clone(),
+            },
+        )?;
+
+        let server_hello: ServerHello = read_message(&mut stream)?;
+        if server_hello.version != PROTOCOL_VERSION {
+            return Err(SignerError::UnsupportedVersion(server_hello.version));
+        }
+        let server_proof = HandshakeProof {
+            role: Role::Server,
+            client_nonce: client_nonce.clone(),
+            server_nonce: server_hello.nonce.clone(),
+        };
+        if &server_hello.public_key != server_public_key
+            || !server_public_key.verify(&server_hello.signature, &server_proof.message_data())
+        {
+            return Err(SignerError::AuthenticationFailed);
+        }
+
+        let client_proof = HandshakeProof {
+            role: Role::Client,
+            client_nonce: client_nonce.clone(),
+            server_nonce: server_hello.nonce.clone(),
+        };
+        write_message(
+            &mut stream,
+            &ClientAuth {
+                public_key: key_pair.public,
+                signature: key_pair.sign(&client_proof.message_data()),
+            },
+        )?;
+
+        Ok(Channel {
+            stream,
+            role: Role::Client,
+            key_pair,
+            peer_public_key: *server_public_key,
+            client_nonce,
+            server_nonce: server_hello.nonce,
+            send_counter: 0,
+            receive_counter: 0,
+        })
+    }
+
+    /// Performs the handshake on the server side. The client must authenticate with one of
+    /// `client_public_keys`.
+    pub fn accept(
+        mut stream: S,
+        key_pair: keys::KeyPair,
+        client_public_keys: &[keys::PublicKey],
+    ) -> Result<Self, SignerError> {
+        let client_hello: ClientHello = read_message(&mut stream)?;
+        if client_hello.version != PROTOCOL_VERSION {
+            return Err(SignerError::UnsupportedVersion(client_hello.version));
+        }
+
+        let server_nonce = HandshakeNonce::generate();
+        let server_proof = HandshakeProof {
+            role: Role::Server,
+            client_nonce: client_hello.nonce.clone(),
+            server_nonce: server_nonce.clone(),
+        };
+        write_message(
+            &mut stream,
+            &ServerHello {
+                version: PROTOCOL_VERSION,
+                nonce: server_nonce.clone(),
+                public_key: key_pair.public,
+                signature: key_pair.sign(&server_proof.message_data()),
+            },
+        )?;
+
+        let client_auth: ClientAuth = read_message(&mut stream)?;
+        let client_proof = HandshakeProof {
+            role: Role::Client,
+            client_nonce: client_hello.nonce.clone(),
+            server_nonce: server_nonce.clone(),
+        };
+        if !client_public_keys.contains(&client_auth.public_key)
+            || !client_auth
+                .public_key
+                .verify(&client_auth.signature, &client_proof.message_data())
+        {
+            return Err(SignerError::AuthenticationFailed);
+        }
+
+        Ok(Channel {
+            stream,
+            role: Role::Server,
+            key_pair,
+            peer_public_key: client_auth.public_key,
+            client_nonce: client_hello.nonce,
+            server_nonce,
+            send_counter: 0,
+            receive_counter: 0,
+        })
+    }
+
+    /// The underlying stream, e.g. to change its timeouts after the handshake.
+    pub fn stream_mut(&mut self) -> &mut S {
+        &mut self.stream
+    }
+
+    /// The authentication key of the other side.
+    pub fn peer_public_key(&self) -> &keys::PublicKey {
+        &self.peer_public_key
+    }
+
+    pub fn send<M: Serialize>(&mut self, message: &M) -> Result<(), SignerError> {
+        let proof = MessageProof {
+            role: self.role,
+            client_nonce: self.client_nonce.clone(),
+            server_nonce: self.server_nonce.clone(),
+            counter: self.send_counter,
+            payload: message.serialize_to_vec(),
+        };
+        let signature = self.key_pair.sign(&proof.message_data());
+
+        write_message(
+            &mut self.stream,
+            &AuthenticatedMessage {
+                counter: proof.counter,
+                payload: proof.payload,
+                signature,
+            },
+        )?;
+        self.send_counter += 1;
+
+        Ok(())
+    }
+
+    pub fn receive<M: Deserialize>(&mut self) -> Result<M, SignerError> {
+        let message: AuthenticatedMessage = read_message(&mut self.stream)?;
+        if message.counter != self.receive_counter {
+            return Err(SignerError::AuthenticationFailed);
+        }
+
+        let proof = MessageProof {
+            role: self.role.other(),
+            client_nonce: self.client_nonce.clone(),
+            server_nonce: self.server_nonce.clone(),
+            counter: message.counter,
+            payload: message.payload,
+        };
+        if !self
+            .peer_public_key
+            .verify(&message.signature, &proof.message_data())
+        {
+            return Err(SignerError::AuthenticationFailed);
+        }
+        self.receive_counter += 1;
+
+        Ok(Deserialize::deserialize_from_vec(&proof.payload)?)
+    }
+}
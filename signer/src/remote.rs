@@ -0,0 +1,116 @@
+use std::time::Duration;
+
+use parking_lot::Mutex;
+
+use nimiq_transaction::Transaction;
+
+use crate::protocol::{Channel, SignerRequest, SignerResponse};
+use crate::transport::{SignerAddress, SignerStream};
+use crate::{SignRequest, Signer, SignerError};
+
+#[derive(Clone, Debug)]
+pub struct RemoteSignerConfig {
+    /// The address the signer listens on.
+    pub address: SignerAddress,
+    /// The key with which the validator authenticates itself to the signer.
+    pub auth_key: keys::KeyPair,
+    /// The key with which the signer must authenticate itself.
+    pub server_key: keys::PublicKey,
+    /// How long to wait for the signer to answer a request.
+    pub timeout: Duration,
+}
+
+impl RemoteSignerConfig {
+    pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(2);
+}
+
+/// A signer that keeps the keys in a separate signer process, possibly on another host. See
+/// `protocol` for how the validator and the signer talk to each other.
+///
+/// Every request blocks until the signer answers or the timeout expires.
+pub struct RemoteSigner {
+    config: RemoteSignerConfig,
+    public_key: bls::PublicKey,
+    wallet_public_key: Option<keys::PublicKey>,
+    // The connection to the signer. If a request fails, the connection is dropped and opened again.
+    channel: Mutex<Option<Channel<SignerStream>>>,
+}
+
+impl RemoteSigner {
+    /// Connects to the signer and asks it for the public keys.
+    pub fn connect(config: RemoteSignerConfig) -> Result<Self, SignerError> {
+        let mut channel = Self::open_channel(&config)?;
+        channel.send(&SignerRequest::PublicKeys)?;
+
+        match channel.receive()? {
+            SignerResponse::PublicKeys {
+                public_key,
+                wallet_public_key,
+            } => Ok(RemoteSigner {
+                config,
+                public_key,
+                wallet_public_key,
+                channel: Mutex::new(Some(channel)),
+            }),
+            _ => Err(SignerError::UnexpectedMessage),
+        }
+    }
+
+    fn open_channel(config: &RemoteSignerConfig) -> Result<Channel<SignerStream>, SignerError> {
+        let stream = SignerStream::connect(&config.address, config.timeout)?;
+        Channel::connect(stream, config.auth_key.clone(), &config.server_key)
+    }
+
+    fn request(&self, request: &SignerRequest) -> Result<SignerResponse, SignerError> {
+        let mut channel = self.channel.lock();
+
+        // The signer closes idle connections, so a request that fails on an existing connection is
+        // tried once more on a new one.
+        if let Some(connection) = channel.as_mut() {
+            match connection.send(request).and_then(|_| connection.receive()) {
+                Ok(response) => return Ok(response),
+                Err(e) => {
+                    debug!("Request to signer failed: {}", e);
+                    *channel = None;
+                }
+            }
+        }
+
+        debug!("Reconnecting to signer at {}", self.config.address);
+        let mut connection = Self::open_channel(&self.config)?;
+        let response = connection
+            .send(request)
+            .and_then(|_| connection.receive())?;
+        *channel = Some(connection);
+
+        Ok(response)
+    }
+}
+
+impl Signer for RemoteSigner {
+    fn public_key(&self) -> &bls::PublicKey {
+        &self.public_key
+    }
+
+    fn sign(&self, request: &SignRequest) -> Result<bls::Signature, SignerError> {
+        match self.request(&SignerRequest::Sign(request.clone()))? {
+            SignerResponse::Signature(signature) => Ok(signature),
+            SignerResponse::Refused(e) => Err(SignerError::SlashingProtection(e)),
+            SignerResponse::TransactionRefused => Err(SignerError::TransactionRefused),
+            _ => Err(SignerError::UnexpectedMessage),
+        }
+    }
+
+    fn wallet_public_key(&self) -> Option<&keys::PublicKey> {
+        self.wallet_public_key.as_ref()
+    }
+
+    fn sign_transaction(&self, transaction: &Transaction) -> Result<keys::Signature, SignerError> {
+        match self.request(&SignerRequest::SignTransaction(transaction.clone()))? {
+            SignerResponse::WalletSignature(signature) => Ok(signature),
+            SignerResponse::NoWalletKey => Err(SignerError::NoWalletKey),
+            SignerResponse::TransactionRefused => Err(SignerError::TransactionRefused),
+            _ => Err(SignerError::UnexpectedMessage),
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use std::io::Write;
+
+use beserial::{Deserialize, Serialize, WriteBytesExt};
+use block_albatross::{
+    Message, MicroHeader, TendermintProposal, TendermintVote, ViewChange, PREFIX_VALIDATOR_INFO,
+};
+use hash::{Blake2bHash, Blake2sHasher, Hash, Hasher};
+use nimiq_transaction::Transaction;
+use nimiq_vrf::VrfSeed;
+
+use crate::slashing_protection::{SignedPosition, SigningKind};
+
+/// A message that the validator wants to have signed with its signing key.
+///
+/// The signer is given the message itself instead of its hash, so that it only ever signs the
+/// messages a validator signs, and can check the slashable ones against its own slashing
+/// protection.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[repr(u8)]
+pub enum SignRequest {
+    /// The header of a micro block produced by the validator.
+    #[beserial(discriminant = 0)]
+    MicroBlock(MicroHeader),
+    /// The seed of the previous block, to get the seed of the next block.
+    #[beserial(discriminant = 1)]
+    Seed(VrfSeed),
+    #[beserial(discriminant = 2)]
+    ViewChange(ViewChange),
+    /// A Tendermint proposal and the round it is proposed in. The round isn't part of the signed
+    /// message, so the signer has to trust the validator with it.
+    #[beserial(discriminant = 3)]
+    TendermintProposal {
+        proposal: TendermintProposal,
+        round: u32,
+    },
+    #[beserial(discriminant = 4)]
+    TendermintVote(TendermintVote),
+    /// The serialized record with which the validator announces its peer id to the other
+    /// validators.
+    #[beserial(discriminant = 5)]
+    ValidatorRecord(#[beserial(len_type(u16))] Vec<u8>),
+    /// A transaction that needs the validator's signature, e.g. to unpark it.
+    #[beserial(discriminant = 6)]
+    Transaction(Transaction),
+}
+
+impl SignRequest {
+    /// The hash that is signed for this request.
+    pub fn hash(&self) -> bls::SigHash {
+        match self {
+            SignRequest::MicroBlock(header) => header.hash(),
+            SignRequest::Seed(prev_seed) => prev_seed.next_hash(),
+            SignRequest::ViewChange(view_change) => view_change.hash_with_prefix(),
+            SignRequest::TendermintProposal { proposal, .. } => proposal.hash_with_prefix(),
+            SignRequest::TendermintVote(vote) => vote.hash(),
+            SignRequest::ValidatorRecord(record) => {
+                let mut hasher = Blake2sHasher::new();
+                hasher
+                    .write_u8(PREFIX_VALIDATOR_INFO)
+                    .expect("Failed to write prefix to hasher for signature.");
+                hasher
+                    .write_all(record)
+                    .expect("Failed to write message to hasher for signature.");
+                hasher.finish()
+            }
+            SignRequest::Transaction(transaction) => {
+                transaction.serialize_content().as_slice().hash()
+            }
+        }
+    }
+
+    /// The position at which signing this request could get the validator slashed, or `None` if
+    /// it can't.
+    pub fn signed_position(&self) -> Option<(SigningKind, SignedPosition)> {
+        match self {
+            SignRequest::MicroBlock(header) => Some((
+                SigningKind::MicroBlock,
+                SignedPosition {
+                    block_number: header.block_number,
+                    round: header.view_number,
+                    hash: header.hash::<Blake2bHash>(),
+                },
+            )),
+            SignRequest::ViewChange(view_change) => Some((
+                SigningKind::ViewChange,
+                SignedPosition {
+                    block_number: view_change.block_number,
+                    round: view_change.new_view_number,
+                    hash: view_change.hash::<Blake2bHash>(),
+                },
+            )),
+            SignRequest::TendermintProposal { proposal, round } => Some((
+                SigningKind::TendermintProposal,
+                SignedPosition {
+                    block_number: proposal.value.block_number,
+                    round: *round,
+                    hash: proposal.value.hash::<Blake2bHash>(),
+                },
+            )),
+            SignRequest::TendermintVote(vote) => Some((
+                SigningKind::from_tendermint_step(vote.id.step),
+                SignedPosition {
+                    block_number: vote.id.block_number,
+                    round: vote.id.round_number,
+                    hash: vote.hash::<Blake2bHash>(),
+                },
+            )),
+            SignRequest::Seed(_)
+            | SignRequest::ValidatorRecord(_)
+            | SignRequest::Transaction(_) => None,
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use std::io;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use hash::{Blake2bHash, Hash};
+
+use crate::protocol::{Channel, SignerRequest, SignerResponse};
+use crate::slashing_protection::SlashingProtection;
+use crate::transport::{SignerListener, SignerStream};
+use crate::{LocalSigner, SignRequest, Signer, SignerError, TransactionPolicy};
+
+/// Serves the keys of a `LocalSigner` to remote validators. Only validators whose authentication
+/// key is in `client_keys` are served.
+///
+/// Slashable messages are checked against the signer's own `slashing_protection` before they are
+/// signed, so that a compromised validator host can't make it sign two different messages at the
+/// same position. Transactions are only signed if the `transaction_policy` allows them.
+pub struct SignerServer {
+    signer: LocalSigner,
+    slashing_protection: SlashingProtection,
+    transaction_policy: TransactionPolicy,
+    auth_key: keys::KeyPair,
+    client_keys: Vec<keys::PublicKey>,
+}
+
+impl SignerServer {
+    /// How long a client has to complete the handshake.
+    pub const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+    /// How long a connection may be idle, or a write may take, before the connection is closed.
+    pub const CONNECTION_TIMEOUT: Duration = Duration::from_secs(300);
+    /// The maximum number of connections that are served at the same time. Further connections
+    /// are closed right away.
+    pub const MAX_CONNECTIONS: usize = 16;
+
+    pub fn new(
+        signer: LocalSigner,
+        slashing_protection: SlashingProtection,
+        transaction_policy: TransactionPolicy,
+        auth_key: keys::KeyPair,
+        client_keys: Vec<keys::PublicKey>,
+    ) -> Self {
+        SignerServer {
+            signer,
+            slashing_protection,
+            transaction_policy,
+            auth_key,
+            client_keys,
+        }
+    }
+
+    /// Accepts connections on `listener` and serves each of them on its own thread. This only
+    /// returns if accepting a connection fails.
+    pub fn serve(self, listener: SignerListener) -> io::Result<()> {
+        let server = Arc::new(self);
+        let num_connections = Arc::new(AtomicUsize::new(0));
+        loop {
+            let stream = listener.accept(Self::CONNECTION_TIMEOUT)?;
+            if num_connections.fetch_add(1, Ordering::AcqRel) >= Self::MAX_CONNECTIONS {
+                num_connections.fetch_sub(1, Ordering::AcqRel);
+                warn!(
+                    "Refusing connection, because {} connections are open already",
+                    Self::MAX_CONNECTIONS
+                );
+                continue;
+            }
+
+            let server = Arc::clone(&server);
+            let num_connections = Arc::clone(&num_connections);
+            thread::spawn(move || {
+                if let Err(e) = server.handle_connection(stream) {
+                    warn!("Signer connection closed: {}", e);
+                }
+                num_connections.fetch_sub(1, Ordering::AcqRel);
+            });
+        }
+    }
+
+    fn handle_connection(&self, mut stream: SignerStream) -> Result<(), SignerError> {
+        // Until the client is authenticated, it must not keep the connection open for long.
+        stream.set_deadline(Some(Instant::now() + Self::HANDSHAKE_TIMEOUT))?;
+        let mut channel = Channel::accept(stream, self.auth_key.clone(), &self.client_keys)?;
+        channel.stream_mut().set_deadline(None)?;
+        info!("Validator {:?} connected", channel.peer_public_key());
+
+        loop {
+            let request = match channel.receive() {
+                Ok(request) => request,
+                // The validator closed the connection.
+                Err(SignerError::Io(ref e)) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                    return Ok(())
+                }
+                Err(e) => return Err(e),
+            };
+
+            let response = self.handle_request(request)?;
+            channel.send(&response)?;
+        }
+    }
+
+    fn handle_request(&self, request: SignerRequest) -> Result<SignerResponse, SignerError> {
+        match request {
+            SignerRequest::PublicKeys => Ok(SignerResponse::PublicKeys {
+                public_key: *self.signer.public_key(),
+                wallet_public_key: self.signer.wallet_public_key().copied(),
+            }),
+            SignerRequest::Sign(request) => {
+                trace!("Signing {:?}", request);
+                if let SignRequest::Transaction(transaction) = &request {
+                    if !self.transaction_policy.allows(transaction) {
+                        warn!(
+                            "Refusing to sign transaction {} with the signing key",
+                            transaction.hash::<Blake2bHash>()
+                        );
+                        return Ok(SignerResponse::TransactionRefused);
+                    }
+                }
+                if let Some((kind, position)) = request.signed_position() {
+                    if let Err(e) = self.slashing_protection.check_and_record(
+                        kind,
+                        position.block_number,
+                        position.round,
+                        &position.hash,
+                    ) {
+                        warn!("{}", e);
+                        return Ok(SignerResponse::Refused(e));
+                    }
+                }
+                Ok(SignerResponse::Signature(self.signer.sign(&request)?))
+            }
+            SignerRequest::SignTransaction(transaction) => {
+                if !self.transaction_policy.allows(&transaction) {
+                    warn!(
+                        "Refusing to sign transaction {} with the wallet key",
+                        transaction.hash::<Blake2bHash>()
+                    );
+                    return Ok(SignerResponse::TransactionRefused);
+                }
+                trace!(
+                    "Signing transaction {} with the wallet key",
+                    transaction.hash::<Blake2bHash>()
+                );
+                match self.signer.sign_transaction(&transaction) {
+                    Ok(signature) => Ok(SignerResponse::WalletSignature(signature)),
+                    Err(SignerError::NoWalletKey) => Ok(SignerResponse::NoWalletKey),
+                    Err(e) => Err(e),
+                }
+            }
+        }
+    }
+}
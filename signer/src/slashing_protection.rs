@@ -0,0 +1,212 @@
+use std::fmt;
+use std::io;
+
+use beserial::{Deserialize, Serialize};
+use block_albatross::TendermintStep;
+use database::{
+    Database, Environment, FromDatabaseValue, IntoDatabaseValue, ReadTransaction, WriteTransaction,
+};
+use hash::Blake2bHash;
+
+/// The kinds of messages that the validator signs with its signing key. A separate high-water mark
+/// is kept for each of them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[repr(u8)]
+pub enum SigningKind {
+    MicroBlock = 0,
+    ViewChange = 1,
+    TendermintProposal = 2,
+    TendermintPrevote = 3,
+    TendermintPrecommit = 4,
+}
+
+impl SigningKind {
+    pub const ALL: [SigningKind; 5] = [
+        SigningKind::MicroBlock,
+        SigningKind::ViewChange,
+        SigningKind::TendermintProposal,
+        SigningKind::TendermintPrevote,
+        SigningKind::TendermintPrecommit,
+    ];
+
+    /// Returns the kind of a Tendermint message for the given step.
+    pub fn from_tendermint_step(step: TendermintStep) -> Self {
+        match step {
+            TendermintStep::Propose => SigningKind::TendermintProposal,
+            TendermintStep::PreVote => SigningKind::TendermintPrevote,
+            TendermintStep::PreCommit => SigningKind::TendermintPrecommit,
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            SigningKind::MicroBlock => "microBlock",
+            SigningKind::ViewChange => "viewChange",
+            SigningKind::TendermintProposal => "tendermintProposal",
+            SigningKind::TendermintPrevote => "tendermintPrevote",
+            SigningKind::TendermintPrecommit => "tendermintPrecommit",
+        }
+    }
+}
+
+/// The position of the last message of a kind that was signed. The round is the view number for
+/// micro blocks and view changes and the Tendermint round otherwise.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedPosition {
+    pub block_number: u32,
+    pub round: u32,
+    pub hash: Blake2bHash,
+}
+
+impl SignedPosition {
+    fn is_above(&self, other: &SignedPosition) -> bool {
+        (self.block_number, self.round) > (other.block_number, other.round)
+    }
+}
+
+impl IntoDatabaseValue for SignedPosition {
+    fn database_byte_size(&self) -> usize {
+        self.serialized_size()
+    }
+
+    fn copy_into_database(&self, mut bytes: &mut [u8]) {
+        Serialize::serialize(&self, &mut bytes).unwrap();
+    }
+}
+
+impl FromDatabaseValue for SignedPosition {
+    fn copy_from_database(bytes: &[u8]) -> io::Result<Self>
+    where
+        Self: Sized,
+    {
+        let mut cursor = io::Cursor::new(bytes);
+        Ok(Deserialize::deserialize(&mut cursor)?)
+    }
+}
+
+/// A single high-water mark, as it is exported and imported.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SlashingProtectionRecord {
+    pub kind: SigningKind,
+    pub position: SignedPosition,
+}
+
+/// All high-water marks of a validator. This is what is exported from one machine and imported
+/// on another one when migrating a validator.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SlashingProtectionRecords {
+    #[beserial(len_type(u8))]
+    pub records: Vec<SlashingProtectionRecord>,
+}
+
+/// The error that is returned if signing a message could get the validator slashed.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SlashingProtectionError {
+    pub kind: SigningKind,
+    pub block_number: u32,
+    pub round: u32,
+    pub signed: SignedPosition,
+}
+
+impl fmt::Display for SlashingProtectionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Refusing to sign {:?} at #{}.{}, a different message was already signed at #{}.{}",
+            self.kind, self.block_number, self.round, self.signed.block_number, self.signed.round
+        )
+    }
+}
+
+impl std::error::Error for SlashingProtectionError {}
+
+/// A persistent store of the high-water marks of everything the validator signed. Every signing
+/// path asks it before signing, so that a restarted or duplicated node doesn't sign two different
+/// messages for the same position.
+pub struct SlashingProtection {
+    env: Environment,
+    database: Database,
+}
+
+impl SlashingProtection {
+    /// The number of databases the slashing protection opens in its environment.
+    pub const NUM_DATABASES: u32 = 1;
+
+    const DB_NAME: &'static str = "ValidatorSlashingProtection";
+
+    pub fn new(env: Environment) -> Self {
+        let database = env.open_database(Self::DB_NAME.to_string());
+        SlashingProtection { env, database }
+    }
+
+    /// Checks that signing the message with the given hash at the given position can't get the
+    /// validator slashed and, if so, records it as the new high-water mark. Signing the exact same
+    /// message again is allowed.
+    pub fn check_and_record(
+        &self,
+        kind: SigningKind,
+        block_number: u32,
+        round: u32,
+        hash: &Blake2bHash,
+    ) -> Result<(), SlashingProtectionError> {
+        let position = SignedPosition {
+            block_number,
+            round,
+            hash: hash.clone(),
+        };
+
+        let mut txn = WriteTransaction::new(&self.env);
+        if let Some(signed) = txn.get::<str, SignedPosition>(&self.database, kind.as_str()) {
+            if signed == position {
+                return Ok(());
+            }
+            if !position.is_above(&signed) {
+                return Err(SlashingProtectionError {
+                    kind,
+                    block_number,
+                    round,
+                    signed,
+                });
+            }
+        }
+        txn.put_reserve(&self.database, kind.as_str(), &position);
+        txn.commit();
+
+        Ok(())
+    }
+
+    /// Returns the high-water mark for the given kind of message.
+    pub fn get(&self, kind: SigningKind) -> Option<SignedPosition> {
+        ReadTransaction::new(&self.env).get(&self.database, kind.as_str())
+    }
+
+    /// Exports all high-water marks.
+    pub fn export(&self) -> SlashingProtectionRecords {
+        let txn = ReadTransaction::new(&self.env);
+        let records = SigningKind::ALL
+            .iter()
+            .filter_map(|&kind| {
+                txn.get(&self.database, kind.as_str())
+                    .map(|position| SlashingProtectionRecord { kind, position })
+            })
+            .collect();
+        SlashingProtectionRecords { records }
+    }
+
+    /// Imports high-water marks, e.g. from another machine. Existing high-water marks are only
+    /// replaced if the imported ones are higher, so importing never weakens the protection.
+    pub fn import(&self, records: &SlashingProtectionRecords) {
+        let mut txn = WriteTransaction::new(&self.env);
+        for record in &records.records {
+            let key = record.kind.as_str();
+            let replace = match txn.get::<str, SignedPosition>(&self.database, key) {
+                Some(signed) => record.position.is_above(&signed),
+                None => true,
+            };
+            if replace {
+                txn.put_reserve(&self.database, key, &record.position);
+            }
+        }
+        txn.commit();
+    }
+}
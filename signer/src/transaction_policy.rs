@@ -0,0 +1,48 @@
+use nimiq_primitives::account::{AccountType, ValidatorId};
+use nimiq_primitives::coin::Coin;
+use nimiq_transaction::account::staking_contract::IncomingStakingTransactionData;
+use nimiq_transaction::Transaction;
+
+/// The transactions that a `SignerServer` signs for a validator, with either of its keys.
+///
+/// The validator itself only needs unpark transactions to be signed. Signing any other transaction
+/// would let a compromised validator host spend the funds of the wallet key.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TransactionPolicy {
+    /// Don't sign any transactions.
+    RefuseAll,
+    /// Only sign transactions that unpark the validator `validator_id`, don't transfer any value
+    /// and pay a fee of at most `max_fee`. A compromised validator host can still have any number
+    /// of those signed, so `max_fee` should be low.
+    Unpark {
+        validator_id: ValidatorId,
+        max_fee: Coin,
+    },
+    /// Sign all transactions. This trusts the validator host with the funds of the wallet key.
+    SignAll,
+}
+
+impl TransactionPolicy {
+    /// Whether `transaction` may be signed under this policy.
+    pub fn allows(&self, transaction: &Transaction) -> bool {
+        match self {
+            TransactionPolicy::RefuseAll => false,
+            TransactionPolicy::Unpark {
+                validator_id,
+                max_fee,
+            } => {
+                transaction.recipient_type == AccountType::Staking
+                    && transaction.value == Coin::ZERO
+                    && transaction.fee <= *max_fee
+                    && matches!(
+                        IncomingStakingTransactionData::parse(transaction),
+                        Ok(IncomingStakingTransactionData::UnparkValidator {
+                            validator_id: ref unparked_id,
+                            ..
+                        }) if unparked_id == validator_id
+                    )
+            }
+            TransactionPolicy::SignAll => true,
+        }
+    }
+}
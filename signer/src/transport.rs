@@ -0,0 +1,205 @@
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::net::{TcpListener, TcpStream};
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+#[cfg(unix)]
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+/// The address of a signer. Signers listen either on a TCP address, e.g. `127.0.0.1:8649`, or on
+/// a Unix socket, e.g. `unix:/run/nimiq/signer.sock`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SignerAddress {
+    Tcp(String),
+    #[cfg(unix)]
+    Unix(PathBuf),
+}
+
+impl SignerAddress {
+    const UNIX_PREFIX: &'static str = "unix:";
+}
+
+impl FromStr for SignerAddress {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.strip_prefix(Self::UNIX_PREFIX) {
+            #[cfg(unix)]
+            Some(path) => Ok(SignerAddress::Unix(PathBuf::from(path))),
+            #[cfg(not(unix))]
+            Some(_) => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Unix sockets are not supported on this platform",
+            )),
+            None => Ok(SignerAddress::Tcp(s.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for SignerAddress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SignerAddress::Tcp(address) => write!(f, "{}", address),
+            #[cfg(unix)]
+            SignerAddress::Unix(path) => write!(f, "{}{}", Self::UNIX_PREFIX, path.display()),
+        }
+    }
+}
+
+/// A connection between a signer and its client.
+pub struct SignerStream {
+    stream: Stream,
+    // The read timeout when no deadline is set.
+    timeout: Duration,
+    // The time by which all reads have to be done. Unlike the read timeout, which applies to each
+    // read on its own, it also limits peers that send their messages byte by byte.
+    deadline: Option<Instant>,
+}
+
+enum Stream {
+    Tcp(TcpStream),
+    #[cfg(unix)]
+    Unix(UnixStream),
+}
+
+impl SignerStream {
+    /// Connects to a signer. Reads and writes fail after `timeout`, so that an unresponsive
+    /// signer doesn't block the validator.
+    pub fn connect(address: &SignerAddress, timeout: Duration) -> io::Result<Self> {
+        let stream = match address {
+            SignerAddress::Tcp(address) => {
+                let stream = TcpStream::connect(address)?;
+                stream.set_nodelay(true)?;
+                Stream::Tcp(stream)
+            }
+            #[cfg(unix)]
+            SignerAddress::Unix(path) => Stream::Unix(UnixStream::connect(path)?),
+        };
+        Self::new(stream, timeout)
+    }
+
+    fn new(stream: Stream, timeout: Duration) -> io::Result<Self> {
+        stream.set_read_timeout(timeout)?;
+        stream.set_write_timeout(timeout)?;
+        Ok(SignerStream {
+            stream,
+            timeout,
+            deadline: None,
+        })
+    }
+
+    /// Sets the time by which all reads have to be done, or removes it.
+    pub fn set_deadline(&mut self, deadline: Option<Instant>) -> io::Result<()> {
+        self.deadline = deadline;
+        if deadline.is_none() {
+            self.stream.set_read_timeout(self.timeout)?;
+        }
+        Ok(())
+    }
+}
+
+impl Stream {
+    fn set_read_timeout(&self, timeout: Duration) -> io::Result<()> {
+        match self {
+            Stream::Tcp(stream) => stream.set_read_timeout(Some(timeout)),
+            #[cfg(unix)]
+            Stream::Unix(stream) => stream.set_read_timeout(Some(timeout)),
+        }
+    }
+
+    fn set_write_timeout(&self, timeout: Duration) -> io::Result<()> {
+        match self {
+            Stream::Tcp(stream) => stream.set_write_timeout(Some(timeout)),
+            #[cfg(unix)]
+            Stream::Unix(stream) => stream.set_write_timeout(Some(timeout)),
+        }
+    }
+}
+
+impl Read for SignerStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if let Some(deadline) = self.deadline {
+            let remaining = deadline
+                .checked_duration_since(Instant::now())
+                .filter(|remaining| *remaining > Duration::from_secs(0))
+                .ok_or_else(|| io::Error::new(io::ErrorKind::TimedOut, "Deadline expired"))?;
+            self.stream.set_read_timeout(remaining.min(self.timeout))?;
+        }
+
+        match &mut self.stream {
+            Stream::Tcp(stream) => stream.read(buf),
+            #[cfg(unix)]
+            Stream::Unix(stream) => stream.read(buf),
+        }
+    }
+}
+
+impl Write for SignerStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match &mut self.stream {
+            Stream::Tcp(stream) => stream.write(buf),
+            #[cfg(unix)]
+            Stream::Unix(stream) => stream.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match &mut self.stream {
+            Stream::Tcp(stream) => stream.flush(),
+            #[cfg(unix)]
+            Stream::Unix(stream) => stream.flush(),
+        }
+    }
+}
+
+/// The listener on which a signer accepts connections from its clients.
+pub enum SignerListener {
+    Tcp(TcpListener),
+    #[cfg(unix)]
+    Unix(UnixListener),
+}
+
+impl SignerListener {
+    pub fn bind(address: &SignerAddress) -> io::Result<Self> {
+        match address {
+            SignerAddress::Tcp(address) => Ok(SignerListener::Tcp(TcpListener::bind(address)?)),
+            #[cfg(unix)]
+            SignerAddress::Unix(path) => Ok(SignerListener::Unix(UnixListener::bind(path)?)),
+        }
+    }
+
+    /// Returns the address the listener is bound to. This is useful to find out the port if the
+    /// listener was bound to port 0.
+    pub fn local_address(&self) -> io::Result<SignerAddress> {
+        match self {
+            SignerListener::Tcp(listener) => {
+                Ok(SignerAddress::Tcp(listener.local_addr()?.to_string()))
+            }
+            #[cfg(unix)]
+            SignerListener::Unix(listener) => {
+                let address = listener.local_addr()?;
+                let path = address.as_pathname().ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::Other, "Unix socket has no path")
+                })?;
+                Ok(SignerAddress::Unix(path.to_path_buf()))
+            }
+        }
+    }
+
+    /// Accepts a connection. Like for `SignerStream::connect`, reads and writes on it fail after
+    /// `timeout`.
+    pub fn accept(&self, timeout: Duration) -> io::Result<SignerStream> {
+        let stream = match self {
+            SignerListener::Tcp(listener) => {
+                let (stream, _) = listener.accept()?;
+                stream.set_nodelay(true)?;
+                Stream::Tcp(stream)
+            }
+            #[cfg(unix)]
+            SignerListener::Unix(listener) => Stream::Unix(listener.accept()?.0),
+        };
+        SignerStream::new(stream, timeout)
+    }
+}
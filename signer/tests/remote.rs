@@ -0,0 +1,239 @@
+use std::net::TcpStream;
+use std::str::FromStr;
+use std::thread;
+use std::time::Duration;
+
+use beserial::Serialize;
+
+use nimiq_block_albatross::ViewChange;
+use nimiq_database::volatile::VolatileEnvironment;
+use nimiq_keys::Address;
+use nimiq_primitives::account::{AccountType, ValidatorId};
+use nimiq_primitives::coin::Coin;
+use nimiq_primitives::networks::NetworkId;
+use nimiq_signer::slashing_protection::SlashingProtection;
+use nimiq_signer::{
+    LocalSigner, RemoteSigner, RemoteSignerConfig, SignRequest, Signer, SignerAddress, SignerError,
+    SignerListener, SignerServer, TransactionPolicy,
+};
+use nimiq_transaction::account::staking_contract::IncomingStakingTransactionData;
+use nimiq_transaction::Transaction;
+use nimiq_utils::key_rng::SecureGenerate;
+use nimiq_vrf::VrfSeed;
+
+struct Setup {
+    local_signer: LocalSigner,
+    config: RemoteSignerConfig,
+}
+
+// Starts a signer on a random port that accepts `client_key`.
+fn start_signer(client_key: nimiq_keys::KeyPair, transaction_policy: TransactionPolicy) -> Setup {
+    let local_signer = LocalSigner::new(
+        nimiq_bls::KeyPair::generate_default_csprng(),
+        Some(nimiq_keys::KeyPair::generate_default_csprng()),
+    );
+    let server_key = nimiq_keys::KeyPair::generate_default_csprng();
+
+    let listener = SignerListener::bind(&SignerAddress::from_str("127.0.0.1:0").unwrap()).unwrap();
+    let address = listener.local_address().unwrap();
+
+    let server = SignerServer::new(
+        local_signer.clone(),
+        SlashingProtection::new(
+            VolatileEnvironment::new(SlashingProtection::NUM_DATABASES).unwrap(),
+        ),
+        transaction_policy,
+        server_key.clone(),
+        vec![client_key.public],
+    );
+    thread::spawn(move || server.serve(listener));
+
+    Setup {
+        local_signer,
+        config: RemoteSignerConfig {
+            address,
+            auth_key: client_key,
+            server_key: server_key.public,
+            timeout: RemoteSignerConfig::DEFAULT_TIMEOUT,
+        },
+    }
+}
+
+#[test]
+fn it_signs_remotely() {
+    let setup = start_signer(
+        nimiq_keys::KeyPair::generate_default_csprng(),
+        TransactionPolicy::SignAll,
+    );
+    let remote_signer = RemoteSigner::connect(setup.config).unwrap();
+
+    assert_eq!(remote_signer.public_key(), setup.local_signer.public_key());
+    assert_eq!(
+        remote_signer.wallet_public_key(),
+        setup.local_signer.wallet_public_key()
+    );
+
+    let request = SignRequest::Seed(VrfSeed::default());
+    let signature = remote_signer.sign(&request).unwrap();
+    assert!(remote_signer
+        .public_key()
+        .verify_hash(request.hash(), &signature));
+
+    let transaction = Transaction::new_basic(
+        Address::from(remote_signer.wallet_public_key().unwrap()),
+        Address::default(),
+        Coin::from_u64_unchecked(1),
+        Coin::ZERO,
+        1,
+        NetworkId::UnitAlbatross,
+    );
+    let signature = remote_signer.sign_transaction(&transaction).unwrap();
+    assert!(remote_signer
+        .wallet_public_key()
+        .unwrap()
+        .verify(&signature, &transaction.serialize_content()));
+}
+
+#[test]
+fn it_checks_the_slashing_protection() {
+    let setup = start_signer(
+        nimiq_keys::KeyPair::generate_default_csprng(),
+        TransactionPolicy::RefuseAll,
+    );
+    let remote_signer = RemoteSigner::connect(setup.config).unwrap();
+
+    let view_change = ViewChange {
+        block_number: 10,
+        new_view_number: 1,
+        prev_seed: VrfSeed::default(),
+    };
+    let request = SignRequest::ViewChange(view_change.clone());
+    assert!(remote_signer.sign(&request).is_ok());
+    // Signing the same message again is fine.
+    assert!(remote_signer.sign(&request).is_ok());
+
+    // A different view change at the same position could get the validator slashed.
+    let conflicting = SignRequest::ViewChange(ViewChange {
+        prev_seed: VrfSeed::default().sign_next(&nimiq_bls::SecretKey::generate_default_csprng()),
+        ..view_change
+    });
+    assert!(matches!(
+        remote_signer.sign(&conflicting),
+        Err(SignerError::SlashingProtection(_))
+    ));
+}
+
+#[test]
+fn it_requires_mutual_authentication() {
+    let client_key = nimiq_keys::KeyPair::generate_default_csprng();
+
+    // The signer doesn't know this validator.
+    let mut setup = start_signer(client_key.clone(), TransactionPolicy::RefuseAll);
+    setup.config.auth_key = nimiq_keys::KeyPair::generate_default_csprng();
+    assert!(RemoteSigner::connect(setup.config).is_err());
+
+    // The validator doesn't know this signer.
+    let mut setup = start_signer(client_key, TransactionPolicy::RefuseAll);
+    setup.config.server_key = nimiq_keys::KeyPair::generate_default_csprng().public;
+    assert!(matches!(
+        RemoteSigner::connect(setup.config),
+        Err(SignerError::AuthenticationFailed)
+    ));
+}
+
+fn unpark_transaction(validator_id: ValidatorId, value: Coin, fee: Coin) -> Transaction {
+    Transaction::new_signalling(
+        Address::default(),
+        AccountType::Basic,
+        Address::default(),
+        AccountType::Staking,
+        value,
+        fee,
+        IncomingStakingTransactionData::UnparkValidator {
+            validator_id,
+            signature: Default::default(),
+        }
+        .serialize_to_vec(),
+        1,
+        NetworkId::UnitAlbatross,
+    )
+}
+
+#[test]
+fn it_only_signs_transactions_allowed_by_the_policy() {
+    let validator_id = ValidatorId::from([1u8; ValidatorId::SIZE]);
+    let setup = start_signer(
+        nimiq_keys::KeyPair::generate_default_csprng(),
+        TransactionPolicy::Unpark {
+            validator_id: validator_id.clone(),
+            max_fee: Coin::from_u64_unchecked(10),
+        },
+    );
+    let remote_signer = RemoteSigner::connect(setup.config).unwrap();
+
+    let unpark = unpark_transaction(
+        validator_id.clone(),
+        Coin::ZERO,
+        Coin::from_u64_unchecked(10),
+    );
+    assert!(remote_signer
+        .sign(&SignRequest::Transaction(unpark.clone()))
+        .is_ok());
+    assert!(remote_signer.sign_transaction(&unpark).is_ok());
+
+    let refused = vec![
+        // Unparks another validator.
+        unpark_transaction(
+            ValidatorId::from([2u8; ValidatorId::SIZE]),
+            Coin::ZERO,
+            Coin::ZERO,
+        ),
+        // Transfers funds.
+        unpark_transaction(
+            validator_id.clone(),
+            Coin::from_u64_unchecked(1),
+            Coin::ZERO,
+        ),
+        // Pays too much fee.
+        unpark_transaction(validator_id, Coin::ZERO, Coin::from_u64_unchecked(11)),
+        // Isn't an unpark transaction.
+        Transaction::new_basic(
+            Address::from(remote_signer.wallet_public_key().unwrap()),
+            Address::default(),
+            Coin::from_u64_unchecked(1),
+            Coin::ZERO,
+            1,
+            NetworkId::UnitAlbatross,
+        ),
+    ];
+    for transaction in refused {
+        assert!(matches!(
+            remote_signer.sign(&SignRequest::Transaction(transaction.clone())),
+            Err(SignerError::TransactionRefused)
+        ));
+        assert!(matches!(
+            remote_signer.sign_transaction(&transaction),
+            Err(SignerError::TransactionRefused)
+        ));
+    }
+}
+
+#[test]
+fn it_limits_unauthenticated_connections() {
+    let setup = start_signer(
+        nimiq_keys::KeyPair::generate_default_csprng(),
+        TransactionPolicy::RefuseAll,
+    );
+    let address = setup.config.address.to_string();
+
+    // Connections that never complete the handshake use up all the slots.
+    let idle_connections = (0..SignerServer::MAX_CONNECTIONS)
+        .map(|_| TcpStream::connect(&address).unwrap())
+        .collect::<Vec<_>>();
+    assert!(RemoteSigner::connect(setup.config.clone()).is_err());
+
+    // They are closed once the handshake times out.
+    thread::sleep(SignerServer::HANDSHAKE_TIMEOUT + Duration::from_secs(1));
+    assert!(RemoteSigner::connect(setup.config).is_ok());
+    drop(idle_connections);
+}
@@ -0,0 +1,86 @@
+use nimiq_database::volatile::VolatileEnvironment;
+use nimiq_hash::{Blake2bHash, Hash};
+use nimiq_signer::slashing_protection::{SigningKind, SlashingProtection};
+
+#[test]
+fn it_refuses_to_sign_conflicting_messages() {
+    let env = VolatileEnvironment::new(SlashingProtection::NUM_DATABASES).unwrap();
+    let slashing_protection = SlashingProtection::new(env);
+
+    let hash1 = "block1".hash::<Blake2bHash>();
+    let hash2 = "block2".hash::<Blake2bHash>();
+
+    assert!(slashing_protection
+        .check_and_record(SigningKind::MicroBlock, 10, 0, &hash1)
+        .is_ok());
+    // Signing the same message again is fine.
+    assert!(slashing_protection
+        .check_and_record(SigningKind::MicroBlock, 10, 0, &hash1)
+        .is_ok());
+    // A different message at the same position or below it is refused.
+    assert!(slashing_protection
+        .check_and_record(SigningKind::MicroBlock, 10, 0, &hash2)
+        .is_err());
+    assert!(slashing_protection
+        .check_and_record(SigningKind::MicroBlock, 9, 3, &hash2)
+        .is_err());
+    // The high-water marks are kept per kind.
+    assert!(slashing_protection
+        .check_and_record(SigningKind::ViewChange, 10, 1, &hash2)
+        .is_ok());
+    // A higher view is fine.
+    assert!(slashing_protection
+        .check_and_record(SigningKind::MicroBlock, 10, 1, &hash2)
+        .is_ok());
+
+    let position = slashing_protection.get(SigningKind::MicroBlock).unwrap();
+    assert_eq!(position.block_number, 10);
+    assert_eq!(position.round, 1);
+    assert_eq!(position.hash, hash2);
+    assert_eq!(
+        slashing_protection.get(SigningKind::TendermintPrevote),
+        None
+    );
+}
+
+#[test]
+fn it_imports_only_higher_marks() {
+    let hash1 = "block1".hash::<Blake2bHash>();
+    let hash2 = "block2".hash::<Blake2bHash>();
+
+    let old_machine = SlashingProtection::new(
+        VolatileEnvironment::new(SlashingProtection::NUM_DATABASES).unwrap(),
+    );
+    old_machine
+        .check_and_record(SigningKind::MicroBlock, 20, 0, &hash1)
+        .unwrap();
+    old_machine
+        .check_and_record(SigningKind::TendermintPrecommit, 5, 0, &hash1)
+        .unwrap();
+
+    let new_machine = SlashingProtection::new(
+        VolatileEnvironment::new(SlashingProtection::NUM_DATABASES).unwrap(),
+    );
+    new_machine
+        .check_and_record(SigningKind::TendermintPrecommit, 10, 0, &hash2)
+        .unwrap();
+
+    let records = old_machine.export();
+    assert_eq!(records.records.len(), 2);
+    new_machine.import(&records);
+
+    assert_eq!(
+        new_machine.get(SigningKind::MicroBlock),
+        old_machine.get(SigningKind::MicroBlock)
+    );
+    assert_eq!(
+        new_machine
+            .get(SigningKind::TendermintPrecommit)
+            .unwrap()
+            .block_number,
+        10
+    );
+    assert!(new_machine
+        .check_and_record(SigningKind::MicroBlock, 20, 0, &hash2)
+        .is_err());
+}
@@ -21,7 +21,8 @@ pub trait TendermintOutsideDeps {
 
     /// Produces a proposal for the given round. It is used when it is our turn to propose. The
     /// proposal is guaranteed to be valid.
-    fn get_value(&mut self, round: u32) -> Result<Self::ProposalTy, TendermintError>;
+    /// This is a Future, since producing a proposal might have to wait for a signer.
+    async fn get_value(&mut self, round: u32) -> Result<Self::ProposalTy, TendermintError>;
 
     /// Takes a proposal and a proof (2f+1 precommits) and returns a completed block.
     fn assemble_block(
@@ -44,7 +44,7 @@ impl<
             let proposal = if self.state.valid_value.is_some() {
                 self.state.valid_value.clone().unwrap()
             } else {
-                self.deps.get_value(round)?
+                self.deps.get_value(round).await?
             };
 
             // Update our state and broadcast our proposal.
@@ -79,7 +79,7 @@ impl TendermintOutsideDeps for TestValidator {
 
     // When it is our turn to propose, the proposal message in `proposal_rounds` is used instead to
     // give us the value that we will propose.
-    fn get_value(&mut self, round: u32) -> Result<Self::ProposalTy, TendermintError> {
+    async fn get_value(&mut self, round: u32) -> Result<Self::ProposalTy, TendermintError> {
         Ok(self.proposal_rounds[round as usize].1)
     }
 
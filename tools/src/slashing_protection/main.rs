@@ -0,0 +1,94 @@
+extern crate nimiq_database as database;
+extern crate nimiq_validator as validator;
+
+use std::io::stdin;
+use std::process::exit;
+
+use clap::{crate_authors, crate_description, crate_version, App, Arg, SubCommand};
+use failure::Error;
+use failure::Fail;
+
+use beserial::{Deserialize, Serialize};
+use database::lmdb::{open, LmdbEnvironment};
+use validator::slashing_protection::{SlashingProtection, SlashingProtectionRecords};
+
+// The validator must not be running while its database is opened here, otherwise the exported
+// high-water marks might already be outdated.
+fn run_app() -> Result<(), Error> {
+    let matches = App::new("Slashing protection")
+        .version(crate_version!())
+        .author(crate_authors!())
+        .about(crate_description!())
+        .arg(
+            Arg::with_name("database")
+                .short("d")
+                .long("database")
+                .value_name("PATH")
+                .help("Path to the consensus database of the (stopped) validator or the database of the (stopped) signer.")
+                .takes_value(true)
+                .required(true),
+        )
+        .arg(
+            Arg::with_name("size")
+                .long("size")
+                .value_name("BYTES")
+                .help("Size of the mapped memory. Default: 50 MB")
+                .takes_value(true),
+        )
+        .subcommand(
+            SubCommand::with_name("export")
+                .about("Print the high-water marks of the validator as hex."),
+        )
+        .subcommand(SubCommand::with_name("import").about(
+            "Read high-water marks as hex from STDIN and merge them into the validator's database.",
+        ))
+        .get_matches();
+
+    let path = matches.value_of("database").ok_or(AppError::DatabasePath)?;
+    let size = match matches.value_of("size") {
+        Some(size) => size.parse()?,
+        None => 50 * 1024 * 1024,
+    };
+    let env = LmdbEnvironment::new(
+        path,
+        size,
+        SlashingProtection::NUM_DATABASES,
+        open::Flags::empty(),
+    )?;
+    let slashing_protection = SlashingProtection::new(env);
+
+    match matches.subcommand_name() {
+        Some("export") => {
+            let records = slashing_protection.export();
+            println!("{}", hex::encode(records.serialize_to_vec()));
+        }
+        Some("import") => {
+            let mut line = String::new();
+            stdin().read_line(&mut line)?;
+            let records =
+                SlashingProtectionRecords::deserialize_from_vec(&hex::decode(line.trim())?)?;
+            slashing_protection.import(&records);
+        }
+        _ => return Err(AppError::Command.into()),
+    }
+
+    Ok(())
+}
+
+fn main() {
+    exit(match run_app() {
+        Ok(_) => 0,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            1
+        }
+    });
+}
+
+#[derive(Debug, Fail)]
+enum AppError {
+    #[fail(display = "Database path is missing")]
+    DatabasePath,
+    #[fail(display = "Either export or import must be given")]
+    Command,
+}
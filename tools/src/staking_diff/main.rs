@@ -0,0 +1,95 @@
+extern crate nimiq_blockchain_albatross as blockchain_albatross;
+extern crate nimiq_database as database;
+extern crate nimiq_primitives as primitives;
+
+use std::process::exit;
+use std::str::FromStr;
+
+use clap::{crate_authors, crate_description, crate_version, App, Arg};
+use failure::Error;
+use failure::Fail;
+
+use blockchain_albatross::Blockchain;
+use database::lmdb::{open, LmdbEnvironment};
+use primitives::networks::NetworkId;
+
+// The node must not be running while its database is opened here.
+fn run_app() -> Result<(), Error> {
+    let matches = App::new("Staking contract diff")
+        .version(crate_version!())
+        .author(crate_authors!())
+        .about(crate_description!())
+        .arg(
+            Arg::with_name("database")
+                .short("d")
+                .long("database")
+                .value_name("PATH")
+                .help("Path to the consensus database of the (stopped) node.")
+                .takes_value(true)
+                .required(true),
+        )
+        .arg(
+            Arg::with_name("size")
+                .long("size")
+                .value_name("BYTES")
+                .help("Size of the mapped memory. Default: 1 GB")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("network")
+                .short("n")
+                .long("network")
+                .value_name("NETWORK")
+                .help("The network of the database. Default: devalbatross")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("from")
+                .value_name("FROM_BLOCK")
+                .help("The diff starts with the state after this block.")
+                .required(true),
+        )
+        .arg(
+            Arg::with_name("to")
+                .value_name("TO_BLOCK")
+                .help("The diff ends with the state after this block.")
+                .required(true),
+        )
+        .get_matches();
+
+    let path = matches.value_of("database").ok_or(AppError::DatabasePath)?;
+    let size = match matches.value_of("size") {
+        Some(size) => size.parse()?,
+        None => 1024 * 1024 * 1024,
+    };
+    let network_id = match matches.value_of("network") {
+        Some(network) => NetworkId::from_str(network)?,
+        None => NetworkId::DevAlbatross,
+    };
+    let from_block: u32 = matches.value_of("from").unwrap().parse()?;
+    let to_block: u32 = matches.value_of("to").unwrap().parse()?;
+
+    let env = LmdbEnvironment::new(path, size, Blockchain::NUM_DATABASES, open::Flags::empty())?;
+    let blockchain = Blockchain::new(env, network_id)?;
+
+    let diff = blockchain.get_staking_contract_diff(from_block, to_block)?;
+    println!("{:#?}", diff);
+
+    Ok(())
+}
+
+fn main() {
+    exit(match run_app() {
+        Ok(_) => 0,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            1
+        }
+    });
+}
+
+#[derive(Debug, Fail)]
+enum AppError {
+    #[fail(display = "Database path is missing")]
+    DatabasePath,
+}
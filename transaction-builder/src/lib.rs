@@ -22,7 +22,12 @@ pub use crate::recipient::Recipient;
 pub mod proof;
 pub mod recipient;
 
-fn fill_in_staking_contract_address(address: Option<Address>, network_id: NetworkId) -> Address {
+/// Returns `address` or, if it is `None`, the address of the staking contract of the network with
+/// ID `network_id`.
+pub fn fill_in_staking_contract_address(
+    address: Option<Address>,
+    network_id: NetworkId,
+) -> Address {
     address.unwrap_or_else(|| {
         NetworkInfo::from_network_id(network_id)
             .staking_contract()
@@ -714,6 +719,8 @@ impl TransactionBuilder {
     ///                             account belonging to this key pair.
     ///  - `reward_address`:        The address to which the staking reward is sent.
     ///  - `validator_key_pair`:    The validator BLS key pair used by the validator.
+    ///  - `commission`:            The commission in basis points, if the validator shares its rewards with its
+    ///                             stakers.
     ///  - `value`:                 The value for the initial stake. This is sent from the account belonging to
     ///                             `key_pair` to the initial stake.
     ///  - `fee`:                   Transaction fee.
@@ -729,6 +736,7 @@ impl TransactionBuilder {
         key_pair: &KeyPair,
         reward_address: Address,
         validator_key_pair: &BlsKeyPair,
+        commission: Option<u16>,
         value: Coin,
         fee: Coin,
         validity_start_height: u32,
@@ -737,7 +745,7 @@ impl TransactionBuilder {
         let staking_contract_address =
             fill_in_staking_contract_address(staking_contract, network_id);
         let mut recipient = Recipient::new_staking_builder(Some(staking_contract_address));
-        recipient.create_validator(validator_key_pair, reward_address);
+        recipient.create_validator(validator_key_pair, reward_address, commission);
 
         let mut builder = Self::new();
         builder
@@ -758,7 +766,8 @@ impl TransactionBuilder {
         }
     }
 
-    /// Creates a transaction that updates the validator BLS key and reward address for a validator entry.
+    /// Creates a transaction that updates the validator BLS key, reward address and commission for a
+    /// validator entry.
     ///
     /// # Arguments
     ///
@@ -769,6 +778,7 @@ impl TransactionBuilder {
     ///  - `new_reward_address`:       The new address to which the staking reward is sent.
     ///  - `old_validator_key_pair`:   The old BLS key pair used by this validator.
     ///  - `new_validator_key_pair`:   The new validator BLS key pair used by the validator.
+    ///  - `new_commission`:           The new commission in basis points. `Some(None)` removes the commission.
     ///  - `fee`:                      Transaction fee.
     ///  - `validity_start_height`:    Block height from which this transaction is valid.
     ///  - `network_id`:               ID of network for which the transaction is valid.
@@ -788,6 +798,7 @@ impl TransactionBuilder {
         new_reward_address: Option<Address>,
         old_validator_key_pair: &BlsKeyPair,
         new_validator_key_pair: Option<&BlsKeyPair>,
+        new_commission: Option<Option<u16>>,
         fee: Coin,
         validity_start_height: u32,
         network_id: NetworkId,
@@ -800,6 +811,7 @@ impl TransactionBuilder {
             &old_validator_key_pair.public_key,
             new_validator_key_pair,
             new_reward_address,
+            new_commission,
         );
 
         let mut builder = Self::new();
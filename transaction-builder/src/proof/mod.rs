@@ -228,7 +228,7 @@ impl TransactionProofBuilder {
     ///
     /// let sender_address = Address::from(&key_pair.public);
     /// let mut recipient = Recipient::new_staking_builder(Some(staking_contract_address));
-    /// recipient.update_validator(&validator_id, &bls_key_pair.public_key, None, Some(sender_address.clone()));
+    /// recipient.update_validator(&validator_id, &bls_key_pair.public_key, None, Some(sender_address.clone()), None);
     ///
     /// let tx_builder = TransactionBuilder::with_required(
     ///     sender_address,
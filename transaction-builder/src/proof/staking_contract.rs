@@ -1,5 +1,5 @@
 use beserial::{Deserialize, Serialize};
-use bls::KeyPair as BlsKeyPair;
+use bls::{CompressedSignature, KeyPair as BlsKeyPair};
 use keys::KeyPair;
 use primitives::account::ValidatorId;
 use transaction::account::staking_contract::{
@@ -30,6 +30,22 @@ impl SignallingProofBuilder {
         }
     }
 
+    /// Manually sets the required signalling `signature` proof for the builder, e.g. if the
+    /// validator key is not available in this process.
+    /// In most cases, it is recommended to generate the signature using [`sign_with_validator_key_pair`].
+    ///
+    /// [`sign_with_validator_key_pair`]: struct.SignallingProofBuilder.html#method.sign_with_validator_key_pair
+    pub fn with_validator_signature(
+        &mut self,
+        validator_signature: CompressedSignature,
+    ) -> &mut Self {
+        let mut data: IncomingStakingTransactionData =
+            Deserialize::deserialize_from_vec(&self.transaction.data[..]).unwrap();
+        data.set_validator_signature(validator_signature);
+        self.data = Some(data);
+        self
+    }
+
     /// This method sets the required signalling `signature` proof by signing the transaction
     /// using a BLS key pair `validator_key_pair`.
     pub fn sign_with_validator_key_pair(&mut self, validator_key_pair: &BlsKeyPair) -> &mut Self {
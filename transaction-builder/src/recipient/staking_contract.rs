@@ -99,31 +99,45 @@ impl StakingRecipientBuilder {
     }
 
     /// This method allows to create a new validator entry using a BLS key pair `key_pair`.
-    /// All rewards for this validator will be paid out to its `reward_address`.
-    pub fn create_validator(&mut self, key_pair: &KeyPair, reward_address: Address) -> &mut Self {
+    /// All rewards for this validator will be paid out to its `reward_address`, unless it charges
+    /// a `commission` (in basis points). A validator with a commission keeps the commission on
+    /// its rewards and shares the rest with its stakers, in proportion to their stake.
+    pub fn create_validator(
+        &mut self,
+        key_pair: &KeyPair,
+        reward_address: Address,
+        commission: Option<u16>,
+    ) -> &mut Self {
         self.staking_data = Some(StakingTransaction::IncomingTransaction(
             IncomingStakingTransactionData::CreateValidator {
                 validator_key: key_pair.public_key.compress(),
                 proof_of_knowledge: StakingRecipientBuilder::generate_proof_of_knowledge(&key_pair),
                 reward_address,
+                commission,
             },
         ));
         self
     }
 
     /// This method allows to create a new validator entry and also generates a new key pair for it.
-    /// All rewards for this validator will be paid out to its `reward_address`.
+    /// All rewards for this validator will be paid out to its `reward_address`, unless it charges
+    /// a `commission`.
     ///
     /// The method returns the generated BLS key pair.
-    pub fn create_validator_with_new_bls_key(&mut self, reward_address: Address) -> KeyPair {
+    pub fn create_validator_with_new_bls_key(
+        &mut self,
+        reward_address: Address,
+        commission: Option<u16>,
+    ) -> KeyPair {
         let key = KeyPair::generate_default_csprng();
-        self.create_validator(&key, reward_address);
+        self.create_validator(&key, reward_address, commission);
         key
     }
 
     /// This method allows to update the details of an existing validator entry with the
     /// public key `old_validator_key`.
-    /// Both the key pair and the reward address can be updated.
+    /// The key pair, the reward address and the commission can be updated. A `new_commission` of
+    /// `Some(None)` removes the commission of the validator.
     /// All updates will only take effect starting in the following epoch.
     pub fn update_validator(
         &mut self,
@@ -131,6 +145,7 @@ impl StakingRecipientBuilder {
         old_validator_key: &PublicKey,
         new_key_pair: Option<&KeyPair>,
         new_reward_address: Option<Address>,
+        new_commission: Option<Option<u16>>,
     ) -> &mut Self {
         self.staking_data = Some(StakingTransaction::IncomingTransaction(
             IncomingStakingTransactionData::UpdateValidator {
@@ -140,6 +155,7 @@ impl StakingRecipientBuilder {
                 new_proof_of_knowledge: new_key_pair
                     .map(|key| StakingRecipientBuilder::generate_proof_of_knowledge(&key)),
                 new_reward_address,
+                new_commission,
                 signature: Default::default(),
             },
         ));
@@ -260,7 +276,7 @@ impl StakingRecipientBuilder {
     ///
     /// let reward_address = Address::from_any_str("NQ46 MNYU LQ93 GYYS P5DC YA51 L5JP UPUT KR62").unwrap();
     /// let mut recipient_builder = Recipient::new_staking_builder(None);
-    /// recipient_builder.create_validator(&validator_key_pair, reward_address);
+    /// recipient_builder.create_validator(&validator_key_pair, reward_address, None);
     /// let recipient = recipient_builder.generate();
     /// assert!(recipient.is_some());
     /// ```
@@ -109,6 +109,7 @@ fn it_can_verify_validator_transactions() {
             validator_key: bls_pair.public_key.compress(),
             proof_of_knowledge: bls_pair.sign(&bls_pair.public_key).compress(),
             reward_address: Address::from_any_str(STAKER_ADDRESS).unwrap(),
+            commission: None,
         },
         150_000_000,
         &bls_pair,
@@ -116,7 +117,11 @@ fn it_can_verify_validator_transactions() {
     );
 
     let mut recipient = Recipient::new_staking_builder(Some(Address::from([1u8; 20])));
-    recipient.create_validator(&bls_pair, Address::from_any_str(STAKER_ADDRESS).unwrap());
+    recipient.create_validator(
+        &bls_pair,
+        Address::from_any_str(STAKER_ADDRESS).unwrap(),
+        None,
+    );
 
     let mut tx_builder = TransactionBuilder::new();
     tx_builder
@@ -140,6 +145,7 @@ fn it_can_verify_validator_transactions() {
             new_validator_key: None,
             new_proof_of_knowledge: None,
             new_reward_address: Some(Address::from([1u8; 20])),
+            new_commission: Some(None),
             signature: Default::default(),
         },
         0,
@@ -153,6 +159,7 @@ fn it_can_verify_validator_transactions() {
         &bls_pair.public_key,
         None,
         Some(Address::from([1u8; 20])),
+        Some(None),
     );
 
     let mut tx_builder = TransactionBuilder::new();
@@ -24,6 +24,8 @@ use beserial::{Deserialize, Serialize};
 ///  - `0x01`: [`ChallengeNonce`](nimiq_network_libp2p::discovery::protocol::ChallengeNonce)
 ///  - `0x02`: [`PeerContact`](nimiq_network_libp2p::discovery::peer_contacts::PeerContact)
 ///  - `0x03`: [`ValidatorRecord`]
+///  - `0x04`: [`HandshakeProof`](nimiq_signer::protocol::HandshakeProof)
+///  - `0x05`: [`MessageProof`](nimiq_signer::protocol::MessageProof)
 ///
 pub trait TaggedSignable: Serialize {
     const TAG: u8;
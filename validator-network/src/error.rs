@@ -2,6 +2,7 @@ use thiserror::Error;
 
 use beserial::SerializingError;
 use nimiq_network_interface::peer::SendError;
+use nimiq_signer::SignerError;
 
 /// No notion of connected or disconnected!
 /// If a peer is not connected the connection must be pursued.
@@ -32,4 +33,8 @@ where
 
     #[error("Send error: {0}")]
     Send(SendError),
+
+    /// Signing a record with the validator key failed.
+    #[error("Signer error: {0}")]
+    Signer(SignerError),
 }
@@ -9,12 +9,13 @@ use std::{pin::Pin, sync::Arc, time::Duration};
 use async_trait::async_trait;
 use futures::Stream;
 
-use nimiq_bls::{CompressedPublicKey, SecretKey};
+use nimiq_bls::CompressedPublicKey;
 use nimiq_network_interface::{
     message::Message,
     network::{PubsubId, Topic},
     peer::Peer,
 };
+use nimiq_signer::Signer;
 
 pub use crate::error::NetworkError;
 
@@ -68,6 +69,6 @@ pub trait ValidatorNetwork: Send + Sync {
     async fn set_public_key(
         &self,
         public_key: &CompressedPublicKey,
-        secret_key: &SecretKey,
+        signer: Arc<dyn Signer>,
     ) -> Result<(), Self::Error>;
 }
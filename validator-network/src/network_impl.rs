@@ -9,8 +9,9 @@ use async_trait::async_trait;
 use beserial::{Deserialize, Serialize};
 use futures::{future::join_all, lock::Mutex, Stream, StreamExt};
 
-use nimiq_bls::{CompressedPublicKey, PublicKey, SecretKey, Signature};
+use nimiq_bls::{CompressedPublicKey, PublicKey, Signature};
 use nimiq_network_interface::{message::Message, network::Network, network::Topic, peer::Peer};
+use nimiq_signer::{sign_blocking, SignRequest, Signer, SignerError};
 use nimiq_utils::tagged_signing::TaggedSignable;
 
 use super::{MessageStream, NetworkError, ValidatorNetwork};
@@ -46,14 +47,17 @@ where
         Self { peer_id }
     }
 
-    pub fn sign(self, secret_key: &SecretKey) -> SignedValidatorRecord<TPeerId> {
-        let data = self.serialize_to_vec();
-        let signature = secret_key.sign(&data);
+    pub async fn sign(
+        self,
+        signer: Arc<dyn Signer>,
+    ) -> Result<SignedValidatorRecord<TPeerId>, SignerError> {
+        let request = SignRequest::ValidatorRecord(self.serialize_to_vec());
+        let signature = sign_blocking(signer, request).await?;
 
-        SignedValidatorRecord {
+        Ok(SignedValidatorRecord {
             record: self,
             signature,
-        }
+        })
     }
 }
 
@@ -71,7 +75,8 @@ where
     TPeerId: Serialize + Deserialize,
 {
     pub fn verify(&self, public_key: &PublicKey) -> bool {
-        public_key.verify(&self.record.serialize_to_vec(), &self.signature)
+        let request = SignRequest::ValidatorRecord(self.record.serialize_to_vec());
+        public_key.verify_hash(request.hash(), &self.signature)
     }
 }
 
@@ -258,13 +263,14 @@ where
     async fn set_public_key(
         &self,
         public_key: &CompressedPublicKey,
-        secret_key: &SecretKey,
+        signer: Arc<dyn Signer>,
     ) -> Result<(), Self::Error> {
         let peer_id = self.network.get_local_peer_id();
-        let record = ValidatorRecord::new(peer_id);
-        self.network
-            .dht_put(public_key, &record.sign(secret_key))
-            .await?;
+        let record = ValidatorRecord::new(peer_id)
+            .sign(signer)
+            .await
+            .map_err(NetworkError::Signer)?;
+        self.network.dht_put(public_key, &record).await?;
 
         Ok(())
     }
@@ -2,7 +2,7 @@ use std::collections::BTreeMap;
 
 use beserial::{Deserialize, Serialize};
 use nimiq_block_albatross::{MultiSignature, TendermintVote};
-use nimiq_bls::{AggregateSignature, SecretKey};
+use nimiq_bls::{AggregateSignature, Signature};
 use nimiq_collections::bitset::BitSet;
 use nimiq_hash::Blake2bHash;
 
@@ -17,14 +17,15 @@ pub struct TendermintContribution {
 impl TendermintContribution {
     pub(crate) fn from_vote(
         vote: TendermintVote,
-        secret_key: &SecretKey,
+        signature: Signature,
         validator_slots: Vec<u16>,
     ) -> Self {
         assert!(!validator_slots.is_empty());
-        // sign the hash
-        let signature = AggregateSignature::from_signatures(&[secret_key
-            .sign(&vote)
-            .multiply(validator_slots.len() as u16)]);
+        // weigh the signature of the vote by the number of slots
+        let signature =
+            AggregateSignature::from_signatures(
+                &[signature.multiply(validator_slots.len() as u16)],
+            );
 
         // get the slots of the validator ad insert them into the bitset
         let mut signers = BitSet::new();
@@ -18,9 +18,8 @@ use futures_locks::RwLock;
 use nimiq_block_albatross::{
     MacroBlock, MultiSignature, TendermintIdentifier, TendermintStep, TendermintVote,
 };
-use nimiq_bls::SecretKey;
 use nimiq_collections::bitset::BitSet;
-use nimiq_hash::Blake2bHash;
+use nimiq_hash::{Blake2bHash, Hash};
 use nimiq_primitives::policy;
 use nimiq_primitives::slots::Validators;
 
@@ -30,9 +29,13 @@ use nimiq_handel::contribution::AggregatableContribution;
 use nimiq_handel::identity::WeightRegistry;
 use nimiq_handel::update::{LevelUpdate, LevelUpdateMessage};
 
+use nimiq_signer::{sign_blocking, SignRequest, Signer};
 use nimiq_tendermint::{AggregationResult, TendermintError};
 use nimiq_validator_network::ValidatorNetwork;
 
+use crate::duty::DutyTracker;
+use crate::slashing_protection::{SigningKind, SlashingProtection};
+
 use super::super::network_sink::NetworkSink;
 use super::super::registry::ValidatorRegistry;
 
@@ -286,7 +289,9 @@ pub struct HandelTendermintAdapter<N: ValidatorNetwork> {
     pending_new_round: RwLock<Option<u32>>,
     validator_merkle_root: Vec<u8>,
     block_height: u32,
-    secret_key: SecretKey,
+    signer: Arc<dyn Signer>,
+    slashing_protection: Arc<SlashingProtection>,
+    duty_tracker: Arc<DutyTracker>,
     validator_id: u16,
     validator_registry: Arc<ValidatorRegistry>,
     network: Arc<N>,
@@ -302,7 +307,9 @@ where
         active_validators: Validators,
         block_height: u32,
         network: Arc<N>,
-        secret_key: SecretKey,
+        signer: Arc<dyn Signer>,
+        slashing_protection: Arc<SlashingProtection>,
+        duty_tracker: Arc<DutyTracker>,
     ) -> Self {
         let validator_merkle_root = MacroBlock::create_pk_tree_root(&active_validators);
 
@@ -340,7 +347,9 @@ where
             pending_new_round: pending_new_round.clone(),
             validator_merkle_root,
             block_height,
-            secret_key,
+            signer,
+            slashing_protection,
+            duty_tracker,
             validator_id,
             validator_registry: validator_registry.clone(),
             network,
@@ -522,6 +531,49 @@ where
         proposal_hash: Option<Blake2bHash>,
     ) -> Result<AggregationResult<MultiSignature>, TendermintError> {
         let step = step.into();
+
+        // Assemble identifier from availablle information
+        let id = TendermintIdentifier {
+            block_number: self.block_height,
+            round_number: round,
+            step,
+        };
+
+        // Construct the vote so it can be hashed and signed
+        let vote = TendermintVote {
+            proposal_hash: proposal_hash.clone(),
+            id: id.clone(),
+            validator_merkle_root: self.validator_merkle_root.clone(),
+        };
+
+        // Make sure that we didn't sign a different vote for this round and step before.
+        if let Err(e) = self.slashing_protection.check_and_record(
+            SigningKind::from_tendermint_step(step),
+            self.block_height,
+            round,
+            &vote.hash::<Blake2bHash>(),
+        ) {
+            error!("{}", e);
+            return Err(TendermintError::AggregationError);
+        }
+
+        // Sign the vote before an aggregation is set up, so that a failing signer doesn't leave one
+        // behind.
+        let signature = sign_blocking(
+            Arc::clone(&self.signer),
+            SignRequest::TendermintVote(vote.clone()),
+        )
+        .await
+        .map_err(|e| {
+            error!("Failed to sign vote: {}", e);
+            TendermintError::AggregationError
+        })?;
+        self.duty_tracker.on_vote_signed(
+            SigningKind::from_tendermint_step(step),
+            self.block_height,
+            round,
+        );
+
         // make sure that there is no currently ongoing aggregation from a previous call to `broadcast_and_aggregate` which has not yet been awaited.
         // if there is none make sure to set this one with the same lock to prevent a race condition
         let (mut aggregate_receiver, _aggregate_sender) = {
@@ -549,24 +601,10 @@ where
             }
         };
 
-        // Assemble identifier from availablle information
-        let id = TendermintIdentifier {
-            block_number: self.block_height,
-            round_number: round,
-            step,
-        };
-
-        // Construct the vote so it can be hashed and signed
-        let vote = TendermintVote {
-            proposal_hash: proposal_hash.clone(),
-            id: id.clone(),
-            validator_merkle_root: self.validator_merkle_root.clone(),
-        };
-
         // Create the signed contribution of this validator
         let own_contribution = TendermintContribution::from_vote(
             vote,
-            &self.secret_key,
+            signature,
             self.validator_registry.get_slots(self.validator_id),
         );
 
@@ -20,11 +20,15 @@ use handel::partitioner::BinomialPartitioner;
 use handel::protocol::Protocol;
 use handel::store::ReplaceStore;
 use handel::update::{LevelUpdate, LevelUpdateMessage};
-use hash::Blake2sHash;
+use hash::{Blake2bHash, Blake2sHash, Hash};
+use nimiq_signer::{sign_blocking, SignRequest, Signer};
 use nimiq_validator_network::ValidatorNetwork;
 use primitives::policy;
 use primitives::slots::Validators;
 
+use crate::duty::DutyTracker;
+use crate::slashing_protection::{SigningKind, SlashingProtection};
+
 use super::network_sink::NetworkSink;
 use super::registry::ValidatorRegistry;
 use super::verifier::MultithreadedVerifier;
@@ -207,7 +211,9 @@ impl ViewChangeAggregation {
     pub async fn start<N: ValidatorNetwork + 'static>(
         mut view_change: ViewChange,
         mut previous_proof: Option<MultiSignature>,
-        signing_key: bls::KeyPair,
+        signer: Arc<dyn Signer>,
+        slashing_protection: Arc<SlashingProtection>,
+        duty_tracker: Arc<DutyTracker>,
         // TODO: This seems to be a SlotBand. Change this to a proper Validator ID.
         validator_id: u16,
         active_validators: Validators,
@@ -229,10 +235,39 @@ impl ViewChangeAggregation {
                 &view_change,
                 message_hash
             );
-            let signed_view_change = SignedViewChange::from_message(
-                view_change.clone(),
-                &signing_key.secret_key,
-                validator_id,
+
+            // Without our own contribution we can't take part in the view change. We wait for the
+            // chain to move on instead, which replaces this aggregation.
+            if let Err(e) = slashing_protection.check_and_record(
+                SigningKind::ViewChange,
+                view_change.block_number,
+                view_change.new_view_number,
+                &view_change.hash::<Blake2bHash>(),
+            ) {
+                error!("{}", e);
+                return futures::future::pending().await;
+            }
+
+            let signed_view_change = match sign_blocking(
+                Arc::clone(&signer),
+                SignRequest::ViewChange(view_change.clone()),
+            )
+            .await
+            {
+                Ok(signature) => SignedViewChange {
+                    message: view_change.clone(),
+                    signer_idx: validator_id,
+                    signature,
+                },
+                Err(e) => {
+                    error!("Failed to sign view change: {}", e);
+                    return futures::future::pending().await;
+                }
+            };
+            duty_tracker.on_vote_signed(
+                SigningKind::ViewChange,
+                view_change.block_number,
+                view_change.new_view_number,
             );
 
             let signature = bls::AggregateSignature::from_signatures(&[signed_view_change
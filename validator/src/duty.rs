@@ -0,0 +1,349 @@
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use parking_lot::RwLock;
+
+use block_albatross::{Block, MultiSignature};
+use blockchain_albatross::{AbstractBlockchain, Blockchain};
+use bls::CompressedPublicKey;
+use primitives::policy;
+use primitives::slots::Validators;
+
+use crate::slashing_protection::SigningKind;
+
+/// What happened to a micro block slot of this validator.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BlockOutcome {
+    /// We produced the block.
+    Produced,
+    /// The view was changed away from us, so the block was produced by somebody else.
+    Missed,
+}
+
+/// A micro block slot of this validator.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BlockDuty {
+    pub block_number: u32,
+    pub view_number: u32,
+    pub outcome: BlockOutcome,
+}
+
+/// A vote of this validator in a Tendermint round or a view change.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VoteDuty {
+    /// Either `ViewChange`, `TendermintPrevote` or `TendermintPrecommit`.
+    pub kind: SigningKind,
+    pub block_number: u32,
+    /// The Tendermint round, or the new view number for view changes.
+    pub round: u32,
+    /// Whether we signed the vote.
+    pub signed: bool,
+    /// Whether our vote is part of the proof in the chain. This is `None` as long as there is no
+    /// proof for the vote in the chain, which for most votes never happens.
+    pub included: Option<bool>,
+}
+
+/// A view in which this validator produces the next block, or proposes it if it is a macro block.
+/// The producers of later blocks aren't known yet, because they depend on the seed of the block
+/// before.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UpcomingSlot {
+    pub block_number: u32,
+    pub view_number: u32,
+}
+
+/// Totals since the validator was started.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DutyCounts {
+    pub produced_blocks: u64,
+    pub missed_blocks: u64,
+    pub signed_votes: u64,
+    /// Votes that are part of a proof in the chain.
+    pub included_votes: u64,
+    /// Votes that are missing from a proof in the chain.
+    pub missed_votes: u64,
+}
+
+#[derive(Clone, Debug)]
+pub struct DutyReport {
+    /// The slot band of this validator in the current epoch, or `None` if it isn't active.
+    pub slot_band: Option<u16>,
+    pub num_slots: u16,
+    pub upcoming_slots: Vec<UpcomingSlot>,
+    pub counts: DutyCounts,
+    /// The most recent micro block slots, oldest first.
+    pub recent_blocks: Vec<BlockDuty>,
+    /// The most recent votes, oldest first.
+    pub recent_votes: Vec<VoteDuty>,
+}
+
+#[derive(Default)]
+struct DutyState {
+    counts: DutyCounts,
+    blocks: VecDeque<BlockDuty>,
+    votes: VecDeque<VoteDuty>,
+}
+
+impl DutyState {
+    fn vote_mut(&mut self, kind: SigningKind, block_number: u32, round: u32) -> &mut VoteDuty {
+        let position = self.votes.iter().rposition(|vote| {
+            vote.kind == kind && vote.block_number == block_number && vote.round == round
+        });
+
+        let index = match position {
+            Some(index) => index,
+            None => {
+                if self.votes.len() == DutyTracker::HISTORY_SIZE {
+                    self.votes.pop_front();
+                }
+                self.votes.push_back(VoteDuty {
+                    kind,
+                    block_number,
+                    round,
+                    signed: false,
+                    included: None,
+                });
+                self.votes.len() - 1
+            }
+        };
+
+        &mut self.votes[index]
+    }
+
+    fn push_block(&mut self, duty: BlockDuty) {
+        match duty.outcome {
+            BlockOutcome::Produced => self.counts.produced_blocks += 1,
+            BlockOutcome::Missed => self.counts.missed_blocks += 1,
+        }
+        if self.blocks.len() == DutyTracker::HISTORY_SIZE {
+            self.blocks.pop_front();
+        }
+        self.blocks.push_back(duty);
+    }
+
+    fn set_included(&mut self, kind: SigningKind, block_number: u32, round: u32, included: bool) {
+        let vote = self.vote_mut(kind, block_number, round);
+        let previous = vote.included.replace(included);
+
+        match previous {
+            Some(true) => self.counts.included_votes -= 1,
+            Some(false) => self.counts.missed_votes -= 1,
+            None => {}
+        }
+        if included {
+            self.counts.included_votes += 1;
+        } else {
+            self.counts.missed_votes += 1;
+        }
+    }
+}
+
+/// Keeps track of the duties of this validator: the micro blocks it produced or missed and the
+/// Tendermint and view change votes it signed and that made it into the chain.
+///
+/// The history is only kept in memory and is limited to the last `HISTORY_SIZE` slots and votes.
+pub struct DutyTracker {
+    blockchain: Arc<Blockchain>,
+    public_key: CompressedPublicKey,
+    state: RwLock<DutyState>,
+}
+
+impl DutyTracker {
+    /// The number of micro block slots and the number of votes that are kept in the history.
+    pub const HISTORY_SIZE: usize = 1024;
+    /// The number of views of the next block that are searched for upcoming slots.
+    pub const UPCOMING_VIEWS: u32 = 16;
+
+    pub fn new(blockchain: Arc<Blockchain>, public_key: CompressedPublicKey) -> Self {
+        Self {
+            blockchain,
+            public_key,
+            state: RwLock::new(DutyState::default()),
+        }
+    }
+
+    /// Records that we signed a Tendermint vote or a view change.
+    pub fn on_vote_signed(&self, kind: SigningKind, block_number: u32, round: u32) {
+        let mut state = self.state.write();
+        state.counts.signed_votes += 1;
+        state.vote_mut(kind, block_number, round).signed = true;
+    }
+
+    /// Records our micro block slots in a block that was added to the chain and whether our votes
+    /// are part of its justification.
+    pub fn on_block_added(&self, block: &Block) {
+        let block_number = block.block_number();
+        let validators = match self.validators_at(block_number) {
+            Some(validators) => validators,
+            None => return,
+        };
+        let slot_range = match self.slot_band(&validators) {
+            Some(band) => validators.validators[band as usize].slot_range,
+            None => return,
+        };
+
+        match block {
+            Block::Micro(micro_block) => {
+                // The views before the one of the block were changed away from. The first one is
+                // where the previous block left off.
+                let view_number = micro_block.header.view_number;
+                let first_view = self
+                    .blockchain
+                    .get_block_at(block_number - 1, false, None)
+                    .map_or(view_number, |previous| previous.next_view_number());
+                for view in first_view..=view_number {
+                    if !self.is_slot_owner(block_number, view) {
+                        continue;
+                    }
+                    self.state.write().push_block(BlockDuty {
+                        block_number,
+                        view_number: view,
+                        outcome: if view == view_number {
+                            BlockOutcome::Produced
+                        } else {
+                            BlockOutcome::Missed
+                        },
+                    });
+                }
+
+                let view_change_proof = micro_block
+                    .justification
+                    .as_ref()
+                    .and_then(|justification| justification.view_change_proof.as_ref());
+                if let Some(proof) = view_change_proof {
+                    self.state.write().set_included(
+                        SigningKind::ViewChange,
+                        block_number,
+                        view_number,
+                        Self::contains_slots(&proof.sig, slot_range),
+                    );
+                }
+            }
+            Block::Macro(macro_block) => {
+                if let Some(proof) = &macro_block.justification {
+                    self.state.write().set_included(
+                        SigningKind::TendermintPrecommit,
+                        block_number,
+                        proof.round,
+                        Self::contains_slots(&proof.sig, slot_range),
+                    );
+                }
+            }
+        }
+    }
+
+    /// Forgets our micro block slots and view change votes in a block that was reverted. The
+    /// counts are adjusted as long as the block is still in the history.
+    pub fn on_block_reverted(&self, block: &Block) {
+        let block_number = block.block_number();
+        let mut state = self.state.write();
+
+        let mut produced_blocks = 0;
+        let mut missed_blocks = 0;
+        for duty in state.blocks.iter() {
+            if duty.block_number == block_number {
+                match duty.outcome {
+                    BlockOutcome::Produced => produced_blocks += 1,
+                    BlockOutcome::Missed => missed_blocks += 1,
+                }
+            }
+        }
+        state
+            .blocks
+            .retain(|duty| duty.block_number != block_number);
+        state.counts.produced_blocks -= produced_blocks;
+        state.counts.missed_blocks -= missed_blocks;
+
+        let mut included_votes = 0;
+        let mut missed_votes = 0;
+        for vote in state.votes.iter_mut() {
+            if vote.kind == SigningKind::ViewChange && vote.block_number == block_number {
+                match vote.included.take() {
+                    Some(true) => included_votes += 1,
+                    Some(false) => missed_votes += 1,
+                    None => {}
+                }
+            }
+        }
+        state.counts.included_votes -= included_votes;
+        state.counts.missed_votes -= missed_votes;
+    }
+
+    /// Returns the views in which we produce or propose the next block, searching the next
+    /// `UPCOMING_VIEWS` views.
+    pub fn upcoming_slots(&self) -> Vec<UpcomingSlot> {
+        let head = self.blockchain.head();
+        let block_number = head.block_number() + 1;
+        let first_view = head.next_view_number();
+
+        (first_view..first_view + Self::UPCOMING_VIEWS)
+            .filter(|&view_number| self.is_slot_owner(block_number, view_number))
+            .map(|view_number| UpcomingSlot {
+                block_number,
+                view_number,
+            })
+            .collect()
+    }
+
+    pub fn counts(&self) -> DutyCounts {
+        self.state.read().counts.clone()
+    }
+
+    pub fn report(&self) -> DutyReport {
+        let validators = self.blockchain.current_validators();
+        let slot_band = validators
+            .as_ref()
+            .and_then(|validators| self.slot_band(validators));
+        let num_slots = match (&validators, slot_band) {
+            (Some(validators), Some(band)) => {
+                let (start, end) = validators.validators[band as usize].slot_range;
+                end - start
+            }
+            _ => 0,
+        };
+        let upcoming_slots = self.upcoming_slots();
+
+        let state = self.state.read();
+        DutyReport {
+            slot_band,
+            num_slots,
+            upcoming_slots,
+            counts: state.counts.clone(),
+            recent_blocks: state.blocks.iter().cloned().collect(),
+            recent_votes: state.votes.iter().cloned().collect(),
+        }
+    }
+
+    // Returns the validators that sign the block at `block_number`. The election block is signed
+    // by the validators of the epoch it ends, but once it is applied, these are the previous ones.
+    fn validators_at(&self, block_number: u32) -> Option<Validators> {
+        if policy::is_election_block_at(block_number)
+            && block_number == self.blockchain.block_number()
+        {
+            self.blockchain.previous_validators()
+        } else {
+            self.blockchain
+                .get_validators_for_epoch(policy::epoch_at(block_number))
+        }
+    }
+
+    fn slot_band(&self, validators: &Validators) -> Option<u16> {
+        validators
+            .validators
+            .iter()
+            .position(|validator| validator.public_key.compressed() == &self.public_key)
+            .map(|band| band as u16)
+    }
+
+    fn is_slot_owner(&self, block_number: u32, view_number: u32) -> bool {
+        self.blockchain
+            .get_slot_owner_at(block_number, view_number, None)
+            .map_or(false, |(validator, _)| {
+                validator.public_key.compressed() == &self.public_key
+            })
+    }
+
+    fn contains_slots(signature: &MultiSignature, slot_range: (u16, u16)) -> bool {
+        (slot_range.0..slot_range.1).any(|slot| signature.signers.contains(slot as usize))
+    }
+}
@@ -22,12 +22,20 @@ extern crate nimiq_messages as messages;
 extern crate nimiq_network_interface as network_interface;
 extern crate nimiq_primitives as primitives;
 extern crate nimiq_tendermint as tendermint;
+extern crate nimiq_transaction as transaction;
 extern crate nimiq_utils as utils;
 extern crate nimiq_vrf as vrf;
 
 pub mod aggregation;
+pub mod duty;
 mod r#macro;
 mod micro;
 mod slash;
 mod tendermint_outside_deps;
+pub mod unpark;
 pub mod validator;
+#[cfg(feature = "metrics")]
+pub mod validator_metrics;
+
+// The slashing protection is shared with the signer, which checks it again before signing.
+pub use nimiq_signer::slashing_protection;
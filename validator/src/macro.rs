@@ -12,11 +12,14 @@ use nimiq_block_albatross::{
 use nimiq_block_production_albatross::BlockProducer;
 use nimiq_blockchain_albatross::{AbstractBlockchain, Blockchain};
 use nimiq_database::{FromDatabaseValue, IntoDatabaseValue};
+use nimiq_signer::Signer;
 use nimiq_tendermint::{
     Checkpoint, Step, TendermintOutsideDeps, TendermintReturn, TendermintState,
 };
 use nimiq_validator_network::ValidatorNetwork;
 
+use crate::duty::DutyTracker;
+use crate::slashing_protection::SlashingProtection;
 use crate::tendermint_outside_deps::TendermintInterface;
 
 #[derive(Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -65,7 +68,9 @@ impl ProduceMacroBlock {
         blockchain: Arc<Blockchain>,
         network: Arc<TValidatorNetwork>,
         block_producer: BlockProducer,
-        signing_key: bls::KeyPair, // probably SecretKey is enough (it is for the handel part of it).
+        signer: Arc<dyn Signer>,
+        slashing_protection: Arc<SlashingProtection>,
+        duty_tracker: Arc<DutyTracker>,
         validator_id: u16,
         state: Option<PersistedMacroState<TValidatorNetwork>>,
         proposal_stream: BoxStream<
@@ -82,7 +87,9 @@ impl ProduceMacroBlock {
         // create the TendermintOutsideDeps instance
         // Replace here with the actual OutSide Deps instead of the Mocked ones.
         let deps = TendermintInterface::new(
-            signing_key,
+            signer,
+            slashing_protection,
+            duty_tracker,
             validator_id,
             network,
             active_validators,
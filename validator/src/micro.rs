@@ -10,12 +10,16 @@ use tokio::time;
 use block_albatross::{ForkProof, MicroBlock, ViewChange, ViewChangeProof};
 use block_production_albatross::BlockProducer;
 use blockchain_albatross::{AbstractBlockchain, Blockchain};
+use hash::{Blake2bHash, Hash};
 use mempool::Mempool;
+use nimiq_signer::Signer;
 use nimiq_validator_network::ValidatorNetwork;
 use utils::time::systemtime_to_timestamp;
 use vrf::VrfSeed;
 
 use crate::aggregation::view_change::ViewChangeAggregation;
+use crate::duty::DutyTracker;
+use crate::slashing_protection::{SigningKind, SlashingProtection};
 
 pub(crate) enum ProduceMicroBlockEvent {
     MicroBlock(MicroBlock),
@@ -27,7 +31,9 @@ struct NextProduceMicroBlockEvent<TValidatorNetwork> {
     blockchain: Arc<Blockchain>,
     mempool: Arc<Mempool>,
     network: Arc<TValidatorNetwork>,
-    signing_key: bls::KeyPair,
+    signer: Arc<dyn Signer>,
+    slashing_protection: Arc<SlashingProtection>,
+    duty_tracker: Arc<DutyTracker>,
     validator_id: u16,
     fork_proofs: Vec<ForkProof>,
     view_number: u32,
@@ -43,7 +49,9 @@ impl<TValidatorNetwork: ValidatorNetwork + 'static> NextProduceMicroBlockEvent<T
         blockchain: Arc<Blockchain>,
         mempool: Arc<Mempool>,
         network: Arc<TValidatorNetwork>,
-        signing_key: bls::KeyPair,
+        signer: Arc<dyn Signer>,
+        slashing_protection: Arc<SlashingProtection>,
+        duty_tracker: Arc<DutyTracker>,
         validator_id: u16,
         fork_proofs: Vec<ForkProof>,
         view_number: u32,
@@ -60,7 +68,9 @@ impl<TValidatorNetwork: ValidatorNetwork + 'static> NextProduceMicroBlockEvent<T
             blockchain,
             mempool,
             network,
-            signing_key,
+            signer,
+            slashing_protection,
+            duty_tracker,
             validator_id,
             fork_proofs,
             view_number,
@@ -78,17 +88,25 @@ impl<TValidatorNetwork: ValidatorNetwork + 'static> NextProduceMicroBlockEvent<T
         ProduceMicroBlockEvent,
         NextProduceMicroBlockEvent<TValidatorNetwork>,
     ) {
-        let event = if self.is_our_turn() {
+        let block = if self.is_our_turn() {
             info!(
                 "[{}] Our turn at #{}:{}, producing micro block",
                 self.validator_id, self.block_number, self.view_number
             );
-            ProduceMicroBlockEvent::MicroBlock(self.produce_micro_block())
+            self.produce_micro_block().await
         } else {
             debug!(
                 "[{}] Not our turn at #{}:{}, waiting for micro block",
                 self.validator_id, self.block_number, self.view_number
             );
+            None
+        };
+
+        // If we didn't produce a block, we wait for somebody else to produce one and change the
+        // view if nobody does.
+        let event = if let Some(block) = block {
+            ProduceMicroBlockEvent::MicroBlock(block)
+        } else {
             time::delay_for(self.view_change_delay).await;
             info!(
                 "No micro block received within timeout at #{}:{}, starting view change",
@@ -112,28 +130,85 @@ impl<TValidatorNetwork: ValidatorNetwork + 'static> NextProduceMicroBlockEvent<T
             .get_slot_owner_at(self.block_number, self.view_number, None)
             .expect("Couldn't find slot owner!");
 
-        &self.signing_key.public_key.compress() == slot.public_key.compressed()
+        &self.signer.public_key().compress() == slot.public_key.compressed()
     }
 
-    fn produce_micro_block(&self) -> MicroBlock {
-        let producer = BlockProducer::new(
+    /// Produces our micro block, unless the slashing protection refuses to sign it or the signer
+    /// fails. Signing might wait for a remote signer while the blockchain is locked, so this runs
+    /// on a thread for blocking tasks.
+    async fn produce_micro_block(&self) -> Option<MicroBlock> {
+        let producer = BlockProducer::new_with_signer(
             Arc::clone(&self.blockchain),
             Arc::clone(&self.mempool),
-            self.signing_key.clone(),
+            Arc::clone(&self.signer),
         );
+        let slashing_protection = Arc::clone(&self.slashing_protection);
+        let view_number = self.view_number;
+        let fork_proofs = self.fork_proofs.clone();
+        let view_change_proof = self.view_change_proof.clone();
+
+        let result = tokio::task::spawn_blocking(move || {
+            Self::produce_micro_block_blocking(
+                producer,
+                &slashing_protection,
+                view_number,
+                fork_proofs,
+                view_change_proof,
+            )
+        })
+        .await;
+
+        match result {
+            Ok(block) => block,
+            Err(e) => {
+                error!("Failed to produce micro block: {}", e);
+                None
+            }
+        }
+    }
 
-        let _lock = self.blockchain.lock();
+    fn produce_micro_block_blocking(
+        producer: BlockProducer,
+        slashing_protection: &SlashingProtection,
+        view_number: u32,
+        fork_proofs: Vec<ForkProof>,
+        view_change_proof: Option<ViewChangeProof>,
+    ) -> Option<MicroBlock> {
+        let _lock = producer.blockchain.lock();
         let timestamp = u64::max(
-            self.blockchain.head().header().timestamp(),
+            producer.blockchain.head().header().timestamp(),
             systemtime_to_timestamp(SystemTime::now()),
         );
-        producer.next_micro_block(
+        let (header, body) = match producer.next_unsigned_micro_block(
             timestamp,
-            self.view_number,
-            self.view_change_proof.clone(),
-            self.fork_proofs.clone(),
+            view_number,
+            fork_proofs,
             vec![], // TODO
-        )
+        ) {
+            Ok(unsigned_block) => unsigned_block,
+            Err(e) => {
+                error!("Failed to produce micro block: {}", e);
+                return None;
+            }
+        };
+
+        if let Err(e) = slashing_protection.check_and_record(
+            SigningKind::MicroBlock,
+            header.block_number,
+            header.view_number,
+            &header.hash::<Blake2bHash>(),
+        ) {
+            error!("{}", e);
+            return None;
+        }
+
+        match producer.sign_micro_block(header, body, view_change_proof) {
+            Ok(block) => Some(block),
+            Err(e) => {
+                error!("Failed to sign micro block: {}", e);
+                None
+            }
+        }
     }
 
     async fn change_view(&mut self) -> (ViewChange, ViewChangeProof) {
@@ -158,7 +233,9 @@ impl<TValidatorNetwork: ValidatorNetwork + 'static> NextProduceMicroBlockEvent<T
         let (view_change, view_change_proof) = ViewChangeAggregation::start(
             view_change.clone(),
             view_change_proof,
-            self.signing_key.clone(),
+            Arc::clone(&self.signer),
+            Arc::clone(&self.slashing_protection),
+            Arc::clone(&self.duty_tracker),
             self.validator_id,
             active_validators,
             Arc::clone(&self.network),
@@ -190,7 +267,9 @@ impl<TValidatorNetwork: ValidatorNetwork + 'static> ProduceMicroBlock<TValidator
         blockchain: Arc<Blockchain>,
         mempool: Arc<Mempool>,
         network: Arc<TValidatorNetwork>,
-        signing_key: bls::KeyPair,
+        signer: Arc<dyn Signer>,
+        slashing_protection: Arc<SlashingProtection>,
+        duty_tracker: Arc<DutyTracker>,
         validator_id: u16,
         fork_proofs: Vec<ForkProof>,
         view_number: u32,
@@ -202,7 +281,9 @@ impl<TValidatorNetwork: ValidatorNetwork + 'static> ProduceMicroBlock<TValidator
             blockchain,
             mempool,
             network,
-            signing_key,
+            signer,
+            slashing_protection,
+            duty_tracker,
             validator_id,
             fork_proofs,
             view_number,
@@ -11,10 +11,11 @@ use block_albatross::{
 };
 use block_production_albatross::BlockProducer;
 use blockchain_albatross::{AbstractBlockchain, Blockchain};
-use bls::{KeyPair, PublicKey};
+use bls::PublicKey;
 use database::WriteTransaction;
 use hash::{Blake2bHash, Hash};
 use nimiq_primitives::slots::Validators;
+use nimiq_signer::{sign_blocking, SignRequest, Signer, SignerError};
 use nimiq_validator_network::ValidatorNetwork;
 use primitives::policy::{TENDERMINT_TIMEOUT_DELTA, TENDERMINT_TIMEOUT_INIT};
 use tendermint::{
@@ -24,6 +25,8 @@ use tendermint::{
 use utils::time::OffsetTime;
 
 use crate::aggregation::tendermint::HandelTendermintAdapter;
+use crate::duty::DutyTracker;
+use crate::slashing_protection::{SigningKind, SlashingProtection};
 use crate::validator::ProposalTopic;
 
 /// The struct that interfaces with the Tendermint crate. It only has to implement the
@@ -39,8 +42,10 @@ pub struct TendermintInterface<N: ValidatorNetwork> {
     pub blockchain: Arc<Blockchain>,
     // The aggregation adapter allows Tendermint to use Handel functions and networking.
     pub aggregation_adapter: HandelTendermintAdapter<N>,
-    // This validator's key pair.
-    pub validator_key: KeyPair,
+    // Signs with this validator's key.
+    pub signer: Arc<dyn Signer>,
+    // The slashing protection that is checked before signing a proposal.
+    pub slashing_protection: Arc<SlashingProtection>,
     // Just a field to temporarily store a block body. Since the body of a macro block is completely
     // deterministic, our Tendermint proposal only contains the block header. If the validator needs
     // the body, it is supposed for him to calculate it from the header and his current state.
@@ -78,18 +83,27 @@ impl<N: ValidatorNetwork + 'static> TendermintOutsideDeps for TendermintInterfac
             .expect("Couldn't find slot owner!");
 
         // Get our public key.
-        let our_public_key = self.validator_key.public_key.compress();
+        let our_public_key = self.signer.public_key().compress();
 
         // Compare the two public keys.
         slot.public_key.compressed() == &our_public_key
     }
 
     /// Produces a proposal. Evidently, used when we are the proposer.
-    fn get_value(&mut self, round: u32) -> Result<Self::ProposalTy, TendermintError> {
+    async fn get_value(&mut self, round: u32) -> Result<Self::ProposalTy, TendermintError> {
         // Call the block producer to produce the next macro block (minus the justification, of course).
-        let block =
-            self.block_producer
-                .next_macro_block_proposal(self.offset_time.now(), round, vec![]);
+        // Signing the seed might wait for a remote signer, so this runs on a thread for blocking tasks.
+        let block_producer = self.block_producer.clone();
+        let timestamp = self.offset_time.now();
+        let block = tokio::task::spawn_blocking(move || {
+            block_producer.next_macro_block_proposal(timestamp, round, vec![])
+        })
+        .await
+        .unwrap_or(Err(SignerError::TaskFailed))
+        .map_err(|e| {
+            error!("Failed to produce macro block proposal: {}", e);
+            TendermintError::CannotProduceProposal
+        })?;
 
         // Cache the block body for future use.
         self.cache_body = block.body;
@@ -138,7 +152,7 @@ impl<N: ValidatorNetwork + 'static> TendermintOutsideDeps for TendermintInterfac
     // One at the beginning and another at half of the timeout duration.
     async fn broadcast_proposal(
         &mut self,
-        _round: u32,
+        round: u32,
         proposal: Self::ProposalTy,
         valid_round: Option<u32>,
     ) -> Result<(), TendermintError> {
@@ -154,13 +168,24 @@ impl<N: ValidatorNetwork + 'static> TendermintOutsideDeps for TendermintInterfac
             .iter()
             .enumerate()
         {
-            if validator.public_key.compressed() == &self.validator_key.public_key.compress() {
+            if validator.public_key.compressed() == &self.signer.public_key().compress() {
                 validator_index_opt = Some(i as u16);
                 break;
             }
         }
         let validator_index = validator_index_opt.ok_or(TendermintError::ProposalBroadcastError)?;
 
+        // Make sure that we didn't sign a different proposal for this round before.
+        if let Err(e) = self.slashing_protection.check_and_record(
+            SigningKind::TendermintProposal,
+            proposal.block_number,
+            round,
+            &proposal.hash::<Blake2bHash>(),
+        ) {
+            error!("{}", e);
+            return Err(TendermintError::ProposalBroadcastError);
+        }
+
         // Create the Tendermint proposal message.
         let proposal_message = TendermintProposal {
             value: proposal,
@@ -168,11 +193,23 @@ impl<N: ValidatorNetwork + 'static> TendermintOutsideDeps for TendermintInterfac
         };
 
         // Sign the message with our validator key.
-        let signed_proposal = SignedTendermintProposal::from_message(
-            proposal_message,
-            &self.validator_key.secret_key,
-            validator_index,
-        );
+        let signature = sign_blocking(
+            Arc::clone(&self.signer),
+            SignRequest::TendermintProposal {
+                proposal: proposal_message.clone(),
+                round,
+            },
+        )
+        .await
+        .map_err(|e| {
+            error!("Failed to sign proposal: {}", e);
+            TendermintError::ProposalBroadcastError
+        })?;
+        let signed_proposal = SignedTendermintProposal {
+            message: proposal_message,
+            signer_idx: validator_index,
+            signature,
+        };
 
         // Broadcast the signed proposal to the network.
         if let Err(err) = self.network.publish(&ProposalTopic, signed_proposal).await {
@@ -367,7 +404,9 @@ impl<N: ValidatorNetwork + 'static> TendermintInterface<N> {
     }
 
     pub fn new(
-        validator_key: KeyPair,
+        signer: Arc<dyn Signer>,
+        slashing_protection: Arc<SlashingProtection>,
+        duty_tracker: Arc<DutyTracker>,
         validator_id: u16,
         network: Arc<N>,
         active_validators: Validators,
@@ -385,12 +424,15 @@ impl<N: ValidatorNetwork + 'static> TendermintInterface<N> {
             active_validators,
             block_height,
             network.clone(),
-            validator_key.secret_key,
+            Arc::clone(&signer),
+            Arc::clone(&slashing_protection),
+            duty_tracker,
         );
 
         // Create the instance and return it.
         Self {
-            validator_key,
+            signer,
+            slashing_protection,
             network,
             aggregation_adapter,
             cache_body: None,
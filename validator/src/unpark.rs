@@ -0,0 +1,208 @@
+use hash::Blake2bHash;
+use keys::Address;
+use nimiq_signer::{SignRequest, Signer, SignerError};
+use nimiq_transaction_builder::{
+    fill_in_staking_contract_address, Recipient, TransactionBuilder, TransactionProofBuilder,
+};
+use primitives::account::ValidatorId;
+use primitives::coin::Coin;
+use primitives::networks::NetworkId;
+use primitives::policy;
+use transaction::{SignatureProof, Transaction};
+
+/// The policy by which a parked validator sends unpark transactions on its own.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AutoUnparkConfig {
+    /// The fee that is paid for each unpark transaction.
+    pub fee: Coin,
+    /// The number of blocks to wait for an unpark transaction to be included before sending
+    /// another one.
+    pub retry_interval: u32,
+    /// The maximum number of unpark transactions that are sent per epoch. Once the limit is
+    /// reached, the validator has to be unparked by hand.
+    pub max_transactions_per_epoch: u32,
+}
+
+impl AutoUnparkConfig {
+    pub const DEFAULT_RETRY_INTERVAL: u32 = 10;
+    pub const DEFAULT_MAX_TRANSACTIONS_PER_EPOCH: u32 = 3;
+}
+
+impl Default for AutoUnparkConfig {
+    fn default() -> Self {
+        Self {
+            fee: Coin::ZERO,
+            retry_interval: Self::DEFAULT_RETRY_INTERVAL,
+            max_transactions_per_epoch: Self::DEFAULT_MAX_TRANSACTIONS_PER_EPOCH,
+        }
+    }
+}
+
+/// What to do about a parked validator at a given block.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UnparkDecision {
+    /// Send an unpark transaction now.
+    Send,
+    /// Don't send an unpark transaction now, either because one was sent recently and might still
+    /// be included or because the limit of unpark transactions was reached for this epoch.
+    Wait,
+    /// The maximum number of unpark transactions for this epoch was just reached. This is only
+    /// returned once per epoch, afterwards the decision is to wait for the next epoch.
+    LimitReached,
+}
+
+/// Keeps track of the unpark transactions that were sent and decides, according to the
+/// `AutoUnparkConfig`, when to send the next one.
+#[derive(Clone, Debug)]
+pub struct AutoUnpark {
+    config: AutoUnparkConfig,
+    // The epoch in which the last unpark transaction was sent.
+    epoch: u32,
+    // The number of unpark transactions that were sent in `epoch`.
+    num_sent: u32,
+    // The block number at which the last unpark transaction was sent, if we are still waiting for
+    // it to be included.
+    last_sent_at: Option<u32>,
+    // The hash of the last unpark transaction once it was accepted by the mempool, as long as it
+    // was neither included in a block nor dropped.
+    pending_transaction: Option<Blake2bHash>,
+    // The epoch in which the limit of unpark transactions was reached.
+    limit_reached_epoch: Option<u32>,
+}
+
+impl AutoUnpark {
+    pub fn new(config: AutoUnparkConfig) -> Self {
+        Self {
+            config,
+            epoch: 0,
+            num_sent: 0,
+            last_sent_at: None,
+            pending_transaction: None,
+            limit_reached_epoch: None,
+        }
+    }
+
+    pub fn config(&self) -> &AutoUnparkConfig {
+        &self.config
+    }
+
+    /// The hash of the unpark transaction that was accepted by the mempool and is waiting to be
+    /// included in a block.
+    pub fn pending_transaction(&self) -> Option<&Blake2bHash> {
+        self.pending_transaction.as_ref()
+    }
+
+    /// Decides whether an unpark transaction should be sent, given that the validator is parked
+    /// at `block_number`.
+    pub fn on_parked(&mut self, block_number: u32) -> UnparkDecision {
+        let epoch = policy::epoch_at(block_number);
+        let num_sent = if epoch == self.epoch {
+            self.num_sent
+        } else {
+            0
+        };
+
+        if let Some(last_sent_at) = self.last_sent_at {
+            if block_number < last_sent_at.saturating_add(self.config.retry_interval) {
+                return UnparkDecision::Wait;
+            }
+        }
+
+        if num_sent >= self.config.max_transactions_per_epoch {
+            if self.limit_reached_epoch == Some(epoch) {
+                return UnparkDecision::Wait;
+            }
+            self.limit_reached_epoch = Some(epoch);
+            return UnparkDecision::LimitReached;
+        }
+
+        UnparkDecision::Send
+    }
+
+    /// Records that an unpark transaction was sent at `block_number`.
+    pub fn on_sent(&mut self, block_number: u32) {
+        let epoch = policy::epoch_at(block_number);
+        if epoch != self.epoch {
+            self.epoch = epoch;
+            self.num_sent = 0;
+        }
+        self.num_sent += 1;
+        self.last_sent_at = Some(block_number);
+    }
+
+    /// Records that the last unpark transaction was accepted by the mempool. Accepted transactions
+    /// of which a newer one was sent already, or which are not awaited anymore, are ignored.
+    pub fn on_accepted(&mut self, block_number: u32, hash: Blake2bHash) {
+        if self.last_sent_at == Some(block_number) {
+            self.pending_transaction = Some(hash);
+        }
+    }
+
+    /// Records that the pending unpark transaction was included in a block.
+    pub fn on_included(&mut self) {
+        self.pending_transaction = None;
+    }
+
+    /// Records that the unpark transaction sent at `block_number` could not be signed or was
+    /// rejected by the mempool. Like a dropped transaction, it is retried right away.
+    pub fn on_rejected(&mut self, block_number: u32) {
+        if self.last_sent_at == Some(block_number) {
+            self.on_dropped();
+        }
+    }
+
+    /// Records that the pending unpark transaction was dropped from the mempool without being
+    /// included in a block. The next unpark transaction is then sent right away, as long as the
+    /// limit for this epoch isn't reached.
+    pub fn on_dropped(&mut self) {
+        self.last_sent_at = None;
+        self.pending_transaction = None;
+    }
+
+    /// Records that the validator is not parked (anymore), so that the next parking is handled
+    /// right away.
+    pub fn on_unparked(&mut self) {
+        self.last_sent_at = None;
+        self.pending_transaction = None;
+    }
+}
+
+/// Builds an unpark transaction like `TransactionBuilder::new_unpark_validator`, but signs it
+/// with the `signer` instead of key pairs.
+pub fn unpark_transaction(
+    signer: &dyn Signer,
+    validator_id: &ValidatorId,
+    fee: Coin,
+    validity_start_height: u32,
+    network_id: NetworkId,
+) -> Result<Transaction, SignerError> {
+    let wallet_key = signer.wallet_public_key().ok_or(SignerError::NoWalletKey)?;
+
+    let mut recipient =
+        Recipient::new_staking_builder(Some(fill_in_staking_contract_address(None, network_id)));
+    recipient.unpark_validator(validator_id);
+
+    let mut builder = TransactionBuilder::new();
+    builder
+        .with_sender(Address::from(wallet_key))
+        .with_recipient(recipient.generate().unwrap())
+        .with_value(Coin::default())
+        .with_fee(fee)
+        .with_validity_start_height(validity_start_height)
+        .with_network_id(network_id);
+
+    match builder.generate().unwrap() {
+        TransactionProofBuilder::Signalling(mut builder) => {
+            let validator_signature =
+                signer.sign(&SignRequest::Transaction(builder.transaction.clone()))?;
+            builder.with_validator_signature(validator_signature.compress());
+
+            // The wallet signs the transaction including the validator signature.
+            let mut builder = builder.generate().unwrap().unwrap_basic();
+            let signature = signer.sign_transaction(&builder.transaction)?;
+            builder.with_signature_proof(SignatureProof::from(*wallet_key, signature));
+            Ok(builder.generate().unwrap())
+        }
+        _ => unreachable!(),
+    }
+}
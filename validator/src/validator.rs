@@ -7,7 +7,7 @@ use futures::{
     Future, Stream, StreamExt,
 };
 use linked_hash_map::LinkedHashMap;
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 use tokio::sync::{broadcast, mpsc};
 
 use block_albatross::{Block, BlockType, SignedTendermintProposal, ViewChange, ViewChangeProof};
@@ -17,18 +17,26 @@ use consensus_albatross::{
     sync::block_queue::BlockTopic, Consensus, ConsensusEvent, ConsensusProxy,
 };
 use database::{Database, Environment, ReadTransaction, WriteTransaction};
-use hash::Blake2bHash;
+use hash::{Blake2bHash, Hash};
+use mempool::ReturnCode;
 use network_interface::{
     network::{Network, PubsubId, Topic},
     peer::Peer,
 };
 use nimiq_block_production_albatross::BlockProducer;
+use nimiq_signer::{Signer, SignerError};
 use nimiq_tendermint::TendermintReturn;
 use nimiq_validator_network::ValidatorNetwork;
+use primitives::account::ValidatorId;
 
+use crate::duty::DutyTracker;
 use crate::micro::{ProduceMicroBlock, ProduceMicroBlockEvent};
 use crate::r#macro::{PersistedMacroState, ProduceMacroBlock};
 use crate::slash::ForkProofPool;
+use crate::slashing_protection::SlashingProtection;
+use crate::unpark::{unpark_transaction, AutoUnpark, AutoUnparkConfig, UnparkDecision};
+#[cfg(feature = "metrics")]
+use crate::validator_metrics::ValidatorMetrics;
 
 pub struct ProposalTopic;
 
@@ -44,6 +52,7 @@ impl Topic for ProposalTopic {
     }
 }
 
+#[derive(Clone, Debug, PartialEq, Eq)]
 enum ValidatorStakingState {
     Active,
     Parked,
@@ -69,8 +78,9 @@ pub struct Validator<TNetwork: Network, TValidatorNetwork: ValidatorNetwork + 's
     pub consensus: ConsensusProxy<TNetwork>,
     network: Arc<TValidatorNetwork>,
     // TODO: Also have the validator ID here.
-    signing_key: bls::KeyPair,
-    wallet_key: Option<keys::KeyPair>,
+    signer: Arc<dyn Signer>,
+    slashing_protection: Arc<SlashingProtection>,
+    duty_tracker: Arc<DutyTracker>,
     database: Database,
     env: Environment,
 
@@ -88,6 +98,18 @@ pub struct Validator<TNetwork: Network, TValidatorNetwork: ValidatorNetwork + 's
 
     micro_producer: Option<ProduceMicroBlock<TValidatorNetwork>>,
     micro_state: ProduceMicroBlockState,
+
+    // The id of the validator in the staking contract and its state there. Only kept up to date
+    // if automatic unparking is enabled.
+    staking_id: Option<ValidatorId>,
+    // Whether to search the staking contract for the id of the validator, see
+    // `update_staking_state`.
+    search_staking_id: bool,
+    staking_state: ValidatorStakingState,
+    auto_unpark: Option<Arc<Mutex<AutoUnpark>>>,
+
+    #[cfg(feature = "metrics")]
+    metrics: Arc<ValidatorMetrics>,
 }
 
 impl<TNetwork: Network, TValidatorNetwork: ValidatorNetwork>
@@ -101,8 +123,8 @@ impl<TNetwork: Network, TValidatorNetwork: ValidatorNetwork>
     pub fn new(
         consensus: &Consensus<TNetwork>,
         network: Arc<TValidatorNetwork>,
-        signing_key: bls::KeyPair,
-        wallet_key: Option<keys::KeyPair>,
+        signer: Arc<dyn Signer>,
+        auto_unpark: Option<AutoUnparkConfig>,
     ) -> Self {
         let consensus_event_rx = consensus.subscribe_events();
         let blockchain_event_rx = consensus.blockchain.notifier.write().as_stream();
@@ -120,20 +142,35 @@ impl<TNetwork: Network, TValidatorNetwork: ValidatorNetwork>
 
         let env = consensus.env.clone();
         let database = env.open_database(Self::MACRO_STATE_DB_NAME.to_string());
+        let slashing_protection = Arc::new(SlashingProtection::new(env.clone()));
+        let duty_tracker = Arc::new(DutyTracker::new(
+            Arc::clone(&consensus.blockchain),
+            signer.public_key().compress(),
+        ));
 
         let macro_state: Option<PersistedMacroState<TValidatorNetwork>> = {
             let read_transaction = ReadTransaction::new(&env);
             read_transaction.get(&database, Self::MACRO_STATE_KEY)
         };
 
+        let auto_unpark = match (auto_unpark, signer.wallet_public_key()) {
+            (Some(config), Some(_)) => Some(Arc::new(Mutex::new(AutoUnpark::new(config)))),
+            (Some(_), None) => {
+                warn!("Automatic unparking is disabled, because no wallet key is configured");
+                None
+            }
+            (None, _) => None,
+        };
+
         let network1 = Arc::clone(&network);
         let (proposal_sender, proposal_receiver) = ProposalBuffer::new();
 
         let mut this = Self {
             consensus: consensus.proxy(),
             network,
-            signing_key,
-            wallet_key,
+            signer,
+            slashing_protection,
+            duty_tracker,
             database,
             env,
 
@@ -151,6 +188,14 @@ impl<TNetwork: Network, TValidatorNetwork: ValidatorNetwork>
 
             micro_producer: None,
             micro_state,
+
+            staking_id: None,
+            search_staking_id: true,
+            staking_state: ValidatorStakingState::NoStake,
+            auto_unpark,
+
+            #[cfg(feature = "metrics")]
+            metrics: Arc::new(ValidatorMetrics::default()),
         };
         this.init();
 
@@ -169,11 +214,14 @@ impl<TNetwork: Network, TValidatorNetwork: ValidatorNetwork>
     fn init(&mut self) {
         self.init_epoch();
         self.init_block_producer();
+        self.update_staking_state();
     }
 
     fn init_epoch(&mut self) {
         log::debug!("Initializing epoch");
 
+        self.search_staking_id = true;
+
         let validators = self.consensus.blockchain.current_validators().unwrap();
 
         // TODO: This code block gets this validators position in the validators struct by searching it
@@ -181,7 +229,7 @@ impl<TNetwork: Network, TValidatorNetwork: ValidatorNetwork>
         //  id in the Validator struct (the one in this crate).
         self.epoch_state = None;
         for (i, validator) in validators.iter().enumerate() {
-            if validator.public_key.compressed() == &self.signing_key.public_key.compress() {
+            if validator.public_key.compressed() == &self.signer.public_key().compress() {
                 self.epoch_state = Some(ActiveEpochState {
                     validator_id: i as u16,
                 });
@@ -193,7 +241,7 @@ impl<TNetwork: Network, TValidatorNetwork: ValidatorNetwork>
             .iter()
             .map(|validator| validator.public_key.compressed().clone())
             .collect();
-        let key = self.signing_key.clone();
+        let signer = Arc::clone(&self.signer);
         let network = Arc::clone(&self.network);
 
         // TODO might better be done without the task.
@@ -201,7 +249,7 @@ impl<TNetwork: Network, TValidatorNetwork: ValidatorNetwork>
         // Also the setting up of our own public key record should probably not be done here but in `init` instead.
         tokio::spawn(async move {
             if let Err(err) = network
-                .set_public_key(&key.public_key.compress(), &key.secret_key)
+                .set_public_key(&signer.public_key().compress(), Arc::clone(&signer))
                 .await
             {
                 error!("could not set up DHT record: {:?}", err);
@@ -224,10 +272,10 @@ impl<TNetwork: Network, TValidatorNetwork: ValidatorNetwork>
         let _lock = self.consensus.blockchain.lock();
         match self.consensus.blockchain.get_next_block_type(None) {
             BlockType::Macro => {
-                let block_producer = BlockProducer::new(
+                let block_producer = BlockProducer::new_with_signer(
                     self.consensus.blockchain.clone(),
                     self.consensus.mempool.clone(),
-                    self.signing_key.clone(),
+                    Arc::clone(&self.signer),
                 );
 
                 // Take the current state and see if it is applicable to the current height.
@@ -252,7 +300,9 @@ impl<TNetwork: Network, TValidatorNetwork: ValidatorNetwork>
                     self.consensus.blockchain.clone(),
                     self.network.clone(),
                     block_producer,
-                    self.signing_key.clone(),
+                    Arc::clone(&self.signer),
+                    Arc::clone(&self.slashing_protection),
+                    Arc::clone(&self.duty_tracker),
                     self.validator_id(),
                     state,
                     proposal_stream,
@@ -273,7 +323,9 @@ impl<TNetwork: Network, TValidatorNetwork: ValidatorNetwork>
                     Arc::clone(&self.consensus.blockchain),
                     Arc::clone(&self.consensus.mempool),
                     Arc::clone(&self.network),
-                    self.signing_key.clone(),
+                    Arc::clone(&self.signer),
+                    Arc::clone(&self.slashing_protection),
+                    Arc::clone(&self.duty_tracker),
                     self.validator_id(),
                     fork_proofs,
                     self.micro_state.view_number,
@@ -299,6 +351,203 @@ impl<TNetwork: Network, TValidatorNetwork: ValidatorNetwork>
         }
 
         self.init_block_producer();
+        self.update_staking_state();
+    }
+
+    /// Looks up this validator in the staking contract and, if it got parked, sends an unpark
+    /// transaction according to the automatic unparking policy.
+    fn update_staking_state(&mut self) {
+        if self.auto_unpark.is_none() || !self.consensus.is_established() {
+            return;
+        }
+
+        self.check_unpark_transaction();
+
+        // A validator can only get slots, and thereby get parked, with an election block. So while
+        // its id is unknown, it is enough to search for it once per epoch.
+        if self.staking_id.is_none() && self.search_staking_id {
+            self.staking_id = self.find_staking_id();
+            self.search_staking_id = false;
+        }
+
+        let staking_state = match &self.staking_id {
+            Some(validator_id) => {
+                let staking_contract = self
+                    .consensus
+                    .blockchain
+                    .get_staking_contract_with_validator(validator_id);
+
+                let is_active = staking_contract
+                    .active_validators_by_id
+                    .contains_key(validator_id);
+                let is_inactive = staking_contract
+                    .inactive_validators_by_id
+                    .contains_key(validator_id);
+                let is_parked = staking_contract
+                    .current_epoch_parking
+                    .contains(validator_id)
+                    || staking_contract
+                        .previous_epoch_parking
+                        .contains(validator_id);
+
+                match (is_active, is_inactive) {
+                    (true, _) | (false, true) if is_parked => ValidatorStakingState::Parked,
+                    (true, _) => ValidatorStakingState::Active,
+                    (false, true) => ValidatorStakingState::Inactive,
+                    (false, false) => ValidatorStakingState::NoStake,
+                }
+            }
+            None => ValidatorStakingState::NoStake,
+        };
+
+        if staking_state != self.staking_state {
+            if staking_state == ValidatorStakingState::Parked {
+                warn!("Validator {:?} got parked", self.staking_id);
+                #[cfg(feature = "metrics")]
+                self.metrics.note_parked();
+            } else if self.staking_state == ValidatorStakingState::Parked {
+                info!("Validator {:?} is not parked anymore", self.staking_id);
+            }
+            self.staking_state = staking_state;
+        }
+
+        // The validator was dropped, so it has to be searched again.
+        if self.staking_state == ValidatorStakingState::NoStake {
+            self.staking_id = None;
+        }
+
+        if self.staking_state == ValidatorStakingState::Parked {
+            self.unpark();
+        } else if let Some(auto_unpark) = self.auto_unpark.as_ref() {
+            auto_unpark.lock().on_unparked();
+        }
+    }
+
+    /// Checks whether the pending unpark transaction was included in a block, or dropped from the
+    /// mempool without being included.
+    fn check_unpark_transaction(&self) {
+        let mut auto_unpark = self.auto_unpark.as_ref().unwrap().lock();
+        let hash = match auto_unpark.pending_transaction() {
+            Some(hash) => hash.clone(),
+            None => return,
+        };
+
+        if self
+            .consensus
+            .blockchain
+            .contains_tx_in_validity_window(&hash)
+        {
+            info!("Unpark transaction {} was included in a block", hash);
+            auto_unpark.on_included();
+            #[cfg(feature = "metrics")]
+            self.metrics.note_unpark_included();
+        } else if !self.consensus.mempool.contains(&hash) {
+            warn!(
+                "Unpark transaction {} was dropped without being included in a block",
+                hash
+            );
+            auto_unpark.on_dropped();
+            #[cfg(feature = "metrics")]
+            self.metrics.note_unpark_dropped();
+        }
+    }
+
+    /// Searches the staking contract for the id of this validator by its key.
+    fn find_staking_id(&self) -> Option<ValidatorId> {
+        let staking_contract = self.consensus.blockchain.get_staking_contract();
+        let validator_key = self.signer.public_key().compress();
+
+        staking_contract
+            .active_validators_by_id
+            .iter()
+            .find(|(_, validator)| validator.validator_key == validator_key)
+            .map(|(validator_id, _)| validator_id.clone())
+            .or_else(|| {
+                staking_contract
+                    .inactive_validators_by_id
+                    .iter()
+                    .find(|(_, inactive)| inactive.validator.validator_key == validator_key)
+                    .map(|(validator_id, _)| validator_id.clone())
+            })
+    }
+
+    /// Sends an unpark transaction for this validator, unless the automatic unparking policy
+    /// says otherwise.
+    fn unpark(&mut self) {
+        let block_number = self.consensus.blockchain.block_number();
+        let auto_unpark_lock = Arc::clone(self.auto_unpark.as_ref().unwrap());
+        let mut auto_unpark = auto_unpark_lock.lock();
+        let validator_id = self.staking_id.clone().unwrap();
+
+        match auto_unpark.on_parked(block_number) {
+            UnparkDecision::Send => {}
+            UnparkDecision::Wait => return,
+            UnparkDecision::LimitReached => {
+                warn!(
+                    "Not unparking validator {:?} anymore in this epoch, because the limit of {} unpark transactions was reached",
+                    validator_id,
+                    auto_unpark.config().max_transactions_per_epoch
+                );
+                #[cfg(feature = "metrics")]
+                self.metrics.note_unpark_limit_reached();
+                return;
+            }
+        }
+
+        auto_unpark.on_sent(block_number);
+
+        let signer = Arc::clone(&self.signer);
+        let fee = auto_unpark.config().fee;
+        drop(auto_unpark);
+        let network_id = self.consensus.blockchain.network_id;
+        // todo get rid of spawn
+        let consensus = self.consensus.clone();
+        #[cfg(feature = "metrics")]
+        let metrics = Arc::clone(&self.metrics);
+        tokio::spawn(async move {
+            // Signing might wait for a remote signer, so it runs on a thread for blocking tasks.
+            let unpark_id = validator_id.clone();
+            let transaction = match tokio::task::spawn_blocking(move || {
+                unpark_transaction(&*signer, &unpark_id, fee, block_number, network_id)
+            })
+            .await
+            .unwrap_or(Err(SignerError::TaskFailed))
+            {
+                Ok(transaction) => transaction,
+                Err(e) => {
+                    error!("Failed to sign unpark transaction: {}", e);
+                    auto_unpark_lock.lock().on_rejected(block_number);
+                    return;
+                }
+            };
+
+            let hash = transaction.hash::<Blake2bHash>();
+            info!(
+                "Sending unpark transaction {} for validator {:?}",
+                hash, validator_id
+            );
+            #[cfg(feature = "metrics")]
+            metrics.note_unpark_sent();
+
+            match consensus.send_transaction(transaction).await {
+                // Whether the transaction gets included is checked with every block.
+                Ok(ReturnCode::Accepted) => {
+                    auto_unpark_lock.lock().on_accepted(block_number, hash);
+                }
+                Ok(return_code) => {
+                    warn!("Unpark transaction was rejected: {:?}", return_code);
+                    auto_unpark_lock.lock().on_rejected(block_number);
+                    #[cfg(feature = "metrics")]
+                    metrics.note_unpark_rejected();
+                }
+                Err(e) => {
+                    error!("Failed to send unpark transaction: {:?}", e);
+                    auto_unpark_lock.lock().on_rejected(block_number);
+                    #[cfg(feature = "metrics")]
+                    metrics.note_unpark_rejected();
+                }
+            }
+        });
     }
 
     fn on_blockchain_extended(&mut self, hash: &Blake2bHash) {
@@ -308,6 +557,7 @@ impl<TNetwork: Network, TValidatorNetwork: ValidatorNetwork>
             .get_block(hash, true, None)
             .expect("Head block not found");
         self.blockchain_state.fork_proofs.apply_block(&block);
+        self.duty_tracker.on_block_added(&block);
     }
 
     fn on_blockchain_rebranched(
@@ -317,9 +567,11 @@ impl<TNetwork: Network, TValidatorNetwork: ValidatorNetwork>
     ) {
         for (_hash, block) in old_chain.iter() {
             self.blockchain_state.fork_proofs.revert_block(block);
+            self.duty_tracker.on_block_reverted(block);
         }
         for (_hash, block) in new_chain.iter() {
             self.blockchain_state.fork_proofs.apply_block(&block);
+            self.duty_tracker.on_block_added(block);
         }
     }
 
@@ -446,8 +698,21 @@ impl<TNetwork: Network, TValidatorNetwork: ValidatorNetwork>
             .validator_id
     }
 
-    pub fn signing_key(&self) -> bls::KeyPair {
-        self.signing_key.clone()
+    pub fn signer(&self) -> Arc<dyn Signer> {
+        Arc::clone(&self.signer)
+    }
+
+    pub fn slashing_protection(&self) -> Arc<SlashingProtection> {
+        Arc::clone(&self.slashing_protection)
+    }
+
+    pub fn duty_tracker(&self) -> Arc<DutyTracker> {
+        Arc::clone(&self.duty_tracker)
+    }
+
+    #[cfg(feature = "metrics")]
+    pub fn metrics(&self) -> Arc<ValidatorMetrics> {
+        Arc::clone(&self.metrics)
     }
 }
 
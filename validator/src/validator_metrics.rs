@@ -0,0 +1,74 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+#[derive(Default)]
+pub struct ValidatorMetrics {
+    parked_count: AtomicUsize,
+    unpark_sent_count: AtomicUsize,
+    unpark_included_count: AtomicUsize,
+    unpark_dropped_count: AtomicUsize,
+    unpark_rejected_count: AtomicUsize,
+    unpark_limit_reached_count: AtomicUsize,
+}
+
+impl ValidatorMetrics {
+    #[inline]
+    pub fn note_parked(&self) {
+        self.parked_count.fetch_add(1, Ordering::Release);
+    }
+
+    #[inline]
+    pub fn parked_count(&self) -> usize {
+        self.parked_count.load(Ordering::Acquire)
+    }
+
+    #[inline]
+    pub fn note_unpark_sent(&self) {
+        self.unpark_sent_count.fetch_add(1, Ordering::Release);
+    }
+
+    #[inline]
+    pub fn unpark_sent_count(&self) -> usize {
+        self.unpark_sent_count.load(Ordering::Acquire)
+    }
+
+    #[inline]
+    pub fn note_unpark_included(&self) {
+        self.unpark_included_count.fetch_add(1, Ordering::Release);
+    }
+
+    #[inline]
+    pub fn unpark_included_count(&self) -> usize {
+        self.unpark_included_count.load(Ordering::Acquire)
+    }
+
+    #[inline]
+    pub fn note_unpark_dropped(&self) {
+        self.unpark_dropped_count.fetch_add(1, Ordering::Release);
+    }
+
+    #[inline]
+    pub fn unpark_dropped_count(&self) -> usize {
+        self.unpark_dropped_count.load(Ordering::Acquire)
+    }
+
+    #[inline]
+    pub fn note_unpark_rejected(&self) {
+        self.unpark_rejected_count.fetch_add(1, Ordering::Release);
+    }
+
+    #[inline]
+    pub fn unpark_rejected_count(&self) -> usize {
+        self.unpark_rejected_count.load(Ordering::Acquire)
+    }
+
+    #[inline]
+    pub fn note_unpark_limit_reached(&self) {
+        self.unpark_limit_reached_count
+            .fetch_add(1, Ordering::Release);
+    }
+
+    #[inline]
+    pub fn unpark_limit_reached_count(&self) -> usize {
+        self.unpark_limit_reached_count.load(Ordering::Acquire)
+    }
+}
@@ -20,6 +20,7 @@ use nimiq_network_libp2p::libp2p::core::multiaddr::multiaddr;
 use nimiq_network_libp2p::{Config, Keypair, Network};
 use nimiq_primitives::coin::Coin;
 use nimiq_primitives::networks::NetworkId;
+use nimiq_signer::LocalSigner;
 use nimiq_utils::time::OffsetTime;
 use nimiq_validator::validator::Validator as AbstractValidator;
 use nimiq_validator_network::network_impl::ValidatorNetworkImpl;
@@ -32,7 +33,8 @@ fn seeded_rng(seed: u64) -> StdRng {
 }
 
 async fn consensus(peer_id: u64, genesis_info: GenesisInfo) -> Consensus {
-    let env = VolatileEnvironment::new(12).unwrap();
+    // The validator opens its macro state and slashing protection databases as well.
+    let env = VolatileEnvironment::new(Blockchain::NUM_DATABASES + 2).unwrap();
     let clock = Arc::new(OffsetTime::new());
     let blockchain = Arc::new(
         Blockchain::with_genesis(
@@ -72,7 +74,12 @@ async fn validator(
     let consensus = consensus(peer_id, genesis_info).await;
     let validator_network = Arc::new(ValidatorNetworkImpl::new(consensus.network.clone()));
     (
-        Validator::new(&consensus, validator_network, signing_key, None),
+        Validator::new(
+            &consensus,
+            validator_network,
+            Arc::new(LocalSigner::new(signing_key, None)),
+            None,
+        ),
         consensus,
     )
 }
@@ -21,6 +21,8 @@ use nimiq_network_mock::{MockHub, MockNetwork};
 use nimiq_primitives::account::ValidatorId;
 use nimiq_primitives::coin::Coin;
 use nimiq_primitives::networks::NetworkId;
+use nimiq_primitives::policy;
+use nimiq_signer::{LocalSigner, SignRequest, Signer};
 use nimiq_utils::time::OffsetTime;
 use nimiq_validator::aggregation::view_change::SignedViewChangeMessage;
 use nimiq_validator::validator::Validator as AbstractValidator;
@@ -37,7 +39,8 @@ fn seeded_rng(seed: u64) -> StdRng {
 }
 
 async fn mock_consensus(hub: &mut MockHub, peer_id: u64, genesis_info: GenesisInfo) -> Consensus {
-    let env = VolatileEnvironment::new(12).unwrap();
+    // The validator opens its macro state and slashing protection databases as well.
+    let env = VolatileEnvironment::new(Blockchain::NUM_DATABASES + 2).unwrap();
     let time = Arc::new(OffsetTime::new());
     let blockchain = Arc::new(
         Blockchain::with_genesis(
@@ -65,7 +68,12 @@ async fn mock_validator(
     let consensus = mock_consensus(hub, peer_id, genesis_info).await;
     let validator_network = Arc::new(ValidatorNetworkImpl::new(consensus.network.clone()));
     (
-        Validator::new(&consensus, validator_network, signing_key, None),
+        Validator::new(
+            &consensus,
+            validator_network,
+            Arc::new(LocalSigner::new(signing_key, None)),
+            None,
+        ),
         consensus,
     )
 }
@@ -139,7 +147,7 @@ fn validator_for_slot(
     validators
         .iter()
         .find(|validator| {
-            &validator.signing_key().public_key.compress() == slot.public_key.compressed()
+            &validator.signer().public_key().compress() == slot.public_key.compressed()
         })
         .unwrap()
 }
@@ -174,6 +182,39 @@ async fn one_validator_can_create_micro_blocks() {
     assert!(consensus1.blockchain.block_number() >= 10);
 }
 
+#[tokio::test]
+async fn validator_tracks_its_duties() {
+    let mut hub = MockHub::default();
+
+    let key = KeyPair::generate(&mut seeded_rng(0));
+    let genesis = GenesisBuilder::default()
+        .with_genesis_validator(
+            ValidatorId::default(),
+            key.public_key,
+            Address::default(),
+            Coin::from_u64_unchecked(10000),
+        )
+        .generate()
+        .unwrap();
+
+    let (validator, mut consensus1) = mock_validator(&mut hub, 1, key, genesis.clone()).await;
+    let duty_tracker = validator.duty_tracker();
+
+    consensus1.force_established();
+    tokio::spawn(validator);
+
+    let events1 = consensus1.blockchain.notifier.write().as_stream();
+    events1.take(10).for_each(|_| future::ready(())).await;
+
+    // The only validator owns all slots, so it produces every block.
+    let report = duty_tracker.report();
+    assert_eq!(report.slot_band, Some(0));
+    assert_eq!(report.num_slots, policy::SLOTS);
+    assert!(!report.upcoming_slots.is_empty());
+    assert!(report.counts.produced_blocks >= 5);
+    assert_eq!(report.counts.missed_blocks, 0);
+}
+
 #[tokio::test]
 async fn four_validators_can_create_micro_blocks() {
     let mut hub = MockHub::default();
@@ -226,7 +267,7 @@ fn create_view_change_update(
     block_number: u32,
     new_view_number: u32,
     prev_seed: VrfSeed,
-    key_pair: KeyPair,
+    signer: &dyn Signer,
     validator_id: u16,
     slots: &Vec<u16>,
 ) -> LevelUpdateMessage<SignedViewChangeMessage, ViewChange> {
@@ -238,8 +279,13 @@ fn create_view_change_update(
     };
 
     // get a single signature for this view_change
-    let signed_view_change =
-        SignedViewChange::from_message(view_change.clone(), &key_pair.secret_key, validator_id);
+    let signed_view_change = SignedViewChange {
+        message: view_change.clone(),
+        signer_idx: validator_id,
+        signature: signer
+            .sign(&SignRequest::ViewChange(view_change.clone()))
+            .unwrap(),
+    };
 
     // multiply with number of slots to get a signature representing all the slots of this public_key
     let signature = AggregateSignature::from_signatures(&[signed_view_change
@@ -325,7 +371,7 @@ async fn validator_can_catch_up() {
         1,
         1,
         blockchain.head().seed().clone(),
-        validator.signing_key(),
+        &*validator.signer(),
         validator.validator_id(),
         &slots,
     );
@@ -0,0 +1,101 @@
+use nimiq_hash::{Blake2bHash, Hash};
+use nimiq_primitives::coin::Coin;
+use nimiq_primitives::policy;
+use nimiq_validator::unpark::{AutoUnpark, AutoUnparkConfig, UnparkDecision};
+
+fn auto_unpark(retry_interval: u32, max_transactions_per_epoch: u32) -> AutoUnpark {
+    AutoUnpark::new(AutoUnparkConfig {
+        fee: Coin::ZERO,
+        retry_interval,
+        max_transactions_per_epoch,
+    })
+}
+
+#[test]
+fn it_retries_unparking_within_the_limit() {
+    let mut auto_unpark = auto_unpark(5, 2);
+
+    // Parked for the first time: send right away.
+    assert_eq!(auto_unpark.on_parked(1), UnparkDecision::Send);
+    auto_unpark.on_sent(1);
+
+    // Wait for the transaction to be included before retrying.
+    assert_eq!(auto_unpark.on_parked(2), UnparkDecision::Wait);
+    assert_eq!(auto_unpark.on_parked(5), UnparkDecision::Wait);
+    assert_eq!(auto_unpark.on_parked(6), UnparkDecision::Send);
+    auto_unpark.on_sent(6);
+
+    // The limit is reported once, afterwards we wait for the next epoch.
+    assert_eq!(auto_unpark.on_parked(11), UnparkDecision::LimitReached);
+    assert_eq!(auto_unpark.on_parked(12), UnparkDecision::Wait);
+
+    // The limit is per epoch.
+    let next_epoch = policy::EPOCH_LENGTH + 1;
+    assert_eq!(auto_unpark.on_parked(next_epoch), UnparkDecision::Send);
+    auto_unpark.on_sent(next_epoch);
+
+    // Once the validator was unparked, a new parking is handled right away.
+    auto_unpark.on_unparked();
+    assert_eq!(auto_unpark.on_parked(next_epoch + 1), UnparkDecision::Send);
+}
+
+#[test]
+fn it_keeps_track_of_the_pending_unpark_transaction() {
+    let mut auto_unpark = auto_unpark(5, 2);
+    let hash: Blake2bHash = "unpark".hash();
+
+    assert_eq!(auto_unpark.on_parked(1), UnparkDecision::Send);
+    auto_unpark.on_sent(1);
+    assert_eq!(auto_unpark.pending_transaction(), None);
+
+    // The transaction is pending once the mempool accepted it.
+    auto_unpark.on_accepted(1, hash.clone());
+    assert_eq!(auto_unpark.pending_transaction(), Some(&hash));
+
+    // Once it is included, we still wait for the validator to be unparked.
+    auto_unpark.on_included();
+    assert_eq!(auto_unpark.pending_transaction(), None);
+    assert_eq!(auto_unpark.on_parked(2), UnparkDecision::Wait);
+
+    // Acceptances of transactions that are not awaited anymore are ignored.
+    auto_unpark.on_unparked();
+    auto_unpark.on_accepted(1, hash);
+    assert_eq!(auto_unpark.pending_transaction(), None);
+}
+
+#[test]
+fn it_retries_dropped_unpark_transactions_right_away() {
+    let mut auto_unpark = auto_unpark(5, 2);
+    let hash: Blake2bHash = "unpark".hash();
+
+    assert_eq!(auto_unpark.on_parked(1), UnparkDecision::Send);
+    auto_unpark.on_sent(1);
+    auto_unpark.on_accepted(1, hash);
+    assert_eq!(auto_unpark.on_parked(2), UnparkDecision::Wait);
+
+    // The transaction left the mempool without being included.
+    auto_unpark.on_dropped();
+    assert_eq!(auto_unpark.pending_transaction(), None);
+    assert_eq!(auto_unpark.on_parked(2), UnparkDecision::Send);
+    auto_unpark.on_sent(2);
+
+    // Dropped transactions still count towards the limit.
+    auto_unpark.on_dropped();
+    assert_eq!(auto_unpark.on_parked(3), UnparkDecision::LimitReached);
+    assert_eq!(auto_unpark.on_parked(4), UnparkDecision::Wait);
+}
+
+#[test]
+fn it_retries_rejected_unpark_transactions_right_away() {
+    let mut auto_unpark = auto_unpark(5, 3);
+
+    assert_eq!(auto_unpark.on_parked(1), UnparkDecision::Send);
+    auto_unpark.on_sent(1);
+    auto_unpark.on_rejected(1);
+    assert_eq!(auto_unpark.on_parked(2), UnparkDecision::Send);
+    auto_unpark.on_sent(2);
+
+    // A late rejection of an older transaction doesn't cut the wait for the newer one short.
+    auto_unpark.on_rejected(1);
+    assert_eq!(auto_unpark.on_parked(3), UnparkDecision::Wait);
+}
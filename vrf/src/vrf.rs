@@ -1,3 +1,4 @@
+use std::convert::Infallible;
 use std::fmt;
 use std::hash::{Hash, Hasher as StdHasher};
 use std::io::Write;
@@ -5,7 +6,7 @@ use std::io::Write;
 use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
 
 use beserial::{Deserialize, Serialize};
-use bls::{CompressedSignature, PublicKey, SecretKey};
+use bls::{CompressedSignature, PublicKey, SecretKey, Signature};
 use hash::{Blake2sHash, Blake2sHasher, HashOutput, Hasher};
 
 use crate::rng::Rng;
@@ -55,14 +56,28 @@ impl VrfSeed {
     }
 
     pub fn sign_next(&self, secret_key: &SecretKey) -> Self {
+        self.sign_next_with(|hash| Ok::<_, Infallible>(secret_key.sign_hash(hash)))
+            .unwrap()
+    }
+
+    /// Like `sign_next`, but the hash is signed by `sign_hash`, which might fail, e.g. because the
+    /// key is held by a remote signer.
+    pub fn sign_next_with<E, F>(&self, sign_hash: F) -> Result<Self, E>
+    where
+        F: FnOnce(Blake2sHash) -> Result<Signature, E>,
+    {
+        // Sign the hash and contruct new VrfSeed from it
+        let signature = sign_hash(self.next_hash())?.compress();
+        Ok(Self { signature })
+    }
+
+    /// The hash that is signed to get the next seed.
+    pub fn next_hash(&self) -> Blake2sHash {
         // Hash use-case prefix and signature
         let mut hasher = Blake2sHasher::new();
         hasher.write_u8(VrfUseCase::Seed as u8).unwrap();
         hasher.write_all(self.signature.as_ref()).unwrap();
-
-        // Sign that hash and contruct new VrfSeed from it
-        let signature = secret_key.sign_hash(hasher.finish()).compress();
-        Self { signature }
+        hasher.finish()
     }
 
     pub fn rng(&self, use_case: VrfUseCase, round: u32) -> VrfRng {